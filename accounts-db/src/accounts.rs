@@ -29,6 +29,7 @@ use {
     std::{
         cmp::Reverse,
         collections::{BinaryHeap, HashMap, HashSet},
+        ops::Bound,
         sync::{
             atomic::{AtomicUsize, Ordering},
             Arc, Mutex,
@@ -38,6 +39,10 @@ use {
 
 pub type PubkeyAccountSlot = (Pubkey, AccountSharedData, Slot);
 
+/// The most accounts a page of `Accounts::load_by_program_with_filter_after` visits, so that
+/// pages of programs with few matching accounts do not scan the entire accounts index
+pub const MAX_KEYS_VISITED_PER_PAGE: usize = 100_000;
+
 struct TransactionAccountLocksIterator<'a, T: SVMMessage> {
     transaction: &'a T,
 }
@@ -364,6 +369,98 @@ impl Accounts {
             .map(|_| collector)
     }
 
+    /// Loads at most `limit` accounts owned by `program_id` that pass `filter`, in pubkey order,
+    /// starting after `start_after`. The accounts are found with the program id secondary index
+    /// when it is enabled, and a page visits at most `MAX_KEYS_VISITED_PER_PAGE` of them.
+    ///
+    /// Also returns the pubkey to start the next page after, unless this page reached the last
+    /// account. Fails once the loaded accounts exceed `byte_limit_for_scan`, or once the root
+    /// advanced past `ancestors`, as the scan would then read newer roots instead.
+    #[allow(clippy::too_many_arguments)]
+    pub fn load_by_program_with_filter_after<F: Fn(&AccountSharedData) -> bool>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        program_id: &Pubkey,
+        start_after: Option<&Pubkey>,
+        limit: usize,
+        filter: F,
+        byte_limit_for_scan: Option<usize>,
+    ) -> ScanResult<(Vec<TransactionAccount>, Option<Pubkey>)> {
+        self.check_root_in_ancestors(ancestors)?;
+        let sum = AtomicUsize::default();
+        let config = ScanConfig::new(ScanOrder::Sorted).recreate_with_abort();
+        let range = (
+            start_after.map_or(Bound::Unbounded, |pubkey| Bound::Excluded(*pubkey)),
+            Bound::Unbounded,
+        );
+        let mut collector = Vec::with_capacity(limit);
+        let mut num_keys_visited = 0;
+        let mut last_key_visited = None;
+        let mut is_truncated = false;
+        let result = self
+            .accounts_db
+            .range_index_scan_accounts(
+                ancestors,
+                bank_id,
+                IndexKey::ProgramId(*program_id),
+                range,
+                |some_account_tuple| {
+                    if collector.len() >= limit || num_keys_visited >= MAX_KEYS_VISITED_PER_PAGE {
+                        // the page ends before this key
+                        is_truncated = true;
+                        config.abort();
+                        return;
+                    }
+                    if let Some((pubkey, _, _)) = &some_account_tuple {
+                        num_keys_visited += 1;
+                        last_key_visited = Some(**pubkey);
+                    }
+                    Self::load_while_filtering(&mut collector, some_account_tuple, |account| {
+                        let use_account = account.owner() == program_id && filter(account);
+                        if use_account
+                            && Self::accumulate_and_check_scan_result_size(
+                                &sum,
+                                account,
+                                &byte_limit_for_scan,
+                            )
+                        {
+                            // total size of results exceeds size limit, so abort scan
+                            config.abort();
+                        }
+                        use_account
+                    })
+                },
+                &config,
+            )
+            .map(|_| collector);
+        // The scan is also aborted once the page ends, which is not an error
+        let result = if byte_limit_for_scan
+            .is_some_and(|byte_limit_for_scan| sum.load(Ordering::Relaxed) > byte_limit_for_scan)
+        {
+            Self::maybe_abort_scan(result, &config)
+        } else {
+            result
+        }?;
+        // The scan read newer roots if the root advanced past `ancestors` meanwhile
+        self.check_root_in_ancestors(ancestors)?;
+        Ok((result, last_key_visited.filter(|_| is_truncated)))
+    }
+
+    /// Fails once the root advanced past `ancestors`. Scans then read the state of the newest
+    /// root instead, as the slots of `ancestors` may have been cleaned.
+    fn check_root_in_ancestors(&self, ancestors: &Ancestors) -> ScanResult<()> {
+        let max_root = self.accounts_db.accounts_index.max_root_inclusive();
+        if ancestors.contains_key(&max_root) {
+            Ok(())
+        } else {
+            Err(ScanError::RootAdvanced {
+                slot: ancestors.max_slot(),
+                max_root,
+            })
+        }
+    }
+
     fn calc_scan_result_size(account: &AccountSharedData) -> usize {
         account.data().len()
             + std::mem::size_of::<AccountSharedData>()
@@ -570,6 +667,7 @@ impl Accounts {
 mod tests {
     use {
         super::*,
+        crate::accounts_index::{AccountIndex, AccountSecondaryIndexes},
         agave_reserved_account_keys::ReservedAccountKeys,
         solana_account::{AccountSharedData, WritableAccount},
         solana_address_lookup_table_interface::state::LookupTableMeta,
@@ -773,6 +871,156 @@ mod tests {
         assert_eq!(loaded, vec![]);
     }
 
+    #[test_case(false; "unindexed")]
+    #[test_case(true; "program_id_index")]
+    fn test_load_by_program_with_filter_after(use_program_id_index: bool) {
+        let account_indexes = AccountSecondaryIndexes {
+            keys: None,
+            indexes: use_program_id_index
+                .then_some(AccountIndex::ProgramId)
+                .into_iter()
+                .collect(),
+        };
+        let mut accounts_db = AccountsDb::new_single_for_tests();
+        accounts_db.account_indexes = account_indexes;
+        let accounts = Accounts::new(Arc::new(accounts_db));
+        let ancestors = Ancestors::from(vec![0]);
+        let program_id = Pubkey::new_unique();
+
+        // owned by another program, and ordered before the accounts of `program_id`
+        accounts.store_for_tests(
+            0,
+            &Pubkey::new_unique(),
+            &AccountSharedData::new(1, 0, &Pubkey::new_unique()),
+        );
+        let pubkeys = (0..5)
+            .map(|lamports| {
+                let pubkey = Pubkey::new_unique();
+                let account = AccountSharedData::new(lamports + 1, 0, &program_id);
+                accounts.store_for_tests(0, &pubkey, &account);
+                pubkey
+            })
+            .collect::<Vec<_>>();
+        accounts.add_root_and_flush_write_cache(0);
+
+        let load_page = |start_after: Option<&Pubkey>, limit| {
+            let (page, next_start_after) = accounts
+                .load_by_program_with_filter_after(
+                    &ancestors,
+                    0,
+                    &program_id,
+                    start_after,
+                    limit,
+                    |_| true,
+                    None,
+                )
+                .unwrap();
+            let page = page
+                .into_iter()
+                .map(|(pubkey, _)| pubkey)
+                .collect::<Vec<_>>();
+            (page, next_start_after)
+        };
+
+        // Walk all accounts in pages of two
+        let mut loaded = vec![];
+        let mut start_after = None;
+        loop {
+            let (page, next_start_after) = load_page(start_after.as_ref(), 2);
+            assert!(page.len() <= 2);
+            loaded.extend(page);
+            start_after = next_start_after;
+            if start_after.is_none() {
+                break;
+            }
+        }
+        assert_eq!(loaded, pubkeys);
+
+        // A page which reaches the last account is the last one, even if it is full
+        assert_eq!(load_page(None, pubkeys.len()), (pubkeys.clone(), None));
+        assert_eq!(
+            load_page(None, pubkeys.len() - 1),
+            (
+                pubkeys[..pubkeys.len() - 1].to_vec(),
+                pubkeys.iter().nth_back(1).copied()
+            )
+        );
+
+        // Filters apply before the limit
+        let (loaded, _) = accounts
+            .load_by_program_with_filter_after(
+                &ancestors,
+                0,
+                &program_id,
+                Some(&pubkeys[0]),
+                10,
+                |account| account.lamports() % 2 == 1,
+                None,
+            )
+            .unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert!(loaded
+            .iter()
+            .all(|(_, account)| account.lamports() % 2 == 1));
+
+        // Pages must fit in the byte limit of scans
+        let account_size =
+            Accounts::calc_scan_result_size(&AccountSharedData::new(1, 0, &program_id));
+        for (limit, byte_limit_for_scan, is_ok) in [
+            (2, 2 * account_size, true),
+            (3, 2 * account_size, false),
+            (5, 2 * account_size, false),
+        ] {
+            let result = accounts.load_by_program_with_filter_after(
+                &ancestors,
+                0,
+                &program_id,
+                None,
+                limit,
+                |_| true,
+                Some(byte_limit_for_scan),
+            );
+            assert_eq!(result.is_ok(), is_ok, "limit {limit}");
+        }
+    }
+
+    #[test]
+    fn test_load_by_program_with_filter_after_root_advanced() {
+        let accounts_db = AccountsDb::new_single_for_tests();
+        let accounts = Accounts::new(Arc::new(accounts_db));
+        let program_id = Pubkey::new_unique();
+        let pubkey = Pubkey::new_unique();
+        accounts.store_for_tests(0, &pubkey, &AccountSharedData::new(1, 0, &program_id));
+        accounts.add_root_and_flush_write_cache(0);
+
+        let load_page = |ancestors: &Ancestors| {
+            accounts.load_by_program_with_filter_after(
+                ancestors,
+                0,
+                &program_id,
+                None,
+                1,
+                |_| true,
+                None,
+            )
+        };
+        let (page, _) = load_page(&Ancestors::from(vec![0])).unwrap();
+        assert_eq!(page.len(), 1);
+
+        // Once the root advances past the scanned slot, its accounts may have been cleaned
+        accounts.store_for_tests(1, &pubkey, &AccountSharedData::new(2, 0, &program_id));
+        accounts.add_root_and_flush_write_cache(1);
+        assert_eq!(
+            load_page(&Ancestors::from(vec![0])),
+            Err(ScanError::RootAdvanced {
+                slot: 0,
+                max_root: 1
+            })
+        );
+        let (page, _) = load_page(&Ancestors::from(vec![0, 1])).unwrap();
+        assert_eq!(page[0].1.lamports(), 2);
+    }
+
     #[test_case(false; "old")]
     #[test_case(true; "simd83")]
     fn test_lock_accounts_with_duplicates(relax_intrabatch_account_locks: bool) {
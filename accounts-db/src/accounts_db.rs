@@ -3934,6 +3934,42 @@ impl AccountsDb {
         Ok(())
     }
 
+    /// Like `scan_accounts()`, but only visits the pubkeys in `range`
+    pub fn range_scan_accounts<F, R>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        range: R,
+        mut scan_func: F,
+        config: &ScanConfig,
+    ) -> ScanResult<()>
+    where
+        F: FnMut(Option<(&Pubkey, AccountSharedData, Slot)>),
+        R: RangeBounds<Pubkey> + std::fmt::Debug,
+    {
+        // This can error out if the slots being scanned over are aborted
+        self.accounts_index.range_scan_accounts(
+            ancestors,
+            bank_id,
+            range,
+            |pubkey, (account_info, slot)| {
+                let mut account_accessor =
+                    self.get_account_accessor(slot, pubkey, &account_info.storage_location());
+
+                let account_slot = match account_accessor {
+                    LoadedAccountAccessor::Cached(None) => None,
+                    _ => account_accessor.get_loaded_account(|loaded_account| {
+                        (pubkey, loaded_account.take_account(), slot)
+                    }),
+                };
+                scan_func(account_slot)
+            },
+            config,
+        )?;
+
+        Ok(())
+    }
+
     #[cfg(feature = "dev-context-only-utils")]
     pub fn unchecked_scan_accounts<F>(
         &self,
@@ -3968,12 +4004,7 @@ impl AccountsDb {
     where
         F: FnMut(Option<(&Pubkey, AccountSharedData, Slot)>),
     {
-        let key = match &index_key {
-            IndexKey::ProgramId(key) => key,
-            IndexKey::SplTokenMint(key) => key,
-            IndexKey::SplTokenOwner(key) => key,
-        };
-        if !self.account_indexes.include_key(key) {
+        if !self.account_indexes.include_key(index_key.key()) {
             // the requested key was not indexed in the secondary index, so do a normal scan
             let used_index = false;
             self.scan_accounts(ancestors, bank_id, scan_func, config)?;
@@ -3998,6 +4029,49 @@ impl AccountsDb {
         Ok(used_index)
     }
 
+    /// Like `index_scan_accounts`, but only scans the pubkeys of `index_key` in `range`. Falls
+    /// back to a range scan of the whole index when `index_key` is not indexed.
+    pub fn range_index_scan_accounts<F, R>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        index_key: IndexKey,
+        range: R,
+        mut scan_func: F,
+        config: &ScanConfig,
+    ) -> ScanResult<bool>
+    where
+        F: FnMut(Option<(&Pubkey, AccountSharedData, Slot)>),
+        R: RangeBounds<Pubkey> + std::fmt::Debug,
+    {
+        if !self.account_indexes.contains(&index_key.index())
+            || !self.account_indexes.include_key(index_key.key())
+        {
+            // the requested key was not indexed in the secondary index, so do a range scan
+            let used_index = false;
+            self.range_scan_accounts(ancestors, bank_id, range, scan_func, config)?;
+            return Ok(used_index);
+        }
+
+        self.accounts_index.range_index_scan_accounts(
+            ancestors,
+            bank_id,
+            index_key,
+            range,
+            |pubkey, (account_info, slot)| {
+                let account_slot = self
+                    .get_account_accessor(slot, pubkey, &account_info.storage_location())
+                    .get_loaded_account(|loaded_account| {
+                        (pubkey, loaded_account.take_account(), slot)
+                    });
+                scan_func(account_slot)
+            },
+            config,
+        )?;
+        let used_index = true;
+        Ok(used_index)
+    }
+
     /// Scan a specific slot through all the account storage
     pub(crate) fn scan_account_storage<R, B>(
         &self,
@@ -15,6 +15,7 @@ use {
         rolling_bit_field::RollingBitField,
     },
     account_map_entry::{AccountMapEntry, PreAllocatedAccountMapEntry},
+    dashmap::DashMap,
     in_mem_accounts_index::{
         ExistedLocation, InMemAccountsIndex, InsertNewEntryResults, StartupStats,
     },
@@ -45,6 +46,7 @@ pub use {
     iter::ITER_BATCH_SIZE,
     secondary::{
         AccountIndex, AccountSecondaryIndexes, AccountSecondaryIndexesIncludeExclude, IndexKey,
+        ProgramDataIndex,
     },
 };
 
@@ -207,11 +209,16 @@ pub enum ScanError {
     SlotRemoved { slot: Slot, bank_id: BankId },
     #[error("scan aborted: {0}")]
     Aborted(String),
+    #[error(
+        "The root advanced to slot {max_root} past scanned slot {slot}, which can no longer be \
+         scanned consistently"
+    )]
+    RootAdvanced { slot: Slot, max_root: Slot },
 }
 
 enum ScanTypes<R: RangeBounds<Pubkey>> {
     Unindexed(Option<R>),
-    Indexed(IndexKey),
+    Indexed(IndexKey, Option<R>),
 }
 
 /// specification of how much memory in-mem portion of account index can use
@@ -284,6 +291,8 @@ pub struct AccountsIndex<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> {
     program_id_index: SecondaryIndex<RwLockSecondaryIndexEntry>,
     spl_token_mint_index: SecondaryIndex<RwLockSecondaryIndexEntry>,
     spl_token_owner_index: SecondaryIndex<RwLockSecondaryIndexEntry>,
+    /// created on first insert, one per configured `AccountIndex::ProgramData`
+    program_data_indexes: DashMap<ProgramDataIndex, Arc<SecondaryIndex<RwLockSecondaryIndexEntry>>>,
     pub roots_tracker: RwLock<RootsTracker>,
     ongoing_scan_roots: RwLock<BTreeMap<Slot, u64>>,
     // Each scan has some latest slot `S` that is the tip of the fork the scan
@@ -336,6 +345,7 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
             spl_token_owner_index: SecondaryIndex::<RwLockSecondaryIndexEntry>::new(
                 "spl_token_owner_index_stats",
             ),
+            program_data_indexes: DashMap::default(),
             roots_tracker: RwLock::<RootsTracker>::default(),
             ongoing_scan_roots: RwLock::<BTreeMap<Slot, u64>>::default(),
             removed_bank_ids: Mutex::<HashSet<BankId>>::default(),
@@ -608,36 +618,53 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
                 // Pass "" not to log metrics, so RPC doesn't get spammy
                 self.do_scan_accounts(metric_name, ancestors, func, range, Some(max_root), config);
             }
-            ScanTypes::Indexed(IndexKey::ProgramId(program_id)) => {
+            ScanTypes::Indexed(IndexKey::ProgramId(program_id), range) => {
                 self.do_scan_secondary_index(
                     ancestors,
                     func,
                     &self.program_id_index,
                     &program_id,
+                    range,
                     Some(max_root),
                     config,
                 );
             }
-            ScanTypes::Indexed(IndexKey::SplTokenMint(mint_key)) => {
+            ScanTypes::Indexed(IndexKey::SplTokenMint(mint_key), range) => {
                 self.do_scan_secondary_index(
                     ancestors,
                     func,
                     &self.spl_token_mint_index,
                     &mint_key,
+                    range,
                     Some(max_root),
                     config,
                 );
             }
-            ScanTypes::Indexed(IndexKey::SplTokenOwner(owner_key)) => {
+            ScanTypes::Indexed(IndexKey::SplTokenOwner(owner_key), range) => {
                 self.do_scan_secondary_index(
                     ancestors,
                     func,
                     &self.spl_token_owner_index,
                     &owner_key,
+                    range,
                     Some(max_root),
                     config,
                 );
             }
+            ScanTypes::Indexed(IndexKey::ProgramData(program_data_index, data_key), range) => {
+                // nothing has been indexed yet if the index does not exist
+                if let Some(index) = self.get_program_data_index(&program_data_index) {
+                    self.do_scan_secondary_index(
+                        ancestors,
+                        func,
+                        &index,
+                        &data_key,
+                        range,
+                        Some(max_root),
+                        config,
+                    );
+                }
+            }
         }
 
         {
@@ -760,6 +787,7 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
 
     fn do_scan_secondary_index<
         F,
+        R,
         SecondaryIndexEntryType: SecondaryIndexEntry + Default + Sync + Send,
     >(
         &self,
@@ -767,12 +795,21 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
         mut func: F,
         index: &SecondaryIndex<SecondaryIndexEntryType>,
         index_key: &Pubkey,
+        range: Option<R>,
         max_root: Option<Slot>,
         config: &ScanConfig,
     ) where
         F: FnMut(&Pubkey, (&T, Slot)),
+        R: RangeBounds<Pubkey>,
     {
-        for pubkey in index.get(index_key) {
+        let mut pubkeys = index.get(index_key);
+        if let Some(range) = range {
+            pubkeys.retain(|pubkey| range.contains(pubkey));
+        }
+        if config.scan_order == ScanOrder::Sorted {
+            pubkeys.sort_unstable();
+        }
+        for pubkey in pubkeys {
             if config.is_aborted() {
                 break;
             }
@@ -910,6 +947,30 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
         )
     }
 
+    /// call func with every pubkey in `range` and index visible from a given set of ancestors
+    pub(crate) fn range_scan_accounts<F, R>(
+        &self,
+        ancestors: &Ancestors,
+        scan_bank_id: BankId,
+        range: R,
+        func: F,
+        config: &ScanConfig,
+    ) -> Result<(), ScanError>
+    where
+        F: FnMut(&Pubkey, (&T, Slot)),
+        R: RangeBounds<Pubkey> + std::fmt::Debug,
+    {
+        // Pass "" not to log metrics, so RPC doesn't get spammy
+        self.do_checked_scan_accounts(
+            "",
+            ancestors,
+            scan_bank_id,
+            func,
+            ScanTypes::Unindexed(Some(range)),
+            config,
+        )
+    }
+
     #[cfg(feature = "dev-context-only-utils")]
     pub(crate) fn unchecked_scan_accounts<F>(
         &self,
@@ -946,7 +1007,33 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
             ancestors,
             scan_bank_id,
             func,
-            ScanTypes::<Range<Pubkey>>::Indexed(index_key),
+            ScanTypes::<Range<Pubkey>>::Indexed(index_key, None),
+            config,
+        )
+    }
+
+    /// call func with every pubkey in `range` of `index_key`'s secondary index and index visible
+    /// from a given set of ancestors
+    pub(crate) fn range_index_scan_accounts<F, R>(
+        &self,
+        ancestors: &Ancestors,
+        scan_bank_id: BankId,
+        index_key: IndexKey,
+        range: R,
+        func: F,
+        config: &ScanConfig,
+    ) -> Result<(), ScanError>
+    where
+        F: FnMut(&Pubkey, (&T, Slot)),
+        R: RangeBounds<Pubkey> + std::fmt::Debug,
+    {
+        // Pass "" not to log metrics, so RPC doesn't get spammy
+        self.do_checked_scan_accounts(
+            "",
+            ancestors,
+            scan_bank_id,
+            func,
+            ScanTypes::Indexed(index_key, Some(range)),
             config,
         )
     }
@@ -1254,6 +1341,49 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
                 .index
                 .get(index_key)
                 .map(|x| x.len()),
+            AccountIndex::ProgramData(program_data_index) => self
+                .get_program_data_index(program_data_index)
+                .and_then(|index| index.index.get(index_key).map(|x| x.len())),
+        }
+    }
+
+    fn get_program_data_index(
+        &self,
+        program_data_index: &ProgramDataIndex,
+    ) -> Option<Arc<SecondaryIndex<RwLockSecondaryIndexEntry>>> {
+        self.program_data_indexes
+            .get(program_data_index)
+            .map(|index| Arc::clone(index.value()))
+    }
+
+    fn update_program_data_secondary_indexes(
+        &self,
+        pubkey: &Pubkey,
+        account_owner: &Pubkey,
+        account_data: &[u8],
+        account_indexes: &AccountSecondaryIndexes,
+    ) {
+        for program_data_index in account_indexes.program_data_indexes() {
+            if program_data_index.program_id != *account_owner {
+                continue;
+            }
+            if let Some(data_key) = program_data_index.account_index_key(account_data) {
+                if account_indexes.include_key(&data_key) {
+                    let index = self
+                        .get_program_data_index(program_data_index)
+                        .unwrap_or_else(|| {
+                            Arc::clone(
+                                self.program_data_indexes
+                                    .entry(*program_data_index)
+                                    .or_insert_with(|| {
+                                        Arc::new(SecondaryIndex::new("program_data_index_stats"))
+                                    })
+                                    .value(),
+                            )
+                        });
+                    index.insert(&data_key, pubkey);
+                }
+            }
         }
     }
 
@@ -1271,6 +1401,15 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
             info!("secondary index: {:?}", AccountIndex::SplTokenOwner);
             self.spl_token_owner_index.log_contents();
         }
+        for entry in self.program_data_indexes.iter() {
+            if !entry.value().index.is_empty() {
+                info!(
+                    "secondary index: {:?}",
+                    AccountIndex::ProgramData(*entry.key())
+                );
+                entry.value().log_contents();
+            }
+        }
     }
 
     pub(crate) fn update_secondary_indexes(
@@ -1319,6 +1458,12 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
             account_data,
             account_indexes,
         );
+        self.update_program_data_secondary_indexes(
+            pubkey,
+            account_owner,
+            account_data,
+            account_indexes,
+        );
     }
 
     pub(crate) fn get_bin(&self, pubkey: &Pubkey) -> &InMemAccountsIndex<T, U> {
@@ -1526,6 +1671,12 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
         if account_indexes.contains(&AccountIndex::SplTokenMint) {
             self.spl_token_mint_index.remove_by_inner_key(inner_key);
         }
+
+        for program_data_index in account_indexes.program_data_indexes() {
+            if let Some(index) = self.get_program_data_index(program_data_index) {
+                index.remove_by_inner_key(inner_key);
+            }
+        }
     }
 
     fn purge_older_root_entries(
@@ -3366,6 +3517,124 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_program_data_index_from_str() {
+        let program_id = Pubkey::new_unique();
+        assert_eq!(
+            format!("{program_id}:8:32").parse::<ProgramDataIndex>(),
+            Ok(ProgramDataIndex {
+                program_id,
+                offset: 8,
+                length: 32,
+            })
+        );
+        assert!(format!("{program_id}:8:0")
+            .parse::<ProgramDataIndex>()
+            .is_err());
+        assert!(format!("{program_id}:8:33")
+            .parse::<ProgramDataIndex>()
+            .is_err());
+        assert!(format!("{program_id}:8")
+            .parse::<ProgramDataIndex>()
+            .is_err());
+        assert!(format!("{program_id}:8:8:8")
+            .parse::<ProgramDataIndex>()
+            .is_err());
+        assert!("not-a-pubkey:8:8".parse::<ProgramDataIndex>().is_err());
+    }
+
+    #[test]
+    fn test_program_data_index_memcmp_index_key() {
+        let program_data_index = ProgramDataIndex::new(Pubkey::new_unique(), 8, 4).unwrap();
+        let mut expected = [0u8; PUBKEY_BYTES];
+        expected[..4].copy_from_slice(&[1, 2, 3, 4]);
+        let expected = Pubkey::new_from_array(expected);
+
+        // exact match
+        assert_eq!(
+            program_data_index.memcmp_index_key(8, &[1, 2, 3, 4]),
+            Some(expected)
+        );
+        // comparison covers the indexed range
+        assert_eq!(
+            program_data_index.memcmp_index_key(6, &[0, 0, 1, 2, 3, 4, 5]),
+            Some(expected)
+        );
+        // comparison is too short or starts after the indexed range
+        assert_eq!(program_data_index.memcmp_index_key(8, &[1, 2, 3]), None);
+        assert_eq!(program_data_index.memcmp_index_key(9, &[2, 3, 4, 5]), None);
+    }
+
+    #[test]
+    fn test_program_data_secondary_index() {
+        let program_id = Pubkey::new_unique();
+        let program_data_index = ProgramDataIndex::new(program_id, 4, 8).unwrap();
+        let mut secondary_indexes = AccountSecondaryIndexes::default();
+        secondary_indexes
+            .indexes
+            .insert(AccountIndex::ProgramData(program_data_index));
+        let index = AccountsIndex::<bool, bool>::default_for_tests();
+
+        let account_key = Pubkey::new_unique();
+        let mut account_data = vec![0u8; 16];
+        account_data[4..12].copy_from_slice(&[7u8; 8]);
+        let index_key = program_data_index.account_index_key(&account_data).unwrap();
+
+        // Wrong program id
+        index.update_secondary_indexes(
+            &account_key,
+            &AccountSharedData::create(0, account_data.clone(), Pubkey::default(), false, 0),
+            &secondary_indexes,
+        );
+        assert!(index.get_program_data_index(&program_data_index).is_none());
+
+        // Account data too short
+        index.update_secondary_indexes(
+            &account_key,
+            &AccountSharedData::create(0, account_data[..8].to_vec(), program_id, false, 0),
+            &secondary_indexes,
+        );
+        assert!(index.get_program_data_index(&program_data_index).is_none());
+
+        // Excluded key
+        secondary_indexes.keys = Some(AccountSecondaryIndexesIncludeExclude {
+            keys: [index_key].iter().cloned().collect::<HashSet<_>>(),
+            exclude: true,
+        });
+        index.update_secondary_indexes(
+            &account_key,
+            &AccountSharedData::create(0, account_data.clone(), program_id, false, 0),
+            &secondary_indexes,
+        );
+        assert!(index.get_program_data_index(&program_data_index).is_none());
+        secondary_indexes.keys = None;
+
+        // Just right. Inserting the same index multiple times should be ok
+        for _ in 0..2 {
+            index.update_secondary_indexes(
+                &account_key,
+                &AccountSharedData::create(0, account_data.clone(), program_id, false, 0),
+                &secondary_indexes,
+            );
+            let secondary_index = index.get_program_data_index(&program_data_index).unwrap();
+            check_secondary_index_mapping_correct(&secondary_index, &[index_key], &account_key);
+        }
+        assert_eq!(
+            index.get_index_key_size(&AccountIndex::ProgramData(program_data_index), &index_key),
+            Some(1)
+        );
+
+        // Everything should be deleted
+        let secondary_index = index.get_program_data_index(&program_data_index).unwrap();
+        index.purge_secondary_indexes_by_inner_key(&account_key, &secondary_indexes);
+        assert!(secondary_index.index.is_empty());
+        assert!(secondary_index.reverse_index.is_empty());
+        assert_eq!(
+            index.get_index_key_size(&AccountIndex::ProgramData(program_data_index), &index_key),
+            None
+        );
+    }
+
     fn run_test_secondary_indexes_same_slot_and_forks<
         SecondaryIndexEntryType: SecondaryIndexEntry + Default + Sync + Send,
     >(
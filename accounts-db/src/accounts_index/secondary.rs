@@ -1,11 +1,12 @@
 use {
     dashmap::{mapref::entry::Entry::Occupied, DashMap},
     log::*,
-    solana_pubkey::Pubkey,
+    solana_pubkey::{Pubkey, PUBKEY_BYTES},
     solana_time_utils::AtomicInterval,
     std::{
         collections::HashSet,
         fmt::Debug,
+        str::FromStr,
         sync::{
             atomic::{AtomicU64, Ordering},
             RwLock,
@@ -26,6 +27,12 @@ impl AccountSecondaryIndexes {
     pub fn contains(&self, index: &AccountIndex) -> bool {
         self.indexes.contains(index)
     }
+    pub fn program_data_indexes(&self) -> impl Iterator<Item = &ProgramDataIndex> {
+        self.indexes.iter().filter_map(|index| match index {
+            AccountIndex::ProgramData(index) => Some(index),
+            _ => None,
+        })
+    }
     pub fn include_key(&self, key: &Pubkey) -> bool {
         match &self.keys {
             Some(options) => options.exclude ^ options.keys.contains(key),
@@ -45,6 +52,7 @@ pub enum AccountIndex {
     ProgramId,
     SplTokenMint,
     SplTokenOwner,
+    ProgramData(ProgramDataIndex),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -52,6 +60,101 @@ pub enum IndexKey {
     ProgramId(Pubkey),
     SplTokenMint(Pubkey),
     SplTokenOwner(Pubkey),
+    ProgramData(ProgramDataIndex, Pubkey),
+}
+
+impl IndexKey {
+    /// The outer key looked up in the secondary index
+    pub fn key(&self) -> &Pubkey {
+        match self {
+            IndexKey::ProgramId(key)
+            | IndexKey::SplTokenMint(key)
+            | IndexKey::SplTokenOwner(key)
+            | IndexKey::ProgramData(_, key) => key,
+        }
+    }
+
+    /// The secondary index this key is looked up in
+    pub fn index(&self) -> AccountIndex {
+        match self {
+            IndexKey::ProgramId(_) => AccountIndex::ProgramId,
+            IndexKey::SplTokenMint(_) => AccountIndex::SplTokenMint,
+            IndexKey::SplTokenOwner(_) => AccountIndex::SplTokenOwner,
+            IndexKey::ProgramData(program_data_index, _) => {
+                AccountIndex::ProgramData(*program_data_index)
+            }
+        }
+    }
+}
+
+/// Indexes the accounts owned by `program_id` by the `length` bytes of account data found at
+/// `offset`. Index keys shorter than a pubkey are zero-padded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProgramDataIndex {
+    pub program_id: Pubkey,
+    pub offset: usize,
+    pub length: usize,
+}
+
+impl ProgramDataIndex {
+    /// Returns `None` if `length` is zero or larger than a pubkey
+    pub fn new(program_id: Pubkey, offset: usize, length: usize) -> Option<Self> {
+        (1..=PUBKEY_BYTES).contains(&length).then_some(Self {
+            program_id,
+            offset,
+            length,
+        })
+    }
+
+    /// Converts exactly `length` bytes into an index key
+    pub fn index_key(&self, bytes: &[u8]) -> Option<Pubkey> {
+        if bytes.len() != self.length {
+            return None;
+        }
+        let mut key = [0u8; PUBKEY_BYTES];
+        key[..self.length].copy_from_slice(bytes);
+        Some(Pubkey::new_from_array(key))
+    }
+
+    /// Extracts the index key from the data of an account owned by `program_id`
+    pub fn account_index_key(&self, account_data: &[u8]) -> Option<Pubkey> {
+        let end = self.offset.checked_add(self.length)?;
+        self.index_key(account_data.get(self.offset..end)?)
+    }
+
+    /// Extracts the index key from a comparison of `bytes` against account data at `offset`, if
+    /// the comparison covers the indexed range
+    pub fn memcmp_index_key(&self, offset: usize, bytes: &[u8]) -> Option<Pubkey> {
+        let start = self.offset.checked_sub(offset)?;
+        self.index_key(bytes.get(start..start.checked_add(self.length)?)?)
+    }
+}
+
+impl FromStr for ProgramDataIndex {
+    type Err = String;
+
+    /// Parses `<PROGRAM_ID>:<OFFSET>:<LENGTH>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let (Some(program_id), Some(offset), Some(length), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(format!(
+                "invalid program data index {s:?}, expected <PROGRAM_ID>:<OFFSET>:<LENGTH>"
+            ));
+        };
+        let program_id = program_id
+            .parse::<Pubkey>()
+            .map_err(|err| format!("invalid program id {program_id:?}: {err}"))?;
+        let offset = offset
+            .parse::<usize>()
+            .map_err(|err| format!("invalid offset {offset:?}: {err}"))?;
+        let length = length
+            .parse::<usize>()
+            .map_err(|err| format!("invalid length {length:?}: {err}"))?;
+        Self::new(program_id, offset, length)
+            .ok_or_else(|| format!("length must be between 1 and {PUBKEY_BYTES}, got {length}"))
+    }
 }
 
 // The only cases where an inner key should map to a different outer key is
@@ -468,6 +468,9 @@ fn main() {
         None,
         bank_forks.clone(),
         &prioritization_fee_cache,
+        None,
+        None,
+        None,
     );
 
     // This is so that the signal_receiver does not go out of scope after the closure.
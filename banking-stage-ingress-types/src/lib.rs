@@ -1,4 +1,21 @@
-use {crossbeam_channel::Receiver, solana_perf::packet::PacketBatch, std::sync::Arc};
+use {
+    crossbeam_channel::{Receiver, Sender},
+    solana_perf::packet::PacketBatch,
+    std::sync::Arc,
+};
 
 pub type BankingPacketBatch = Arc<Vec<PacketBatch>>;
 pub type BankingPacketReceiver = Receiver<BankingPacketBatch>;
+
+/// A bundle is a group of transactions that must be committed together, in
+/// order, or not at all. Each message carries exactly one bundle, with its
+/// packets in execution order.
+pub type BankingBundle = PacketBatch;
+pub type BankingBundleSender = Sender<BankingBundle>;
+pub type BankingBundleReceiver = Receiver<BankingBundle>;
+
+/// Maximum number of transactions in a single bundle.
+pub const MAX_BUNDLE_LEN: usize = 16;
+/// Capacity of the bounded bundle channel into the banking stage. Senders
+/// should reject bundles rather than block while the channel is full.
+pub const BUNDLE_CHANNEL_CAPACITY: usize = 1024;
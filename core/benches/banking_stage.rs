@@ -248,6 +248,9 @@ fn bench_banking(
         None,
         bank_forks,
         &Arc::new(PrioritizationFeeCache::new(0u64)),
+        None,
+        None,
+        None,
     );
 
     let chunk_len = verified.len() / CHUNKS;
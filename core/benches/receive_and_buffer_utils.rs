@@ -149,6 +149,7 @@ impl ReceiveAndBufferCreator for TransactionViewReceiveAndBuffer {
         TransactionViewReceiveAndBuffer {
             receiver,
             bank_forks,
+            transaction_outcome_log: None,
         }
     }
 }
@@ -158,7 +159,11 @@ impl ReceiveAndBufferCreator for SanitizedTransactionReceiveAndBuffer {
         receiver: Receiver<Arc<Vec<PacketBatch>>>,
         bank_forks: Arc<RwLock<BankForks>>,
     ) -> Self {
-        SanitizedTransactionReceiveAndBuffer::new(PacketDeserializer::new(receiver), bank_forks)
+        SanitizedTransactionReceiveAndBuffer::new(
+            PacketDeserializer::new(receiver),
+            bank_forks,
+            None,
+        )
     }
 }
 
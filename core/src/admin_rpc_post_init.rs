@@ -1,16 +1,18 @@
 use {
     crate::{
+        banking_stage::transaction_outcome_log::TransactionOutcomeLog,
         cluster_slots_service::cluster_slots::ClusterSlots,
         repair::{outstanding_requests::OutstandingRequests, serve_repair::ShredRepairType},
     },
     solana_gossip::cluster_info::ClusterInfo,
     solana_pubkey::Pubkey,
     solana_quic_definitions::NotifyKeyUpdate,
-    solana_runtime::bank_forks::BankForks,
+    solana_runtime::{bank_forks::BankForks, snapshot_config::SnapshotConfig},
     solana_streamer::atomic_udp_socket::AtomicUdpSocket,
     std::{
         collections::{HashMap, HashSet},
         net::UdpSocket,
+        path::PathBuf,
         sync::{Arc, RwLock},
     },
 };
@@ -80,4 +82,7 @@ pub struct AdminRpcRequestMetadataPostInit {
     pub outstanding_repair_requests: Arc<RwLock<OutstandingRequests<ShredRepairType>>>,
     pub cluster_slots: Arc<ClusterSlots>,
     pub gossip_socket: Option<AtomicUdpSocket>,
+    pub transaction_outcome_log: Option<Arc<TransactionOutcomeLog>>,
+    pub ledger_path: PathBuf,
+    pub snapshot_config: SnapshotConfig,
 }
@@ -205,7 +205,7 @@ impl BankingTraceEvents {
         Ok(events)
     }
 
-    fn load_event(&mut self, TimedTracedEvent(event_time, event): TimedTracedEvent) {
+    pub(crate) fn load_event(&mut self, TimedTracedEvent(event_time, event): TimedTracedEvent) {
         match event {
             TracedEvent::PacketBatch(label, batch) => {
                 // Deserialized PacketBatches will mostly be ordered by event_time, but this
@@ -234,6 +234,23 @@ impl BankingTraceEvents {
     pub fn hash_overrides(&self) -> &HashOverrides {
         &self.hash_overrides
     }
+
+    /// The last traced slot before `slot`, which blocks starting at `slot` are built on
+    pub fn parent_slot(&self, slot: Slot) -> Option<Slot> {
+        self.freeze_time_by_slot
+            .range(..slot)
+            .last()
+            .map(|(slot, _time)| slot)
+            .copied()
+    }
+
+    pub(crate) fn packet_batches_by_time(&self) -> &PacketBatchesByTime {
+        &self.packet_batches_by_time
+    }
+
+    pub(crate) fn freeze_time_by_slot(&self) -> &FreezeTimeBySlot {
+        &self.freeze_time_by_slot
+    }
 }
 
 struct DummyClusterInfo {
@@ -680,11 +697,7 @@ impl BankingSimulator {
 
     pub fn parent_slot(&self) -> Option<Slot> {
         self.banking_trace_events
-            .freeze_time_by_slot
-            .range(..self.first_simulated_slot)
-            .last()
-            .map(|(slot, _time)| slot)
-            .copied()
+            .parent_slot(self.first_simulated_slot)
     }
 
     fn prepare_simulation(
@@ -837,6 +850,9 @@ impl BankingSimulator {
             None,
             bank_forks.clone(),
             prioritization_fee_cache,
+            None,
+            None,
+            None,
         );
 
         let (&_slot, &raw_base_event_time) = freeze_time_by_slot
@@ -7,20 +7,23 @@ use qualifier_attr::qualifiers;
 use {
     self::{
         committer::Committer, consumer::Consumer, decision_maker::DecisionMaker,
-        packet_receiver::PacketReceiver, qos_service::QosService, vote_storage::VoteStorage,
+        packet_receiver::PacketReceiver, qos_service::QosService,
+        transaction_outcome_log::TransactionOutcomeLog, vote_storage::VoteStorage,
     },
     crate::{
         banking_stage::{
+            bundle_worker::BundleWorker,
             consume_worker::ConsumeWorker,
             packet_deserializer::PacketDeserializer,
             transaction_scheduler::{
-                prio_graph_scheduler::PrioGraphScheduler,
+                bundle_scheduler::BundleScheduler, prio_graph_scheduler::PrioGraphScheduler,
                 scheduler_controller::SchedulerController, scheduler_error::SchedulerError,
             },
         },
         validator::{BlockProductionMethod, TransactionStructure},
     },
-    agave_banking_stage_ingress_types::BankingPacketReceiver,
+    agave_banking_stage_ingress_types::{BankingBundleReceiver, BankingPacketReceiver},
+    agave_scheduler_bindings::SharedRegion,
     conditional_mod::conditional_vis_mod,
     crossbeam_channel::{unbounded, Receiver, Sender},
     histogram::Histogram,
@@ -46,6 +49,7 @@ use {
         time::Duration,
     },
     transaction_scheduler::{
+        external_scheduler::{ExternalScheduler, ExternalSchedulerConfig},
         greedy_scheduler::{GreedyScheduler, GreedySchedulerConfig},
         prio_graph_scheduler::PrioGraphSchedulerConfig,
         receive_and_buffer::{
@@ -61,8 +65,10 @@ pub mod committer;
 pub mod consumer;
 pub mod leader_slot_metrics;
 pub mod qos_service;
+pub mod transaction_outcome_log;
 pub mod vote_storage;
 
+mod bundle_worker;
 mod consume_worker;
 mod vote_worker;
 conditional_vis_mod!(decision_maker, feature = "dev-context-only-utils", pub);
@@ -375,6 +381,9 @@ impl BankingStage {
         log_messages_bytes_limit: Option<usize>,
         bank_forks: Arc<RwLock<BankForks>>,
         prioritization_fee_cache: &Arc<PrioritizationFeeCache>,
+        transaction_outcome_log: Option<Arc<TransactionOutcomeLog>>,
+        external_scheduler_region: Option<SharedRegion>,
+        bundle_receiver: Option<BankingBundleReceiver>,
     ) -> Self {
         Self::new_num_threads(
             block_production_method,
@@ -390,6 +399,9 @@ impl BankingStage {
             log_messages_bytes_limit,
             bank_forks,
             prioritization_fee_cache,
+            transaction_outcome_log,
+            external_scheduler_region,
+            bundle_receiver,
         )
     }
 
@@ -408,6 +420,9 @@ impl BankingStage {
         log_messages_bytes_limit: Option<usize>,
         bank_forks: Arc<RwLock<BankForks>>,
         prioritization_fee_cache: &Arc<PrioritizationFeeCache>,
+        transaction_outcome_log: Option<Arc<TransactionOutcomeLog>>,
+        external_scheduler_region: Option<SharedRegion>,
+        bundle_receiver: Option<BankingBundleReceiver>,
     ) -> Self {
         let use_greedy_scheduler = matches!(
             block_production_method,
@@ -427,6 +442,9 @@ impl BankingStage {
             log_messages_bytes_limit,
             bank_forks,
             prioritization_fee_cache,
+            transaction_outcome_log,
+            external_scheduler_region,
+            bundle_receiver,
         )
     }
 
@@ -445,6 +463,9 @@ impl BankingStage {
         log_messages_bytes_limit: Option<usize>,
         bank_forks: Arc<RwLock<BankForks>>,
         prioritization_fee_cache: &Arc<PrioritizationFeeCache>,
+        transaction_outcome_log: Option<Arc<TransactionOutcomeLog>>,
+        external_scheduler_region: Option<SharedRegion>,
+        bundle_receiver: Option<BankingBundleReceiver>,
     ) -> Self {
         assert!(num_threads >= MIN_TOTAL_THREADS);
         let vote_storage = {
@@ -459,8 +480,8 @@ impl BankingStage {
             prioritization_fee_cache.clone(),
         );
 
-        // + 1 for the central scheduler thread
-        let mut bank_thread_hdls = Vec::with_capacity(num_threads as usize + 1);
+        // + 1 for the central scheduler thread, + 1 for the bundle worker thread
+        let mut bank_thread_hdls = Vec::with_capacity(num_threads as usize + 2);
 
         // Spawn legacy voting thread
         bank_thread_hdls.push(Self::spawn_vote_worker(
@@ -479,6 +500,7 @@ impl BankingStage {
                 let receive_and_buffer = SanitizedTransactionReceiveAndBuffer::new(
                     PacketDeserializer::new(non_vote_receiver),
                     bank_forks.clone(),
+                    transaction_outcome_log.clone(),
                 );
                 Self::spawn_scheduler_and_workers(
                     &mut bank_thread_hdls,
@@ -491,12 +513,16 @@ impl BankingStage {
                     num_threads,
                     log_messages_bytes_limit,
                     bank_forks,
+                    transaction_outcome_log,
+                    external_scheduler_region,
+                    bundle_receiver,
                 );
             }
             TransactionStructure::View => {
                 let receive_and_buffer = TransactionViewReceiveAndBuffer {
                     receiver: non_vote_receiver,
                     bank_forks: bank_forks.clone(),
+                    transaction_outcome_log: transaction_outcome_log.clone(),
                 };
                 Self::spawn_scheduler_and_workers(
                     &mut bank_thread_hdls,
@@ -509,6 +535,9 @@ impl BankingStage {
                     num_threads,
                     log_messages_bytes_limit,
                     bank_forks,
+                    transaction_outcome_log,
+                    external_scheduler_region,
+                    bundle_receiver,
                 );
             }
         }
@@ -528,6 +557,9 @@ impl BankingStage {
         num_threads: u32,
         log_messages_bytes_limit: Option<usize>,
         bank_forks: Arc<RwLock<BankForks>>,
+        transaction_outcome_log: Option<Arc<TransactionOutcomeLog>>,
+        external_scheduler_region: Option<SharedRegion>,
+        bundle_receiver: Option<BankingBundleReceiver>,
     ) {
         // Create channels for communication between scheduler and workers
         let num_workers = (num_threads).saturating_sub(NUM_VOTE_PROCESSING_THREADS);
@@ -547,6 +579,7 @@ impl BankingStage {
                     transaction_recorder.clone(),
                     QosService::new(id),
                     log_messages_bytes_limit,
+                    transaction_outcome_log.clone(),
                 ),
                 finished_work_sender.clone(),
                 poh_recorder.read().unwrap().new_leader_bank_notifier(),
@@ -563,6 +596,37 @@ impl BankingStage {
             )
         }
 
+        // Spawn the bundle worker, bundles are scheduled by the central
+        // scheduler along with the transactions.
+        let bundle_scheduler = bundle_receiver.map(|bundle_receiver| {
+            let (consume_bundle_sender, consume_bundle_receiver) = unbounded();
+            let (finished_bundle_sender, finished_bundle_receiver) = unbounded();
+            let id = num_threads;
+            let bundle_worker = BundleWorker::new(
+                consume_bundle_receiver,
+                Consumer::new(
+                    committer.clone(),
+                    transaction_recorder.clone(),
+                    QosService::new(id),
+                    log_messages_bytes_limit,
+                    transaction_outcome_log.clone(),
+                ),
+                finished_bundle_sender,
+                poh_recorder.read().unwrap().new_leader_bank_notifier(),
+            );
+            bank_thread_hdls.push(
+                Builder::new()
+                    .name("solBanknStgBndl".to_string())
+                    .spawn(move || bundle_worker.run())
+                    .unwrap(),
+            );
+            BundleScheduler::new(
+                bundle_receiver,
+                consume_bundle_sender,
+                finished_bundle_receiver,
+            )
+        });
+
         // Macro to spawn the scheduler. Different type on `scheduler` and thus
         // scheduler_controller mean we cannot have an easy if for `scheduler`
         // assignment without introducing `dyn`.
@@ -578,6 +642,8 @@ impl BankingStage {
                                 bank_forks,
                                 $scheduler,
                                 worker_metrics,
+                                transaction_outcome_log,
+                                bundle_scheduler,
                             );
 
                             match scheduler_controller.run() {
@@ -593,21 +659,37 @@ impl BankingStage {
             };
         }
 
-        // Spawn the central scheduler thread
+        // Spawn the central scheduler thread, an external scheduler takes
+        // precedence and falls back to the built-in one while it is absent.
+        macro_rules! spawn_scheduler_or_external {
+            ($scheduler:ident) => {
+                if let Some(region) = external_scheduler_region {
+                    let scheduler = ExternalScheduler::new(
+                        $scheduler,
+                        region,
+                        ExternalSchedulerConfig::default(),
+                    );
+                    spawn_scheduler!(scheduler);
+                } else {
+                    spawn_scheduler!($scheduler);
+                }
+            };
+        }
+
         if use_greedy_scheduler {
             let scheduler = GreedyScheduler::new(
                 work_senders,
                 finished_work_receiver,
                 GreedySchedulerConfig::default(),
             );
-            spawn_scheduler!(scheduler);
+            spawn_scheduler_or_external!(scheduler);
         } else {
             let scheduler = PrioGraphScheduler::new(
                 work_senders,
                 finished_work_receiver,
                 PrioGraphSchedulerConfig::default(),
             );
-            spawn_scheduler!(scheduler);
+            spawn_scheduler_or_external!(scheduler);
         }
     }
 
@@ -628,6 +710,7 @@ impl BankingStage {
             transaction_recorder,
             QosService::new(0),
             log_messages_bytes_limit,
+            None,
         );
 
         Builder::new()
@@ -646,6 +729,11 @@ impl BankingStage {
             .unwrap()
     }
 
+    /// Number of consume workers [`Self::new`] assigns transactions to.
+    pub fn num_workers() -> u32 {
+        Self::num_threads().saturating_sub(NUM_VOTE_PROCESSING_THREADS)
+    }
+
     pub fn num_threads() -> u32 {
         cmp::max(
             env::var("SOLANA_BANKING_THREADS")
@@ -759,6 +847,9 @@ mod tests {
             None,
             bank_forks,
             &Arc::new(PrioritizationFeeCache::new(0u64)),
+            None,
+            None,
+            None,
         );
         drop(non_vote_sender);
         drop(tpu_vote_sender);
@@ -814,6 +905,9 @@ mod tests {
             None,
             bank_forks,
             &Arc::new(PrioritizationFeeCache::new(0u64)),
+            None,
+            None,
+            None,
         );
         trace!("sending bank");
         drop(non_vote_sender);
@@ -878,6 +972,9 @@ mod tests {
             None,
             bank_forks.clone(), // keep a local-copy of bank-forks so worker threads do not lose weak access to bank-forks
             &Arc::new(PrioritizationFeeCache::new(0u64)),
+            None,
+            None,
+            None,
         );
 
         // fund another account so we can send 2 good transactions in a single batch.
@@ -1030,6 +1127,9 @@ mod tests {
                 None,
                 bank_forks,
                 &Arc::new(PrioritizationFeeCache::new(0u64)),
+                None,
+                None,
+                None,
             );
 
             // wait for banking_stage to eat the packets
@@ -1216,6 +1316,9 @@ mod tests {
             None,
             bank_forks,
             &Arc::new(PrioritizationFeeCache::new(0u64)),
+            None,
+            None,
+            None,
         );
 
         let keypairs = (0..100).map(|_| Keypair::new()).collect_vec();
@@ -0,0 +1,66 @@
+//! Executes bundles, groups of transactions that must be committed together,
+//! in order, or not at all, against the leader bank.
+//!
+//! Bundles are buffered and their accounts locked by the scheduler, see
+//! [`BundleScheduler`](super::transaction_scheduler::bundle_scheduler::BundleScheduler).
+//! The worker executes them one at a time with
+//! [`Consumer::process_and_record_bundle`] and hands them back to the
+//! scheduler to release their locks.
+
+use {
+    super::{
+        consumer::{BundleExecutionError, Consumer},
+        scheduler_messages::{ConsumeBundle, FinishedConsumeBundle},
+    },
+    crossbeam_channel::{Receiver, Sender},
+    solana_poh::leader_bank_notifier::LeaderBankNotifier,
+    std::{sync::Arc, time::Duration},
+};
+
+pub(crate) struct BundleWorker {
+    consume_receiver: Receiver<ConsumeBundle>,
+    consumer: Consumer,
+    consumed_sender: Sender<FinishedConsumeBundle>,
+    leader_bank_notifier: Arc<LeaderBankNotifier>,
+}
+
+impl BundleWorker {
+    pub fn new(
+        consume_receiver: Receiver<ConsumeBundle>,
+        consumer: Consumer,
+        consumed_sender: Sender<FinishedConsumeBundle>,
+        leader_bank_notifier: Arc<LeaderBankNotifier>,
+    ) -> Self {
+        Self {
+            consume_receiver,
+            consumer,
+            consumed_sender,
+            leader_bank_notifier,
+        }
+    }
+
+    /// Runs until the scheduler disconnects.
+    pub fn run(self) {
+        while let Ok(work) = self.consume_receiver.recv() {
+            let result = self.consume(&work);
+            if self
+                .consumed_sender
+                .send(FinishedConsumeBundle { work, result })
+                .is_err()
+            {
+                break;
+            }
+        }
+    }
+
+    fn consume(&self, work: &ConsumeBundle) -> Result<(), BundleExecutionError> {
+        let bank = self
+            .leader_bank_notifier
+            .get_or_wait_for_in_progress(Duration::from_millis(50))
+            .upgrade()
+            .ok_or(BundleExecutionError::BankUnavailable)?;
+        self.consumer
+            .process_and_record_bundle(&bank, &work.transactions)
+            .map(|_| ())
+    }
+}
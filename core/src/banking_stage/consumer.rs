@@ -2,11 +2,13 @@ use {
     super::{
         committer::{CommitTransactionDetails, Committer},
         leader_slot_timing_metrics::LeaderExecuteAndCommitTimings,
-        qos_service::QosService,
+        qos_service::{BundleCostError, QosService},
         scheduler_messages::MaxAge,
+        transaction_outcome_log::{TransactionOutcome, TransactionOutcomeLog},
     },
+    ahash::AHashMap,
     itertools::Itertools,
-    solana_clock::MAX_PROCESSING_AGE,
+    solana_clock::{Slot, MAX_PROCESSING_AGE},
     solana_fee::FeeFeatures,
     solana_fee_structure::FeeBudgetLimits,
     solana_measure::measure_us,
@@ -27,8 +29,9 @@ use {
         transaction_processing_result::TransactionProcessingResultExtensions,
         transaction_processor::{ExecutionRecordingConfig, TransactionProcessingConfig},
     },
-    solana_transaction_error::TransactionError,
+    solana_transaction_error::{TransactionError, TransactionResult},
     std::{num::Saturating, sync::Arc},
+    thiserror::Error,
 };
 
 /// Consumer will create chunks of transactions from buffer with up to this size.
@@ -70,11 +73,47 @@ pub struct LeaderProcessedTransactionCounts {
     pub(crate) processed_with_successful_result_count: u64,
 }
 
+/// Reasons a bundle was not committed. None of its transactions are committed
+/// in any of these cases.
+#[derive(Debug, Error)]
+pub enum BundleExecutionError {
+    #[error("bundle transaction {index} failed: {err}")]
+    TransactionFailure { index: usize, err: TransactionError },
+    #[error("bundle does not fit in the block: {0}")]
+    CostLimit(TransactionError),
+    #[error("bundle exceeds the cost limits of an empty block: {0}")]
+    ExceedsCostLimit(TransactionError),
+    #[error("bundle transactions {first} and {second} lock the same account")]
+    ConflictingTransactions { first: usize, second: usize },
+    #[error("bundle accounts could not be locked: {0}")]
+    LockFailure(TransactionError),
+    #[error("bundle could not be recorded: {0}")]
+    PohRecorder(#[from] PohRecorderError),
+    #[error("no leader bank to execute the bundle against")]
+    BankUnavailable,
+}
+
+impl BundleExecutionError {
+    /// Whether the bundle may succeed if attempted again later.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::TransactionFailure { .. }
+            | Self::ExceedsCostLimit(_)
+            | Self::ConflictingTransactions { .. } => false,
+            Self::CostLimit(_)
+            | Self::LockFailure(_)
+            | Self::PohRecorder(_)
+            | Self::BankUnavailable => true,
+        }
+    }
+}
+
 pub struct Consumer {
     committer: Committer,
     transaction_recorder: TransactionRecorder,
     qos_service: QosService,
     log_messages_bytes_limit: Option<usize>,
+    transaction_outcome_log: Option<Arc<TransactionOutcomeLog>>,
 }
 
 impl Consumer {
@@ -83,12 +122,14 @@ impl Consumer {
         transaction_recorder: TransactionRecorder,
         qos_service: QosService,
         log_messages_bytes_limit: Option<usize>,
+        transaction_outcome_log: Option<Arc<TransactionOutcomeLog>>,
     ) -> Self {
         Self {
             committer,
             transaction_recorder,
             qos_service,
             log_messages_bytes_limit,
+            transaction_outcome_log,
         }
     }
 
@@ -157,6 +198,182 @@ impl Consumer {
         self.process_and_record_transactions_with_pre_results(bank, txs, pre_results)
     }
 
+    /// Execute `txs` in order against `bank` and commit them as a single PoH
+    /// entry, but only if every transaction is processed successfully.
+    ///
+    /// Block space is reserved and accounts are locked for the whole bundle
+    /// up front. Transactions later in the bundle observe the state written by
+    /// earlier ones, which requires intra-batch account conflicts to be
+    /// allowed (`relax_intrabatch_account_locks`) for bundles that touch the
+    /// same writable accounts more than once.
+    pub fn process_and_record_bundle(
+        &self,
+        bank: &Arc<Bank>,
+        txs: &[impl TransactionWithMeta],
+    ) -> Result<Vec<CommitTransactionDetails>, BundleExecutionError> {
+        let result = self.process_and_record_bundle_inner(bank, txs);
+        self.qos_service.report_metrics(bank.slot());
+
+        if let Some(transaction_outcome_log) = self.transaction_outcome_log.as_ref() {
+            let outcome = match &result {
+                Ok(_) => TransactionOutcome::Committed(None),
+                Err(
+                    BundleExecutionError::CostLimit(err)
+                    | BundleExecutionError::ExceedsCostLimit(err),
+                ) => TransactionOutcome::CostLimitRejected(err.clone()),
+                Err(BundleExecutionError::LockFailure(err)) => {
+                    TransactionOutcome::Retried(Some(err.clone()))
+                }
+                Err(
+                    BundleExecutionError::PohRecorder(_) | BundleExecutionError::BankUnavailable,
+                ) => TransactionOutcome::Retried(None),
+                Err(BundleExecutionError::TransactionFailure { err, .. }) => {
+                    TransactionOutcome::NotCommitted(err.clone())
+                }
+                Err(BundleExecutionError::ConflictingTransactions { .. }) => {
+                    TransactionOutcome::NotCommitted(TransactionError::AccountInUse)
+                }
+            };
+            transaction_outcome_log.record_all(
+                bank.slot(),
+                txs.iter().map(|tx| (*tx.signature(), outcome.clone())),
+            );
+        }
+
+        result
+    }
+
+    fn process_and_record_bundle_inner(
+        &self,
+        bank: &Arc<Bank>,
+        txs: &[impl TransactionWithMeta],
+    ) -> Result<Vec<CommitTransactionDetails>, BundleExecutionError> {
+        // Without relaxed intra-batch locks the bank cannot lock the bundle
+        // at all, no matter how often it is retried.
+        if !bank
+            .feature_set
+            .is_active(&agave_feature_set::relax_intrabatch_account_locks::id())
+        {
+            if let Some((first, second)) = find_conflicting_transactions(txs) {
+                return Err(BundleExecutionError::ConflictingTransactions { first, second });
+            }
+        }
+
+        let mut error_counters = TransactionErrorMetrics::default();
+        let pre_results = vec![Ok(()); txs.len()];
+        let check_results =
+            bank.check_transactions(txs, &pre_results, MAX_PROCESSING_AGE, &mut error_counters);
+        if let Some((index, err)) = check_results
+            .into_iter()
+            .enumerate()
+            .find_map(|(index, result)| result.err().map(|err| (index, err)))
+        {
+            return Err(BundleExecutionError::TransactionFailure { index, err });
+        }
+
+        let transaction_qos_cost_results = self
+            .qos_service
+            .select_and_accumulate_bundle_costs(bank, txs)
+            .map_err(|err| match err {
+                BundleCostError::WouldExceedLimit(err) => BundleExecutionError::CostLimit(err),
+                BundleCostError::ExceedsLimit(err) => BundleExecutionError::ExceedsCostLimit(err),
+            })?;
+
+        let batch = bank.prepare_sanitized_batch(txs);
+        let result = match batch
+            .lock_results()
+            .iter()
+            .find_map(|result| result.as_ref().err())
+        {
+            Some(err) => Err(BundleExecutionError::LockFailure(err.clone())),
+            None => self.execute_and_commit_bundle_locked(bank, &batch),
+        };
+        drop(batch);
+
+        // Reserved costs are either updated with the actual costs of the
+        // committed bundle, or removed entirely.
+        QosService::remove_or_update_costs(
+            transaction_qos_cost_results.iter(),
+            result.as_ref().ok(),
+            bank,
+        );
+        result
+    }
+
+    fn execute_and_commit_bundle_locked(
+        &self,
+        bank: &Arc<Bank>,
+        batch: &TransactionBatch<impl TransactionWithMeta>,
+    ) -> Result<Vec<CommitTransactionDetails>, BundleExecutionError> {
+        let mut execute_and_commit_timings = LeaderExecuteAndCommitTimings::default();
+        let mut error_counters = TransactionErrorMetrics::default();
+        let LoadAndExecuteTransactionsOutput {
+            processing_results,
+            processed_counts,
+            balance_collector,
+        } = bank.load_and_execute_transactions(
+            batch,
+            MAX_PROCESSING_AGE,
+            &mut execute_and_commit_timings.execute_timings,
+            &mut error_counters,
+            TransactionProcessingConfig {
+                account_overrides: None,
+                check_program_modification_slot: bank.check_program_modification_slot(),
+                log_messages_bytes_limit: self.log_messages_bytes_limit,
+                limit_to_load_programs: true,
+                recording_config: ExecutionRecordingConfig {
+                    enable_instruction_trace_recording: bank
+                        .get_transaction_processor()
+                        .has_instruction_trace_notifier(),
+                    enable_instruction_trace_account_data_recording: bank
+                        .get_transaction_processor()
+                        .instruction_trace_notifier_account_data_enabled(),
+                    ..ExecutionRecordingConfig::new_single_setting(
+                        self.committer.transaction_status_sender_enabled(),
+                    )
+                },
+            },
+        );
+
+        // A failed transaction, even one that would be committed with its fee
+        // charged, fails the whole bundle.
+        if let Some((index, err)) = processing_results
+            .iter()
+            .enumerate()
+            .find_map(|(index, result)| result.flattened_result().err().map(|err| (index, err)))
+        {
+            return Err(BundleExecutionError::TransactionFailure { index, err });
+        }
+
+        let processed_transactions = batch
+            .sanitized_transactions()
+            .iter()
+            .map(|tx| tx.to_versioned_transaction())
+            .collect_vec();
+        let freeze_lock = bank.freeze_lock();
+        let RecordTransactionsSummary {
+            result: record_transactions_result,
+            starting_transaction_index,
+            ..
+        } = self
+            .transaction_recorder
+            .record_transactions(bank.slot(), processed_transactions);
+        record_transactions_result?;
+
+        let (_commit_time_us, commit_transaction_statuses) = self.committer.commit_transactions(
+            batch,
+            processing_results,
+            starting_transaction_index,
+            bank,
+            balance_collector,
+            &mut execute_and_commit_timings,
+            &processed_counts,
+        );
+        drop(freeze_lock);
+
+        Ok(commit_transaction_statuses)
+    }
+
     fn process_and_record_transactions_with_pre_results(
         &self,
         bank: &Arc<Bank>,
@@ -295,9 +512,17 @@ impl Consumer {
                     check_program_modification_slot: bank.check_program_modification_slot(),
                     log_messages_bytes_limit: self.log_messages_bytes_limit,
                     limit_to_load_programs: true,
-                    recording_config: ExecutionRecordingConfig::new_single_setting(
-                        transaction_status_sender_enabled
-                    ),
+                    recording_config: ExecutionRecordingConfig {
+                        enable_instruction_trace_recording: bank
+                            .get_transaction_processor()
+                            .has_instruction_trace_notifier(),
+                        enable_instruction_trace_account_data_recording: bank
+                            .get_transaction_processor()
+                            .instruction_trace_notifier_account_data_enabled(),
+                        ..ExecutionRecordingConfig::new_single_setting(
+                            transaction_status_sender_enabled
+                        )
+                    },
                 }
             ));
         execute_and_commit_timings.load_execute_us = load_execute_us;
@@ -334,6 +559,20 @@ impl Consumer {
             attempted_processing_count: processing_results.len() as u64,
         };
 
+        // Keep a copy of the per-transaction results for the outcome log, since
+        // `processing_results` is consumed by the committer.
+        let outcome_processing_results = self.transaction_outcome_log.as_ref().map(|_| {
+            processing_results
+                .iter()
+                .map(|processing_result| {
+                    (
+                        processing_result.was_processed(),
+                        processing_result.flattened_result(),
+                    )
+                })
+                .collect_vec()
+        });
+
         let (processed_transactions, processing_results_to_transactions_us) =
             measure_us!(processing_results
                 .iter()
@@ -376,6 +615,10 @@ impl Consumer {
             // `extend` can cause that assumption to be violated.
             retryable_transaction_indexes.sort_unstable();
 
+            if let Some(processing_results) = outcome_processing_results {
+                self.record_transaction_outcomes(bank.slot(), batch, &processing_results, false);
+            }
+
             return ExecuteAndCommitTransactionsOutput {
                 transaction_counts,
                 retryable_transaction_indexes,
@@ -407,6 +650,10 @@ impl Consumer {
 
         drop(freeze_lock);
 
+        if let Some(processing_results) = outcome_processing_results {
+            self.record_transaction_outcomes(bank.slot(), batch, &processing_results, true);
+        }
+
         debug!(
             "bank: {} process_and_record_locked: {}us record: {}us commit: {}us txs_len: {}",
             bank.slot(),
@@ -437,6 +684,53 @@ impl Consumer {
         }
     }
 
+    /// Record the outcome of every transaction in `batch` to the outcome log.
+    /// `processing_results` holds whether each transaction was processed and
+    /// its flattened result. `recorded` is false if the processed
+    /// transactions could not be recorded into PoH and will be retried.
+    fn record_transaction_outcomes(
+        &self,
+        slot: Slot,
+        batch: &TransactionBatch<impl TransactionWithMeta>,
+        processing_results: &[(bool, TransactionResult<()>)],
+        recorded: bool,
+    ) {
+        let Some(transaction_outcome_log) = self.transaction_outcome_log.as_ref() else {
+            return;
+        };
+        let outcomes = batch
+            .sanitized_transactions()
+            .iter()
+            .zip(batch.lock_results())
+            .zip(processing_results)
+            .map(|((transaction, lock_result), (was_processed, result))| {
+                let outcome = match (lock_result, was_processed) {
+                    (
+                        Err(
+                            err @ (TransactionError::WouldExceedMaxBlockCostLimit
+                            | TransactionError::WouldExceedMaxVoteCostLimit
+                            | TransactionError::WouldExceedMaxAccountCostLimit
+                            | TransactionError::WouldExceedAccountDataBlockLimit),
+                        ),
+                        _,
+                    ) => TransactionOutcome::CostLimitRejected(err.clone()),
+                    (Err(err @ TransactionError::AccountInUse), _) => {
+                        TransactionOutcome::Retried(Some(err.clone()))
+                    }
+                    (Err(err), _) => TransactionOutcome::dropped(err.clone()),
+                    (Ok(()), false) => TransactionOutcome::NotCommitted(
+                        result
+                            .clone()
+                            .expect_err("unprocessed transaction has an error"),
+                    ),
+                    (Ok(()), true) if !recorded => TransactionOutcome::Retried(None),
+                    (Ok(()), true) => TransactionOutcome::Committed(result.clone().err()),
+                };
+                (*transaction.signature(), outcome)
+            });
+        transaction_outcome_log.record_all(slot, outcomes);
+    }
+
     pub fn check_fee_payer_unlocked(
         bank: &Bank,
         transaction: &impl TransactionWithMeta,
@@ -473,6 +767,37 @@ impl Consumer {
     }
 }
 
+/// Returns the indexes of the first pair of transactions in `txs` that lock
+/// the same account, where at least one of them locks it as writable.
+fn find_conflicting_transactions(txs: &[impl TransactionWithMeta]) -> Option<(usize, usize)> {
+    let mut write_locks = AHashMap::new();
+    let mut read_locks = AHashMap::new();
+    for (index, tx) in txs.iter().enumerate() {
+        for (key_index, key) in tx.account_keys().iter().enumerate() {
+            let conflict = if tx.is_writable(key_index) {
+                write_locks
+                    .get(key)
+                    .or_else(|| read_locks.get(key))
+                    .copied()
+            } else {
+                write_locks.get(key).copied()
+            };
+            if let Some(first) = conflict {
+                return Some((first, index));
+            }
+        }
+        for (key_index, key) in tx.account_keys().iter().enumerate() {
+            let locks = if tx.is_writable(key_index) {
+                &mut write_locks
+            } else {
+                &mut read_locks
+            };
+            locks.entry(*key).or_insert(index);
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -570,7 +895,7 @@ mod tests {
             replay_vote_sender,
             Arc::new(PrioritizationFeeCache::new(0u64)),
         );
-        let consumer = Consumer::new(committer, recorder, QosService::new(1), None);
+        let consumer = Consumer::new(committer, recorder, QosService::new(1), None, None);
         let process_transactions_summary =
             consumer.process_and_record_transactions(&bank, &transactions);
 
@@ -680,7 +1005,7 @@ mod tests {
             replay_vote_sender,
             Arc::new(PrioritizationFeeCache::new(0u64)),
         );
-        let consumer = Consumer::new(committer, recorder, QosService::new(1), None);
+        let consumer = Consumer::new(committer, recorder, QosService::new(1), None, None);
 
         let process_transactions_batch_output =
             consumer.process_and_record_transactions(&bank, &transactions);
@@ -764,6 +1089,138 @@ mod tests {
         assert_eq!(bank.get_balance(&pubkey), 1);
     }
 
+    #[test_case(false; "old")]
+    #[test_case(true; "simd83")]
+    fn test_bank_process_and_record_bundle(relax_intrabatch_account_locks: bool) {
+        solana_logger::setup();
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config_with_leader(
+            10_000,
+            &Pubkey::new_unique(),
+            bootstrap_validator_stake_lamports(),
+        );
+        let mut bank = Bank::new_for_tests(&genesis_config);
+        if !relax_intrabatch_account_locks {
+            bank.deactivate_feature(&agave_feature_set::relax_intrabatch_account_locks::id());
+        }
+        bank.ns_per_slot = u128::MAX;
+        let (bank, _bank_forks) = bank.wrap_with_bank_forks_for_tests();
+        let funded_keypair = Keypair::new();
+        bank.transfer(1_000, &mint_keypair, &funded_keypair.pubkey())
+            .unwrap();
+        let pubkey = solana_pubkey::new_rand();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path())
+            .expect("Expected to be able to open database ledger");
+        let (poh_recorder, _entry_receiver) = PohRecorder::new(
+            bank.tick_height(),
+            bank.last_blockhash(),
+            bank.clone(),
+            Some((4, 4)),
+            bank.ticks_per_slot(),
+            Arc::new(blockstore),
+            &Arc::new(LeaderScheduleCache::new_from_bank(&bank)),
+            &PohConfig::default(),
+            Arc::new(AtomicBool::default()),
+        );
+        let (record_sender, record_receiver) = unbounded();
+        let recorder = TransactionRecorder::new(record_sender, poh_recorder.is_exited.clone());
+        let poh_recorder = Arc::new(RwLock::new(poh_recorder));
+
+        let poh_simulator = simulate_poh(record_receiver, &poh_recorder);
+
+        poh_recorder
+            .write()
+            .unwrap()
+            .set_bank_for_test(bank.clone());
+        let (replay_vote_sender, _replay_vote_receiver) = unbounded();
+        let committer = Committer::new(
+            None,
+            replay_vote_sender,
+            Arc::new(PrioritizationFeeCache::new(0u64)),
+        );
+        let consumer = Consumer::new(committer, recorder, QosService::new(1), None, None);
+
+        // The second transaction's fee payer does not exist, so neither
+        // transaction in the bundle is committed.
+        let bundle = sanitize_transactions(vec![
+            system_transaction::transfer(&mint_keypair, &pubkey, 1, genesis_config.hash()),
+            system_transaction::transfer(&Keypair::new(), &pubkey, 1, genesis_config.hash()),
+        ]);
+        assert_matches!(
+            consumer.process_and_record_bundle(&bank, &bundle),
+            Err(BundleExecutionError::TransactionFailure {
+                index: 1,
+                err: TransactionError::AccountNotFound,
+            })
+        );
+        assert_eq!(bank.get_balance(&pubkey), 0);
+        assert_eq!(bank.read_cost_tracker().unwrap().block_cost(), 0);
+
+        let bundle = sanitize_transactions(vec![
+            system_transaction::transfer(&mint_keypair, &pubkey, 1, genesis_config.hash()),
+            system_transaction::transfer(&funded_keypair, &pubkey, 2, genesis_config.hash()),
+        ]);
+        let commit_transaction_details =
+            consumer.process_and_record_bundle(&bank, &bundle).unwrap();
+        assert_eq!(commit_transaction_details.len(), 2);
+        assert!(commit_transaction_details
+            .iter()
+            .all(|details| matches!(details, CommitTransactionDetails::Committed { .. })));
+        assert_eq!(bank.get_balance(&pubkey), 3);
+
+        // Both transactions write the mint account, which the bank can only
+        // lock within a single batch if intra-batch conflicts are allowed.
+        let bundle = sanitize_transactions(vec![
+            system_transaction::transfer(&mint_keypair, &pubkey, 3, genesis_config.hash()),
+            system_transaction::transfer(&mint_keypair, &pubkey, 4, genesis_config.hash()),
+        ]);
+        let result = consumer.process_and_record_bundle(&bank, &bundle);
+        if relax_intrabatch_account_locks {
+            assert_eq!(result.unwrap().len(), 2);
+            assert_eq!(bank.get_balance(&pubkey), 10);
+        } else {
+            let err = result.unwrap_err();
+            assert_matches!(
+                err,
+                BundleExecutionError::ConflictingTransactions {
+                    first: 0,
+                    second: 1
+                }
+            );
+            assert!(!err.is_retryable());
+            assert_eq!(bank.get_balance(&pubkey), 3);
+        }
+
+        // A bundle that exceeds the block limits on its own is never retried.
+        bank.write_cost_tracker().unwrap().set_limits(1, 1, 1);
+        let bundle = sanitize_transactions(vec![system_transaction::transfer(
+            &funded_keypair,
+            &pubkey,
+            5,
+            genesis_config.hash(),
+        )]);
+        let err = consumer
+            .process_and_record_bundle(&bank, &bundle)
+            .unwrap_err();
+        assert_matches!(
+            err,
+            BundleExecutionError::ExceedsCostLimit(TransactionError::WouldExceedMaxBlockCostLimit)
+        );
+        assert!(!err.is_retryable());
+
+        poh_recorder
+            .read()
+            .unwrap()
+            .is_exited
+            .store(true, Ordering::Relaxed);
+        let _ = poh_simulator.join();
+    }
+
     #[test]
     fn test_bank_nonce_update_blockhash_queried_before_transaction_record() {
         solana_logger::setup();
@@ -872,7 +1329,7 @@ mod tests {
             replay_vote_sender,
             Arc::new(PrioritizationFeeCache::new(0u64)),
         );
-        let consumer = Consumer::new(committer, recorder, QosService::new(1), None);
+        let consumer = Consumer::new(committer, recorder, QosService::new(1), None, None);
 
         let process_transactions_batch_output =
             consumer.process_and_record_transactions(&bank, &transactions);
@@ -974,7 +1431,7 @@ mod tests {
             replay_vote_sender,
             Arc::new(PrioritizationFeeCache::new(0u64)),
         );
-        let consumer = Consumer::new(committer, recorder, QosService::new(1), None);
+        let consumer = Consumer::new(committer, recorder, QosService::new(1), None, None);
 
         let process_transactions_batch_output =
             consumer.process_and_record_transactions(&bank, &transactions);
@@ -1057,7 +1514,7 @@ mod tests {
             replay_vote_sender,
             Arc::new(PrioritizationFeeCache::new(0u64)),
         );
-        let consumer = Consumer::new(committer, recorder, QosService::new(1), None);
+        let consumer = Consumer::new(committer, recorder, QosService::new(1), None, None);
 
         let get_block_cost = || bank.read_cost_tracker().unwrap().block_cost();
         let get_tx_count = || bank.read_cost_tracker().unwrap().transaction_count();
@@ -1249,7 +1706,7 @@ mod tests {
             replay_vote_sender,
             Arc::new(PrioritizationFeeCache::new(0u64)),
         );
-        let consumer = Consumer::new(committer, recorder, QosService::new(1), None);
+        let consumer = Consumer::new(committer, recorder, QosService::new(1), None, None);
 
         // with simd83 and no duplicate, we take a cross-batch lock on an account to create a conflict
         // with a duplicate transaction and simd83 it comes from message hash equality in the batch
@@ -1495,7 +1952,7 @@ mod tests {
             replay_vote_sender,
             Arc::new(PrioritizationFeeCache::new(0u64)),
         );
-        let consumer = Consumer::new(committer, recorder.clone(), QosService::new(1), None);
+        let consumer = Consumer::new(committer, recorder.clone(), QosService::new(1), None, None);
 
         let process_transactions_summary =
             consumer.process_and_record_transactions(&bank, &transactions);
@@ -1638,7 +2095,7 @@ mod tests {
             replay_vote_sender,
             Arc::new(PrioritizationFeeCache::new(0u64)),
         );
-        let consumer = Consumer::new(committer, recorder, QosService::new(1), None);
+        let consumer = Consumer::new(committer, recorder, QosService::new(1), None, None);
 
         let _ = consumer.process_and_record_transactions(&bank, &transactions);
 
@@ -1784,7 +2241,7 @@ mod tests {
             replay_vote_sender,
             Arc::new(PrioritizationFeeCache::new(0u64)),
         );
-        let consumer = Consumer::new(committer, recorder, QosService::new(1), None);
+        let consumer = Consumer::new(committer, recorder, QosService::new(1), None, None);
 
         let consumer_output =
             consumer.process_and_record_transactions(&bank, &[sanitized_tx.clone()]);
@@ -27,6 +27,15 @@ mod transaction {
     pub use solana_transaction_error::TransactionResult as Result;
 }
 
+/// Why block space could not be reserved for a bundle
+#[derive(Debug, PartialEq, Eq)]
+pub enum BundleCostError {
+    /// The bundle does not fit in what is left of the block, but may fit in a later one
+    WouldExceedLimit(TransactionError),
+    /// The bundle exceeds a limit even in an empty block, so it never fits
+    ExceedsLimit(TransactionError),
+}
+
 // QosService is local to each banking thread, each instance of QosService provides services to
 // one banking thread.
 // Banking thread calls `report_metrics(slot)` at end of `process_and_record_transaction()`, or any time
@@ -72,6 +81,72 @@ impl QosService {
         )
     }
 
+    /// Reserve block space for all transactions of a bundle, or for none of them.
+    /// On success every returned cost is `Ok`, in transaction order, in the same
+    /// shape `select_and_accumulate_transaction_costs` returns so that it can be
+    /// passed to `remove_or_update_costs` once the bundle is done.
+    pub fn select_and_accumulate_bundle_costs<'a, Tx: TransactionWithMeta>(
+        &self,
+        bank: &Bank,
+        transactions: &'a [Tx],
+    ) -> Result<Vec<transaction::Result<TransactionCost<'a, Tx>>>, BundleCostError> {
+        let transaction_costs = self
+            .compute_transaction_costs(
+                &bank.feature_set,
+                transactions.iter(),
+                std::iter::repeat(Ok(())),
+            )
+            .into_iter()
+            .collect::<transaction::Result<Vec<_>>>()
+            .map_err(BundleCostError::WouldExceedLimit)?;
+
+        let mut cost_tracking_time = Measure::start("cost_tracking_time");
+        let mut cost_tracker = bank.write_cost_tracker().unwrap();
+        let mut result = Ok(());
+        for (num_added, cost) in transaction_costs.iter().enumerate() {
+            if let Err(err) = cost_tracker.try_add(cost) {
+                // Roll back the part of the bundle that was already added
+                // before releasing the lock, so it never throttles other
+                // transactions.
+                transaction_costs[..num_added]
+                    .iter()
+                    .for_each(|cost| cost_tracker.remove(cost));
+                // Retrying is pointless if the bundle alone exceeds the limits
+                let mut empty_cost_tracker = cost_tracker.new_from_parent_limits();
+                let fits_in_empty_block = transaction_costs
+                    .iter()
+                    .all(|cost| empty_cost_tracker.try_add(cost).is_ok());
+                let err = TransactionError::from(err);
+                result = Err(if fits_in_empty_block {
+                    BundleCostError::WouldExceedLimit(err)
+                } else {
+                    BundleCostError::ExceedsLimit(err)
+                });
+                break;
+            }
+        }
+        if result.is_ok() {
+            cost_tracker.add_transactions_in_flight(transaction_costs.len());
+        }
+        drop(cost_tracker);
+        cost_tracking_time.stop();
+        self.metrics
+            .stats
+            .cost_tracking_time
+            .fetch_add(cost_tracking_time.as_us(), Ordering::Relaxed);
+        result?;
+
+        self.metrics
+            .stats
+            .selected_txs_count
+            .fetch_add(transaction_costs.len() as u64, Ordering::Relaxed);
+        let transaction_costs: Vec<_> = transaction_costs.into_iter().map(Ok).collect();
+        self.accumulate_estimated_transaction_costs(&Self::accumulate_batched_transaction_costs(
+            transaction_costs.iter(),
+        ));
+        Ok(transaction_costs)
+    }
+
     // invoke cost_model to calculate cost for the given list of transactions that have not
     // been filtered out already.
     fn compute_transaction_costs<'a, Tx: TransactionWithMeta>(
@@ -694,6 +769,67 @@ mod tests {
         assert!(results[3].is_err());
     }
 
+    #[test]
+    fn test_select_and_accumulate_bundle_costs() {
+        solana_logger::setup();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+
+        let keypair = Keypair::new();
+        let transfer_tx = RuntimeTransaction::from_transaction_for_tests(
+            system_transaction::transfer(&keypair, &keypair.pubkey(), 1, Hash::default()),
+        );
+        let transfer_tx_cost = CostModel::calculate_cost(&transfer_tx, &bank.feature_set).sum();
+        let qos_service = QosService::new(1);
+
+        // set cost tracker limit to fit 2 transfer txs
+        let cost_limit = 2 * transfer_tx_cost;
+        bank.write_cost_tracker()
+            .unwrap()
+            .set_limits(cost_limit, cost_limit, cost_limit);
+
+        // A bundle that does not fit reserves nothing.
+        let txs = vec![
+            transfer_tx.clone(),
+            transfer_tx.clone(),
+            transfer_tx.clone(),
+        ];
+        assert_eq!(
+            qos_service
+                .select_and_accumulate_bundle_costs(&bank, &txs)
+                .unwrap_err(),
+            BundleCostError::ExceedsLimit(TransactionError::WouldExceedMaxBlockCostLimit)
+        );
+        assert_eq!(0, bank.read_cost_tracker().unwrap().block_cost());
+        assert_eq!(
+            0,
+            bank.read_cost_tracker()
+                .unwrap()
+                .in_flight_transaction_count()
+        );
+
+        // A bundle that fits is reserved as a whole.
+        let txs = vec![transfer_tx.clone(), transfer_tx];
+        let costs = qos_service
+            .select_and_accumulate_bundle_costs(&bank, &txs)
+            .unwrap();
+        assert_eq!(costs.len(), 2);
+        assert!(costs.iter().all(Result::is_ok));
+        assert_eq!(cost_limit, bank.read_cost_tracker().unwrap().block_cost());
+
+        // Another bundle that fits an empty block may fit once the block has room again.
+        assert_eq!(
+            qos_service
+                .select_and_accumulate_bundle_costs(&bank, &txs[..1])
+                .unwrap_err(),
+            BundleCostError::WouldExceedLimit(TransactionError::WouldExceedMaxBlockCostLimit)
+        );
+        assert_eq!(cost_limit, bank.read_cost_tracker().unwrap().block_cost());
+
+        QosService::remove_or_update_costs(costs.iter(), None, &bank);
+        assert_eq!(0, bank.read_cost_tracker().unwrap().block_cost());
+    }
+
     #[test]
     fn test_update_and_remove_transaction_costs_committed() {
         solana_logger::setup();
@@ -1,5 +1,10 @@
 use {
+    super::{
+        consumer::BundleExecutionError, immutable_deserialized_packet::ImmutableDeserializedPacket,
+    },
     solana_clock::{Epoch, Slot},
+    solana_runtime_transaction::runtime_transaction::RuntimeTransaction,
+    solana_transaction::sanitized::SanitizedTransaction,
     std::fmt::Display,
 };
 
@@ -49,3 +54,19 @@ pub struct FinishedConsumeWork<Tx> {
     pub work: ConsumeWork<Tx>,
     pub retryable_indexes: Vec<usize>,
 }
+
+/// Message: [Scheduler -> BundleWorker]
+/// A bundle to be consumed atomically. The scheduler holds the locks on the
+/// accounts of every transaction in the bundle until it is finished.
+pub struct ConsumeBundle {
+    /// Packets of the bundle, kept to sanitize it again if it is retried.
+    pub packets: Vec<ImmutableDeserializedPacket>,
+    pub transactions: Vec<RuntimeTransaction<SanitizedTransaction>>,
+}
+
+/// Message: [BundleWorker -> Scheduler]
+/// Processed bundle.
+pub struct FinishedConsumeBundle {
+    pub work: ConsumeBundle,
+    pub result: Result<(), BundleExecutionError>,
+}
@@ -0,0 +1,364 @@
+//! An opt-in, in-memory log of what happened to individual transactions while
+//! this node was leader.
+//!
+//! Every stage of the banking pipeline that accepts, drops, retries or commits
+//! a transaction can record a [`TransactionOutcome`] for its signature. The
+//! log only retains outcomes for the most recent `max_slots` slots, and at most
+//! `max_outcomes` outcomes in total, so it can be queried (e.g. via admin RPC)
+//! to explain why a transaction did or did not land in one of this leader's
+//! recent blocks.
+
+use {
+    solana_clock::Slot,
+    solana_signature::Signature,
+    solana_transaction_error::TransactionError,
+    std::{
+        collections::{BTreeMap, HashMap},
+        fmt,
+        sync::Mutex,
+    },
+};
+
+/// Default number of recent slots for which outcomes are retained.
+pub const DEFAULT_TRANSACTION_OUTCOME_LOG_SLOTS: usize = 150;
+
+/// Default upper bound on the total number of outcomes retained. Once reached,
+/// the oldest slots are evicted early.
+pub const DEFAULT_TRANSACTION_OUTCOME_LOG_MAX_OUTCOMES: usize = 1_000_000;
+
+/// Upper bound on the number of outcomes kept per signature. Spammed
+/// duplicates of the same transaction would otherwise grow without bound
+/// within the retention window.
+const MAX_OUTCOMES_PER_SIGNATURE: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind", content = "error")]
+pub enum TransactionOutcome {
+    /// Passed sanitization and was buffered by the scheduler.
+    Received,
+    /// Dropped before execution because it was already processed.
+    Deduplicated,
+    /// Dropped before execution, e.g. expired blockhash, unpayable fee or
+    /// invalid account locks.
+    Dropped(TransactionError),
+    /// Dropped from the scheduler's buffer while it was full, in favor of
+    /// higher priority transactions.
+    DroppedOnCapacity,
+    /// Rejected because it would exceed the named block cost limit.
+    CostLimitRejected(TransactionError),
+    /// Not executed in this attempt but returned to the scheduler for a retry,
+    /// e.g. account lock contention or the slot ending before recording.
+    Retried(Option<TransactionError>),
+    /// Executed but could not be committed to the block.
+    NotCommitted(TransactionError),
+    /// Committed to the block, with the execution status.
+    Committed(Option<TransactionError>),
+}
+
+impl fmt::Display for TransactionOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Received => write!(f, "received"),
+            Self::Deduplicated => write!(f, "deduplicated"),
+            Self::Dropped(err) => write!(f, "dropped: {err}"),
+            Self::DroppedOnCapacity => write!(f, "dropped: buffer full"),
+            Self::CostLimitRejected(err) => write!(f, "cost limit rejected: {err}"),
+            Self::Retried(None) => write!(f, "retried"),
+            Self::Retried(Some(err)) => write!(f, "retried: {err}"),
+            Self::NotCommitted(err) => write!(f, "not committed: {err}"),
+            Self::Committed(None) => write!(f, "committed"),
+            Self::Committed(Some(err)) => write!(f, "committed with error: {err}"),
+        }
+    }
+}
+
+impl TransactionOutcome {
+    /// The outcome of a transaction dropped before execution because of `err`.
+    pub fn dropped(err: TransactionError) -> Self {
+        match err {
+            TransactionError::AlreadyProcessed => Self::Deduplicated,
+            err => Self::Dropped(err),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionOutcomeRecord {
+    /// The working bank slot when the outcome was recorded.
+    pub slot: Slot,
+    /// Wallclock time in milliseconds since the UNIX epoch.
+    pub timestamp: u64,
+    pub outcome: TransactionOutcome,
+}
+
+#[derive(Default)]
+struct TransactionOutcomeLogInner {
+    outcomes: HashMap<Signature, Vec<TransactionOutcomeRecord>>,
+    signatures_by_slot: BTreeMap<Slot, Vec<Signature>>,
+    num_outcomes: usize,
+}
+
+impl TransactionOutcomeLogInner {
+    /// Remove all outcomes of the oldest retained slot.
+    fn evict_oldest_slot(&mut self) {
+        let Some((evicted_slot, signatures)) = self.signatures_by_slot.pop_first() else {
+            return;
+        };
+        for signature in signatures {
+            let Some(records) = self.outcomes.get_mut(&signature) else {
+                continue;
+            };
+            let len = records.len();
+            records.retain(|record| record.slot > evicted_slot);
+            self.num_outcomes = self.num_outcomes.saturating_sub(len - records.len());
+            if records.is_empty() {
+                self.outcomes.remove(&signature);
+            }
+        }
+    }
+}
+
+pub struct TransactionOutcomeLog {
+    max_slots: usize,
+    max_outcomes: usize,
+    inner: Mutex<TransactionOutcomeLogInner>,
+}
+
+impl TransactionOutcomeLog {
+    pub fn new(max_slots: usize) -> Self {
+        Self::new_with_max_outcomes(max_slots, DEFAULT_TRANSACTION_OUTCOME_LOG_MAX_OUTCOMES)
+    }
+
+    pub fn new_with_max_outcomes(max_slots: usize, max_outcomes: usize) -> Self {
+        assert!(max_slots > 0, "max_slots must be non-zero");
+        assert!(max_outcomes > 0, "max_outcomes must be non-zero");
+        Self {
+            max_slots,
+            max_outcomes,
+            inner: Mutex::default(),
+        }
+    }
+
+    pub fn max_slots(&self) -> usize {
+        self.max_slots
+    }
+
+    pub fn record(&self, slot: Slot, signature: Signature, outcome: TransactionOutcome) {
+        self.record_all(slot, std::iter::once((signature, outcome)));
+    }
+
+    /// Record several outcomes observed in `slot` under a single lock
+    /// acquisition.
+    pub fn record_all(
+        &self,
+        slot: Slot,
+        outcomes: impl IntoIterator<Item = (Signature, TransactionOutcome)>,
+    ) {
+        let timestamp = solana_time_utils::timestamp();
+        let mut inner = self.inner.lock().unwrap();
+
+        // Outcomes for slots that have already been evicted are not retained.
+        if inner.signatures_by_slot.len() >= self.max_slots
+            && inner
+                .signatures_by_slot
+                .first_key_value()
+                .is_some_and(|(oldest, _)| slot < *oldest)
+        {
+            return;
+        }
+
+        for (signature, outcome) in outcomes {
+            // Make room by evicting older slots. Once only newer slots or
+            // this one remain, further outcomes are not retained.
+            while inner.num_outcomes >= self.max_outcomes {
+                match inner.signatures_by_slot.first_key_value() {
+                    Some((oldest, _)) if *oldest < slot => inner.evict_oldest_slot(),
+                    _ => break,
+                }
+            }
+            if inner.num_outcomes >= self.max_outcomes {
+                break;
+            }
+
+            let TransactionOutcomeLogInner {
+                outcomes: outcomes_by_signature,
+                signatures_by_slot,
+                num_outcomes,
+            } = &mut *inner;
+            let records = outcomes_by_signature.entry(signature).or_default();
+            if records.len() >= MAX_OUTCOMES_PER_SIGNATURE {
+                continue;
+            }
+            if records.last().is_none_or(|record| record.slot != slot) {
+                signatures_by_slot.entry(slot).or_default().push(signature);
+            }
+            records.push(TransactionOutcomeRecord {
+                slot,
+                timestamp,
+                outcome,
+            });
+            *num_outcomes += 1;
+        }
+
+        while inner.signatures_by_slot.len() > self.max_slots {
+            inner.evict_oldest_slot();
+        }
+    }
+
+    /// Return all retained outcomes for `signature`, oldest first.
+    pub fn get(&self, signature: &Signature) -> Vec<TransactionOutcomeRecord> {
+        self.inner
+            .lock()
+            .unwrap()
+            .outcomes
+            .get(signature)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Return the range of slots currently retained, if any.
+    pub fn slot_range(&self) -> Option<(Slot, Slot)> {
+        let inner = self.inner.lock().unwrap();
+        let (first, _) = inner.signatures_by_slot.first_key_value()?;
+        let (last, _) = inner.signatures_by_slot.last_key_value()?;
+        Some((*first, *last))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_get() {
+        let log = TransactionOutcomeLog::new(4);
+        let signature = Signature::new_unique();
+        assert!(log.get(&signature).is_empty());
+        assert_eq!(log.slot_range(), None);
+
+        log.record(1, signature, TransactionOutcome::Received);
+        log.record(
+            1,
+            signature,
+            TransactionOutcome::Retried(Some(TransactionError::AccountInUse)),
+        );
+        log.record(2, signature, TransactionOutcome::Committed(None));
+
+        let outcomes: Vec<_> = log
+            .get(&signature)
+            .into_iter()
+            .map(|record| (record.slot, record.outcome))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                (1, TransactionOutcome::Received),
+                (
+                    1,
+                    TransactionOutcome::Retried(Some(TransactionError::AccountInUse))
+                ),
+                (2, TransactionOutcome::Committed(None)),
+            ]
+        );
+        assert_eq!(log.slot_range(), Some((1, 2)));
+        assert!(log.get(&Signature::new_unique()).is_empty());
+    }
+
+    #[test]
+    fn test_eviction() {
+        let log = TransactionOutcomeLog::new(2);
+        let old = Signature::new_unique();
+        let spanning = Signature::new_unique();
+        let new = Signature::new_unique();
+
+        log.record_all(
+            1,
+            [
+                (old, TransactionOutcome::Received),
+                (spanning, TransactionOutcome::Received),
+            ],
+        );
+        log.record(
+            2,
+            spanning,
+            TransactionOutcome::CostLimitRejected(TransactionError::WouldExceedMaxBlockCostLimit),
+        );
+        log.record(3, new, TransactionOutcome::Received);
+
+        // Slot 1 was evicted, only the later outcome for `spanning` remains.
+        assert_eq!(log.slot_range(), Some((2, 3)));
+        assert!(log.get(&old).is_empty());
+        let spanning_outcomes = log.get(&spanning);
+        assert_eq!(spanning_outcomes.len(), 1);
+        assert_eq!(spanning_outcomes[0].slot, 2);
+        assert_eq!(log.get(&new).len(), 1);
+
+        // Outcomes for already evicted slots are ignored.
+        log.record(1, old, TransactionOutcome::Received);
+        assert!(log.get(&old).is_empty());
+    }
+
+    #[test]
+    fn test_max_outcomes_per_signature() {
+        let log = TransactionOutcomeLog::new(1);
+        let signature = Signature::new_unique();
+        log.record_all(
+            0,
+            (0..MAX_OUTCOMES_PER_SIGNATURE + 10).map(|_| {
+                (
+                    signature,
+                    TransactionOutcome::Dropped(TransactionError::AlreadyProcessed),
+                )
+            }),
+        );
+        assert_eq!(log.get(&signature).len(), MAX_OUTCOMES_PER_SIGNATURE);
+    }
+
+    #[test]
+    fn test_max_outcomes() {
+        let log = TransactionOutcomeLog::new_with_max_outcomes(4, 3);
+        let signatures: Vec<_> = (0..4).map(|_| Signature::new_unique()).collect();
+
+        log.record_all(
+            1,
+            signatures[..2]
+                .iter()
+                .map(|signature| (*signature, TransactionOutcome::Received)),
+        );
+        log.record(2, signatures[2], TransactionOutcome::Received);
+        // Slot 1 is evicted early to make room
+        log.record(3, signatures[3], TransactionOutcome::Received);
+        assert_eq!(log.slot_range(), Some((2, 3)));
+        assert!(log.get(&signatures[0]).is_empty());
+        assert!(log.get(&signatures[1]).is_empty());
+        assert_eq!(log.get(&signatures[2]).len(), 1);
+        assert_eq!(log.get(&signatures[3]).len(), 1);
+
+        // Slot 2 is evicted for the latest slot, after which nothing older is
+        // left to evict and further outcomes are not retained
+        log.record_all(
+            3,
+            signatures[..3]
+                .iter()
+                .map(|signature| (*signature, TransactionOutcome::DroppedOnCapacity)),
+        );
+        assert_eq!(log.slot_range(), Some((3, 3)));
+        assert_eq!(log.get(&signatures[0]).len(), 1);
+        assert_eq!(log.get(&signatures[1]).len(), 1);
+        assert!(log.get(&signatures[2]).is_empty());
+        assert_eq!(log.get(&signatures[3]).len(), 1);
+    }
+
+    #[test]
+    fn test_dropped_outcome() {
+        assert_eq!(
+            TransactionOutcome::dropped(TransactionError::AlreadyProcessed),
+            TransactionOutcome::Deduplicated
+        );
+        assert_eq!(
+            TransactionOutcome::dropped(TransactionError::BlockhashNotFound),
+            TransactionOutcome::Dropped(TransactionError::BlockhashNotFound)
+        );
+    }
+}
@@ -0,0 +1,510 @@
+//! Scheduling of bundles, groups of transactions that must be committed
+//! together, in order, or not at all.
+//!
+//! Bundles arrive on their own bounded channel next to the TPU packet path.
+//! Each bundle is signature verified and deserialized as a unit; a single
+//! invalid packet drops the whole bundle. While leader, the accounts of every
+//! transaction of a bundle are locked in the scheduler's
+//! [`ThreadAwareAccountLocks`] under the thread reserved for bundles, so no
+//! conflicting transaction is scheduled to the workers while the bundle
+//! worker executes it. Bundles are never forwarded.
+
+use {
+    super::{
+        scheduler_error::SchedulerError,
+        thread_aware_account_locks::{ThreadAwareAccountLocks, ThreadId, ThreadSet},
+    },
+    crate::banking_stage::{
+        decision_maker::BufferedPacketsDecision,
+        immutable_deserialized_packet::ImmutableDeserializedPacket,
+        scheduler_messages::{ConsumeBundle, FinishedConsumeBundle},
+    },
+    agave_banking_stage_ingress_types::{BankingBundle, BankingBundleReceiver, MAX_BUNDLE_LEN},
+    crossbeam_channel::{Receiver, Sender, TryRecvError},
+    solana_accounts_db::account_locks::validate_account_locks,
+    solana_perf::sigverify::ed25519_verify_cpu,
+    solana_runtime::bank::Bank,
+    solana_runtime_transaction::{
+        runtime_transaction::RuntimeTransaction, transaction_with_meta::TransactionWithMeta,
+    },
+    solana_transaction::sanitized::SanitizedTransaction,
+    std::{
+        collections::VecDeque,
+        time::{Duration, Instant},
+    },
+};
+
+/// Maximum number of bundles held while waiting to become leader. The oldest
+/// bundle is dropped to make room for a new one.
+pub const MAX_BUFFERED_BUNDLES: usize = 1024;
+/// Maximum number of bundles handed to the bundle worker at once. Their
+/// accounts stay locked until the worker has finished them.
+const MAX_IN_FLIGHT_BUNDLES: usize = 4;
+/// Maximum number of bundles received, or attempted to be scheduled, per
+/// iteration of the scheduler, bounding the time spent on bundles.
+const MAX_BUNDLES_PER_ITERATION: usize = 16;
+
+pub(crate) struct BundleScheduler {
+    /// `None` once every bundle sender has been dropped.
+    bundle_receiver: Option<BankingBundleReceiver>,
+    consume_bundle_sender: Sender<ConsumeBundle>,
+    finished_bundle_receiver: Receiver<FinishedConsumeBundle>,
+    buffered_bundles: VecDeque<Vec<ImmutableDeserializedPacket>>,
+    num_in_flight: usize,
+    metrics: BundleSchedulerMetrics,
+}
+
+impl BundleScheduler {
+    pub fn new(
+        bundle_receiver: BankingBundleReceiver,
+        consume_bundle_sender: Sender<ConsumeBundle>,
+        finished_bundle_receiver: Receiver<FinishedConsumeBundle>,
+    ) -> Self {
+        Self {
+            bundle_receiver: Some(bundle_receiver),
+            consume_bundle_sender,
+            finished_bundle_receiver,
+            buffered_bundles: VecDeque::new(),
+            num_in_flight: 0,
+            metrics: BundleSchedulerMetrics::new(),
+        }
+    }
+
+    /// Receive and buffer new bundles without blocking. Bundles received while
+    /// the decision is to forward are dropped right away.
+    pub fn receive_and_buffer_bundles(&mut self, decision: &BufferedPacketsDecision) {
+        let Some(bundle_receiver) = self.bundle_receiver.clone() else {
+            return;
+        };
+        for _ in 0..MAX_BUNDLES_PER_ITERATION {
+            match bundle_receiver.try_recv() {
+                Ok(bundle) => {
+                    self.metrics.counts.received_bundles += 1;
+                    if matches!(decision, BufferedPacketsDecision::Forward) {
+                        self.metrics.counts.dropped_not_leader_bundles += 1;
+                    } else {
+                        self.buffer_bundle(bundle);
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.bundle_receiver = None;
+                    break;
+                }
+            }
+        }
+    }
+
+    fn buffer_bundle(&mut self, bundle: BankingBundle) {
+        let Some(bundle) = deserialize_bundle(bundle) else {
+            self.metrics.counts.dropped_invalid_bundles += 1;
+            return;
+        };
+
+        if self.buffered_bundles.len() >= MAX_BUFFERED_BUNDLES {
+            self.buffered_bundles.pop_front();
+            self.metrics.counts.dropped_buffer_full_bundles += 1;
+        }
+        self.buffered_bundles.push_back(bundle);
+    }
+
+    /// Drop all buffered bundles, since bundles are only meaningful to the
+    /// leader they were sent to. In-flight bundles are not affected.
+    pub fn clear(&mut self) {
+        self.metrics.counts.dropped_not_leader_bundles += self.buffered_bundles.len() as u64;
+        self.buffered_bundles.clear();
+    }
+
+    /// Lock the accounts of buffered bundles under `thread_id` and send them
+    /// to the bundle worker. Bundles conflicting with transactions in flight on
+    /// other threads stay buffered, keeping their place.
+    pub fn schedule(
+        &mut self,
+        bank: &Bank,
+        account_locks: &mut ThreadAwareAccountLocks,
+        thread_id: ThreadId,
+    ) -> Result<(), SchedulerError> {
+        let mut unschedulable_bundles = Vec::new();
+        for _ in 0..MAX_BUNDLES_PER_ITERATION {
+            if self.num_in_flight >= MAX_IN_FLIGHT_BUNDLES {
+                break;
+            }
+            let Some(packets) = self.buffered_bundles.pop_front() else {
+                break;
+            };
+
+            let Some(transactions) = sanitize_bundle(bank, &packets) else {
+                self.metrics.counts.dropped_invalid_bundles += 1;
+                continue;
+            };
+            if !try_lock_bundle(account_locks, thread_id, &transactions) {
+                self.metrics.counts.unschedulable_bundles += 1;
+                unschedulable_bundles.push(packets);
+                continue;
+            }
+
+            self.consume_bundle_sender
+                .send(ConsumeBundle {
+                    packets,
+                    transactions,
+                })
+                .map_err(|_| SchedulerError::DisconnectedSendChannel("consume bundle sender"))?;
+            self.num_in_flight += 1;
+            self.metrics.counts.scheduled_bundles += 1;
+        }
+
+        for packets in unschedulable_bundles.into_iter().rev() {
+            self.buffered_bundles.push_front(packets);
+        }
+        Ok(())
+    }
+
+    /// Receive finished bundles without blocking and release the locks they
+    /// held under `thread_id`. Retryable bundles are buffered again, ahead of
+    /// the ones not yet attempted.
+    pub fn receive_completed(
+        &mut self,
+        account_locks: &mut ThreadAwareAccountLocks,
+        thread_id: ThreadId,
+    ) -> Result<(), SchedulerError> {
+        loop {
+            match self.finished_bundle_receiver.try_recv() {
+                Ok(FinishedConsumeBundle {
+                    work:
+                        ConsumeBundle {
+                            packets,
+                            transactions,
+                        },
+                    result,
+                }) => {
+                    self.num_in_flight -= 1;
+                    unlock_bundle(account_locks, thread_id, &transactions);
+                    match result {
+                        Ok(()) => {
+                            self.metrics.counts.committed_bundles += 1;
+                            self.metrics.counts.committed_transactions += transactions.len() as u64;
+                        }
+                        Err(err) if err.is_retryable() => {
+                            self.metrics.counts.retried_bundles += 1;
+                            self.buffered_bundles.push_front(packets);
+                        }
+                        Err(err) => {
+                            debug!("dropping bundle: {err}");
+                            self.metrics.counts.failed_bundles += 1;
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => return Ok(()),
+                Err(TryRecvError::Disconnected) => {
+                    return Err(SchedulerError::DisconnectedRecvChannel(
+                        "finished consume bundle",
+                    ))
+                }
+            }
+        }
+    }
+
+    pub fn maybe_report_metrics(&mut self) {
+        self.metrics.maybe_report_and_reset();
+    }
+}
+
+/// Verify signatures and deserialize every packet of `bundle`. Returns `None`
+/// if the bundle is empty, too long, or contains any invalid packet.
+fn deserialize_bundle(mut bundle: BankingBundle) -> Option<Vec<ImmutableDeserializedPacket>> {
+    let packet_count = bundle.len();
+    if packet_count == 0 || packet_count > MAX_BUNDLE_LEN {
+        return None;
+    }
+
+    ed25519_verify_cpu(std::slice::from_mut(&mut bundle), false, packet_count);
+    bundle
+        .iter()
+        .map(|packet| {
+            if packet.meta().discard() {
+                return None;
+            }
+            ImmutableDeserializedPacket::new(packet).ok()
+        })
+        .collect()
+}
+
+/// Sanitize every transaction of `bundle` against `bank`. Returns `None` if
+/// any transaction fails sanitization or has invalid account locks.
+fn sanitize_bundle(
+    bank: &Bank,
+    bundle: &[ImmutableDeserializedPacket],
+) -> Option<Vec<RuntimeTransaction<SanitizedTransaction>>> {
+    bundle
+        .iter()
+        .map(|packet| {
+            let (transaction, _deactivation_slot) = packet.build_sanitized_transaction(
+                bank.vote_only_bank(),
+                bank,
+                bank.get_reserved_account_keys(),
+            )?;
+            validate_account_locks(
+                transaction.message().account_keys(),
+                bank.get_transaction_account_lock_limit(),
+            )
+            .ok()?;
+            Some(transaction)
+        })
+        .collect()
+}
+
+/// Lock the accounts of every transaction in `transactions` under
+/// `thread_id`. Either all locks are taken, or none.
+fn try_lock_bundle(
+    account_locks: &mut ThreadAwareAccountLocks,
+    thread_id: ThreadId,
+    transactions: &[impl TransactionWithMeta],
+) -> bool {
+    for (index, transaction) in transactions.iter().enumerate() {
+        let account_keys = transaction.account_keys();
+        let write_account_locks = account_keys
+            .iter()
+            .enumerate()
+            .filter_map(|(index, key)| transaction.is_writable(index).then_some(key));
+        let read_account_locks = account_keys
+            .iter()
+            .enumerate()
+            .filter_map(|(index, key)| (!transaction.is_writable(index)).then_some(key));
+        if account_locks
+            .try_lock_accounts(
+                write_account_locks,
+                read_account_locks,
+                ThreadSet::only(thread_id),
+                |_| thread_id,
+            )
+            .is_err()
+        {
+            unlock_bundle(account_locks, thread_id, &transactions[..index]);
+            return false;
+        }
+    }
+    true
+}
+
+fn unlock_bundle(
+    account_locks: &mut ThreadAwareAccountLocks,
+    thread_id: ThreadId,
+    transactions: &[impl TransactionWithMeta],
+) {
+    for transaction in transactions {
+        let account_keys = transaction.account_keys();
+        let write_account_locks = account_keys
+            .iter()
+            .enumerate()
+            .filter_map(|(index, key)| transaction.is_writable(index).then_some(key));
+        let read_account_locks = account_keys
+            .iter()
+            .enumerate()
+            .filter_map(|(index, key)| (!transaction.is_writable(index)).then_some(key));
+        account_locks.unlock_accounts(write_account_locks, read_account_locks, thread_id);
+    }
+}
+
+#[derive(Default)]
+struct BundleSchedulerCounts {
+    received_bundles: u64,
+    scheduled_bundles: u64,
+    unschedulable_bundles: u64,
+    committed_bundles: u64,
+    committed_transactions: u64,
+    retried_bundles: u64,
+    failed_bundles: u64,
+    dropped_invalid_bundles: u64,
+    dropped_buffer_full_bundles: u64,
+    dropped_not_leader_bundles: u64,
+}
+
+struct BundleSchedulerMetrics {
+    last_report: Instant,
+    counts: BundleSchedulerCounts,
+}
+
+impl BundleSchedulerMetrics {
+    fn new() -> Self {
+        Self {
+            last_report: Instant::now(),
+            counts: BundleSchedulerCounts::default(),
+        }
+    }
+
+    fn maybe_report_and_reset(&mut self) {
+        const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+        if self.last_report.elapsed() < REPORT_INTERVAL {
+            return;
+        }
+        self.last_report = Instant::now();
+        let counts = std::mem::take(&mut self.counts);
+
+        datapoint_info!(
+            "banking_stage_bundle_scheduler",
+            ("received_bundles", counts.received_bundles, i64),
+            ("scheduled_bundles", counts.scheduled_bundles, i64),
+            ("unschedulable_bundles", counts.unschedulable_bundles, i64),
+            ("committed_bundles", counts.committed_bundles, i64),
+            ("committed_transactions", counts.committed_transactions, i64),
+            ("retried_bundles", counts.retried_bundles, i64),
+            ("failed_bundles", counts.failed_bundles, i64),
+            (
+                "dropped_invalid_bundles",
+                counts.dropped_invalid_bundles,
+                i64
+            ),
+            (
+                "dropped_buffer_full_bundles",
+                counts.dropped_buffer_full_bundles,
+                i64
+            ),
+            (
+                "dropped_not_leader_bundles",
+                counts.dropped_not_leader_bundles,
+                i64
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, crate::banking_stage::consumer::BundleExecutionError,
+        crossbeam_channel::unbounded, solana_hash::Hash, solana_keypair::Keypair,
+        solana_ledger::genesis_utils::create_genesis_config, solana_message::Message,
+        solana_perf::packet::to_packet_batches, solana_pubkey::Pubkey, solana_signer::Signer,
+        solana_system_transaction as system_transaction, solana_transaction::Transaction,
+    };
+
+    fn bundle_of(transactions: &[Transaction]) -> BankingBundle {
+        to_packet_batches(transactions, transactions.len())
+            .pop()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_deserialize_bundle() {
+        let keypair = Keypair::new();
+        let transactions: Vec<_> = (1..=3)
+            .map(|lamports| {
+                system_transaction::transfer(
+                    &keypair,
+                    &Pubkey::new_unique(),
+                    lamports,
+                    Hash::default(),
+                )
+            })
+            .collect();
+
+        let bundle = deserialize_bundle(bundle_of(&transactions)).unwrap();
+        assert_eq!(bundle.len(), 3);
+        for (packet, transaction) in bundle.iter().zip(&transactions) {
+            assert_eq!(
+                packet.message_hash(),
+                &Message::hash_raw_message(&transaction.message_data())
+            );
+        }
+
+        // One bad signature invalidates the whole bundle.
+        let mut transactions = transactions;
+        transactions[1].signatures[0] = transactions[0].signatures[0];
+        assert!(deserialize_bundle(bundle_of(&transactions)).is_none());
+
+        let transactions = vec![transactions[0].clone(); MAX_BUNDLE_LEN + 1];
+        assert!(deserialize_bundle(bundle_of(&transactions)).is_none());
+    }
+
+    #[test]
+    fn test_schedule_bundles() {
+        const NUM_WORKERS: usize = 2;
+        const BUNDLE_THREAD_ID: ThreadId = NUM_WORKERS;
+
+        let genesis_config = create_genesis_config(1_000_000).genesis_config;
+        let bank = Bank::new_for_tests(&genesis_config);
+        let (bundle_sender, bundle_receiver) = unbounded();
+        let (consume_bundle_sender, consume_bundle_receiver) = unbounded();
+        let (finished_bundle_sender, finished_bundle_receiver) = unbounded();
+        let mut bundle_scheduler = BundleScheduler::new(
+            bundle_receiver,
+            consume_bundle_sender,
+            finished_bundle_receiver,
+        );
+        let mut account_locks = ThreadAwareAccountLocks::new(NUM_WORKERS + 1);
+
+        let payer = Keypair::new();
+        let transactions: Vec<_> = (1..=2)
+            .map(|lamports| {
+                system_transaction::transfer(
+                    &payer,
+                    &Pubkey::new_unique(),
+                    lamports,
+                    genesis_config.hash(),
+                )
+            })
+            .collect();
+        bundle_sender.send(bundle_of(&transactions)).unwrap();
+        bundle_scheduler.receive_and_buffer_bundles(&BufferedPacketsDecision::Hold);
+        assert_eq!(bundle_scheduler.buffered_bundles.len(), 1);
+
+        // The payer is write locked by a worker, so the bundle must wait.
+        let payer_pubkey = payer.pubkey();
+        account_locks
+            .try_lock_accounts(
+                [&payer_pubkey].into_iter(),
+                [].into_iter(),
+                ThreadSet::only(0),
+                |_| 0,
+            )
+            .unwrap();
+        bundle_scheduler
+            .schedule(&bank, &mut account_locks, BUNDLE_THREAD_ID)
+            .unwrap();
+        assert!(consume_bundle_receiver.try_recv().is_err());
+        assert_eq!(bundle_scheduler.buffered_bundles.len(), 1);
+        account_locks.unlock_accounts([&payer_pubkey].into_iter(), [].into_iter(), 0);
+
+        bundle_scheduler
+            .schedule(&bank, &mut account_locks, BUNDLE_THREAD_ID)
+            .unwrap();
+        let work = consume_bundle_receiver.try_recv().unwrap();
+        assert_eq!(work.transactions.len(), 2);
+        assert!(bundle_scheduler.buffered_bundles.is_empty());
+
+        // Transactions conflicting with the in-flight bundle cannot be
+        // scheduled to any worker.
+        assert!(account_locks
+            .try_lock_accounts(
+                [&payer_pubkey].into_iter(),
+                [].into_iter(),
+                ThreadSet::any(NUM_WORKERS),
+                |thread_set| thread_set.contained_threads_iter().next().unwrap(),
+            )
+            .is_err());
+
+        // A retryable bundle is buffered again and its locks are released.
+        finished_bundle_sender
+            .send(FinishedConsumeBundle {
+                work,
+                result: Err(BundleExecutionError::BankUnavailable),
+            })
+            .unwrap();
+        bundle_scheduler
+            .receive_completed(&mut account_locks, BUNDLE_THREAD_ID)
+            .unwrap();
+        assert_eq!(bundle_scheduler.buffered_bundles.len(), 1);
+        assert_eq!(
+            account_locks.try_lock_accounts(
+                [&payer_pubkey].into_iter(),
+                [].into_iter(),
+                ThreadSet::any(NUM_WORKERS),
+                |_| 1,
+            ),
+            Ok(1)
+        );
+
+        bundle_scheduler.clear();
+        assert!(bundle_scheduler.buffered_bundles.is_empty());
+    }
+}
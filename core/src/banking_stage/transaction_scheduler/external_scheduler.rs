@@ -0,0 +1,654 @@
+#[cfg(feature = "dev-context-only-utils")]
+use qualifier_attr::qualifiers;
+use {
+    super::{
+        scheduler::{PreLockFilterAction, Scheduler, SchedulingSummary},
+        scheduler_common::{Batches, SchedulingCommon},
+        scheduler_error::SchedulerError,
+        thread_aware_account_locks::{ThreadSet, TryLockError},
+        transaction_priority_id::TransactionPriorityId,
+        transaction_state::TransactionState,
+        transaction_state_container::StateContainer,
+    },
+    crate::banking_stage::{
+        consumer::TARGET_NUM_TRANSACTIONS_PER_BATCH, read_write_account_set::ReadWriteAccountSet,
+    },
+    agave_scheduler_bindings::{Assignment, SharedRegion, MAX_TRANSACTIONS_PER_REQUEST},
+    solana_measure::measure_us,
+    solana_runtime_transaction::transaction_with_meta::TransactionWithMeta,
+    std::{
+        marker::PhantomData,
+        num::Saturating,
+        thread,
+        time::{Duration, Instant},
+    },
+};
+
+/// Number of times to poll for a response before yielding the thread between
+/// polls.
+const RESPONSE_SPINS_BEFORE_YIELD: u32 = 64;
+
+#[cfg_attr(feature = "dev-context-only-utils", qualifiers(pub))]
+pub(crate) struct ExternalSchedulerConfig {
+    /// How long to wait for the external process to answer a request before
+    /// scheduling with the built-in scheduler instead.
+    pub response_timeout: Duration,
+    pub max_transactions_per_request: usize,
+    pub target_transactions_per_batch: usize,
+}
+
+impl Default for ExternalSchedulerConfig {
+    fn default() -> Self {
+        Self {
+            response_timeout: Duration::from_millis(5),
+            max_transactions_per_request: MAX_TRANSACTIONS_PER_REQUEST,
+            target_transactions_per_batch: TARGET_NUM_TRANSACTIONS_PER_BATCH,
+        }
+    }
+}
+
+/// Scheduler that delegates ordering and thread assignment to an external
+/// process, see `agave_scheduler_bindings` for the shared memory protocol.
+///
+/// Assignments are only hints: account locks are still taken here, so
+/// conflicting or invalid assignments are never sent to the workers.
+///
+/// While the external process is absent or late, scheduling falls back to the
+/// built-in scheduler `S`, which owns the account locks and in-flight state
+/// shared by both.
+pub struct ExternalScheduler<Tx: TransactionWithMeta, S: Scheduler<Tx>> {
+    fallback: S,
+    region: SharedRegion,
+    working_account_set: ReadWriteAccountSet,
+    offered: Vec<TransactionPriorityId>,
+    assigned: Vec<bool>,
+    assignments: Vec<Assignment>,
+    /// Sequence of a request the external process did not answer in time.
+    /// No new request is written until it is answered.
+    unanswered_sequence: Option<u64>,
+    config: ExternalSchedulerConfig,
+    _phantom: PhantomData<Tx>,
+}
+
+impl<Tx: TransactionWithMeta, S: Scheduler<Tx>> ExternalScheduler<Tx, S> {
+    #[cfg_attr(feature = "dev-context-only-utils", qualifiers(pub))]
+    pub(crate) fn new(fallback: S, region: SharedRegion, config: ExternalSchedulerConfig) -> Self {
+        let max_transactions_per_request = config
+            .max_transactions_per_request
+            .min(MAX_TRANSACTIONS_PER_REQUEST);
+        Self {
+            fallback,
+            region,
+            working_account_set: ReadWriteAccountSet::default(),
+            offered: Vec::with_capacity(max_transactions_per_request),
+            assigned: Vec::with_capacity(max_transactions_per_request),
+            assignments: Vec::with_capacity(max_transactions_per_request),
+            unanswered_sequence: None,
+            config: ExternalSchedulerConfig {
+                max_transactions_per_request,
+                ..config
+            },
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Whether the external process is answering requests. A request it
+    /// missed counts as answered once its response shows up, e.g. after the
+    /// process (re)starts.
+    fn is_external_scheduler_available(&mut self) -> bool {
+        match self.unanswered_sequence {
+            Some(sequence) if !self.region.is_response_ready(sequence) => false,
+            Some(_) => {
+                info!("external scheduler answered, no longer using the built-in scheduler");
+                self.unanswered_sequence = None;
+                true
+            }
+            None => true,
+        }
+    }
+
+    /// Pop up to `max_transactions_per_request` transactions from the queue
+    /// into `self.offered`, removing any rejected by `pre_graph_filter`.
+    /// Returns the number filtered out and the time spent filtering.
+    fn pop_offered<C: StateContainer<Tx>>(
+        &mut self,
+        container: &mut C,
+        pre_graph_filter: &impl Fn(&[&Tx], &mut [bool]),
+    ) -> (usize, u64) {
+        const MAX_FILTER_CHUNK_SIZE: usize = 128;
+        let mut num_filtered_out = Saturating::<usize>(0);
+        let mut total_filter_time_us = Saturating::<u64>(0);
+        let mut filter_array = [true; MAX_FILTER_CHUNK_SIZE];
+        let mut ids = Vec::with_capacity(MAX_FILTER_CHUNK_SIZE);
+
+        while self.offered.len() < self.config.max_transactions_per_request {
+            let chunk_size = (self.config.max_transactions_per_request - self.offered.len())
+                .min(MAX_FILTER_CHUNK_SIZE);
+            ids.clear();
+            ids.extend((0..chunk_size).map_while(|_| container.pop()));
+            if ids.is_empty() {
+                break;
+            }
+
+            let txs: Vec<_> = ids
+                .iter()
+                .map(|id| container.get_transaction(id.id).unwrap())
+                .collect();
+            filter_array[..ids.len()].fill(true);
+            let (_, filter_us) =
+                measure_us!(pre_graph_filter(&txs, &mut filter_array[..ids.len()]));
+            total_filter_time_us += filter_us;
+
+            for (id, filter_result) in ids.iter().zip(&filter_array) {
+                if *filter_result {
+                    self.offered.push(*id);
+                } else {
+                    num_filtered_out += 1;
+                    container.remove_by_id(id.id);
+                }
+            }
+
+            if ids.len() != chunk_size {
+                break;
+            }
+        }
+
+        (num_filtered_out.0, total_filter_time_us.0)
+    }
+
+    /// Write the offered transactions into the shared region. Transactions
+    /// that cannot be described in the region are removed from the container.
+    /// Returns the number of transactions removed.
+    fn write_request<C: StateContainer<Tx>>(&mut self, container: &mut C) -> usize {
+        let mut num_dropped = 0;
+        let request = self.region.request_mut();
+        let mut num_written = 0;
+        self.offered.retain(|id| {
+            let transaction_state = container.get_mut_transaction_state(id.id).unwrap();
+            let transaction = transaction_state.transaction();
+            let entry = &mut request.transactions[num_written];
+            if !entry.set_accounts(
+                transaction
+                    .account_keys()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, key)| (key.to_bytes(), transaction.is_writable(index))),
+            ) {
+                num_dropped += 1;
+                container.remove_by_id(id.id);
+                return false;
+            }
+            entry.priority = id.priority;
+            entry.cost = transaction_state.cost();
+            num_written += 1;
+            true
+        });
+        request.set_num_transactions(num_written);
+
+        let in_flight_tracker = &self.fallback.scheduling_common_mut().in_flight_tracker;
+        for (thread_id, (cus, num_transactions)) in in_flight_tracker
+            .cus_in_flight_per_thread()
+            .iter()
+            .zip(in_flight_tracker.num_in_flight_per_thread())
+            .enumerate()
+        {
+            request.in_flight_cus[thread_id] = *cus;
+            request.in_flight_transactions[thread_id] = *num_transactions as u64;
+        }
+
+        num_dropped
+    }
+
+    fn wait_for_response(&self, sequence: u64) -> bool {
+        let deadline = Instant::now() + self.config.response_timeout;
+        let mut num_spins = 0;
+        while !self.region.is_response_ready(sequence) {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            if num_spins < RESPONSE_SPINS_BEFORE_YIELD {
+                num_spins += 1;
+                std::hint::spin_loop();
+            } else {
+                thread::yield_now();
+            }
+        }
+        true
+    }
+}
+
+impl<Tx: TransactionWithMeta, S: Scheduler<Tx>> Scheduler<Tx> for ExternalScheduler<Tx, S> {
+    fn schedule<C: StateContainer<Tx>>(
+        &mut self,
+        container: &mut C,
+        pre_graph_filter: impl Fn(&[&Tx], &mut [bool]),
+        pre_lock_filter: impl Fn(&TransactionState<Tx>) -> PreLockFilterAction,
+    ) -> Result<SchedulingSummary, SchedulerError> {
+        if !self.is_external_scheduler_available() {
+            return self
+                .fallback
+                .schedule(container, pre_graph_filter, pre_lock_filter);
+        }
+
+        let starting_queue_size = container.queue_size();
+        let starting_buffer_size = container.buffer_size();
+
+        let (num_filtered_out, filter_time_us) = self.pop_offered(container, &pre_graph_filter);
+        let num_filtered_out = num_filtered_out + self.write_request(container);
+        if self.offered.is_empty() {
+            return Ok(SchedulingSummary {
+                starting_queue_size,
+                starting_buffer_size,
+                num_filtered_out,
+                filter_time_us,
+                ..SchedulingSummary::default()
+            });
+        }
+
+        let sequence = self.region.publish_request();
+        if !self.wait_for_response(sequence) {
+            warn!("external scheduler did not answer in time, using the built-in scheduler");
+            self.unanswered_sequence = Some(sequence);
+            container.push_ids_into_queue(self.offered.drain(..));
+            let summary = self
+                .fallback
+                .schedule(container, pre_graph_filter, pre_lock_filter)?;
+            return Ok(SchedulingSummary {
+                starting_queue_size,
+                starting_buffer_size,
+                num_filtered_out: num_filtered_out + summary.num_filtered_out,
+                filter_time_us: filter_time_us + summary.filter_time_us,
+                ..summary
+            });
+        }
+
+        let num_threads = self
+            .fallback
+            .scheduling_common_mut()
+            .consume_work_senders
+            .len();
+        let target_transactions_per_batch = self.config.target_transactions_per_batch;
+        let mut batches = Batches::new(num_threads, target_transactions_per_batch);
+        let mut num_scheduled = Saturating::<usize>(0);
+        let mut num_sent: usize = 0;
+        let mut num_unschedulable_conflicts: usize = 0;
+        let mut num_unschedulable_threads: usize = 0;
+
+        self.assigned.clear();
+        self.assigned.resize(self.offered.len(), false);
+        self.region.read_assignments(&mut self.assignments);
+        for &Assignment { index, worker } in &self.assignments {
+            let (index, thread_id) = (index as usize, worker as usize);
+            if index >= self.offered.len() || self.assigned[index] || thread_id >= num_threads {
+                continue;
+            }
+            let id = self.offered[index];
+            let Some(transaction_state) = container.get_mut_transaction_state(id.id) else {
+                panic!("transaction state must exist")
+            };
+            match pre_lock_filter(transaction_state) {
+                PreLockFilterAction::AttemptToSchedule => {}
+            }
+
+            // Transactions within a batch must not conflict with each other.
+            if !self
+                .working_account_set
+                .check_locks(transaction_state.transaction())
+            {
+                self.working_account_set.clear();
+                num_sent += self
+                    .fallback
+                    .scheduling_common_mut()
+                    .send_batches(&mut batches, target_transactions_per_batch)?;
+            }
+
+            let transaction = transaction_state.transaction();
+            let account_keys = transaction.account_keys();
+            let write_account_locks = account_keys
+                .iter()
+                .enumerate()
+                .filter_map(|(index, key)| transaction.is_writable(index).then_some(key));
+            let read_account_locks = account_keys
+                .iter()
+                .enumerate()
+                .filter_map(|(index, key)| (!transaction.is_writable(index)).then_some(key));
+            match self
+                .fallback
+                .scheduling_common_mut()
+                .account_locks
+                .try_lock_accounts(
+                    write_account_locks,
+                    read_account_locks,
+                    ThreadSet::only(thread_id),
+                    |_| thread_id,
+                ) {
+                Ok(_) => {}
+                Err(TryLockError::MultipleConflicts) => {
+                    num_unschedulable_conflicts += 1;
+                    continue;
+                }
+                Err(TryLockError::ThreadNotAllowed) => {
+                    num_unschedulable_threads += 1;
+                    continue;
+                }
+            }
+
+            let (transaction, max_age) = transaction_state.take_transaction_for_scheduling();
+            let cost = transaction_state.cost();
+            assert!(
+                self.working_account_set.take_locks(&transaction),
+                "locks must be available"
+            );
+            self.assigned[index] = true;
+            num_scheduled += 1;
+            batches.add_transaction_to_batch(thread_id, id.id, transaction, max_age, cost);
+
+            if batches.transactions()[thread_id].len() >= target_transactions_per_batch {
+                self.working_account_set.clear();
+                num_sent += self
+                    .fallback
+                    .scheduling_common_mut()
+                    .send_batches(&mut batches, target_transactions_per_batch)?;
+            }
+        }
+
+        self.working_account_set.clear();
+        // Use zero here to avoid allocating since we are done with `Batches`.
+        num_sent += self
+            .fallback
+            .scheduling_common_mut()
+            .send_batches(&mut batches, 0)?;
+        let Saturating(num_scheduled) = num_scheduled;
+        assert_eq!(
+            num_scheduled, num_sent,
+            "number of scheduled and sent transactions must match"
+        );
+
+        // Anything not scheduled stays buffered for a later request.
+        container.push_ids_into_queue(
+            self.offered
+                .drain(..)
+                .zip(&self.assigned)
+                .filter_map(|(id, assigned)| (!assigned).then_some(id)),
+        );
+
+        Ok(SchedulingSummary {
+            starting_queue_size,
+            starting_buffer_size,
+            num_scheduled,
+            num_unschedulable_conflicts,
+            num_unschedulable_threads,
+            num_filtered_out,
+            filter_time_us,
+        })
+    }
+
+    fn scheduling_common_mut(&mut self) -> &mut SchedulingCommon<Tx> {
+        self.fallback.scheduling_common_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::banking_stage::{
+            scheduler_messages::{ConsumeWork, FinishedConsumeWork, MaxAge, TransactionId},
+            transaction_scheduler::{
+                greedy_scheduler::{GreedyScheduler, GreedySchedulerConfig},
+                transaction_state_container::TransactionStateContainer,
+            },
+        },
+        agave_scheduler_bindings::Request,
+        crossbeam_channel::{unbounded, Receiver, Sender},
+        solana_hash::Hash,
+        solana_keypair::Keypair,
+        solana_pubkey::Pubkey,
+        solana_runtime_transaction::runtime_transaction::RuntimeTransaction,
+        solana_system_transaction as system_transaction,
+        solana_transaction::sanitized::SanitizedTransaction,
+        std::{path::Path, thread::JoinHandle},
+        tempfile::TempDir,
+    };
+
+    type Tx = RuntimeTransaction<SanitizedTransaction>;
+
+    #[allow(clippy::type_complexity)]
+    fn create_test_frame(
+        num_threads: usize,
+        path: &Path,
+        response_timeout: Duration,
+    ) -> (
+        ExternalScheduler<Tx, GreedyScheduler<Tx>>,
+        Vec<Receiver<ConsumeWork<Tx>>>,
+        Sender<FinishedConsumeWork<Tx>>,
+    ) {
+        let (consume_work_senders, consume_work_receivers) =
+            (0..num_threads).map(|_| unbounded()).unzip();
+        let (finished_consume_work_sender, finished_consume_work_receiver) = unbounded();
+        let scheduler = ExternalScheduler::new(
+            GreedyScheduler::new(
+                consume_work_senders,
+                finished_consume_work_receiver,
+                GreedySchedulerConfig::default(),
+            ),
+            SharedRegion::create(path, num_threads).unwrap(),
+            ExternalSchedulerConfig {
+                response_timeout,
+                ..ExternalSchedulerConfig::default()
+            },
+        );
+        (
+            scheduler,
+            consume_work_receivers,
+            finished_consume_work_sender,
+        )
+    }
+
+    /// Answer a single request published after `last_sequence` from the
+    /// region at `path` with `policy`.
+    fn spawn_external_scheduler(
+        path: &Path,
+        last_sequence: u64,
+        policy: impl FnOnce(&Request) -> Vec<Assignment> + Send + 'static,
+    ) -> JoinHandle<()> {
+        let mut region = SharedRegion::open(path).unwrap();
+        std::thread::spawn(move || {
+            let sequence = loop {
+                if let Some(sequence) = region.poll_request(last_sequence) {
+                    break sequence;
+                }
+                std::hint::spin_loop();
+            };
+            let assignments = policy(region.request());
+            region.response_mut().set_assignments(&assignments);
+            region.publish_response(sequence);
+        })
+    }
+
+    fn create_container(transfers: &[(&Keypair, Pubkey, u64)]) -> TransactionStateContainer<Tx> {
+        let mut container = TransactionStateContainer::with_capacity(1024);
+        for (from_keypair, to_pubkey, priority) in transfers {
+            let transaction = RuntimeTransaction::from_transaction_for_tests(
+                system_transaction::transfer(from_keypair, to_pubkey, 1, Hash::default()),
+            );
+            container.insert_new_transaction(transaction, MaxAge::MAX, *priority, 5000);
+        }
+        container
+    }
+
+    fn collect_ids(receiver: &Receiver<ConsumeWork<Tx>>) -> Vec<Vec<TransactionId>> {
+        receiver.try_iter().map(|work| work.ids).collect()
+    }
+
+    fn test_pre_graph_filter(_txs: &[&Tx], results: &mut [bool]) {
+        results.fill(true);
+    }
+
+    fn test_pre_lock_filter(_tx: &TransactionState<Tx>) -> PreLockFilterAction {
+        PreLockFilterAction::AttemptToSchedule
+    }
+
+    #[test]
+    fn test_schedule_follows_assignments() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("scheduler");
+        let (mut scheduler, work_receivers, _finished_work_sender) =
+            create_test_frame(2, &path, Duration::from_secs(10));
+        let mut container = create_container(&[
+            (&Keypair::new(), Pubkey::new_unique(), 1),
+            (&Keypair::new(), Pubkey::new_unique(), 2),
+            (&Keypair::new(), Pubkey::new_unique(), 3),
+        ]);
+
+        // Highest priority first, leave the lowest priority transaction
+        // unassigned.
+        let external = spawn_external_scheduler(&path, 0, |request| {
+            assert_eq!(request.transactions().len(), 3);
+            assert_eq!(request.transactions()[0].priority, 3);
+            assert_eq!(request.transactions()[0].cost, 5000);
+            assert_eq!(request.transactions()[0].account_keys().len(), 3);
+            assert!(request.transactions()[0].is_writable(0));
+            vec![
+                Assignment {
+                    index: 1,
+                    worker: 0,
+                },
+                Assignment {
+                    index: 0,
+                    worker: 1,
+                },
+            ]
+        });
+
+        let scheduling_summary = scheduler
+            .schedule(&mut container, test_pre_graph_filter, test_pre_lock_filter)
+            .unwrap();
+        external.join().unwrap();
+        assert_eq!(scheduling_summary.num_scheduled, 2);
+        assert_eq!(scheduling_summary.num_unschedulable_conflicts, 0);
+        assert_eq!(collect_ids(&work_receivers[0]), vec![vec![1]]);
+        assert_eq!(collect_ids(&work_receivers[1]), vec![vec![2]]);
+        assert_eq!(container.queue_size(), 1);
+        assert_eq!(container.pop().unwrap().id, 0);
+    }
+
+    #[test]
+    fn test_schedule_rejects_conflicting_and_invalid_assignments() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("scheduler");
+        let (mut scheduler, work_receivers, _finished_work_sender) =
+            create_test_frame(2, &path, Duration::from_secs(10));
+        let payer = Keypair::new();
+        let mut container = create_container(&[
+            (&payer, Pubkey::new_unique(), 1),
+            (&payer, Pubkey::new_unique(), 2),
+        ]);
+
+        // Both transactions write the payer, so they cannot run concurrently
+        // on different workers.
+        let external = spawn_external_scheduler(&path, 0, |_| {
+            vec![
+                Assignment {
+                    index: 0,
+                    worker: 0,
+                },
+                Assignment {
+                    index: 1,
+                    worker: 1,
+                },
+                Assignment {
+                    index: 7,
+                    worker: 0,
+                },
+                Assignment {
+                    index: 1,
+                    worker: 2,
+                },
+            ]
+        });
+
+        let scheduling_summary = scheduler
+            .schedule(&mut container, test_pre_graph_filter, test_pre_lock_filter)
+            .unwrap();
+        external.join().unwrap();
+        assert_eq!(scheduling_summary.num_scheduled, 1);
+        assert_eq!(scheduling_summary.num_unschedulable_threads, 1);
+        assert_eq!(collect_ids(&work_receivers[0]), vec![vec![1]]);
+        assert!(collect_ids(&work_receivers[1]).is_empty());
+        assert_eq!(container.queue_size(), 1);
+    }
+
+    #[test]
+    fn test_schedule_response_timeout_falls_back() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("scheduler");
+        let (mut scheduler, work_receivers, _finished_work_sender) =
+            create_test_frame(1, &path, Duration::from_millis(1));
+        let mut container = create_container(&[
+            (&Keypair::new(), Pubkey::new_unique(), 1),
+            (&Keypair::new(), Pubkey::new_unique(), 2),
+        ]);
+
+        // Nobody answers, so the built-in scheduler takes over.
+        let scheduling_summary = scheduler
+            .schedule(&mut container, test_pre_graph_filter, test_pre_lock_filter)
+            .unwrap();
+        assert_eq!(scheduling_summary.num_scheduled, 2);
+        assert_eq!(collect_ids(&work_receivers[0]), vec![vec![1, 0]]);
+        assert_eq!(container.queue_size(), 0);
+        let external_region = SharedRegion::open(&path).unwrap();
+        assert_eq!(external_region.poll_request(0), Some(1));
+
+        // Until the missed request is answered, no new request is published.
+        let mut container = create_container(&[(&Keypair::new(), Pubkey::new_unique(), 1)]);
+        let scheduling_summary = scheduler
+            .schedule(&mut container, test_pre_graph_filter, test_pre_lock_filter)
+            .unwrap();
+        assert_eq!(scheduling_summary.num_scheduled, 1);
+        assert_eq!(external_region.poll_request(0), Some(1));
+
+        // Once answered, requests resume.
+        scheduler.config.response_timeout = Duration::from_secs(10);
+        spawn_external_scheduler(&path, 0, |_| vec![])
+            .join()
+            .unwrap();
+        let mut container = create_container(&[(&Keypair::new(), Pubkey::new_unique(), 1)]);
+        let external = spawn_external_scheduler(&path, 1, |request| {
+            assert_eq!(request.transactions().len(), 1);
+            vec![]
+        });
+        let scheduling_summary = scheduler
+            .schedule(&mut container, test_pre_graph_filter, test_pre_lock_filter)
+            .unwrap();
+        external.join().unwrap();
+        assert_eq!(scheduling_summary.num_scheduled, 0);
+        assert_eq!(container.queue_size(), 1);
+        assert_eq!(external_region.poll_request(0), Some(2));
+    }
+
+    #[test]
+    fn test_schedule_pre_graph_filter() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("scheduler");
+        let (mut scheduler, _work_receivers, _finished_work_sender) =
+            create_test_frame(1, &path, Duration::from_millis(1));
+        let mut container = create_container(&[
+            (&Keypair::new(), Pubkey::new_unique(), 1),
+            (&Keypair::new(), Pubkey::new_unique(), 2),
+        ]);
+
+        let scheduling_summary = scheduler
+            .schedule(
+                &mut container,
+                |_txs, results| results.fill(false),
+                test_pre_lock_filter,
+            )
+            .unwrap();
+        assert_eq!(scheduling_summary.num_filtered_out, 2);
+        assert_eq!(scheduling_summary.num_unschedulable_threads, 0);
+        assert_eq!(container.buffer_size(), 0);
+        // Nothing was left to offer, so no request was published.
+        assert_eq!(SharedRegion::open(&path).unwrap().poll_request(0), None);
+    }
+}
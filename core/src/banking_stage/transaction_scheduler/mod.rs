@@ -1,6 +1,8 @@
 use conditional_mod::conditional_vis_mod;
 
 mod batch_id_generator;
+pub(crate) mod bundle_scheduler;
+conditional_vis_mod!(external_scheduler, feature = "dev-context-only-utils", pub, pub(crate));
 conditional_vis_mod!(greedy_scheduler, feature = "dev-context-only-utils", pub, pub(crate));
 mod in_flight_tracker;
 conditional_vis_mod!(prio_graph_scheduler, feature = "dev-context-only-utils", pub, pub(crate));
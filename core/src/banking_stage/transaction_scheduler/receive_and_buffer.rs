@@ -11,9 +11,12 @@ use {
         },
     },
     crate::banking_stage::{
-        consumer::Consumer, decision_maker::BufferedPacketsDecision,
+        consumer::Consumer,
+        decision_maker::BufferedPacketsDecision,
         immutable_deserialized_packet::ImmutableDeserializedPacket,
-        packet_deserializer::PacketDeserializer, scheduler_messages::MaxAge,
+        packet_deserializer::PacketDeserializer,
+        scheduler_messages::MaxAge,
+        transaction_outcome_log::{TransactionOutcome, TransactionOutcomeLog},
         TransactionStateContainer,
     },
     agave_banking_stage_ingress_types::{BankingPacketBatch, BankingPacketReceiver},
@@ -35,8 +38,9 @@ use {
         runtime_transaction::RuntimeTransaction, transaction_meta::StaticMeta,
         transaction_with_meta::TransactionWithMeta,
     },
+    solana_signature::Signature,
     solana_svm::transaction_error_metrics::TransactionErrorMetrics,
-    solana_svm_transaction::svm_message::SVMMessage,
+    solana_svm_transaction::{svm_message::SVMMessage, svm_transaction::SVMTransaction},
     solana_transaction::sanitized::{MessageHash, SanitizedTransaction},
     std::{
         num::Saturating,
@@ -70,6 +74,7 @@ pub(crate) struct SanitizedTransactionReceiveAndBuffer {
     /// Packet/Transaction ingress.
     packet_receiver: PacketDeserializer,
     bank_forks: Arc<RwLock<BankForks>>,
+    transaction_outcome_log: Option<Arc<TransactionOutcomeLog>>,
 }
 
 impl ReceiveAndBuffer for SanitizedTransactionReceiveAndBuffer {
@@ -141,10 +146,15 @@ impl ReceiveAndBuffer for SanitizedTransactionReceiveAndBuffer {
 }
 
 impl SanitizedTransactionReceiveAndBuffer {
-    pub fn new(packet_receiver: PacketDeserializer, bank_forks: Arc<RwLock<BankForks>>) -> Self {
+    pub fn new(
+        packet_receiver: PacketDeserializer,
+        bank_forks: Arc<RwLock<BankForks>>,
+        transaction_outcome_log: Option<Arc<TransactionOutcomeLog>>,
+    ) -> Self {
         Self {
             packet_receiver,
             bank_forks,
+            transaction_outcome_log,
         }
     }
 
@@ -177,6 +187,14 @@ impl SanitizedTransactionReceiveAndBuffer {
         let mut fee_budget_limits_vec = ArrayVec::<_, CHUNK_SIZE>::new();
 
         let mut error_counts = TransactionErrorMetrics::default();
+        // Outcomes are only collected if the outcome log is enabled.
+        let mut outcomes = Vec::new();
+        let record_outcomes = self.transaction_outcome_log.is_some();
+        let mut record_outcome = |signature: &Signature, outcome: TransactionOutcome| {
+            if record_outcomes {
+                outcomes.push((*signature, outcome));
+            }
+        };
         for chunk in packets.chunks(CHUNK_SIZE) {
             let mut post_sanitization_count = Saturating::<usize>(0);
             chunk
@@ -189,18 +207,24 @@ impl SanitizedTransactionReceiveAndBuffer {
                     )
                 })
                 .inspect(|_| post_sanitization_count += 1)
-                .filter(|(tx, _deactivation_slot)| {
-                    validate_account_locks(
+                .filter_map(|(tx, deactivation_slot)| {
+                    let compute_budget = validate_account_locks(
                         tx.message().account_keys(),
                         transaction_account_lock_limit,
                     )
-                    .is_ok()
-                })
-                .filter_map(|(tx, deactivation_slot)| {
-                    tx.compute_budget_instruction_details()
-                        .sanitize_and_convert_to_compute_budget_limits(&working_bank.feature_set)
-                        .map(|compute_budget| (tx, deactivation_slot, compute_budget.into()))
-                        .ok()
+                    .and_then(|_| {
+                        tx.compute_budget_instruction_details()
+                            .sanitize_and_convert_to_compute_budget_limits(
+                                &working_bank.feature_set,
+                            )
+                    });
+                    match compute_budget {
+                        Ok(compute_budget) => Some((tx, deactivation_slot, compute_budget.into())),
+                        Err(err) => {
+                            record_outcome(tx.signature(), TransactionOutcome::dropped(err));
+                            None
+                        }
+                    }
                 })
                 .for_each(|(tx, deactivation_slot, fee_budget_limits)| {
                     transactions.push(tx);
@@ -223,25 +247,45 @@ impl SanitizedTransactionReceiveAndBuffer {
             let mut post_transaction_check_count = Saturating::<usize>(0);
             let mut num_dropped_on_capacity = Saturating::<usize>(0);
             let mut num_buffered = Saturating::<usize>(0);
-            for (((transaction, max_age), fee_budget_limits), _check_result) in transactions
+            for (((transaction, max_age), fee_budget_limits), check_result) in transactions
                 .drain(..)
                 .zip(max_ages.drain(..))
                 .zip(fee_budget_limits_vec.drain(..))
                 .zip(check_results)
-                .filter(|(_, check_result)| check_result.is_ok())
-                .filter(|(((tx, _), _), _)| {
-                    Consumer::check_fee_payer_unlocked(&working_bank, tx, &mut error_counts).is_ok()
-                })
             {
+                if let Err(err) = check_result.and_then(|_| {
+                    Consumer::check_fee_payer_unlocked(
+                        &working_bank,
+                        &transaction,
+                        &mut error_counts,
+                    )
+                }) {
+                    record_outcome(transaction.signature(), TransactionOutcome::dropped(err));
+                    continue;
+                }
                 post_transaction_check_count += 1;
 
                 let (priority, cost) =
                     calculate_priority_and_cost(&transaction, &fee_budget_limits, &working_bank);
 
-                if container.insert_new_transaction(transaction, max_age, priority, cost) {
+                let signature = *transaction.signature();
+                let mut dropped_signature = None;
+                if container.insert_new_transaction_with_callback(
+                    transaction,
+                    max_age,
+                    priority,
+                    cost,
+                    |dropped| dropped_signature = Some(*dropped.signature()),
+                ) {
                     num_dropped_on_capacity += 1;
                 }
                 num_buffered += 1;
+                if dropped_signature != Some(signature) {
+                    record_outcome(&signature, TransactionOutcome::Received);
+                }
+                if let Some(dropped_signature) = dropped_signature {
+                    record_outcome(&dropped_signature, TransactionOutcome::DroppedOnCapacity);
+                }
             }
 
             let Saturating(post_sanitization_count) = post_sanitization_count;
@@ -265,6 +309,10 @@ impl SanitizedTransactionReceiveAndBuffer {
                     num_dropped_on_transaction_checks;
             });
         }
+
+        if let Some(transaction_outcome_log) = self.transaction_outcome_log.as_ref() {
+            transaction_outcome_log.record_all(working_bank.slot(), outcomes);
+        }
     }
 }
 
@@ -272,6 +320,7 @@ impl SanitizedTransactionReceiveAndBuffer {
 pub(crate) struct TransactionViewReceiveAndBuffer {
     pub receiver: BankingPacketReceiver,
     pub bank_forks: Arc<RwLock<BankForks>>,
+    pub transaction_outcome_log: Option<Arc<TransactionOutcomeLog>>,
 }
 
 impl ReceiveAndBuffer for TransactionViewReceiveAndBuffer {
@@ -394,10 +443,15 @@ impl TransactionViewReceiveAndBuffer {
         let mut transaction_priority_ids = ArrayVec::<_, EXTRA_CAPACITY>::new();
         let lock_results: [_; EXTRA_CAPACITY] = core::array::from_fn(|_| Ok(()));
         let mut error_counters = TransactionErrorMetrics::default();
+        // Outcomes are only collected if the outcome log is enabled.
+        let record_outcomes = self.transaction_outcome_log.is_some();
+        let mut outcomes = Vec::new();
+        let mut received = ArrayVec::<Signature, EXTRA_CAPACITY>::new();
 
         let mut check_and_push_to_queue =
             |container: &mut TransactionViewStateContainer,
-             transaction_priority_ids: &mut ArrayVec<TransactionPriorityId, 64>| {
+             transaction_priority_ids: &mut ArrayVec<TransactionPriorityId, 64>,
+             outcomes: &mut Vec<(Signature, TransactionOutcome)>| {
                 // Temporary scope so that transaction references are immediately
                 // dropped and transactions not passing
                 let mut check_results = {
@@ -420,32 +474,66 @@ impl TransactionViewReceiveAndBuffer {
                     .iter_mut()
                     .zip(transaction_priority_ids.iter())
                 {
-                    if result.is_err() {
+                    let transaction = container
+                        .get_transaction(priority_id.id)
+                        .expect("transaction must exist");
+                    if let Err(err) = result {
+                        if record_outcomes {
+                            outcomes.push((
+                                *transaction.signature(),
+                                TransactionOutcome::dropped(err.clone()),
+                            ));
+                        }
                         num_dropped_on_status_age_checks += 1;
                         container.remove_by_id(priority_id.id);
                         continue;
                     }
-                    let transaction = container
-                        .get_transaction(priority_id.id)
-                        .expect("transaction must exist");
                     if let Err(err) = Consumer::check_fee_payer_unlocked(
                         working_bank,
                         transaction,
                         &mut error_counters,
                     ) {
+                        if record_outcomes {
+                            outcomes.push((
+                                *transaction.signature(),
+                                TransactionOutcome::dropped(err.clone()),
+                            ));
+                        }
                         *result = Err(err);
                         num_dropped_on_status_age_checks += 1;
                         container.remove_by_id(priority_id.id);
                         continue;
                     }
+                    if record_outcomes {
+                        received.push(*transaction.signature());
+                    }
                 }
                 // Push non-errored transaction into queue.
-                num_dropped_on_capacity += container.push_ids_into_queue(
+                let mut dropped = Vec::new();
+                num_dropped_on_capacity += container.push_ids_into_queue_with_callback(
                     check_results
                         .into_iter()
                         .zip(transaction_priority_ids.drain(..))
                         .filter(|(r, _)| r.is_ok())
                         .map(|(_, id)| id),
+                    |transaction| {
+                        if record_outcomes {
+                            dropped.push(*transaction.signature());
+                        }
+                    },
+                );
+                // Only record transactions as received once they made it into
+                // the queue.
+                outcomes.extend(
+                    received
+                        .drain(..)
+                        .filter(|signature| !dropped.contains(signature))
+                        .map(|signature| (signature, TransactionOutcome::Received)),
+                );
+                outcomes.extend(
+                    dropped
+                        .into_iter()
+                        .map(|signature| (signature, TransactionOutcome::DroppedOnCapacity)),
                 );
             };
 
@@ -467,6 +555,7 @@ impl TransactionViewReceiveAndBuffer {
                             alt_resolved_slot,
                             sanitized_epoch,
                             transaction_account_lock_limit,
+                            record_outcomes.then_some(&mut outcomes),
                         ) {
                             Ok(state) => {
                                 num_buffered += 1;
@@ -488,14 +577,22 @@ impl TransactionViewReceiveAndBuffer {
 
                     // If at capacity, run checks and remove invalid transactions.
                     if transaction_priority_ids.len() == EXTRA_CAPACITY {
-                        check_and_push_to_queue(container, &mut transaction_priority_ids);
+                        check_and_push_to_queue(
+                            container,
+                            &mut transaction_priority_ids,
+                            &mut outcomes,
+                        );
                     }
                 }
             }
         }
 
         // Any remaining packets undergo status/age checks
-        check_and_push_to_queue(container, &mut transaction_priority_ids);
+        check_and_push_to_queue(container, &mut transaction_priority_ids, &mut outcomes);
+
+        if let Some(transaction_outcome_log) = self.transaction_outcome_log.as_ref() {
+            transaction_outcome_log.record_all(working_bank.slot(), outcomes);
+        }
 
         let buffer_time_us = start.elapsed().as_micros() as u64;
         timing_metrics.update(|timing_metrics| {
@@ -519,6 +616,7 @@ impl TransactionViewReceiveAndBuffer {
         alt_resolved_slot: Slot,
         sanitized_epoch: Epoch,
         transaction_account_lock_limit: usize,
+        outcomes: Option<&mut Vec<(Signature, TransactionOutcome)>>,
     ) -> Result<TransactionViewState, ()> {
         // Parsing and basic sanitization checks
         let Ok(view) = SanitizedTransactionView::try_new_sanitized(bytes) else {
@@ -559,16 +657,20 @@ impl TransactionViewReceiveAndBuffer {
             return Err(());
         };
 
-        if validate_account_locks(view.account_keys(), transaction_account_lock_limit).is_err() {
-            return Err(());
-        }
-
-        let Ok(compute_budget_limits) = view
-            .compute_budget_instruction_details()
-            .sanitize_and_convert_to_compute_budget_limits(&working_bank.feature_set)
-        else {
-            return Err(());
-        };
+        let compute_budget_limits =
+            match validate_account_locks(view.account_keys(), transaction_account_lock_limit)
+                .and_then(|_| {
+                    view.compute_budget_instruction_details()
+                        .sanitize_and_convert_to_compute_budget_limits(&working_bank.feature_set)
+                }) {
+                Ok(compute_budget_limits) => compute_budget_limits,
+                Err(err) => {
+                    if let Some(outcomes) = outcomes {
+                        outcomes.push((*view.signature(), TransactionOutcome::dropped(err)));
+                    }
+                    return Err(());
+                }
+            };
 
         let max_age = calculate_max_age(sanitized_epoch, deactivation_slot, alt_resolved_slot);
         let fee_budget_limits = FeeBudgetLimits::from(compute_budget_limits);
@@ -688,6 +790,7 @@ mod tests {
         let receive_and_buffer = SanitizedTransactionReceiveAndBuffer {
             packet_receiver: PacketDeserializer::new(receiver),
             bank_forks,
+            transaction_outcome_log: None,
         };
         let container = TransactionStateContainer::with_capacity(TEST_CONTAINER_CAPACITY);
         (receive_and_buffer, container)
@@ -703,6 +806,7 @@ mod tests {
         let receive_and_buffer = TransactionViewReceiveAndBuffer {
             receiver,
             bank_forks,
+            transaction_outcome_log: None,
         };
         let container = TransactionViewStateContainer::with_capacity(TEST_CONTAINER_CAPACITY);
         (receive_and_buffer, container)
@@ -1096,4 +1200,96 @@ mod tests {
         assert_eq!(num_received, num_transactions);
         verify_container(&mut container, TEST_CONTAINER_CAPACITY);
     }
+
+    fn setup_sanitized_transaction_receive_and_buffer_with_outcome_log(
+        receiver: Receiver<BankingPacketBatch>,
+        bank_forks: Arc<RwLock<BankForks>>,
+        transaction_outcome_log: Arc<TransactionOutcomeLog>,
+    ) -> (
+        SanitizedTransactionReceiveAndBuffer,
+        TransactionStateContainer<RuntimeTransaction<SanitizedTransaction>>,
+    ) {
+        let (mut receive_and_buffer, container) =
+            setup_sanitized_transaction_receive_and_buffer(receiver, bank_forks);
+        receive_and_buffer.transaction_outcome_log = Some(transaction_outcome_log);
+        (receive_and_buffer, container)
+    }
+
+    fn setup_transaction_view_receive_and_buffer_with_outcome_log(
+        receiver: Receiver<BankingPacketBatch>,
+        bank_forks: Arc<RwLock<BankForks>>,
+        transaction_outcome_log: Arc<TransactionOutcomeLog>,
+    ) -> (
+        TransactionViewReceiveAndBuffer,
+        TransactionViewStateContainer,
+    ) {
+        let (mut receive_and_buffer, container) =
+            setup_transaction_view_receive_and_buffer(receiver, bank_forks);
+        receive_and_buffer.transaction_outcome_log = Some(transaction_outcome_log);
+        (receive_and_buffer, container)
+    }
+
+    #[test_case(setup_sanitized_transaction_receive_and_buffer_with_outcome_log; "testcase-sdk")]
+    #[test_case(setup_transaction_view_receive_and_buffer_with_outcome_log; "testcase-view")]
+    fn test_receive_and_buffer_transaction_outcome_log<R: ReceiveAndBuffer>(
+        setup_receive_and_buffer: impl FnOnce(
+            Receiver<BankingPacketBatch>,
+            Arc<RwLock<BankForks>>,
+            Arc<TransactionOutcomeLog>,
+        ) -> (R, R::Container),
+    ) {
+        let (sender, receiver) = unbounded();
+        let (bank_forks, mint_keypair) = test_bank_forks();
+        let transaction_outcome_log = Arc::new(TransactionOutcomeLog::new(1));
+        let (mut receive_and_buffer, mut container) = setup_receive_and_buffer(
+            receiver,
+            bank_forks.clone(),
+            transaction_outcome_log.clone(),
+        );
+        let mut timing_metrics = SchedulerTimingMetrics::default();
+        let mut count_metrics = SchedulerCountMetrics::default();
+
+        let valid_transaction = transfer(
+            &mint_keypair,
+            &Pubkey::new_unique(),
+            1,
+            bank_forks.read().unwrap().root_bank().last_blockhash(),
+        );
+        let expired_transaction =
+            transfer(&mint_keypair, &Pubkey::new_unique(), 1, Hash::new_unique());
+        let packet_batches = Arc::new(to_packet_batches(
+            &[valid_transaction.clone(), expired_transaction.clone()],
+            2,
+        ));
+        sender.send(packet_batches).unwrap();
+
+        let num_received = receive_and_buffer
+            .receive_and_buffer_packets(
+                &mut container,
+                &mut timing_metrics,
+                &mut count_metrics,
+                &BufferedPacketsDecision::Hold,
+            )
+            .unwrap();
+        assert_eq!(num_received, 2);
+        verify_container(&mut container, 1);
+
+        let outcomes = |signature| -> Vec<_> {
+            transaction_outcome_log
+                .get(signature)
+                .into_iter()
+                .map(|record| record.outcome)
+                .collect()
+        };
+        assert_eq!(
+            outcomes(&valid_transaction.signatures[0]),
+            vec![TransactionOutcome::Received]
+        );
+        assert_eq!(
+            outcomes(&expired_transaction.signatures[0]),
+            vec![TransactionOutcome::Dropped(
+                solana_transaction_error::TransactionError::BlockhashNotFound
+            )]
+        );
+    }
 }
@@ -136,6 +136,8 @@ pub(crate) struct SchedulingCommon<Tx> {
     pub(crate) consume_work_senders: Vec<Sender<ConsumeWork<Tx>>>,
     pub(crate) finished_consume_work_receiver: Receiver<FinishedConsumeWork<Tx>>,
     pub(crate) in_flight_tracker: InFlightTracker,
+    /// Locks held by the workers, plus one extra thread for the bundle worker,
+    /// see [`SchedulingCommon::bundle_thread_id`].
     pub(crate) account_locks: ThreadAwareAccountLocks,
 }
 
@@ -147,17 +149,25 @@ impl<Tx> SchedulingCommon<Tx> {
         let num_threads = consume_work_senders.len();
         assert!(num_threads > 0, "must have at least one worker");
         assert!(
-            num_threads <= MAX_THREADS,
-            "cannot have more than {MAX_THREADS} workers"
+            num_threads < MAX_THREADS,
+            "cannot have more than {} workers",
+            MAX_THREADS - 1
         );
         Self {
             consume_work_senders,
             finished_consume_work_receiver,
             in_flight_tracker: InFlightTracker::new(num_threads),
-            account_locks: ThreadAwareAccountLocks::new(num_threads),
+            account_locks: ThreadAwareAccountLocks::new(num_threads + 1),
         }
     }
 
+    /// Thread id under which bundles lock their accounts. No transactions are
+    /// scheduled to it, so transactions conflicting with an in-flight bundle
+    /// are held back until the bundle has finished.
+    pub fn bundle_thread_id(&self) -> ThreadId {
+        self.consume_work_senders.len()
+    }
+
     /// Send a batch of transactions to the given thread's `ConsumeWork` channel.
     /// Returns the number of transactions sent.
     pub fn send_batch(
@@ -1,8 +1,11 @@
 //! Control flow for BankingStage's transaction scheduler.
 //!
 
+#[cfg(feature = "dev-context-only-utils")]
+use qualifier_attr::qualifiers;
 use {
     super::{
+        bundle_scheduler::BundleScheduler,
         receive_and_buffer::{DisconnectedError, ReceiveAndBuffer},
         scheduler::{PreLockFilterAction, Scheduler},
         scheduler_error::SchedulerError,
@@ -15,6 +18,7 @@ use {
         consume_worker::ConsumeWorkerMetrics,
         consumer::Consumer,
         decision_maker::{BufferedPacketsDecision, DecisionMaker},
+        transaction_outcome_log::{TransactionOutcome, TransactionOutcomeLog},
         transaction_scheduler::transaction_state_container::StateContainer,
         TOTAL_BUFFERED_PACKETS,
     },
@@ -55,6 +59,10 @@ where
     worker_metrics: Vec<Arc<ConsumeWorkerMetrics>>,
     /// Detailed scheduling metrics.
     scheduling_details: SchedulingDetails,
+    /// Optional log of per-signature transaction outcomes.
+    transaction_outcome_log: Option<Arc<TransactionOutcomeLog>>,
+    /// Buffers bundles and locks their accounts, if bundles are enabled.
+    bundle_scheduler: Option<BundleScheduler>,
 }
 
 impl<R, S> SchedulerController<R, S>
@@ -68,6 +76,8 @@ where
         bank_forks: Arc<RwLock<BankForks>>,
         scheduler: S,
         worker_metrics: Vec<Arc<ConsumeWorkerMetrics>>,
+        transaction_outcome_log: Option<Arc<TransactionOutcomeLog>>,
+        bundle_scheduler: Option<BundleScheduler>,
     ) -> Self {
         Self {
             decision_maker,
@@ -80,6 +90,8 @@ where
             timing_metrics: SchedulerTimingMetrics::default(),
             worker_metrics,
             scheduling_details: SchedulingDetails::default(),
+            transaction_outcome_log,
+            bundle_scheduler,
         }
     }
 
@@ -113,6 +125,9 @@ where
             if self.receive_and_buffer_packets(&decision).is_err() {
                 break;
             }
+            if let Some(bundle_scheduler) = self.bundle_scheduler.as_mut() {
+                bundle_scheduler.receive_and_buffer_bundles(&decision);
+            }
             // Report metrics only if there is data.
             // Reset intervals when appropriate, regardless of report.
             let should_report = self.count_metrics.interval_has_data();
@@ -128,6 +143,9 @@ where
                 .iter()
                 .for_each(|metrics| metrics.maybe_report_and_reset());
             self.scheduling_details.maybe_report();
+            if let Some(bundle_scheduler) = self.bundle_scheduler.as_mut() {
+                bundle_scheduler.maybe_report_metrics();
+            }
         }
 
         Ok(())
@@ -140,6 +158,18 @@ where
     ) -> Result<(), SchedulerError> {
         match decision {
             BufferedPacketsDecision::Consume(bank_start) => {
+                // Bundles lock their accounts ahead of the transactions.
+                if let Some(bundle_scheduler) = self.bundle_scheduler.as_mut() {
+                    let common = self.scheduler.scheduling_common_mut();
+                    let bundle_thread_id = common.bundle_thread_id();
+                    bundle_scheduler.schedule(
+                        &bank_start.working_bank,
+                        &mut common.account_locks,
+                        bundle_thread_id,
+                    )?;
+                }
+
+                let transaction_outcome_log = self.transaction_outcome_log.as_deref();
                 let (scheduling_summary, schedule_time_us) = measure_us!(self.scheduler.schedule(
                     &mut self.container,
                     |txs, results| {
@@ -148,6 +178,7 @@ where
                             results,
                             &bank_start.working_bank,
                             MAX_PROCESSING_AGE,
+                            transaction_outcome_log,
                         )
                     },
                     |_| PreLockFilterAction::AttemptToSchedule // no pre-lock filter for now
@@ -169,6 +200,9 @@ where
                 self.scheduling_details.update(&scheduling_summary);
             }
             BufferedPacketsDecision::Forward => {
+                if let Some(bundle_scheduler) = self.bundle_scheduler.as_mut() {
+                    bundle_scheduler.clear();
+                }
                 let (_, clear_time_us) = measure_us!(self.clear_container());
                 self.timing_metrics.update(|timing_metrics| {
                     timing_metrics.clear_time_us += clear_time_us;
@@ -186,11 +220,14 @@ where
         Ok(())
     }
 
+    // Banking trace replay filters transactions as the banking stage does
+    #[cfg_attr(feature = "dev-context-only-utils", qualifiers(pub(crate)))]
     fn pre_graph_filter(
         transactions: &[&R::Transaction],
         results: &mut [bool],
         bank: &Bank,
         max_age: usize,
+        transaction_outcome_log: Option<&TransactionOutcomeLog>,
     ) {
         let lock_results = vec![Ok(()); transactions.len()];
         let mut error_counters = TransactionErrorMetrics::default();
@@ -201,14 +238,22 @@ where
             &mut error_counters,
         );
 
+        let mut dropped = transaction_outcome_log.map(|_| vec![]);
         for ((check_result, tx), result) in check_results
             .into_iter()
             .zip(transactions)
             .zip(results.iter_mut())
         {
-            *result = check_result
-                .and_then(|_| Consumer::check_fee_payer_unlocked(bank, *tx, &mut error_counters))
-                .is_ok();
+            let check_result = check_result
+                .and_then(|_| Consumer::check_fee_payer_unlocked(bank, *tx, &mut error_counters));
+            if let (Err(err), Some(dropped)) = (&check_result, dropped.as_mut()) {
+                dropped.push((*tx.signature(), TransactionOutcome::dropped(err.clone())));
+            }
+            *result = check_result.is_ok();
+        }
+
+        if let (Some(transaction_outcome_log), Some(dropped)) = (transaction_outcome_log, dropped) {
+            transaction_outcome_log.record_all(bank.slot(), dropped);
         }
     }
 
@@ -267,8 +312,20 @@ where
 
             // Remove errored transactions
             for (result, id) in check_results.iter().zip(chunk.iter()) {
-                if result.is_err() {
+                if let Err(err) = result {
                     num_dropped_on_age_and_status += 1;
+                    if let Some(transaction_outcome_log) = self.transaction_outcome_log.as_ref() {
+                        let signature = *self
+                            .container
+                            .get_transaction(id.id)
+                            .expect("transaction must exist")
+                            .signature();
+                        transaction_outcome_log.record(
+                            bank.slot(),
+                            signature,
+                            TransactionOutcome::dropped(err.clone()),
+                        );
+                    }
                     self.container.remove_by_id(id.id);
                 }
             }
@@ -301,6 +358,12 @@ where
             timing_metrics.receive_completed_time_us += receive_completed_time_us;
         });
 
+        if let Some(bundle_scheduler) = self.bundle_scheduler.as_mut() {
+            let common = self.scheduler.scheduling_common_mut();
+            let bundle_thread_id = common.bundle_thread_id();
+            bundle_scheduler.receive_completed(&mut common.account_locks, bundle_thread_id)?;
+        }
+
         Ok(())
     }
 
@@ -380,7 +443,11 @@ mod tests {
         receiver: BankingPacketReceiver,
         bank_forks: Arc<RwLock<BankForks>>,
     ) -> SanitizedTransactionReceiveAndBuffer {
-        SanitizedTransactionReceiveAndBuffer::new(PacketDeserializer::new(receiver), bank_forks)
+        SanitizedTransactionReceiveAndBuffer::new(
+            PacketDeserializer::new(receiver),
+            bank_forks,
+            None,
+        )
     }
 
     fn test_create_transaction_view_receive_and_buffer(
@@ -390,6 +457,7 @@ mod tests {
         TransactionViewReceiveAndBuffer {
             receiver,
             bank_forks,
+            transaction_outcome_log: None,
         }
     }
 
@@ -454,6 +522,8 @@ mod tests {
             bank_forks,
             scheduler,
             vec![], // no actual workers with metrics to report, this can be empty
+            None,
+            None,
         );
 
         (test_frame, scheduler_controller)
@@ -33,6 +33,12 @@ impl SchedulerCountMetrics {
     pub fn interval_has_data(&self) -> bool {
         self.interval.metrics.has_data()
     }
+
+    /// The counts accumulated since the slot was last reset
+    #[cfg(feature = "dev-context-only-utils")]
+    pub fn slot_metrics(&self) -> &SchedulerCountMetricsInner {
+        &self.slot.metrics
+    }
 }
 
 #[derive(Default)]
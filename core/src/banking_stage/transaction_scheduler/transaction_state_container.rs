@@ -89,6 +89,16 @@ pub(crate) trait StateContainer<Tx: TransactionWithMeta> {
     fn push_ids_into_queue(
         &mut self,
         priority_ids: impl Iterator<Item = TransactionPriorityId>,
+    ) -> usize {
+        self.push_ids_into_queue_with_callback(priority_ids, |_| {})
+    }
+
+    /// Same as [`Self::push_ids_into_queue`], but calls `on_drop` with each
+    /// transaction dropped due to capacity limits before it is removed.
+    fn push_ids_into_queue_with_callback(
+        &mut self,
+        priority_ids: impl Iterator<Item = TransactionPriorityId>,
+        on_drop: impl FnMut(&Tx),
     ) -> usize;
 
     /// Remove transaction by id.
@@ -142,9 +152,10 @@ impl<Tx: TransactionWithMeta> StateContainer<Tx> for TransactionStateContainer<T
             .map(|state| state.transaction())
     }
 
-    fn push_ids_into_queue(
+    fn push_ids_into_queue_with_callback(
         &mut self,
         priority_ids: impl Iterator<Item = TransactionPriorityId>,
+        mut on_drop: impl FnMut(&Tx),
     ) -> usize {
         for id in priority_ids {
             self.priority_queue.push(id);
@@ -161,7 +172,8 @@ impl<Tx: TransactionWithMeta> StateContainer<Tx> for TransactionStateContainer<T
 
         for _ in 0..num_dropped {
             let priority_id = self.priority_queue.pop_min().expect("queue is not empty");
-            self.id_to_transaction_state.remove(priority_id.id);
+            let transaction_state = self.id_to_transaction_state.remove(priority_id.id);
+            on_drop(transaction_state.transaction());
         }
 
         num_dropped
@@ -198,6 +210,19 @@ impl<Tx: TransactionWithMeta> TransactionStateContainer<Tx> {
         max_age: MaxAge,
         priority: u64,
         cost: u64,
+    ) -> bool {
+        self.insert_new_transaction_with_callback(transaction, max_age, priority, cost, |_| {})
+    }
+
+    /// Same as [`Self::insert_new_transaction`], but calls `on_drop` with the
+    /// transaction dropped due to capacity limits, which may be the new one.
+    pub(crate) fn insert_new_transaction_with_callback(
+        &mut self,
+        transaction: Tx,
+        max_age: MaxAge,
+        priority: u64,
+        cost: u64,
+        on_drop: impl FnMut(&Tx),
     ) -> bool {
         let priority_id = {
             let entry = self.get_vacant_map_entry();
@@ -206,7 +231,7 @@ impl<Tx: TransactionWithMeta> TransactionStateContainer<Tx> {
             TransactionPriorityId::new(priority, transaction_id)
         };
 
-        self.push_ids_into_queue(std::iter::once(priority_id)) > 0
+        self.push_ids_into_queue_with_callback(std::iter::once(priority_id), on_drop) > 0
     }
 
     fn get_vacant_map_entry(&mut self) -> VacantEntry<TransactionState<Tx>> {
@@ -316,11 +341,13 @@ impl StateContainer<RuntimeTransactionView> for TransactionViewStateContainer {
     }
 
     #[inline]
-    fn push_ids_into_queue(
+    fn push_ids_into_queue_with_callback(
         &mut self,
         priority_ids: impl Iterator<Item = TransactionPriorityId>,
+        on_drop: impl FnMut(&RuntimeTransactionView),
     ) -> usize {
-        self.inner.push_ids_into_queue(priority_ids)
+        self.inner
+            .push_ids_into_queue_with_callback(priority_ids, on_drop)
     }
 
     #[inline]
@@ -353,6 +380,7 @@ mod tests {
         solana_perf::packet::Packet,
         solana_runtime_transaction::runtime_transaction::RuntimeTransaction,
         solana_signer::Signer,
+        solana_svm_transaction::svm_transaction::SVMTransaction,
         solana_system_interface::instruction as system_instruction,
         solana_transaction::{
             sanitized::{MessageHash, SanitizedTransaction},
@@ -417,6 +445,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_insert_new_transaction_with_callback() {
+        let mut container = TransactionStateContainer::with_capacity(1);
+        let (low, max_age, priority, cost) = test_transaction(1);
+        let low_signature = *low.signature();
+        let mut dropped = vec![];
+        assert!(!container.insert_new_transaction_with_callback(
+            low,
+            max_age,
+            priority,
+            cost,
+            |transaction| dropped.push(*transaction.signature()),
+        ));
+        assert!(dropped.is_empty());
+
+        // The lower priority transaction is dropped in favor of the new one
+        let (high, max_age, priority, cost) = test_transaction(2);
+        let high_signature = *high.signature();
+        assert!(container.insert_new_transaction_with_callback(
+            high,
+            max_age,
+            priority,
+            cost,
+            |transaction| dropped.push(*transaction.signature()),
+        ));
+        assert_eq!(dropped, vec![low_signature]);
+
+        // A new transaction of lower priority is dropped itself
+        let (low, max_age, priority, cost) = test_transaction(0);
+        let low_signature = *low.signature();
+        dropped.clear();
+        assert!(container.insert_new_transaction_with_callback(
+            low,
+            max_age,
+            priority,
+            cost,
+            |transaction| dropped.push(*transaction.signature()),
+        ));
+        assert_eq!(dropped, vec![low_signature]);
+        assert_eq!(
+            container
+                .get_transaction(container.priority_queue.peek_max().unwrap().id)
+                .unwrap()
+                .signature(),
+            &high_signature
+        );
+    }
+
     #[test]
     fn test_get_mut_transaction_state() {
         let mut container = TransactionStateContainer::with_capacity(5);
@@ -1,3 +1,28 @@
+//! Tracing of the packets received by the banking stage and the blocks it produced
+//!
+//! # Trace format
+//!
+//! A trace is a directory holding a `version` file and up to [`TRACE_FILE_ROTATE_COUNT`] event
+//! files named `events`, `events.1`, `events.2` and so on, where a higher index holds older
+//! events. The `version` file contains [`BANKING_TRACE_FORMAT_VERSION`] as decimal text. Traces
+//! written before the `version` file was introduced have no such file and are version 1.
+//!
+//! ## Version 1
+//!
+//! Each event file is a sequence of bincode-serialized [`TimedTracedEvent`]s without any framing,
+//! in the order they were traced. An event file only ends in a truncated event if the validator
+//! didn't shut down cleanly. Each event is the wall clock time it was traced at, followed by one
+//! of:
+//!
+//! - [`TracedEvent::PacketBatch`]: a batch of packets sent to the banking stage, along with the
+//!   [`ChannelLabel`] of the channel it was sent through. The packets are traced after signature
+//!   verification, so the packets which failed it are marked as discarded.
+//! - [`TracedEvent::BlockAndBankHash`]: the slot, blockhash and bank hash of a bank which was
+//!   frozen.
+//!
+//! Events are traced from several threads without synchronization, so their times are only
+//! roughly in order.
+
 use {
     agave_banking_stage_ingress_types::{BankingPacketBatch, BankingPacketReceiver},
     bincode::serialize_into,
@@ -7,9 +32,9 @@ use {
     solana_clock::Slot,
     solana_hash::Hash,
     std::{
-        fs::{create_dir_all, remove_dir_all},
-        io::{self, Write},
-        path::PathBuf,
+        fs::{self, create_dir_all, remove_dir_all, File},
+        io::{self, BufRead, BufReader, Write},
+        path::{Path, PathBuf},
         sync::{
             atomic::{AtomicBool, Ordering},
             Arc,
@@ -38,10 +63,19 @@ pub enum TraceError {
 
     #[error("Trace directory's byte limit is too small (must be larger than {1}): {0}")]
     TooSmallDirByteLimit(DirByteLimit, DirByteLimit),
+
+    #[error("Invalid trace format version: {0:?}")]
+    InvalidFormatVersion(String),
+
+    #[error("Unsupported trace format version (must be at most {1}): {0}")]
+    UnsupportedFormatVersion(u32, u32),
 }
 
+/// The version of the trace format written by this tracer
+pub const BANKING_TRACE_FORMAT_VERSION: u32 = 1;
+pub const VERSION_FILE_NAME: &str = "version";
 pub(crate) const BASENAME: &str = "events";
-const TRACE_FILE_ROTATE_COUNT: u64 = 14; // target 2 weeks retention under normal load
+pub const TRACE_FILE_ROTATE_COUNT: u64 = 14; // target 2 weeks retention under normal load
 const TRACE_FILE_WRITE_INTERVAL_MS: u64 = 100;
 const BUF_WRITER_CAPACITY: usize = 10 * 1024 * 1024;
 pub const TRACE_FILE_DEFAULT_ROTATE_BYTE_THRESHOLD: u64 = 1024 * 1024 * 1024;
@@ -383,6 +417,10 @@ impl BankingTracer {
         rotate_threshold_size: u64,
     ) -> Result<RollingFileAppender<RollingConditionGrouped>, TraceError> {
         create_dir_all(path)?;
+        fs::write(
+            path.join(VERSION_FILE_NAME),
+            format!("{BANKING_TRACE_FORMAT_VERSION}\n"),
+        )?;
         let grouped = RollingConditionGrouped::new(
             RollingConditionBasic::new()
                 .daily()
@@ -397,6 +435,60 @@ impl BankingTracer {
         Ok(appender)
     }
 
+    /// Returns the format version of the trace in the directory at `path`
+    pub fn read_format_version(path: &Path) -> Result<u32, TraceError> {
+        let version = match fs::read_to_string(path.join(VERSION_FILE_NAME)) {
+            Ok(version) => version,
+            // The version file was introduced after version 1
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(1),
+            Err(err) => return Err(err.into()),
+        };
+        let version = version
+            .trim()
+            .parse()
+            .map_err(|_| TraceError::InvalidFormatVersion(version.clone()))?;
+        if version > BANKING_TRACE_FORMAT_VERSION {
+            return Err(TraceError::UnsupportedFormatVersion(
+                version,
+                BANKING_TRACE_FORMAT_VERSION,
+            ));
+        }
+        Ok(version)
+    }
+
+    /// Returns the event files of the trace in the directory at `path`, oldest first
+    pub fn event_file_paths(path: &Path) -> Vec<PathBuf> {
+        let mut event_file_paths: Vec<_> = (0..TRACE_FILE_ROTATE_COUNT)
+            .map(|index| {
+                if index == 0 {
+                    path.join(BASENAME)
+                } else {
+                    path.join(format!("{BASENAME}.{index}"))
+                }
+            })
+            .take_while(|event_file_path| event_file_path.exists())
+            .collect();
+        event_file_paths.reverse();
+        event_file_paths
+    }
+
+    /// Reads the events of an event file in the order they were traced
+    ///
+    /// A truncated event at the end of the file is returned as an error after all complete
+    /// events were passed to `callback`.
+    pub fn read_events(
+        event_file_path: &Path,
+        mut callback: impl FnMut(TimedTracedEvent),
+    ) -> Result<(), TraceError> {
+        let mut reader = BufReader::new(File::open(event_file_path)?);
+        // Look ahead without consuming, so that an empty file or reaching EOF at an event
+        // boundary ends the stream
+        while !reader.fill_buf()?.is_empty() {
+            callback(bincode::deserialize_from(&mut reader)?);
+        }
+        Ok(())
+    }
+
     fn spawn_background_thread(
         trace_receiver: Receiver<TimedTracedEvent>,
         mut file_appender: RollingFileAppender<RollingConditionGrouped>,
@@ -644,6 +736,89 @@ mod tests {
         for_test::drop_and_clean_temp_dir_unless_suppressed(temp_dir);
     }
 
+    #[test]
+    fn test_event_file_paths_and_read_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("banking-trace");
+        std::fs::create_dir_all(&path).unwrap();
+        assert!(BankingTracer::event_file_paths(&path).is_empty());
+
+        let event = |slot| {
+            bincode::serialize(&TimedTracedEvent(
+                SystemTime::now(),
+                TracedEvent::BlockAndBankHash(slot, Hash::default(), Hash::default()),
+            ))
+            .unwrap()
+        };
+        let slots = |event_file_path: &Path| {
+            let mut slots = vec![];
+            let result = BankingTracer::read_events(event_file_path, |event| {
+                let TimedTracedEvent(_, TracedEvent::BlockAndBankHash(slot, _, _)) = event else {
+                    panic!("unexpected event");
+                };
+                slots.push(slot);
+            });
+            (slots, result)
+        };
+
+        // The rotated file holds the older events, the newest one ends with a truncated event
+        std::fs::write(
+            path.join(format!("{BASENAME}.1")),
+            [event(1), event(2)].concat(),
+        )
+        .unwrap();
+        let mut newest = [event(3), event(4)].concat();
+        newest.pop();
+        std::fs::write(path.join(BASENAME), newest).unwrap();
+
+        assert_eq!(
+            BankingTracer::event_file_paths(&path),
+            vec![path.join(format!("{BASENAME}.1")), path.join(BASENAME)]
+        );
+        assert_matches!(
+            slots(&path.join(format!("{BASENAME}.1"))),
+            (slots, Ok(())) if slots == [1, 2]
+        );
+        assert_matches!(
+            slots(&path.join(BASENAME)),
+            (slots, Err(TraceError::SerializeError(_))) if slots == [3]
+        );
+
+        for_test::drop_and_clean_temp_dir_unless_suppressed(temp_dir);
+    }
+
+    #[test]
+    fn test_format_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("banking-trace");
+
+        std::fs::create_dir_all(&path).unwrap();
+        assert_eq!(BankingTracer::read_format_version(&path).unwrap(), 1);
+
+        BankingTracer::create_file_appender(&path, TRACE_FILE_DEFAULT_ROTATE_BYTE_THRESHOLD)
+            .unwrap();
+        assert_eq!(
+            BankingTracer::read_format_version(&path).unwrap(),
+            BANKING_TRACE_FORMAT_VERSION
+        );
+
+        std::fs::write(path.join(VERSION_FILE_NAME), "9999\n").unwrap();
+        assert_matches!(
+            BankingTracer::read_format_version(&path),
+            Err(TraceError::UnsupportedFormatVersion(
+                9999,
+                BANKING_TRACE_FORMAT_VERSION
+            ))
+        );
+        std::fs::write(path.join(VERSION_FILE_NAME), "foo").unwrap();
+        assert_matches!(
+            BankingTracer::read_format_version(&path),
+            Err(TraceError::InvalidFormatVersion(_))
+        );
+
+        for_test::drop_and_clean_temp_dir_unless_suppressed(temp_dir);
+    }
+
     #[test]
     fn test_spill_over_at_rotation() {
         let temp_dir = TempDir::new().unwrap();
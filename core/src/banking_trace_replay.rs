@@ -0,0 +1,702 @@
+#![cfg(feature = "dev-context-only-utils")]
+//! Lightweight replay of banking trace events directly against banks, without PoH, gossip or
+//! broadcast.
+//!
+//! Unlike [`BankingSimulator`](crate::banking_simulation::BankingSimulator), which replays the
+//! trace in real time through a complete `BankingStage`, this drives a transaction scheduler and
+//! executes its batches in-line on a single thread, as fast as possible. Each replayed slot is
+//! split into [`DEFAULT_TICKS_PER_SLOT`] steps; at every step, the non-vote packets traced until
+//! then are buffered and scheduled. This keeps the arrival pattern of transactions within a slot
+//! while making the replay deterministic, so different schedulers can be compared offline.
+
+use {
+    crate::{
+        banking_simulation::BankingTraceEvents,
+        banking_stage::{
+            committer::Committer,
+            consumer::Consumer,
+            decision_maker::BufferedPacketsDecision,
+            packet_deserializer::PacketDeserializer,
+            qos_service::QosService,
+            scheduler_messages::{ConsumeWork, FinishedConsumeWork},
+            transaction_scheduler::{
+                greedy_scheduler::{GreedyScheduler, GreedySchedulerConfig},
+                prio_graph_scheduler::{PrioGraphScheduler, PrioGraphSchedulerConfig},
+                receive_and_buffer::{
+                    ReceiveAndBuffer, SanitizedTransactionReceiveAndBuffer,
+                    TransactionViewReceiveAndBuffer,
+                },
+                scheduler::{PreLockFilterAction, Scheduler},
+                scheduler_controller::SchedulerController,
+                scheduler_metrics::{
+                    SchedulerCountMetrics, SchedulerCountMetricsInner, SchedulerTimingMetrics,
+                },
+                transaction_state_container::StateContainer,
+            },
+            BankingStage, TOTAL_BUFFERED_PACKETS,
+        },
+        banking_trace::ChannelLabel,
+        validator::{BlockProductionMethod, TransactionStructure},
+    },
+    agave_banking_stage_ingress_types::BankingPacketBatch,
+    crossbeam_channel::{unbounded, Receiver, Sender},
+    log::*,
+    solana_clock::{
+        Slot, DEFAULT_MS_PER_SLOT, DEFAULT_TICKS_PER_SLOT, HOLD_TRANSACTIONS_SLOT_OFFSET,
+        MAX_PROCESSING_AGE,
+    },
+    solana_poh::{poh_recorder::BankStart, transaction_recorder::TransactionRecorder},
+    solana_runtime::{
+        bank::Bank, bank_forks::BankForks, prioritization_fee_cache::PrioritizationFeeCache,
+    },
+    std::{
+        fmt::{self, Display},
+        num::Saturating,
+        ops::Bound,
+        sync::{atomic::AtomicBool, Arc, RwLock},
+        thread::{self, JoinHandle},
+        time::{Duration, Instant, SystemTime},
+    },
+    thiserror::Error,
+};
+
+/// The maximum number of times transactions are scheduled within a step of a slot. Scheduling
+/// stops earlier once no transaction could be scheduled.
+const MAX_SCHEDULING_PASSES_PER_STEP: usize = 16;
+
+/// Packets traced this long before the first replayed slot are buffered before it starts, like
+/// the transactions a leader holds ahead of its slots.
+const HOLD_DURATION: Duration =
+    Duration::from_millis(HOLD_TRANSACTIONS_SLOT_OFFSET * DEFAULT_MS_PER_SLOT);
+
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("the banking trace has no events before slot {0}")]
+    MissingParentSlot(Slot),
+
+    #[error("the parent bank is at slot {actual}, but the banking trace expects slot {expected}")]
+    ParentSlotMismatch { expected: Slot, actual: Slot },
+
+    #[error("scheduler failed: {0}")]
+    Scheduler(String),
+}
+
+/// Replays banking trace events against banks built on top of a parent bank
+pub struct BankingTraceReplayer {
+    banking_trace_events: BankingTraceEvents,
+    first_replayed_slot: Slot,
+    num_slots: usize,
+}
+
+/// Counts for a replayed slot, or the sum over several slots
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReplayedSlotMetrics {
+    pub slot: Slot,
+    /// Non-vote packets received by the scheduler
+    pub num_received: usize,
+    /// Transactions buffered by the scheduler
+    pub num_buffered: usize,
+    /// Transactions sent to execution
+    pub num_scheduled: usize,
+    /// Transactions which could not be scheduled due to account conflicts
+    pub num_unschedulable_conflicts: usize,
+    /// Transactions which could not be scheduled due to thread capacity
+    pub num_unschedulable_threads: usize,
+    /// Scheduled transactions which were returned by execution to be retried
+    pub num_retryable: usize,
+    /// Transactions dropped for any reason: failed sanitization, lock validation, age or status
+    /// checks, or exceeded buffer capacity
+    pub num_dropped: usize,
+    /// Transactions committed to the bank
+    pub num_committed: u64,
+    /// Compute units packed into the block
+    pub block_cost: u64,
+    /// Fees collected, including priority fees
+    pub fees: u64,
+    /// Priority fees collected
+    pub priority_fees: u64,
+}
+
+impl ReplayedSlotMetrics {
+    fn new(bank: &Bank, count_metrics: &SchedulerCountMetricsInner) -> Self {
+        let SchedulerCountMetricsInner {
+            num_received: Saturating(num_received),
+            num_buffered: Saturating(num_buffered),
+            num_scheduled: Saturating(num_scheduled),
+            num_unschedulable_conflicts: Saturating(num_unschedulable_conflicts),
+            num_unschedulable_threads: Saturating(num_unschedulable_threads),
+            num_retryable: Saturating(num_retryable),
+            num_schedule_filtered_out: Saturating(num_schedule_filtered_out),
+            num_dropped_on_receive: Saturating(num_dropped_on_receive),
+            num_dropped_on_sanitization: Saturating(num_dropped_on_sanitization),
+            num_dropped_on_validate_locks: Saturating(num_dropped_on_validate_locks),
+            num_dropped_on_receive_transaction_checks:
+                Saturating(num_dropped_on_receive_transaction_checks),
+            num_dropped_on_clear: Saturating(num_dropped_on_clear),
+            num_dropped_on_age_and_status: Saturating(num_dropped_on_age_and_status),
+            num_dropped_on_capacity: Saturating(num_dropped_on_capacity),
+            ..
+        } = *count_metrics;
+        let fee_details = bank.get_collector_fee_details();
+        Self {
+            slot: bank.slot(),
+            num_received,
+            num_buffered,
+            num_scheduled,
+            num_unschedulable_conflicts,
+            num_unschedulable_threads,
+            num_retryable,
+            num_dropped: num_schedule_filtered_out
+                + num_dropped_on_receive
+                + num_dropped_on_sanitization
+                + num_dropped_on_validate_locks
+                + num_dropped_on_receive_transaction_checks
+                + num_dropped_on_clear
+                + num_dropped_on_age_and_status
+                + num_dropped_on_capacity,
+            num_committed: bank.executed_transaction_count(),
+            block_cost: bank.read_cost_tracker().unwrap().block_cost(),
+            fees: fee_details.total_transaction_fee(),
+            priority_fees: fee_details.total_priority_fee(),
+        }
+    }
+
+    fn accumulate(&mut self, other: &Self) {
+        self.num_received += other.num_received;
+        self.num_buffered += other.num_buffered;
+        self.num_scheduled += other.num_scheduled;
+        self.num_unschedulable_conflicts += other.num_unschedulable_conflicts;
+        self.num_unschedulable_threads += other.num_unschedulable_threads;
+        self.num_retryable += other.num_retryable;
+        self.num_dropped += other.num_dropped;
+        self.num_committed += other.num_committed;
+        self.block_cost += other.block_cost;
+        self.fees += other.fees;
+        self.priority_fees += other.priority_fees;
+    }
+}
+
+impl Display for ReplayedSlotMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "received: {}, buffered: {}, scheduled: {}, conflicts: {}, thread limited: {}, \
+             retryable: {}, dropped: {}, committed: {}, CUs: {}, fees: {}, priority fees: {}",
+            self.num_received,
+            self.num_buffered,
+            self.num_scheduled,
+            self.num_unschedulable_conflicts,
+            self.num_unschedulable_threads,
+            self.num_retryable,
+            self.num_dropped,
+            self.num_committed,
+            self.block_cost,
+            self.fees,
+            self.priority_fees,
+        )
+    }
+}
+
+#[derive(Clone)]
+pub struct BankingTraceReplayReport {
+    pub block_production_method: BlockProductionMethod,
+    pub slots: Vec<ReplayedSlotMetrics>,
+    /// Vote packets in the replayed time range, which are not replayed
+    pub num_skipped_vote_packets: usize,
+}
+
+impl BankingTraceReplayReport {
+    /// The sum of the metrics of all replayed slots
+    pub fn total(&self) -> ReplayedSlotMetrics {
+        let mut total = ReplayedSlotMetrics::default();
+        for slot in &self.slots {
+            total.accumulate(slot);
+        }
+        total
+    }
+}
+
+impl Display for BankingTraceReplayReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Block production method: {}",
+            self.block_production_method
+        )?;
+        for slot in &self.slots {
+            writeln!(f, "Slot {}: {slot}", slot.slot)?;
+        }
+        writeln!(f, "Total: {}", self.total())?;
+        write!(f, "Skipped vote packets: {}", self.num_skipped_vote_packets)
+    }
+}
+
+impl BankingTraceReplayer {
+    pub fn new(
+        banking_trace_events: BankingTraceEvents,
+        first_replayed_slot: Slot,
+        num_slots: usize,
+    ) -> Self {
+        Self {
+            banking_trace_events,
+            first_replayed_slot,
+            num_slots,
+        }
+    }
+
+    /// The slot of the bank the replayed slots must be built on
+    pub fn parent_slot(&self) -> Option<Slot> {
+        self.banking_trace_events
+            .parent_slot(self.first_replayed_slot)
+    }
+
+    /// Replay `num_slots` slots on top of `parent_bank` with the scheduler of
+    /// `block_production_method`
+    ///
+    /// The replayed banks are never rooted. Once replayed, they are removed from the accounts
+    /// db, status cache and program cache they share with `parent_bank`, so replays with
+    /// different schedulers can be run one after another on the same parent bank.
+    pub fn replay(
+        &self,
+        parent_bank: Arc<Bank>,
+        block_production_method: BlockProductionMethod,
+        transaction_struct: TransactionStructure,
+    ) -> Result<BankingTraceReplayReport, ReplayError> {
+        let parent_slot = self
+            .parent_slot()
+            .ok_or(ReplayError::MissingParentSlot(self.first_replayed_slot))?;
+        if parent_bank.slot() != parent_slot {
+            return Err(ReplayError::ParentSlotMismatch {
+                expected: parent_slot,
+                actual: parent_bank.slot(),
+            });
+        }
+        parent_bank.set_hash_overrides(self.banking_trace_events.hash_overrides().clone());
+
+        let num_workers = BankingStage::num_workers() as usize;
+        let (work_senders, work_receivers): (Vec<_>, Vec<_>) =
+            (0..num_workers).map(|_| unbounded()).unzip();
+        let (finished_work_sender, finished_work_receiver) = unbounded();
+        let use_greedy_scheduler = matches!(
+            block_production_method,
+            BlockProductionMethod::CentralSchedulerGreedy
+        );
+
+        macro_rules! replay_with_scheduler {
+            ($receive_and_buffer:expr) => {
+                if use_greedy_scheduler {
+                    self.replay_slots(
+                        &parent_bank,
+                        $receive_and_buffer,
+                        GreedyScheduler::new(
+                            work_senders,
+                            finished_work_receiver,
+                            GreedySchedulerConfig::default(),
+                        ),
+                        work_receivers,
+                        finished_work_sender,
+                    )
+                } else {
+                    self.replay_slots(
+                        &parent_bank,
+                        $receive_and_buffer,
+                        PrioGraphScheduler::new(
+                            work_senders,
+                            finished_work_receiver,
+                            PrioGraphSchedulerConfig::default(),
+                        ),
+                        work_receivers,
+                        finished_work_sender,
+                    )
+                }
+            };
+        }
+
+        let (packet_sender, packet_receiver) = unbounded();
+        let first_bank = Bank::new_from_parent(
+            parent_bank.clone(),
+            parent_bank.collector_id(),
+            self.first_replayed_slot,
+        );
+        let bank_forks = BankForks::new_rw_arc(first_bank);
+        let result = match transaction_struct {
+            TransactionStructure::Sdk => replay_with_scheduler!(ReplayInput {
+                receive_and_buffer: SanitizedTransactionReceiveAndBuffer::new(
+                    PacketDeserializer::new(packet_receiver),
+                    bank_forks.clone(),
+                    None,
+                ),
+                packet_sender,
+                bank_forks: bank_forks.clone(),
+            }),
+            TransactionStructure::View => replay_with_scheduler!(ReplayInput {
+                receive_and_buffer: TransactionViewReceiveAndBuffer {
+                    receiver: packet_receiver,
+                    bank_forks: bank_forks.clone(),
+                    transaction_outcome_log: None,
+                },
+                packet_sender,
+                bank_forks: bank_forks.clone(),
+            }),
+        };
+        purge_replayed_banks(&parent_bank, bank_forks);
+        let (slots, num_skipped_vote_packets) = result?;
+
+        Ok(BankingTraceReplayReport {
+            block_production_method,
+            slots,
+            num_skipped_vote_packets,
+        })
+    }
+
+    fn replay_slots<R, S>(
+        &self,
+        parent_bank: &Bank,
+        input: ReplayInput<R>,
+        mut scheduler: S,
+        work_receivers: Vec<Receiver<ConsumeWork<R::Transaction>>>,
+        finished_work_sender: Sender<FinishedConsumeWork<R::Transaction>>,
+    ) -> Result<(Vec<ReplayedSlotMetrics>, usize), ReplayError>
+    where
+        R: ReceiveAndBuffer,
+        S: Scheduler<R::Transaction>,
+    {
+        let ReplayInput {
+            mut receive_and_buffer,
+            packet_sender,
+            bank_forks,
+        } = input;
+        let (transaction_recorder, recorder_thread) = spawn_transaction_recorder();
+        let (replay_vote_sender, _replay_vote_receiver) = unbounded();
+        let consumer = Consumer::new(
+            Committer::new(
+                None,
+                replay_vote_sender,
+                Arc::new(PrioritizationFeeCache::default()),
+            ),
+            transaction_recorder,
+            QosService::new(0),
+            None,
+            None,
+        );
+        let mut container = R::Container::with_capacity(TOTAL_BUFFERED_PACKETS);
+        let mut timing_metrics = SchedulerTimingMetrics::default();
+
+        let packet_batches_by_time = self.banking_trace_events.packet_batches_by_time();
+        let freeze_time_by_slot = self.banking_trace_events.freeze_time_by_slot();
+        let parent_slot = parent_bank.slot();
+        let mut slot_start = freeze_time_by_slot[&parent_slot];
+        let mut sent_until = Bound::Included(slot_start - HOLD_DURATION);
+        let mut num_skipped_vote_packets = 0;
+        let mut slots = Vec::with_capacity(self.num_slots);
+
+        let mut bank = bank_forks.read().unwrap().working_bank();
+        for index in 0..self.num_slots {
+            if index > 0 {
+                let slot = bank.slot() + 1;
+                bank.fill_bank_with_ticks_for_tests();
+                let new_bank = Bank::new_from_parent(bank.clone(), bank.collector_id(), slot);
+                bank = bank_forks
+                    .write()
+                    .unwrap()
+                    .insert(new_bank)
+                    .clone_without_scheduler();
+            }
+            let slot_end = freeze_time_by_slot
+                .get(&bank.slot())
+                .copied()
+                .unwrap_or(slot_start + Duration::from_millis(DEFAULT_MS_PER_SLOT))
+                .max(slot_start);
+            let step = slot_end.duration_since(slot_start).unwrap() / DEFAULT_TICKS_PER_SLOT as u32;
+            let decision = BufferedPacketsDecision::Consume(BankStart {
+                working_bank: bank.clone(),
+                bank_creation_time: Arc::new(Instant::now()),
+            });
+            let mut count_metrics = SchedulerCountMetrics::default();
+            count_metrics.maybe_report_and_reset_slot(Some(bank.slot()));
+
+            for tick in 1..=DEFAULT_TICKS_PER_SLOT {
+                let step_end = if tick == DEFAULT_TICKS_PER_SLOT {
+                    slot_end
+                } else {
+                    slot_start + step * tick as u32
+                };
+                for (_time, (label, batch)) in
+                    packet_batches_by_time.range((sent_until, Bound::Excluded(step_end)))
+                {
+                    match label {
+                        ChannelLabel::NonVote => packet_sender.send(batch.clone()).unwrap(),
+                        ChannelLabel::TpuVote | ChannelLabel::GossipVote => {
+                            num_skipped_vote_packets += num_packets(batch);
+                        }
+                        ChannelLabel::Dummy => {}
+                    }
+                }
+                sent_until = Bound::Included(step_end);
+
+                while !packet_sender.is_empty() {
+                    // The sender is held, so the receiver cannot be disconnected
+                    let _ = receive_and_buffer.receive_and_buffer_packets(
+                        &mut container,
+                        &mut timing_metrics,
+                        &mut count_metrics,
+                        &decision,
+                    );
+                }
+
+                for _ in 0..MAX_SCHEDULING_PASSES_PER_STEP {
+                    let summary = scheduler
+                        .schedule(
+                            &mut container,
+                            |txs, results| {
+                                SchedulerController::<R, S>::pre_graph_filter(
+                                    txs,
+                                    results,
+                                    &bank,
+                                    MAX_PROCESSING_AGE,
+                                    None,
+                                )
+                            },
+                            |_| PreLockFilterAction::AttemptToSchedule,
+                        )
+                        .map_err(|err| ReplayError::Scheduler(err.to_string()))?;
+                    count_metrics.update(|count_metrics| {
+                        count_metrics.num_scheduled += summary.num_scheduled;
+                        count_metrics.num_unschedulable_conflicts +=
+                            summary.num_unschedulable_conflicts;
+                        count_metrics.num_unschedulable_threads +=
+                            summary.num_unschedulable_threads;
+                        count_metrics.num_schedule_filtered_out += summary.num_filtered_out;
+                    });
+                    if summary.num_scheduled == 0 {
+                        break;
+                    }
+
+                    for work in work_receivers
+                        .iter()
+                        .flat_map(|receiver| receiver.try_iter())
+                    {
+                        let output = consumer.process_and_record_aged_transactions(
+                            &bank,
+                            &work.transactions,
+                            &work.max_ages,
+                        );
+                        let retryable_indexes = output
+                            .execute_and_commit_transactions_output
+                            .retryable_transaction_indexes;
+                        finished_work_sender
+                            .send(FinishedConsumeWork {
+                                work,
+                                retryable_indexes,
+                            })
+                            .unwrap();
+                    }
+                    let (num_finished, num_retryable) = scheduler
+                        .receive_completed(&mut container)
+                        .map_err(|err| ReplayError::Scheduler(err.to_string()))?;
+                    count_metrics.update(|count_metrics| {
+                        count_metrics.num_finished += num_finished;
+                        count_metrics.num_retryable += num_retryable;
+                    });
+                }
+            }
+
+            let metrics = ReplayedSlotMetrics::new(&bank, count_metrics.slot_metrics());
+            info!("Replayed slot {}: {metrics}", bank.slot());
+            slots.push(metrics);
+            slot_start = slot_end;
+        }
+        bank.fill_bank_with_ticks_for_tests();
+        bank.freeze();
+
+        drop(consumer);
+        recorder_thread.join().unwrap();
+        Ok((slots, num_skipped_vote_packets))
+    }
+}
+
+struct ReplayInput<R> {
+    receive_and_buffer: R,
+    packet_sender: Sender<BankingPacketBatch>,
+    bank_forks: Arc<RwLock<BankForks>>,
+}
+
+/// Removes the replayed banks of `bank_forks` from the accounts db, status cache and program
+/// cache they share with `parent_bank`
+fn purge_replayed_banks(parent_bank: &Bank, bank_forks: Arc<RwLock<BankForks>>) {
+    let replayed_slots: Vec<_> = bank_forks
+        .read()
+        .unwrap()
+        .banks()
+        .values()
+        .filter(|bank| bank.slot() > parent_bank.slot())
+        .map(|bank| (bank.slot(), bank.bank_id()))
+        .collect();
+    // As when replay stage purges a duplicate slot, the accounts are removed before the banks
+    // are dropped, so that dropping them does not purge the slots again
+    parent_bank.remove_unrooted_slots(&replayed_slots);
+    drop(bank_forks);
+    for (slot, _bank_id) in replayed_slots {
+        parent_bank.clear_slot_signatures(slot);
+        parent_bank.prune_program_cache_by_deployment_slot(slot);
+    }
+}
+
+fn num_packets(batch: &BankingPacketBatch) -> usize {
+    batch.iter().map(|packet_batch| packet_batch.len()).sum()
+}
+
+/// Stands in for the PoH service, accepting every record
+fn spawn_transaction_recorder() -> (TransactionRecorder, JoinHandle<()>) {
+    let (record_sender, record_receiver) = unbounded();
+    let recorder_thread = thread::Builder::new()
+        .name("solBtReplayRec".to_string())
+        .spawn(move || {
+            for record in record_receiver {
+                let _ = record.sender.send(Ok(None));
+            }
+        })
+        .unwrap();
+    (
+        TransactionRecorder::new(record_sender, Arc::new(AtomicBool::default())),
+        recorder_thread,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::banking_trace::{TimedTracedEvent, TracedEvent},
+        solana_hash::Hash,
+        solana_ledger::genesis_utils::{create_genesis_config, GenesisConfigInfo},
+        solana_perf::packet::to_packet_batches,
+        solana_system_transaction as system_transaction,
+    };
+
+    #[test]
+    fn test_replayed_slot_metrics_total() {
+        let report = BankingTraceReplayReport {
+            block_production_method: BlockProductionMethod::CentralSchedulerGreedy,
+            slots: vec![
+                ReplayedSlotMetrics {
+                    slot: 1,
+                    num_scheduled: 3,
+                    block_cost: 100,
+                    fees: 10,
+                    ..ReplayedSlotMetrics::default()
+                },
+                ReplayedSlotMetrics {
+                    slot: 2,
+                    num_scheduled: 4,
+                    num_dropped: 1,
+                    block_cost: 50,
+                    fees: 5,
+                    ..ReplayedSlotMetrics::default()
+                },
+            ],
+            num_skipped_vote_packets: 0,
+        };
+        assert_eq!(
+            report.total(),
+            ReplayedSlotMetrics {
+                slot: 0,
+                num_scheduled: 7,
+                num_dropped: 1,
+                block_cost: 150,
+                fees: 15,
+                ..ReplayedSlotMetrics::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parent_slot() {
+        let mut events = BankingTraceEvents::default();
+        let now = SystemTime::now();
+        for (offset, slot) in [(0, 3), (1, 5)] {
+            events.load_event(TimedTracedEvent(
+                now + Duration::from_millis(offset),
+                TracedEvent::BlockAndBankHash(slot, Hash::default(), Hash::default()),
+            ));
+        }
+        assert_eq!(
+            BankingTraceReplayer::new(events, 5, 4).parent_slot(),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_replay() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(1_000_000_000);
+        let parent_bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let transactions: Vec<_> = (0..5)
+            .map(|_| {
+                system_transaction::transfer(
+                    &mint_keypair,
+                    &solana_pubkey::new_rand(),
+                    10_000_000,
+                    genesis_config.hash(),
+                )
+            })
+            .collect();
+
+        let mut events = BankingTraceEvents::default();
+        let now = SystemTime::now();
+        events.load_event(TimedTracedEvent(
+            now,
+            TracedEvent::BlockAndBankHash(0, Hash::default(), Hash::default()),
+        ));
+        events.load_event(TimedTracedEvent(
+            now + Duration::from_millis(10),
+            TracedEvent::PacketBatch(
+                ChannelLabel::NonVote,
+                BankingPacketBatch::new(to_packet_batches(&transactions[..4], 2)),
+            ),
+        ));
+        events.load_event(TimedTracedEvent(
+            now + Duration::from_millis(20),
+            TracedEvent::PacketBatch(
+                ChannelLabel::TpuVote,
+                BankingPacketBatch::new(to_packet_batches(&transactions[4..], 1)),
+            ),
+        ));
+        let replayer = BankingTraceReplayer::new(events, 1, 2);
+
+        // Both replays commit the same transactions, as the first one is purged from the parent
+        for (block_production_method, transaction_struct) in [
+            (
+                BlockProductionMethod::CentralSchedulerGreedy,
+                TransactionStructure::View,
+            ),
+            (
+                BlockProductionMethod::CentralScheduler,
+                TransactionStructure::Sdk,
+            ),
+        ] {
+            let report = replayer
+                .replay(
+                    parent_bank.clone(),
+                    block_production_method,
+                    transaction_struct,
+                )
+                .unwrap();
+            assert_eq!(
+                report
+                    .slots
+                    .iter()
+                    .map(|slot| slot.slot)
+                    .collect::<Vec<_>>(),
+                vec![1, 2]
+            );
+            let total = report.total();
+            assert_eq!(total.num_received, 4);
+            assert_eq!(total.num_committed, 4);
+            assert_eq!(report.num_skipped_vote_packets, 1);
+        }
+    }
+}
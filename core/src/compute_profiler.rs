@@ -0,0 +1,564 @@
+//! Aggregates the compute units and wall time spent per program, and per
+//! instruction of each program, from the instruction traces recorded while
+//! executing transactions. Used by `agave-ledger-tool compute-profile` and by
+//! the validator's `--compute-profile-path` mode.
+//!
+//! The traces are queued for a profiler thread which aggregates them, so that
+//! executing transactions does not contend on the statistics.
+
+use {
+    crossbeam_channel::{bounded, Receiver, Sender, TrySendError},
+    histogram::Histogram,
+    serde_derive::Serialize,
+    solana_clock::Slot,
+    solana_metrics::inc_new_counter_info,
+    solana_program_runtime::invoke_context::InstructionTrace,
+    solana_pubkey::Pubkey,
+    solana_signature::Signature,
+    solana_svm::instruction_trace_notifier::InstructionTraceNotifier,
+    std::{
+        collections::{HashMap, HashSet},
+        ops::RangeInclusive,
+        sync::{Arc, Mutex},
+        thread::Builder,
+    },
+};
+
+/// Number of leading instruction data bytes that identify an instruction of a
+/// program, e.g. an Anchor discriminator
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+/// Maximum number of distinct discriminators tracked per program, the
+/// invocations of any further discriminators are grouped together under
+/// [`OTHER_INSTRUCTIONS`]
+pub const MAX_INSTRUCTIONS_PER_PROGRAM: usize = 256;
+
+/// Discriminator reported for the invocations beyond
+/// [`MAX_INSTRUCTIONS_PER_PROGRAM`]
+pub const OTHER_INSTRUCTIONS: &str = "other";
+
+const TOP_LEVEL_STACK_HEIGHT: usize = solana_instruction::TRANSACTION_LEVEL_STACK_HEIGHT;
+
+/// The maximum number of transactions whose instruction traces wait to be
+/// aggregated. Once reached, executing transactions waits for the profiler
+/// thread to catch up.
+const MAX_QUEUED_TRANSACTIONS: usize = 10_000;
+
+#[derive(Default)]
+struct ExecutionStats {
+    count: u64,
+    compute_units: u64,
+    elapsed_ns: u64,
+    compute_units_hist: Histogram,
+    elapsed_ns_hist: Histogram,
+}
+
+impl ExecutionStats {
+    fn record(&mut self, trace: &InstructionTrace) {
+        self.count = self.count.saturating_add(1);
+        self.compute_units = self
+            .compute_units
+            .saturating_add(trace.compute_units_consumed);
+        self.elapsed_ns = self.elapsed_ns.saturating_add(trace.elapsed_ns);
+        let _ = self
+            .compute_units_hist
+            .increment(trace.compute_units_consumed);
+        let _ = self.elapsed_ns_hist.increment(trace.elapsed_ns);
+    }
+
+    fn report(&self) -> ExecutionStatsReport {
+        ExecutionStatsReport {
+            count: self.count,
+            total_compute_units: self.compute_units,
+            avg_compute_units: self.compute_units.checked_div(self.count).unwrap_or(0),
+            p99_compute_units: self.compute_units_hist.percentile(99.0).unwrap_or(0),
+            total_elapsed_ns: self.elapsed_ns,
+            avg_elapsed_ns: self.elapsed_ns.checked_div(self.count).unwrap_or(0),
+            p99_elapsed_ns: self.elapsed_ns_hist.percentile(99.0).unwrap_or(0),
+        }
+    }
+}
+
+/// Compute units consumed by transactions compared to the compute unit limits
+/// they requested
+#[derive(Default)]
+struct LimitStats {
+    transactions: u64,
+    compute_units_consumed: u64,
+    compute_unit_limit: u64,
+    utilization_percent_hist: Histogram,
+}
+
+impl LimitStats {
+    fn record(&mut self, compute_units_consumed: u64, compute_unit_limit: u64) {
+        self.transactions = self.transactions.saturating_add(1);
+        self.compute_units_consumed = self
+            .compute_units_consumed
+            .saturating_add(compute_units_consumed);
+        self.compute_unit_limit = self.compute_unit_limit.saturating_add(compute_unit_limit);
+        if let Some(percent) = compute_units_consumed
+            .saturating_mul(100)
+            .checked_div(compute_unit_limit)
+        {
+            let _ = self.utilization_percent_hist.increment(percent);
+        }
+    }
+
+    fn report(&self) -> LimitStatsReport {
+        LimitStatsReport {
+            transactions: self.transactions,
+            compute_units_consumed: self.compute_units_consumed,
+            compute_unit_limit: self.compute_unit_limit,
+            utilization: if self.compute_unit_limit == 0 {
+                0.0
+            } else {
+                self.compute_units_consumed as f64 / self.compute_unit_limit as f64
+            },
+            p50_utilization_percent: self.utilization_percent_hist.percentile(50.0).unwrap_or(0),
+            p99_utilization_percent: self.utilization_percent_hist.percentile(99.0).unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ProgramStats {
+    execution: ExecutionStats,
+    /// Transactions invoking the program at the top level
+    limits: LimitStats,
+    instructions: HashMap<Vec<u8>, ExecutionStats>,
+    /// Invocations with a discriminator not in `instructions` once it is full
+    other_instructions: ExecutionStats,
+}
+
+impl ProgramStats {
+    fn record_instruction(&mut self, trace: &InstructionTrace) {
+        let discriminator_len = trace.instruction_data.len().min(DISCRIMINATOR_LEN);
+        let discriminator = &trace.instruction_data[..discriminator_len];
+        if let Some(execution) = self.instructions.get_mut(discriminator) {
+            execution.record(trace);
+        } else if self.instructions.len() < MAX_INSTRUCTIONS_PER_PROGRAM {
+            self.instructions
+                .entry(discriminator.to_vec())
+                .or_default()
+                .record(trace);
+        } else {
+            self.other_instructions.record(trace);
+        }
+    }
+}
+
+#[derive(Default)]
+struct ProfileStats {
+    first_slot: Option<Slot>,
+    last_slot: Option<Slot>,
+    limits: LimitStats,
+    programs: HashMap<Pubkey, ProgramStats>,
+}
+
+impl ProfileStats {
+    fn record(
+        &mut self,
+        slot: Slot,
+        compute_unit_limit: u64,
+        instruction_traces: &[InstructionTrace],
+    ) {
+        let compute_units_consumed = instruction_traces
+            .iter()
+            .filter(|trace| trace.stack_height == TOP_LEVEL_STACK_HEIGHT)
+            .map(|trace| trace.compute_units_consumed)
+            .fold(0u64, u64::saturating_add);
+
+        self.first_slot = Some(self.first_slot.map_or(slot, |first| first.min(slot)));
+        self.last_slot = Some(self.last_slot.map_or(slot, |last| last.max(slot)));
+        self.limits
+            .record(compute_units_consumed, compute_unit_limit);
+
+        let mut top_level_programs = HashSet::new();
+        for trace in instruction_traces {
+            let program_stats = self.programs.entry(trace.program_id).or_default();
+            program_stats.execution.record(trace);
+            program_stats.record_instruction(trace);
+            if trace.stack_height == TOP_LEVEL_STACK_HEIGHT
+                && top_level_programs.insert(trace.program_id)
+            {
+                program_stats
+                    .limits
+                    .record(compute_units_consumed, compute_unit_limit);
+            }
+        }
+    }
+}
+
+enum ProfilerMessage {
+    Traces {
+        slot: Slot,
+        compute_unit_limit: u64,
+        instruction_traces: Vec<InstructionTrace>,
+    },
+    /// Acknowledged once the traces queued before it are aggregated
+    Flush(Sender<()>),
+}
+
+/// Collects per program compute statistics of the transactions executed in a
+/// range of slots
+///
+/// The profiler thread exits once the profiler is dropped.
+pub struct ComputeProfiler {
+    slots: RangeInclusive<Slot>,
+    sender: Sender<ProfilerMessage>,
+    stats: Arc<Mutex<ProfileStats>>,
+}
+
+impl Default for ComputeProfiler {
+    fn default() -> Self {
+        Self::new(0..=Slot::MAX)
+    }
+}
+
+impl ComputeProfiler {
+    /// Profile the transactions executed in `slots`, transactions of other
+    /// slots are ignored
+    pub fn new(slots: RangeInclusive<Slot>) -> Self {
+        let (sender, receiver) = bounded(MAX_QUEUED_TRANSACTIONS);
+        let stats = Arc::<Mutex<ProfileStats>>::default();
+        Builder::new()
+            .name("solComputeProf".to_string())
+            .spawn({
+                let stats = stats.clone();
+                move || Self::run(receiver, stats)
+            })
+            .unwrap();
+        Self {
+            slots,
+            sender,
+            stats,
+        }
+    }
+
+    fn run(receiver: Receiver<ProfilerMessage>, stats: Arc<Mutex<ProfileStats>>) {
+        for message in receiver {
+            match message {
+                ProfilerMessage::Traces {
+                    slot,
+                    compute_unit_limit,
+                    instruction_traces,
+                } => stats
+                    .lock()
+                    .unwrap()
+                    .record(slot, compute_unit_limit, &instruction_traces),
+                ProfilerMessage::Flush(flushed_sender) => {
+                    let _ = flushed_sender.send(());
+                }
+            }
+        }
+    }
+
+    /// Returns the statistics aggregated so far, including those of all the
+    /// traces notified before, with programs ordered by the total compute units
+    /// they consumed
+    pub fn report(&self) -> ComputeProfileReport {
+        let (flushed_sender, flushed_receiver) = bounded(1);
+        if self
+            .sender
+            .send(ProfilerMessage::Flush(flushed_sender))
+            .is_ok()
+        {
+            let _ = flushed_receiver.recv();
+        }
+        let stats = self.stats.lock().unwrap();
+        let mut programs: Vec<_> = stats
+            .programs
+            .iter()
+            .map(|(program_id, program_stats)| {
+                let mut instructions: Vec<_> = program_stats
+                    .instructions
+                    .iter()
+                    .map(|(discriminator, execution)| InstructionProfile {
+                        discriminator: discriminator
+                            .iter()
+                            .map(|byte| format!("{byte:02x}"))
+                            .collect(),
+                        execution: execution.report(),
+                    })
+                    .collect();
+                if program_stats.other_instructions.count > 0 {
+                    instructions.push(InstructionProfile {
+                        discriminator: OTHER_INSTRUCTIONS.to_string(),
+                        execution: program_stats.other_instructions.report(),
+                    });
+                }
+                instructions.sort_by(|a, b| {
+                    b.execution
+                        .total_compute_units
+                        .cmp(&a.execution.total_compute_units)
+                        .then_with(|| a.discriminator.cmp(&b.discriminator))
+                });
+                ProgramProfile {
+                    program_id: program_id.to_string(),
+                    execution: program_stats.execution.report(),
+                    top_level_transactions: program_stats.limits.report(),
+                    instructions,
+                }
+            })
+            .collect();
+        programs.sort_by(|a, b| {
+            b.execution
+                .total_compute_units
+                .cmp(&a.execution.total_compute_units)
+                .then_with(|| a.program_id.cmp(&b.program_id))
+        });
+        ComputeProfileReport {
+            first_slot: stats.first_slot,
+            last_slot: stats.last_slot,
+            transactions: stats.limits.report(),
+            programs,
+        }
+    }
+}
+
+impl InstructionTraceNotifier for ComputeProfiler {
+    fn notify_instruction_traces(
+        &self,
+        slot: Slot,
+        _signature: &Signature,
+        compute_unit_limit: u64,
+        instruction_traces: &[InstructionTrace],
+    ) {
+        if !self.slots.contains(&slot) || instruction_traces.is_empty() {
+            return;
+        }
+        let message = ProfilerMessage::Traces {
+            slot,
+            compute_unit_limit,
+            instruction_traces: instruction_traces.to_vec(),
+        };
+        let message = match self.sender.try_send(message) {
+            Ok(()) => return,
+            Err(TrySendError::Full(message)) => {
+                inc_new_counter_info!("compute-profiler-queue-full", 1);
+                message
+            }
+            Err(TrySendError::Disconnected(_)) => return,
+        };
+        // The profiler thread has exited if this fails, so the traces are dropped
+        let _ = self.sender.send(message);
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComputeProfileReport {
+    pub first_slot: Option<Slot>,
+    pub last_slot: Option<Slot>,
+    pub transactions: LimitStatsReport,
+    pub programs: Vec<ProgramProfile>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgramProfile {
+    pub program_id: String,
+    /// Invocations of the program, including cross-program invocations. The
+    /// compute units and wall time of an invocation include those of its
+    /// inner instructions.
+    #[serde(flatten)]
+    pub execution: ExecutionStatsReport,
+    /// Transactions invoking the program from a top level instruction
+    pub top_level_transactions: LimitStatsReport,
+    /// Invocations grouped by the hex encoded first `DISCRIMINATOR_LEN` bytes
+    /// of their instruction data, for up to `MAX_INSTRUCTIONS_PER_PROGRAM`
+    /// discriminators and then under `OTHER_INSTRUCTIONS`
+    pub instructions: Vec<InstructionProfile>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstructionProfile {
+    pub discriminator: String,
+    #[serde(flatten)]
+    pub execution: ExecutionStatsReport,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionStatsReport {
+    pub count: u64,
+    pub total_compute_units: u64,
+    pub avg_compute_units: u64,
+    pub p99_compute_units: u64,
+    pub total_elapsed_ns: u64,
+    pub avg_elapsed_ns: u64,
+    pub p99_elapsed_ns: u64,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LimitStatsReport {
+    pub transactions: u64,
+    pub compute_units_consumed: u64,
+    /// Sum of the compute unit limits requested by the transactions
+    pub compute_unit_limit: u64,
+    /// Ratio of the compute units consumed to the compute unit limits
+    pub utilization: f64,
+    pub p50_utilization_percent: u64,
+    pub p99_utilization_percent: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trace(
+        program_id: Pubkey,
+        stack_height: usize,
+        compute_units_before: u64,
+        compute_units_consumed: u64,
+        instruction_data: &[u8],
+    ) -> InstructionTrace {
+        InstructionTrace {
+            program_id,
+            stack_height,
+            compute_units_before,
+            compute_units_consumed,
+            compute_units_after: compute_units_before - compute_units_consumed,
+            elapsed_ns: compute_units_consumed,
+            instruction_data: instruction_data.to_vec(),
+            ..InstructionTrace::default()
+        }
+    }
+
+    #[test]
+    fn test_compute_profiler() {
+        let caller = Pubkey::new_unique();
+        let callee = Pubkey::new_unique();
+        let profiler = ComputeProfiler::new(10..=20);
+
+        // Caller consumes 1_000 units, 400 of them in its invocation of callee
+        profiler.notify_instruction_traces(
+            10,
+            &Signature::default(),
+            10_000,
+            &[
+                trace(caller, 1, 10_000, 1_000, &[1, 2, 3, 4, 5, 6, 7, 8, 9]),
+                trace(callee, 2, 9_500, 400, &[7]),
+                trace(callee, 1, 9_000, 1_500, &[7]),
+            ],
+        );
+        // Slots outside of the range are ignored
+        profiler.notify_instruction_traces(
+            21,
+            &Signature::default(),
+            10_000,
+            &[trace(caller, 1, 10_000, 1_000, &[])],
+        );
+
+        let report = profiler.report();
+        assert_eq!(report.first_slot, Some(10));
+        assert_eq!(report.last_slot, Some(10));
+        assert_eq!(report.transactions.transactions, 1);
+        assert_eq!(report.transactions.compute_units_consumed, 2_500);
+        assert_eq!(report.transactions.compute_unit_limit, 10_000);
+        assert_eq!(report.transactions.utilization, 0.25);
+
+        let [callee_profile, caller_profile] = report.programs.as_slice() else {
+            panic!("unexpected programs: {:?}", report.programs);
+        };
+        assert_eq!(callee_profile.program_id, callee.to_string());
+        assert_eq!(callee_profile.execution.count, 2);
+        assert_eq!(callee_profile.execution.total_compute_units, 1_900);
+        assert_eq!(callee_profile.execution.avg_compute_units, 950);
+        assert_eq!(callee_profile.top_level_transactions.transactions, 1);
+        assert_eq!(callee_profile.instructions.len(), 1);
+        assert_eq!(callee_profile.instructions[0].discriminator, "07");
+
+        assert_eq!(caller_profile.program_id, caller.to_string());
+        assert_eq!(caller_profile.execution.total_compute_units, 1_000);
+        assert_eq!(
+            caller_profile.instructions[0].discriminator,
+            "0102030405060708"
+        );
+        assert_eq!(
+            caller_profile.top_level_transactions.compute_units_consumed,
+            2_500
+        );
+    }
+
+    #[test]
+    fn test_notify_does_not_wait_for_aggregation() {
+        let program = Pubkey::new_unique();
+        let profiler = ComputeProfiler::default();
+
+        // Notifying does not wait for the profiler thread, which can't aggregate
+        // the traces while the statistics are locked
+        let stats = profiler.stats.lock().unwrap();
+        for _ in 0..2 {
+            profiler.notify_instruction_traces(
+                0,
+                &Signature::default(),
+                10_000,
+                &[trace(program, 1, 10_000, 100, &[])],
+            );
+        }
+        drop(stats);
+
+        let report = profiler.report();
+        assert_eq!(report.transactions.transactions, 2);
+        assert_eq!(report.programs[0].execution.total_compute_units, 200);
+    }
+
+    #[test]
+    fn test_compute_unit_limit() {
+        let program = Pubkey::new_unique();
+        let profiler = ComputeProfiler::default();
+
+        // The requested limit is reported even if the first instruction starts
+        // with fewer units available, e.g. after loading accounts
+        profiler.notify_instruction_traces(
+            0,
+            &Signature::default(),
+            200_000,
+            &[trace(program, 1, 150_000, 50_000, &[])],
+        );
+        let report = profiler.report();
+        assert_eq!(report.transactions.compute_unit_limit, 200_000);
+        assert_eq!(
+            report.programs[0].top_level_transactions.compute_unit_limit,
+            200_000
+        );
+    }
+
+    #[test]
+    fn test_max_instructions_per_program() {
+        let program = Pubkey::new_unique();
+        let profiler = ComputeProfiler::default();
+        for discriminator in 0..MAX_INSTRUCTIONS_PER_PROGRAM as u16 + 2 {
+            profiler.notify_instruction_traces(
+                0,
+                &Signature::default(),
+                10_000,
+                &[trace(program, 1, 10_000, 100, &discriminator.to_le_bytes())],
+            );
+        }
+        // A known discriminator is still tracked once the limit is reached
+        profiler.notify_instruction_traces(
+            0,
+            &Signature::default(),
+            10_000,
+            &[trace(program, 1, 10_000, 100, &0u16.to_le_bytes())],
+        );
+
+        let report = profiler.report();
+        let instructions = &report.programs[0].instructions;
+        assert_eq!(instructions.len(), MAX_INSTRUCTIONS_PER_PROGRAM + 1);
+        let other = instructions
+            .iter()
+            .find(|instruction| instruction.discriminator == OTHER_INSTRUCTIONS)
+            .unwrap();
+        assert_eq!(other.execution.count, 2);
+        let first = instructions
+            .iter()
+            .find(|instruction| instruction.discriminator == "0000")
+            .unwrap();
+        assert_eq!(first.execution.count, 2);
+    }
+}
@@ -12,10 +12,12 @@ pub mod admin_rpc_post_init;
 pub mod banking_simulation;
 pub mod banking_stage;
 pub mod banking_trace;
+pub mod banking_trace_replay;
 pub mod cluster_info_vote_listener;
 pub mod cluster_slots_service;
 pub mod commitment_service;
 pub mod completed_data_sets_service;
+pub mod compute_profiler;
 pub mod consensus;
 pub mod cost_update_service;
 pub mod drop_bank_service;
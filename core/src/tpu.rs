@@ -13,7 +13,7 @@ pub use {
 use {
     crate::{
         admin_rpc_post_init::{KeyUpdaterType, KeyUpdaters},
-        banking_stage::BankingStage,
+        banking_stage::{transaction_outcome_log::TransactionOutcomeLog, BankingStage},
         banking_trace::{Channels, TracerThread},
         cluster_info_vote_listener::{
             ClusterInfoVoteListener, DuplicateConfirmedSlotsSender, GossipVerifiedVoteHashSender,
@@ -30,6 +30,8 @@ use {
         validator::{BlockProductionMethod, GeneratorConfig, TransactionStructure},
         vortexor_receiver_adapter::VortexorReceiverAdapter,
     },
+    agave_banking_stage_ingress_types::BankingBundleReceiver,
+    agave_scheduler_bindings::SharedRegion,
     bytes::Bytes,
     crossbeam_channel::{bounded, unbounded, Receiver},
     solana_clock::Slot,
@@ -161,6 +163,9 @@ impl Tpu {
         enable_block_production_forwarding: bool,
         _generator_config: Option<GeneratorConfig>, /* vestigial code for replay invalidator */
         key_notifiers: Arc<RwLock<KeyUpdaters>>,
+        transaction_outcome_log: Option<Arc<TransactionOutcomeLog>>,
+        external_scheduler_region: Option<SharedRegion>,
+        bundle_receiver: Option<BankingBundleReceiver>,
     ) -> Self {
         let TpuSockets {
             transactions: transactions_sockets,
@@ -337,6 +342,9 @@ impl Tpu {
             log_messages_bytes_limit,
             bank_forks.clone(),
             prioritization_fee_cache,
+            transaction_outcome_log,
+            external_scheduler_region,
+            bundle_receiver,
         );
 
         let SpawnForwardingStageResult {
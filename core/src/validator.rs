@@ -4,9 +4,11 @@ pub use solana_perf::report_target_features;
 use {
     crate::{
         admin_rpc_post_init::{AdminRpcRequestMetadataPostInit, KeyUpdaterType, KeyUpdaters},
+        banking_stage::{transaction_outcome_log::TransactionOutcomeLog, BankingStage},
         banking_trace::{self, BankingTracer, TraceError},
         cluster_info_vote_listener::VoteTracker,
         completed_data_sets_service::CompletedDataSetsService,
+        compute_profiler::ComputeProfiler,
         consensus::{
             reconcile_blockstore_roots_with_external_source,
             tower_storage::{NullTowerStorage, TowerStorage},
@@ -28,6 +30,8 @@ use {
         tpu::{ForwardingClientOption, Tpu, TpuSockets, DEFAULT_TPU_COALESCE},
         tvu::{Tvu, TvuConfig, TvuSockets},
     },
+    agave_banking_stage_ingress_types::BUNDLE_CHANNEL_CAPACITY,
+    agave_scheduler_bindings::SharedRegion,
     anyhow::{anyhow, Context, Result},
     crossbeam_channel::{bounded, unbounded, Receiver},
     quinn::Endpoint,
@@ -74,13 +78,16 @@ use {
         leader_schedule_cache::LeaderScheduleCache,
         use_snapshot_archives_at_startup::UseSnapshotArchivesAtStartup,
     },
-    solana_measure::measure::Measure,
+    solana_measure::{measure::Measure, measure_us},
     solana_metrics::{datapoint_info, metrics::metrics_config_sanity_check},
     solana_poh::{
         poh_recorder::PohRecorder,
         poh_service::{self, PohService},
         transaction_recorder::TransactionRecorder,
     },
+    solana_program_runtime::{
+        invoke_context::InstructionTrace, persistent_program_cache::PersistentProgramCache,
+    },
     solana_pubkey::Pubkey,
     solana_rayon_threadlimit::get_thread_count,
     solana_rpc::{
@@ -117,8 +124,12 @@ use {
     },
     solana_send_transaction_service::send_transaction_service::Config as SendTransactionServiceConfig,
     solana_shred_version::compute_shred_version,
+    solana_signature::Signature,
     solana_signer::Signer,
     solana_streamer::{quic::QuicServerParams, socket::SocketAddrSpace, streamer::StakedNodes},
+    solana_svm::instruction_trace_notifier::{
+        InstructionTraceNotifier, InstructionTraceNotifierArc,
+    },
     solana_time_utils::timestamp,
     solana_tpu_client::tpu_client::{
         DEFAULT_TPU_CONNECTION_POOL_SIZE, DEFAULT_TPU_USE_QUIC, DEFAULT_VOTE_USE_QUIC,
@@ -135,6 +146,7 @@ use {
     std::{
         borrow::Cow,
         collections::{HashMap, HashSet},
+        fs::File,
         net::SocketAddr,
         num::NonZeroUsize,
         path::{Path, PathBuf},
@@ -283,6 +295,16 @@ pub struct ValidatorConfig {
     pub block_production_method: BlockProductionMethod,
     pub transaction_struct: TransactionStructure,
     pub enable_block_production_forwarding: bool,
+    /// Number of recent slots for which per-signature transaction outcomes
+    /// are retained while leader. `None` disables the outcome log.
+    pub transaction_outcome_log_slots: Option<usize>,
+    /// Path of the shared memory file used to delegate transaction scheduling
+    /// to an external process. `block_production_method` is used while the
+    /// process is absent or does not answer in time.
+    pub external_scheduler_path: Option<PathBuf>,
+    /// Accept bundles over the `sendBundle` RPC method and execute them
+    /// atomically in the banking stage.
+    pub enable_bundles: bool,
     pub generator_config: Option<GeneratorConfig>,
     pub use_snapshot_archives_at_startup: UseSnapshotArchivesAtStartup,
     pub wen_restart_proto_path: Option<PathBuf>,
@@ -297,6 +319,12 @@ pub struct ValidatorConfig {
     pub use_tpu_client_next: bool,
     pub retransmit_xdp: Option<XdpConfig>,
     pub repair_handler_type: RepairHandlerType,
+    /// Profile the compute units consumed per program while replaying and
+    /// write the report to this file when the validator exits
+    pub compute_profile_path: Option<PathBuf>,
+    /// Directory in which to record the loaded programs, which are loaded
+    /// again at startup
+    pub persistent_program_cache_path: Option<PathBuf>,
 }
 
 impl ValidatorConfig {
@@ -362,6 +390,9 @@ impl ValidatorConfig {
             transaction_struct: TransactionStructure::default(),
             // enable forwarding by default for tests
             enable_block_production_forwarding: true,
+            transaction_outcome_log_slots: None,
+            external_scheduler_path: None,
+            enable_bundles: false,
             generator_config: None,
             use_snapshot_archives_at_startup: UseSnapshotArchivesAtStartup::default(),
             wen_restart_proto_path: None,
@@ -377,6 +408,8 @@ impl ValidatorConfig {
             use_tpu_client_next: true,
             retransmit_xdp: None,
             repair_handler_type: RepairHandlerType::default(),
+            compute_profile_path: None,
+            persistent_program_cache_path: None,
         }
     }
 
@@ -552,6 +585,7 @@ pub struct Validator {
     repair_quic_endpoints_runtime: Option<TokioRuntime>,
     repair_quic_endpoints_join_handle: Option<repair::quic_endpoint::AsyncTryJoinHandle>,
     xdp_retransmitter: Option<XdpRetransmitter>,
+    compute_profiler: Option<(PathBuf, Arc<ComputeProfiler>)>,
     // This runtime is used to run the client owned by SendTransactionService.
     // We don't wait for its JoinHandle here because ownership and shutdown
     // are managed elsewhere. This variable is intentionally unused.
@@ -717,6 +751,7 @@ impl Validator {
             accounts_update_notifier,
             transaction_notifier,
             entry_notifier,
+            instruction_trace_notifier,
             block_metadata_notifier,
             slot_status_notifier,
         ) = if let Some(service) = &geyser_plugin_service {
@@ -724,19 +759,21 @@ impl Validator {
                 service.get_accounts_update_notifier(),
                 service.get_transaction_notifier(),
                 service.get_entry_notifier(),
+                service.get_instruction_trace_notifier(),
                 service.get_block_metadata_notifier(),
                 service.get_slot_status_notifier(),
             )
         } else {
-            (None, None, None, None, None)
+            (None, None, None, None, None, None)
         };
 
         info!(
             "Geyser plugin: accounts_update_notifier: {}, transaction_notifier: {}, \
-             entry_notifier: {}",
+             entry_notifier: {}, instruction_trace_notifier: {}",
             accounts_update_notifier.is_some(),
             transaction_notifier.is_some(),
-            entry_notifier.is_some()
+            entry_notifier.is_some(),
+            instruction_trace_notifier.is_some()
         );
 
         let system_monitor_service = Some(SystemMonitorService::new(
@@ -783,6 +820,54 @@ impl Validator {
         )
         .map_err(ValidatorError::Other)?;
 
+        if let Some(path) = &config.persistent_program_cache_path {
+            let persistent_cache = PersistentProgramCache::new(path).map_err(|err| {
+                ValidatorError::Other(format!(
+                    "Unable to open the persistent program cache at {}: {err}",
+                    path.display()
+                ))
+            })?;
+            let root_bank = bank_forks.read().unwrap().root_bank();
+            let (num_programs, load_programs_us) =
+                measure_us!(root_bank.load_persisted_programs(&persistent_cache));
+            info!(
+                "Loaded {num_programs} programs recorded in {} in {load_programs_us}us",
+                path.display()
+            );
+            root_bank
+                .get_transaction_processor()
+                .program_cache
+                .write()
+                .unwrap()
+                .persistent_cache = Some(Arc::new(persistent_cache));
+        }
+
+        let compute_profiler = config
+            .compute_profile_path
+            .as_ref()
+            .map(|path| (path.clone(), Arc::new(ComputeProfiler::default())));
+        let instruction_trace_notifier = match (&compute_profiler, instruction_trace_notifier) {
+            (Some((_, compute_profiler)), Some(instruction_trace_notifier)) => {
+                Some(Arc::new(InstructionTraceNotifiers(vec![
+                    compute_profiler.clone(),
+                    instruction_trace_notifier,
+                ])) as InstructionTraceNotifierArc)
+            }
+            (Some((_, compute_profiler)), None) => {
+                Some(compute_profiler.clone() as InstructionTraceNotifierArc)
+            }
+            (None, instruction_trace_notifier) => instruction_trace_notifier,
+        };
+        if instruction_trace_notifier.is_some() {
+            // Banks created from now on inherit the notifier from their parent
+            bank_forks
+                .read()
+                .unwrap()
+                .root_bank()
+                .get_transaction_processor()
+                .set_instruction_trace_notifier(instruction_trace_notifier);
+        }
+
         if !config.no_poh_speed_test {
             check_poh_speed(&bank_forks.read().unwrap().root_bank(), None)?;
         }
@@ -1101,6 +1186,12 @@ impl Validator {
 
         let rpc_override_health_check =
             Arc::new(AtomicBool::new(config.rpc_config.disable_health_check));
+        let (bundle_sender, bundle_receiver) = if config.enable_bundles {
+            let (bundle_sender, bundle_receiver) = bounded(BUNDLE_CHANNEL_CAPACITY);
+            (Some(bundle_sender), Some(bundle_receiver))
+        } else {
+            (None, None)
+        };
         let (
             json_rpc_service,
             rpc_subscriptions,
@@ -1143,7 +1234,10 @@ impl Validator {
             };
             let rpc_svc_config = JsonRpcServiceConfig {
                 rpc_addr,
-                rpc_config: config.rpc_config.clone(),
+                rpc_config: JsonRpcConfig {
+                    bundle_sender,
+                    ..config.rpc_config.clone()
+                },
                 snapshot_config: Some(snapshot_controller.snapshot_config().clone()),
                 bank_forks: bank_forks.clone(),
                 block_commitment_cache: block_commitment_cache.clone(),
@@ -1561,6 +1655,21 @@ impl Validator {
         }
 
         let key_notifiers = Arc::new(RwLock::new(KeyUpdaters::default()));
+        let transaction_outcome_log = config
+            .transaction_outcome_log_slots
+            .map(|max_slots| Arc::new(TransactionOutcomeLog::new(max_slots)));
+        let external_scheduler_region = config
+            .external_scheduler_path
+            .as_ref()
+            .map(|path| {
+                SharedRegion::create(path, BankingStage::num_workers() as usize).map_err(|err| {
+                    ValidatorError::Other(format!(
+                        "failed to create external scheduler region at {}: {err}",
+                        path.display()
+                    ))
+                })
+            })
+            .transpose()?;
         let forwarding_tpu_client = if let Some(connection_cache) = &connection_cache {
             ForwardingClientOption::ConnectionCache(connection_cache.clone())
         } else {
@@ -1629,6 +1738,9 @@ impl Validator {
             config.enable_block_production_forwarding,
             config.generator_config.clone(),
             key_notifiers.clone(),
+            transaction_outcome_log.clone(),
+            external_scheduler_region,
+            bundle_receiver,
         );
 
         datapoint_info!(
@@ -1663,6 +1775,9 @@ impl Validator {
             outstanding_repair_requests,
             cluster_slots,
             gossip_socket: Some(node.sockets.gossip.clone()),
+            transaction_outcome_log,
+            ledger_path: ledger_path.to_path_buf(),
+            snapshot_config: config.snapshot_config.clone(),
         });
 
         Ok(Self {
@@ -1698,6 +1813,7 @@ impl Validator {
             repair_quic_endpoints_runtime,
             repair_quic_endpoints_join_handle,
             xdp_retransmitter,
+            compute_profiler,
             _tpu_client_next_runtime: tpu_client_next_runtime,
         })
     }
@@ -1846,6 +1962,54 @@ impl Validator {
         if let Some(geyser_plugin_service) = self.geyser_plugin_service {
             geyser_plugin_service.join().expect("geyser_plugin_service");
         }
+
+        if let Some((compute_profile_path, compute_profiler)) = self.compute_profiler {
+            write_compute_profile(&compute_profile_path, &compute_profiler);
+        }
+    }
+}
+
+/// Forwards instruction traces to several notifiers
+struct InstructionTraceNotifiers(Vec<InstructionTraceNotifierArc>);
+
+impl InstructionTraceNotifier for InstructionTraceNotifiers {
+    fn notify_instruction_traces(
+        &self,
+        slot: Slot,
+        signature: &Signature,
+        compute_unit_limit: u64,
+        instruction_traces: &[InstructionTrace],
+    ) {
+        for notifier in &self.0 {
+            notifier.notify_instruction_traces(
+                slot,
+                signature,
+                compute_unit_limit,
+                instruction_traces,
+            );
+        }
+    }
+
+    fn account_data_enabled(&self) -> bool {
+        self.0
+            .iter()
+            .any(|notifier| notifier.account_data_enabled())
+    }
+}
+
+fn write_compute_profile(path: &Path, compute_profiler: &ComputeProfiler) {
+    let result = File::create(path)
+        .map_err(|err| err.to_string())
+        .and_then(|file| {
+            serde_json::to_writer_pretty(file, &compute_profiler.report())
+                .map_err(|err| err.to_string())
+        });
+    match result {
+        Ok(()) => info!("Wrote compute profile to {}", path.display()),
+        Err(err) => error!(
+            "Unable to write compute profile to {}: {err}",
+            path.display()
+        ),
     }
 }
 
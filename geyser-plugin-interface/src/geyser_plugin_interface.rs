@@ -235,6 +235,82 @@ pub enum ReplicaEntryInfoVersions<'a> {
     V0_0_2(&'a ReplicaEntryInfoV2<'a>),
 }
 
+/// Execution details of a single instruction, either a top level instruction
+/// or one invoked through CPI
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct ReplicaInstructionTrace<'a> {
+    /// The instruction's index in the transaction's instruction trace, which
+    /// orders all instructions by the time they were invoked
+    pub index_in_trace: usize,
+
+    /// The Pubkey of the invoked program
+    pub program_id: &'a [u8],
+
+    /// The invocation depth, 1 for top level instructions
+    pub stack_height: usize,
+
+    /// The compute units consumed, including by inner instructions
+    pub compute_units_consumed: u64,
+
+    /// The Pubkey of the program which set the return data, empty if no
+    /// return data was set when the instruction completed
+    pub return_data_program_id: &'a [u8],
+
+    /// The return data when the instruction completed
+    pub return_data: &'a [u8],
+
+    /// The log messages emitted while the instruction executed, including
+    /// those emitted by inner instructions
+    pub log_messages: &'a [String],
+
+    /// The accounts the instruction can read, as they were when it started
+    pub accounts: &'a [ReplicaInstructionTraceAccount<'a>],
+}
+
+/// An account passed to an instruction, see [ReplicaInstructionTrace::accounts]
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct ReplicaInstructionTraceAccount<'a> {
+    /// The Pubkey of the account
+    pub pubkey: &'a [u8],
+
+    /// Whether the instruction was signed by the account
+    pub is_signer: bool,
+
+    /// Whether the instruction can write to the account
+    pub is_writable: bool,
+
+    /// The lamports in the account
+    pub lamports: u64,
+
+    /// The Pubkey of the owner program account
+    pub owner: &'a [u8],
+
+    /// The data held in the account
+    pub data: &'a [u8],
+}
+
+/// The instruction traces of an executed transaction
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct ReplicaInstructionTraceInfo<'a> {
+    /// The first signature of the transaction, used for identifying the transaction.
+    pub signature: &'a Signature,
+
+    /// The traces of every instruction processed by the transaction, in
+    /// invocation order
+    pub instructions: &'a [ReplicaInstructionTrace<'a>],
+}
+
+/// A wrapper to future-proof ReplicaInstructionTraceInfo handling. To make a change to the
+/// structure of ReplicaInstructionTraceInfo, add an new enum variant wrapping a newer version,
+/// which will force plugin implementations to handle the change.
+#[repr(u32)]
+pub enum ReplicaInstructionTraceInfoVersions<'a> {
+    V0_0_1(&'a ReplicaInstructionTraceInfo<'a>),
+}
+
 #[derive(Clone, Debug)]
 #[repr(C)]
 pub struct ReplicaBlockInfo<'a> {
@@ -467,6 +543,19 @@ pub trait GeyserPlugin: Any + Send + Sync + std::fmt::Debug {
         Ok(())
     }
 
+    /// Called when a transaction has been executed, with the traces of the
+    /// instructions it processed. Unlike `notify_transaction`, this is called
+    /// shortly after execution, before the transaction is committed, and so
+    /// may be called for transactions which never land in a block.
+    #[allow(unused_variables)]
+    fn notify_instruction_trace(
+        &self,
+        instruction_trace: ReplicaInstructionTraceInfoVersions,
+        slot: Slot,
+    ) -> Result<()> {
+        Ok(())
+    }
+
     /// Called when block's metadata is updated.
     #[allow(unused_variables)]
     fn notify_block_metadata(&self, blockinfo: ReplicaBlockInfoVersions) -> Result<()> {
@@ -501,4 +590,21 @@ pub trait GeyserPlugin: Any + Send + Sync + std::fmt::Debug {
     fn entry_notifications_enabled(&self) -> bool {
         false
     }
+
+    /// Check if the plugin is interested in instruction traces
+    /// Default is false -- if the plugin is interested in instruction
+    /// traces, return true. Recording traces slows down transaction
+    /// execution, so it is only enabled when a plugin asks for it.
+    fn instruction_trace_notifications_enabled(&self) -> bool {
+        false
+    }
+
+    /// Check if the plugin is interested in the data of the accounts of
+    /// traced instructions
+    /// Default is false -- the `data` of every `ReplicaInstructionTraceAccount`
+    /// is then empty. Copying the data of every instruction's accounts is
+    /// costly, so only return true if the plugin needs it.
+    fn instruction_trace_account_data_enabled(&self) -> bool {
+        false
+    }
 }
@@ -1,6 +1,8 @@
 /// Module responsible for notifying plugins of account updates
 use {
-    crate::geyser_plugin_manager::GeyserPluginManager,
+    crate::{
+        geyser_plugin_manager::GeyserPluginManager, notification_buffer::BufferedNotification,
+    },
     agave_geyser_plugin_interface::geyser_plugin_interface::{
         ReplicaAccountInfoV3, ReplicaAccountInfoVersions,
     },
@@ -158,6 +160,8 @@ impl AccountsUpdateNotifierImpl {
     ) {
         let mut measure2 = Measure::start("geyser-plugin-notify_plugins_of_account_update");
         let plugin_manager = self.plugin_manager.read().unwrap();
+        plugin_manager
+            .buffer_notification(|| BufferedNotification::account(&account, slot, is_startup));
 
         if plugin_manager.plugins.is_empty() {
             return;
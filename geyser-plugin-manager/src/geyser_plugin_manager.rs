@@ -1,15 +1,44 @@
+#[cfg(unix)]
+use crate::ipc_plugin::{IpcGeyserPlugin, IpcPluginConfig};
 use {
-    agave_geyser_plugin_interface::geyser_plugin_interface::GeyserPlugin,
+    crate::notification_buffer::{
+        replay, BufferedNotification, NotificationBuffer, MAX_BUFFERED_NOTIFICATION_BYTES,
+    },
+    agave_geyser_plugin_interface::geyser_plugin_interface::{
+        GeyserPlugin, ReplicaAccountInfoV3, ReplicaAccountInfoVersions,
+    },
     jsonrpc_core::{ErrorCode, Result as JsonRpcResult},
     libloading::Library,
     log::*,
+    solana_account::ReadableAccount,
+    solana_accounts_db::{
+        hardened_unpack::{open_genesis_config, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE},
+        utils::{create_all_accounts_run_and_snapshot_dirs, move_and_async_delete_path},
+    },
+    solana_clock::Slot,
+    solana_runtime::{
+        bank::Bank, bank_forks::BankForks, runtime_config::RuntimeConfig, snapshot_bank_utils,
+        snapshot_config::SnapshotConfig,
+    },
     std::{
+        fmt,
         ops::{Deref, DerefMut},
-        path::Path,
+        path::{Path, PathBuf},
+        sync::{atomic::AtomicBool, Arc, Mutex, RwLock},
     },
     tokio::sync::oneshot::Sender as OneShotSender,
 };
 
+/// Rounds of replaying buffered notifications to a swapped plugin without holding the manager's
+/// lock, after which the remaining notifications are replayed under the write lock
+const MAX_REPLAY_ROUNDS_WITHOUT_LOCK: usize = 16;
+/// Once a round of replay leaves at most this many notifications, they are replayed under the
+/// write lock
+const MAX_NOTIFICATIONS_REPLAYED_UNDER_LOCK: usize = 1024;
+/// Scratch directory, within the ledger directory, in which the latest snapshot is unpacked to
+/// replay its accounts to a newly loaded plugin
+const ACCOUNT_REPLAY_SCRATCH_DIR: &str = "geyser_account_replay";
+
 #[derive(Debug)]
 pub struct LoadedGeyserPlugin {
     name: String,
@@ -22,8 +51,10 @@ pub struct LoadedGeyserPlugin {
     // any code before returning from Drop. This means if the Geyser plugins
     // spawn threads that access the Library, those threads must be `join`ed
     // before the Geyser plugin returns from on_unload / Drop.
+    //
+    // Out-of-process plugins are not backed by a library.
     #[allow(dead_code)]
-    library: Library,
+    library: Option<Library>,
 }
 
 impl LoadedGeyserPlugin {
@@ -31,7 +62,17 @@ impl LoadedGeyserPlugin {
         Self {
             name: name.unwrap_or_else(|| plugin.name().to_owned()),
             plugin,
-            library,
+            library: Some(library),
+        }
+    }
+
+    /// A plugin hosted in another process, see [`crate::ipc_plugin`]
+    #[cfg(unix)]
+    pub fn new_out_of_process(name: String, config: IpcPluginConfig) -> Self {
+        Self {
+            plugin: Box::new(IpcGeyserPlugin::new(name.clone(), config)),
+            name,
+            library: None,
         }
     }
 
@@ -57,12 +98,24 @@ impl DerefMut for LoadedGeyserPlugin {
 #[derive(Default, Debug)]
 pub struct GeyserPluginManager {
     pub plugins: Vec<LoadedGeyserPlugin>,
+    /// Notifications received while a plugin is swapped, which are replayed to the new plugin
+    /// instance once it is loaded. Only set or cleared while holding the manager's write lock.
+    buffered_notifications: Option<Mutex<NotificationBuffer>>,
 }
 
 impl GeyserPluginManager {
     pub fn new() -> Self {
         GeyserPluginManager {
             plugins: Vec::default(),
+            buffered_notifications: None,
+        }
+    }
+
+    /// Buffer a notification for the plugin being swapped, if any. `notification` is only
+    /// called while a swap is in progress, since copying the notification is expensive.
+    pub(crate) fn buffer_notification(&self, notification: impl FnOnce() -> BufferedNotification) {
+        if let Some(buffered_notifications) = &self.buffered_notifications {
+            buffered_notifications.lock().unwrap().push(notification());
         }
     }
 
@@ -115,6 +168,26 @@ impl GeyserPluginManager {
         false
     }
 
+    /// Check if there is any plugin interested in instruction traces
+    pub fn instruction_trace_notifications_enabled(&self) -> bool {
+        for plugin in &self.plugins {
+            if plugin.instruction_trace_notifications_enabled() {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Check if there is any plugin interested in the account data of instruction traces
+    pub fn instruction_trace_account_data_enabled(&self) -> bool {
+        for plugin in &self.plugins {
+            if plugin.instruction_trace_account_data_enabled() {
+                return true;
+            }
+        }
+        false
+    }
+
     /// Admin RPC request handler
     pub(crate) fn list_plugins(&self) -> JsonRpcResult<Vec<String>> {
         Ok(self.plugins.iter().map(|p| p.name().to_owned()).collect())
@@ -276,6 +349,343 @@ impl GeyserPluginManager {
         current_plugin.on_unload();
         info!("Unloaded plugin {name} at idx {idx}");
     }
+
+    /// Reloads the plugin `name` without pausing notification dispatch to the other plugins
+    /// while the new instance loads. Account, transaction and slot status notifications
+    /// received during the swap are buffered and replayed to the new instance in order.
+    pub(crate) fn reload_plugin_with_replay(
+        plugin_manager: &RwLock<Self>,
+        name: &str,
+        config_file: &str,
+    ) -> JsonRpcResult<()> {
+        let mut current_plugin = {
+            let mut plugin_manager = plugin_manager.write().unwrap();
+            let Some(idx) = plugin_manager
+                .plugins
+                .iter()
+                .position(|plugin| plugin.name().eq(name))
+            else {
+                return Err(jsonrpc_core::error::Error {
+                    code: ErrorCode::InvalidRequest,
+                    message: String::from("The plugin you requested to reload is not loaded"),
+                    data: None,
+                });
+            };
+            plugin_manager.start_buffering_notifications()?;
+            plugin_manager.plugins.remove(idx)
+        };
+
+        // Unload the current plugin first in case plugin requires exclusive access to resource,
+        // such as a particular port or database.
+        current_plugin.on_unload();
+        drop(current_plugin);
+        info!("Unloaded plugin {name} for reload");
+
+        Self::prepare_plugin(plugin_manager, config_file.as_ref(), true)
+            .and_then(|new_plugin| Self::finish_swap(plugin_manager, new_plugin))
+            .inspect_err(|_| {
+                plugin_manager
+                    .write()
+                    .unwrap()
+                    .buffered_notifications
+                    .take();
+            })
+            .map_err(|err| jsonrpc_core::error::Error {
+                code: ErrorCode::InvalidRequest,
+                message: format!(
+                    "Failed to start new plugin (previous plugin was dropped!): {}",
+                    err.message
+                ),
+                data: None,
+            })
+    }
+
+    /// Loads a new plugin and notifies it of every account in the latest snapshot, as at
+    /// validator startup, followed by the accounts updated in the root bank since the snapshot.
+    /// Notifications received meanwhile are buffered and replayed to the plugin afterwards.
+    pub(crate) fn load_plugin_with_account_replay(
+        plugin_manager: &RwLock<Self>,
+        config_file: &str,
+        source: &AccountsReplaySource,
+        exit: Arc<AtomicBool>,
+    ) -> JsonRpcResult<String> {
+        let mut new_plugin = Self::prepare_plugin(plugin_manager, config_file.as_ref(), false)?;
+        if let Err(err) = plugin_manager
+            .write()
+            .unwrap()
+            .start_buffering_notifications()
+        {
+            new_plugin.on_unload();
+            return Err(err);
+        }
+
+        let name = new_plugin.name().to_string();
+        let result = notify_plugin_of_snapshot_accounts(&new_plugin, source, exit)
+            .map_err(|err| jsonrpc_core::Error {
+                code: ErrorCode::InvalidRequest,
+                message: format!("Failed to replay accounts to plugin {name}: {err}"),
+                data: None,
+            })
+            .map(|snapshot_slot| {
+                let root_bank = source.bank_forks.read().unwrap().root_bank();
+                notify_plugin_of_bank_accounts(&new_plugin, &root_bank, Some(snapshot_slot));
+            });
+        match result {
+            Ok(()) => Self::finish_swap(plugin_manager, new_plugin).map(|()| name),
+            Err(err) => {
+                plugin_manager
+                    .write()
+                    .unwrap()
+                    .buffered_notifications
+                    .take();
+                new_plugin.on_unload();
+                Err(err)
+            }
+        }
+    }
+
+    fn start_buffering_notifications(&mut self) -> JsonRpcResult<()> {
+        if self.buffered_notifications.is_some() {
+            return Err(jsonrpc_core::Error {
+                code: ErrorCode::InvalidRequest,
+                message: String::from("Another plugin is being swapped"),
+                data: None,
+            });
+        }
+        self.buffered_notifications = Some(Mutex::default());
+        Ok(())
+    }
+
+    /// Loads and starts a plugin without holding the manager's lock, so notification dispatch
+    /// continues while the plugin loads
+    fn prepare_plugin(
+        plugin_manager: &RwLock<Self>,
+        config_file: &Path,
+        is_reload: bool,
+    ) -> JsonRpcResult<LoadedGeyserPlugin> {
+        // SAFETY: It is up to the validator to ensure this is a valid plugin library.
+        let (mut new_plugin, new_parsed_config_file) = load_plugin_from_config(config_file)
+            .map_err(|err| jsonrpc_core::Error {
+                code: ErrorCode::InvalidRequest,
+                message: format!("Failed to load plugin: {err}"),
+                data: None,
+            })?;
+
+        if plugin_manager
+            .read()
+            .unwrap()
+            .plugins
+            .iter()
+            .any(|plugin| plugin.name().eq(new_plugin.name()))
+        {
+            return Err(jsonrpc_core::Error {
+                code: ErrorCode::InvalidRequest,
+                message: format!(
+                    "There already exists a plugin named {} loaded. Did not load requested plugin",
+                    new_plugin.name()
+                ),
+                data: None,
+            });
+        }
+
+        setup_logger_for_plugin(&*new_plugin.plugin)?;
+
+        new_plugin
+            .on_load(new_parsed_config_file, is_reload)
+            .map_err(|on_load_err| jsonrpc_core::Error {
+                code: ErrorCode::InvalidRequest,
+                message: format!(
+                    "on_load method of plugin {} failed: {on_load_err}",
+                    new_plugin.name()
+                ),
+                data: None,
+            })?;
+        Ok(new_plugin)
+    }
+
+    /// Replays the buffered notifications to `new_plugin` and adds it to the manager, then
+    /// stops buffering.
+    ///
+    /// Most notifications are replayed without holding the manager's lock, so dispatch to the
+    /// other plugins continues meanwhile. Only the few notifications received during the last
+    /// round of replay are replayed under the write lock, so no notification can be dispatched
+    /// to the new plugin ahead of the buffered ones. If the buffer overflowed the plugin would
+    /// miss notifications, so it is unloaded and the swap fails.
+    fn finish_swap(
+        plugin_manager: &RwLock<Self>,
+        mut new_plugin: LoadedGeyserPlugin,
+    ) -> JsonRpcResult<()> {
+        for _ in 0..MAX_REPLAY_ROUNDS_WITHOUT_LOCK {
+            let notifications = plugin_manager
+                .read()
+                .unwrap()
+                .buffered_notifications
+                .as_ref()
+                .and_then(|buffered_notifications| buffered_notifications.lock().unwrap().take());
+            let Some(notifications) = notifications else {
+                break;
+            };
+            let num_notifications = notifications.len();
+            replay(notifications, &new_plugin);
+            if num_notifications <= MAX_NOTIFICATIONS_REPLAYED_UNDER_LOCK {
+                break;
+            }
+        }
+
+        let mut plugin_manager = plugin_manager.write().unwrap();
+        let notifications = plugin_manager
+            .buffered_notifications
+            .take()
+            .and_then(|buffered_notifications| buffered_notifications.into_inner().unwrap().take());
+        match notifications {
+            Some(notifications) => {
+                replay(notifications, &new_plugin);
+                plugin_manager.plugins.push(new_plugin);
+                Ok(())
+            }
+            None => {
+                drop(plugin_manager);
+                let name = new_plugin.name().to_string();
+                new_plugin.on_unload();
+                Err(jsonrpc_core::Error {
+                    code: ErrorCode::InvalidRequest,
+                    message: format!(
+                        "Notifications for plugin {name} were dropped while it was loading, since \
+                         more than {MAX_BUFFERED_NOTIFICATION_BYTES} bytes of notifications were \
+                         buffered"
+                    ),
+                    data: None,
+                })
+            }
+        }
+    }
+}
+
+/// Notify `plugin` of every account in the latest snapshot, followed by the end of startup,
+/// and return the slot of the snapshot. The bank is rebuilt from the snapshot archives in a
+/// scratch directory, which is removed afterwards.
+fn notify_plugin_of_snapshot_accounts(
+    plugin: &LoadedGeyserPlugin,
+    source: &AccountsReplaySource,
+    exit: Arc<AtomicBool>,
+) -> Result<Slot, String> {
+    let genesis_config =
+        open_genesis_config(&source.ledger_path, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE)
+            .map_err(|err| format!("failed to open genesis config: {err}"))?;
+    let scratch_dir = source.ledger_path.join(ACCOUNT_REPLAY_SCRATCH_DIR);
+    move_and_async_delete_path(&scratch_dir);
+    let bank_snapshots_dir = scratch_dir.join("snapshot");
+    let (account_run_paths, _) =
+        create_all_accounts_run_and_snapshot_dirs(&[scratch_dir.join("accounts")])
+            .and_then(|paths| std::fs::create_dir_all(&bank_snapshots_dir).map(|_| paths))
+            .map_err(|err| format!("failed to create {}: {err}", scratch_dir.display()))?;
+
+    let result = snapshot_bank_utils::bank_from_latest_snapshot_archives(
+        &bank_snapshots_dir,
+        &source.snapshot_config.full_snapshot_archives_dir,
+        &source.snapshot_config.incremental_snapshot_archives_dir,
+        &account_run_paths,
+        &genesis_config,
+        &RuntimeConfig::default(),
+        None,
+        None,
+        None,
+        false,
+        false,
+        false,
+        None,
+        None,
+        exit,
+    );
+    let result = result
+        .map(|(bank, ..)| {
+            notify_plugin_of_bank_accounts(plugin, &bank, None);
+            if let Err(err) = plugin.notify_end_of_startup() {
+                error!(
+                    "Failed to notify the end of startup, error: {err} to plugin {}",
+                    plugin.name()
+                );
+            }
+            bank.slot()
+        })
+        .map_err(|err| format!("failed to load the latest snapshot: {err}"));
+    move_and_async_delete_path(&scratch_dir);
+    result
+}
+
+/// Notify `plugin` of the accounts in `bank`. With `updated_after`, only the accounts last
+/// updated after that slot are notified, at the slot of their update. Otherwise every account
+/// is notified at the slot of the bank, as at startup.
+fn notify_plugin_of_bank_accounts(
+    plugin: &LoadedGeyserPlugin,
+    bank: &Bank,
+    updated_after: Option<Slot>,
+) {
+    let bank_slot = bank.slot();
+    let is_startup = updated_after.is_none();
+    let mut notified_accounts = 0usize;
+    let scan_result = bank.scan_all_accounts(
+        |scanned| {
+            let Some((pubkey, account, slot)) = scanned else {
+                return;
+            };
+            let slot = match updated_after {
+                Some(updated_after) if slot <= updated_after => return,
+                Some(_) => slot,
+                None => bank_slot,
+            };
+            let account = ReplicaAccountInfoV3 {
+                pubkey: pubkey.as_ref(),
+                lamports: account.lamports(),
+                owner: account.owner().as_ref(),
+                executable: account.executable(),
+                rent_epoch: account.rent_epoch(),
+                data: account.data(),
+                write_version: 0,
+                txn: None,
+            };
+            if let Err(err) = plugin.update_account(
+                ReplicaAccountInfoVersions::V0_0_3(&account),
+                slot,
+                is_startup,
+            ) {
+                error!(
+                    "Failed to update account {pubkey} at slot {slot}, error: {err} to plugin {}",
+                    plugin.name()
+                );
+            }
+            notified_accounts = notified_accounts.saturating_add(1);
+        },
+        false,
+    );
+    if let Err(err) = scan_result {
+        error!(
+            "Failed to scan the accounts of slot {bank_slot} for plugin {}: {err}",
+            plugin.name()
+        );
+    }
+    info!(
+        "Notified plugin {} of {notified_accounts} accounts at slot {bank_slot}",
+        plugin.name()
+    );
+}
+
+/// Where a newly loaded plugin is notified of accounts from: the latest snapshot archives,
+/// followed by the accounts updated in the root bank since the snapshot
+#[derive(Clone)]
+pub struct AccountsReplaySource {
+    pub bank_forks: Arc<RwLock<BankForks>>,
+    pub ledger_path: PathBuf,
+    pub snapshot_config: SnapshotConfig,
+}
+
+impl fmt::Debug for AccountsReplaySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccountsReplaySource")
+            .field("ledger_path", &self.ledger_path)
+            .field("snapshot_config", &self.snapshot_config)
+            .finish()
+    }
 }
 
 // Initialize logging for the plugin
@@ -297,6 +707,8 @@ pub enum GeyserPluginManagerRequest {
     ReloadPlugin {
         name: String,
         config_file: String,
+        /// Buffer notifications during the reload and replay them to the new instance
+        replay_notifications: bool,
         response_sender: OneShotSender<JsonRpcResult<()>>,
     },
     UnloadPlugin {
@@ -305,6 +717,8 @@ pub enum GeyserPluginManagerRequest {
     },
     LoadPlugin {
         config_file: String,
+        /// Notify the new plugin of the accounts in the latest snapshot once it is loaded
+        replay_accounts_from: Option<AccountsReplaySource>,
         response_sender: OneShotSender<JsonRpcResult<String>>,
     },
     ListPlugins {
@@ -337,6 +751,9 @@ pub enum GeyserPluginManagerError {
 
     #[error("The GeyserPlugin on_load method failed (error: {0})")]
     PluginStartError(String),
+
+    #[error("Invalid out-of-process plugin config (error: {0})")]
+    InvalidIpcConfig(String),
 }
 
 /// # Safety
@@ -381,6 +798,16 @@ pub(crate) fn load_plugin_from_config(
         }
     };
 
+    let config_file = geyser_plugin_config_file
+        .as_os_str()
+        .to_str()
+        .ok_or(GeyserPluginManagerError::InvalidPluginPath)?;
+
+    if let Some(ipc_config) = result.get("ipc") {
+        return load_out_of_process_plugin(&result, ipc_config, geyser_plugin_config_file)
+            .map(|plugin| (plugin, config_file));
+    }
+
     let libpath = result["libpath"]
         .as_str()
         .ok_or(GeyserPluginManagerError::LibPathNotSet)?;
@@ -396,11 +823,6 @@ pub(crate) fn load_plugin_from_config(
 
     let plugin_name = result["name"].as_str().map(|s| s.to_owned());
 
-    let config_file = geyser_plugin_config_file
-        .as_os_str()
-        .to_str()
-        .ok_or(GeyserPluginManagerError::InvalidPluginPath)?;
-
     let (plugin, lib) = unsafe {
         let lib = Library::new(libpath)
             .map_err(|e| GeyserPluginManagerError::PluginLoadError(e.to_string()))?;
@@ -416,6 +838,52 @@ pub(crate) fn load_plugin_from_config(
     ))
 }
 
+/// Builds the out-of-process plugin described by the `ipc` section of a plugin config file
+fn load_out_of_process_plugin(
+    config: &serde_json::Value,
+    ipc_config: &serde_json::Value,
+    geyser_plugin_config_file: &Path,
+) -> Result<LoadedGeyserPlugin, GeyserPluginManagerError> {
+    #[cfg(unix)]
+    {
+        let (name, ipc_config) =
+            parse_ipc_plugin_config(config, ipc_config, geyser_plugin_config_file)?;
+        Ok(LoadedGeyserPlugin::new_out_of_process(name, ipc_config))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (config, ipc_config, geyser_plugin_config_file);
+        Err(GeyserPluginManagerError::InvalidIpcConfig(
+            "out-of-process plugins are only supported on Unix".to_string(),
+        ))
+    }
+}
+
+/// Parses the `ipc` section of a plugin config file, returning the plugin name and its config
+/// with a relative `socket_path` resolved against the directory of the config file
+#[cfg(unix)]
+fn parse_ipc_plugin_config(
+    config: &serde_json::Value,
+    ipc_config: &serde_json::Value,
+    geyser_plugin_config_file: &Path,
+) -> Result<(String, IpcPluginConfig), GeyserPluginManagerError> {
+    let mut ipc_config: IpcPluginConfig = serde_json::from_value(ipc_config.clone())
+        .map_err(|err| GeyserPluginManagerError::InvalidIpcConfig(err.to_string()))?;
+    if ipc_config.socket_path.is_relative() {
+        let config_dir = geyser_plugin_config_file.parent().ok_or_else(|| {
+            GeyserPluginManagerError::CannotOpenConfigFile(format!(
+                "Failed to resolve parent of {geyser_plugin_config_file:?}",
+            ))
+        })?;
+        ipc_config.socket_path = config_dir.join(&ipc_config.socket_path);
+    }
+    let name = config["name"]
+        .as_str()
+        .map(|s| s.to_owned())
+        .unwrap_or_else(|| ipc_config.socket_path.display().to_string());
+    Ok((name, ipc_config))
+}
+
 #[cfg(test)]
 const TESTPLUGIN_CONFIG: &str = "TESTPLUGIN_CONFIG";
 #[cfg(test)]
@@ -453,9 +921,10 @@ mod tests {
         crate::geyser_plugin_manager::{
             GeyserPluginManager, LoadedGeyserPlugin, TESTPLUGIN2_CONFIG, TESTPLUGIN_CONFIG,
         },
-        agave_geyser_plugin_interface::geyser_plugin_interface::GeyserPlugin,
+        crate::notification_buffer::{BufferedNotification, NotificationBuffer},
+        agave_geyser_plugin_interface::geyser_plugin_interface::{GeyserPlugin, SlotStatus},
         libloading::Library,
-        std::sync::{Arc, RwLock},
+        std::sync::{Arc, Mutex, RwLock},
     };
 
     pub(super) fn dummy_plugin_and_library<P: GeyserPlugin>(
@@ -571,4 +1040,169 @@ mod tests {
         assert!(unload_result.is_ok());
         assert_eq!(plugin_manager_lock.plugins.len(), 0);
     }
+
+    #[test]
+    fn test_geyser_reload_with_replay() {
+        let plugin_manager = Arc::new(RwLock::new(GeyserPluginManager::new()));
+
+        // No plugins are loaded, this should fail without starting to buffer
+        let reload_result = GeyserPluginManager::reload_plugin_with_replay(
+            &plugin_manager,
+            DUMMY_NAME,
+            DUMMY_CONFIG,
+        );
+        assert_eq!(
+            reload_result.unwrap_err().message,
+            "The plugin you requested to reload is not loaded"
+        );
+        assert!(plugin_manager
+            .read()
+            .unwrap()
+            .buffered_notifications
+            .is_none());
+
+        let (mut plugin, config) = dummy_plugin_and_library(TestPlugin, DUMMY_CONFIG);
+        plugin.on_load(config, false).unwrap();
+        plugin_manager.write().unwrap().plugins.push(plugin);
+
+        // Replace TestPlugin with TestPlugin2
+        GeyserPluginManager::reload_plugin_with_replay(
+            &plugin_manager,
+            DUMMY_NAME,
+            TESTPLUGIN2_CONFIG,
+        )
+        .unwrap();
+        let plugin_manager_lock = plugin_manager.read().unwrap();
+        assert_eq!(
+            plugin_manager_lock.list_plugins().unwrap(),
+            vec![ANOTHER_DUMMY_NAME.to_string()]
+        );
+        assert!(plugin_manager_lock.buffered_notifications.is_none());
+    }
+
+    #[test]
+    fn test_notifications_buffered_during_swap() {
+        let plugin_manager = RwLock::new(GeyserPluginManager::new());
+        let slot_status = |slot| BufferedNotification::SlotStatus {
+            slot,
+            parent: None,
+            status: SlotStatus::Processed,
+        };
+
+        // Notifications are not buffered outside of a swap
+        plugin_manager
+            .read()
+            .unwrap()
+            .buffer_notification(|| slot_status(1));
+        assert!(plugin_manager
+            .read()
+            .unwrap()
+            .buffered_notifications
+            .is_none());
+
+        plugin_manager
+            .write()
+            .unwrap()
+            .start_buffering_notifications()
+            .unwrap();
+        // Only one swap may be in progress at a time
+        assert_eq!(
+            plugin_manager
+                .write()
+                .unwrap()
+                .start_buffering_notifications()
+                .unwrap_err()
+                .message,
+            "Another plugin is being swapped"
+        );
+        plugin_manager
+            .read()
+            .unwrap()
+            .buffer_notification(|| slot_status(2));
+        plugin_manager
+            .read()
+            .unwrap()
+            .buffer_notification(|| slot_status(3));
+        assert_eq!(
+            plugin_manager
+                .read()
+                .unwrap()
+                .buffered_notifications
+                .as_ref()
+                .unwrap()
+                .lock()
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let (mut plugin, config) = dummy_plugin_and_library(TestPlugin, DUMMY_CONFIG);
+        plugin.on_load(config, false).unwrap();
+        GeyserPluginManager::finish_swap(&plugin_manager, plugin).unwrap();
+        let plugin_manager_lock = plugin_manager.read().unwrap();
+        assert_eq!(plugin_manager_lock.plugins.len(), 1);
+        assert!(plugin_manager_lock.buffered_notifications.is_none());
+    }
+
+    #[test]
+    fn test_swap_fails_when_buffer_overflows() {
+        let plugin_manager = RwLock::new(GeyserPluginManager::new());
+        plugin_manager.write().unwrap().buffered_notifications =
+            Some(Mutex::new(NotificationBuffer::with_max_size(1)));
+        plugin_manager
+            .read()
+            .unwrap()
+            .buffer_notification(|| BufferedNotification::SlotStatus {
+                slot: 1,
+                parent: None,
+                status: SlotStatus::Processed,
+            });
+
+        // The plugin would have missed a notification, so it is not added
+        let (mut plugin, config) = dummy_plugin_and_library(TestPlugin, DUMMY_CONFIG);
+        plugin.on_load(config, false).unwrap();
+        let err = GeyserPluginManager::finish_swap(&plugin_manager, plugin).unwrap_err();
+        assert!(err.message.contains("were dropped while it was loading"));
+        let plugin_manager_lock = plugin_manager.read().unwrap();
+        assert!(plugin_manager_lock.plugins.is_empty());
+        assert!(plugin_manager_lock.buffered_notifications.is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_load_out_of_process_plugin() {
+        use {
+            super::{load_out_of_process_plugin, parse_ipc_plugin_config},
+            crate::geyser_plugin_manager::GeyserPluginManagerError,
+            std::path::Path,
+        };
+
+        let config_file = Path::new("/etc/geyser/plugin.json");
+        let config = serde_json::json!({
+            "name": "my-plugin",
+            "ipc": {"socket_path": "plugin.sock", "transactions": true},
+        });
+        let plugin = load_out_of_process_plugin(&config, &config["ipc"], config_file).unwrap();
+        assert_eq!(plugin.name(), "my-plugin");
+        assert!(plugin.transaction_notifications_enabled());
+        assert!(!plugin.account_data_notifications_enabled());
+
+        // A relative socket path is resolved against the directory of the config file
+        let (_, ipc_config) =
+            parse_ipc_plugin_config(&config, &config["ipc"], config_file).unwrap();
+        assert_eq!(ipc_config.socket_path, Path::new("/etc/geyser/plugin.sock"));
+
+        // An absolute one is kept, and names the plugin if it has no name
+        let config = serde_json::json!({"ipc": {"socket_path": "/run/plugin.sock"}});
+        let (name, ipc_config) =
+            parse_ipc_plugin_config(&config, &config["ipc"], config_file).unwrap();
+        assert_eq!(ipc_config.socket_path, Path::new("/run/plugin.sock"));
+        assert_eq!(name, "/run/plugin.sock");
+
+        let config = serde_json::json!({"ipc": {"socket_path": "plugin.sock", "bogus": 1}});
+        assert!(matches!(
+            load_out_of_process_plugin(&config, &config["ipc"], config_file),
+            Err(GeyserPluginManagerError::InvalidIpcConfig(_))
+        ));
+    }
 }
@@ -5,6 +5,7 @@ use {
         block_metadata_notifier_interface::BlockMetadataNotifierArc,
         entry_notifier::EntryNotifierImpl,
         geyser_plugin_manager::{GeyserPluginManager, GeyserPluginManagerRequest},
+        instruction_trace_notifier::InstructionTraceNotifierImpl,
         slot_status_notifier::SlotStatusNotifierImpl,
         slot_status_observer::SlotStatusObserver,
         transaction_notifier::TransactionNotifierImpl,
@@ -18,13 +19,14 @@ use {
         slot_status_notifier::SlotStatusNotifier,
         transaction_notifier_interface::TransactionNotifierArc,
     },
+    solana_svm::instruction_trace_notifier::InstructionTraceNotifierArc,
     std::{
         path::{Path, PathBuf},
         sync::{
             atomic::{AtomicBool, Ordering},
             Arc, RwLock,
         },
-        thread,
+        thread::{self, JoinHandle},
         time::Duration,
     },
     thiserror::Error,
@@ -37,6 +39,9 @@ pub struct GeyserPluginService {
     accounts_update_notifier: Option<AccountsUpdateNotifier>,
     transaction_notifier: Option<TransactionNotifierArc>,
     entry_notifier: Option<EntryNotifierArc>,
+    instruction_trace_notifier: Option<InstructionTraceNotifierArc>,
+    instruction_trace_notifier_thread: Option<JoinHandle<()>>,
+    exit_instruction_trace_notifier: Arc<AtomicBool>,
     block_metadata_notifier: Option<BlockMetadataNotifierArc>,
     slot_status_notifier: Option<SlotStatusNotifier>,
 }
@@ -93,6 +98,12 @@ impl GeyserPluginService {
             plugin_manager.transaction_notifications_enabled() || geyser_plugin_always_enabled;
         let entry_notifications_enabled =
             plugin_manager.entry_notifications_enabled() || geyser_plugin_always_enabled;
+        // Recording instruction traces is costly, so unlike other notifications they are not
+        // enabled by `geyser_plugin_always_enabled`
+        let instruction_trace_notifications_enabled =
+            plugin_manager.instruction_trace_notifications_enabled();
+        let instruction_trace_account_data_enabled =
+            plugin_manager.instruction_trace_account_data_enabled();
         let plugin_manager = Arc::new(RwLock::new(plugin_manager));
 
         let accounts_update_notifier: Option<AccountsUpdateNotifier> =
@@ -121,6 +132,25 @@ impl GeyserPluginService {
             None
         };
 
+        let exit_instruction_trace_notifier = Arc::new(AtomicBool::new(false));
+        let (instruction_trace_notifier, instruction_trace_notifier_thread): (
+            Option<InstructionTraceNotifierArc>,
+            Option<JoinHandle<()>>,
+        ) = if instruction_trace_notifications_enabled {
+            let (instruction_trace_notifier, instruction_trace_notifier_thread) =
+                InstructionTraceNotifierImpl::new(
+                    plugin_manager.clone(),
+                    instruction_trace_account_data_enabled,
+                    exit_instruction_trace_notifier.clone(),
+                );
+            (
+                Some(Arc::new(instruction_trace_notifier)),
+                Some(instruction_trace_notifier_thread),
+            )
+        } else {
+            (None, None)
+        };
+
         let (slot_status_observer, block_metadata_notifier, slot_status_notifier): (
             Option<SlotStatusObserver>,
             Option<BlockMetadataNotifierArc>,
@@ -128,6 +158,7 @@ impl GeyserPluginService {
         ) = if account_data_notifications_enabled
             || transaction_notifications_enabled
             || entry_notifications_enabled
+            || instruction_trace_notifications_enabled
         {
             let slot_status_notifier = SlotStatusNotifierImpl::new(plugin_manager.clone());
             let slot_status_notifier = Arc::new(RwLock::new(slot_status_notifier));
@@ -158,6 +189,9 @@ impl GeyserPluginService {
             accounts_update_notifier,
             transaction_notifier,
             entry_notifier,
+            instruction_trace_notifier,
+            instruction_trace_notifier_thread,
+            exit_instruction_trace_notifier,
             block_metadata_notifier,
             slot_status_notifier,
         })
@@ -185,6 +219,10 @@ impl GeyserPluginService {
         self.entry_notifier.clone()
     }
 
+    pub fn get_instruction_trace_notifier(&self) -> Option<InstructionTraceNotifierArc> {
+        self.instruction_trace_notifier.clone()
+    }
+
     pub fn get_block_metadata_notifier(&self) -> Option<BlockMetadataNotifierArc> {
         self.block_metadata_notifier.clone()
     }
@@ -197,6 +235,11 @@ impl GeyserPluginService {
         if let Some(mut slot_status_observer) = self.slot_status_observer {
             slot_status_observer.join()?;
         }
+        self.exit_instruction_trace_notifier
+            .store(true, Ordering::Relaxed);
+        if let Some(instruction_trace_notifier_thread) = self.instruction_trace_notifier_thread {
+            instruction_trace_notifier_thread.join()?;
+        }
         self.plugin_manager.write().unwrap().unload();
         Ok(())
     }
@@ -221,12 +264,21 @@ impl GeyserPluginService {
                         GeyserPluginManagerRequest::ReloadPlugin {
                             ref name,
                             ref config_file,
+                            replay_notifications,
                             response_sender,
                         } => {
-                            let reload_result = plugin_manager
-                                .write()
-                                .unwrap()
-                                .reload_plugin(name, config_file);
+                            let reload_result = if replay_notifications {
+                                GeyserPluginManager::reload_plugin_with_replay(
+                                    &plugin_manager,
+                                    name,
+                                    config_file,
+                                )
+                            } else {
+                                plugin_manager
+                                    .write()
+                                    .unwrap()
+                                    .reload_plugin(name, config_file)
+                            };
                             response_sender
                                 .send(reload_result)
                                 .expect("Admin rpc service will be waiting for response");
@@ -234,10 +286,20 @@ impl GeyserPluginService {
 
                         GeyserPluginManagerRequest::LoadPlugin {
                             ref config_file,
+                            ref replay_accounts_from,
                             response_sender,
                         } => {
-                            let load_result =
-                                plugin_manager.write().unwrap().load_plugin(config_file);
+                            let load_result = match replay_accounts_from {
+                                Some(source) => {
+                                    GeyserPluginManager::load_plugin_with_account_replay(
+                                        &plugin_manager,
+                                        config_file,
+                                        source,
+                                        exit.clone(),
+                                    )
+                                }
+                                None => plugin_manager.write().unwrap().load_plugin(config_file),
+                            };
                             response_sender
                                 .send(load_result)
                                 .expect("Admin rpc service will be waiting for response");
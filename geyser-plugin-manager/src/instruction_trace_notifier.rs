@@ -0,0 +1,336 @@
+/// Module responsible for notifying plugins of the instruction traces of executed transactions
+use {
+    crate::geyser_plugin_manager::GeyserPluginManager,
+    agave_geyser_plugin_interface::geyser_plugin_interface::{
+        ReplicaInstructionTrace, ReplicaInstructionTraceAccount, ReplicaInstructionTraceInfo,
+        ReplicaInstructionTraceInfoVersions,
+    },
+    crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender, TrySendError},
+    log::*,
+    solana_clock::Slot,
+    solana_measure::measure::Measure,
+    solana_metrics::*,
+    solana_program_runtime::invoke_context::InstructionTrace,
+    solana_signature::Signature,
+    solana_svm::instruction_trace_notifier::InstructionTraceNotifier,
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        thread::{Builder, JoinHandle},
+        time::Duration,
+    },
+};
+
+/// The maximum number of transactions whose instruction traces wait to be delivered to the
+/// plugins. Once reached, executing transactions waits for the plugins to catch up.
+const MAX_QUEUED_NOTIFICATIONS: usize = 10_000;
+
+const RECV_TIMEOUT: Duration = Duration::from_millis(100);
+
+struct InstructionTraceNotification {
+    slot: Slot,
+    signature: Signature,
+    instruction_traces: Vec<InstructionTrace>,
+}
+
+/// This implementation of InstructionTraceNotifier is set on the root bank's transaction
+/// processor at validator startup and inherited by its descendants. The transaction processor
+/// invokes notify_instruction_traces for every executed transaction, which queues the traces
+/// for the notifier thread. That thread invokes the notify_instruction_trace of each plugin
+/// enabled with instruction trace notification, so slow plugins do not hold up execution
+/// until the queue is full.
+pub(crate) struct InstructionTraceNotifierImpl {
+    sender: Sender<InstructionTraceNotification>,
+    account_data_enabled: bool,
+}
+
+impl InstructionTraceNotifier for InstructionTraceNotifierImpl {
+    fn notify_instruction_traces(
+        &self,
+        slot: Slot,
+        signature: &Signature,
+        _compute_unit_limit: u64,
+        instruction_traces: &[InstructionTrace],
+    ) {
+        let notification = InstructionTraceNotification {
+            slot,
+            signature: *signature,
+            instruction_traces: instruction_traces.to_vec(),
+        };
+        let notification = match self.sender.try_send(notification) {
+            Ok(()) => return,
+            Err(TrySendError::Full(notification)) => {
+                inc_new_counter_info!("geyser-plugin-instruction-trace-queue-full", 1);
+                notification
+            }
+            Err(TrySendError::Disconnected(_)) => return,
+        };
+        // The notifier thread has exited if this fails, so the traces are dropped
+        let _ = self.sender.send(notification);
+    }
+
+    fn account_data_enabled(&self) -> bool {
+        self.account_data_enabled
+    }
+}
+
+impl InstructionTraceNotifierImpl {
+    /// Returns the notifier with the handle of its notifier thread, which runs until `exit`
+    /// is set or the notifier is dropped. The traces notified only include account data if
+    /// `account_data_enabled`.
+    pub fn new(
+        plugin_manager: Arc<RwLock<GeyserPluginManager>>,
+        account_data_enabled: bool,
+        exit: Arc<AtomicBool>,
+    ) -> (Self, JoinHandle<()>) {
+        let (sender, receiver) = bounded(MAX_QUEUED_NOTIFICATIONS);
+        let notifier_thread = Builder::new()
+            .name("solGeyserInsTrc".to_string())
+            .spawn(move || Self::run(plugin_manager, receiver, exit))
+            .unwrap();
+        (
+            Self {
+                sender,
+                account_data_enabled,
+            },
+            notifier_thread,
+        )
+    }
+
+    fn run(
+        plugin_manager: Arc<RwLock<GeyserPluginManager>>,
+        receiver: Receiver<InstructionTraceNotification>,
+        exit: Arc<AtomicBool>,
+    ) {
+        while !exit.load(Ordering::Relaxed) {
+            match receiver.recv_timeout(RECV_TIMEOUT) {
+                Ok(notification) => Self::notify_plugins(&plugin_manager, &notification),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    }
+
+    fn notify_plugins(
+        plugin_manager: &RwLock<GeyserPluginManager>,
+        notification: &InstructionTraceNotification,
+    ) {
+        let mut measure = Measure::start("geyser-plugin-notify_plugins_of_instruction_traces");
+
+        let plugin_manager = plugin_manager.read().unwrap();
+        if plugin_manager.plugins.is_empty() {
+            return;
+        }
+
+        let accounts =
+            Self::build_replica_instruction_trace_accounts(&notification.instruction_traces);
+        let instructions =
+            Self::build_replica_instruction_traces(&notification.instruction_traces, &accounts);
+        let instruction_trace_info = ReplicaInstructionTraceInfo {
+            signature: &notification.signature,
+            instructions: &instructions,
+        };
+
+        for plugin in plugin_manager.plugins.iter() {
+            if !plugin.instruction_trace_notifications_enabled() {
+                continue;
+            }
+            match plugin.notify_instruction_trace(
+                ReplicaInstructionTraceInfoVersions::V0_0_1(&instruction_trace_info),
+                notification.slot,
+            ) {
+                Err(err) => {
+                    error!(
+                        "Failed to notify instruction trace, error: ({}) to plugin {}",
+                        err,
+                        plugin.name()
+                    )
+                }
+                Ok(_) => {
+                    trace!(
+                        "Successfully notified instruction trace to plugin {}",
+                        plugin.name()
+                    );
+                }
+            }
+        }
+        measure.stop();
+        inc_new_counter_debug!(
+            "geyser-plugin-notify_plugins_of_instruction_traces-us",
+            measure.as_us() as usize,
+            10000,
+            10000
+        );
+    }
+
+    /// The accounts of each of `instruction_traces`
+    fn build_replica_instruction_trace_accounts(
+        instruction_traces: &[InstructionTrace],
+    ) -> Vec<Vec<ReplicaInstructionTraceAccount<'_>>> {
+        instruction_traces
+            .iter()
+            .map(|instruction_trace| {
+                instruction_trace
+                    .accounts
+                    .iter()
+                    .map(|account| ReplicaInstructionTraceAccount {
+                        pubkey: account.pubkey.as_ref(),
+                        is_signer: account.is_signer,
+                        is_writable: account.is_writable,
+                        lamports: account.lamports,
+                        owner: account.owner.as_ref(),
+                        data: &account.data,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn build_replica_instruction_traces<'a>(
+        instruction_traces: &'a [InstructionTrace],
+        accounts: &'a [Vec<ReplicaInstructionTraceAccount<'a>>],
+    ) -> Vec<ReplicaInstructionTrace<'a>> {
+        instruction_traces
+            .iter()
+            .zip(accounts)
+            .map(|(instruction_trace, accounts)| {
+                let (return_data_program_id, return_data) = instruction_trace
+                    .return_data
+                    .as_ref()
+                    .map(|return_data| {
+                        (return_data.program_id.as_ref(), return_data.data.as_slice())
+                    })
+                    .unwrap_or_default();
+                ReplicaInstructionTrace {
+                    index_in_trace: instruction_trace.index_in_trace,
+                    program_id: instruction_trace.program_id.as_ref(),
+                    stack_height: instruction_trace.stack_height,
+                    compute_units_consumed: instruction_trace.compute_units_consumed,
+                    return_data_program_id,
+                    return_data,
+                    log_messages: &instruction_trace.log_messages,
+                    accounts,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::geyser_plugin_manager::LoadedGeyserPlugin,
+        agave_geyser_plugin_interface::geyser_plugin_interface::{GeyserPlugin, Result},
+        crossbeam_channel::unbounded,
+        libloading::Library,
+        solana_program_runtime::invoke_context::InstructionTraceAccount,
+        solana_pubkey::Pubkey,
+    };
+
+    /// The accounts of every instruction of a notification, with the slot and signature
+    type ReceivedAccounts = (Slot, Signature, Vec<(Vec<u8>, bool, u64, Vec<u8>)>);
+
+    #[derive(Debug)]
+    struct TracePlugin {
+        sender: Sender<ReceivedAccounts>,
+    }
+
+    impl GeyserPlugin for TracePlugin {
+        fn name(&self) -> &'static str {
+            "trace"
+        }
+
+        fn notify_instruction_trace(
+            &self,
+            instruction_trace: ReplicaInstructionTraceInfoVersions,
+            slot: Slot,
+        ) -> Result<()> {
+            let ReplicaInstructionTraceInfoVersions::V0_0_1(info) = instruction_trace;
+            let accounts = info
+                .instructions
+                .iter()
+                .flat_map(|instruction| instruction.accounts)
+                .map(|account| {
+                    (
+                        account.pubkey.to_vec(),
+                        account.is_writable,
+                        account.lamports,
+                        account.data.to_vec(),
+                    )
+                })
+                .collect();
+            self.sender.send((slot, *info.signature, accounts)).unwrap();
+            Ok(())
+        }
+
+        fn instruction_trace_notifications_enabled(&self) -> bool {
+            true
+        }
+
+        fn instruction_trace_account_data_enabled(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_notify_instruction_traces() {
+        let (sender, receiver) = unbounded();
+        #[cfg(unix)]
+        let library = libloading::os::unix::Library::this();
+        #[cfg(windows)]
+        let library = libloading::os::windows::Library::this().unwrap();
+        let mut plugin_manager = GeyserPluginManager::new();
+        plugin_manager.plugins.push(LoadedGeyserPlugin::new(
+            Library::from(library),
+            Box::new(TracePlugin { sender }),
+            None,
+        ));
+        let plugin_manager = Arc::new(RwLock::new(plugin_manager));
+        let exit = Arc::new(AtomicBool::new(false));
+        assert!(plugin_manager
+            .read()
+            .unwrap()
+            .instruction_trace_account_data_enabled());
+        let (notifier, notifier_thread) =
+            InstructionTraceNotifierImpl::new(plugin_manager.clone(), true, exit.clone());
+        assert!(notifier.account_data_enabled());
+
+        let account = InstructionTraceAccount {
+            pubkey: Pubkey::new_unique(),
+            is_signer: false,
+            is_writable: true,
+            lamports: 42,
+            owner: Pubkey::new_unique(),
+            data: vec![1, 2, 3],
+        };
+        let instruction_traces = vec![InstructionTrace {
+            program_id: Pubkey::new_unique(),
+            stack_height: 1,
+            accounts: vec![account.clone()],
+            ..InstructionTrace::default()
+        }];
+        let signature = Signature::from([7; 64]);
+
+        // Notifying does not wait for the plugins, which can't be notified while the plugin
+        // manager is locked
+        let plugin_manager_lock = plugin_manager.write().unwrap();
+        notifier.notify_instruction_traces(5, &signature, 200_000, &instruction_traces);
+        assert!(receiver.try_recv().is_err());
+        drop(plugin_manager_lock);
+
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(10)).unwrap(),
+            (
+                5,
+                signature,
+                vec![(account.pubkey.to_bytes().to_vec(), true, 42, vec![1, 2, 3])]
+            )
+        );
+
+        exit.store(true, Ordering::Relaxed);
+        notifier_thread.join().unwrap();
+    }
+}
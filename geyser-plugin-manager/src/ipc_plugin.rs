@@ -0,0 +1,854 @@
+//! A Geyser plugin hosted in another process
+//!
+//! Instead of loading a dynamic library, the validator connects to a Unix domain socket and
+//! streams notifications to whichever process listens on it. Such a plugin can be written in any
+//! language, and can crash or be restarted without affecting the validator: the connection is
+//! re-established whenever it is lost.
+//!
+//! An out-of-process plugin is configured with an `ipc` section instead of a `libpath`:
+//!
+//! ```json5
+//! {
+//!     name: "my-plugin",
+//!     ipc: {
+//!         socket_path: "/run/my-plugin.sock",
+//!         // The number of notifications queued while the plugin is slow or disconnected
+//!         buffer_capacity: 65536,
+//!         // What to do once the queue is full: "block" the validator, or "drop" notifications
+//!         overflow_policy: "drop",
+//!         // How long "block" waits for room in the queue before dropping the notification
+//!         block_timeout_ms: 1000,
+//!         accounts: true,
+//!         accounts_snapshot: false,
+//!         transactions: true,
+//!         entries: false,
+//!         instruction_traces: false,
+//!     },
+//! }
+//! ```
+//!
+//! Notifications are newline-delimited JSON objects, tagged by `type`. The first message on every
+//! connection is a `hello` carrying [`IPC_PROTOCOL_VERSION`], which is incremented whenever a
+//! message changes incompatibly. Byte strings are base64 encoded, keys and hashes base58 encoded,
+//! and transactions are the base64 encoding of their wire format.
+
+use {
+    agave_geyser_plugin_interface::geyser_plugin_interface::{
+        GeyserPlugin, GeyserPluginError, ReplicaAccountInfoVersions, ReplicaBlockInfoVersions,
+        ReplicaEntryInfoVersions, ReplicaInstructionTraceInfoVersions,
+        ReplicaTransactionInfoVersions, Result as PluginResult, SlotStatus,
+    },
+    base64::{prelude::BASE64_STANDARD, Engine},
+    crossbeam_channel::{
+        bounded, Receiver, RecvTimeoutError, SendTimeoutError, Sender, TrySendError,
+    },
+    log::*,
+    serde_derive::{Deserialize, Serialize},
+    solana_clock::{Slot, UnixTimestamp},
+    solana_metrics::*,
+    solana_transaction_status::{Reward, UiTransactionStatusMeta},
+    std::{
+        io::{BufWriter, Write},
+        os::unix::net::UnixStream,
+        path::PathBuf,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc,
+        },
+        thread::{self, JoinHandle},
+        time::Duration,
+    },
+};
+
+/// The version of the messages streamed to out-of-process plugins
+pub const IPC_PROTOCOL_VERSION: u32 = 1;
+
+const DEFAULT_BUFFER_CAPACITY: usize = 65_536;
+const DEFAULT_BLOCK_TIMEOUT_MS: u64 = 1_000;
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+const RECV_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// What to do with a notification when the queue of an out-of-process plugin is full
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IpcOverflowPolicy {
+    /// Wait for the plugin to catch up, slowing down the validator, for up to
+    /// `block_timeout_ms` before dropping the notification
+    Block,
+    /// Drop the notification
+    #[default]
+    Drop,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IpcPluginConfig {
+    pub socket_path: PathBuf,
+    #[serde(default = "default_buffer_capacity")]
+    pub buffer_capacity: usize,
+    #[serde(default)]
+    pub overflow_policy: IpcOverflowPolicy,
+    #[serde(default = "default_block_timeout_ms")]
+    pub block_timeout_ms: u64,
+    #[serde(default)]
+    pub accounts: bool,
+    #[serde(default)]
+    pub accounts_snapshot: bool,
+    #[serde(default)]
+    pub transactions: bool,
+    #[serde(default)]
+    pub entries: bool,
+    #[serde(default)]
+    pub instruction_traces: bool,
+    #[serde(default)]
+    pub instruction_trace_account_data: bool,
+}
+
+fn default_buffer_capacity() -> usize {
+    DEFAULT_BUFFER_CAPACITY
+}
+
+fn default_block_timeout_ms() -> u64 {
+    DEFAULT_BLOCK_TIMEOUT_MS
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum IpcMessage {
+    #[serde(rename_all = "camelCase")]
+    Hello {
+        protocol_version: u32,
+        name: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Account {
+        slot: Slot,
+        is_startup: bool,
+        pubkey: String,
+        lamports: u64,
+        owner: String,
+        executable: bool,
+        rent_epoch: u64,
+        data: String,
+        write_version: u64,
+        txn_signature: Option<String>,
+    },
+    EndOfStartup,
+    #[serde(rename_all = "camelCase")]
+    SlotStatus {
+        slot: Slot,
+        parent: Option<Slot>,
+        status: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        dead_error: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Transaction {
+        slot: Slot,
+        index: usize,
+        signature: String,
+        is_vote: bool,
+        transaction: String,
+        meta: UiTransactionStatusMeta,
+    },
+    #[serde(rename_all = "camelCase")]
+    Entry {
+        slot: Slot,
+        index: usize,
+        num_hashes: u64,
+        hash: String,
+        executed_transaction_count: u64,
+        starting_transaction_index: usize,
+    },
+    #[serde(rename_all = "camelCase")]
+    InstructionTrace {
+        slot: Slot,
+        signature: String,
+        instructions: Vec<IpcInstructionTrace>,
+    },
+    #[serde(rename_all = "camelCase")]
+    BlockMetadata {
+        slot: Slot,
+        parent_slot: Slot,
+        blockhash: String,
+        parent_blockhash: String,
+        rewards: Vec<Reward>,
+        num_partitions: Option<u64>,
+        block_time: Option<UnixTimestamp>,
+        block_height: Option<u64>,
+        executed_transaction_count: u64,
+        entry_count: u64,
+    },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IpcInstructionTrace {
+    index_in_trace: usize,
+    program_id: String,
+    stack_height: usize,
+    compute_units_consumed: u64,
+    return_data_program_id: Option<String>,
+    return_data: String,
+    log_messages: Vec<String>,
+    accounts: Vec<IpcInstructionTraceAccount>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IpcInstructionTraceAccount {
+    pubkey: String,
+    is_signer: bool,
+    is_writable: bool,
+    lamports: u64,
+    owner: String,
+    data: String,
+}
+
+/// Streams notifications to a plugin listening on a Unix domain socket
+#[derive(Debug)]
+pub struct IpcGeyserPlugin {
+    config: IpcPluginConfig,
+    name: String,
+    sender: Option<Sender<Vec<u8>>>,
+    writer: Option<JoinHandle<()>>,
+    exit: Arc<AtomicBool>,
+    dropped_notifications: Arc<AtomicU64>,
+}
+
+impl IpcGeyserPlugin {
+    pub fn new(name: String, config: IpcPluginConfig) -> Self {
+        Self {
+            config,
+            name,
+            sender: None,
+            writer: None,
+            exit: Arc::default(),
+            dropped_notifications: Arc::default(),
+        }
+    }
+
+    fn send(&self, message: &IpcMessage) -> PluginResult<()> {
+        let Some(sender) = &self.sender else {
+            return Err(GeyserPluginError::Custom(
+                format!("out-of-process plugin {} is not loaded", self.name).into(),
+            ));
+        };
+        let mut line =
+            serde_json::to_vec(message).map_err(|err| GeyserPluginError::Custom(Box::new(err)))?;
+        line.push(b'\n');
+        // The writer only exits once the sender is dropped, so the queue cannot be disconnected
+        let dropped = match self.config.overflow_policy {
+            IpcOverflowPolicy::Block => matches!(
+                sender.send_timeout(line, Duration::from_millis(self.config.block_timeout_ms)),
+                Err(SendTimeoutError::Timeout(_))
+            ),
+            IpcOverflowPolicy::Drop => {
+                matches!(sender.try_send(line), Err(TrySendError::Full(_)))
+            }
+        };
+        if dropped {
+            self.dropped_notifications.fetch_add(1, Ordering::Relaxed);
+            inc_new_counter_debug!("geyser-plugin-ipc-dropped-notifications", 1);
+        }
+        Ok(())
+    }
+}
+
+impl GeyserPlugin for IpcGeyserPlugin {
+    fn name(&self) -> &'static str {
+        "ipc"
+    }
+
+    fn on_load(&mut self, _config_file: &str, _is_reload: bool) -> PluginResult<()> {
+        let (sender, receiver) = bounded(self.config.buffer_capacity);
+        let hello = IpcMessage::Hello {
+            protocol_version: IPC_PROTOCOL_VERSION,
+            name: self.name.clone(),
+        };
+        let mut hello = serde_json::to_vec(&hello).expect("hello message serializes");
+        hello.push(b'\n');
+        let writer = IpcWriter {
+            socket_path: self.config.socket_path.clone(),
+            hello,
+            receiver,
+            exit: self.exit.clone(),
+        };
+        self.writer = Some(
+            thread::Builder::new()
+                .name("solGeyserIpc".to_string())
+                .spawn(move || writer.run())
+                .map_err(|err| GeyserPluginError::Custom(Box::new(err)))?,
+        );
+        self.sender = Some(sender);
+        Ok(())
+    }
+
+    fn on_unload(&mut self) {
+        // Dropping the sender lets the writer flush the queue and exit, unless the plugin is
+        // disconnected, in which case the queued notifications are discarded
+        self.sender = None;
+        self.exit.store(true, Ordering::Relaxed);
+        if let Some(writer) = self.writer.take() {
+            if writer.join().is_err() {
+                error!(
+                    "Writer thread of out-of-process plugin {} panicked",
+                    self.name
+                );
+            }
+        }
+        let dropped_notifications = self.dropped_notifications.load(Ordering::Relaxed);
+        if dropped_notifications > 0 {
+            warn!(
+                "Out-of-process plugin {} dropped {dropped_notifications} notifications",
+                self.name
+            );
+        }
+    }
+
+    fn update_account(
+        &self,
+        account: ReplicaAccountInfoVersions,
+        slot: Slot,
+        is_startup: bool,
+    ) -> PluginResult<()> {
+        let message = match account {
+            ReplicaAccountInfoVersions::V0_0_1(account) => IpcMessage::Account {
+                slot,
+                is_startup,
+                pubkey: bs58::encode(account.pubkey).into_string(),
+                lamports: account.lamports,
+                owner: bs58::encode(account.owner).into_string(),
+                executable: account.executable,
+                rent_epoch: account.rent_epoch,
+                data: BASE64_STANDARD.encode(account.data),
+                write_version: account.write_version,
+                txn_signature: None,
+            },
+            ReplicaAccountInfoVersions::V0_0_2(account) => IpcMessage::Account {
+                slot,
+                is_startup,
+                pubkey: bs58::encode(account.pubkey).into_string(),
+                lamports: account.lamports,
+                owner: bs58::encode(account.owner).into_string(),
+                executable: account.executable,
+                rent_epoch: account.rent_epoch,
+                data: BASE64_STANDARD.encode(account.data),
+                write_version: account.write_version,
+                txn_signature: account.txn_signature.map(ToString::to_string),
+            },
+            ReplicaAccountInfoVersions::V0_0_3(account) => IpcMessage::Account {
+                slot,
+                is_startup,
+                pubkey: bs58::encode(account.pubkey).into_string(),
+                lamports: account.lamports,
+                owner: bs58::encode(account.owner).into_string(),
+                executable: account.executable,
+                rent_epoch: account.rent_epoch,
+                data: BASE64_STANDARD.encode(account.data),
+                write_version: account.write_version,
+                txn_signature: account.txn.map(|txn| txn.signature().to_string()),
+            },
+        };
+        self.send(&message)
+    }
+
+    fn notify_end_of_startup(&self) -> PluginResult<()> {
+        self.send(&IpcMessage::EndOfStartup)
+    }
+
+    fn update_slot_status(
+        &self,
+        slot: Slot,
+        parent: Option<Slot>,
+        status: &SlotStatus,
+    ) -> PluginResult<()> {
+        let dead_error = match status {
+            SlotStatus::Dead(error) => Some(error.clone()),
+            _ => None,
+        };
+        self.send(&IpcMessage::SlotStatus {
+            slot,
+            parent,
+            status: status.as_str(),
+            dead_error,
+        })
+    }
+
+    fn notify_transaction(
+        &self,
+        transaction: ReplicaTransactionInfoVersions,
+        slot: Slot,
+    ) -> PluginResult<()> {
+        let (signature, is_vote, transaction, meta, index) = match transaction {
+            ReplicaTransactionInfoVersions::V0_0_1(info) => (
+                info.signature,
+                info.is_vote,
+                info.transaction.to_versioned_transaction(),
+                info.transaction_status_meta,
+                0,
+            ),
+            ReplicaTransactionInfoVersions::V0_0_2(info) => (
+                info.signature,
+                info.is_vote,
+                info.transaction.to_versioned_transaction(),
+                info.transaction_status_meta,
+                info.index,
+            ),
+            ReplicaTransactionInfoVersions::V0_0_3(info) => (
+                info.signature,
+                info.is_vote,
+                info.transaction.clone(),
+                info.transaction_status_meta,
+                info.index,
+            ),
+        };
+        let transaction = bincode::serialize(&transaction).map_err(|err| {
+            GeyserPluginError::TransactionUpdateError {
+                msg: err.to_string(),
+            }
+        })?;
+        self.send(&IpcMessage::Transaction {
+            slot,
+            index,
+            signature: signature.to_string(),
+            is_vote,
+            transaction: BASE64_STANDARD.encode(transaction),
+            meta: meta.clone().into(),
+        })
+    }
+
+    fn notify_entry(&self, entry: ReplicaEntryInfoVersions) -> PluginResult<()> {
+        let message = match entry {
+            ReplicaEntryInfoVersions::V0_0_1(entry) => IpcMessage::Entry {
+                slot: entry.slot,
+                index: entry.index,
+                num_hashes: entry.num_hashes,
+                hash: bs58::encode(entry.hash).into_string(),
+                executed_transaction_count: entry.executed_transaction_count,
+                starting_transaction_index: 0,
+            },
+            ReplicaEntryInfoVersions::V0_0_2(entry) => IpcMessage::Entry {
+                slot: entry.slot,
+                index: entry.index,
+                num_hashes: entry.num_hashes,
+                hash: bs58::encode(entry.hash).into_string(),
+                executed_transaction_count: entry.executed_transaction_count,
+                starting_transaction_index: entry.starting_transaction_index,
+            },
+        };
+        self.send(&message)
+    }
+
+    fn notify_instruction_trace(
+        &self,
+        instruction_trace: ReplicaInstructionTraceInfoVersions,
+        slot: Slot,
+    ) -> PluginResult<()> {
+        let message = match instruction_trace {
+            ReplicaInstructionTraceInfoVersions::V0_0_1(info) => IpcMessage::InstructionTrace {
+                slot,
+                signature: info.signature.to_string(),
+                instructions: info
+                    .instructions
+                    .iter()
+                    .map(|instruction| IpcInstructionTrace {
+                        index_in_trace: instruction.index_in_trace,
+                        program_id: bs58::encode(instruction.program_id).into_string(),
+                        stack_height: instruction.stack_height,
+                        compute_units_consumed: instruction.compute_units_consumed,
+                        return_data_program_id: (!instruction.return_data_program_id.is_empty())
+                            .then(|| {
+                                bs58::encode(instruction.return_data_program_id).into_string()
+                            }),
+                        return_data: BASE64_STANDARD.encode(instruction.return_data),
+                        log_messages: instruction.log_messages.to_vec(),
+                        accounts: instruction
+                            .accounts
+                            .iter()
+                            .map(|account| IpcInstructionTraceAccount {
+                                pubkey: bs58::encode(account.pubkey).into_string(),
+                                is_signer: account.is_signer,
+                                is_writable: account.is_writable,
+                                lamports: account.lamports,
+                                owner: bs58::encode(account.owner).into_string(),
+                                data: BASE64_STANDARD.encode(account.data),
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            },
+        };
+        self.send(&message)
+    }
+
+    fn notify_block_metadata(&self, blockinfo: ReplicaBlockInfoVersions) -> PluginResult<()> {
+        let message = match blockinfo {
+            ReplicaBlockInfoVersions::V0_0_1(block) => IpcMessage::BlockMetadata {
+                slot: block.slot,
+                parent_slot: 0,
+                blockhash: block.blockhash.to_string(),
+                parent_blockhash: String::new(),
+                rewards: block.rewards.to_vec(),
+                num_partitions: None,
+                block_time: block.block_time,
+                block_height: block.block_height,
+                executed_transaction_count: 0,
+                entry_count: 0,
+            },
+            ReplicaBlockInfoVersions::V0_0_2(block) => IpcMessage::BlockMetadata {
+                slot: block.slot,
+                parent_slot: block.parent_slot,
+                blockhash: block.blockhash.to_string(),
+                parent_blockhash: block.parent_blockhash.to_string(),
+                rewards: block.rewards.to_vec(),
+                num_partitions: None,
+                block_time: block.block_time,
+                block_height: block.block_height,
+                executed_transaction_count: block.executed_transaction_count,
+                entry_count: 0,
+            },
+            ReplicaBlockInfoVersions::V0_0_3(block) => IpcMessage::BlockMetadata {
+                slot: block.slot,
+                parent_slot: block.parent_slot,
+                blockhash: block.blockhash.to_string(),
+                parent_blockhash: block.parent_blockhash.to_string(),
+                rewards: block.rewards.to_vec(),
+                num_partitions: None,
+                block_time: block.block_time,
+                block_height: block.block_height,
+                executed_transaction_count: block.executed_transaction_count,
+                entry_count: block.entry_count,
+            },
+            ReplicaBlockInfoVersions::V0_0_4(block) => IpcMessage::BlockMetadata {
+                slot: block.slot,
+                parent_slot: block.parent_slot,
+                blockhash: block.blockhash.to_string(),
+                parent_blockhash: block.parent_blockhash.to_string(),
+                rewards: block.rewards.rewards.clone(),
+                num_partitions: block.rewards.num_partitions,
+                block_time: block.block_time,
+                block_height: block.block_height,
+                executed_transaction_count: block.executed_transaction_count,
+                entry_count: block.entry_count,
+            },
+        };
+        self.send(&message)
+    }
+
+    fn account_data_notifications_enabled(&self) -> bool {
+        self.config.accounts
+    }
+
+    fn account_data_snapshot_notifications_enabled(&self) -> bool {
+        self.config.accounts_snapshot
+    }
+
+    fn transaction_notifications_enabled(&self) -> bool {
+        self.config.transactions
+    }
+
+    fn entry_notifications_enabled(&self) -> bool {
+        self.config.entries
+    }
+
+    fn instruction_trace_notifications_enabled(&self) -> bool {
+        self.config.instruction_traces
+    }
+
+    fn instruction_trace_account_data_enabled(&self) -> bool {
+        self.config.instruction_trace_account_data
+    }
+}
+
+/// Writes queued notifications to the plugin's socket, reconnecting whenever the connection is
+/// lost
+struct IpcWriter {
+    socket_path: PathBuf,
+    hello: Vec<u8>,
+    receiver: Receiver<Vec<u8>>,
+    exit: Arc<AtomicBool>,
+}
+
+impl IpcWriter {
+    fn run(self) {
+        // A notification which could not be written before the connection was lost
+        let mut pending = None;
+        while let Some(stream) = self.connect() {
+            let mut stream = BufWriter::new(stream);
+            if let Err(err) = stream.write_all(&self.hello) {
+                warn!("Failed to write to {:?}: {err}", self.socket_path);
+                continue;
+            }
+            loop {
+                let line = match pending.take() {
+                    Some(line) => line,
+                    None => match self.receiver.recv_timeout(RECV_TIMEOUT) {
+                        Ok(line) => line,
+                        Err(RecvTimeoutError::Timeout) => {
+                            if let Err(err) = stream.flush() {
+                                warn!("Failed to write to {:?}: {err}", self.socket_path);
+                                break;
+                            }
+                            continue;
+                        }
+                        Err(RecvTimeoutError::Disconnected) => {
+                            let _ = stream.flush();
+                            return;
+                        }
+                    },
+                };
+                let result = stream.write_all(&line).and_then(|_| {
+                    if self.receiver.is_empty() {
+                        stream.flush()
+                    } else {
+                        Ok(())
+                    }
+                });
+                if let Err(err) = result {
+                    warn!("Failed to write to {:?}: {err}", self.socket_path);
+                    pending = Some(line);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Connect to the plugin, retrying until it listens or the plugin is unloaded
+    fn connect(&self) -> Option<UnixStream> {
+        let mut logged = false;
+        loop {
+            if self.exit.load(Ordering::Relaxed) && self.receiver.is_empty() {
+                return None;
+            }
+            match UnixStream::connect(&self.socket_path) {
+                Ok(stream) => {
+                    info!(
+                        "Connected to out-of-process plugin at {:?}",
+                        self.socket_path
+                    );
+                    return Some(stream);
+                }
+                Err(err) => {
+                    if !logged {
+                        warn!(
+                            "Failed to connect to out-of-process plugin at {:?}: {err}, retrying",
+                            self.socket_path
+                        );
+                        logged = true;
+                    }
+                    if self.exit.load(Ordering::Relaxed) {
+                        return None;
+                    }
+                    thread::sleep(RECONNECT_INTERVAL);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        agave_geyser_plugin_interface::geyser_plugin_interface::{
+            ReplicaAccountInfoV3, ReplicaInstructionTrace, ReplicaInstructionTraceInfo,
+        },
+        solana_signature::Signature,
+        std::{
+            io::{BufRead, BufReader},
+            os::unix::net::UnixListener,
+        },
+    };
+
+    #[test]
+    fn test_ipc_plugin_streams_notifications() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("plugin.sock");
+        let listener = UnixListener::bind(&socket_path).unwrap();
+
+        let config: IpcPluginConfig = serde_json::from_value(serde_json::json!({
+            "socket_path": socket_path,
+            "overflow_policy": "block",
+            "accounts": true,
+        }))
+        .unwrap();
+        assert_eq!(config.buffer_capacity, DEFAULT_BUFFER_CAPACITY);
+        let mut plugin = IpcGeyserPlugin::new("test".to_string(), config);
+        assert!(plugin.account_data_notifications_enabled());
+        assert!(!plugin.transaction_notifications_enabled());
+        plugin.on_load("", false).unwrap();
+
+        let pubkey = [1u8; 32];
+        let owner = [2u8; 32];
+        let account = ReplicaAccountInfoV3 {
+            pubkey: &pubkey,
+            lamports: 42,
+            owner: &owner,
+            executable: false,
+            rent_epoch: 0,
+            data: &[1, 2, 3],
+            write_version: 7,
+            txn: None,
+        };
+        plugin
+            .update_account(ReplicaAccountInfoVersions::V0_0_3(&account), 5, false)
+            .unwrap();
+        plugin
+            .update_slot_status(5, Some(4), &SlotStatus::Rooted)
+            .unwrap();
+        let program_id = [3u8; 32];
+        let log_messages = vec!["Program log: hi".to_string()];
+        let accounts = [ReplicaInstructionTraceAccount {
+            pubkey: &[5u8; 32],
+            is_signer: true,
+            is_writable: false,
+            lamports: 42,
+            owner: &program_id,
+            data: &[6, 7],
+        }];
+        let instructions = [ReplicaInstructionTrace {
+            index_in_trace: 0,
+            program_id: &program_id,
+            stack_height: 1,
+            compute_units_consumed: 150,
+            return_data_program_id: &[],
+            return_data: &[],
+            log_messages: &log_messages,
+            accounts: &accounts,
+        }];
+        let signature = Signature::from([4u8; 64]);
+        plugin
+            .notify_instruction_trace(
+                ReplicaInstructionTraceInfoVersions::V0_0_1(&ReplicaInstructionTraceInfo {
+                    signature: &signature,
+                    instructions: &instructions,
+                }),
+                5,
+            )
+            .unwrap();
+        plugin.on_unload();
+
+        let (stream, _) = listener.accept().unwrap();
+        let messages: Vec<serde_json::Value> = BufReader::new(stream)
+            .lines()
+            .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+            .collect();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[0]["type"], "hello");
+        assert_eq!(messages[0]["protocolVersion"], IPC_PROTOCOL_VERSION);
+        assert_eq!(messages[1]["type"], "account");
+        assert_eq!(messages[1]["pubkey"], bs58::encode(pubkey).into_string());
+        assert_eq!(messages[1]["lamports"], 42);
+        assert_eq!(messages[1]["data"], "AQID");
+        assert_eq!(messages[1]["writeVersion"], 7);
+        assert_eq!(messages[2]["type"], "slotStatus");
+        assert_eq!(messages[2]["status"], "rooted");
+        assert_eq!(messages[2]["parent"], 4);
+        assert_eq!(messages[3]["type"], "instructionTrace");
+        assert_eq!(messages[3]["signature"], signature.to_string());
+        let instruction = &messages[3]["instructions"][0];
+        assert_eq!(
+            instruction["programId"],
+            bs58::encode(program_id).into_string()
+        );
+        assert_eq!(instruction["stackHeight"], 1);
+        assert_eq!(instruction["computeUnitsConsumed"], 150);
+        assert!(instruction["returnDataProgramId"].is_null());
+        assert_eq!(instruction["logMessages"][0], "Program log: hi");
+        let account = &instruction["accounts"][0];
+        assert_eq!(account["pubkey"], bs58::encode([5u8; 32]).into_string());
+        assert_eq!(account["isSigner"], true);
+        assert_eq!(account["isWritable"], false);
+        assert_eq!(account["lamports"], 42);
+        assert_eq!(account["data"], BASE64_STANDARD.encode([6, 7]));
+    }
+
+    #[test]
+    fn test_ipc_plugin_config() {
+        let config: IpcPluginConfig = serde_json::from_value(serde_json::json!({
+            "socket_path": "plugin.sock",
+        }))
+        .unwrap();
+        assert_eq!(
+            config,
+            IpcPluginConfig {
+                socket_path: PathBuf::from("plugin.sock"),
+                buffer_capacity: DEFAULT_BUFFER_CAPACITY,
+                overflow_policy: IpcOverflowPolicy::Drop,
+                block_timeout_ms: DEFAULT_BLOCK_TIMEOUT_MS,
+                accounts: false,
+                accounts_snapshot: false,
+                transactions: false,
+                entries: false,
+                instruction_traces: false,
+                instruction_trace_account_data: false,
+            }
+        );
+
+        let config: IpcPluginConfig = serde_json::from_value(serde_json::json!({
+            "socket_path": "/run/plugin.sock",
+            "buffer_capacity": 16,
+            "overflow_policy": "block",
+            "block_timeout_ms": 10,
+            "instruction_traces": true,
+        }))
+        .unwrap();
+        assert_eq!(config.buffer_capacity, 16);
+        assert_eq!(config.overflow_policy, IpcOverflowPolicy::Block);
+        assert_eq!(config.block_timeout_ms, 10);
+        assert!(config.instruction_traces);
+
+        // The socket path is required, and typos are rejected
+        assert!(serde_json::from_value::<IpcPluginConfig>(serde_json::json!({})).is_err());
+        assert!(
+            serde_json::from_value::<IpcPluginConfig>(serde_json::json!({
+                "socket_path": "plugin.sock",
+                "overflow_policy": "wait",
+            }))
+            .is_err()
+        );
+        assert!(
+            serde_json::from_value::<IpcPluginConfig>(serde_json::json!({
+                "socket_path": "plugin.sock",
+                "transaction": true,
+            }))
+            .is_err()
+        );
+    }
+
+    fn test_drops_notifications_when_full(overflow_policy: IpcOverflowPolicy) {
+        let dir = tempfile::tempdir().unwrap();
+        let config = IpcPluginConfig {
+            socket_path: dir.path().join("missing.sock"),
+            buffer_capacity: 1,
+            overflow_policy,
+            block_timeout_ms: 10,
+            accounts: false,
+            accounts_snapshot: false,
+            transactions: false,
+            entries: false,
+            instruction_traces: false,
+            instruction_trace_account_data: false,
+        };
+        let mut plugin = IpcGeyserPlugin::new("test".to_string(), config);
+        plugin.on_load("", false).unwrap();
+        for slot in 0..3 {
+            plugin
+                .update_slot_status(slot, None, &SlotStatus::Processed)
+                .unwrap();
+        }
+        assert_eq!(plugin.dropped_notifications.load(Ordering::Relaxed), 2);
+        plugin.on_unload();
+    }
+
+    #[test]
+    fn test_ipc_plugin_drops_notifications_when_full() {
+        test_drops_notifications_when_full(IpcOverflowPolicy::Drop);
+    }
+
+    #[test]
+    fn test_ipc_plugin_block_times_out_when_full() {
+        test_drops_notifications_when_full(IpcOverflowPolicy::Block);
+    }
+}
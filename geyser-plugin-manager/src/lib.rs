@@ -4,8 +4,12 @@ pub mod block_metadata_notifier_interface;
 pub mod entry_notifier;
 pub mod geyser_plugin_manager;
 pub mod geyser_plugin_service;
+pub mod instruction_trace_notifier;
+#[cfg(unix)]
+pub mod ipc_plugin;
+pub mod notification_buffer;
 pub mod slot_status_notifier;
 pub mod slot_status_observer;
 pub mod transaction_notifier;
 
-pub use geyser_plugin_manager::GeyserPluginManagerRequest;
+pub use geyser_plugin_manager::{AccountsReplaySource, GeyserPluginManagerRequest};
@@ -0,0 +1,364 @@
+/// Module responsible for buffering notifications while a plugin is swapped, so they can be
+/// replayed to the new plugin instance
+use {
+    crate::geyser_plugin_manager::LoadedGeyserPlugin,
+    agave_geyser_plugin_interface::geyser_plugin_interface::{
+        ReplicaAccountInfoV3, ReplicaAccountInfoVersions, ReplicaTransactionInfoV3,
+        ReplicaTransactionInfoVersions, Result as PluginResult, SlotStatus,
+    },
+    log::*,
+    solana_clock::Slot,
+    solana_hash::Hash,
+    solana_signature::Signature,
+    solana_transaction::{sanitized::SanitizedTransaction, versioned::VersionedTransaction},
+    solana_transaction_status::{TransactionStatusMeta, TransactionTokenBalance},
+    std::mem::size_of,
+};
+
+/// The maximum estimated size, in bytes, of the notifications buffered during a swap. Once
+/// exceeded the buffered notifications are discarded and the swap fails, so a plugin which
+/// takes too long to load cannot exhaust the validator's memory.
+pub const MAX_BUFFERED_NOTIFICATION_BYTES: usize = 1 << 30;
+
+/// An owned copy of a notification
+#[derive(Debug)]
+pub(crate) enum BufferedNotification {
+    Account {
+        slot: Slot,
+        is_startup: bool,
+        pubkey: Vec<u8>,
+        lamports: u64,
+        owner: Vec<u8>,
+        executable: bool,
+        rent_epoch: u64,
+        data: Vec<u8>,
+        write_version: u64,
+        txn: Option<SanitizedTransaction>,
+    },
+    Transaction {
+        slot: Slot,
+        index: usize,
+        signature: Signature,
+        message_hash: Hash,
+        is_vote: bool,
+        transaction: VersionedTransaction,
+        transaction_status_meta: TransactionStatusMeta,
+    },
+    SlotStatus {
+        slot: Slot,
+        parent: Option<Slot>,
+        status: SlotStatus,
+    },
+}
+
+impl BufferedNotification {
+    pub(crate) fn account(account: &ReplicaAccountInfoV3, slot: Slot, is_startup: bool) -> Self {
+        Self::Account {
+            slot,
+            is_startup,
+            pubkey: account.pubkey.to_vec(),
+            lamports: account.lamports,
+            owner: account.owner.to_vec(),
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+            data: account.data.to_vec(),
+            write_version: account.write_version,
+            txn: account.txn.cloned(),
+        }
+    }
+
+    pub(crate) fn transaction(transaction: &ReplicaTransactionInfoV3, slot: Slot) -> Self {
+        Self::Transaction {
+            slot,
+            index: transaction.index,
+            signature: *transaction.signature,
+            message_hash: *transaction.message_hash,
+            is_vote: transaction.is_vote,
+            transaction: transaction.transaction.clone(),
+            transaction_status_meta: transaction.transaction_status_meta.clone(),
+        }
+    }
+
+    /// The estimated memory used by this notification, in bytes
+    fn size(&self) -> usize {
+        let heap_size = match self {
+            Self::Account {
+                pubkey,
+                owner,
+                data,
+                txn,
+                ..
+            } => pubkey
+                .len()
+                .saturating_add(owner.len())
+                .saturating_add(data.len())
+                .saturating_add(txn.as_ref().map_or(0, sanitized_transaction_size)),
+            Self::Transaction {
+                transaction,
+                transaction_status_meta,
+                ..
+            } => bincode::serialized_size(transaction)
+                .map_or(0, |size| size as usize)
+                .saturating_add(transaction_status_meta_size(transaction_status_meta)),
+            Self::SlotStatus { .. } => 0,
+        };
+        size_of::<Self>().saturating_add(heap_size)
+    }
+
+    /// Deliver this notification to `plugin`, subject to the same filtering as live notifications
+    fn notify(&self, plugin: &LoadedGeyserPlugin) -> PluginResult<()> {
+        match self {
+            Self::Account {
+                slot,
+                is_startup,
+                pubkey,
+                lamports,
+                owner,
+                executable,
+                rent_epoch,
+                data,
+                write_version,
+                txn,
+            } => {
+                let account = ReplicaAccountInfoV3 {
+                    pubkey,
+                    lamports: *lamports,
+                    owner,
+                    executable: *executable,
+                    rent_epoch: *rent_epoch,
+                    data,
+                    write_version: *write_version,
+                    txn: txn.as_ref(),
+                };
+                plugin.update_account(
+                    ReplicaAccountInfoVersions::V0_0_3(&account),
+                    *slot,
+                    *is_startup,
+                )
+            }
+            Self::Transaction {
+                slot,
+                index,
+                signature,
+                message_hash,
+                is_vote,
+                transaction,
+                transaction_status_meta,
+            } => {
+                if !plugin.transaction_notifications_enabled() {
+                    return Ok(());
+                }
+                let transaction = ReplicaTransactionInfoV3 {
+                    signature,
+                    message_hash,
+                    is_vote: *is_vote,
+                    transaction,
+                    transaction_status_meta,
+                    index: *index,
+                };
+                plugin
+                    .notify_transaction(ReplicaTransactionInfoVersions::V0_0_3(&transaction), *slot)
+            }
+            Self::SlotStatus {
+                slot,
+                parent,
+                status,
+            } => plugin.update_slot_status(*slot, *parent, status),
+        }
+    }
+}
+
+fn sanitized_transaction_size(transaction: &SanitizedTransaction) -> usize {
+    let message = transaction.message();
+    let instructions_size = message
+        .instructions()
+        .iter()
+        .map(|instruction| {
+            instruction
+                .data
+                .len()
+                .saturating_add(instruction.accounts.len())
+        })
+        .sum::<usize>();
+    transaction
+        .signatures()
+        .len()
+        .saturating_mul(size_of::<Signature>())
+        .saturating_add(message.account_keys().len().saturating_mul(32))
+        .saturating_add(instructions_size)
+}
+
+fn transaction_status_meta_size(meta: &TransactionStatusMeta) -> usize {
+    let balances_size = meta
+        .pre_balances
+        .len()
+        .saturating_add(meta.post_balances.len())
+        .saturating_mul(size_of::<u64>());
+    let token_balances_size = [&meta.pre_token_balances, &meta.post_token_balances]
+        .into_iter()
+        .flatten()
+        .map(|balances| {
+            balances
+                .iter()
+                .map(|balance| {
+                    size_of::<TransactionTokenBalance>()
+                        .saturating_add(balance.mint.len())
+                        .saturating_add(balance.owner.len())
+                        .saturating_add(balance.program_id.len())
+                        .saturating_add(balance.ui_token_amount.amount.len())
+                        .saturating_add(balance.ui_token_amount.ui_amount_string.len())
+                })
+                .sum::<usize>()
+        })
+        .sum::<usize>();
+    let log_messages_size = meta
+        .log_messages
+        .iter()
+        .flatten()
+        .map(String::len)
+        .sum::<usize>();
+    let inner_instructions_size = meta
+        .inner_instructions
+        .iter()
+        .flatten()
+        .flat_map(|inner_instructions| &inner_instructions.instructions)
+        .map(|inner_instruction| {
+            inner_instruction
+                .instruction
+                .data
+                .len()
+                .saturating_add(inner_instruction.instruction.accounts.len())
+        })
+        .sum::<usize>();
+    let return_data_size = meta
+        .return_data
+        .as_ref()
+        .map_or(0, |return_data| return_data.data.len());
+    balances_size
+        .saturating_add(token_balances_size)
+        .saturating_add(log_messages_size)
+        .saturating_add(inner_instructions_size)
+        .saturating_add(return_data_size)
+}
+
+/// Notifications received while a plugin is swapped, in the order they were received
+#[derive(Debug)]
+pub(crate) struct NotificationBuffer {
+    notifications: Vec<BufferedNotification>,
+    /// The estimated size of `notifications`
+    size: usize,
+    max_size: usize,
+    /// Set once the buffer exceeded `max_size`. The new plugin would miss notifications, so
+    /// nothing more is buffered and the swap must fail.
+    overflowed: bool,
+}
+
+impl Default for NotificationBuffer {
+    fn default() -> Self {
+        Self {
+            notifications: Vec::default(),
+            size: 0,
+            max_size: MAX_BUFFERED_NOTIFICATION_BYTES,
+            overflowed: false,
+        }
+    }
+}
+
+impl NotificationBuffer {
+    pub(crate) fn push(&mut self, notification: BufferedNotification) {
+        if self.overflowed {
+            return;
+        }
+        let size = self.size.saturating_add(notification.size());
+        if size > self.max_size {
+            self.notifications = Vec::default();
+            self.size = 0;
+            self.overflowed = true;
+        } else {
+            self.notifications.push(notification);
+            self.size = size;
+        }
+    }
+
+    /// Take the notifications buffered so far, leaving the buffer empty. Returns `None` if the
+    /// buffer overflowed, since some notifications were lost.
+    pub(crate) fn take(&mut self) -> Option<Vec<BufferedNotification>> {
+        if self.overflowed {
+            return None;
+        }
+        self.size = 0;
+        Some(std::mem::take(&mut self.notifications))
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_max_size(max_size: usize) -> Self {
+        Self {
+            max_size,
+            ..Self::default()
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.notifications.len()
+    }
+}
+
+/// Deliver `notifications` to `plugin` in order
+pub(crate) fn replay(notifications: Vec<BufferedNotification>, plugin: &LoadedGeyserPlugin) {
+    let replayed = notifications.len();
+    for notification in notifications {
+        if let Err(err) = notification.notify(plugin) {
+            error!(
+                "Failed to replay notification, error: ({}) to plugin {}",
+                err,
+                plugin.name()
+            );
+        }
+    }
+    debug!(
+        "Replayed {replayed} buffered notifications to plugin {}",
+        plugin.name()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_buffer_overflow() {
+        let account = |data_len| BufferedNotification::Account {
+            slot: 0,
+            is_startup: false,
+            pubkey: vec![0; 32],
+            lamports: 1,
+            owner: vec![0; 32],
+            executable: false,
+            rent_epoch: 0,
+            data: vec![0; data_len],
+            write_version: 0,
+            txn: None,
+        };
+        let max_size = account(1_000).size() * 3 / 2;
+        let mut buffer = NotificationBuffer::with_max_size(max_size);
+
+        buffer.push(account(10));
+        buffer.push(account(20));
+        assert_eq!(buffer.take().unwrap().len(), 2);
+        assert_eq!(buffer.size, 0);
+
+        // Draining the buffer frees room for more notifications
+        buffer.push(account(1_000));
+        assert_eq!(buffer.take().unwrap().len(), 1);
+        buffer.push(account(1_000));
+        assert_eq!(buffer.len(), 1);
+
+        // Exceeding the limit discards everything, including later notifications
+        buffer.push(account(1_000));
+        assert_eq!(buffer.len(), 0);
+        buffer.push(account(10));
+        assert_eq!(buffer.len(), 0);
+        assert!(buffer.take().is_none());
+    }
+}
@@ -1,5 +1,7 @@
 use {
-    crate::geyser_plugin_manager::GeyserPluginManager,
+    crate::{
+        geyser_plugin_manager::GeyserPluginManager, notification_buffer::BufferedNotification,
+    },
     agave_geyser_plugin_interface::geyser_plugin_interface::SlotStatus,
     log::*,
     solana_clock::Slot,
@@ -50,6 +52,11 @@ impl SlotStatusNotifierImpl {
 
     pub fn notify_slot_status(&self, slot: Slot, parent: Option<Slot>, slot_status: SlotStatus) {
         let plugin_manager = self.plugin_manager.read().unwrap();
+        plugin_manager.buffer_notification(|| BufferedNotification::SlotStatus {
+            slot,
+            parent,
+            status: slot_status.clone(),
+        });
         if plugin_manager.plugins.is_empty() {
             return;
         }
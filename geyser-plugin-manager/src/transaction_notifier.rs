@@ -1,6 +1,8 @@
 /// Module responsible for notifying plugins of transactions
 use {
-    crate::geyser_plugin_manager::GeyserPluginManager,
+    crate::{
+        geyser_plugin_manager::GeyserPluginManager, notification_buffer::BufferedNotification,
+    },
     agave_geyser_plugin_interface::geyser_plugin_interface::{
         ReplicaTransactionInfoV3, ReplicaTransactionInfoVersions,
     },
@@ -46,6 +48,8 @@ impl TransactionNotifier for TransactionNotifierImpl {
         );
 
         let plugin_manager = self.plugin_manager.read().unwrap();
+        plugin_manager
+            .buffer_notification(|| BufferedNotification::transaction(&transaction_log_info, slot));
 
         if plugin_manager.plugins.is_empty() {
             return;
@@ -0,0 +1,228 @@
+use {
+    base64::{prelude::BASE64_STANDARD, Engine},
+    serde_derive::Serialize,
+    solana_account::{AccountSharedData, ReadableAccount},
+    solana_accounts_db::accounts_update_notifier_interface::{
+        AccountForGeyser, AccountsUpdateNotifierInterface,
+    },
+    solana_clock::{Epoch, Slot},
+    solana_pubkey::Pubkey,
+    solana_transaction::sanitized::SanitizedTransaction,
+    std::{
+        collections::HashMap,
+        io::{self, Write},
+        str::FromStr,
+        sync::Mutex,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountDiffFormat {
+    /// One JSON object per line
+    Json,
+    Csv,
+}
+
+impl FromStr for AccountDiffFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!("unknown account diff format: {s}")),
+        }
+    }
+}
+
+/// Collects every account written while replaying, keyed by slot
+///
+/// Only the last write of an account within a slot is kept, so the collected accounts are the
+/// state of each written account at the end of the slot.
+#[derive(Debug, Default)]
+pub struct AccountDiffRecorder {
+    slots: Mutex<HashMap<Slot, HashMap<Pubkey, (u64, AccountSharedData)>>>,
+}
+
+impl AccountDiffRecorder {
+    /// Remove and return the accounts written in `slot`, sorted by pubkey
+    pub fn take_slot(&self, slot: Slot) -> Vec<(Pubkey, AccountSharedData)> {
+        let accounts = self.slots.lock().unwrap().remove(&slot);
+        let mut accounts: Vec<_> = accounts
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(pubkey, (_write_version, account))| (pubkey, account))
+            .collect();
+        accounts.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        accounts
+    }
+}
+
+impl AccountsUpdateNotifierInterface for AccountDiffRecorder {
+    fn snapshot_notifications_enabled(&self) -> bool {
+        false
+    }
+
+    fn notify_account_update(
+        &self,
+        slot: Slot,
+        account: &AccountSharedData,
+        _txn: &Option<&SanitizedTransaction>,
+        pubkey: &Pubkey,
+        write_version: u64,
+    ) {
+        let mut slots = self.slots.lock().unwrap();
+        let entry = slots
+            .entry(slot)
+            .or_default()
+            .entry(*pubkey)
+            .or_insert_with(|| (write_version, account.clone()));
+        if entry.0 <= write_version {
+            *entry = (write_version, account.clone());
+        }
+    }
+
+    fn notify_account_restore_from_snapshot(
+        &self,
+        _slot: Slot,
+        _write_version: u64,
+        _account: &AccountForGeyser<'_>,
+    ) {
+    }
+
+    fn notify_end_of_restore_from_snapshot(&self) {}
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountDiffRecord {
+    slot: Slot,
+    pubkey: String,
+    owner: String,
+    lamports: u64,
+    executable: bool,
+    rent_epoch: Epoch,
+    data_len: usize,
+    data_hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<String>,
+}
+
+impl AccountDiffRecord {
+    fn new(slot: Slot, pubkey: &Pubkey, account: &AccountSharedData, include_data: bool) -> Self {
+        Self {
+            slot,
+            pubkey: pubkey.to_string(),
+            owner: account.owner().to_string(),
+            lamports: account.lamports(),
+            executable: account.executable(),
+            rent_epoch: account.rent_epoch(),
+            data_len: account.data().len(),
+            data_hash: solana_sha256_hasher::hash(account.data()).to_string(),
+            data: include_data.then(|| BASE64_STANDARD.encode(account.data())),
+        }
+    }
+}
+
+enum AccountDiffSink<W: Write> {
+    Json(W),
+    Csv(csv::Writer<W>),
+}
+
+/// Writes the accounts collected by an [`AccountDiffRecorder`] one slot at a time
+pub struct AccountDiffWriter<W: Write> {
+    sink: AccountDiffSink<W>,
+    include_data: bool,
+}
+
+impl<W: Write> AccountDiffWriter<W> {
+    pub fn new(writer: W, format: AccountDiffFormat, include_data: bool) -> Self {
+        let sink = match format {
+            AccountDiffFormat::Json => AccountDiffSink::Json(writer),
+            AccountDiffFormat::Csv => AccountDiffSink::Csv(csv::Writer::from_writer(writer)),
+        };
+        Self { sink, include_data }
+    }
+
+    pub fn write_slot(
+        &mut self,
+        slot: Slot,
+        accounts: &[(Pubkey, AccountSharedData)],
+    ) -> io::Result<()> {
+        for (pubkey, account) in accounts {
+            let record = AccountDiffRecord::new(slot, pubkey, account, self.include_data);
+            match &mut self.sink {
+                AccountDiffSink::Json(writer) => {
+                    serde_json::to_writer(&mut *writer, &record)?;
+                    writeln!(writer)?;
+                }
+                AccountDiffSink::Csv(writer) => writer.serialize(&record)?,
+            }
+        }
+        self.flush()
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match &mut self.sink {
+            AccountDiffSink::Json(writer) => writer.flush(),
+            AccountDiffSink::Csv(writer) => writer.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_diff_recorder_keeps_last_write() {
+        let recorder = AccountDiffRecorder::default();
+        let pubkey = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let first = AccountSharedData::new(1, 0, &owner);
+        let second = AccountSharedData::new(2, 0, &owner);
+
+        recorder.notify_account_update(5, &second, &None, &pubkey, 2);
+        recorder.notify_account_update(5, &first, &None, &pubkey, 1);
+        recorder.notify_account_update(6, &first, &None, &pubkey, 3);
+
+        assert_eq!(recorder.take_slot(5), vec![(pubkey, second)]);
+        assert!(recorder.take_slot(5).is_empty());
+        assert_eq!(recorder.take_slot(6), vec![(pubkey, first)]);
+    }
+
+    #[test]
+    fn test_account_diff_writer() {
+        let pubkey = Pubkey::new_unique();
+        let account = AccountSharedData::new(42, 3, &Pubkey::default());
+        let accounts = vec![(pubkey, account)];
+
+        let mut writer = AccountDiffWriter::new(Vec::new(), AccountDiffFormat::Json, true);
+        writer.write_slot(7, &accounts).unwrap();
+        let AccountDiffSink::Json(output) = writer.sink else {
+            unreachable!()
+        };
+        let record: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(record["slot"], 7);
+        assert_eq!(record["pubkey"], pubkey.to_string());
+        assert_eq!(record["lamports"], 42);
+        assert_eq!(record["dataLen"], 3);
+        assert_eq!(record["data"], "AAAA");
+
+        let mut writer = AccountDiffWriter::new(Vec::new(), AccountDiffFormat::Csv, false);
+        writer.write_slot(7, &accounts).unwrap();
+        let AccountDiffSink::Csv(output) = writer.sink else {
+            unreachable!()
+        };
+        let output = String::from_utf8(output.into_inner().unwrap()).unwrap();
+        let mut lines = output.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "slot,pubkey,owner,lamports,executable,rentEpoch,dataLen,dataHash"
+        );
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with(&format!("7,{pubkey},11111111111111111111111111111111,42,")));
+    }
+}
@@ -0,0 +1,611 @@
+//! The `banking-trace` subcommand
+
+use {
+    crate::{
+        error::{LedgerToolError, Result},
+        ledger_path::canonicalize_ledger_path,
+    },
+    agave_feature_set::FeatureSet,
+    base64::{prelude::BASE64_STANDARD, Engine},
+    chrono::{DateTime, SecondsFormat, Utc},
+    clap::{value_t, values_t, App, AppSettings, Arg, ArgMatches, SubCommand},
+    log::*,
+    serde_derive::Serialize,
+    solana_clap_utils::input_validators::{is_parsable, is_pubkey, is_slot},
+    solana_cli_output::OutputFormat,
+    solana_clock::Slot,
+    solana_core::banking_trace::{
+        BankingTracer, ChannelLabel, TimedTracedEvent, TraceError, TracedEvent,
+    },
+    solana_hash::Hash,
+    solana_ledger::blockstore::banking_trace_path,
+    solana_pubkey::Pubkey,
+    solana_runtime_transaction::{
+        runtime_transaction::RuntimeTransaction, transaction_meta::StaticMeta,
+    },
+    solana_signature::Signature,
+    solana_transaction::{
+        sanitized::MessageHash,
+        versioned::{sanitized::SanitizedVersionedTransaction, VersionedTransaction},
+    },
+    std::{
+        collections::{BTreeMap, HashSet},
+        fs::File,
+        io::{stdout, BufWriter, Write},
+        path::{Path, PathBuf},
+        time::SystemTime,
+    },
+};
+
+const CHANNEL_NAMES: &[&str] = &["non-vote", "tpu-vote", "gossip-vote"];
+
+fn channel_name(label: ChannelLabel) -> &'static str {
+    match label {
+        ChannelLabel::NonVote => "non-vote",
+        ChannelLabel::TpuVote => "tpu-vote",
+        ChannelLabel::GossipVote => "gossip-vote",
+        ChannelLabel::Dummy => "dummy",
+    }
+}
+
+pub trait BankingTraceSubCommand {
+    fn banking_trace_subcommand(self) -> Self;
+}
+
+impl BankingTraceSubCommand for App<'_, '_> {
+    fn banking_trace_subcommand(self) -> Self {
+        let trace_dir_arg = Arg::with_name("trace_dir")
+            .long("trace-dir")
+            .value_name("DIR")
+            .takes_value(true)
+            .help("Banking trace directory [default: the banking_trace directory of the ledger]");
+        let filter_args = vec![
+            Arg::with_name("signature")
+                .long("signature")
+                .value_name("SIGNATURE")
+                .takes_value(true)
+                .multiple(true)
+                .validator(is_parsable::<Signature>)
+                .help("Only include packets of transactions with this signature"),
+            Arg::with_name("fee_payer")
+                .long("fee-payer")
+                .value_name("PUBKEY")
+                .takes_value(true)
+                .multiple(true)
+                .validator(is_pubkey)
+                .help("Only include packets of transactions paid for by this account"),
+            Arg::with_name("min_compute_unit_price")
+                .long("min-compute-unit-price")
+                .value_name("MICROLAMPORTS")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .help(
+                    "Only include packets of transactions with at least this priority fee per \
+                     compute unit",
+                ),
+            Arg::with_name("slot")
+                .long("slot")
+                .value_name("SLOT")
+                .takes_value(true)
+                .multiple(true)
+                .validator(is_slot)
+                .help("Only include packets received while this slot was the next to be frozen"),
+            Arg::with_name("channel")
+                .long("channel")
+                .value_name("CHANNEL")
+                .takes_value(true)
+                .multiple(true)
+                .possible_values(CHANNEL_NAMES)
+                .help("Only include packets sent through this channel"),
+            Arg::with_name("include_discarded")
+                .long("include-discarded")
+                .takes_value(false)
+                .help(
+                    "Include packets which were discarded before the banking stage, such as \
+                     packets failing signature verification",
+                ),
+        ];
+
+        self.subcommand(
+            SubCommand::with_name("banking-trace")
+                .about("Commands to inspect the banking trace of a validator")
+                .setting(AppSettings::InferSubcommands)
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("info")
+                        .about(
+                            "Print the format version, event files, time range and slots of the \
+                             trace",
+                        )
+                        .arg(&trace_dir_arg),
+                )
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("List the traced packets, in the order they were traced")
+                        .arg(&trace_dir_arg)
+                        .args(&filter_args),
+                )
+                .subcommand(
+                    SubCommand::with_name("export")
+                        .about(
+                            "Export the traced packets as JSON, one object per line, in the order \
+                             they were traced",
+                        )
+                        .arg(&trace_dir_arg)
+                        .args(&filter_args)
+                        .arg(
+                            Arg::with_name("output_file")
+                                .long("output-file")
+                                .value_name("FILE")
+                                .takes_value(true)
+                                .help("Write to FILE instead of stdout"),
+                        )
+                        .arg(
+                            Arg::with_name("include_transaction")
+                                .long("include-transaction")
+                                .takes_value(false)
+                                .help("Include the base64-encoded packet data"),
+                        ),
+                ),
+        )
+    }
+}
+
+/// A packet of the trace, as it is listed and exported
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TracedPacket {
+    /// When the packet was sent to the banking stage, in RFC 3339 format
+    time: String,
+    /// The first slot frozen after the packet was received, if any
+    slot: Option<Slot>,
+    channel: &'static str,
+    /// The fields below are only set if the packet holds a valid transaction
+    signature: Option<String>,
+    fee_payer: Option<String>,
+    compute_unit_price: Option<u64>,
+    compute_unit_limit: Option<u32>,
+    discarded: bool,
+    forwarded: bool,
+    from_staked_node: bool,
+    source: String,
+    size: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transaction: Option<String>,
+}
+
+struct PacketFilter {
+    signatures: Option<HashSet<Signature>>,
+    fee_payers: Option<HashSet<Pubkey>>,
+    min_compute_unit_price: Option<u64>,
+    slots: Option<HashSet<Slot>>,
+    channels: Option<HashSet<String>>,
+    include_discarded: bool,
+}
+
+impl PacketFilter {
+    fn from_matches(matches: &ArgMatches<'_>) -> Self {
+        Self {
+            signatures: values_t!(matches, "signature", Signature)
+                .ok()
+                .map(HashSet::from_iter),
+            fee_payers: values_t!(matches, "fee_payer", Pubkey)
+                .ok()
+                .map(HashSet::from_iter),
+            min_compute_unit_price: value_t!(matches, "min_compute_unit_price", u64).ok(),
+            slots: values_t!(matches, "slot", Slot)
+                .ok()
+                .map(HashSet::from_iter),
+            channels: values_t!(matches, "channel", String)
+                .ok()
+                .map(HashSet::from_iter),
+            include_discarded: matches.is_present("include_discarded"),
+        }
+    }
+
+    /// Whether `packet` passes the filters, except for the slot one
+    fn matches(&self, packet: &ParsedPacket) -> bool {
+        (self.include_discarded || !packet.discarded)
+            && self
+                .channels
+                .as_ref()
+                .is_none_or(|channels| channels.contains(channel_name(packet.label)))
+            && self.signatures.as_ref().is_none_or(|signatures| {
+                packet
+                    .signature
+                    .is_some_and(|signature| signatures.contains(&signature))
+            })
+            && self.fee_payers.as_ref().is_none_or(|fee_payers| {
+                packet
+                    .fee_payer
+                    .is_some_and(|fee_payer| fee_payers.contains(&fee_payer))
+            })
+            && self.min_compute_unit_price.is_none_or(|min_price| {
+                packet
+                    .compute_unit_price
+                    .is_some_and(|price| price >= min_price)
+            })
+    }
+
+    fn matches_slot(&self, slot: Option<Slot>) -> bool {
+        self.slots
+            .as_ref()
+            .is_none_or(|slots| slot.is_some_and(|slot| slots.contains(&slot)))
+    }
+}
+
+struct ParsedPacket {
+    time: SystemTime,
+    label: ChannelLabel,
+    signature: Option<Signature>,
+    fee_payer: Option<Pubkey>,
+    compute_unit_price: Option<u64>,
+    compute_unit_limit: Option<u32>,
+    discarded: bool,
+    forwarded: bool,
+    from_staked_node: bool,
+    source: String,
+    size: usize,
+    /// Only kept if the packet data is exported
+    data: Option<Vec<u8>>,
+}
+
+impl ParsedPacket {
+    fn into_traced_packet(self, slot: Option<Slot>) -> TracedPacket {
+        TracedPacket {
+            time: DateTime::<Utc>::from(self.time).to_rfc3339_opts(SecondsFormat::Micros, true),
+            slot,
+            channel: channel_name(self.label),
+            signature: self.signature.map(|signature| signature.to_string()),
+            fee_payer: self.fee_payer.map(|fee_payer| fee_payer.to_string()),
+            compute_unit_price: self.compute_unit_price,
+            compute_unit_limit: self.compute_unit_limit,
+            discarded: self.discarded,
+            forwarded: self.forwarded,
+            from_staked_node: self.from_staked_node,
+            source: self.source,
+            size: self.size,
+            transaction: self.data.map(|data| BASE64_STANDARD.encode(data)),
+        }
+    }
+}
+
+/// The signature, fee payer and compute budget of the transaction in `data`
+fn parse_transaction(data: &[u8]) -> Option<(Signature, Pubkey, u64, u32)> {
+    let transaction: VersionedTransaction = bincode::deserialize(data).ok()?;
+    let transaction = SanitizedVersionedTransaction::try_from(transaction).ok()?;
+    // The message hash isn't used, so don't spend time computing it
+    let transaction = RuntimeTransaction::<SanitizedVersionedTransaction>::try_from(
+        transaction,
+        MessageHash::Precomputed(Hash::default()),
+        None,
+    )
+    .ok()?;
+    let signature = *transaction.get_signatures().first()?;
+    let fee_payer = *transaction
+        .get_message()
+        .message
+        .static_account_keys()
+        .first()?;
+    let limits = transaction
+        .compute_budget_instruction_details()
+        .sanitize_and_convert_to_compute_budget_limits(&FeatureSet::all_enabled())
+        .ok()?;
+    Some((
+        signature,
+        fee_payer,
+        limits.compute_unit_price,
+        limits.compute_unit_limit,
+    ))
+}
+
+/// Reads every event of the trace in `trace_dir`, oldest first
+fn read_trace(trace_dir: &Path, mut callback: impl FnMut(TimedTracedEvent)) -> Result<()> {
+    let version = BankingTracer::read_format_version(trace_dir)?;
+    let event_file_paths = BankingTracer::event_file_paths(trace_dir);
+    if event_file_paths.is_empty() {
+        return Err(LedgerToolError::BadArgument(format!(
+            "no banking trace event files found in {}",
+            trace_dir.display()
+        )));
+    }
+    info!("Reading banking trace format version {version} from {event_file_paths:?}");
+    for event_file_path in &event_file_paths {
+        match BankingTracer::read_events(event_file_path, &mut callback) {
+            Ok(()) => {}
+            Err(TraceError::SerializeError(err)) if matches!(&*err, bincode::ErrorKind::Io(_)) => {
+                warn!(
+                    "Reading {event_file_path:?} failed {err:?} due to file corruption or \
+                     unclean validator shutdown",
+                );
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(())
+}
+
+/// Reads the time each traced slot was frozen at
+fn read_freeze_times(trace_dir: &Path) -> Result<BTreeMap<SystemTime, Slot>> {
+    let mut slot_by_freeze_time = BTreeMap::new();
+    read_trace(trace_dir, |TimedTracedEvent(time, event)| {
+        if let TracedEvent::BlockAndBankHash(slot, _blockhash, _bank_hash) = event {
+            slot_by_freeze_time.insert(time, slot);
+        }
+    })?;
+    Ok(slot_by_freeze_time)
+}
+
+/// Calls `callback` with each packet of the trace which passes `filter` and the slot it was
+/// received in, in the order they were traced. The packet data is only kept if `include_data`
+/// is set. Stops at the first error returned by `callback`.
+fn for_each_packet(
+    trace_dir: &Path,
+    filter: &PacketFilter,
+    include_data: bool,
+    mut callback: impl FnMut(ParsedPacket, Option<Slot>) -> Result<()>,
+) -> Result<()> {
+    // Slots are frozen after their packets are traced, so find every freeze time first
+    let slot_by_freeze_time = read_freeze_times(trace_dir)?;
+    let mut result = Ok(());
+    read_trace(trace_dir, |TimedTracedEvent(time, event)| {
+        let TracedEvent::PacketBatch(label, batch) = event else {
+            return;
+        };
+        if result.is_err() {
+            return;
+        }
+        let slot = slot_of(&slot_by_freeze_time, time);
+        if !filter.matches_slot(slot) {
+            return;
+        }
+        for packet in batch.iter().flat_map(|packet_batch| packet_batch.iter()) {
+            let meta = packet.meta();
+            let data = packet.data(..).unwrap_or_default();
+            let parsed = parse_transaction(data);
+            let packet = ParsedPacket {
+                time,
+                label,
+                signature: parsed.map(|(signature, ..)| signature),
+                fee_payer: parsed.map(|(_, fee_payer, ..)| fee_payer),
+                compute_unit_price: parsed.map(|(_, _, price, _)| price),
+                compute_unit_limit: parsed.map(|(.., limit)| limit),
+                discarded: meta.discard(),
+                forwarded: meta.forwarded(),
+                from_staked_node: meta.is_from_staked_node(),
+                source: meta.addr.to_string(),
+                size: data.len(),
+                data: include_data.then(|| data.to_vec()),
+            };
+            if filter.matches(&packet) {
+                if let Err(err) = callback(packet, slot) {
+                    result = Err(err);
+                    return;
+                }
+            }
+        }
+    })?;
+    result
+}
+
+fn slot_of(slot_by_freeze_time: &BTreeMap<SystemTime, Slot>, time: SystemTime) -> Option<Slot> {
+    slot_by_freeze_time
+        .range(time..)
+        .next()
+        .map(|(_time, slot)| *slot)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceInfo {
+    format_version: u32,
+    event_files: Vec<String>,
+    first_event_time: Option<String>,
+    last_event_time: Option<String>,
+    first_slot: Option<Slot>,
+    last_slot: Option<Slot>,
+    num_frozen_slots: usize,
+    num_packet_batches: usize,
+    num_packets_by_channel: BTreeMap<&'static str, usize>,
+}
+
+fn trace_info(trace_dir: &Path) -> Result<TraceInfo> {
+    let format_time =
+        |time: SystemTime| DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Micros, true);
+    let mut first_event_time = None::<SystemTime>;
+    let mut last_event_time = None::<SystemTime>;
+    let mut slots = vec![];
+    let mut num_packet_batches = 0;
+    let mut num_packets_by_channel = BTreeMap::new();
+    read_trace(trace_dir, |TimedTracedEvent(time, event)| {
+        first_event_time = Some(first_event_time.map_or(time, |first| first.min(time)));
+        last_event_time = Some(last_event_time.map_or(time, |last| last.max(time)));
+        match event {
+            TracedEvent::PacketBatch(label, batch) => {
+                num_packet_batches += 1;
+                *num_packets_by_channel
+                    .entry(channel_name(label))
+                    .or_default() += batch
+                    .iter()
+                    .map(|packet_batch| packet_batch.len())
+                    .sum::<usize>();
+            }
+            TracedEvent::BlockAndBankHash(slot, _blockhash, _bank_hash) => slots.push(slot),
+        }
+    })?;
+    Ok(TraceInfo {
+        format_version: BankingTracer::read_format_version(trace_dir)?,
+        event_files: BankingTracer::event_file_paths(trace_dir)
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect(),
+        first_event_time: first_event_time.map(format_time),
+        last_event_time: last_event_time.map(format_time),
+        first_slot: slots.iter().min().copied(),
+        last_slot: slots.iter().max().copied(),
+        num_frozen_slots: slots.len(),
+        num_packet_batches,
+        num_packets_by_channel,
+    })
+}
+
+pub fn banking_trace_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
+    do_banking_trace_process_command(ledger_path, matches).unwrap_or_else(|err| {
+        eprintln!("Failed to complete command: {err:?}");
+        std::process::exit(1);
+    });
+}
+
+fn trace_dir(ledger_path: &Path, matches: &ArgMatches<'_>) -> PathBuf {
+    value_t!(matches, "trace_dir", String)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| banking_trace_path(&canonicalize_ledger_path(ledger_path)))
+}
+
+fn do_banking_trace_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -> Result<()> {
+    match matches.subcommand() {
+        ("info", Some(arg_matches)) => {
+            let trace_dir = trace_dir(ledger_path, arg_matches);
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
+            let info = trace_info(&trace_dir)?;
+            match output_format {
+                OutputFormat::Json | OutputFormat::JsonCompact => {
+                    println!("{}", serde_json::to_string_pretty(&info)?)
+                }
+                _ => {
+                    println!("Format version: {}", info.format_version);
+                    println!("Event files: {}", info.event_files.join(", "));
+                    if let (Some(first), Some(last)) =
+                        (&info.first_event_time, &info.last_event_time)
+                    {
+                        println!("Events: {first} to {last}");
+                    }
+                    if let (Some(first), Some(last)) = (info.first_slot, info.last_slot) {
+                        println!(
+                            "Frozen slots: {} between {first} and {last}",
+                            info.num_frozen_slots
+                        );
+                    }
+                    println!("Packet batches: {}", info.num_packet_batches);
+                    for (channel, num_packets) in &info.num_packets_by_channel {
+                        println!("Packets through {channel}: {num_packets}");
+                    }
+                }
+            }
+        }
+        ("list", Some(arg_matches)) => {
+            let trace_dir = trace_dir(ledger_path, arg_matches);
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
+            let filter = PacketFilter::from_matches(arg_matches);
+            match output_format {
+                OutputFormat::Json | OutputFormat::JsonCompact => {
+                    let mut packets = vec![];
+                    for_each_packet(&trace_dir, &filter, false, |packet, slot| {
+                        packets.push(packet.into_traced_packet(slot));
+                        Ok(())
+                    })?;
+                    println!("{}", serde_json::to_string_pretty(&packets)?)
+                }
+                _ => {
+                    let mut stdout = stdout().lock();
+                    for_each_packet(&trace_dir, &filter, false, |packet, slot| {
+                        let packet = packet.into_traced_packet(slot);
+                        let slot = packet
+                            .slot
+                            .map_or_else(|| "-".to_string(), |slot| slot.to_string());
+                        writeln!(
+                            stdout,
+                            "{} {slot:>12} {:<11} {:<88} {:<44} {:>12}{}{}",
+                            packet.time,
+                            packet.channel,
+                            packet
+                                .signature
+                                .as_deref()
+                                .unwrap_or("<invalid transaction>"),
+                            packet.fee_payer.as_deref().unwrap_or("-"),
+                            packet
+                                .compute_unit_price
+                                .map_or_else(|| "-".to_string(), |price| price.to_string()),
+                            if packet.discarded { " discarded" } else { "" },
+                            if packet.forwarded { " forwarded" } else { "" },
+                        )?;
+                        Ok(())
+                    })?;
+                }
+            }
+        }
+        ("export", Some(arg_matches)) => {
+            let trace_dir = trace_dir(ledger_path, arg_matches);
+            let filter = PacketFilter::from_matches(arg_matches);
+            let include_transaction = arg_matches.is_present("include_transaction");
+            let mut output: Box<dyn Write> = match value_t!(arg_matches, "output_file", String) {
+                Ok(output_file) => Box::new(BufWriter::new(File::create(output_file)?)),
+                Err(_) => Box::new(BufWriter::new(stdout())),
+            };
+            for_each_packet(&trace_dir, &filter, include_transaction, |packet, slot| {
+                serde_json::to_writer(&mut output, &packet.into_traced_packet(slot))?;
+                writeln!(output)?;
+                Ok(())
+            })?;
+            output.flush()?;
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, solana_keypair::Keypair, solana_signer::Signer,
+        solana_system_interface::instruction as system_instruction,
+        solana_transaction::Transaction,
+    };
+
+    #[test]
+    fn test_parse_transaction() {
+        let payer = Keypair::new();
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                solana_compute_budget_interface::ComputeBudgetInstruction::set_compute_unit_price(
+                    42,
+                ),
+                system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1),
+            ],
+            Some(&payer.pubkey()),
+            &[&payer],
+            Hash::new_unique(),
+        );
+        let data = bincode::serialize(&VersionedTransaction::from(transaction.clone())).unwrap();
+
+        let (signature, fee_payer, compute_unit_price, _compute_unit_limit) =
+            parse_transaction(&data).unwrap();
+        assert_eq!(signature, transaction.signatures[0]);
+        assert_eq!(fee_payer, payer.pubkey());
+        assert_eq!(compute_unit_price, 42);
+
+        assert!(parse_transaction(&data[..data.len() - 1]).is_none());
+    }
+
+    #[test]
+    fn test_slot_of() {
+        let now = SystemTime::now();
+        let slot_by_freeze_time =
+            BTreeMap::from([(now, 10), (now + std::time::Duration::from_millis(400), 11)]);
+        assert_eq!(slot_of(&slot_by_freeze_time, now), Some(10));
+        assert_eq!(
+            slot_of(
+                &slot_by_freeze_time,
+                now + std::time::Duration::from_millis(1)
+            ),
+            Some(11)
+        );
+        assert_eq!(
+            slot_of(
+                &slot_by_freeze_time,
+                now + std::time::Duration::from_secs(1)
+            ),
+            None
+        );
+    }
+}
@@ -38,12 +38,14 @@ use {
     solana_pubkey::Pubkey,
     solana_shred_version::compute_shred_version,
     solana_signature::Signature,
-    solana_storage_bigtable::CredentialType,
+    solana_storage_bigtable::{
+        CredentialType, FileLedgerStorageConfig, LongTermLedgerStorageConfig,
+    },
     solana_transaction_status::{ConfirmedBlock, UiTransactionEncoding, VersionedConfirmedBlock},
     std::{
         cmp::min,
         collections::HashSet,
-        path::Path,
+        path::{Path, PathBuf},
         process::exit,
         result::Result,
         str::FromStr,
@@ -56,9 +58,9 @@ async fn upload(
     starting_slot: Option<Slot>,
     ending_slot: Option<Slot>,
     force_reupload: bool,
-    config: solana_storage_bigtable::LedgerStorageConfig,
+    config: LongTermLedgerStorageConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config)
+    let bigtable = solana_storage_bigtable::new_long_term_ledger_storage(config)
         .await
         .map_err(|err| format!("Failed to connect to storage: {err:?}"))?;
 
@@ -102,10 +104,10 @@ async fn upload(
 
 async fn delete_slots(
     slots: Vec<Slot>,
-    config: solana_storage_bigtable::LedgerStorageConfig,
+    config: LongTermLedgerStorageConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let dry_run = config.read_only;
-    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config)
+    let dry_run = config.read_only();
+    let bigtable = solana_storage_bigtable::new_long_term_ledger_storage(config)
         .await
         .map_err(|err| format!("Failed to connect to storage: {err:?}"))?;
 
@@ -113,9 +115,9 @@ async fn delete_slots(
 }
 
 async fn first_available_block(
-    config: solana_storage_bigtable::LedgerStorageConfig,
+    config: LongTermLedgerStorageConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config).await?;
+    let bigtable = solana_storage_bigtable::new_long_term_ledger_storage(config).await?;
     match bigtable.get_first_available_block().await? {
         Some(block) => println!("{block}"),
         None => println!("No blocks available"),
@@ -128,9 +130,9 @@ async fn block(
     slot: Slot,
     output_format: OutputFormat,
     show_entries: bool,
-    config: solana_storage_bigtable::LedgerStorageConfig,
+    config: LongTermLedgerStorageConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config)
+    let bigtable = solana_storage_bigtable::new_long_term_ledger_storage(config)
         .await
         .map_err(|err| format!("Failed to connect to storage: {err:?}"))?;
 
@@ -160,15 +162,15 @@ async fn block(
 async fn entries(
     slot: Slot,
     output_format: OutputFormat,
-    config: solana_storage_bigtable::LedgerStorageConfig,
+    config: LongTermLedgerStorageConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config)
+    let bigtable = solana_storage_bigtable::new_long_term_ledger_storage(config)
         .await
         .map_err(|err| format!("Failed to connect to storage: {err:?}"))?;
 
     let entries = bigtable.get_entries(slot).await?;
     let cli_entries = CliEntries {
-        entries: entries.map(Into::into).collect(),
+        entries: entries.into_iter().map(Into::into).collect(),
         slot,
     };
     println!("{}", output_format.formatted_string(&cli_entries));
@@ -206,6 +208,8 @@ fn get_shred_config_from_ledger(
         blockstore.clone(),
         process_options,
         None,
+        None,
+        None,
     );
 
     let bank = bank_forks.read().unwrap().working_bank();
@@ -256,9 +260,9 @@ async fn shreds(
     starting_slot: Slot,
     ending_slot: Slot,
     shred_config: ShredConfig,
-    config: solana_storage_bigtable::LedgerStorageConfig,
+    config: LongTermLedgerStorageConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config)
+    let bigtable = solana_storage_bigtable::new_long_term_ledger_storage(config)
         .await
         .map_err(|err| format!("Failed to connect to storage: {err:?}"))?;
 
@@ -283,6 +287,7 @@ async fn shreds(
 
         let entries = match entry_summaries {
             Ok(entry_summaries) => entry_summaries
+                .into_iter()
                 .enumerate()
                 .map(|(i, entry_summary)| {
                     let num_hashes = entry_summary.num_hashes;
@@ -410,9 +415,9 @@ async fn shreds(
 async fn blocks(
     starting_slot: Slot,
     limit: usize,
-    config: solana_storage_bigtable::LedgerStorageConfig,
+    config: LongTermLedgerStorageConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config)
+    let bigtable = solana_storage_bigtable::new_long_term_ledger_storage(config)
         .await
         .map_err(|err| format!("Failed to connect to storage: {err:?}"))?;
 
@@ -426,7 +431,7 @@ async fn blocks(
 async fn compare_blocks(
     starting_slot: Slot,
     limit: usize,
-    config: solana_storage_bigtable::LedgerStorageConfig,
+    config: LongTermLedgerStorageConfig,
     ref_config: solana_storage_bigtable::LedgerStorageConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let reference_bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(ref_config)
@@ -446,7 +451,7 @@ async fn compare_blocks(
         return Ok(());
     }
 
-    let owned_bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config)
+    let owned_bigtable = solana_storage_bigtable::new_long_term_ledger_storage(config)
         .await
         .map_err(|err| format!("failed to connect to owned bigtable: {err:?}"))?;
     let owned_bigtable_slots = owned_bigtable
@@ -483,9 +488,9 @@ async fn confirm(
     signature: &Signature,
     verbose: bool,
     output_format: OutputFormat,
-    config: solana_storage_bigtable::LedgerStorageConfig,
+    config: LongTermLedgerStorageConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config)
+    let bigtable = solana_storage_bigtable::new_long_term_ledger_storage(config)
         .await
         .map_err(|err| format!("Failed to connect to storage: {err:?}"))?;
 
@@ -535,9 +540,9 @@ pub async fn transaction_history(
     verbose: bool,
     show_transactions: bool,
     query_chunk_size: usize,
-    config: solana_storage_bigtable::LedgerStorageConfig,
+    config: LongTermLedgerStorageConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config).await?;
+    let bigtable = solana_storage_bigtable::new_long_term_ledger_storage(config).await?;
 
     let mut loaded_block: Option<(Slot, ConfirmedBlock)> = None;
     while limit > 0 {
@@ -914,6 +919,18 @@ impl BigTableSubCommand for App<'_, '_> {
                         .default_value(solana_storage_bigtable::DEFAULT_APP_PROFILE_ID)
                         .help("Bigtable application profile id to use in requests"),
                 )
+                .arg(
+                    Arg::with_name("storage_path")
+                        .global(true)
+                        .long("storage-path")
+                        .takes_value(true)
+                        .value_name("DIR")
+                        .help(
+                            "Use this local directory, laid out like the BigTable tables, instead \
+                             of a BigTable instance. compare-blocks still reads its reference \
+                             blocks from BigTable. Not supported by the copy subcommand",
+                        ),
+                )
                 .subcommand(
                     SubCommand::with_name("upload")
                         .about("Upload the ledger to BigTable")
@@ -1367,6 +1384,19 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
         solana_storage_bigtable::DEFAULT_APP_PROFILE_ID,
     );
 
+    let storage_path = matches
+        .value_of("storage_path")
+        .or_else(|| sub_matches.and_then(|sub_matches| sub_matches.value_of("storage_path")))
+        .map(PathBuf::from);
+    let long_term_storage_config =
+        |config: solana_storage_bigtable::LedgerStorageConfig| match &storage_path {
+            Some(path) => LongTermLedgerStorageConfig::FileSystem(FileLedgerStorageConfig {
+                path: path.clone(),
+                read_only: config.read_only,
+            }),
+            None => LongTermLedgerStorageConfig::BigTable(config),
+        };
+
     let future = match (subcommand, sub_matches) {
         ("upload", Some(arg_matches)) => {
             let starting_slot = value_t!(arg_matches, "starting_slot", Slot).ok();
@@ -1388,7 +1418,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 starting_slot,
                 ending_slot,
                 force_reupload,
-                config,
+                long_term_storage_config(config),
             ))
         }
         ("delete-slots", Some(arg_matches)) => {
@@ -1399,7 +1429,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 app_profile_id,
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
-            runtime.block_on(delete_slots(slots, config))
+            runtime.block_on(delete_slots(slots, long_term_storage_config(config)))
         }
         ("first-available-block", Some(_arg_matches)) => {
             let config = solana_storage_bigtable::LedgerStorageConfig {
@@ -1408,7 +1438,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 app_profile_id,
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
-            runtime.block_on(first_available_block(config))
+            runtime.block_on(first_available_block(long_term_storage_config(config)))
         }
         ("block", Some(arg_matches)) => {
             let slot = value_t_or_exit!(arg_matches, "slot", Slot);
@@ -1419,7 +1449,12 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 app_profile_id,
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
-            runtime.block_on(block(slot, output_format, show_entries, config))
+            runtime.block_on(block(
+                slot,
+                output_format,
+                show_entries,
+                long_term_storage_config(config),
+            ))
         }
         ("entries", Some(arg_matches)) => {
             let slot = value_t_or_exit!(arg_matches, "slot", Slot);
@@ -1429,7 +1464,11 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 app_profile_id,
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
-            runtime.block_on(entries(slot, output_format, config))
+            runtime.block_on(entries(
+                slot,
+                output_format,
+                long_term_storage_config(config),
+            ))
         }
         ("shreds", Some(arg_matches)) => {
             let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
@@ -1479,7 +1518,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 starting_slot,
                 ending_slot,
                 shred_config,
-                config,
+                long_term_storage_config(config),
             ))
         }
         ("blocks", Some(arg_matches)) => {
@@ -1492,7 +1531,11 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
 
-            runtime.block_on(blocks(starting_slot, limit, config))
+            runtime.block_on(blocks(
+                starting_slot,
+                limit,
+                long_term_storage_config(config),
+            ))
         }
         ("compare-blocks", Some(arg_matches)) => {
             let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
@@ -1522,7 +1565,12 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
 
-            runtime.block_on(compare_blocks(starting_slot, limit, config, ref_config))
+            runtime.block_on(compare_blocks(
+                starting_slot,
+                limit,
+                long_term_storage_config(config),
+                ref_config,
+            ))
         }
         ("confirm", Some(arg_matches)) => {
             let signature = arg_matches
@@ -1537,7 +1585,12 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
 
-            runtime.block_on(confirm(&signature, verbose, output_format, config))
+            runtime.block_on(confirm(
+                &signature,
+                verbose,
+                output_format,
+                long_term_storage_config(config),
+            ))
         }
         ("transaction-history", Some(arg_matches)) => {
             let address = pubkey_of(arg_matches, "address").unwrap();
@@ -1565,10 +1618,19 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 verbose,
                 show_transactions,
                 query_chunk_size,
-                config,
+                long_term_storage_config(config),
             ))
         }
-        ("copy", Some(arg_matches)) => runtime.block_on(copy(CopyArgs::process(arg_matches))),
+        ("copy", Some(arg_matches)) => {
+            if storage_path.is_some() {
+                eprintln!(
+                    "--storage-path is not supported by copy, which copies between BigTable \
+                     instances"
+                );
+                exit(1);
+            }
+            runtime.block_on(copy(CopyArgs::process(arg_matches)))
+        }
         _ => unreachable!(),
     };
 
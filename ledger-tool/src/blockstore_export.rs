@@ -0,0 +1,875 @@
+//! Export of rooted blocks from the blockstore into columnar Parquet files
+//!
+//! Each table is written to its own file in the output directory. Rows are buffered in Arrow
+//! builders and flushed to the file as a record batch every `EXPORT_BATCH_ROWS` rows.
+
+use {
+    crate::error::Result,
+    arrow::{
+        array::{
+            ArrayRef, BinaryBuilder, BooleanBuilder, Int64Builder, ListBuilder, StringBuilder,
+            UInt32Builder, UInt64Builder, UInt8Builder,
+        },
+        datatypes::{DataType, Field, Schema, SchemaRef},
+        record_batch::RecordBatch,
+    },
+    log::*,
+    parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties},
+    serde_derive::Serialize,
+    solana_clock::Slot,
+    solana_ledger::blockstore::{Blockstore, BlockstoreError},
+    solana_message::compiled_instruction::CompiledInstruction,
+    solana_transaction::versioned::TransactionVersion,
+    solana_transaction_status::{
+        TransactionTokenBalance, VersionedConfirmedBlock, VersionedTransactionWithStatusMeta,
+    },
+    std::{collections::BTreeMap, fs::File, path::Path, sync::Arc},
+};
+
+/// The number of buffered rows at which a table is flushed to its file
+const EXPORT_BATCH_ROWS: usize = 64 * 1024;
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportStats {
+    pub blocks: usize,
+    pub transactions: usize,
+    pub skipped_slots: usize,
+}
+
+/// Export the rooted blocks in `starting_slot..=ending_slot` into Parquet files in `output_dir`
+pub fn export_rooted_blocks(
+    blockstore: &Blockstore,
+    starting_slot: Slot,
+    ending_slot: Slot,
+    output_dir: &Path,
+) -> Result<ExportStats> {
+    std::fs::create_dir_all(output_dir)?;
+    let mut blocks = TableWriter::<BlocksTable>::new(output_dir)?;
+    let mut transactions = TableWriter::<TransactionsTable>::new(output_dir)?;
+    let mut instructions = TableWriter::<InstructionsTable>::new(output_dir)?;
+    let mut balances = TableWriter::<BalancesTable>::new(output_dir)?;
+    let mut token_balances = TableWriter::<TokenBalancesTable>::new(output_dir)?;
+    let mut rewards = TableWriter::<RewardsTable>::new(output_dir)?;
+
+    let mut stats = ExportStats::default();
+    for slot in blockstore
+        .rooted_slot_iterator(starting_slot)?
+        .take_while(|slot| *slot <= ending_slot)
+    {
+        let block = match blockstore.get_rooted_block(slot, false) {
+            Ok(block) => block,
+            Err(err @ (BlockstoreError::SlotUnavailable | BlockstoreError::SlotNotRooted)) => {
+                warn!("Skipping slot {slot}: {err}");
+                stats.skipped_slots += 1;
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        blocks.table.push(slot, &block);
+        rewards.table.push(slot, &block);
+        for (transaction_index, transaction) in block.transactions.iter().enumerate() {
+            let transaction_index = transaction_index as u32;
+            transactions
+                .table
+                .push(slot, transaction_index, transaction);
+            instructions
+                .table
+                .push(slot, transaction_index, transaction);
+            balances.table.push(slot, transaction_index, transaction);
+            token_balances
+                .table
+                .push(slot, transaction_index, transaction);
+        }
+        stats.blocks += 1;
+        stats.transactions += block.transactions.len();
+
+        blocks.flush_if_full()?;
+        transactions.flush_if_full()?;
+        instructions.flush_if_full()?;
+        balances.flush_if_full()?;
+        token_balances.flush_if_full()?;
+        rewards.flush_if_full()?;
+    }
+
+    blocks.close()?;
+    transactions.close()?;
+    instructions.close()?;
+    balances.close()?;
+    token_balances.close()?;
+    rewards.close()?;
+    Ok(stats)
+}
+
+trait ExportTable: Default {
+    /// File name of the table, without extension
+    const NAME: &'static str;
+
+    fn schema() -> Schema;
+
+    fn len(&self) -> usize;
+
+    /// Take the buffered rows as columns, in the order of `schema()`
+    fn finish(&mut self) -> Vec<ArrayRef>;
+}
+
+struct TableWriter<T: ExportTable> {
+    table: T,
+    schema: SchemaRef,
+    writer: ArrowWriter<File>,
+}
+
+impl<T: ExportTable> TableWriter<T> {
+    fn new(output_dir: &Path) -> Result<Self> {
+        let schema = Arc::new(T::schema());
+        let file = File::create(output_dir.join(format!("{}.parquet", T::NAME)))?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer = ArrowWriter::try_new(file, schema.clone(), Some(properties))?;
+        Ok(Self {
+            table: T::default(),
+            schema,
+            writer,
+        })
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if self.table.len() == 0 {
+            return Ok(());
+        }
+        let batch = RecordBatch::try_new(self.schema.clone(), self.table.finish())?;
+        self.writer.write(&batch)?;
+        Ok(())
+    }
+
+    fn flush_if_full(&mut self) -> Result<()> {
+        if self.table.len() >= EXPORT_BATCH_ROWS {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn close(mut self) -> Result<()> {
+        self.flush()?;
+        self.writer.close()?;
+        Ok(())
+    }
+}
+
+fn first_signature(transaction: &VersionedTransactionWithStatusMeta) -> String {
+    transaction
+        .transaction
+        .signatures
+        .first()
+        .map(ToString::to_string)
+        .unwrap_or_default()
+}
+
+#[derive(Default)]
+struct BlocksTable {
+    slot: UInt64Builder,
+    parent_slot: UInt64Builder,
+    blockhash: StringBuilder,
+    previous_blockhash: StringBuilder,
+    block_time: Int64Builder,
+    block_height: UInt64Builder,
+    transaction_count: UInt64Builder,
+}
+
+impl BlocksTable {
+    fn push(&mut self, slot: Slot, block: &VersionedConfirmedBlock) {
+        self.slot.append_value(slot);
+        self.parent_slot.append_value(block.parent_slot);
+        self.blockhash.append_value(&block.blockhash);
+        self.previous_blockhash
+            .append_value(&block.previous_blockhash);
+        self.block_time.append_option(block.block_time);
+        self.block_height.append_option(block.block_height);
+        self.transaction_count
+            .append_value(block.transactions.len() as u64);
+    }
+}
+
+impl ExportTable for BlocksTable {
+    const NAME: &'static str = "blocks";
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("parent_slot", DataType::UInt64, false),
+            Field::new("blockhash", DataType::Utf8, false),
+            Field::new("previous_blockhash", DataType::Utf8, false),
+            Field::new("block_time", DataType::Int64, true),
+            Field::new("block_height", DataType::UInt64, true),
+            Field::new("transaction_count", DataType::UInt64, false),
+        ])
+    }
+
+    fn len(&self) -> usize {
+        self.slot.len()
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        vec![
+            Arc::new(self.slot.finish()),
+            Arc::new(self.parent_slot.finish()),
+            Arc::new(self.blockhash.finish()),
+            Arc::new(self.previous_blockhash.finish()),
+            Arc::new(self.block_time.finish()),
+            Arc::new(self.block_height.finish()),
+            Arc::new(self.transaction_count.finish()),
+        ]
+    }
+}
+
+#[derive(Default)]
+struct TransactionsTable {
+    slot: UInt64Builder,
+    transaction_index: UInt32Builder,
+    signature: StringBuilder,
+    version: StringBuilder,
+    success: BooleanBuilder,
+    error: StringBuilder,
+    fee: UInt64Builder,
+    compute_units_consumed: UInt64Builder,
+    num_signatures: UInt32Builder,
+    num_account_keys: UInt32Builder,
+    recent_blockhash: StringBuilder,
+    log_messages: ListBuilder<StringBuilder>,
+}
+
+impl TransactionsTable {
+    fn push(
+        &mut self,
+        slot: Slot,
+        transaction_index: u32,
+        transaction: &VersionedTransactionWithStatusMeta,
+    ) {
+        let meta = &transaction.meta;
+        self.slot.append_value(slot);
+        self.transaction_index.append_value(transaction_index);
+        self.signature.append_value(first_signature(transaction));
+        self.version
+            .append_value(match transaction.transaction.version() {
+                TransactionVersion::Legacy(_) => "legacy".to_string(),
+                TransactionVersion::Number(version) => version.to_string(),
+            });
+        self.success.append_value(meta.status.is_ok());
+        self.error
+            .append_option(meta.status.as_ref().err().map(ToString::to_string));
+        self.fee.append_value(meta.fee);
+        self.compute_units_consumed
+            .append_option(meta.compute_units_consumed);
+        self.num_signatures
+            .append_value(transaction.transaction.signatures.len() as u32);
+        self.num_account_keys
+            .append_value(transaction.account_keys().len() as u32);
+        self.recent_blockhash.append_value(
+            transaction
+                .transaction
+                .message
+                .recent_blockhash()
+                .to_string(),
+        );
+        match &meta.log_messages {
+            Some(log_messages) => {
+                for log_message in log_messages {
+                    self.log_messages.values().append_value(log_message);
+                }
+                self.log_messages.append(true);
+            }
+            None => self.log_messages.append(false),
+        }
+    }
+}
+
+impl ExportTable for TransactionsTable {
+    const NAME: &'static str = "transactions";
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("transaction_index", DataType::UInt32, false),
+            Field::new("signature", DataType::Utf8, false),
+            Field::new("version", DataType::Utf8, false),
+            Field::new("success", DataType::Boolean, false),
+            Field::new("error", DataType::Utf8, true),
+            Field::new("fee", DataType::UInt64, false),
+            Field::new("compute_units_consumed", DataType::UInt64, true),
+            Field::new("num_signatures", DataType::UInt32, false),
+            Field::new("num_account_keys", DataType::UInt32, false),
+            Field::new("recent_blockhash", DataType::Utf8, false),
+            Field::new_list(
+                "log_messages",
+                Field::new_list_field(DataType::Utf8, true),
+                true,
+            ),
+        ])
+    }
+
+    fn len(&self) -> usize {
+        self.slot.len()
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        vec![
+            Arc::new(self.slot.finish()),
+            Arc::new(self.transaction_index.finish()),
+            Arc::new(self.signature.finish()),
+            Arc::new(self.version.finish()),
+            Arc::new(self.success.finish()),
+            Arc::new(self.error.finish()),
+            Arc::new(self.fee.finish()),
+            Arc::new(self.compute_units_consumed.finish()),
+            Arc::new(self.num_signatures.finish()),
+            Arc::new(self.num_account_keys.finish()),
+            Arc::new(self.recent_blockhash.finish()),
+            Arc::new(self.log_messages.finish()),
+        ]
+    }
+}
+
+#[derive(Default)]
+struct InstructionsTable {
+    slot: UInt64Builder,
+    transaction_index: UInt32Builder,
+    signature: StringBuilder,
+    instruction_index: UInt32Builder,
+    inner_instruction_index: UInt32Builder,
+    stack_height: UInt32Builder,
+    program_id: StringBuilder,
+    accounts: ListBuilder<StringBuilder>,
+    data: BinaryBuilder,
+}
+
+impl InstructionsTable {
+    fn push(
+        &mut self,
+        slot: Slot,
+        transaction_index: u32,
+        transaction: &VersionedTransactionWithStatusMeta,
+    ) {
+        let signature = first_signature(transaction);
+        let account_keys = transaction.account_keys();
+        let mut push_instruction =
+            |instruction_index: u32,
+             inner_instruction_index: Option<u32>,
+             stack_height: Option<u32>,
+             instruction: &CompiledInstruction| {
+                self.slot.append_value(slot);
+                self.transaction_index.append_value(transaction_index);
+                self.signature.append_value(&signature);
+                self.instruction_index.append_value(instruction_index);
+                self.inner_instruction_index
+                    .append_option(inner_instruction_index);
+                self.stack_height.append_option(stack_height);
+                self.program_id.append_option(
+                    account_keys
+                        .get(instruction.program_id_index as usize)
+                        .map(ToString::to_string),
+                );
+                for account_index in &instruction.accounts {
+                    self.accounts.values().append_option(
+                        account_keys
+                            .get(*account_index as usize)
+                            .map(ToString::to_string),
+                    );
+                }
+                self.accounts.append(true);
+                self.data.append_value(&instruction.data);
+            };
+
+        for (instruction_index, instruction) in transaction
+            .transaction
+            .message
+            .instructions()
+            .iter()
+            .enumerate()
+        {
+            push_instruction(instruction_index as u32, None, Some(1), instruction);
+        }
+        for inner_instructions in transaction.meta.inner_instructions.iter().flatten() {
+            for (inner_instruction_index, inner_instruction) in
+                inner_instructions.instructions.iter().enumerate()
+            {
+                push_instruction(
+                    u32::from(inner_instructions.index),
+                    Some(inner_instruction_index as u32),
+                    inner_instruction.stack_height,
+                    &inner_instruction.instruction,
+                );
+            }
+        }
+    }
+}
+
+impl ExportTable for InstructionsTable {
+    const NAME: &'static str = "instructions";
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("transaction_index", DataType::UInt32, false),
+            Field::new("signature", DataType::Utf8, false),
+            Field::new("instruction_index", DataType::UInt32, false),
+            Field::new("inner_instruction_index", DataType::UInt32, true),
+            Field::new("stack_height", DataType::UInt32, true),
+            Field::new("program_id", DataType::Utf8, true),
+            Field::new_list(
+                "accounts",
+                Field::new_list_field(DataType::Utf8, true),
+                false,
+            ),
+            Field::new("data", DataType::Binary, false),
+        ])
+    }
+
+    fn len(&self) -> usize {
+        self.slot.len()
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        vec![
+            Arc::new(self.slot.finish()),
+            Arc::new(self.transaction_index.finish()),
+            Arc::new(self.signature.finish()),
+            Arc::new(self.instruction_index.finish()),
+            Arc::new(self.inner_instruction_index.finish()),
+            Arc::new(self.stack_height.finish()),
+            Arc::new(self.program_id.finish()),
+            Arc::new(self.accounts.finish()),
+            Arc::new(self.data.finish()),
+        ]
+    }
+}
+
+#[derive(Default)]
+struct BalancesTable {
+    slot: UInt64Builder,
+    transaction_index: UInt32Builder,
+    signature: StringBuilder,
+    account_index: UInt32Builder,
+    account: StringBuilder,
+    pre_balance: UInt64Builder,
+    post_balance: UInt64Builder,
+}
+
+impl BalancesTable {
+    fn push(
+        &mut self,
+        slot: Slot,
+        transaction_index: u32,
+        transaction: &VersionedTransactionWithStatusMeta,
+    ) {
+        let signature = first_signature(transaction);
+        let meta = &transaction.meta;
+        for (account_index, account) in transaction.account_keys().iter().enumerate() {
+            self.slot.append_value(slot);
+            self.transaction_index.append_value(transaction_index);
+            self.signature.append_value(&signature);
+            self.account_index.append_value(account_index as u32);
+            self.account.append_value(account.to_string());
+            self.pre_balance
+                .append_option(meta.pre_balances.get(account_index).copied());
+            self.post_balance
+                .append_option(meta.post_balances.get(account_index).copied());
+        }
+    }
+}
+
+impl ExportTable for BalancesTable {
+    const NAME: &'static str = "balances";
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("transaction_index", DataType::UInt32, false),
+            Field::new("signature", DataType::Utf8, false),
+            Field::new("account_index", DataType::UInt32, false),
+            Field::new("account", DataType::Utf8, false),
+            Field::new("pre_balance", DataType::UInt64, true),
+            Field::new("post_balance", DataType::UInt64, true),
+        ])
+    }
+
+    fn len(&self) -> usize {
+        self.slot.len()
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        vec![
+            Arc::new(self.slot.finish()),
+            Arc::new(self.transaction_index.finish()),
+            Arc::new(self.signature.finish()),
+            Arc::new(self.account_index.finish()),
+            Arc::new(self.account.finish()),
+            Arc::new(self.pre_balance.finish()),
+            Arc::new(self.post_balance.finish()),
+        ]
+    }
+}
+
+#[derive(Default)]
+struct TokenBalancesTable {
+    slot: UInt64Builder,
+    transaction_index: UInt32Builder,
+    signature: StringBuilder,
+    account_index: UInt32Builder,
+    account: StringBuilder,
+    mint: StringBuilder,
+    owner: StringBuilder,
+    program_id: StringBuilder,
+    decimals: UInt8Builder,
+    pre_amount: StringBuilder,
+    post_amount: StringBuilder,
+}
+
+impl TokenBalancesTable {
+    fn push(
+        &mut self,
+        slot: Slot,
+        transaction_index: u32,
+        transaction: &VersionedTransactionWithStatusMeta,
+    ) {
+        let signature = first_signature(transaction);
+        let account_keys = transaction.account_keys();
+
+        // Pair up the pre and post balances of each token account
+        let mut token_balances: BTreeMap<
+            u8,
+            (
+                Option<&TransactionTokenBalance>,
+                Option<&TransactionTokenBalance>,
+            ),
+        > = BTreeMap::new();
+        for balance in transaction.meta.pre_token_balances.iter().flatten() {
+            token_balances.entry(balance.account_index).or_default().0 = Some(balance);
+        }
+        for balance in transaction.meta.post_token_balances.iter().flatten() {
+            token_balances.entry(balance.account_index).or_default().1 = Some(balance);
+        }
+
+        for (account_index, (pre, post)) in token_balances {
+            let Some(balance) = post.or(pre) else {
+                continue;
+            };
+            self.slot.append_value(slot);
+            self.transaction_index.append_value(transaction_index);
+            self.signature.append_value(&signature);
+            self.account_index.append_value(u32::from(account_index));
+            self.account.append_option(
+                account_keys
+                    .get(account_index as usize)
+                    .map(ToString::to_string),
+            );
+            self.mint.append_value(&balance.mint);
+            self.owner.append_value(&balance.owner);
+            self.program_id.append_value(&balance.program_id);
+            self.decimals.append_value(balance.ui_token_amount.decimals);
+            self.pre_amount
+                .append_option(pre.map(|pre| &pre.ui_token_amount.amount));
+            self.post_amount
+                .append_option(post.map(|post| &post.ui_token_amount.amount));
+        }
+    }
+}
+
+impl ExportTable for TokenBalancesTable {
+    const NAME: &'static str = "token_balances";
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("transaction_index", DataType::UInt32, false),
+            Field::new("signature", DataType::Utf8, false),
+            Field::new("account_index", DataType::UInt32, false),
+            Field::new("account", DataType::Utf8, true),
+            Field::new("mint", DataType::Utf8, false),
+            Field::new("owner", DataType::Utf8, false),
+            Field::new("program_id", DataType::Utf8, false),
+            Field::new("decimals", DataType::UInt8, false),
+            // Raw amounts, as strings since they may not fit in a signed 64-bit integer
+            Field::new("pre_amount", DataType::Utf8, true),
+            Field::new("post_amount", DataType::Utf8, true),
+        ])
+    }
+
+    fn len(&self) -> usize {
+        self.slot.len()
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        vec![
+            Arc::new(self.slot.finish()),
+            Arc::new(self.transaction_index.finish()),
+            Arc::new(self.signature.finish()),
+            Arc::new(self.account_index.finish()),
+            Arc::new(self.account.finish()),
+            Arc::new(self.mint.finish()),
+            Arc::new(self.owner.finish()),
+            Arc::new(self.program_id.finish()),
+            Arc::new(self.decimals.finish()),
+            Arc::new(self.pre_amount.finish()),
+            Arc::new(self.post_amount.finish()),
+        ]
+    }
+}
+
+#[derive(Default)]
+struct RewardsTable {
+    slot: UInt64Builder,
+    pubkey: StringBuilder,
+    lamports: Int64Builder,
+    post_balance: UInt64Builder,
+    reward_type: StringBuilder,
+    commission: UInt8Builder,
+}
+
+impl RewardsTable {
+    fn push(&mut self, slot: Slot, block: &VersionedConfirmedBlock) {
+        for reward in &block.rewards {
+            self.slot.append_value(slot);
+            self.pubkey.append_value(&reward.pubkey);
+            self.lamports.append_value(reward.lamports);
+            self.post_balance.append_value(reward.post_balance);
+            self.reward_type
+                .append_option(reward.reward_type.as_ref().map(ToString::to_string));
+            self.commission.append_option(reward.commission);
+        }
+    }
+}
+
+impl ExportTable for RewardsTable {
+    const NAME: &'static str = "rewards";
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("pubkey", DataType::Utf8, false),
+            Field::new("lamports", DataType::Int64, false),
+            Field::new("post_balance", DataType::UInt64, false),
+            Field::new("reward_type", DataType::Utf8, true),
+            Field::new("commission", DataType::UInt8, true),
+        ])
+    }
+
+    fn len(&self) -> usize {
+        self.slot.len()
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        vec![
+            Arc::new(self.slot.finish()),
+            Arc::new(self.pubkey.finish()),
+            Arc::new(self.lamports.finish()),
+            Arc::new(self.post_balance.finish()),
+            Arc::new(self.reward_type.finish()),
+            Arc::new(self.commission.finish()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        arrow::array::{AsArray, Int64Array},
+        parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder,
+        solana_account_decoder::parse_token::UiTokenAmount,
+        solana_entry::entry::next_entry,
+        solana_hash::Hash,
+        solana_keypair::Keypair,
+        solana_ledger::blockstore::{entries_to_test_shreds, make_many_slot_entries},
+        solana_pubkey::Pubkey,
+        solana_signer::Signer,
+        solana_system_interface::instruction as system_instruction,
+        solana_transaction::Transaction,
+        solana_transaction_status::{
+            InnerInstruction, InnerInstructions, Reward, RewardType, RewardsAndNumPartitions,
+            TransactionStatusMeta,
+        },
+    };
+
+    fn num_rows(output_dir: &Path, table: &str) -> i64 {
+        let file = File::open(output_dir.join(format!("{table}.parquet"))).unwrap();
+        ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .metadata()
+            .file_metadata()
+            .num_rows()
+    }
+
+    fn read_table(output_dir: &Path, table: &str) -> RecordBatch {
+        let file = File::open(output_dir.join(format!("{table}.parquet"))).unwrap();
+        let mut reader = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap();
+        reader.next().unwrap().unwrap()
+    }
+
+    fn token_balance(account_index: u8, mint: &Pubkey, amount: &str) -> TransactionTokenBalance {
+        TransactionTokenBalance {
+            account_index,
+            mint: mint.to_string(),
+            ui_token_amount: UiTokenAmount {
+                ui_amount: None,
+                decimals: 2,
+                amount: amount.to_string(),
+                ui_amount_string: String::default(),
+            },
+            owner: Pubkey::new_unique().to_string(),
+            program_id: Pubkey::new_unique().to_string(),
+        }
+    }
+
+    #[test]
+    fn test_export_rooted_blocks() {
+        let ledger_path = tempfile::tempdir().unwrap();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let (shreds, _) = make_many_slot_entries(0, 5, 5);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        blockstore.set_roots([1, 2, 3].iter()).unwrap();
+
+        let output_dir = tempfile::tempdir().unwrap();
+        let stats = export_rooted_blocks(&blockstore, 0, 2, output_dir.path()).unwrap();
+        assert_eq!(stats.blocks, 2);
+        assert_eq!(stats.transactions, 0);
+        assert_eq!(stats.skipped_slots, 0);
+
+        assert_eq!(num_rows(output_dir.path(), BlocksTable::NAME), 2);
+        for table in [
+            TransactionsTable::NAME,
+            InstructionsTable::NAME,
+            BalancesTable::NAME,
+            TokenBalancesTable::NAME,
+            RewardsTable::NAME,
+        ] {
+            assert_eq!(num_rows(output_dir.path(), table), 0);
+        }
+    }
+
+    #[test]
+    fn test_export_rooted_blocks_with_transactions() {
+        let ledger_path = tempfile::tempdir().unwrap();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let slot = 1;
+
+        let payer = Keypair::new();
+        let recipients = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                system_instruction::transfer(&payer.pubkey(), &recipients[0], 1),
+                system_instruction::transfer(&payer.pubkey(), &recipients[1], 2),
+            ],
+            Some(&payer.pubkey()),
+            &[&payer],
+            Hash::default(),
+        );
+        let signature = transaction.signatures[0];
+        let account_keys = transaction.message.account_keys.clone();
+        let entries = vec![next_entry(&Hash::default(), 1, vec![transaction])];
+        let shreds = entries_to_test_shreds(&entries, slot, slot - 1, true, 0);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        blockstore.set_roots([slot].iter()).unwrap();
+
+        let mint = Pubkey::new_unique();
+        blockstore
+            .write_transaction_status(
+                slot,
+                signature,
+                account_keys.iter().map(|key| (key, true)),
+                TransactionStatusMeta {
+                    fee: 5000,
+                    pre_balances: vec![100, 0, 0, 1],
+                    post_balances: vec![92, 1, 2, 1],
+                    inner_instructions: Some(vec![InnerInstructions {
+                        index: 1,
+                        instructions: vec![InnerInstruction {
+                            instruction: CompiledInstruction::new_from_raw_parts(
+                                3,
+                                vec![],
+                                vec![0],
+                            ),
+                            stack_height: Some(2),
+                        }],
+                    }]),
+                    pre_token_balances: Some(vec![token_balance(1, &mint, "10")]),
+                    post_token_balances: Some(vec![
+                        token_balance(1, &mint, "7"),
+                        token_balance(2, &mint, "3"),
+                    ]),
+                    ..TransactionStatusMeta::default()
+                },
+                0,
+            )
+            .unwrap();
+        let rewards = [(Pubkey::new_unique(), 10), (Pubkey::new_unique(), -4)]
+            .into_iter()
+            .map(|(pubkey, lamports)| Reward {
+                pubkey: pubkey.to_string(),
+                lamports,
+                post_balance: 100,
+                reward_type: Some(RewardType::Fee),
+                commission: None,
+            })
+            .collect();
+        blockstore
+            .write_rewards(
+                slot,
+                RewardsAndNumPartitions {
+                    rewards,
+                    num_partitions: None,
+                },
+            )
+            .unwrap();
+
+        let output_dir = tempfile::tempdir().unwrap();
+        let stats = export_rooted_blocks(&blockstore, 0, slot, output_dir.path()).unwrap();
+        assert_eq!(stats.blocks, 1);
+        assert_eq!(stats.transactions, 1);
+        assert_eq!(stats.skipped_slots, 0);
+
+        assert_eq!(num_rows(output_dir.path(), BlocksTable::NAME), 1);
+        assert_eq!(num_rows(output_dir.path(), TransactionsTable::NAME), 1);
+        // Both top level instructions and the inner instruction
+        assert_eq!(num_rows(output_dir.path(), InstructionsTable::NAME), 3);
+        assert_eq!(num_rows(output_dir.path(), BalancesTable::NAME), 4);
+
+        // The pre and post balances of a token account share a row
+        let token_balances = read_table(output_dir.path(), TokenBalancesTable::NAME);
+        assert_eq!(token_balances.num_rows(), 2);
+        let column = |name: &str| {
+            token_balances
+                .column_by_name(name)
+                .unwrap()
+                .as_string::<i32>()
+                .iter()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            column("account"),
+            [
+                Some(account_keys[1].to_string().as_str()),
+                Some(account_keys[2].to_string().as_str()),
+            ]
+        );
+        assert_eq!(column("pre_amount"), [Some("10"), None]);
+        assert_eq!(column("post_amount"), [Some("7"), Some("3")]);
+
+        let rewards = read_table(output_dir.path(), RewardsTable::NAME);
+        assert_eq!(
+            rewards
+                .column_by_name("lamports")
+                .unwrap()
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .unwrap()
+                .values()
+                .to_vec(),
+            vec![10, -4]
+        );
+    }
+}
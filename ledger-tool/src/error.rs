@@ -1,4 +1,7 @@
-use {solana_ledger::blockstore::BlockstoreError, thiserror::Error};
+use {
+    solana_core::banking_trace::TraceError, solana_ledger::blockstore::BlockstoreError,
+    thiserror::Error,
+};
 
 pub type Result<T> = std::result::Result<T, LedgerToolError>;
 
@@ -16,6 +19,15 @@ pub enum LedgerToolError {
     #[error("{0}")]
     Io(#[from] std::io::Error),
 
+    #[error("{0}")]
+    Arrow(#[from] arrow::error::ArrowError),
+
+    #[error("{0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+
+    #[error("{0}")]
+    BankingTrace(#[from] TraceError),
+
     #[error("{0}")]
     Generic(String),
 
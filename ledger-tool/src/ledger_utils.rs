@@ -4,6 +4,7 @@ use {
     crossbeam_channel::unbounded,
     log::*,
     solana_accounts_db::{
+        accounts_update_notifier_interface::AccountsUpdateNotifier,
         hardened_unpack::open_genesis_config,
         utils::{create_all_accounts_run_and_snapshot_dirs, move_and_async_delete_path_contents},
     },
@@ -37,6 +38,7 @@ use {
         snapshot_hash::StartingSnapshotHashes,
         snapshot_utils::{self, clean_orphaned_account_snapshot_dirs},
     },
+    solana_svm::instruction_trace_notifier::InstructionTraceNotifierArc,
     solana_transaction::versioned::VersionedTransaction,
     solana_unified_scheduler_pool::DefaultSchedulerPool,
     std::{
@@ -108,6 +110,8 @@ pub fn load_and_process_ledger_or_exit(
     blockstore: Arc<Blockstore>,
     process_options: ProcessOptions,
     transaction_status_sender: Option<TransactionStatusSender>,
+    accounts_update_notifier: Option<AccountsUpdateNotifier>,
+    instruction_trace_notifier: Option<InstructionTraceNotifierArc>,
 ) -> LoadAndProcessLedgerOutput {
     load_and_process_ledger(
         arg_matches,
@@ -115,6 +119,8 @@ pub fn load_and_process_ledger_or_exit(
         blockstore,
         process_options,
         transaction_status_sender,
+        accounts_update_notifier,
+        instruction_trace_notifier,
     )
     .unwrap_or_else(|err| {
         eprintln!("Exiting. Failed to load and process ledger: {err}");
@@ -122,12 +128,18 @@ pub fn load_and_process_ledger_or_exit(
     })
 }
 
+/// Load the bank forks and replay the blockstore
+///
+/// `accounts_update_notifier` receives every account written during replay; it is ignored if a
+/// geyser plugin is configured, since the plugin provides its own notifier.
 pub fn load_and_process_ledger(
     arg_matches: &ArgMatches,
     genesis_config: &GenesisConfig,
     blockstore: Arc<Blockstore>,
     process_options: ProcessOptions,
     transaction_status_sender: Option<TransactionStatusSender>,
+    accounts_update_notifier: Option<AccountsUpdateNotifier>,
+    instruction_trace_notifier: Option<InstructionTraceNotifierArc>,
 ) -> Result<LoadAndProcessLedgerOutput, LoadAndProcessLedgerError> {
     let bank_snapshots_dir = if blockstore.is_primary_access() {
         blockstore.ledger_path().join("snapshot")
@@ -279,7 +291,7 @@ pub fn load_and_process_ledger(
             geyser_service.get_transaction_notifier(),
         )
     } else {
-        (None, None)
+        (accounts_update_notifier, None)
     };
 
     let exit = Arc::new(AtomicBool::new(false));
@@ -340,6 +352,15 @@ pub fn load_and_process_ledger(
             exit.clone(),
         )
         .map_err(LoadAndProcessLedgerError::LoadBankForks)?;
+    if instruction_trace_notifier.is_some() {
+        // Banks created from now on inherit the notifier from their parent
+        bank_forks
+            .read()
+            .unwrap()
+            .root_bank()
+            .get_transaction_processor()
+            .set_instruction_trace_notifier(instruction_trace_notifier);
+    }
     let block_verification_method = value_t_or_exit!(
         arg_matches,
         "block_verification_method",
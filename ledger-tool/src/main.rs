@@ -1,7 +1,9 @@
 #![allow(clippy::arithmetic_side_effects)]
 use {
     crate::{
+        account_diff::{AccountDiffFormat, AccountDiffRecorder, AccountDiffWriter},
         args::*,
+        banking_trace::*,
         bigtable::*,
         blockstore::*,
         ledger_path::*,
@@ -22,7 +24,10 @@ use {
     log::*,
     serde_derive::Serialize,
     solana_account::{state_traits::StateMut, AccountSharedData, ReadableAccount, WritableAccount},
-    solana_accounts_db::accounts_index::{ScanConfig, ScanOrder},
+    solana_accounts_db::{
+        accounts_index::{ScanConfig, ScanOrder},
+        accounts_update_notifier_interface::AccountsUpdateNotifier,
+    },
     solana_clap_utils::{
         input_parsers::{cluster_type_of, pubkey_of, pubkeys_of},
         input_validators::{
@@ -34,6 +39,9 @@ use {
     solana_clock::{Epoch, Slot},
     solana_core::{
         banking_simulation::{BankingSimulator, BankingTraceEvents},
+        banking_trace::{BankingTracer, VERSION_FILE_NAME},
+        banking_trace_replay::BankingTraceReplayer,
+        compute_profiler::ComputeProfiler,
         system_monitor_service::{SystemMonitorService, SystemMonitorStatsReportConfig},
         validator::{BlockProductionMethod, BlockVerificationMethod, TransactionStructure},
     },
@@ -73,6 +81,7 @@ use {
     solana_shred_version::compute_shred_version,
     solana_stake_interface::{self as stake, state::StakeStateV2},
     solana_stake_program::stake_state,
+    solana_svm::instruction_trace_notifier::InstructionTraceNotifierArc,
     solana_system_interface::program as system_program,
     solana_transaction::sanitized::MessageHash,
     solana_transaction_status::parse_ui_instruction,
@@ -99,9 +108,12 @@ use {
     },
 };
 
+mod account_diff;
 mod args;
+mod banking_trace;
 mod bigtable;
 mod blockstore;
+mod blockstore_export;
 mod error;
 mod ledger_path;
 mod ledger_utils;
@@ -534,7 +546,12 @@ fn assert_capitalization(bank: &Bank) {
 }
 
 fn load_banking_trace_events_or_exit(ledger_path: &Path) -> BankingTraceEvents {
-    let file_paths = read_banking_trace_event_file_paths_or_exit(banking_trace_path(ledger_path));
+    let banking_trace_path = banking_trace_path(ledger_path);
+    if let Err(error) = BankingTracer::read_format_version(&banking_trace_path) {
+        eprintln!("Failed to load banking trace events: {error}");
+        exit(1);
+    }
+    let file_paths = read_banking_trace_event_file_paths_or_exit(banking_trace_path);
 
     info!("Using: banking trace event files: {file_paths:?}");
     match BankingTraceEvents::load(&file_paths) {
@@ -561,6 +578,7 @@ fn read_banking_trace_event_file_paths_or_exit(banking_trace_path: PathBuf) -> V
         .flat_map(|entry| entry.ok().map(|entry| entry.file_name()))
         .collect::<HashSet<OsString>>();
 
+    entry_names.remove(OsStr::new(VERSION_FILE_NAME));
     let mut event_file_paths = vec![];
 
     if entry_names.is_empty() {
@@ -1038,6 +1056,7 @@ fn main() {
                 .takes_value(false)
                 .help("Show additional information where supported"),
         )
+        .banking_trace_subcommand()
         .bigtable_subcommand()
         .blockstore_subcommand()
         // All of the blockstore commands are added under the blockstore command.
@@ -1505,9 +1524,15 @@ fn main() {
                         .long("block-production-method")
                         .value_name("METHOD")
                         .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
                         .possible_values(BlockProductionMethod::cli_names())
                         .default_value(BlockProductionMethod::default().into())
-                        .help(BlockProductionMethod::cli_message()),
+                        .help(
+                            "Switch transaction scheduling method for producing ledger entries. \
+                             With --replay-slots, this may be specified multiple times to \
+                             replay the same slots with each method in turn",
+                        ),
                 )
                 .arg(
                     Arg::with_name("transaction_struct")
@@ -1532,6 +1557,19 @@ fn main() {
                         .long("no-block-cost-limits")
                         .takes_value(false)
                         .help("Disable block cost limits effectively by setting them to the max"),
+                )
+                .arg(
+                    Arg::with_name("replay_slots")
+                        .long("replay-slots")
+                        .value_name("NUM_SLOTS")
+                        .validator(is_parsable::<usize>)
+                        .takes_value(true)
+                        .help(
+                            "Instead of simulating block production in real time, replay the \
+                             traced non-vote transactions of NUM_SLOTS slots directly against \
+                             banks with the chosen scheduler, as fast as possible, and print \
+                             per-slot scheduler metrics",
+                        ),
                 ),
         )
         .subcommand(
@@ -1588,6 +1626,100 @@ fn main() {
                         .help("Limit output to accounts owned by the provided program pubkey"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("account-diff")
+                .about("Replay the ledger and output every account written in each replayed slot")
+                .arg(&load_genesis_config_arg)
+                .args(&accounts_db_config_args)
+                .args(&snapshot_config_args)
+                .arg(&halt_at_slot_arg)
+                .arg(&hard_forks_arg)
+                .arg(&log_messages_bytes_limit_arg)
+                .arg(
+                    Arg::with_name("starting_slot")
+                        .long("starting-slot")
+                        .value_name("SLOT")
+                        .validator(is_slot)
+                        .takes_value(true)
+                        .help(
+                            "Only output accounts written in this slot and later. Slots before \
+                             the snapshot that the ledger is loaded from are never replayed",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .takes_value(true)
+                        .possible_values(&["json", "csv"])
+                        .default_value("json")
+                        .help("Output one JSON object per line, or CSV with a header row"),
+                )
+                .arg(
+                    Arg::with_name("include_data")
+                        .long("include-data")
+                        .takes_value(false)
+                        .help("Output the base64 encoded account data, not only its hash"),
+                )
+                .arg(
+                    Arg::with_name("output_file")
+                        .long("output-file")
+                        .value_name("FILENAME")
+                        .takes_value(true)
+                        .help("Write the accounts to this file instead of stdout"),
+                )
+                .arg(
+                    Arg::with_name("account")
+                        .long("account")
+                        .takes_value(true)
+                        .value_name("PUBKEY")
+                        .validator(is_pubkey)
+                        .multiple(true)
+                        .help(
+                            "Limit output to accounts corresponding to the specified pubkey(s), \
+                             may be specified multiple times",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("program_accounts")
+                        .long("program-accounts")
+                        .takes_value(true)
+                        .value_name("PUBKEY")
+                        .validator(is_pubkey)
+                        .conflicts_with("account")
+                        .help("Limit output to accounts owned by the provided program pubkey"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("compute-profile")
+                .about(
+                    "Replay the ledger and output the compute units and time spent per program \
+                     and per instruction discriminator",
+                )
+                .arg(&load_genesis_config_arg)
+                .args(&accounts_db_config_args)
+                .args(&snapshot_config_args)
+                .arg(&halt_at_slot_arg)
+                .arg(&hard_forks_arg)
+                .arg(
+                    Arg::with_name("starting_slot")
+                        .long("starting-slot")
+                        .value_name("SLOT")
+                        .validator(is_slot)
+                        .takes_value(true)
+                        .help(
+                            "Only profile transactions of this slot and later. Slots before the \
+                             snapshot that the ledger is loaded from are never replayed",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("output_file")
+                        .long("output-file")
+                        .value_name("FILENAME")
+                        .takes_value(true)
+                        .help("Write the JSON report to this file instead of stdout"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("capitalization")
                 .about("Print capitalization (aka, total supply) while checksumming it")
@@ -1677,6 +1809,9 @@ fn main() {
         .unwrap();
 
     match matches.subcommand() {
+        ("banking-trace", Some(arg_matches)) => {
+            banking_trace_process_command(&ledger_path, arg_matches)
+        }
         ("bigtable", Some(arg_matches)) => bigtable_process_command(&ledger_path, arg_matches),
         ("blockstore", Some(arg_matches)) => blockstore_process_command(&ledger_path, arg_matches),
         ("program", Some(arg_matches)) => program(&ledger_path, arg_matches),
@@ -1786,6 +1921,8 @@ fn main() {
                             Arc::new(blockstore),
                             process_options,
                             None,
+                            None,
+                            None,
                         );
 
                     println!(
@@ -1850,6 +1987,8 @@ fn main() {
                             Arc::new(blockstore),
                             process_options,
                             transaction_status_sender,
+                            None,
+                            None,
                         );
 
                     let working_bank = bank_forks.read().unwrap().working_bank();
@@ -1921,6 +2060,8 @@ fn main() {
                             Arc::new(blockstore),
                             process_options,
                             None,
+                            None,
+                            None,
                         );
 
                     let dot = graph_forks(&bank_forks.read().unwrap(), &graph_config);
@@ -2118,6 +2259,8 @@ fn main() {
                         blockstore.clone(),
                         process_options,
                         None,
+                        None,
+                        None,
                     );
 
                     let mut bank = bank_forks
@@ -2532,8 +2675,7 @@ fn main() {
                         Some(banking_trace_events.hash_overrides().clone());
 
                     let slot = value_t!(arg_matches, "first_simulated_slot", Slot).unwrap();
-                    let simulator = BankingSimulator::new(banking_trace_events, slot);
-                    let Some(parent_slot) = simulator.parent_slot() else {
+                    let Some(parent_slot) = banking_trace_events.parent_slot(slot) else {
                         eprintln!(
                             "Couldn't determine parent_slot of first_simulated_slot: {slot} due \
                              to missing banking_trace_event data."
@@ -2557,9 +2699,11 @@ fn main() {
                             blockstore.clone(),
                             process_options,
                             None, // transaction status sender
+                            None, // accounts update notifier
+                            None, // instruction trace notifier
                         );
 
-                    let block_production_method = value_t_or_exit!(
+                    let block_production_methods = values_t_or_exit!(
                         arg_matches,
                         "block_production_method",
                         BlockProductionMethod
@@ -2567,24 +2711,56 @@ fn main() {
                     let transaction_struct =
                         value_t_or_exit!(arg_matches, "transaction_struct", TransactionStructure);
 
-                    info!(
-                        "Using: block-production-method: {block_production_method} \
-                         transaction-structure: {transaction_struct}"
-                    );
-
-                    match simulator.start(
-                        genesis_config,
-                        bank_forks,
-                        blockstore,
-                        block_production_method,
-                        transaction_struct,
-                    ) {
-                        Ok(()) => println!("Ok"),
-                        Err(error) => {
-                            eprintln!("{error:?}");
+                    if let Ok(num_slots) = value_t!(arg_matches, "replay_slots", usize) {
+                        let replayer =
+                            BankingTraceReplayer::new(banking_trace_events, slot, num_slots);
+                        let parent_bank = bank_forks.read().unwrap().working_bank();
+                        for block_production_method in block_production_methods {
+                            info!(
+                                "Using: block-production-method: {block_production_method} \
+                                 transaction-structure: {transaction_struct}"
+                            );
+                            match replayer.replay(
+                                parent_bank.clone(),
+                                block_production_method,
+                                transaction_struct.clone(),
+                            ) {
+                                Ok(report) => println!("{report}"),
+                                Err(error) => {
+                                    eprintln!("{error}");
+                                    exit(1);
+                                }
+                            }
+                        }
+                    } else {
+                        if block_production_methods.len() > 1 {
+                            eprintln!(
+                                "Only one --block-production-method can be simulated in real \
+                                 time; use --replay-slots to compare several"
+                            );
                             exit(1);
                         }
-                    };
+                        let block_production_method =
+                            block_production_methods.into_iter().next().unwrap();
+                        info!(
+                            "Using: block-production-method: {block_production_method} \
+                             transaction-structure: {transaction_struct}"
+                        );
+                        let simulator = BankingSimulator::new(banking_trace_events, slot);
+                        match simulator.start(
+                            genesis_config,
+                            bank_forks,
+                            blockstore,
+                            block_production_method,
+                            transaction_struct,
+                        ) {
+                            Ok(()) => println!("Ok"),
+                            Err(error) => {
+                                eprintln!("{error:?}");
+                                exit(1);
+                            }
+                        };
+                    }
                 }
                 ("accounts", Some(arg_matches)) => {
                     let process_options = parse_process_options(&ledger_path, arg_matches);
@@ -2601,6 +2777,8 @@ fn main() {
                             Arc::new(blockstore),
                             process_options,
                             None,
+                            None,
+                            None,
                         );
                     let bank = bank_forks.read().unwrap().working_bank();
 
@@ -2639,6 +2817,109 @@ fn main() {
                     );
                     info!("{scan_time}");
                 }
+                ("account-diff", Some(arg_matches)) => {
+                    let mut process_options = parse_process_options(&ledger_path, arg_matches);
+                    let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+                    let blockstore = open_blockstore(
+                        &ledger_path,
+                        arg_matches,
+                        get_access_type(&process_options),
+                    );
+
+                    let starting_slot = value_t!(arg_matches, "starting_slot", Slot).unwrap_or(0);
+                    let format = value_t_or_exit!(arg_matches, "format", AccountDiffFormat);
+                    let include_data = arg_matches.is_present("include_data");
+                    let accounts: Option<HashSet<Pubkey>> =
+                        pubkeys_of(arg_matches, "account").map(HashSet::from_iter);
+                    let program_id = pubkey_of(arg_matches, "program_accounts");
+                    let output: Box<dyn Write + Send> = match arg_matches.value_of("output_file") {
+                        Some(filename) => Box::new(io::BufWriter::new(
+                            File::create(filename).unwrap_or_else(|err| {
+                                eprintln!("Unable to write to file: {filename}: {err:#}");
+                                exit(1);
+                            }),
+                        )),
+                        None => Box::new(io::stdout()),
+                    };
+
+                    let recorder = Arc::new(AccountDiffRecorder::default());
+                    let writer = Mutex::new(AccountDiffWriter::new(output, format, include_data));
+                    process_options.slot_callback = Some(Arc::new({
+                        let recorder = recorder.clone();
+                        move |bank: &Bank| {
+                            let slot = bank.slot();
+                            let mut written = recorder.take_slot(slot);
+                            if slot < starting_slot {
+                                return;
+                            }
+                            written.retain(|(pubkey, account)| {
+                                accounts
+                                    .as_ref()
+                                    .is_none_or(|accounts| accounts.contains(pubkey))
+                                    && program_id
+                                        .is_none_or(|program_id| account.owner() == &program_id)
+                            });
+                            writer
+                                .lock()
+                                .unwrap()
+                                .write_slot(slot, &written)
+                                .unwrap_or_else(|err| {
+                                    eprintln!(
+                                        "Error while writing accounts for slot {slot}: {err}"
+                                    );
+                                    exit(1);
+                                });
+                        }
+                    }));
+
+                    load_and_process_ledger_or_exit(
+                        arg_matches,
+                        &genesis_config,
+                        Arc::new(blockstore),
+                        process_options,
+                        None,
+                        Some(recorder as AccountsUpdateNotifier),
+                        None,
+                    );
+                }
+                ("compute-profile", Some(arg_matches)) => {
+                    let process_options = parse_process_options(&ledger_path, arg_matches);
+                    let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+                    let blockstore = open_blockstore(
+                        &ledger_path,
+                        arg_matches,
+                        get_access_type(&process_options),
+                    );
+
+                    let starting_slot = value_t!(arg_matches, "starting_slot", Slot).unwrap_or(0);
+                    let ending_slot = process_options.halt_at_slot.unwrap_or(Slot::MAX);
+                    let output: Box<dyn Write> = match arg_matches.value_of("output_file") {
+                        Some(filename) => Box::new(io::BufWriter::new(
+                            File::create(filename).unwrap_or_else(|err| {
+                                eprintln!("Unable to write to file: {filename}: {err:#}");
+                                exit(1);
+                            }),
+                        )),
+                        None => Box::new(io::stdout()),
+                    };
+
+                    let compute_profiler =
+                        Arc::new(ComputeProfiler::new(starting_slot..=ending_slot));
+                    load_and_process_ledger_or_exit(
+                        arg_matches,
+                        &genesis_config,
+                        Arc::new(blockstore),
+                        process_options,
+                        None,
+                        None,
+                        Some(compute_profiler.clone() as InstructionTraceNotifierArc),
+                    );
+                    serde_json::to_writer_pretty(output, &compute_profiler.report())
+                        .unwrap_or_else(|err| {
+                            eprintln!("Error while writing the compute profile: {err}");
+                            exit(1);
+                        });
+                }
                 ("capitalization", Some(arg_matches)) => {
                     let process_options = parse_process_options(&ledger_path, arg_matches);
                     let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
@@ -2654,6 +2935,8 @@ fn main() {
                             Arc::new(blockstore),
                             process_options,
                             None,
+                            None,
+                            None,
                         );
                     let bank_forks = bank_forks.read().unwrap();
                     let slot = bank_forks.working_bank().slot();
@@ -1,20 +1,28 @@
 use {
     crate::{args::*, canonicalize_ledger_path, ledger_utils::*},
-    agave_syscalls::create_program_runtime_environment_v1,
-    clap::{App, AppSettings, Arg, ArgMatches, SubCommand},
+    addr2line::object::{Object, ObjectSection},
+    agave_syscalls::{
+        create_program_runtime_environment_v1,
+        create_syscall_tracing_program_runtime_environment_v1,
+    },
+    clap::{value_t, value_t_or_exit, App, AppSettings, Arg, ArgMatches, SubCommand},
     log::*,
     serde_derive::{Deserialize, Serialize},
     serde_json::Result,
     solana_account::{
         create_account_shared_data_for_test, state_traits::StateMut, AccountSharedData,
+        ReadableAccount,
     },
     solana_bpf_loader_program::{create_vm, load_program_from_bytes},
+    solana_clap_utils::input_validators::{is_parsable, is_slot},
     solana_cli_output::{OutputFormat, QuietDisplay, VerboseDisplay},
     solana_clock::Slot,
-    solana_ledger::blockstore_options::AccessType,
+    solana_entry::entry::Entry,
+    solana_ledger::{blockstore_options::AccessType, leader_schedule_cache::LeaderScheduleCache},
     solana_loader_v3_interface::state::UpgradeableLoaderState,
     solana_program_runtime::{
-        invoke_context::InvokeContext,
+        execution_trace::ExecutionTraceFormat,
+        invoke_context::{InstructionTrace, InvokeContext},
         loaded_programs::{
             LoadProgramMetrics, ProgramCacheEntryType, DELAY_VISIBILITY_SLOT_OFFSET,
         },
@@ -24,10 +32,12 @@ use {
     solana_pubkey::Pubkey,
     solana_runtime::bank::Bank,
     solana_sbpf::{
-        assembler::assemble, elf::Executable, static_analysis::Analysis,
+        assembler::assemble, ebpf, elf::Executable, static_analysis::Analysis,
         verifier::RequisiteVerifier,
     },
-    solana_sdk_ids::{bpf_loader_upgradeable, sysvar},
+    solana_sdk_ids::{bpf_loader, bpf_loader_deprecated, bpf_loader_upgradeable, sysvar},
+    solana_signature::Signature,
+    solana_transaction::versioned::VersionedTransaction,
     solana_transaction_context::{IndexOfAccount, InstructionAccount},
     std::{
         collections::HashMap,
@@ -84,6 +94,8 @@ fn load_blockstore(ledger_path: &Path, arg_matches: &ArgMatches<'_>) -> Arc<Bank
         Arc::new(blockstore),
         process_options,
         None,
+        None,
+        None,
     );
     let bank = bank_forks.read().unwrap().working_bank();
     bank
@@ -206,8 +218,89 @@ and the following fields are required
                         .takes_value(true)
                         .value_name("FILE"),
                 )
+                .arg(
+                    Arg::with_name("execution_trace")
+                        .help(
+                            "Output a trace of every instruction executed, with the \
+                             compute units, syscalls and account resizes of each, to FILE \
+                             or to 'stdout'",
+                        )
+                        .long("execution-trace")
+                        .takes_value(true)
+                        .value_name("FILE"),
+                )
+                .arg(
+                    Arg::with_name("execution_trace_format")
+                        .help(
+                            "Format of the execution trace, where 'tree' nests inner \
+                             instructions within the instruction invoking them, and 'chrome' \
+                             is the Chrome trace event format loaded by Perfetto",
+                        )
+                        .long("execution-trace-format")
+                        .takes_value(true)
+                        .value_name("FORMAT")
+                        .possible_values(&["tree", "chrome"])
+                        .default_value("tree")
+                        .requires("execution_trace"),
+                )
                 .arg(&program_arg)
         )
+        .subcommand(
+            SubCommand::with_name("replay")
+                .about(
+                    "Re-executes a transaction in the ledger with the accounts, programs and \
+                     feature set of its slot, and outputs the instruction trace of every \
+                     sBPF program it invoked, including through CPI.",
+                )
+                .arg(
+                    Arg::with_name("signature")
+                        .help("Signature of the transaction to replay")
+                        .long("signature")
+                        .takes_value(true)
+                        .value_name("SIGNATURE")
+                        .validator(is_parsable::<Signature>)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("slot")
+                        .help(
+                            "Slot of the transaction. Required if the ledger has no \
+                             transaction status for it",
+                        )
+                        .long("slot")
+                        .takes_value(true)
+                        .value_name("SLOT")
+                        .validator(is_slot),
+                )
+                .arg(
+                    Arg::with_name("debug_elf")
+                        .help(
+                            "Unstripped build of a program, whose DWARF debug info is used \
+                             to annotate the frames of the program with source lines. May be \
+                             specified multiple times",
+                        )
+                        .long("debug-elf")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("PROGRAM_ID:FILE")
+                        .validator(|value| parse_debug_elf(&value).map(|_| ())),
+                )
+                .arg(
+                    Arg::with_name("trace")
+                        .help(
+                            "Output instruction trace to 'stdout', or the trace of each \
+                             frame N to FILE.N",
+                        )
+                        .short("t")
+                        .long("trace")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .default_value("stdout"),
+                )
+                .arg(&load_genesis_config_arg)
+                .args(&snapshot_config_args)
+        )
         )
     }
 }
@@ -264,22 +357,114 @@ fn output_trace(
     matches: &ArgMatches<'_>,
     trace: &[[u64; 12]],
     frame: usize,
+    program_id: Option<&Pubkey>,
     analysis: &mut LazyAnalysis,
+    debug_info: Option<&DebugInfo>,
 ) {
     if matches.value_of("trace").unwrap() == "stdout" {
-        writeln!(&mut std::io::stdout(), "Frame {frame}").unwrap();
-        analysis
-            .analyze()
-            .disassemble_trace_log(&mut std::io::stdout(), trace)
-            .unwrap();
+        write_trace(
+            &mut std::io::stdout(),
+            trace,
+            frame,
+            program_id,
+            analysis,
+            debug_info,
+        )
+        .unwrap();
     } else {
         let filename = format!("{}.{}", matches.value_of("trace").unwrap(), frame);
         let mut fd = File::create(filename).unwrap();
-        writeln!(&fd, "Frame {frame}").unwrap();
-        analysis
-            .analyze()
-            .disassemble_trace_log(&mut fd, trace)
-            .unwrap();
+        write_trace(&mut fd, trace, frame, program_id, analysis, debug_info).unwrap();
+    }
+}
+
+fn write_trace(
+    output: &mut impl Write,
+    trace: &[[u64; 12]],
+    frame: usize,
+    program_id: Option<&Pubkey>,
+    analysis: &mut LazyAnalysis,
+    debug_info: Option<&DebugInfo>,
+) -> std::io::Result<()> {
+    match program_id {
+        Some(program_id) => writeln!(output, "Frame {frame} of program {program_id}")?,
+        None => writeln!(output, "Frame {frame}")?,
+    }
+    let Some(debug_info) = debug_info else {
+        return analysis.analyze().disassemble_trace_log(output, trace);
+    };
+    let mut disassembly = Vec::new();
+    analysis
+        .analyze()
+        .disassemble_trace_log(&mut disassembly, trace)?;
+    for (line, entry) in String::from_utf8_lossy(&disassembly).lines().zip(trace) {
+        // The last register of a trace entry is the program counter
+        match debug_info.source_location(entry[11]) {
+            Some(location) => writeln!(output, "{line}  ; {location}")?,
+            None => writeln!(output, "{line}")?,
+        }
+    }
+    Ok(())
+}
+
+/// Maps program counters to source lines using the DWARF sections of an
+/// unstripped build of the executed program
+struct DebugInfo {
+    context: addr2line::Context<addr2line::gimli::EndianRcSlice<addr2line::gimli::RunTimeEndian>>,
+    text_address: u64,
+}
+
+impl DebugInfo {
+    fn load(filename: &Path) -> std::result::Result<Self, String> {
+        let contents = std::fs::read(filename)
+            .map_err(|err| format!("Unable to read {}: {err}", filename.display()))?;
+        let elf = addr2line::object::File::parse(contents.as_slice())
+            .map_err(|err| format!("Unable to parse {}: {err}", filename.display()))?;
+        let text_address = elf
+            .section_by_name(".text")
+            .map(|section| section.address())
+            .ok_or_else(|| format!("{} has no .text section", filename.display()))?;
+        let context = addr2line::Context::new(&elf).map_err(|err| {
+            format!(
+                "Unable to load debug info from {}: {err}",
+                filename.display()
+            )
+        })?;
+        Ok(Self {
+            context,
+            text_address,
+        })
+    }
+
+    fn source_location(&self, pc: u64) -> Option<String> {
+        let address = self
+            .text_address
+            .saturating_add(pc.saturating_mul(ebpf::INSN_SIZE as u64));
+        let location = self.context.find_location(address).ok()??;
+        let file = location.file?;
+        Some(match (location.line, location.column) {
+            (Some(line), Some(column)) => format!("{file}:{line}:{column}"),
+            (Some(line), None) => format!("{file}:{line}"),
+            _ => file.to_string(),
+        })
+    }
+}
+
+fn output_execution_trace(matches: &ArgMatches<'_>, invoke_context: &mut InvokeContext) {
+    let format = matches
+        .value_of("execution_trace_format")
+        .unwrap()
+        .parse::<ExecutionTraceFormat>()
+        .unwrap();
+    let execution_trace =
+        format.export(&invoke_context.take_instruction_traces().unwrap_or_default());
+    let execution_trace = serde_json::to_string_pretty(&execution_trace).unwrap();
+    match matches.value_of("execution_trace").unwrap() {
+        "stdout" => println!("{execution_trace}"),
+        filename => {
+            let mut fd = File::create(filename).unwrap();
+            writeln!(&mut fd, "{execution_trace}").unwrap();
+        }
     }
 }
 
@@ -287,6 +472,7 @@ fn load_program<'a>(
     filename: &Path,
     program_id: Pubkey,
     invoke_context: &InvokeContext<'a>,
+    trace_syscalls: bool,
 ) -> Executable<InvokeContext<'a>> {
     let mut file = File::open(filename).unwrap();
     let mut magic = [0u8; 4];
@@ -295,15 +481,37 @@ fn load_program<'a>(
     let is_elf = magic == ELF_MAGIC_NUMBER;
     let mut contents = Vec::new();
     file.read_to_end(&mut contents).unwrap();
+    load_executable(
+        &contents,
+        is_elf,
+        program_id,
+        &bpf_loader_upgradeable::id(),
+        invoke_context,
+        trace_syscalls,
+    )
+}
+
+fn load_executable<'a>(
+    contents: &[u8],
+    is_elf: bool,
+    program_id: Pubkey,
+    loader_key: &Pubkey,
+    invoke_context: &InvokeContext<'a>,
+    trace_syscalls: bool,
+) -> Executable<InvokeContext<'a>> {
     let slot = Slot::default();
     let log_collector = invoke_context.get_log_collector();
-    let loader_key = bpf_loader_upgradeable::id();
     let mut load_program_metrics = LoadProgramMetrics {
         program_id: program_id.to_string(),
         ..LoadProgramMetrics::default()
     };
     let account_size = contents.len();
-    let program_runtime_environment = create_program_runtime_environment_v1(
+    let create_program_runtime_environment = if trace_syscalls {
+        create_syscall_tracing_program_runtime_environment_v1
+    } else {
+        create_program_runtime_environment_v1
+    };
+    let program_runtime_environment = create_program_runtime_environment(
         invoke_context.get_feature_set(),
         invoke_context.get_compute_budget(),
         false, /* deployment */
@@ -316,8 +524,8 @@ fn load_program<'a>(
         let result = load_program_from_bytes(
             log_collector,
             &mut load_program_metrics,
-            &contents,
-            &loader_key,
+            contents,
+            loader_key,
             account_size,
             slot,
             Arc::new(program_runtime_environment),
@@ -332,7 +540,7 @@ fn load_program<'a>(
         }
     } else {
         assemble::<InvokeContext>(
-            std::str::from_utf8(contents.as_slice()).unwrap(),
+            std::str::from_utf8(contents).unwrap(),
             Arc::new(program_runtime_environment),
         )
         .map_err(|err| format!("Assembling executable failed: {err:?}"))
@@ -363,7 +571,7 @@ fn process_static_action(action: Action, matches: &ArgMatches<'_>) {
     let program_id = Pubkey::new_unique();
     with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
     let program = matches.value_of("PROGRAM").unwrap();
-    let verified_executable = load_program(Path::new(program), program_id, &invoke_context);
+    let verified_executable = load_program(Path::new(program), program_id, &invoke_context, false);
     let mut analysis = LazyAnalysis::new(&verified_executable);
     match action {
         Action::Cfg => {
@@ -390,6 +598,10 @@ pub fn program(ledger_path: &Path, matches: &ArgMatches<'_>) {
             process_static_action(Action::Dis, arg_matches);
             return;
         }
+        ("replay", Some(arg_matches)) => {
+            replay(ledger_path, arg_matches);
+            return;
+        }
         ("run", Some(arg_matches)) => arg_matches,
         _ => unreachable!(),
     };
@@ -541,7 +753,13 @@ pub fn program(ledger_path: &Path, matches: &ArgMatches<'_>) {
     .unwrap();
 
     let program = matches.value_of("PROGRAM").unwrap();
-    let verified_executable = load_program(Path::new(program), program_id, &invoke_context);
+    let execution_trace = matches.is_present("execution_trace");
+    let verified_executable = load_program(
+        Path::new(program),
+        program_id,
+        &invoke_context,
+        execution_trace,
+    );
     let mut analysis = LazyAnalysis::new(&verified_executable);
     create_vm!(
         vm,
@@ -551,26 +769,38 @@ pub fn program(ledger_path: &Path, matches: &ArgMatches<'_>) {
         &mut invoke_context,
     );
     let (mut vm, _, _) = vm.unwrap();
+    if execution_trace {
+        vm.context_object_pointer
+            .enable_instruction_trace_recording(false);
+        vm.context_object_pointer
+            .start_instruction_trace(&program_id);
+    }
     let start_time = Instant::now();
     if matches.value_of("mode").unwrap() == "debugger" {
         vm.debug_port = Some(matches.value_of("port").unwrap().parse::<u16>().unwrap());
     }
     let (instruction_count, result) = vm.execute_program(&verified_executable, interpreted);
     let duration = Instant::now() - start_time;
+    if execution_trace {
+        vm.context_object_pointer.finish_instruction_trace();
+    }
     if matches.occurrences_of("trace") > 0 {
         // top level trace is stored in syscall_context
         if let Some(Some(syscall_context)) = vm.context_object_pointer.syscall_context.last() {
             let trace = syscall_context.trace_log.as_slice();
-            output_trace(matches, trace, 0, &mut analysis);
+            output_trace(matches, trace, 0, None, &mut analysis, None);
         }
         // the remaining traces are saved in InvokeContext when
         // corresponding syscall_contexts are popped
         let traces = vm.context_object_pointer.get_traces();
         for (frame, trace) in traces.iter().filter(|t| !t.is_empty()).enumerate() {
-            output_trace(matches, trace, frame + 1, &mut analysis);
+            output_trace(matches, trace, frame + 1, None, &mut analysis, None);
         }
     }
     drop(vm);
+    if execution_trace {
+        output_execution_trace(matches, &mut invoke_context);
+    }
 
     let output = Output {
         result: format!("{result:?}"),
@@ -586,3 +816,445 @@ pub fn program(ledger_path: &Path, matches: &ArgMatches<'_>) {
     let output_format = OutputFormat::from_matches(matches, "output_format", false);
     println!("{}", output_format.formatted_string(&output));
 }
+
+fn replay(ledger_path: &Path, matches: &ArgMatches<'_>) {
+    let signature = value_t_or_exit!(matches, "signature", Signature);
+    let debug_infos: HashMap<_, _> = matches
+        .values_of("debug_elf")
+        .into_iter()
+        .flatten()
+        .map(|value| {
+            let (program_id, filename) = parse_debug_elf(value).unwrap();
+            let debug_info = DebugInfo::load(filename).unwrap_or_else(|err| {
+                eprintln!("{err}");
+                exit(1);
+            });
+            (program_id, debug_info)
+        })
+        .collect();
+
+    let ledger_path = canonicalize_ledger_path(ledger_path);
+    let blockstore = Arc::new(open_blockstore(
+        &ledger_path,
+        matches,
+        AccessType::Secondary,
+    ));
+    let slot = value_t!(matches, "slot", Slot).ok().unwrap_or_else(|| {
+        match blockstore.get_rooted_transaction_status(signature) {
+            Ok(Some((slot, _))) => slot,
+            _ => {
+                eprintln!("No transaction status for {signature}, specify its slot with --slot");
+                exit(1);
+            }
+        }
+    });
+    let Some(parent_slot) = blockstore
+        .meta(slot)
+        .ok()
+        .flatten()
+        .and_then(|meta| meta.parent_slot)
+    else {
+        eprintln!("Slot {slot} is not in the ledger");
+        exit(1);
+    };
+    let entries = blockstore.get_slot_entries(slot, 0).unwrap_or_else(|err| {
+        eprintln!("Unable to read the entries of slot {slot}: {err}");
+        exit(1);
+    });
+    let Some((preceding_transactions, transaction)) =
+        split_entries_at_transaction(entries, &signature)
+    else {
+        eprintln!("Transaction {signature} is not in slot {slot}");
+        exit(1);
+    };
+
+    // Replay up to the parent, then re-execute the transactions of the slot
+    // preceding this one, so that it sees the same accounts as it did
+    let mut process_options = parse_process_options(&ledger_path, matches);
+    process_options.halt_at_slot = Some(parent_slot);
+    let genesis_config = open_genesis_config_by(&ledger_path, matches);
+    let LoadAndProcessLedgerOutput { bank_forks, .. } = load_and_process_ledger_or_exit(
+        matches,
+        &genesis_config,
+        blockstore,
+        process_options,
+        None,
+        None,
+        None,
+    );
+    let Some(parent) = bank_forks.read().unwrap().get(parent_slot) else {
+        eprintln!(
+            "Unable to load slot {parent_slot}, the parent of slot {slot}. The snapshot loaded \
+             may be newer than it."
+        );
+        exit(1);
+    };
+    let leader = LeaderScheduleCache::new_from_bank(&parent)
+        .slot_leader_at(slot, Some(parent.as_ref()))
+        .unwrap_or_else(|| *parent.collector_id());
+    let bank = bank_forks
+        .write()
+        .unwrap()
+        .insert(Bank::new_from_parent(parent.clone(), &leader, slot))
+        .clone_without_scheduler();
+    for transactions in preceding_transactions {
+        if let Err(err) = bank.try_process_entry_transactions(transactions) {
+            eprintln!("Unable to execute the transactions preceding {signature}: {err}");
+            exit(1);
+        }
+    }
+
+    // The transaction is executed and committed as replay does, so that its
+    // fees are charged, its nonce is advanced and the rent state of its
+    // accounts is checked
+    let start_time = Instant::now();
+    let (commit_result, instruction_traces) = bank
+        .process_transaction_with_instruction_traces(transaction)
+        .unwrap_or_else(|err| {
+            eprintln!("Unable to execute transaction {signature}: {err}");
+            exit(1);
+        });
+    let duration = Instant::now() - start_time;
+
+    // Programs deployed during the slot are not visible until the next one,
+    // so the programs executed are the ones of the parent
+    let mut executables = HashMap::new();
+    for instruction_trace in traced_frames(&instruction_traces) {
+        let program_id = instruction_trace.program_id;
+        if !executables.contains_key(&program_id) {
+            let executable = load_traced_executable(&parent, &program_id).unwrap_or_else(|err| {
+                eprintln!("{err}");
+                exit(1);
+            });
+            executables.insert(program_id, executable);
+        }
+    }
+    let mut analyses: HashMap<_, _> = executables
+        .iter()
+        .map(|(program_id, executable)| (*program_id, LazyAnalysis::new(executable)))
+        .collect();
+    for (frame, instruction_trace) in traced_frames(&instruction_traces).enumerate() {
+        output_trace(
+            matches,
+            &instruction_trace.register_trace,
+            frame,
+            Some(&instruction_trace.program_id),
+            analyses.get_mut(&instruction_trace.program_id).unwrap(),
+            debug_infos.get(&instruction_trace.program_id),
+        );
+    }
+
+    let (result, log) = match commit_result {
+        Ok(committed_transaction) => (
+            format!("{:?}", committed_transaction.status),
+            committed_transaction.log_messages.unwrap_or_default(),
+        ),
+        Err(err) => (format!("{:?}", Err::<(), _>(err)), Vec::new()),
+    };
+    let output = Output {
+        result,
+        instruction_count: traced_frames(&instruction_traces)
+            .map(|instruction_trace| instruction_trace.register_trace.len() as u64)
+            .sum(),
+        execution_time: duration,
+        log,
+    };
+    let output_format = OutputFormat::from_matches(matches, "output_format", false);
+    println!("{}", output_format.formatted_string(&output));
+}
+
+/// The instructions executed by sBPF programs, which are the frames of the
+/// trace in execution order. Inner instructions follow the instruction
+/// invoking them.
+fn traced_frames(
+    instruction_traces: &[InstructionTrace],
+) -> impl Iterator<Item = &InstructionTrace> {
+    instruction_traces
+        .iter()
+        .filter(|instruction_trace| !instruction_trace.register_trace.is_empty())
+}
+
+/// Parses a `PROGRAM_ID:FILE` value of `--debug-elf`
+fn parse_debug_elf(value: &str) -> std::result::Result<(Pubkey, &Path), String> {
+    let (program_id, filename) = value
+        .split_once(':')
+        .ok_or_else(|| format!("{value} is not of the form PROGRAM_ID:FILE"))?;
+    let program_id = program_id
+        .parse::<Pubkey>()
+        .map_err(|err| format!("Invalid program ID {program_id}: {err}"))?;
+    Ok((program_id, Path::new(filename)))
+}
+
+/// Loads the executable of `program_id` as deployed in `bank`, in the
+/// debugging environment its trace was recorded in
+fn load_traced_executable(
+    bank: &Bank,
+    program_id: &Pubkey,
+) -> std::result::Result<Executable<InvokeContext<'static>>, String> {
+    let (_loader_id, elf) = program_elf(bank, program_id)?;
+    let program_runtime_environment = create_program_runtime_environment_v1(
+        &bank.feature_set.runtime_features(),
+        &bank.compute_budget().unwrap_or_default().to_budget(),
+        false, /* deployment */
+        true,  /* debugging_features */
+    )
+    .map_err(|err| format!("Unable to create the program runtime environment: {err}"))?;
+    Executable::load(&elf, Arc::new(program_runtime_environment))
+        .map_err(|err| format!("Unable to load program {program_id}: {err}"))
+}
+
+/// Splits the transactions of `entries` into the batches preceding the one
+/// with `signature`, and that transaction
+fn split_entries_at_transaction(
+    entries: Vec<Entry>,
+    signature: &Signature,
+) -> Option<(Vec<Vec<VersionedTransaction>>, VersionedTransaction)> {
+    let mut preceding_transactions = Vec::new();
+    for entry in entries {
+        let mut transactions = entry.transactions;
+        match transactions
+            .iter()
+            .position(|transaction| transaction.signatures.first() == Some(signature))
+        {
+            Some(index) => {
+                let transaction = transactions.remove(index);
+                transactions.truncate(index);
+                if !transactions.is_empty() {
+                    preceding_transactions.push(transactions);
+                }
+                return Some((preceding_transactions, transaction));
+            }
+            None if !transactions.is_empty() => preceding_transactions.push(transactions),
+            None => {}
+        }
+    }
+    None
+}
+
+/// Returns the loader and ELF of `program_id` as deployed in `bank`
+fn program_elf(bank: &Bank, program_id: &Pubkey) -> std::result::Result<(Pubkey, Vec<u8>), String> {
+    let account = bank
+        .get_account_with_fixed_root(program_id)
+        .ok_or_else(|| format!("Program {program_id} does not exist"))?;
+    let loader_id = *account.owner();
+    if bpf_loader_upgradeable::check_id(&loader_id) {
+        let Ok(UpgradeableLoaderState::Program {
+            programdata_address,
+        }) = account.state()
+        else {
+            return Err(format!("{program_id} is not a program account"));
+        };
+        let programdata = bank
+            .get_account_with_fixed_root(&programdata_address)
+            .ok_or_else(|| format!("Program data {programdata_address} does not exist"))?;
+        let elf = programdata
+            .data()
+            .get(UpgradeableLoaderState::size_of_programdata_metadata()..)
+            .ok_or_else(|| format!("Program {program_id} is closed"))?;
+        Ok((loader_id, elf.to_vec()))
+    } else if bpf_loader::check_id(&loader_id) || bpf_loader_deprecated::check_id(&loader_id) {
+        Ok((loader_id, account.data().to_vec()))
+    } else {
+        Err(format!(
+            "Program {program_id} is owned by {loader_id}, only sBPF programs of the BPF loaders \
+             can be replayed"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_account::WritableAccount,
+        solana_hash::Hash,
+        solana_message::VersionedMessage,
+        solana_native_token::LAMPORTS_PER_SOL,
+        solana_runtime::genesis_utils::{create_genesis_config, GenesisConfigInfo},
+    };
+
+    const NOOP_ELF: &[u8] =
+        include_bytes!("../../programs/bpf_loader/test_elfs/out/noop_aligned.so");
+
+    fn transaction(signature: Signature) -> VersionedTransaction {
+        VersionedTransaction {
+            signatures: vec![signature],
+            message: VersionedMessage::default(),
+        }
+    }
+
+    #[test]
+    fn test_split_entries_at_transaction() {
+        let signatures: Vec<_> = (1..=4u8).map(|i| Signature::from([i; 64])).collect();
+        let entries = vec![
+            Entry::new(&Hash::default(), 1, vec![transaction(signatures[0])]),
+            Entry::new(&Hash::default(), 1, vec![]),
+            Entry::new(
+                &Hash::default(),
+                1,
+                vec![
+                    transaction(signatures[1]),
+                    transaction(signatures[2]),
+                    transaction(signatures[3]),
+                ],
+            ),
+        ];
+
+        let (preceding_transactions, transaction) =
+            split_entries_at_transaction(entries.clone(), &signatures[2]).unwrap();
+        assert_eq!(transaction.signatures, vec![signatures[2]]);
+        assert_eq!(
+            preceding_transactions
+                .iter()
+                .map(|transactions| transactions
+                    .iter()
+                    .map(|transaction| transaction.signatures[0])
+                    .collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            vec![vec![signatures[0]], vec![signatures[1]]]
+        );
+
+        let (preceding_transactions, _) =
+            split_entries_at_transaction(entries.clone(), &signatures[0]).unwrap();
+        assert!(preceding_transactions.is_empty());
+
+        assert!(split_entries_at_transaction(entries, &Signature::from([5; 64])).is_none());
+    }
+
+    #[test]
+    fn test_parse_debug_elf() {
+        let program_id = Pubkey::new_unique();
+        assert_eq!(
+            parse_debug_elf(&format!("{program_id}:target/deploy/program.so")).unwrap(),
+            (program_id, Path::new("target/deploy/program.so"))
+        );
+        // Only the first colon separates the program ID from the file
+        assert_eq!(
+            parse_debug_elf(&format!("{program_id}:C:/program.so")).unwrap(),
+            (program_id, Path::new("C:/program.so"))
+        );
+        assert!(parse_debug_elf("target/deploy/program.so").is_err());
+        assert!(parse_debug_elf("not-a-pubkey:program.so").is_err());
+    }
+
+    #[test]
+    fn test_traced_frames() {
+        let program_ids: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        // A builtin invoked first, then a program invoking another through
+        // CPI, whose trace is recorded after its caller's
+        let instruction_traces = [
+            InstructionTrace {
+                program_id: program_ids[0],
+                stack_height: 1,
+                ..InstructionTrace::default()
+            },
+            InstructionTrace {
+                program_id: program_ids[1],
+                stack_height: 1,
+                register_trace: vec![[0; 12]; 3],
+                ..InstructionTrace::default()
+            },
+            InstructionTrace {
+                program_id: program_ids[2],
+                stack_height: 2,
+                register_trace: vec![[0; 12]; 2],
+                ..InstructionTrace::default()
+            },
+        ];
+        assert_eq!(
+            traced_frames(&instruction_traces)
+                .map(|instruction_trace| instruction_trace.program_id)
+                .collect::<Vec<_>>(),
+            program_ids[1..]
+        );
+    }
+
+    #[test]
+    fn test_program_elf() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(LAMPORTS_PER_SOL);
+        let bank = Bank::new_for_tests(&genesis_config);
+
+        let program_id = Pubkey::new_unique();
+        let mut program_account = AccountSharedData::new(1, NOOP_ELF.len(), &bpf_loader::id());
+        program_account.set_data_from_slice(NOOP_ELF);
+        program_account.set_executable(true);
+        bank.store_account(&program_id, &program_account);
+        assert_eq!(
+            program_elf(&bank, &program_id).unwrap(),
+            (bpf_loader::id(), NOOP_ELF.to_vec())
+        );
+
+        let upgradeable_program_id = Pubkey::new_unique();
+        let programdata_address = Pubkey::new_unique();
+        let mut upgradeable_program_account = AccountSharedData::new(
+            1,
+            UpgradeableLoaderState::size_of_program(),
+            &bpf_loader_upgradeable::id(),
+        );
+        upgradeable_program_account
+            .set_state(&UpgradeableLoaderState::Program {
+                programdata_address,
+            })
+            .unwrap();
+        upgradeable_program_account.set_executable(true);
+        bank.store_account(&upgradeable_program_id, &upgradeable_program_account);
+        assert!(program_elf(&bank, &upgradeable_program_id).is_err());
+
+        let metadata_len = UpgradeableLoaderState::size_of_programdata_metadata();
+        let mut programdata_account = AccountSharedData::new(
+            1,
+            metadata_len + NOOP_ELF.len(),
+            &bpf_loader_upgradeable::id(),
+        );
+        programdata_account
+            .set_state(&UpgradeableLoaderState::ProgramData {
+                slot: 0,
+                upgrade_authority_address: None,
+            })
+            .unwrap();
+        programdata_account.data_as_mut_slice()[metadata_len..].copy_from_slice(NOOP_ELF);
+        bank.store_account(&programdata_address, &programdata_account);
+        assert_eq!(
+            program_elf(&bank, &upgradeable_program_id).unwrap(),
+            (bpf_loader_upgradeable::id(), NOOP_ELF.to_vec())
+        );
+
+        // Neither missing accounts nor accounts of other owners are programs
+        assert!(program_elf(&bank, &Pubkey::new_unique()).is_err());
+        assert!(program_elf(&bank, &solana_sdk_ids::system_program::id()).is_err());
+    }
+
+    #[test]
+    fn test_write_trace() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(LAMPORTS_PER_SOL);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let program_id = Pubkey::new_unique();
+        let mut program_account = AccountSharedData::new(1, NOOP_ELF.len(), &bpf_loader::id());
+        program_account.set_data_from_slice(NOOP_ELF);
+        program_account.set_executable(true);
+        bank.store_account(&program_id, &program_account);
+
+        let executable = load_traced_executable(&bank, &program_id).unwrap();
+        let mut analysis = LazyAnalysis::new(&executable);
+        // A single entry at the first instruction of the program
+        let mut output = Vec::new();
+        write_trace(
+            &mut output,
+            &[[0; 12]],
+            1,
+            Some(&program_id),
+            &mut analysis,
+            None,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let mut lines = output.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            format!("Frame 1 of program {program_id}")
+        );
+        assert_eq!(lines.count(), 1);
+
+        assert!(load_traced_executable(&bank, &Pubkey::new_unique()).is_err());
+    }
+}
@@ -10,8 +10,10 @@ use {
     },
     log::*,
     solana_accounts_db::accounts_update_notifier_interface::AccountsUpdateNotifier,
+    solana_clock::Slot,
     solana_genesis_config::GenesisConfig,
     solana_runtime::{
+        bank::Bank,
         bank_forks::BankForks,
         snapshot_archive_info::{
             FullSnapshotArchiveInfo, IncrementalSnapshotArchiveInfo, SnapshotArchiveInfoGetter,
@@ -61,6 +63,15 @@ pub enum BankForksUtilsError {
 
     #[error("failed to process blockstore from root: {0}")]
     ProcessBlockstoreFromRoot(#[source] BlockstoreProcessorError),
+
+    #[error("slot {0} is not rooted in the blockstore")]
+    SlotNotRooted(Slot),
+
+    #[error("slot {0} was not reached while replaying the blockstore")]
+    SlotNotReplayed(Slot),
+
+    #[error("the blockstore must be opened with secondary access to reconstruct past banks")]
+    BlockstoreNotSecondary,
 }
 
 pub type LoadResult = result::Result<
@@ -125,44 +136,145 @@ pub fn load_bank_forks(
     accounts_update_notifier: Option<AccountsUpdateNotifier>,
     exit: Arc<AtomicBool>,
 ) -> LoadResult {
-    fn get_snapshots_to_load(
-        snapshot_config: &SnapshotConfig,
-    ) -> Option<(
-        FullSnapshotArchiveInfo,
-        Option<IncrementalSnapshotArchiveInfo>,
-    )> {
-        if !snapshot_config.should_load_snapshots() {
-            info!("Snapshots disabled; will load from genesis");
-            return None;
-        };
+    do_load_bank_forks(
+        genesis_config,
+        blockstore,
+        account_paths,
+        snapshot_config,
+        None,
+        process_options,
+        transaction_status_sender,
+        entry_notification_sender,
+        accounts_update_notifier,
+        exit,
+    )
+}
 
-        let Some(full_snapshot_archive_info) =
-            snapshot_utils::get_highest_full_snapshot_archive_info(
-                &snapshot_config.full_snapshot_archives_dir,
-            )
-        else {
-            warn!(
-                "No snapshot package found in directory: {}; will load from genesis",
-                snapshot_config.full_snapshot_archives_dir.display()
-            );
-            return None;
-        };
+/// Reconstruct the bank for a past rooted `slot`
+///
+/// The bank is loaded from the highest snapshot archives at or below `slot` (or from genesis if
+/// there are none), and then the blockstore is replayed up to `slot`.  The returned bank is not
+/// connected to any running validator state; `account_paths` and the bank snapshots dir of
+/// `snapshot_config` should be scratch directories owned by the caller.
+///
+/// `blockstore` must be opened with secondary access, so that the replay does not record bank
+/// hashes or dead slots in the ledger of the validator.
+pub fn load_bank_at_slot(
+    genesis_config: &GenesisConfig,
+    blockstore: &Blockstore,
+    account_paths: Vec<PathBuf>,
+    snapshot_config: &SnapshotConfig,
+    process_options: ProcessOptions,
+    slot: Slot,
+    exit: Arc<AtomicBool>,
+) -> result::Result<Arc<Bank>, BankForksUtilsError> {
+    if blockstore.is_primary_access() {
+        return Err(BankForksUtilsError::BlockstoreNotSecondary);
+    }
+    if !blockstore.is_root(slot) {
+        return Err(BankForksUtilsError::SlotNotRooted(slot));
+    }
+    let process_options = ProcessOptions {
+        halt_at_slot: Some(slot),
+        ..process_options
+    };
+    let (bank_forks, leader_schedule_cache, ..) = do_load_bank_forks(
+        genesis_config,
+        blockstore,
+        account_paths,
+        snapshot_config,
+        Some(slot),
+        &process_options,
+        None,
+        None,
+        None,
+        exit,
+    )?;
+    blockstore_processor::process_blockstore_from_root(
+        blockstore,
+        &bank_forks,
+        &leader_schedule_cache,
+        &process_options,
+        None,
+        None,
+        None, // snapshot_controller
+    )
+    .map_err(BankForksUtilsError::ProcessBlockstoreFromRoot)?;
 
-        let incremental_snapshot_archive_info =
-            snapshot_utils::get_highest_incremental_snapshot_archive_info(
-                &snapshot_config.incremental_snapshot_archives_dir,
-                full_snapshot_archive_info.slot(),
-            );
+    let bank = bank_forks.read().unwrap().get(slot);
+    bank.ok_or(BankForksUtilsError::SlotNotReplayed(slot))
+}
 
-        Some((
-            full_snapshot_archive_info,
-            incremental_snapshot_archive_info,
-        ))
-    }
+/// Get the highest snapshot archives to load, optionally limited to those at or below `max_slot`
+fn get_snapshots_to_load(
+    snapshot_config: &SnapshotConfig,
+    max_slot: Option<Slot>,
+) -> Option<(
+    FullSnapshotArchiveInfo,
+    Option<IncrementalSnapshotArchiveInfo>,
+)> {
+    if !snapshot_config.should_load_snapshots() {
+        info!("Snapshots disabled; will load from genesis");
+        return None;
+    };
+
+    let full_snapshot_archive_info = match max_slot {
+        None => snapshot_utils::get_highest_full_snapshot_archive_info(
+            &snapshot_config.full_snapshot_archives_dir,
+        ),
+        Some(max_slot) => {
+            snapshot_utils::get_full_snapshot_archives(&snapshot_config.full_snapshot_archives_dir)
+                .into_iter()
+                .filter(|full_snapshot_archive_info| full_snapshot_archive_info.slot() <= max_slot)
+                .max()
+        }
+    };
+    let Some(full_snapshot_archive_info) = full_snapshot_archive_info else {
+        warn!(
+            "No snapshot package found in directory: {}; will load from genesis",
+            snapshot_config.full_snapshot_archives_dir.display()
+        );
+        return None;
+    };
 
+    let incremental_snapshot_archive_info = match max_slot {
+        None => snapshot_utils::get_highest_incremental_snapshot_archive_info(
+            &snapshot_config.incremental_snapshot_archives_dir,
+            full_snapshot_archive_info.slot(),
+        ),
+        Some(max_slot) => snapshot_utils::get_incremental_snapshot_archives(
+            &snapshot_config.incremental_snapshot_archives_dir,
+        )
+        .into_iter()
+        .filter(|incremental_snapshot_archive_info| {
+            incremental_snapshot_archive_info.base_slot() == full_snapshot_archive_info.slot()
+                && incremental_snapshot_archive_info.slot() <= max_slot
+        })
+        .max(),
+    };
+
+    Some((
+        full_snapshot_archive_info,
+        incremental_snapshot_archive_info,
+    ))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn do_load_bank_forks(
+    genesis_config: &GenesisConfig,
+    blockstore: &Blockstore,
+    account_paths: Vec<PathBuf>,
+    snapshot_config: &SnapshotConfig,
+    max_snapshot_slot: Option<Slot>,
+    process_options: &ProcessOptions,
+    transaction_status_sender: Option<&TransactionStatusSender>,
+    entry_notification_sender: Option<&EntryNotifierSender>,
+    accounts_update_notifier: Option<AccountsUpdateNotifier>,
+    exit: Arc<AtomicBool>,
+) -> LoadResult {
     let (bank_forks, starting_snapshot_hashes) =
         if let Some((full_snapshot_archive_info, incremental_snapshot_archive_info)) =
-            get_snapshots_to_load(snapshot_config)
+            get_snapshots_to_load(snapshot_config, max_snapshot_slot)
         {
             info!(
                 "Initializing bank snapshots dir: {}",
@@ -4,6 +4,7 @@ use {
         blockstore::Blockstore,
     },
     solana_runtime::commitment::BlockCommitmentCache,
+    solana_storage_bigtable::LongTermLedgerStorage,
     std::{
         cmp::min,
         sync::{
@@ -22,7 +23,7 @@ pub struct BigTableUploadService {
 impl BigTableUploadService {
     pub fn new(
         runtime: Arc<Runtime>,
-        bigtable_ledger_storage: solana_storage_bigtable::LedgerStorage,
+        bigtable_ledger_storage: Arc<dyn LongTermLedgerStorage>,
         blockstore: Arc<Blockstore>,
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
@@ -41,7 +42,7 @@ impl BigTableUploadService {
 
     pub fn new_with_config(
         runtime: Arc<Runtime>,
-        bigtable_ledger_storage: solana_storage_bigtable::LedgerStorage,
+        bigtable_ledger_storage: Arc<dyn LongTermLedgerStorage>,
         blockstore: Arc<Blockstore>,
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
@@ -69,7 +70,7 @@ impl BigTableUploadService {
 
     fn run(
         runtime: Arc<Runtime>,
-        bigtable_ledger_storage: solana_storage_bigtable::LedgerStorage,
+        bigtable_ledger_storage: Arc<dyn LongTermLedgerStorage>,
         blockstore: Arc<Blockstore>,
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
@@ -224,7 +224,15 @@ pub fn execute_batch<'a>(
         .load_execute_and_commit_transactions_with_pre_commit_callback(
             batch,
             MAX_PROCESSING_AGE,
-            ExecutionRecordingConfig::new_single_setting(transaction_status_sender.is_some()),
+            ExecutionRecordingConfig {
+                enable_instruction_trace_recording: bank
+                    .get_transaction_processor()
+                    .has_instruction_trace_notifier(),
+                enable_instruction_trace_account_data_recording: bank
+                    .get_transaction_processor()
+                    .instruction_trace_notifier_account_data_enabled(),
+                ..ExecutionRecordingConfig::new_single_setting(transaction_status_sender.is_some())
+            },
             timings,
             log_messages_bytes_limit,
             pre_commit_callback,
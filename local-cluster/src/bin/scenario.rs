@@ -0,0 +1,57 @@
+//! Runs declarative local cluster scenarios, see `solana_local_cluster::scenario`.
+
+use {
+    clap::{crate_description, App, Arg},
+    log::*,
+    solana_local_cluster::scenario::Scenario,
+    solana_streamer::socket::SocketAddrSpace,
+    std::{path::PathBuf, process::exit},
+};
+
+fn main() {
+    solana_logger::setup_with_default("solana=info");
+
+    let matches = App::new("agave-local-cluster-scenario")
+        .about(crate_description!())
+        .version(solana_version::version!())
+        .arg(
+            Arg::with_name("scenario")
+                .value_name("FILE")
+                .takes_value(true)
+                .multiple(true)
+                .required(true)
+                .help("Scenario files to run, in TOML (.toml) or YAML (.yaml, .yml) format"),
+        )
+        .arg(
+            Arg::with_name("check")
+                .long("check")
+                .takes_value(false)
+                .help("Only parse and validate the scenarios, without running them"),
+        )
+        .get_matches();
+
+    let check_only = matches.is_present("check");
+    let mut failed = vec![];
+    for path in matches.values_of("scenario").unwrap().map(PathBuf::from) {
+        let result = Scenario::from_path(&path).and_then(|scenario| {
+            if check_only {
+                Ok(())
+            } else {
+                info!("running scenario {} from {}", scenario.name, path.display());
+                scenario.run(SocketAddrSpace::Unspecified)
+            }
+        });
+        match result {
+            Ok(()) => println!("PASS {}", path.display()),
+            Err(err) => {
+                println!("FAIL {}: {err}", path.display());
+                failed.push(path);
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        eprintln!("{} scenario(s) failed", failed.len());
+        exit(1);
+    }
+}
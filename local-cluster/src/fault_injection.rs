@@ -0,0 +1,389 @@
+//! Network faults between the validators of a [`LocalCluster`].
+//!
+//! All validators of a local cluster share one process, so faults are
+//! injected into the streamer receive path instead of the kernel, see
+//! [`solana_streamer::fault_injection`]. A [`FaultSchedule`] is a list of
+//! [`FaultRule`]s, each applying a [`LinkFault`] to the packets sent from one
+//! set of validators to another over some of their gossip, turbine, repair and
+//! TPU sockets during a window of time. Rules are directional, so asymmetric
+//! partitions are a single rule.
+//!
+//! Every link, a pair of validators and a socket kind, draws its faults from
+//! its own random number generator seeded from the schedule seed, so the n-th
+//! packet on a link meets the same fate on every run with the same seed. The
+//! order in which packets arrive on different links still depends on thread
+//! scheduling.
+//!
+//! Limitations:
+//! * Only UDP traffic is affected. Votes and transactions sent to the TPU
+//!   over QUIC are not, so TPU faults need a cluster that uses UDP for TPU.
+//! * The fault injector is global to the process. Only one cluster per
+//!   process may install a schedule at a time.
+//!
+//! [`LocalCluster`]: crate::local_cluster::LocalCluster
+
+use {
+    rand::{Rng, SeedableRng},
+    rand_chacha::ChaChaRng,
+    solana_gossip::cluster_info::Node,
+    solana_pubkey::Pubkey,
+    solana_sha256_hasher::hashv,
+    solana_streamer::fault_injection::{
+        set_packet_fault_injector, PacketFate, PacketFaultInjector,
+    },
+    std::{
+        collections::HashMap,
+        net::{SocketAddr, UdpSocket},
+        sync::{Arc, Mutex, RwLock},
+        time::{Duration, Instant},
+    },
+};
+
+/// Kinds of validator sockets faults can be applied to. The kind of a link is
+/// the kind of its receiving socket.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SocketKind {
+    Gossip,
+    /// Shreds received from the leader or retransmitted by other validators.
+    Turbine,
+    /// Repair requests and responses, including ancestor hashes repair.
+    Repair,
+    /// Transactions and votes received over UDP.
+    Tpu,
+}
+
+/// Faults applied to every packet on a link.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinkFault {
+    /// Probability that a packet is dropped.
+    pub drop_rate: f64,
+    /// Fixed delay added to every delivered packet.
+    pub delay: Duration,
+    /// Upper bound of a random delay added on top of `delay`. Packets with
+    /// different delays are reordered.
+    pub jitter: Duration,
+    /// Probability that a delivered packet is delivered twice.
+    pub duplicate_rate: f64,
+}
+
+impl Default for LinkFault {
+    fn default() -> Self {
+        Self {
+            drop_rate: 0.0,
+            delay: Duration::ZERO,
+            jitter: Duration::ZERO,
+            duplicate_rate: 0.0,
+        }
+    }
+}
+
+impl LinkFault {
+    /// Drop every packet.
+    pub fn partition() -> Self {
+        Self {
+            drop_rate: 1.0,
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FaultRule {
+    /// Senders the rule applies to; any sender, including clients that are
+    /// not validators of the cluster, if empty.
+    pub from: Vec<Pubkey>,
+    /// Receivers the rule applies to; any validator if empty.
+    pub to: Vec<Pubkey>,
+    /// Socket kinds the rule applies to; all of them if empty.
+    pub sockets: Vec<SocketKind>,
+    /// When the rule becomes active, relative to when the schedule is set.
+    pub start: Duration,
+    /// When the rule stops being active; never if `None`.
+    pub end: Option<Duration>,
+    pub fault: LinkFault,
+}
+
+impl FaultRule {
+    fn is_active(&self, elapsed: Duration) -> bool {
+        self.start <= elapsed && self.end.is_none_or(|end| elapsed < end)
+    }
+
+    fn matches(&self, from: Option<&Pubkey>, to: &Pubkey, kind: SocketKind) -> bool {
+        (self.from.is_empty() || from.is_some_and(|from| self.from.contains(from)))
+            && (self.to.is_empty() || self.to.contains(to))
+            && (self.sockets.is_empty() || self.sockets.contains(&kind))
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FaultSchedule {
+    pub seed: u64,
+    /// The first active rule matching a packet applies to it.
+    pub rules: Vec<FaultRule>,
+}
+
+/// Link identity used to seed its random number generator.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct Link {
+    rule: usize,
+    from: Option<Pubkey>,
+    to: Pubkey,
+    kind: SocketKind,
+}
+
+struct ActiveSchedule {
+    schedule: FaultSchedule,
+    start: Instant,
+    rngs: Mutex<HashMap<Link, ChaChaRng>>,
+}
+
+impl ActiveSchedule {
+    fn rng(schedule: &FaultSchedule, link: &Link) -> ChaChaRng {
+        let hash = hashv(&[
+            &schedule.seed.to_le_bytes(),
+            &(link.rule as u64).to_le_bytes(),
+            link.from.unwrap_or_default().as_ref(),
+            link.to.as_ref(),
+            &[link.kind as u8],
+        ]);
+        ChaChaRng::from_seed(hash.to_bytes())
+    }
+
+    fn packet_fate(&self, from: Option<&Pubkey>, to: &Pubkey, kind: SocketKind) -> PacketFate {
+        let elapsed = self.start.elapsed();
+        let Some((rule, fault)) = self
+            .schedule
+            .rules
+            .iter()
+            .enumerate()
+            .find(|(_, rule)| rule.is_active(elapsed) && rule.matches(from, to, kind))
+            .map(|(index, rule)| (index, rule.fault))
+        else {
+            return PacketFate::UNCHANGED;
+        };
+
+        let link = Link {
+            rule,
+            from: from.copied(),
+            to: *to,
+            kind,
+        };
+        let mut rngs = self.rngs.lock().unwrap();
+        let rng = rngs
+            .entry(link)
+            .or_insert_with(|| Self::rng(&self.schedule, &link));
+        if rng.gen_bool(fault.drop_rate.clamp(0.0, 1.0)) {
+            return PacketFate::Drop;
+        }
+        let jitter = fault.jitter.mul_f64(rng.gen::<f64>());
+        let copies = if rng.gen_bool(fault.duplicate_rate.clamp(0.0, 1.0)) {
+            2
+        } else {
+            1
+        };
+        PacketFate::Deliver {
+            delay: fault.delay + jitter,
+            copies,
+        }
+    }
+}
+
+/// Maps the sockets of the validators of a cluster to their owner and applies
+/// the installed [`FaultSchedule`] to packets between them.
+#[derive(Default)]
+pub struct NetworkFaults {
+    sockets: RwLock<HashMap<SocketAddr, (Pubkey, SocketKind)>>,
+    schedule: RwLock<Option<ActiveSchedule>>,
+}
+
+impl NetworkFaults {
+    /// Register the sockets of `node`, replacing those of a previous node
+    /// with the same identity.
+    pub(crate) fn register_node(&self, node: &Node) {
+        let pubkey = *node.info.pubkey();
+        let sockets = &node.sockets;
+        let gossip = [sockets.gossip.local_addr()];
+        let turbine = sockets
+            .tvu
+            .iter()
+            .chain(&sockets.broadcast)
+            .chain(&sockets.retransmit_sockets)
+            .map(UdpSocket::local_addr);
+        let repair = [
+            &sockets.repair,
+            &sockets.serve_repair,
+            &sockets.ancestor_hashes_requests,
+        ]
+        .into_iter()
+        .map(UdpSocket::local_addr);
+        let tpu = sockets
+            .tpu
+            .iter()
+            .chain(&sockets.tpu_forwards)
+            .chain(&sockets.tpu_vote)
+            .chain([
+                &sockets.tpu_vote_forwarding_client,
+                &sockets.tpu_transaction_forwarding_client,
+                &sockets.rpc_sts_client,
+            ])
+            .map(UdpSocket::local_addr);
+
+        let mut registry = self.sockets.write().unwrap();
+        registry.retain(|_, (owner, _)| *owner != pubkey);
+        let kinds = gossip
+            .into_iter()
+            .map(|addr| (addr, SocketKind::Gossip))
+            .chain(turbine.map(|addr| (addr, SocketKind::Turbine)))
+            .chain(repair.map(|addr| (addr, SocketKind::Repair)))
+            .chain(tpu.map(|addr| (addr, SocketKind::Tpu)));
+        for (addr, kind) in kinds {
+            if let Ok(addr) = addr {
+                registry.insert(addr, (pubkey, kind));
+            }
+        }
+    }
+
+    /// Install `schedule` for this cluster, replacing the current one. Rule
+    /// windows are relative to now.
+    pub fn set_schedule(self: &Arc<Self>, schedule: FaultSchedule) {
+        *self.schedule.write().unwrap() = Some(ActiveSchedule {
+            schedule,
+            start: Instant::now(),
+            rngs: Mutex::default(),
+        });
+        set_packet_fault_injector(Some(self.clone()));
+    }
+
+    /// Remove the current schedule, healing every link.
+    pub fn clear(&self) {
+        if self.schedule.write().unwrap().take().is_some() {
+            set_packet_fault_injector(None);
+        }
+    }
+
+    fn lookup(&self, addr: &SocketAddr) -> Option<(Pubkey, SocketKind)> {
+        self.sockets.read().unwrap().get(addr).copied()
+    }
+}
+
+impl PacketFaultInjector for NetworkFaults {
+    fn packet_fate(&self, from: SocketAddr, to: SocketAddr) -> PacketFate {
+        let schedule = self.schedule.read().unwrap();
+        let Some(schedule) = schedule.as_ref() else {
+            return PacketFate::UNCHANGED;
+        };
+        let Some((to, kind)) = self.lookup(&to) else {
+            return PacketFate::UNCHANGED;
+        };
+        let from = self.lookup(&from).map(|(from, _)| from);
+        schedule.packet_fate(from.as_ref(), &to, kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fates(
+        schedule: &ActiveSchedule,
+        from: &Pubkey,
+        to: &Pubkey,
+        kind: SocketKind,
+    ) -> Vec<PacketFate> {
+        (0..64)
+            .map(|_| schedule.packet_fate(Some(from), to, kind))
+            .collect()
+    }
+
+    fn activate(schedule: FaultSchedule) -> ActiveSchedule {
+        ActiveSchedule {
+            schedule,
+            start: Instant::now(),
+            rngs: Mutex::default(),
+        }
+    }
+
+    #[test]
+    fn test_fault_rules() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let schedule = activate(FaultSchedule {
+            seed: 0,
+            rules: vec![
+                FaultRule {
+                    from: vec![a],
+                    to: vec![b],
+                    sockets: vec![SocketKind::Gossip],
+                    fault: LinkFault::partition(),
+                    ..FaultRule::default()
+                },
+                FaultRule {
+                    fault: LinkFault {
+                        delay: Duration::from_millis(10),
+                        ..LinkFault::default()
+                    },
+                    ..FaultRule::default()
+                },
+                FaultRule {
+                    start: Duration::from_secs(3600),
+                    fault: LinkFault::partition(),
+                    ..FaultRule::default()
+                },
+            ],
+        });
+
+        // The partition is one-directional and only applies to gossip.
+        assert_eq!(
+            schedule.packet_fate(Some(&a), &b, SocketKind::Gossip),
+            PacketFate::Drop
+        );
+        let delayed = PacketFate::Deliver {
+            delay: Duration::from_millis(10),
+            copies: 1,
+        };
+        assert_eq!(
+            schedule.packet_fate(Some(&b), &a, SocketKind::Gossip),
+            delayed
+        );
+        assert_eq!(
+            schedule.packet_fate(Some(&a), &b, SocketKind::Turbine),
+            delayed
+        );
+        // Senders outside the cluster only match rules for any sender.
+        assert_eq!(schedule.packet_fate(None, &b, SocketKind::Gossip), delayed);
+    }
+
+    #[test]
+    fn test_fault_schedule_is_deterministic() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let schedule = |seed| {
+            activate(FaultSchedule {
+                seed,
+                rules: vec![FaultRule {
+                    fault: LinkFault {
+                        drop_rate: 0.3,
+                        jitter: Duration::from_millis(100),
+                        duplicate_rate: 0.3,
+                        ..LinkFault::default()
+                    },
+                    ..FaultRule::default()
+                }],
+            })
+        };
+
+        let first = schedule(7);
+        let second = schedule(7);
+        // Interleaving packets on another link does not change the fates on
+        // this one.
+        let _ = fates(&second, &b, &a, SocketKind::Repair);
+        let expected = fates(&first, &a, &b, SocketKind::Repair);
+        assert_eq!(fates(&second, &a, &b, SocketKind::Repair), expected);
+        assert!(expected.contains(&PacketFate::Drop));
+        assert!(expected
+            .iter()
+            .any(|fate| matches!(fate, PacketFate::Deliver { copies: 2, .. })));
+
+        assert_ne!(fates(&schedule(8), &a, &b, SocketKind::Repair), expected);
+    }
+}
@@ -2,6 +2,7 @@ use {
     crate::{
         cluster::{Cluster, ClusterValidatorInfo, QuicTpuClient, ValidatorInfo},
         cluster_tests,
+        fault_injection::NetworkFaults,
         integration_tests::DEFAULT_NODE_STAKE,
         validator_configs::*,
     },
@@ -33,8 +34,8 @@ use {
     solana_rpc_client::rpc_client::RpcClient,
     solana_runtime::{
         genesis_utils::{
-            create_genesis_config_with_vote_accounts_and_cluster_type, GenesisConfigInfo,
-            ValidatorVoteKeypairs,
+            create_genesis_config_with_vote_accounts_and_cluster_type, deactivate_features,
+            GenesisConfigInfo, ValidatorVoteKeypairs,
         },
         snapshot_config::SnapshotConfig,
     },
@@ -95,6 +96,8 @@ pub struct ClusterConfig {
     pub cluster_type: ClusterType,
     pub poh_config: PohConfig,
     pub additional_accounts: Vec<(Pubkey, AccountSharedData)>,
+    /// Features that are not activated in genesis
+    pub features_to_deactivate: Vec<Pubkey>,
     pub tpu_use_quic: bool,
     pub tpu_connection_pool_size: usize,
     pub vote_use_quic: bool,
@@ -135,6 +138,7 @@ impl Default for ClusterConfig {
             poh_config: PohConfig::default(),
             skip_warmup_slots: false,
             additional_accounts: vec![],
+            features_to_deactivate: vec![],
             tpu_use_quic: DEFAULT_TPU_USE_QUIC,
             tpu_connection_pool_size: DEFAULT_TPU_CONNECTION_POOL_SIZE,
             vote_use_quic: DEFAULT_VOTE_USE_QUIC,
@@ -158,6 +162,7 @@ pub struct LocalCluster {
     quic_connection_cache_config: Option<QuicConnectionCacheConfig>,
     tpu_connection_pool_size: usize,
     shred_version: u16,
+    network_faults: Arc<NetworkFaults>,
 }
 
 impl LocalCluster {
@@ -294,6 +299,8 @@ impl LocalCluster {
         let leader_vote_keypair = &keys_in_genesis[0].vote_keypair;
         let leader_pubkey = leader_keypair.pubkey();
         let leader_node = Node::new_localhost_with_pubkey(&leader_pubkey);
+        let network_faults = Arc::new(NetworkFaults::default());
+        network_faults.register_node(&leader_node);
 
         let GenesisConfigInfo {
             mut genesis_config,
@@ -311,6 +318,7 @@ impl LocalCluster {
                 .drain(..)
                 .map(|(key, account)| (key, Account::from(account))),
         );
+        deactivate_features(&mut genesis_config, &config.features_to_deactivate);
         genesis_config.ticks_per_slot = config.ticks_per_slot;
         genesis_config.epoch_schedule = EpochSchedule::custom(
             config.slots_per_epoch,
@@ -380,6 +388,7 @@ impl LocalCluster {
             quic_connection_cache_config,
             tpu_connection_pool_size: config.tpu_connection_pool_size,
             shred_version: leader_contact_info.shred_version(),
+            network_faults,
         };
 
         let node_pubkey_to_vote_key: HashMap<Pubkey, Arc<Keypair>> = keys_in_genesis
@@ -441,6 +450,12 @@ impl LocalCluster {
         self.shred_version = shred_version;
     }
 
+    /// Faults injected into the network between the validators of this
+    /// cluster, including validators added or restarted later.
+    pub fn network_faults(&self) -> &Arc<NetworkFaults> {
+        &self.network_faults
+    }
+
     pub fn exit(&mut self) {
         for node in self.validators.values_mut() {
             if let Some(ref mut v) = node.validator {
@@ -516,6 +531,7 @@ impl LocalCluster {
         }
         let validator_pubkey = validator_keypair.pubkey();
         let validator_node = Node::new_localhost_with_pubkey(&validator_keypair.pubkey());
+        self.network_faults.register_node(&validator_node);
         let contact_info = validator_node.info.clone();
         let (ledger_path, _blockhash) = create_new_tmp_ledger_with_size!(
             &self.genesis_config,
@@ -1029,6 +1045,7 @@ impl Cluster for LocalCluster {
         // Update the stored ContactInfo for this node
         let mut node = Node::new_localhost_with_pubkey(pubkey);
         node.info.set_shred_version(self.shred_version());
+        self.network_faults.register_node(&node);
         cluster_validator_info.info.contact_info = node.info.clone();
         cluster_validator_info.config.rpc_addrs =
             Some((node.info.rpc().unwrap(), node.info.rpc_pubsub().unwrap()));
@@ -1152,6 +1169,7 @@ impl Cluster for LocalCluster {
 
 impl Drop for LocalCluster {
     fn drop(&mut self) {
+        self.network_faults.clear();
         self.close();
     }
 }
@@ -0,0 +1,860 @@
+//! Declarative local cluster scenarios.
+//!
+//! A scenario file describes a cluster (validators, stakes, features) and an
+//! ordered list of steps to run against it: network partitions, validator
+//! stops and restarts, leaders producing duplicate blocks, and assertions
+//! about the cluster's progress. Scenarios
+//! are written in TOML or YAML, chosen by the file extension, and are run by
+//! the `agave-local-cluster-scenario` binary or [`Scenario::run`].
+//!
+//! ```toml
+//! name = "minority-partition"
+//! slots_per_epoch = 64
+//!
+//! [[validators]]
+//! name = "a"
+//! stake = 10_000_000_000
+//!
+//! [[validators]]
+//! name = "b"
+//! stake = 10_000_000_000
+//!
+//! [[validators]]
+//! name = "c"
+//! stake = 1_000_000_000
+//!
+//! [[steps]]
+//! action = "assert_roots_advance"
+//! min_new_roots = 4
+//!
+//! [[steps]]
+//! action = "partition"
+//! validators = ["c"]
+//!
+//! [[steps]]
+//! action = "heal"
+//! at_slot = 80
+//!
+//! [[steps]]
+//! action = "assert_no_fork_divergence"
+//! ```
+//!
+//! Steps run in order. A step with `at_slot` first waits until the cluster
+//! has processed that slot. Validator identities are derived from the
+//! scenario and validator names, so the leader schedule of a scenario is the
+//! same on every run.
+//!
+//! Partitions drop the packets between validators with the cluster's
+//! [`NetworkFaults`], which only affect UDP traffic.
+//!
+//! [`NetworkFaults`]: crate::fault_injection::NetworkFaults
+
+use {
+    crate::{
+        cluster::{Cluster, ClusterValidatorInfo},
+        fault_injection::{FaultRule, FaultSchedule, LinkFault},
+        local_cluster::{ClusterConfig, LocalCluster},
+        validator_configs::make_identical_validator_configs,
+    },
+    agave_feature_set::FEATURE_NAMES,
+    crossbeam_channel::unbounded,
+    log::*,
+    serde_derive::Deserialize,
+    solana_clock::{Slot, DEFAULT_MS_PER_SLOT},
+    solana_commitment_config::CommitmentConfig,
+    solana_core::validator::ValidatorConfig,
+    solana_keypair::{keypair_from_seed, Keypair},
+    solana_pubkey::Pubkey,
+    solana_rpc_client::rpc_client::RpcClient,
+    solana_sha256_hasher::hashv,
+    solana_signer::Signer,
+    solana_streamer::socket::SocketAddrSpace,
+    solana_turbine::broadcast_stage::{
+        broadcast_duplicates_run::{BroadcastDuplicatesConfig, ClusterPartition},
+        BroadcastStageType,
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        fs, io,
+        path::Path,
+        str::FromStr,
+        sync::Arc,
+        thread::sleep,
+        time::{Duration, Instant},
+    },
+    thiserror::Error,
+};
+
+const DEFAULT_STEP_TIMEOUT_SECS: u64 = 180;
+
+#[derive(Debug, Error)]
+pub enum ScenarioError {
+    #[error("failed to read scenario: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to parse scenario: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("failed to parse scenario: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("unsupported scenario file extension, expected .toml, .yaml or .yml")]
+    UnsupportedFormat,
+    #[error("invalid scenario: {0}")]
+    Invalid(String),
+    #[error("step {step} ({action}) timed out: {reason}")]
+    Timeout {
+        step: usize,
+        action: &'static str,
+        reason: String,
+    },
+    #[error("step {step} ({action}) failed: {reason}")]
+    AssertionFailed {
+        step: usize,
+        action: &'static str,
+        reason: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: String,
+    pub validators: Vec<ValidatorSpec>,
+    #[serde(default)]
+    pub features: FeatureSpec,
+    pub slots_per_epoch: Option<u64>,
+    pub ticks_per_slot: Option<u64>,
+    /// Timeout for steps that do not set their own, in seconds.
+    pub default_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorSpec {
+    pub name: String,
+    /// Stake delegated to the validator in genesis, in lamports.
+    pub stake: u64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeatureSpec {
+    /// Feature ids left inactive in genesis. All other features are active.
+    #[serde(default)]
+    pub deactivate: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Step {
+    /// Wait until the cluster has processed this slot before running the step.
+    pub at_slot: Option<Slot>,
+    pub timeout_secs: Option<u64>,
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Action {
+    /// Isolate `validators` from the rest of the cluster by dropping all
+    /// packets between them, in both directions, until the next `heal`.
+    Partition { validators: Vec<String> },
+    /// Undo every active partition.
+    Heal,
+    /// Restart `validator` as a malicious leader that, for the rest of the
+    /// scenario, sends a different version of some of its blocks to the
+    /// validators in `to`, and wait until it has produced one.
+    DuplicateBlocks { validator: String, to: Vec<String> },
+    /// Shut down a validator, keeping its ledger.
+    Stop { validator: String },
+    /// Start a validator stopped by `stop`.
+    Start { validator: String },
+    /// Shut down a validator and start it again from its ledger.
+    Restart { validator: String },
+    /// Wait for the cluster to process `slots` more slots.
+    Wait { slots: u64 },
+    /// The root of every running validator advances by at least
+    /// `min_new_roots` slots.
+    AssertRootsAdvance { min_new_roots: u64 },
+    /// Every running validator has rooted `slot` or a later slot.
+    AssertSlotFinalized { slot: Slot },
+    /// The running validators agree on the rooted slots up to the lowest root
+    /// among them.
+    AssertNoForkDivergence,
+}
+
+impl Action {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Partition { .. } => "partition",
+            Self::Heal => "heal",
+            Self::DuplicateBlocks { .. } => "duplicate_blocks",
+            Self::Stop { .. } => "stop",
+            Self::Start { .. } => "start",
+            Self::Restart { .. } => "restart",
+            Self::Wait { .. } => "wait",
+            Self::AssertRootsAdvance { .. } => "assert_roots_advance",
+            Self::AssertSlotFinalized { .. } => "assert_slot_finalized",
+            Self::AssertNoForkDivergence => "assert_no_fork_divergence",
+        }
+    }
+
+    fn validators(&self) -> Vec<&String> {
+        match self {
+            Self::Partition { validators } => validators.iter().collect(),
+            Self::Stop { validator } | Self::Start { validator } | Self::Restart { validator } => {
+                vec![validator]
+            }
+            Self::DuplicateBlocks { validator, to } => {
+                std::iter::once(validator).chain(to).collect()
+            }
+            _ => vec![],
+        }
+    }
+}
+
+impl Scenario {
+    /// Read a scenario from a `.toml`, `.yaml` or `.yml` file.
+    pub fn from_path(path: &Path) -> Result<Self, ScenarioError> {
+        let contents = fs::read_to_string(path)?;
+        let scenario: Self = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&contents)?,
+            Some("yaml" | "yml") => serde_yaml::from_str(&contents)?,
+            _ => return Err(ScenarioError::UnsupportedFormat),
+        };
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Check that the scenario only refers to validators and features that
+    /// exist.
+    pub fn validate(&self) -> Result<(), ScenarioError> {
+        if self.validators.is_empty() {
+            return Err(ScenarioError::Invalid("no validators".to_string()));
+        }
+        let mut names = HashSet::new();
+        for validator in &self.validators {
+            if !names.insert(&validator.name) {
+                return Err(ScenarioError::Invalid(format!(
+                    "duplicate validator name {}",
+                    validator.name
+                )));
+            }
+            if validator.stake == 0 {
+                return Err(ScenarioError::Invalid(format!(
+                    "validator {} has no stake",
+                    validator.name
+                )));
+            }
+        }
+        self.features_to_deactivate()?;
+        for (index, step) in self.steps.iter().enumerate() {
+            if let Some(name) = step
+                .action
+                .validators()
+                .into_iter()
+                .find(|name| !names.contains(name))
+            {
+                return Err(ScenarioError::Invalid(format!(
+                    "step {index} refers to unknown validator {name}"
+                )));
+            }
+            if let Action::DuplicateBlocks { validator, to } = &step.action {
+                if to.is_empty() || to.contains(validator) {
+                    return Err(ScenarioError::Invalid(format!(
+                        "step {index} must send the duplicate blocks of {validator} to other \
+                         validators"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn features_to_deactivate(&self) -> Result<Vec<Pubkey>, ScenarioError> {
+        self.features
+            .deactivate
+            .iter()
+            .map(|feature| {
+                Pubkey::from_str(feature)
+                    .ok()
+                    .filter(|feature_id| FEATURE_NAMES.contains_key(feature_id))
+                    .ok_or_else(|| ScenarioError::Invalid(format!("unknown feature {feature}")))
+            })
+            .collect()
+    }
+
+    fn keypair(&self, validator: &str, role: &str) -> Keypair {
+        let seed = hashv(&[self.name.as_bytes(), validator.as_bytes(), role.as_bytes()]);
+        keypair_from_seed(seed.as_ref()).unwrap()
+    }
+
+    /// Start the cluster and run every step in order. The cluster is shut
+    /// down when the first step fails.
+    pub fn run(&self, socket_addr_space: SocketAddrSpace) -> Result<(), ScenarioError> {
+        self.validate()?;
+        let mut runner = ScenarioRunner::new(self, socket_addr_space)?;
+        let result = self
+            .steps
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, step)| runner.run_step(index, step));
+        runner.cluster.exit();
+        result
+    }
+}
+
+struct ScenarioRunner<'a> {
+    scenario: &'a Scenario,
+    cluster: LocalCluster,
+    socket_addr_space: SocketAddrSpace,
+    identities: HashMap<String, Pubkey>,
+    /// Validators isolated by each active partition.
+    partitions: Vec<Vec<Pubkey>>,
+    stopped: HashMap<String, ClusterValidatorInfo>,
+}
+
+impl<'a> ScenarioRunner<'a> {
+    fn new(
+        scenario: &'a Scenario,
+        socket_addr_space: SocketAddrSpace,
+    ) -> Result<Self, ScenarioError> {
+        let num_validators = scenario.validators.len();
+        let validator_configs =
+            make_identical_validator_configs(&ValidatorConfig::default_for_test(), num_validators);
+        let validator_keys: Vec<_> = scenario
+            .validators
+            .iter()
+            .map(|validator| {
+                (
+                    Arc::new(scenario.keypair(&validator.name, "identity")),
+                    true,
+                )
+            })
+            .collect();
+        let identities = scenario
+            .validators
+            .iter()
+            .zip(&validator_keys)
+            .map(|(validator, (keypair, _))| (validator.name.clone(), keypair.pubkey()))
+            .collect();
+
+        let mut config = ClusterConfig {
+            validator_configs,
+            validator_keys: Some(validator_keys),
+            node_vote_keys: Some(
+                scenario
+                    .validators
+                    .iter()
+                    .map(|validator| Arc::new(scenario.keypair(&validator.name, "vote")))
+                    .collect(),
+            ),
+            node_stakes: scenario
+                .validators
+                .iter()
+                .map(|validator| validator.stake)
+                .collect(),
+            features_to_deactivate: scenario.features_to_deactivate()?,
+            ..ClusterConfig::default()
+        };
+        if let Some(slots_per_epoch) = scenario.slots_per_epoch {
+            config.slots_per_epoch = slots_per_epoch;
+            config.stakers_slot_offset = slots_per_epoch;
+        }
+        if let Some(ticks_per_slot) = scenario.ticks_per_slot {
+            config.ticks_per_slot = ticks_per_slot;
+        }
+
+        info!(
+            "scenario {}: starting {num_validators} validators",
+            scenario.name
+        );
+        let cluster = LocalCluster::new(&mut config, socket_addr_space);
+        Ok(Self {
+            scenario,
+            cluster,
+            socket_addr_space,
+            identities,
+            partitions: Vec::new(),
+            stopped: HashMap::new(),
+        })
+    }
+
+    fn run_step(&mut self, index: usize, step: &Step) -> Result<(), ScenarioError> {
+        let action = step.action.name();
+        let timeout = Duration::from_secs(
+            step.timeout_secs
+                .or(self.scenario.default_timeout_secs)
+                .unwrap_or(DEFAULT_STEP_TIMEOUT_SECS),
+        );
+        let deadline = Instant::now() + timeout;
+        let timed_out = |reason: String| ScenarioError::Timeout {
+            step: index,
+            action,
+            reason,
+        };
+
+        if let Some(slot) = step.at_slot {
+            self.wait_for_slot(slot, deadline).map_err(|processed| {
+                timed_out(format!("waiting for slot {slot}, processed {processed}"))
+            })?;
+        }
+        info!("scenario {}: step {index} {action}", self.scenario.name);
+
+        match &step.action {
+            Action::Partition { validators } => {
+                self.partitions.push(
+                    validators
+                        .iter()
+                        .map(|validator| self.identities[validator])
+                        .collect(),
+                );
+                let identities: Vec<_> = self.identities.values().copied().collect();
+                self.cluster
+                    .network_faults()
+                    .set_schedule(partition_schedule(&self.partitions, &identities));
+            }
+            Action::Heal => {
+                self.partitions.clear();
+                self.cluster.network_faults().clear();
+            }
+            Action::DuplicateBlocks { validator, to } => {
+                let pubkey = self.running_identity(index, action, validator)?;
+                let mut info = self.cluster.exit_node(&pubkey);
+                let (duplicate_slot_sender, duplicate_slot_receiver) = unbounded();
+                info.config.broadcast_stage_type =
+                    BroadcastStageType::BroadcastDuplicates(BroadcastDuplicatesConfig {
+                        partition: ClusterPartition::Pubkey(
+                            to.iter().map(|name| self.identities[name]).collect(),
+                        ),
+                        duplicate_slot_sender: Some(duplicate_slot_sender),
+                    });
+                self.cluster
+                    .restart_node(&pubkey, info, self.socket_addr_space);
+                let slot = duplicate_slot_receiver
+                    .recv_deadline(deadline)
+                    .map_err(|_| {
+                        timed_out(format!(
+                            "waiting for {validator} to produce a duplicate block"
+                        ))
+                    })?;
+                info!(
+                    "scenario {}: {validator} produced a duplicate block in slot {slot}",
+                    self.scenario.name
+                );
+            }
+            Action::Stop { validator } => {
+                let pubkey = self.running_identity(index, action, validator)?;
+                let info = self.cluster.exit_node(&pubkey);
+                self.stopped.insert(validator.clone(), info);
+            }
+            Action::Start { validator } => {
+                let info = self.stopped.remove(validator).ok_or_else(|| {
+                    ScenarioError::AssertionFailed {
+                        step: index,
+                        action,
+                        reason: format!("validator {validator} is not stopped"),
+                    }
+                })?;
+                let pubkey = self.identities[validator];
+                self.cluster
+                    .restart_node(&pubkey, info, self.socket_addr_space);
+            }
+            Action::Restart { validator } => {
+                let pubkey = self.running_identity(index, action, validator)?;
+                let info = self.cluster.exit_node(&pubkey);
+                self.cluster
+                    .restart_node(&pubkey, info, self.socket_addr_space);
+            }
+            Action::Wait { slots } => {
+                let target = self.processed_slot() + slots;
+                self.wait_for_slot(target, deadline).map_err(|processed| {
+                    timed_out(format!("waiting for slot {target}, processed {processed}"))
+                })?;
+            }
+            Action::AssertRootsAdvance { min_new_roots } => {
+                let start_roots = self.finalized_slots();
+                loop {
+                    let roots = self.finalized_slots();
+                    let lagging: Vec<_> = roots
+                        .iter()
+                        .filter(|(name, root)| {
+                            let start_root = start_roots.get(*name).copied().unwrap_or(0);
+                            **root < start_root + *min_new_roots
+                        })
+                        .collect();
+                    if lagging.is_empty() {
+                        break;
+                    }
+                    if Instant::now() > deadline {
+                        return Err(timed_out(format!(
+                            "roots did not advance by {min_new_roots}: started at \
+                             {start_roots:?}, now {roots:?}"
+                        )));
+                    }
+                    sleep(Duration::from_millis(DEFAULT_MS_PER_SLOT));
+                }
+            }
+            Action::AssertSlotFinalized { slot } => loop {
+                let roots = self.finalized_slots();
+                if roots.values().all(|root| root >= slot) {
+                    break;
+                }
+                if Instant::now() > deadline {
+                    return Err(timed_out(format!(
+                        "slot {slot} was not rooted on every validator, roots: {roots:?}"
+                    )));
+                }
+                sleep(Duration::from_millis(DEFAULT_MS_PER_SLOT));
+            },
+            Action::AssertNoForkDivergence => self.check_no_fork_divergence(index, action)?,
+        }
+        Ok(())
+    }
+
+    fn running_identity(
+        &self,
+        index: usize,
+        action: &'static str,
+        validator: &str,
+    ) -> Result<Pubkey, ScenarioError> {
+        let pubkey = self.identities[validator];
+        if self.cluster.validators.contains_key(&pubkey) {
+            Ok(pubkey)
+        } else {
+            Err(ScenarioError::AssertionFailed {
+                step: index,
+                action,
+                reason: format!("validator {validator} is not running"),
+            })
+        }
+    }
+
+    fn rpc_clients(&self) -> Vec<(&str, RpcClient)> {
+        self.identities
+            .iter()
+            .filter_map(|(name, pubkey)| {
+                let rpc_addr = self
+                    .cluster
+                    .validators
+                    .get(pubkey)?
+                    .info
+                    .contact_info
+                    .rpc()?;
+                Some((name.as_str(), RpcClient::new_socket(rpc_addr)))
+            })
+            .collect()
+    }
+
+    /// Highest slot processed by any running validator.
+    fn processed_slot(&self) -> Slot {
+        self.rpc_clients()
+            .iter()
+            .filter_map(|(_, client)| {
+                client
+                    .get_slot_with_commitment(CommitmentConfig::processed())
+                    .ok()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Latest root of every running validator, or 0 if it cannot be queried.
+    fn finalized_slots(&self) -> HashMap<String, Slot> {
+        self.rpc_clients()
+            .iter()
+            .map(|(name, client)| {
+                let root = client
+                    .get_slot_with_commitment(CommitmentConfig::finalized())
+                    .unwrap_or(0);
+                (name.to_string(), root)
+            })
+            .collect()
+    }
+
+    /// Wait until any running validator has processed `slot`. Returns the
+    /// highest processed slot on timeout.
+    fn wait_for_slot(&self, slot: Slot, deadline: Instant) -> Result<(), Slot> {
+        loop {
+            let processed = self.processed_slot();
+            if processed >= slot {
+                return Ok(());
+            }
+            if Instant::now() > deadline {
+                return Err(processed);
+            }
+            sleep(Duration::from_millis(DEFAULT_MS_PER_SLOT / 2));
+        }
+    }
+
+    fn check_no_fork_divergence(
+        &self,
+        index: usize,
+        action: &'static str,
+    ) -> Result<(), ScenarioError> {
+        let failed = |reason: String| ScenarioError::AssertionFailed {
+            step: index,
+            action,
+            reason,
+        };
+        let clients = self.rpc_clients();
+        let min_root = self.finalized_slots().into_values().min().unwrap_or(0);
+        let mut expected: Option<(&str, Vec<Slot>)> = None;
+        for (name, client) in &clients {
+            let rooted_slots = client
+                .get_blocks_with_commitment(0, Some(min_root), CommitmentConfig::finalized())
+                .map_err(|err| failed(format!("failed to get roots of {name}: {err}")))?;
+            match &expected {
+                None => expected = Some((name, rooted_slots)),
+                Some((expected_name, expected_slots)) => {
+                    if *expected_slots != rooted_slots {
+                        let common = expected_slots
+                            .iter()
+                            .zip(&rooted_slots)
+                            .take_while(|(a, b)| a == b)
+                            .count();
+                        return Err(failed(format!(
+                            "{expected_name} and {name} rooted different forks up to slot \
+                             {min_root}, diverging after {:?}: {:?} vs {:?}",
+                            common.checked_sub(1).map(|index| expected_slots[index]),
+                            expected_slots.get(common),
+                            rooted_slots.get(common),
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A schedule dropping every packet between the validators of each partition
+/// and the rest of `identities`.
+fn partition_schedule(partitions: &[Vec<Pubkey>], identities: &[Pubkey]) -> FaultSchedule {
+    let rules = partitions
+        .iter()
+        .flat_map(|partition| {
+            let rest: Vec<_> = identities
+                .iter()
+                .filter(|pubkey| !partition.contains(pubkey))
+                .copied()
+                .collect();
+            [
+                FaultRule {
+                    from: partition.clone(),
+                    to: rest.clone(),
+                    fault: LinkFault::partition(),
+                    ..FaultRule::default()
+                },
+                FaultRule {
+                    from: rest,
+                    to: partition.clone(),
+                    fault: LinkFault::partition(),
+                    ..FaultRule::default()
+                },
+            ]
+        })
+        .filter(|rule| !rule.from.is_empty() && !rule.to.is_empty())
+        .collect();
+    FaultSchedule { seed: 0, rules }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML_SCENARIO: &str = r#"
+name = "restart"
+slots_per_epoch = 32
+
+[[validators]]
+name = "a"
+stake = 10_000_000_000
+
+[[validators]]
+name = "b"
+stake = 10_000_000_000
+
+[[steps]]
+action = "restart"
+validator = "b"
+at_slot = 20
+timeout_secs = 60
+
+[[steps]]
+action = "assert_slot_finalized"
+slot = 40
+"#;
+
+    const YAML_SCENARIO: &str = r#"
+name: partition
+validators:
+  - name: a
+    stake: 10000000000
+  - name: b
+    stake: 1000000000
+steps:
+  - action: partition
+    validators: [b]
+  - action: wait
+    slots: 10
+  - action: heal
+  - action: assert_no_fork_divergence
+"#;
+
+    const DUPLICATE_BLOCKS_SCENARIO: &str = r#"
+name = "duplicate-blocks"
+
+[[validators]]
+name = "a"
+stake = 10_000_000_000
+
+[[validators]]
+name = "b"
+stake = 1_000_000_000
+
+[[steps]]
+action = "duplicate_blocks"
+validator = "a"
+to = ["b"]
+"#;
+
+    #[test]
+    fn test_parse_scenario() {
+        let scenario: Scenario = toml::from_str(TOML_SCENARIO).unwrap();
+        scenario.validate().unwrap();
+        assert_eq!(scenario.validators.len(), 2);
+        assert_eq!(scenario.slots_per_epoch, Some(32));
+        assert_eq!(scenario.steps.len(), 2);
+        assert_eq!(scenario.steps[0].at_slot, Some(20));
+        assert_eq!(scenario.steps[0].timeout_secs, Some(60));
+        assert!(matches!(
+            &scenario.steps[0].action,
+            Action::Restart { validator } if validator == "b"
+        ));
+        assert!(matches!(
+            scenario.steps[1].action,
+            Action::AssertSlotFinalized { slot: 40 }
+        ));
+
+        let scenario: Scenario = serde_yaml::from_str(YAML_SCENARIO).unwrap();
+        scenario.validate().unwrap();
+        assert_eq!(
+            scenario
+                .steps
+                .iter()
+                .map(|step| step.action.name())
+                .collect::<Vec<_>>(),
+            vec!["partition", "wait", "heal", "assert_no_fork_divergence"]
+        );
+    }
+
+    #[test]
+    fn test_validate_scenario() {
+        let mut scenario: Scenario = serde_yaml::from_str(YAML_SCENARIO).unwrap();
+        scenario.steps[0].action = Action::Stop {
+            validator: "c".to_string(),
+        };
+        assert!(matches!(
+            scenario.validate(),
+            Err(ScenarioError::Invalid(_))
+        ));
+
+        let mut scenario: Scenario = serde_yaml::from_str(YAML_SCENARIO).unwrap();
+        scenario.features.deactivate = vec![Pubkey::new_unique().to_string()];
+        assert!(matches!(
+            scenario.validate(),
+            Err(ScenarioError::Invalid(_))
+        ));
+
+        let mut scenario: Scenario = serde_yaml::from_str(YAML_SCENARIO).unwrap();
+        scenario.validators[1].name = "a".to_string();
+        assert!(matches!(
+            scenario.validate(),
+            Err(ScenarioError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_blocks_scenario() {
+        let scenario: Scenario = toml::from_str(DUPLICATE_BLOCKS_SCENARIO).unwrap();
+        scenario.validate().unwrap();
+        assert!(matches!(
+            &scenario.steps[0].action,
+            Action::DuplicateBlocks { validator, to } if validator == "a" && to == &["b"]
+        ));
+
+        // The duplicate blocks go to known validators other than the leader
+        for to in [vec![], vec!["a".to_string()], vec!["c".to_string()]] {
+            let mut scenario: Scenario = toml::from_str(DUPLICATE_BLOCKS_SCENARIO).unwrap();
+            scenario.steps[0].action = Action::DuplicateBlocks {
+                validator: "a".to_string(),
+                to,
+            };
+            assert!(matches!(
+                scenario.validate(),
+                Err(ScenarioError::Invalid(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_partition_schedule() {
+        let identities: Vec<_> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let [a, b, c, d] = identities.as_slice() else {
+            unreachable!()
+        };
+        let schedule = partition_schedule(&[vec![*a], vec![*b, *c]], &identities);
+        let links: Vec<_> = schedule
+            .rules
+            .iter()
+            .map(|rule| {
+                assert_eq!(rule.fault, LinkFault::partition());
+                assert!(rule.sockets.is_empty());
+                assert_eq!(rule.end, None);
+                (rule.from.clone(), rule.to.clone())
+            })
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                (vec![*a], vec![*b, *c, *d]),
+                (vec![*b, *c, *d], vec![*a]),
+                (vec![*b, *c], vec![*a, *d]),
+                (vec![*a, *d], vec![*b, *c]),
+            ]
+        );
+
+        // Partitioning every validator leaves no one to partition them from
+        assert!(partition_schedule(&[identities.clone()], &identities)
+            .rules
+            .is_empty());
+        assert!(partition_schedule(&[], &identities).rules.is_empty());
+    }
+
+    #[test]
+    fn test_bundled_scenarios() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios");
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            Scenario::from_path(&path).unwrap_or_else(|err| panic!("{}: {err}", path.display()));
+        }
+    }
+
+    #[test]
+    fn test_deterministic_identities() {
+        let scenario: Scenario = toml::from_str(TOML_SCENARIO).unwrap();
+        assert_eq!(
+            scenario.keypair("a", "identity").pubkey(),
+            scenario.keypair("a", "identity").pubkey()
+        );
+        assert_ne!(
+            scenario.keypair("a", "identity").pubkey(),
+            scenario.keypair("b", "identity").pubkey()
+        );
+        assert_ne!(
+            scenario.keypair("a", "identity").pubkey(),
+            scenario.keypair("a", "vote").pubkey()
+        );
+    }
+}
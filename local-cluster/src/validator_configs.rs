@@ -66,6 +66,9 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         block_production_method: config.block_production_method.clone(),
         transaction_struct: config.transaction_struct.clone(),
         enable_block_production_forwarding: config.enable_block_production_forwarding,
+        transaction_outcome_log_slots: config.transaction_outcome_log_slots,
+        external_scheduler_path: config.external_scheduler_path.clone(),
+        enable_bundles: config.enable_bundles,
         generator_config: config.generator_config.clone(),
         use_snapshot_archives_at_startup: config.use_snapshot_archives_at_startup,
         wen_restart_proto_path: config.wen_restart_proto_path.clone(),
@@ -80,6 +83,8 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         use_tpu_client_next: config.use_tpu_client_next,
         retransmit_xdp: config.retransmit_xdp.clone(),
         repair_handler_type: config.repair_handler_type.clone(),
+        compute_profile_path: config.compute_profile_path.clone(),
+        persistent_program_cache_path: config.persistent_program_cache_path.clone(),
     }
 }
 
@@ -55,6 +55,7 @@ use {
             ValidatorTestConfig, DEFAULT_NODE_STAKE, RUST_LOG_FILTER,
         },
         local_cluster::{ClusterConfig, LocalCluster, DEFAULT_MINT_LAMPORTS},
+        scenario::Scenario,
         validator_configs::*,
     },
     solana_poh_config::PohConfig,
@@ -367,6 +368,17 @@ fn test_forwarding() {
     );
 }
 
+#[test]
+#[serial]
+fn test_scenario_validator_restart() {
+    solana_logger::setup_with_default(RUST_LOG_FILTER);
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/validator-restart.toml");
+    Scenario::from_path(&path)
+        .unwrap()
+        .run(SocketAddrSpace::Unspecified)
+        .unwrap();
+}
+
 #[test]
 #[serial]
 fn test_restart_node() {
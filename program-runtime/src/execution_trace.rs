@@ -0,0 +1,264 @@
+//! Export of recorded [InstructionTrace]s, either as a tree of instructions
+//! and the instructions they invoked, or in the Chrome trace event format
+//! loaded by `chrome://tracing` and Perfetto.
+
+use {
+    crate::invoke_context::{InstructionTrace, SyscallTrace},
+    serde_json::{json, Map, Value},
+    std::{fmt, str::FromStr},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecutionTraceFormat {
+    /// Top level instructions, each with its inner instructions nested in
+    /// `innerInstructions`
+    Tree,
+    /// A `traceEvents` list of complete events, one for every instruction and
+    /// syscall
+    ChromeTrace,
+}
+
+impl ExecutionTraceFormat {
+    pub fn export(self, instruction_traces: &[InstructionTrace]) -> Value {
+        match self {
+            Self::Tree => instruction_trace_tree(instruction_traces),
+            Self::ChromeTrace => chrome_trace(instruction_traces),
+        }
+    }
+}
+
+impl FromStr for ExecutionTraceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tree" => Ok(Self::Tree),
+            "chrome" => Ok(Self::ChromeTrace),
+            _ => Err(format!("unknown execution trace format: {s}")),
+        }
+    }
+}
+
+impl fmt::Display for ExecutionTraceFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Tree => write!(f, "tree"),
+            Self::ChromeTrace => write!(f, "chrome"),
+        }
+    }
+}
+
+/// Nest `instruction_traces`, which are in invocation order, by their stack
+/// height
+pub fn instruction_trace_tree(instruction_traces: &[InstructionTrace]) -> Value {
+    let mut top_level_instructions = Vec::new();
+    // Instructions whose inner instructions are still being collected
+    let mut stack: Vec<(usize, Map<String, Value>, Vec<Value>)> = Vec::new();
+    let mut close_last = |stack: &mut Vec<(usize, Map<String, Value>, Vec<Value>)>| {
+        if let Some((_, mut instruction, inner_instructions)) = stack.pop() {
+            instruction.insert(
+                "innerInstructions".to_string(),
+                Value::Array(inner_instructions),
+            );
+            match stack.last_mut() {
+                Some((_, _, parent_inner_instructions)) => {
+                    parent_inner_instructions.push(Value::Object(instruction))
+                }
+                None => top_level_instructions.push(Value::Object(instruction)),
+            }
+        }
+    };
+    for instruction_trace in instruction_traces {
+        while stack
+            .last()
+            .is_some_and(|(stack_height, _, _)| *stack_height >= instruction_trace.stack_height)
+        {
+            close_last(&mut stack);
+        }
+        let Value::Object(instruction) = instruction_json(instruction_trace) else {
+            unreachable!("instructions are JSON objects");
+        };
+        stack.push((instruction_trace.stack_height, instruction, Vec::new()));
+    }
+    while !stack.is_empty() {
+        close_last(&mut stack);
+    }
+    Value::Array(top_level_instructions)
+}
+
+/// A complete event for every instruction and syscall. Events of a
+/// transaction share one thread, so viewers nest them by time.
+pub fn chrome_trace(instruction_traces: &[InstructionTrace]) -> Value {
+    let mut trace_events = Vec::new();
+    for instruction_trace in instruction_traces {
+        trace_events.push(json!({
+            "name": instruction_trace.program_id.to_string(),
+            "cat": "instruction",
+            "ph": "X",
+            "ts": micros(instruction_trace.start_ns),
+            "dur": micros(instruction_trace.elapsed_ns),
+            "pid": 0,
+            "tid": 0,
+            "args": {
+                "stackHeight": instruction_trace.stack_height,
+                "computeUnitsBefore": instruction_trace.compute_units_before,
+                "computeUnitsAfter": instruction_trace.compute_units_after,
+                "computeUnitsConsumed": instruction_trace.compute_units_consumed,
+            },
+        }));
+        for syscall in &instruction_trace.syscalls {
+            trace_events.push(json!({
+                "name": syscall.name,
+                "cat": "syscall",
+                "ph": "X",
+                "ts": micros(syscall.start_ns),
+                "dur": micros(syscall.elapsed_ns),
+                "pid": 0,
+                "tid": 0,
+                "args": {
+                    "computeUnitsConsumed": syscall.compute_units_consumed,
+                },
+            }));
+        }
+    }
+    json!({
+        "traceEvents": trace_events,
+        "displayTimeUnit": "ns",
+    })
+}
+
+fn instruction_json(instruction_trace: &InstructionTrace) -> Value {
+    json!({
+        "programId": instruction_trace.program_id.to_string(),
+        "stackHeight": instruction_trace.stack_height,
+        "computeUnitsBefore": instruction_trace.compute_units_before,
+        "computeUnitsAfter": instruction_trace.compute_units_after,
+        "computeUnitsConsumed": instruction_trace.compute_units_consumed,
+        "startNs": instruction_trace.start_ns,
+        "elapsedNs": instruction_trace.elapsed_ns,
+        "syscalls": instruction_trace
+            .syscalls
+            .iter()
+            .map(syscall_json)
+            .collect::<Vec<_>>(),
+        "accountDataLenChanges": instruction_trace
+            .account_data_len_changes
+            .iter()
+            .map(|change| json!({
+                "pubkey": change.pubkey.to_string(),
+                "preDataLen": change.pre_data_len,
+                "postDataLen": change.post_data_len,
+            }))
+            .collect::<Vec<_>>(),
+        "logMessages": instruction_trace.log_messages,
+    })
+}
+
+fn syscall_json(syscall: &SyscallTrace) -> Value {
+    json!({
+        "name": syscall.name,
+        "computeUnitsConsumed": syscall.compute_units_consumed,
+        "startNs": syscall.start_ns,
+        "elapsedNs": syscall.elapsed_ns,
+    })
+}
+
+/// Chrome trace timestamps are in microseconds
+fn micros(nanos: u64) -> f64 {
+    nanos as f64 / 1_000.0
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing)]
+mod tests {
+    use {super::*, solana_pubkey::Pubkey};
+
+    fn instruction_trace(program_id: Pubkey, stack_height: usize) -> InstructionTrace {
+        InstructionTrace {
+            program_id,
+            stack_height,
+            ..InstructionTrace::default()
+        }
+    }
+
+    #[test]
+    fn test_instruction_trace_tree() {
+        let program_ids: Vec<_> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let [a, b, c, d, e] = program_ids.as_slice() else {
+            unreachable!()
+        };
+        // a invokes b, which invokes c, then a invokes d; e is a second top
+        // level instruction
+        let instruction_traces = [
+            instruction_trace(*a, 1),
+            instruction_trace(*b, 2),
+            instruction_trace(*c, 3),
+            instruction_trace(*d, 2),
+            instruction_trace(*e, 1),
+        ];
+        let tree = instruction_trace_tree(&instruction_traces);
+
+        let program_id =
+            |instruction: &Value| instruction["programId"].as_str().unwrap().to_string();
+        let inner =
+            |instruction: &Value| instruction["innerInstructions"].as_array().unwrap().clone();
+        let top_level = tree.as_array().unwrap();
+        assert_eq!(
+            top_level.iter().map(program_id).collect::<Vec<_>>(),
+            vec![a.to_string(), e.to_string()]
+        );
+        let a_inner = inner(&top_level[0]);
+        assert_eq!(
+            a_inner.iter().map(program_id).collect::<Vec<_>>(),
+            vec![b.to_string(), d.to_string()]
+        );
+        assert_eq!(
+            inner(&a_inner[0])
+                .iter()
+                .map(program_id)
+                .collect::<Vec<_>>(),
+            vec![c.to_string()]
+        );
+        assert!(inner(&a_inner[1]).is_empty());
+        assert!(inner(&top_level[1]).is_empty());
+    }
+
+    #[test]
+    fn test_chrome_trace() {
+        let program_id = Pubkey::new_unique();
+        let instruction_traces = [InstructionTrace {
+            compute_units_consumed: 150,
+            start_ns: 1_000,
+            elapsed_ns: 5_500,
+            syscalls: vec![SyscallTrace {
+                name: "sol_log_".to_string(),
+                compute_units_consumed: 100,
+                start_ns: 2_000,
+                elapsed_ns: 500,
+            }],
+            ..instruction_trace(program_id, 1)
+        }];
+        let trace = chrome_trace(&instruction_traces);
+        let trace_events = trace["traceEvents"].as_array().unwrap();
+        assert_eq!(trace_events.len(), 2);
+        assert_eq!(trace_events[0]["name"], program_id.to_string());
+        assert_eq!(trace_events[0]["ts"], 1.0);
+        assert_eq!(trace_events[0]["dur"], 5.5);
+        assert_eq!(trace_events[0]["args"]["computeUnitsConsumed"], 150);
+        assert_eq!(trace_events[1]["name"], "sol_log_");
+        assert_eq!(trace_events[1]["cat"], "syscall");
+        assert_eq!(trace_events[1]["ts"], 2.0);
+        assert_eq!(trace_events[1]["args"]["computeUnitsConsumed"], 100);
+    }
+
+    #[test]
+    fn test_execution_trace_format_from_str() {
+        for format in [
+            ExecutionTraceFormat::Tree,
+            ExecutionTraceFormat::ChromeTrace,
+        ] {
+            assert_eq!(format.to_string().parse(), Ok(format));
+        }
+        assert!("flame".parse::<ExecutionTraceFormat>().is_err());
+    }
+}
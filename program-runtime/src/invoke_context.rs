@@ -31,7 +31,8 @@ use {
     solana_svm_transaction::{instruction::SVMInstruction, svm_message::SVMMessage},
     solana_timings::{ExecuteDetailsTimings, ExecuteTimings},
     solana_transaction_context::{
-        IndexOfAccount, InstructionAccount, TransactionAccount, TransactionContext,
+        BorrowedAccount, IndexOfAccount, InstructionAccount, TransactionAccount,
+        TransactionContext, TransactionReturnData,
     },
     solana_type_overrides::sync::{atomic::Ordering, Arc},
     std::{
@@ -39,6 +40,7 @@ use {
         cell::RefCell,
         fmt::{self, Debug},
         rc::Rc,
+        time::{Duration, Instant},
     },
 };
 
@@ -172,6 +174,107 @@ pub struct SyscallContext {
     pub trace_log: Vec<[u64; 12]>,
 }
 
+/// Execution details of a single instruction, recorded when instruction trace
+/// recording is enabled on the [InvokeContext]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InstructionTrace {
+    /// Index of the instruction in the transaction's instruction trace
+    pub index_in_trace: usize,
+    /// The invoked program
+    pub program_id: Pubkey,
+    /// Height of the invocation stack, top level instructions are height
+    /// `solana_instruction::TRANSACTION_LEVEL_STACK_HEIGHT`
+    pub stack_height: usize,
+    /// Compute units consumed, including those consumed by inner instructions
+    pub compute_units_consumed: u64,
+    /// Return data present when the instruction completed, if any
+    pub return_data: Option<TransactionReturnData>,
+    /// Log messages emitted while the instruction executed, including those
+    /// emitted by inner instructions
+    pub log_messages: Vec<String>,
+    /// Compute units remaining when the instruction started
+    pub compute_units_before: u64,
+    /// Compute units remaining when the instruction completed
+    pub compute_units_after: u64,
+    /// Nanoseconds from when recording was enabled to when the instruction
+    /// started
+    pub start_ns: u64,
+    /// Nanoseconds the instruction took, including inner instructions
+    pub elapsed_ns: u64,
+    /// Syscalls invoked by the instruction's program, in invocation order.
+    /// Syscalls of inner instructions are recorded in their own traces.
+    pub syscalls: Vec<SyscallTrace>,
+    /// Instruction accounts whose data length the instruction changed
+    pub account_data_len_changes: Vec<AccountDataLenChange>,
+    /// Data of the instruction
+    pub instruction_data: Vec<u8>,
+    /// Accounts the instruction can read, as they were when it started. Their
+    /// data is only recorded if account data recording is enabled.
+    pub accounts: Vec<InstructionTraceAccount>,
+    /// Register states of the instruction's program, one per executed sBPF
+    /// instruction, with the program counter last. Only recorded for
+    /// programs whose runtime environment enables instruction tracing.
+    pub register_trace: Vec<[u64; 12]>,
+}
+
+/// A syscall invoked by a program, see [InstructionTrace::syscalls]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SyscallTrace {
+    pub name: String,
+    /// Compute units consumed, which for cross-program invocations includes
+    /// those consumed by the invoked instruction
+    pub compute_units_consumed: u64,
+    /// Nanoseconds from when recording was enabled to when the syscall was
+    /// invoked
+    pub start_ns: u64,
+    pub elapsed_ns: u64,
+}
+
+/// See [InstructionTrace::account_data_len_changes]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountDataLenChange {
+    pub pubkey: Pubkey,
+    pub pre_data_len: usize,
+    pub post_data_len: usize,
+}
+
+/// See [InstructionTrace::accounts]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InstructionTraceAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+}
+
+/// Recorded and in progress instruction traces of an [InvokeContext]
+struct InstructionTraceRecorder {
+    started: Instant,
+    record_account_data: bool,
+    instruction_traces: Vec<InstructionTrace>,
+    /// Traces of the instructions on the invocation stack
+    open_instruction_traces: Vec<OpenInstructionTrace>,
+}
+
+struct OpenInstructionTrace {
+    position: usize,
+    log_messages_len: usize,
+    started: Instant,
+    account_data_lens: Vec<(Pubkey, usize)>,
+}
+
+impl InstructionTraceRecorder {
+    fn nanos_since_start(&self, instant: Instant) -> u64 {
+        duration_as_nanos(instant.saturating_duration_since(self.started))
+    }
+}
+
+fn duration_as_nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
 #[derive(Debug, Clone)]
 pub struct SerializedAccountMetadata {
     pub original_data_len: usize,
@@ -202,6 +305,8 @@ pub struct InvokeContext<'a> {
     pub timings: ExecuteDetailsTimings,
     pub syscall_context: Vec<Option<SyscallContext>>,
     traces: Vec<Vec<[u64; 12]>>,
+    /// `None` unless instruction trace recording is enabled
+    instruction_trace_recorder: Option<InstructionTraceRecorder>,
 }
 
 impl<'a> InvokeContext<'a> {
@@ -226,6 +331,7 @@ impl<'a> InvokeContext<'a> {
             timings: ExecuteDetailsTimings::default(),
             syscall_context: Vec::new(),
             traces: Vec::new(),
+            instruction_trace_recorder: None,
         }
     }
 
@@ -591,6 +697,7 @@ impl<'a> InvokeContext<'a> {
         self.transaction_context
             .set_return_data(program_id, Vec::new())?;
         let logger = self.get_log_collector();
+        self.start_instruction_trace(&program_id);
         stable_log::program_invoke(&logger, &program_id, self.get_stack_height());
         let pre_remaining_units = self.get_remaining();
         // In program-runtime v2 we will create this VM instance only once per transaction.
@@ -634,6 +741,7 @@ impl<'a> InvokeContext<'a> {
         };
         let post_remaining_units = self.get_remaining();
         *compute_units_consumed = pre_remaining_units.saturating_sub(post_remaining_units);
+        self.finish_instruction_trace();
 
         if builtin_id == program_id && result.is_ok() && *compute_units_consumed == 0 {
             return Err(InstructionError::BuiltinProgramsMustConsumeComputeUnits);
@@ -646,6 +754,224 @@ impl<'a> InvokeContext<'a> {
         result
     }
 
+    /// Record an [InstructionTrace] for every instruction processed from now
+    /// on. The data of the instruction accounts is only copied into the
+    /// traces if `record_account_data`.
+    pub fn enable_instruction_trace_recording(&mut self, record_account_data: bool) {
+        if self.instruction_trace_recorder.is_none() {
+            self.instruction_trace_recorder = Some(InstructionTraceRecorder {
+                started: Instant::now(),
+                record_account_data,
+                instruction_traces: Vec::new(),
+                open_instruction_traces: Vec::new(),
+            });
+        }
+    }
+
+    /// Take the recorded instruction traces, `None` if recording is not enabled
+    pub fn take_instruction_traces(&mut self) -> Option<Vec<InstructionTrace>> {
+        self.instruction_trace_recorder
+            .take()
+            .map(|recorder| recorder.instruction_traces)
+    }
+
+    /// Begin the trace of the current instruction if recording is enabled.
+    ///
+    /// [InvokeContext::process_instruction] traces the instructions it
+    /// processes. Callers that execute a program without it, like
+    /// `agave-ledger-tool program run`, start and finish the trace themselves.
+    pub fn start_instruction_trace(&mut self, program_id: &Pubkey) {
+        let Some(record_account_data) = self
+            .instruction_trace_recorder
+            .as_ref()
+            .map(|recorder| recorder.record_account_data)
+        else {
+            return;
+        };
+        let index_in_trace = self
+            .transaction_context
+            .get_instruction_trace_length()
+            .saturating_sub(1);
+        let stack_height = self.get_stack_height();
+        let log_messages_len = self.recorded_log_messages_len();
+        let compute_units_before = self.get_remaining();
+        let accounts = self.instruction_accounts(record_account_data);
+        let account_data_lens = self.instruction_account_data_lens();
+        let instruction_data = self
+            .transaction_context
+            .get_current_instruction_context()
+            .map(|instruction_context| instruction_context.get_instruction_data().to_vec())
+            .unwrap_or_default();
+        let Some(recorder) = self.instruction_trace_recorder.as_mut() else {
+            return;
+        };
+        let started = Instant::now();
+        recorder.instruction_traces.push(InstructionTrace {
+            index_in_trace,
+            program_id: *program_id,
+            stack_height,
+            compute_units_before,
+            start_ns: recorder.nanos_since_start(started),
+            instruction_data,
+            accounts,
+            ..InstructionTrace::default()
+        });
+        recorder.open_instruction_traces.push(OpenInstructionTrace {
+            position: recorder.instruction_traces.len().saturating_sub(1),
+            log_messages_len,
+            started,
+            account_data_lens,
+        });
+    }
+
+    /// Complete the trace begun by the last [InvokeContext::start_instruction_trace]
+    pub fn finish_instruction_trace(&mut self) {
+        let Some(open_instruction_trace) = self
+            .instruction_trace_recorder
+            .as_mut()
+            .and_then(|recorder| recorder.open_instruction_traces.pop())
+        else {
+            return;
+        };
+        let elapsed_ns = duration_as_nanos(open_instruction_trace.started.elapsed());
+        let compute_units_after = self.get_remaining();
+        let (return_data_program_id, return_data) = self.transaction_context.get_return_data();
+        let return_data = (!return_data.is_empty()).then(|| TransactionReturnData {
+            program_id: *return_data_program_id,
+            data: return_data.to_vec(),
+        });
+        let log_messages = self
+            .log_collector
+            .as_ref()
+            .and_then(|log_collector| {
+                log_collector
+                    .borrow()
+                    .get_recorded_content()
+                    .get(open_instruction_trace.log_messages_len..)
+                    .map(<[String]>::to_vec)
+            })
+            .unwrap_or_default();
+        let account_data_len_changes = self
+            .instruction_account_data_lens()
+            .into_iter()
+            .filter_map(|(pubkey, post_data_len)| {
+                let pre_data_len = open_instruction_trace
+                    .account_data_lens
+                    .iter()
+                    .find(|(pre_pubkey, _)| *pre_pubkey == pubkey)
+                    .map(|(_, pre_data_len)| *pre_data_len)?;
+                (pre_data_len != post_data_len).then_some(AccountDataLenChange {
+                    pubkey,
+                    pre_data_len,
+                    post_data_len,
+                })
+            })
+            .collect();
+        let register_trace = self
+            .get_syscall_context()
+            .map(|syscall_context| syscall_context.trace_log.clone())
+            .unwrap_or_default();
+        if let Some(instruction_trace) =
+            self.instruction_trace_recorder
+                .as_mut()
+                .and_then(|recorder| {
+                    recorder
+                        .instruction_traces
+                        .get_mut(open_instruction_trace.position)
+                })
+        {
+            instruction_trace.compute_units_consumed = instruction_trace
+                .compute_units_before
+                .saturating_sub(compute_units_after);
+            instruction_trace.compute_units_after = compute_units_after;
+            instruction_trace.elapsed_ns = elapsed_ns;
+            instruction_trace.return_data = return_data;
+            instruction_trace.log_messages = log_messages;
+            instruction_trace.account_data_len_changes = account_data_len_changes;
+            instruction_trace.register_trace = register_trace;
+        }
+    }
+
+    /// Invoke `syscall`, recording it in the trace of the current instruction
+    /// if recording is enabled
+    pub fn trace_syscall<T>(&mut self, name: &str, syscall: impl FnOnce(&mut Self) -> T) -> T {
+        let Some(position) = self
+            .instruction_trace_recorder
+            .as_ref()
+            .and_then(|recorder| recorder.open_instruction_traces.last())
+            .map(|open_instruction_trace| open_instruction_trace.position)
+        else {
+            return syscall(self);
+        };
+        let pre_remaining_units = self.get_remaining();
+        let started = Instant::now();
+        let result = syscall(self);
+        let elapsed_ns = duration_as_nanos(started.elapsed());
+        let compute_units_consumed = pre_remaining_units.saturating_sub(self.get_remaining());
+        if let Some(recorder) = self.instruction_trace_recorder.as_mut() {
+            let start_ns = recorder.nanos_since_start(started);
+            if let Some(instruction_trace) = recorder.instruction_traces.get_mut(position) {
+                instruction_trace.syscalls.push(SyscallTrace {
+                    name: name.to_string(),
+                    compute_units_consumed,
+                    start_ns,
+                    elapsed_ns,
+                });
+            }
+        }
+        result
+    }
+
+    /// The distinct accounts of the current instruction, with their current
+    /// state. Their data is left empty unless `with_data`.
+    fn instruction_accounts(&self, with_data: bool) -> Vec<InstructionTraceAccount> {
+        self.map_instruction_accounts(|account| InstructionTraceAccount {
+            pubkey: *account.get_key(),
+            is_signer: account.is_signer(),
+            is_writable: account.is_writable(),
+            lamports: account.get_lamports(),
+            owner: *account.get_owner(),
+            data: if with_data {
+                account.get_data().to_vec()
+            } else {
+                Vec::new()
+            },
+        })
+    }
+
+    /// The keys and data lengths of the current instruction's distinct accounts
+    fn instruction_account_data_lens(&self) -> Vec<(Pubkey, usize)> {
+        self.map_instruction_accounts(|account| (*account.get_key(), account.get_data().len()))
+    }
+
+    fn map_instruction_accounts<T>(&self, f: impl Fn(&BorrowedAccount) -> T) -> Vec<T> {
+        let Ok(instruction_context) = self.transaction_context.get_current_instruction_context()
+        else {
+            return Vec::new();
+        };
+        (0..instruction_context.get_number_of_instruction_accounts())
+            .filter(|index| {
+                matches!(
+                    instruction_context.is_instruction_account_duplicate(*index),
+                    Ok(None)
+                )
+            })
+            .filter_map(|index| {
+                instruction_context
+                    .try_borrow_instruction_account(self.transaction_context, index)
+                    .ok()
+                    .map(|account| f(&account))
+            })
+            .collect()
+    }
+
+    fn recorded_log_messages_len(&self) -> usize {
+        self.log_collector
+            .as_ref()
+            .map(|log_collector| log_collector.borrow().get_recorded_content().len())
+            .unwrap_or(0)
+    }
+
     /// Get this invocation's LogCollector
     pub fn get_log_collector(&self) -> Option<Rc<RefCell<LogCollector>>> {
         self.log_collector.clone()
@@ -1310,6 +1636,234 @@ mod tests {
         invoke_context.pop().unwrap();
     }
 
+    #[test]
+    fn test_process_instruction_instruction_trace_recording() {
+        let callee_program_id = solana_pubkey::new_rand();
+        let owned_account = AccountSharedData::new(42, 1, &callee_program_id);
+        let not_owned_account = AccountSharedData::new(84, 1, &solana_pubkey::new_rand());
+        let loader_account = AccountSharedData::new(0, 1, &native_loader::id());
+        let mut program_account = AccountSharedData::new(1, 1, &native_loader::id());
+        program_account.set_executable(true);
+        let transaction_accounts = vec![
+            (solana_pubkey::new_rand(), owned_account),
+            (solana_pubkey::new_rand(), not_owned_account),
+            (solana_pubkey::new_rand(), AccountSharedData::default()),
+            (callee_program_id, program_account),
+            (solana_pubkey::new_rand(), loader_account),
+        ];
+        let metas = vec![
+            AccountMeta::new(transaction_accounts.first().unwrap().0, false),
+            AccountMeta::new(transaction_accounts.get(1).unwrap().0, false),
+            AccountMeta::new_readonly(transaction_accounts.get(2).unwrap().0, false),
+        ];
+        let expected_accounts = transaction_accounts
+            .iter()
+            .zip(&metas)
+            .map(|((pubkey, account), meta)| InstructionTraceAccount {
+                pubkey: *pubkey,
+                is_signer: false,
+                is_writable: meta.is_writable,
+                lamports: account.lamports(),
+                owner: *account.owner(),
+                data: account.data().to_vec(),
+            })
+            .collect::<Vec<_>>();
+        let instruction_accounts = (0..4)
+            .map(|instruction_account_index| {
+                InstructionAccount::new(
+                    instruction_account_index,
+                    instruction_account_index,
+                    false,
+                    instruction_account_index < 2,
+                )
+            })
+            .collect::<Vec<_>>();
+        with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
+        let mut program_cache_for_tx_batch = ProgramCacheForTxBatch::default();
+        program_cache_for_tx_batch.replenish(
+            callee_program_id,
+            Arc::new(ProgramCacheEntry::new_builtin(0, 1, MockBuiltin::vm)),
+        );
+        invoke_context.program_cache_for_tx_batch = &mut program_cache_for_tx_batch;
+        invoke_context.enable_instruction_trace_recording(true);
+
+        invoke_context
+            .transaction_context
+            .get_next_instruction_context_mut()
+            .unwrap()
+            .configure(vec![4], instruction_accounts, &[]);
+        invoke_context.push().unwrap();
+        let inner_instruction = Instruction::new_with_bincode(
+            callee_program_id,
+            &MockInstruction::ConsumeComputeUnits {
+                compute_units_to_consume: 10,
+                desired_result: Ok(()),
+            },
+            metas,
+        );
+        invoke_context
+            .prepare_next_instruction(&inner_instruction, &[])
+            .unwrap();
+        let compute_units_before = invoke_context.get_remaining();
+        let mut compute_units_consumed = 0;
+        invoke_context
+            .process_instruction(&mut compute_units_consumed, &mut ExecuteTimings::default())
+            .unwrap();
+        invoke_context.pop().unwrap();
+
+        let instruction_traces = invoke_context.take_instruction_traces().unwrap();
+        let (start_ns, elapsed_ns) = instruction_traces
+            .first()
+            .map(|trace| (trace.start_ns, trace.elapsed_ns))
+            .unwrap();
+        assert_eq!(
+            instruction_traces,
+            vec![InstructionTrace {
+                index_in_trace: 1,
+                program_id: callee_program_id,
+                stack_height: 2,
+                compute_units_consumed,
+                return_data: None,
+                log_messages: vec![
+                    format!("Program {callee_program_id} invoke [2]"),
+                    format!("Program {callee_program_id} success"),
+                ],
+                compute_units_before,
+                compute_units_after: compute_units_before.saturating_sub(compute_units_consumed),
+                start_ns,
+                elapsed_ns,
+                syscalls: vec![],
+                account_data_len_changes: vec![],
+                instruction_data: inner_instruction.data.clone(),
+                accounts: expected_accounts,
+                register_trace: vec![],
+            }]
+        );
+        assert!(invoke_context.take_instruction_traces().is_none());
+    }
+
+    #[test]
+    fn test_instruction_trace_recording_syscalls_and_resizes() {
+        let callee_program_id = solana_pubkey::new_rand();
+        let resized_account_key = solana_pubkey::new_rand();
+        let mut program_account = AccountSharedData::new(1, 1, &native_loader::id());
+        program_account.set_executable(true);
+        let transaction_accounts = vec![
+            (
+                resized_account_key,
+                AccountSharedData::new(42, 3, &callee_program_id),
+            ),
+            (
+                solana_pubkey::new_rand(),
+                AccountSharedData::new(10, 0, &callee_program_id),
+            ),
+            (callee_program_id, program_account),
+            (
+                solana_pubkey::new_rand(),
+                AccountSharedData::new(0, 1, &native_loader::id()),
+            ),
+        ];
+        let instruction_accounts = vec![
+            InstructionAccount::new(0, 0, false, true),
+            InstructionAccount::new(1, 1, false, false),
+            InstructionAccount::new(2, 2, false, false),
+        ];
+        let metas = vec![
+            AccountMeta::new(resized_account_key, false),
+            AccountMeta::new_readonly(transaction_accounts.get(1).unwrap().0, false),
+        ];
+        let readonly_account_key = transaction_accounts.get(1).unwrap().0;
+        with_mock_invoke_context!(invoke_context, transaction_context, transaction_accounts);
+        let mut program_cache_for_tx_batch = ProgramCacheForTxBatch::default();
+        program_cache_for_tx_batch.replenish(
+            callee_program_id,
+            Arc::new(ProgramCacheEntry::new_builtin(0, 1, MockBuiltin::vm)),
+        );
+        invoke_context.program_cache_for_tx_batch = &mut program_cache_for_tx_batch;
+
+        // Without recording, syscalls are invoked without being traced
+        assert_eq!(invoke_context.trace_syscall("sol_log_", |_| 7), 7);
+
+        // Resizes are traced without recording the data of the accounts
+        invoke_context.enable_instruction_trace_recording(false);
+        invoke_context
+            .transaction_context
+            .get_next_instruction_context_mut()
+            .unwrap()
+            .configure(vec![3], instruction_accounts, &[]);
+        invoke_context.push().unwrap();
+        invoke_context.start_instruction_trace(&callee_program_id);
+        invoke_context
+            .trace_syscall("sol_log_", |invoke_context| {
+                invoke_context.consume_checked(100)
+            })
+            .unwrap();
+        let inner_instruction = Instruction::new_with_bincode(
+            callee_program_id,
+            &MockInstruction::Resize { new_len: 5 },
+            metas,
+        );
+        invoke_context
+            .trace_syscall("sol_invoke_signed_rust", |invoke_context| {
+                invoke_context.native_invoke(inner_instruction, &[])
+            })
+            .unwrap();
+        invoke_context.finish_instruction_trace();
+        invoke_context.pop().unwrap();
+
+        let instruction_traces = invoke_context.take_instruction_traces().unwrap();
+        let [outer, inner] = instruction_traces.as_slice() else {
+            panic!("unexpected traces {instruction_traces:?}");
+        };
+        assert_eq!(outer.stack_height, 1);
+        assert_eq!(
+            outer
+                .syscalls
+                .iter()
+                .map(|syscall| (syscall.name.as_str(), syscall.compute_units_consumed))
+                .collect::<Vec<_>>(),
+            vec![
+                ("sol_log_", 100),
+                ("sol_invoke_signed_rust", inner.compute_units_consumed),
+            ]
+        );
+        assert_eq!(
+            outer.compute_units_consumed,
+            inner.compute_units_consumed.saturating_add(100)
+        );
+        assert_eq!(
+            outer.compute_units_after,
+            outer
+                .compute_units_before
+                .saturating_sub(outer.compute_units_consumed)
+        );
+        assert!(outer.start_ns <= inner.start_ns);
+        assert!(outer.elapsed_ns >= inner.elapsed_ns);
+
+        let expected_resize = vec![AccountDataLenChange {
+            pubkey: resized_account_key,
+            pre_data_len: 3,
+            post_data_len: 5,
+        }];
+        assert_eq!(inner.stack_height, 2);
+        assert_eq!(inner.compute_units_consumed, MOCK_BUILTIN_COMPUTE_UNIT_COST);
+        assert!(inner.syscalls.is_empty());
+        assert_eq!(inner.account_data_len_changes, expected_resize);
+        assert_eq!(outer.account_data_len_changes, expected_resize);
+        assert_eq!(
+            outer
+                .accounts
+                .iter()
+                .map(|account| (account.pubkey, account.lamports, account.data.is_empty()))
+                .collect::<Vec<_>>(),
+            vec![
+                (resized_account_key, 42, true),
+                (readonly_account_key, 10, true),
+                (callee_program_id, 1, true),
+            ]
+        );
+    }
+
     #[test]
     fn test_invoke_context_compute_budget() {
         let transaction_accounts = vec![(solana_pubkey::new_rand(), AccountSharedData::default())];
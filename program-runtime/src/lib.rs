@@ -8,9 +8,11 @@ extern crate solana_metrics;
 
 pub use solana_sbpf;
 pub mod execution_budget;
+pub mod execution_trace;
 pub mod invoke_context;
 pub mod loaded_programs;
 pub mod mem_pool;
+pub mod persistent_program_cache;
 pub mod serialization;
 pub mod stable_log;
 pub mod sysvar_cache;
@@ -1,5 +1,8 @@
 use {
-    crate::invoke_context::{BuiltinFunctionWithContext, InvokeContext},
+    crate::{
+        invoke_context::{BuiltinFunctionWithContext, InvokeContext},
+        persistent_program_cache::PersistentProgramCache,
+    },
     log::{debug, error, log_enabled, trace},
     percentage::PercentageInteger,
     solana_clock::{Epoch, Slot},
@@ -622,6 +625,7 @@ enum IndexImplementation {
 /// - allows for cooperative loading of TX batches which hit the same missing programs simultaneously.
 /// - enforces that all programs used in a batch are eagerly loaded ahead of execution.
 /// - is not persisted to disk or a snapshot, so it needs to cold start and warm up first.
+///   Which programs were loaded can optionally be persisted, see [PersistentProgramCache].
 pub struct ProgramCache<FG: ForkGraph> {
     /// Index of the cached entries and cooperative loading tasks
     index: IndexImplementation,
@@ -645,6 +649,8 @@ pub struct ProgramCache<FG: ForkGraph> {
     pub fork_graph: Option<Weak<RwLock<FG>>>,
     /// Coordinates TX batches waiting for others to complete their task during cooperative loading
     pub loading_task_waiter: Arc<LoadingTaskWaiter>,
+    /// Records the loaded programs on disk, if enabled
+    pub persistent_cache: Option<Arc<PersistentProgramCache>>,
 }
 
 impl<FG: ForkGraph> Debug for ProgramCache<FG> {
@@ -824,6 +830,7 @@ impl<FG: ForkGraph> ProgramCache<FG> {
             stats: ProgramCacheStats::default(),
             fork_graph: None,
             loading_task_waiter: Arc::new(LoadingTaskWaiter::default()),
+            persistent_cache: None,
         }
     }
 
@@ -965,6 +972,9 @@ impl<FG: ForkGraph> ProgramCache<FG> {
                 preparation_phase_ends = true;
                 self.environments = upcoming_environments;
                 self.programs_to_recompile.clear();
+                if let Some(persistent_cache) = &self.persistent_cache {
+                    persistent_cache.retain_environment(&self.environments.program_runtime_v1);
+                }
             }
         }
         match &mut self.index {
@@ -1223,6 +1233,23 @@ impl<FG: ForkGraph> ProgramCache<FG> {
         }
     }
 
+    /// Returns the list of builtin programs in the cache.
+    pub fn get_builtins(&self) -> Vec<(Pubkey, Arc<ProgramCacheEntry>)> {
+        match &self.index {
+            IndexImplementation::V1 { entries, .. } => entries
+                .iter()
+                .flat_map(|(id, second_level)| {
+                    second_level
+                        .iter()
+                        .filter(|program| {
+                            matches!(program.program, ProgramCacheEntryType::Builtin(_))
+                        })
+                        .map(|program| (*id, program.clone()))
+                })
+                .collect(),
+        }
+    }
+
     /// Returns the list of all entries in the cache.
     pub fn get_flattened_entries_for_tests(&self) -> Vec<(Pubkey, Arc<ProgramCacheEntry>)> {
         match &self.index {
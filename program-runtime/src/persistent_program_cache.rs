@@ -0,0 +1,399 @@
+//! On-disk record of which programs a validator had loaded, so that after a
+//! restart they can be loaded ahead of replay instead of on first use.
+//!
+//! Each record is keyed by the program address, its deployment slot and a
+//! fingerprint of the [ProgramRuntimeEnvironment] it was loaded in. Records of
+//! other environments are never used, and are removed once the cache moves to
+//! a new environment, e.g. after a feature activation.
+//!
+//! Only the keys are persisted. `solana_sbpf` provides no way to serialize a
+//! JIT compiled program, and nothing read from disk is trusted: every program
+//! is loaded from its account, verified and compiled as if it was not cached.
+//!
+//! Records are written and removed by a writer thread, so that program loading
+//! and the pruning of the program cache never wait for the file system.
+
+use {
+    crate::loaded_programs::ProgramRuntimeEnvironment,
+    log::warn,
+    solana_clock::Slot,
+    solana_hash::{Hash, HASH_BYTES},
+    solana_pubkey::Pubkey,
+    solana_sha256_hasher::Hasher,
+    std::{
+        ffi::OsStr,
+        fs, io,
+        path::{Path, PathBuf},
+        str::FromStr,
+        sync::{
+            mpsc::{self, Receiver, SyncSender, TrySendError},
+            Arc, Mutex,
+        },
+        thread::{Builder, JoinHandle},
+        time::SystemTime,
+    },
+};
+
+/// Identifies the record format, bumped whenever the layout changes
+const RECORD_MAGIC: [u8; 4] = *b"PPC2";
+const RECORD_LEN: usize = RECORD_MAGIC.len() + HASH_BYTES;
+const TMP_FILE_EXTENSION: &str = "tmp";
+/// The maximum number of operations waiting for the writer thread. Records
+/// are only a hint, so once reached further records are dropped rather than
+/// holding up program loading.
+const MAX_QUEUED_OPERATIONS: usize = 10_000;
+/// The number of environments whose fingerprint is remembered, enough for the
+/// current and the upcoming environment
+const MAX_FINGERPRINTED_ENVIRONMENTS: usize = 2;
+
+/// Key of a record in the [PersistentProgramCache]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PersistedProgramKey {
+    pub program_id: Pubkey,
+    pub deployment_slot: Slot,
+    pub environment: Hash,
+}
+
+impl PersistedProgramKey {
+    pub fn new(
+        program_id: Pubkey,
+        deployment_slot: Slot,
+        program_runtime_environment: &ProgramRuntimeEnvironment,
+    ) -> Self {
+        Self {
+            program_id,
+            deployment_slot,
+            environment: environment_fingerprint(program_runtime_environment),
+        }
+    }
+
+    fn file_name(&self) -> String {
+        format!(
+            "{}-{}-{}",
+            self.program_id, self.deployment_slot, self.environment
+        )
+    }
+
+    fn from_file_name(file_name: &str) -> Option<Self> {
+        let mut parts = file_name.splitn(3, '-');
+        Some(Self {
+            program_id: Pubkey::from_str(parts.next()?).ok()?,
+            deployment_slot: parts.next()?.parse().ok()?,
+            environment: Hash::from_str(parts.next()?).ok()?,
+        })
+    }
+}
+
+/// Hashes everything about a [ProgramRuntimeEnvironment] that affects how a
+/// program is loaded: the VM config and the registered syscalls.
+pub fn environment_fingerprint(program_runtime_environment: &ProgramRuntimeEnvironment) -> Hash {
+    let mut hasher = Hasher::default();
+    hasher.hash(format!("{:?}", program_runtime_environment.get_config()).as_bytes());
+    let mut functions: Vec<_> = program_runtime_environment
+        .get_function_registry()
+        .iter()
+        .map(|(key, (name, _function))| (key, name))
+        .collect();
+    functions.sort_unstable();
+    for (key, name) in functions {
+        hasher.hash(&key.to_le_bytes());
+        hasher.hash(name);
+    }
+    hasher.result()
+}
+
+enum Operation {
+    Insert(PersistedProgramKey),
+    Remove(PersistedProgramKey),
+    RetainEnvironment(Hash),
+    Flush(mpsc::Sender<()>),
+}
+
+/// Programs loaded by a validator, stored in a directory
+#[derive(Debug)]
+pub struct PersistentProgramCache {
+    path: PathBuf,
+    /// Fingerprints of the most recently used environments, most recent last
+    fingerprints: Mutex<Vec<(ProgramRuntimeEnvironment, Hash)>>,
+    sender: Option<SyncSender<Operation>>,
+    writer_thread: Option<JoinHandle<()>>,
+}
+
+impl PersistentProgramCache {
+    /// Opens the cache stored in `path`, creating the directory if needed
+    pub fn new(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        fs::create_dir_all(&path)?;
+        let (sender, receiver) = mpsc::sync_channel(MAX_QUEUED_OPERATIONS);
+        let writer_thread = Builder::new().name("solPrgCacheWrt".to_string()).spawn({
+            let path = path.clone();
+            move || Self::run_writer(&path, receiver)
+        })?;
+        Ok(Self {
+            path,
+            fingerprints: Mutex::default(),
+            sender: Some(sender),
+            writer_thread: Some(writer_thread),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the [environment_fingerprint] of `environment`, which is only
+    /// computed the first time it is asked for
+    pub fn fingerprint(&self, environment: &ProgramRuntimeEnvironment) -> Hash {
+        let mut fingerprints = self.fingerprints.lock().unwrap();
+        if let Some(position) = fingerprints
+            .iter()
+            .position(|(fingerprinted, _)| Arc::ptr_eq(fingerprinted, environment))
+        {
+            let entry = fingerprints.remove(position);
+            let fingerprint = entry.1;
+            fingerprints.push(entry);
+            return fingerprint;
+        }
+        let fingerprint = environment_fingerprint(environment);
+        if fingerprints.len() >= MAX_FINGERPRINTED_ENVIRONMENTS {
+            fingerprints.remove(0);
+        }
+        fingerprints.push((Arc::clone(environment), fingerprint));
+        fingerprint
+    }
+
+    /// Records that the program deployed at `deployment_slot` was loaded in
+    /// `environment`
+    pub fn insert(
+        &self,
+        program_id: Pubkey,
+        deployment_slot: Slot,
+        environment: &ProgramRuntimeEnvironment,
+    ) {
+        self.send(Operation::Insert(PersistedProgramKey {
+            program_id,
+            deployment_slot,
+            environment: self.fingerprint(environment),
+        }));
+    }
+
+    /// Removes the record of `key`, e.g. because the program was closed
+    pub fn remove(&self, key: &PersistedProgramKey) {
+        self.send(Operation::Remove(*key));
+    }
+
+    /// Removes the records of all environments except `environment`
+    pub fn retain_environment(&self, environment: &ProgramRuntimeEnvironment) {
+        self.send(Operation::RetainEnvironment(self.fingerprint(environment)));
+    }
+
+    /// Waits until the records inserted and removed so far are written
+    pub fn flush(&self) {
+        let (sender, receiver) = mpsc::channel();
+        if let Some(operations) = &self.sender {
+            if operations.send(Operation::Flush(sender)).is_ok() {
+                let _ = receiver.recv();
+            }
+        }
+    }
+
+    /// Returns the keys of the programs recorded for `environment`, most
+    /// recently loaded first
+    pub fn programs(&self, environment: &ProgramRuntimeEnvironment) -> Vec<PersistedProgramKey> {
+        let environment = self.fingerprint(environment);
+        let mut programs: Vec<_> = records(&self.path)
+            .filter(|(key, _path)| key.environment == environment)
+            .filter_map(|(key, path)| {
+                let record = fs::read(&path).ok()?;
+                let valid = record.len() == RECORD_LEN
+                    && record.starts_with(&RECORD_MAGIC)
+                    && record.ends_with(environment.as_ref());
+                if !valid {
+                    let _ = fs::remove_file(&path);
+                    return None;
+                }
+                let modified = fs::metadata(&path)
+                    .and_then(|metadata| metadata.modified())
+                    .unwrap_or(SystemTime::UNIX_EPOCH);
+                Some((modified, key))
+            })
+            .collect();
+        programs.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+        programs.into_iter().map(|(_modified, key)| key).collect()
+    }
+
+    fn send(&self, operation: Operation) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        if let Err(TrySendError::Full(_)) = sender.try_send(operation) {
+            warn!(
+                "Dropped a program cache record, the writer of {} is behind",
+                self.path.display()
+            );
+        }
+    }
+
+    fn run_writer(path: &Path, receiver: Receiver<Operation>) {
+        for operation in receiver {
+            match operation {
+                Operation::Insert(key) => write_record(path, &key),
+                Operation::Remove(key) => {
+                    let _ = fs::remove_file(path.join(key.file_name()));
+                }
+                Operation::RetainEnvironment(environment) => {
+                    for (key, path) in records(path) {
+                        if key.environment != environment {
+                            let _ = fs::remove_file(path);
+                        }
+                    }
+                }
+                Operation::Flush(sender) => {
+                    let _ = sender.send(());
+                }
+            }
+        }
+    }
+}
+
+impl Drop for PersistentProgramCache {
+    /// Writes the queued records before the cache is closed
+    fn drop(&mut self) {
+        drop(self.sender.take());
+        if let Some(writer_thread) = self.writer_thread.take() {
+            let _ = writer_thread.join();
+        }
+    }
+}
+
+/// Records that the program of `key` was loaded
+fn write_record(path: &Path, key: &PersistedProgramKey) {
+    let path = path.join(key.file_name());
+    let tmp_path = path.with_extension(TMP_FILE_EXTENSION);
+    let mut record = Vec::with_capacity(RECORD_LEN);
+    record.extend_from_slice(&RECORD_MAGIC);
+    record.extend_from_slice(key.environment.as_ref());
+    // Write to a temporary file first, so that a crash never leaves a
+    // partially written record behind. Rewriting an existing record
+    // updates its modification time, which orders the records by use.
+    if let Err(err) = fs::write(&tmp_path, record).and_then(|()| fs::rename(&tmp_path, &path)) {
+        warn!(
+            "Unable to write program cache record {}: {err}",
+            path.display()
+        );
+        let _ = fs::remove_file(&tmp_path);
+    }
+}
+
+/// Iterates over the records in the cache directory `path`, skipping
+/// temporary files which are still being written
+fn records(path: &Path) -> impl Iterator<Item = (PersistedProgramKey, PathBuf)> {
+    let entries = fs::read_dir(path)
+        .inspect_err(|err| {
+            warn!(
+                "Unable to read program cache directory {}: {err}",
+                path.display()
+            )
+        })
+        .into_iter()
+        .flatten()
+        .flatten();
+    entries.filter_map(|entry| {
+        let path = entry.path();
+        if path.extension() == Some(OsStr::new(TMP_FILE_EXTENSION)) {
+            return None;
+        }
+        let key = PersistedProgramKey::from_file_name(path.file_name()?.to_str()?)?;
+        Some((key, path))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::invoke_context::InvokeContext,
+        solana_sbpf::{program::BuiltinProgram, vm::Config},
+        std::sync::Arc,
+    };
+
+    fn environment(reject_broken_elfs: bool) -> ProgramRuntimeEnvironment {
+        Arc::new(BuiltinProgram::<InvokeContext<'static>>::new_loader(
+            Config {
+                reject_broken_elfs,
+                ..Config::default()
+            },
+        ))
+    }
+
+    #[test]
+    fn test_persistent_program_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = PersistentProgramCache::new(dir.path()).unwrap();
+        let environment = environment(false);
+        let key = PersistedProgramKey::new(Pubkey::new_unique(), 10, &environment);
+        let redeployed_key = PersistedProgramKey::new(key.program_id, 11, &environment);
+
+        assert_eq!(cache.programs(&environment), vec![]);
+        cache.insert(key.program_id, key.deployment_slot, &environment);
+        cache.insert(key.program_id, redeployed_key.deployment_slot, &environment);
+        cache.flush();
+        let mut programs = cache.programs(&environment);
+        programs.sort_by_key(|key| key.deployment_slot);
+        assert_eq!(programs, vec![key, redeployed_key]);
+
+        // Records survive reopening the cache
+        let cache = PersistentProgramCache::new(dir.path()).unwrap();
+        cache.remove(&key);
+        cache.flush();
+        assert_eq!(cache.programs(&environment), vec![redeployed_key]);
+
+        // Corrupt records are removed
+        fs::write(dir.path().join(redeployed_key.file_name()), b"corrupt").unwrap();
+        assert_eq!(cache.programs(&environment), vec![]);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_persistent_program_cache_environment_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = PersistentProgramCache::new(dir.path()).unwrap();
+        let program_id = Pubkey::new_unique();
+        let old_environment = environment(false);
+        let new_environment = environment(true);
+        assert_ne!(
+            environment_fingerprint(&old_environment),
+            environment_fingerprint(&new_environment)
+        );
+        assert_eq!(
+            environment_fingerprint(&old_environment),
+            environment_fingerprint(&environment(false))
+        );
+
+        assert_eq!(
+            cache.fingerprint(&new_environment),
+            environment_fingerprint(&new_environment)
+        );
+
+        let old_key = PersistedProgramKey::new(program_id, 0, &old_environment);
+        let new_key = PersistedProgramKey::new(program_id, 0, &new_environment);
+        cache.insert(program_id, 0, &old_environment);
+        cache.insert(program_id, 0, &new_environment);
+        cache.flush();
+        assert_eq!(cache.programs(&new_environment), vec![new_key]);
+
+        // Temporary files of concurrent writers are left alone
+        let tmp_path = dir
+            .path()
+            .join(old_key.file_name())
+            .with_extension(TMP_FILE_EXTENSION);
+        fs::write(&tmp_path, b"").unwrap();
+
+        cache.retain_environment(&new_environment);
+        cache.flush();
+        assert_eq!(cache.programs(&old_environment), vec![]);
+        assert_eq!(cache.programs(&new_environment), vec![new_key]);
+        assert!(tmp_path.exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+}
@@ -35,7 +35,7 @@ use {
     solana_pubkey::Pubkey,
     solana_rent::Rent,
     solana_runtime::{
-        bank::Bank,
+        bank::{Bank, TransactionSimulationConfig, TransactionSimulationResult},
         bank_forks::BankForks,
         commitment::BlockCommitmentCache,
         genesis_utils::{create_genesis_config_with_leader_ex, GenesisConfigInfo},
@@ -45,6 +45,8 @@ use {
     solana_sysvar::Sysvar,
     solana_sysvar_id::SysvarId,
     solana_timings::ExecuteTimings,
+    solana_transaction::{versioned::VersionedTransaction, TransactionVerificationMode},
+    solana_transaction_error::TransactionError,
     solana_vote_program::vote_state::{self, VoteStateV3, VoteStateVersions},
     std::{
         cell::RefCell,
@@ -68,7 +70,9 @@ use {
 pub use {
     solana_banks_client::{BanksClient, BanksClientError},
     solana_banks_interface::BanksTransactionResultWithMetadata,
-    solana_program_runtime::invoke_context::InvokeContext,
+    solana_program_runtime::{
+        execution_trace::ExecutionTraceFormat, invoke_context::InvokeContext,
+    },
     solana_sbpf::{
         error::EbpfError,
         vm::{get_runtime_environment_key, EbpfVm},
@@ -84,8 +88,15 @@ pub enum ProgramTestError {
     /// The chosen warp slot is not in the future, so warp is not performed
     #[error("Warp slot not in the future")]
     InvalidWarpSlot,
+    /// The snapshot was discarded by a later warp, so it cannot be restored
+    #[error("Snapshot no longer available")]
+    SnapshotUnavailable,
 }
 
+/// Identifies bank state captured by [`ProgramTestContext::snapshot`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SnapshotId(Slot);
+
 thread_local! {
     static INVOKE_CONTEXT: RefCell<Option<usize>> = const { RefCell::new(None) };
 }
@@ -1240,6 +1251,66 @@ impl ProgramTestContext {
         Ok(())
     }
 
+    /// Capture the state of the working bank, to be restored later with
+    /// [`Self::restore`].
+    ///
+    /// The working bank is frozen and the test continues on a new working bank
+    /// in the next slot, so later transactions do not change the captured
+    /// state. Warping discards all snapshots taken before the warp.
+    pub fn snapshot(&mut self) -> SnapshotId {
+        let mut bank_forks = self.bank_forks.write().unwrap();
+        let bank = bank_forks.working_bank();
+
+        // Fill ticks until a new blockhash is recorded, otherwise retried transactions will have
+        // the same signature
+        bank.fill_bank_with_ticks_for_tests();
+        bank.freeze();
+
+        let snapshot = SnapshotId(bank.slot());
+        let bank = Self::insert_working_bank(&mut bank_forks, bank);
+        drop(bank_forks);
+        self.update_working_slot(&bank);
+        snapshot
+    }
+
+    /// Return to the state captured by `snapshot`, discarding every change
+    /// made since.
+    ///
+    /// The test continues on a new working bank forked from the snapshot, in
+    /// the slot after the highest slot seen so far. A snapshot can be restored
+    /// any number of times.
+    pub fn restore(&mut self, snapshot: SnapshotId) -> Result<(), ProgramTestError> {
+        let mut bank_forks = self.bank_forks.write().unwrap();
+        let SnapshotId(snapshot_slot) = snapshot;
+        let bank = bank_forks
+            .get(snapshot_slot)
+            .ok_or(ProgramTestError::SnapshotUnavailable)?;
+
+        let bank = Self::insert_working_bank(&mut bank_forks, bank);
+        drop(bank_forks);
+        self.update_working_slot(&bank);
+        Ok(())
+    }
+
+    /// Insert a child of the frozen `parent` bank after the highest slot, which
+    /// makes it the working bank.
+    fn insert_working_bank(bank_forks: &mut BankForks, parent: Arc<Bank>) -> Arc<Bank> {
+        let slot = bank_forks.highest_slot() + 1;
+        bank_forks
+            .insert(Bank::new_from_parent(parent, &Pubkey::default(), slot))
+            .clone_without_scheduler()
+    }
+
+    fn update_working_slot(&mut self, bank: &Bank) {
+        // Update block commitment cache, otherwise banks server will poll at
+        // the wrong slot
+        self.block_commitment_cache
+            .write()
+            .unwrap()
+            .set_all_slots(bank.slot(), bank.slot());
+        self.last_blockhash = bank.last_blockhash();
+    }
+
     /// Get a new latest blockhash, similar in spirit to RpcClient::get_latest_blockhash()
     pub async fn get_new_latest_blockhash(&mut self) -> io::Result<Hash> {
         let blockhash = self
@@ -1250,6 +1321,27 @@ impl ProgramTestContext {
         Ok(blockhash)
     }
 
+    /// Simulate `transaction` against the working bank without committing it,
+    /// recording an instruction trace of its execution. Export the result's
+    /// `instruction_traces` with [ExecutionTraceFormat::export].
+    pub fn simulate_transaction_with_instruction_traces(
+        &self,
+        transaction: impl Into<VersionedTransaction>,
+    ) -> Result<TransactionSimulationResult, TransactionError> {
+        let bank = self.bank_forks.read().unwrap().working_bank();
+        let transaction = bank.verify_transaction(
+            transaction.into(),
+            TransactionVerificationMode::FullVerification,
+        )?;
+        Ok(bank.simulate_transaction_unchecked_with_config(
+            &transaction,
+            TransactionSimulationConfig {
+                enable_cpi_recording: true,
+                enable_instruction_trace_recording: true,
+            },
+        ))
+    }
+
     /// record a hard fork slot in working bank; should be in the past
     pub fn register_hard_fork(&mut self, hard_fork_slot: Slot) {
         self.bank_forks
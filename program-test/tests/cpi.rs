@@ -5,7 +5,7 @@ use {
     solana_msg::msg,
     solana_program::{instruction::get_stack_height, program::invoke},
     solana_program_entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
-    solana_program_test::{processor, ProgramTest},
+    solana_program_test::{processor, ExecutionTraceFormat, ProgramTest},
     solana_pubkey::Pubkey,
     solana_rent::Rent,
     solana_signer::Signer,
@@ -148,6 +148,55 @@ async fn cpi() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn cpi_instruction_traces() {
+    let invoker_program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "invoker",
+        invoker_program_id,
+        processor!(invoker_process_instruction),
+    );
+    let invoked_program_id = Pubkey::new_unique();
+    program_test.add_program(
+        "invoked",
+        invoked_program_id,
+        processor!(invoked_process_instruction),
+    );
+
+    let context = program_test.start_with_context().await;
+    let instructions = vec![Instruction::new_with_bincode(
+        invoker_program_id,
+        &[0],
+        vec![AccountMeta::new_readonly(invoked_program_id, false)],
+    )];
+
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    let simulation = context
+        .simulate_transaction_with_instruction_traces(transaction)
+        .unwrap();
+    assert_eq!(simulation.result, Ok(()));
+    let instruction_traces = simulation.instruction_traces.unwrap();
+    assert_eq!(
+        instruction_traces
+            .iter()
+            .map(|trace| (trace.program_id, trace.stack_height))
+            .collect::<Vec<_>>(),
+        vec![(invoker_program_id, 1), (invoked_program_id, 2)]
+    );
+
+    let tree = ExecutionTraceFormat::Tree.export(&instruction_traces);
+    assert_eq!(
+        tree[0]["innerInstructions"][0]["programId"],
+        invoked_program_id.to_string()
+    );
+}
+
 #[tokio::test]
 async fn cpi_dupes() {
     let invoker_program_id = Pubkey::new_unique();
@@ -0,0 +1,89 @@
+use {
+    solana_account::AccountSharedData,
+    solana_program_test::{ProgramTest, ProgramTestContext, ProgramTestError},
+    solana_pubkey::Pubkey,
+    solana_signer::Signer,
+    solana_system_interface::instruction as system_instruction,
+    solana_transaction::Transaction,
+};
+
+async fn transfer(context: &mut ProgramTestContext, to: &Pubkey, lamports: u64) {
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &context.payer.pubkey(),
+            to,
+            lamports,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+async fn balance(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    context.banks_client.get_balance(*address).await.unwrap()
+}
+
+#[tokio::test]
+async fn snapshot_and_restore() {
+    let mut context = ProgramTest::default().start_with_context().await;
+    let recipient = Pubkey::new_unique();
+    let rent_exempt_lamports = 1_000_000_000;
+
+    transfer(&mut context, &recipient, rent_exempt_lamports).await;
+    let snapshot = context.snapshot();
+
+    // Branch one: another transfer and an account overwrite.
+    let other = Pubkey::new_unique();
+    transfer(&mut context, &recipient, 1).await;
+    context.set_account(
+        &other,
+        &AccountSharedData::new(1, 0, &solana_system_interface::program::id()),
+    );
+    assert_eq!(
+        balance(&mut context, &recipient).await,
+        rent_exempt_lamports + 1
+    );
+    assert_eq!(balance(&mut context, &other).await, 1);
+
+    // Branch two starts from the snapshot again, and can repeat the transfer.
+    context.restore(snapshot).unwrap();
+    assert_eq!(
+        balance(&mut context, &recipient).await,
+        rent_exempt_lamports
+    );
+    assert_eq!(balance(&mut context, &other).await, 0);
+    transfer(&mut context, &recipient, 1).await;
+    assert_eq!(
+        balance(&mut context, &recipient).await,
+        rent_exempt_lamports + 1
+    );
+
+    // Restoring the same snapshot twice discards branch two as well.
+    context.restore(snapshot).unwrap();
+    assert_eq!(
+        balance(&mut context, &recipient).await,
+        rent_exempt_lamports
+    );
+}
+
+#[tokio::test]
+async fn warp_discards_snapshots() {
+    let mut context = ProgramTest::default().start_with_context().await;
+    let snapshot = context.snapshot();
+
+    context.warp_to_slot(100).unwrap();
+    assert_eq!(
+        context.restore(snapshot),
+        Err(ProgramTestError::SnapshotUnavailable)
+    );
+
+    // Snapshots taken after the warp are still available.
+    let snapshot = context.snapshot();
+    context.restore(snapshot).unwrap();
+}
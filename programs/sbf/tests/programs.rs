@@ -102,6 +102,8 @@ fn load_execute_and_commit_transaction(bank: &Bank, tx: Transaction) -> Transact
                 enable_log_recording: true,
                 enable_return_data_recording: false,
                 enable_transaction_balance_recording: false,
+                enable_instruction_trace_recording: false,
+                enable_instruction_trace_account_data_recording: false,
             },
             &mut ExecuteTimings::default(),
             None,
@@ -5090,6 +5092,8 @@ fn test_function_call_args() {
                 enable_log_recording: false,
                 enable_return_data_recording: true,
                 enable_transaction_balance_recording: false,
+                enable_instruction_trace_recording: false,
+                enable_instruction_trace_account_data_recording: false,
             },
             &mut ExecuteTimings::default(),
             None,
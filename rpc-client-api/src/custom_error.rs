@@ -27,6 +27,9 @@ pub const JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED: i64 = -32016;
 pub const JSON_RPC_SERVER_ERROR_EPOCH_REWARDS_PERIOD_ACTIVE: i64 = -32017;
 pub const JSON_RPC_SERVER_ERROR_SLOT_NOT_EPOCH_BOUNDARY: i64 = -32018;
 pub const JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_UNREACHABLE: i64 = -32019;
+pub const JSON_RPC_SERVER_ERROR_CURSOR_NOT_AVAILABLE: i64 = -32020;
+pub const JSON_RPC_SERVER_ERROR_HISTORICAL_ACCOUNT_STATE_NOT_AVAILABLE: i64 = -32021;
+pub const JSON_RPC_SERVER_ERROR_BUNDLE_NOT_ACCEPTED: i64 = -32022;
 
 #[derive(Error, Debug)]
 pub enum RpcCustomError {
@@ -78,6 +81,12 @@ pub enum RpcCustomError {
     SlotNotEpochBoundary { slot: Slot },
     #[error("LongTermStorageUnreachable")]
     LongTermStorageUnreachable,
+    #[error("CursorNotAvailable")]
+    CursorNotAvailable,
+    #[error("HistoricalAccountStateNotAvailable")]
+    HistoricalAccountStateNotAvailable { slot: Slot, reason: String },
+    #[error("BundleNotAccepted")]
+    BundleNotAccepted { reason: String },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -257,6 +266,24 @@ impl From<RpcCustomError> for Error {
                 message: "Failed to query long-term storage; please try again".to_string(),
                 data: None,
             },
+            RpcCustomError::CursorNotAvailable => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_CURSOR_NOT_AVAILABLE),
+                message: "Cursor expired or is no longer available; restart from the first page"
+                    .to_string(),
+                data: None,
+            },
+            RpcCustomError::HistoricalAccountStateNotAvailable { slot, reason } => Self {
+                code: ErrorCode::ServerError(
+                    JSON_RPC_SERVER_ERROR_HISTORICAL_ACCOUNT_STATE_NOT_AVAILABLE,
+                ),
+                message: format!("Account state at slot {slot} is not available: {reason}"),
+                data: None,
+            },
+            RpcCustomError::BundleNotAccepted { reason } => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_BUNDLE_NOT_ACCEPTED),
+                message: format!("Bundle not accepted: {reason}"),
+                data: None,
+            },
         }
     }
 }
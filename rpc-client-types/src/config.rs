@@ -1,9 +1,10 @@
 use {
     crate::filter::RpcFilterType,
-    solana_account_decoder_client_types::{UiAccountEncoding, UiDataSliceConfig},
+    solana_account_decoder_client_types::{UiAccountData, UiAccountEncoding, UiDataSliceConfig},
     solana_clock::{Epoch, Slot},
     solana_commitment_config::{CommitmentConfig, CommitmentLevel},
     solana_transaction_status_client_types::{TransactionDetails, UiTransactionEncoding},
+    std::collections::HashMap,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -44,6 +45,54 @@ pub struct RpcSimulateTransactionConfig {
     pub min_context_slot: Option<Slot>,
     #[serde(default)]
     pub inner_instructions: bool,
+    pub execution_trace: Option<RpcExecutionTraceFormat>,
+}
+
+/// How `simulateTransaction` returns the trace of the instructions it
+/// executed: their compute units, syscalls, account resizes and timings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcExecutionTraceFormat {
+    /// Instructions nested in the instructions that invoked them
+    Tree,
+    /// Chrome trace event format, for `chrome://tracing` or Perfetto
+    ChromeTrace,
+}
+
+/// Replaces fields of an account for a bundle simulation. Fields that are not
+/// set keep their current value, or the default value if the account does not
+/// exist.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountOverride {
+    pub lamports: Option<u64>,
+    pub owner: Option<String>, // base-58 encoded program id
+    pub data: Option<UiAccountData>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateBundleConfig {
+    #[serde(default)]
+    pub sig_verify: bool,
+    #[serde(default)]
+    pub replace_recent_blockhash: bool,
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    pub encoding: Option<UiTransactionEncoding>,
+    /// Accounts to return before and after each transaction
+    pub accounts: Option<RpcSimulateTransactionAccountsConfig>,
+    pub min_context_slot: Option<Slot>,
+    #[serde(default)]
+    pub inner_instructions: bool,
+    /// Account fields to replace before simulating, keyed by base-58 encoded address
+    pub state_overrides: Option<HashMap<String, RpcAccountOverride>>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSendBundleConfig {
+    pub encoding: Option<UiTransactionEncoding>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -156,6 +205,13 @@ pub struct RpcAccountInfoConfig {
     pub min_context_slot: Option<Slot>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountInfoAtSlotConfig {
+    pub encoding: Option<UiAccountEncoding>,
+    pub data_slice: Option<UiDataSliceConfig>,
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcProgramAccountsConfig {
@@ -166,6 +222,19 @@ pub struct RpcProgramAccountsConfig {
     pub sort_results: Option<bool>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramAccountsV2Config {
+    pub filters: Option<Vec<RpcFilterType>>,
+    #[serde(flatten)]
+    pub account_config: RpcAccountInfoConfig,
+    /// Maximum number of accounts to return
+    pub limit: Option<usize>,
+    /// Cursor returned with the previous page; the commitment and minimum context slot are
+    /// ignored when set because the cursor pins the bank. Cursors expire after a minute.
+    pub cursor: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum RpcTransactionLogsFilter {
@@ -10,6 +10,10 @@ use {
 const MAX_DATA_SIZE: usize = 128;
 const MAX_DATA_BASE58_SIZE: usize = 175;
 const MAX_DATA_BASE64_SIZE: usize = 172;
+/// Maximum number of filters nested within a single `Or` or `Not` filter
+pub const MAX_NESTED_FILTERS: usize = 8;
+/// Maximum nesting depth of `Or` and `Not` filters
+pub const MAX_FILTER_DEPTH: usize = 3;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,6 +21,14 @@ pub enum RpcFilterType {
     DataSize(u64),
     Memcmp(Memcmp),
     TokenAccountState,
+    /// Matches if any of the filters match
+    Or(Vec<RpcFilterType>),
+    /// Matches if the filter does not match
+    Not(Box<RpcFilterType>),
+    /// Matches if the little-endian integer at an offset into the account data is in range
+    DataRange(DataRange),
+    /// Matches if the account lamports are in range
+    Lamports(LamportsRange),
 }
 
 impl RpcFilterType {
@@ -57,6 +69,38 @@ impl RpcFilterType {
                 }
             }
             RpcFilterType::TokenAccountState => Ok(()),
+            RpcFilterType::Or(_) | RpcFilterType::Not(_) => {
+                if self.verify_nested(0)? > MAX_NESTED_FILTERS {
+                    return Err(RpcFilterError::TooManyNestedFilters);
+                }
+                Ok(())
+            }
+            RpcFilterType::DataRange(range) => range.verify(),
+            RpcFilterType::Lamports(range) => range.verify(),
+        }
+    }
+
+    /// Verifies the filters nested within this one and returns their count
+    fn verify_nested(&self, depth: usize) -> Result<usize, RpcFilterError> {
+        match self {
+            RpcFilterType::Or(filters) => {
+                if depth >= MAX_FILTER_DEPTH {
+                    return Err(RpcFilterError::TooDeeplyNested);
+                }
+                if filters.is_empty() {
+                    return Err(RpcFilterError::EmptyOrFilter);
+                }
+                filters.iter().try_fold(0, |count, filter| {
+                    Ok(count + 1 + filter.verify_nested(depth + 1)?)
+                })
+            }
+            RpcFilterType::Not(filter) => {
+                if depth >= MAX_FILTER_DEPTH {
+                    return Err(RpcFilterError::TooDeeplyNested);
+                }
+                Ok(1 + filter.verify_nested(depth + 1)?)
+            }
+            _ => self.verify().map(|()| 0),
         }
     }
 
@@ -69,6 +113,10 @@ impl RpcFilterType {
             RpcFilterType::DataSize(size) => account.data().len() as u64 == *size,
             RpcFilterType::Memcmp(compare) => compare.bytes_match(account.data()),
             RpcFilterType::TokenAccountState => Account::valid_account_data(account.data()),
+            RpcFilterType::Or(filters) => filters.iter().any(|filter| filter.allows(account)),
+            RpcFilterType::Not(filter) => !filter.allows(account),
+            RpcFilterType::DataRange(range) => range.matches(account.data()),
+            RpcFilterType::Lamports(range) => range.contains(account.lamports()),
         }
     }
 }
@@ -77,6 +125,14 @@ impl RpcFilterType {
 pub enum RpcFilterError {
     #[error("encoded binary data should be less than 129 bytes")]
     DataTooLarge,
+    #[error("or filters should contain at least one filter")]
+    EmptyOrFilter,
+    #[error("or and not filters should contain at most 8 nested filters")]
+    TooManyNestedFilters,
+    #[error("or and not filters should be nested at most 3 deep")]
+    TooDeeplyNested,
+    #[error("range minimum should not be greater than its maximum")]
+    EmptyRange,
     #[error("base58 decode error")]
     Base58DecodeError(#[from] bs58::decode::Error),
     #[error("base64 decode error")]
@@ -222,6 +278,101 @@ impl Memcmp {
     }
 }
 
+/// An inclusive range of lamports; a missing bound is unbounded
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LamportsRange {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<u64>,
+}
+
+impl LamportsRange {
+    pub fn verify(&self) -> Result<(), RpcFilterError> {
+        verify_range(self.min, self.max)
+    }
+
+    pub fn contains(&self, lamports: u64) -> bool {
+        range_contains(self.min, self.max, lamports)
+    }
+}
+
+/// An inclusive range of a little-endian integer read from account data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DataRange {
+    /// Data offset of the integer
+    offset: usize,
+    /// Integer type and bounds
+    #[serde(flatten)]
+    range: DataRangeType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum DataRangeType {
+    U64 {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<u64>,
+    },
+    I64 {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<i64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<i64>,
+    },
+}
+
+impl DataRange {
+    pub fn new(offset: usize, range: DataRangeType) -> Self {
+        Self { offset, range }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn range(&self) -> &DataRangeType {
+        &self.range
+    }
+
+    pub fn verify(&self) -> Result<(), RpcFilterError> {
+        match self.range {
+            DataRangeType::U64 { min, max } => verify_range(min, max),
+            DataRangeType::I64 { min, max } => verify_range(min, max),
+        }
+    }
+
+    /// Data that is too short to contain the integer never matches
+    pub fn matches(&self, data: &[u8]) -> bool {
+        let Some(bytes) = self
+            .offset
+            .checked_add(8)
+            .and_then(|end| data.get(self.offset..end))
+        else {
+            return false;
+        };
+        let bytes: [u8; 8] = bytes.try_into().unwrap();
+        match self.range {
+            DataRangeType::U64 { min, max } => range_contains(min, max, u64::from_le_bytes(bytes)),
+            DataRangeType::I64 { min, max } => range_contains(min, max, i64::from_le_bytes(bytes)),
+        }
+    }
+}
+
+fn verify_range<T: PartialOrd>(min: Option<T>, max: Option<T>) -> Result<(), RpcFilterError> {
+    match (min, max) {
+        (Some(min), Some(max)) if min > max => Err(RpcFilterError::EmptyRange),
+        _ => Ok(()),
+    }
+}
+
+fn range_contains<T: PartialOrd>(min: Option<T>, max: Option<T>, value: T) -> bool {
+    min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -323,6 +474,137 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_verify_compound() {
+        let memcmp = RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, vec![1, 2, 3]));
+        assert_eq!(
+            RpcFilterType::Or(vec![memcmp.clone(), RpcFilterType::DataSize(8)]).verify(),
+            Ok(())
+        );
+        assert_eq!(
+            RpcFilterType::Not(Box::new(memcmp.clone())).verify(),
+            Ok(())
+        );
+        assert_eq!(
+            RpcFilterType::Or(vec![]).verify(),
+            Err(RpcFilterError::EmptyOrFilter)
+        );
+
+        // Nested filters are verified
+        assert_eq!(
+            RpcFilterType::Not(Box::new(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                0,
+                vec![0; MAX_DATA_SIZE + 1],
+            ))))
+            .verify(),
+            Err(RpcFilterError::DataTooLarge)
+        );
+
+        // Nesting limits
+        let mut filter = memcmp.clone();
+        for _ in 0..MAX_FILTER_DEPTH {
+            filter = RpcFilterType::Not(Box::new(filter));
+        }
+        assert_eq!(filter.verify(), Ok(()));
+        let filter = RpcFilterType::Not(Box::new(filter));
+        assert_eq!(filter.verify(), Err(RpcFilterError::TooDeeplyNested));
+
+        assert_eq!(
+            RpcFilterType::Or(vec![memcmp.clone(); MAX_NESTED_FILTERS]).verify(),
+            Ok(())
+        );
+        assert_eq!(
+            RpcFilterType::Or(vec![memcmp; MAX_NESTED_FILTERS + 1]).verify(),
+            Err(RpcFilterError::TooManyNestedFilters)
+        );
+    }
+
+    #[test]
+    fn test_verify_range() {
+        let range = |min, max| LamportsRange { min, max };
+        assert_eq!(range(None, None).verify(), Ok(()));
+        assert_eq!(range(Some(1), Some(1)).verify(), Ok(()));
+        assert_eq!(
+            range(Some(2), Some(1)).verify(),
+            Err(RpcFilterError::EmptyRange)
+        );
+        assert_eq!(
+            RpcFilterType::DataRange(DataRange::new(
+                0,
+                DataRangeType::I64 {
+                    min: Some(1),
+                    max: Some(-1),
+                },
+            ))
+            .verify(),
+            Err(RpcFilterError::EmptyRange)
+        );
+    }
+
+    #[test]
+    fn test_data_range_matches() {
+        let mut data = vec![0u8; 4];
+        data.extend_from_slice(&(-5i64).to_le_bytes());
+
+        let u64_range = |min, max| DataRange::new(4, DataRangeType::U64 { min, max });
+        assert!(u64_range(None, None).matches(&data));
+        assert!(u64_range(Some(u64::MAX - 4), None).matches(&data));
+        assert!(!u64_range(None, Some(u64::MAX - 5)).matches(&data));
+
+        let i64_range = |min, max| DataRange::new(4, DataRangeType::I64 { min, max });
+        assert!(i64_range(Some(-5), Some(-5)).matches(&data));
+        assert!(i64_range(None, Some(0)).matches(&data));
+        assert!(!i64_range(Some(-4), None).matches(&data));
+
+        // Data too short
+        assert!(!DataRange::new(
+            5,
+            DataRangeType::U64 {
+                min: None,
+                max: None
+            }
+        )
+        .matches(&data));
+        assert!(!DataRange::new(
+            usize::MAX,
+            DataRangeType::U64 {
+                min: None,
+                max: None
+            }
+        )
+        .matches(&data));
+    }
+
+    #[test]
+    fn test_compound_filter_deserialize() {
+        let filter: RpcFilterType = serde_json::from_str(
+            r#"{"or":[{"not":{"dataSize":8}},{"lamports":{"min":10}},{"dataRange":{"offset":8,"type":"i64","max":-1}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            filter,
+            RpcFilterType::Or(vec![
+                RpcFilterType::Not(Box::new(RpcFilterType::DataSize(8))),
+                RpcFilterType::Lamports(LamportsRange {
+                    min: Some(10),
+                    max: None,
+                }),
+                RpcFilterType::DataRange(DataRange::new(
+                    8,
+                    DataRangeType::I64 {
+                        min: None,
+                        max: Some(-1),
+                    },
+                )),
+            ])
+        );
+        assert_eq!(
+            serde_json::from_str::<RpcFilterType>(&serde_json::to_string(&filter).unwrap())
+                .unwrap(),
+            filter
+        );
+    }
+
     const BASE58_STR: &str = "Bpf4ERpEvSFmCSTNh1PzTWTkALrKXvMXEdthxHuwCQcf";
     const BASE64_STR: &str = "oMoycDvJzrjQpCfukbO4VW/FLGLfnbqBEc9KUEVgj2g=";
     const BYTES: [u8; 4] = [0, 1, 2, 3];
@@ -12,6 +12,7 @@ pub enum RpcRequest {
     Custom { method: &'static str },
     DeregisterNode,
     GetAccountInfo,
+    GetAccountInfoAtSlot,
     GetBalance,
     GetBlock,
     GetBlockHeight,
@@ -38,6 +39,7 @@ pub enum RpcRequest {
     GetMinimumBalanceForRentExemption,
     GetMultipleAccounts,
     GetProgramAccounts,
+    GetProgramAccountsV2,
     GetRecentPerformanceSamples,
     GetRecentPrioritizationFees,
     GetHighestSnapshotSlot,
@@ -65,7 +67,9 @@ pub enum RpcRequest {
     MinimumLedgerSlot,
     RegisterNode,
     RequestAirdrop,
+    SendBundle,
     SendTransaction,
+    SimulateBundle,
     SimulateTransaction,
     SignVote,
 }
@@ -77,6 +81,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::Custom { method } => method,
             RpcRequest::DeregisterNode => "deregisterNode",
             RpcRequest::GetAccountInfo => "getAccountInfo",
+            RpcRequest::GetAccountInfoAtSlot => "getAccountInfoAtSlot",
             RpcRequest::GetBalance => "getBalance",
             RpcRequest::GetBlock => "getBlock",
             RpcRequest::GetBlockHeight => "getBlockHeight",
@@ -103,6 +108,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetMinimumBalanceForRentExemption => "getMinimumBalanceForRentExemption",
             RpcRequest::GetMultipleAccounts => "getMultipleAccounts",
             RpcRequest::GetProgramAccounts => "getProgramAccounts",
+            RpcRequest::GetProgramAccountsV2 => "getProgramAccountsV2",
             RpcRequest::GetRecentPerformanceSamples => "getRecentPerformanceSamples",
             RpcRequest::GetRecentPrioritizationFees => "getRecentPrioritizationFees",
             RpcRequest::GetHighestSnapshotSlot => "getHighestSnapshotSlot",
@@ -130,7 +136,9 @@ impl fmt::Display for RpcRequest {
             RpcRequest::MinimumLedgerSlot => "minimumLedgerSlot",
             RpcRequest::RegisterNode => "registerNode",
             RpcRequest::RequestAirdrop => "requestAirdrop",
+            RpcRequest::SendBundle => "sendBundle",
             RpcRequest::SendTransaction => "sendTransaction",
+            RpcRequest::SimulateBundle => "simulateBundle",
             RpcRequest::SimulateTransaction => "simulateTransaction",
             RpcRequest::SignVote => "signVote",
         };
@@ -147,7 +155,10 @@ pub const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT: usize = 1_000;
 pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
 pub const NUM_LARGEST_ACCOUNTS: usize = 20;
 pub const MAX_GET_PROGRAM_ACCOUNT_FILTERS: usize = 4;
+pub const MAX_GET_PROGRAM_ACCOUNTS_V2_LIMIT: usize = 10_000;
 pub const MAX_GET_SLOT_LEADERS: usize = 5000;
+pub const MAX_SIMULATE_BUNDLE_TRANSACTIONS: usize = 16;
+pub const MAX_SIMULATE_BUNDLE_STATE_OVERRIDES: usize = 100;
 
 // Limit the length of the `epoch_credits` array for each validator in a `get_vote_accounts`
 // response
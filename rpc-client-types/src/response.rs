@@ -163,6 +163,14 @@ pub struct RpcKeyedAccount {
     pub account: UiAccount,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcKeyedAccountsPage {
+    pub accounts: Vec<RpcKeyedAccount>,
+    /// Cursor of the next page, or `None` if this is the last page
+    pub cursor: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub struct SlotInfo {
     pub slot: Slot,
@@ -405,6 +413,31 @@ pub struct RpcSimulateTransactionResult {
     pub return_data: Option<UiTransactionReturnData>,
     pub inner_instructions: Option<Vec<UiInnerInstructions>>,
     pub replacement_blockhash: Option<RpcBlockhash>,
+    /// Present when requested with `executionTrace`, in the requested format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_trace: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateBundleTransactionResult {
+    pub err: Option<UiTransactionError>,
+    pub logs: Option<Vec<String>>,
+    /// The requested accounts before the transaction, `None` if they do not exist
+    pub pre_accounts: Option<Vec<Option<UiAccount>>>,
+    /// The requested accounts after the transaction, `None` if they do not exist
+    pub post_accounts: Option<Vec<Option<UiAccount>>>,
+    pub units_consumed: Option<u64>,
+    pub loaded_accounts_data_size: Option<u32>,
+    pub return_data: Option<UiTransactionReturnData>,
+    pub inner_instructions: Option<Vec<UiInnerInstructions>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateBundleResult {
+    pub transaction_results: Vec<RpcSimulateBundleTransactionResult>,
+    pub replacement_blockhash: Option<RpcBlockhash>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
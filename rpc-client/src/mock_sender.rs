@@ -355,7 +355,8 @@ impl RpcSender for MockSender {
                     loaded_accounts_data_size: None,
                     return_data: None,
                     inner_instructions: None,
-                    replacement_blockhash: None
+                    replacement_blockhash: None,
+                    execution_trace: None,
                 },
             })?,
             "getMinimumBalanceForRentExemption" => json![20],
@@ -5,6 +5,7 @@ use {
     log::*,
     reqwest::{self, header::CONTENT_TYPE},
     serde_json::{json, Value},
+    solana_account::{AccountSharedData, ReadableAccount},
     solana_account_decoder::UiAccount,
     solana_client::connection_cache::ConnectionCache,
     solana_commitment_config::CommitmentConfig,
@@ -18,6 +19,7 @@ use {
     solana_rpc_client_api::{
         client_error::{ErrorKind as ClientErrorKind, Result as ClientResult},
         config::{RpcAccountInfoConfig, RpcSignatureSubscribeConfig, RpcSimulateTransactionConfig},
+        filter::RpcFilterType,
         request::RpcError,
         response::{Response as RpcResponse, RpcSignatureResult, SlotUpdate},
     },
@@ -25,7 +27,7 @@ use {
     solana_signer::Signer,
     solana_streamer::socket::SocketAddrSpace,
     solana_system_transaction as system_transaction,
-    solana_test_validator::TestValidator,
+    solana_test_validator::{TestValidator, TestValidatorGenesis},
     solana_tpu_client::tpu_client::{TpuClient, TpuClientConfig, DEFAULT_TPU_CONNECTION_POOL_SIZE},
     solana_transaction::Transaction,
     solana_transaction_status::TransactionStatus,
@@ -181,6 +183,53 @@ fn test_simulation_replaced_blockhash() -> ClientResult<()> {
     Ok(())
 }
 
+#[test]
+fn test_clone_program_accounts_and_lazy_clone() {
+    solana_logger::setup();
+
+    let owner = Pubkey::new_unique();
+    let cloned = Pubkey::new_unique();
+    let lazily_cloned = Pubkey::new_unique();
+    let lamports = Rent::default().minimum_balance(16);
+    let (source, _) = TestValidatorGenesis::default()
+        .add_account(cloned, AccountSharedData::new(lamports, 8, &owner))
+        .add_account(lazily_cloned, AccountSharedData::new(lamports, 16, &owner))
+        .start();
+    let source_client = source.get_rpc_client();
+    let slot = source_client.get_slot().unwrap();
+
+    let mut genesis = TestValidatorGenesis::default();
+    genesis
+        .clone_program_accounts(
+            &owner,
+            vec![RpcFilterType::DataSize(8)],
+            &source_client,
+            slot,
+        )
+        .unwrap()
+        .lazy_clone_accounts(Arc::new(source.get_rpc_client()), slot);
+    let (validator, mint_keypair) = genesis.start();
+    let rpc_client = validator.get_rpc_client();
+
+    assert_eq!(rpc_client.get_account(&cloned).unwrap().data().len(), 8);
+    // Reading an account over RPC does not clone it, only transactions do.
+    assert!(rpc_client.get_account(&lazily_cloned).is_err());
+
+    let blockhash = rpc_client.get_latest_blockhash().unwrap();
+    rpc_client
+        .send_and_confirm_transaction(&system_transaction::transfer(
+            &mint_keypair,
+            &lazily_cloned,
+            42,
+            blockhash,
+        ))
+        .unwrap();
+    let account = rpc_client.get_account(&lazily_cloned).unwrap();
+    assert_eq!(account.lamports(), lamports + 42);
+    assert_eq!(account.data().len(), 16);
+    assert_eq!(account.owner(), &owner);
+}
+
 #[test]
 fn test_rpc_invalid_requests() {
     solana_logger::setup();
@@ -9,5 +9,9 @@ pub fn filter_allows(filter: &RpcFilterType, account: &AccountSharedData) -> boo
         RpcFilterType::DataSize(size) => account.data().len() as u64 == *size,
         RpcFilterType::Memcmp(compare) => compare.bytes_match(account.data()),
         RpcFilterType::TokenAccountState => Account::valid_account_data(account.data()),
+        RpcFilterType::Or(filters) => filters.iter().any(|filter| filter_allows(filter, account)),
+        RpcFilterType::Not(filter) => !filter_allows(filter, account),
+        RpcFilterType::DataRange(range) => range.matches(account.data()),
+        RpcFilterType::Lamports(range) => range.contains(account.lamports()),
     }
 }
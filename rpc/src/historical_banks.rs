@@ -0,0 +1,331 @@
+//! Reconstruction of banks at past rooted slots, used to serve historical account state
+//!
+//! Banks are rebuilt from the nearest snapshot archives at or below the requested slot plus a
+//! replay of the blockstore, in a scratch directory owned by this cache.  The blockstore is
+//! opened with secondary access for the replay, so that it never writes to the ledger of the
+//! validator.  Reconstruction is expensive, so only one bank is rebuilt at a time and the most
+//! recently used banks are kept around.  Requests for another slot fail while a bank is being
+//! rebuilt rather than queue up behind it.
+//!
+//! The scratch directory of a bank is deleted when the bank is dropped, so that requests still
+//! using a bank evicted from the cache can complete.
+//!
+//! Every bank unpacks its snapshot archives into its own scratch directory, so the scratch
+//! directory needs room for `MAX_CACHED_HISTORICAL_BANKS + 1` full copies of the accounts: one
+//! for each cached bank and one for the bank being rebuilt.  Evicted banks still in use by a
+//! request hold on to theirs a little longer.
+
+use {
+    solana_accounts_db::{
+        hardened_unpack::{open_genesis_config, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE},
+        utils::{
+            create_all_accounts_run_and_snapshot_dirs, move_and_async_delete_path,
+            move_and_async_delete_path_contents,
+        },
+    },
+    solana_clock::Slot,
+    solana_genesis_config::GenesisConfig,
+    solana_ledger::{
+        bank_forks_utils::{self, BankForksUtilsError},
+        blockstore::{Blockstore, BlockstoreError},
+        blockstore_options::{AccessType, BlockstoreOptions},
+        blockstore_processor::ProcessOptions,
+        use_snapshot_archives_at_startup::UseSnapshotArchivesAtStartup,
+    },
+    solana_runtime::{
+        bank::{Bank, DropCallback},
+        snapshot_config::SnapshotConfig,
+    },
+    std::{
+        collections::VecDeque,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc, Mutex, OnceLock, TryLockError,
+        },
+    },
+    thiserror::Error,
+};
+
+/// The number of reconstructed banks kept in memory, each with a full copy of the accounts on
+/// disk
+pub const MAX_CACHED_HISTORICAL_BANKS: usize = 2;
+
+#[derive(Error, Debug)]
+pub enum HistoricalBankError {
+    #[error("failed to open genesis config: {0}")]
+    OpenGenesisConfig(String),
+
+    #[error("failed to prepare scratch directory '{path}': {source}")]
+    ScratchDirectory {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+
+    #[error("failed to open blockstore: {0}")]
+    OpenBlockstore(#[from] BlockstoreError),
+
+    #[error(transparent)]
+    Load(#[from] BankForksUtilsError),
+
+    #[error("busy reconstructing the bank for slot {0}, try again later")]
+    Busy(Slot),
+}
+
+/// Deletes the scratch directory of a reconstructed bank once the bank is dropped
+#[derive(Debug, Clone)]
+struct DeleteScratchDirOnDrop(PathBuf);
+
+impl DropCallback for DeleteScratchDirOnDrop {
+    fn callback(&self, _bank: &Bank) {
+        move_and_async_delete_path(&self.0);
+    }
+
+    fn clone_box(&self) -> Box<dyn DropCallback + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+pub struct HistoricalBanks {
+    scratch_dir: PathBuf,
+    genesis_config: OnceLock<GenesisConfig>,
+    /// Reconstructed banks, with the most recently used at the back
+    banks: Mutex<VecDeque<Arc<Bank>>>,
+    /// Held while a bank is being reconstructed
+    load_lock: Mutex<()>,
+    /// The slot of the bank being reconstructed
+    loading_slot: Mutex<Option<Slot>>,
+    /// Distinguishes the scratch directories of banks of the same slot, as a bank evicted from
+    /// the cache may still be in use while the slot is reconstructed again
+    next_scratch_id: AtomicU64,
+    exit: Arc<AtomicBool>,
+}
+
+impl HistoricalBanks {
+    pub fn new(scratch_dir: PathBuf, exit: Arc<AtomicBool>) -> Self {
+        // Remove the banks left behind by a previous run
+        move_and_async_delete_path_contents(&scratch_dir);
+        Self {
+            scratch_dir,
+            genesis_config: OnceLock::new(),
+            banks: Mutex::new(VecDeque::with_capacity(MAX_CACHED_HISTORICAL_BANKS)),
+            load_lock: Mutex::new(()),
+            loading_slot: Mutex::default(),
+            next_scratch_id: AtomicU64::default(),
+            exit,
+        }
+    }
+
+    /// Get the bank for the rooted `slot`, reconstructing it if it is not cached
+    ///
+    /// This blocks for as long as the reconstruction takes and must not be called from an async
+    /// context.  Fails with `HistoricalBankError::Busy` if a bank for another slot is being
+    /// reconstructed.
+    pub fn get_or_load(
+        &self,
+        slot: Slot,
+        ledger_path: &Path,
+        snapshot_config: Option<&SnapshotConfig>,
+    ) -> Result<Arc<Bank>, HistoricalBankError> {
+        if let Some(bank) = self.get_cached(slot) {
+            return Ok(bank);
+        }
+
+        let _load_guard = match self.load_lock.try_lock() {
+            Ok(load_guard) => load_guard,
+            Err(TryLockError::WouldBlock) => {
+                let loading_slot = *self.loading_slot.lock().unwrap();
+                match loading_slot {
+                    Some(loading_slot) if loading_slot != slot => {
+                        return Err(HistoricalBankError::Busy(loading_slot));
+                    }
+                    // Wait for the bank of this slot to be reconstructed
+                    _ => self.load_lock.lock().unwrap(),
+                }
+            }
+            Err(TryLockError::Poisoned(err)) => panic!("{err}"),
+        };
+        // Another request may have loaded the bank while we were waiting
+        if let Some(bank) = self.get_cached(slot) {
+            return Ok(bank);
+        }
+
+        *self.loading_slot.lock().unwrap() = Some(slot);
+        let result = self.load(slot, ledger_path, snapshot_config);
+        *self.loading_slot.lock().unwrap() = None;
+        result
+    }
+
+    fn load(
+        &self,
+        slot: Slot,
+        ledger_path: &Path,
+        snapshot_config: Option<&SnapshotConfig>,
+    ) -> Result<Arc<Bank>, HistoricalBankError> {
+        let genesis_config = self.genesis_config(ledger_path)?;
+        let blockstore = Blockstore::open_with_options(
+            ledger_path,
+            BlockstoreOptions {
+                access_type: AccessType::Secondary,
+                ..BlockstoreOptions::default()
+            },
+        )?;
+        let slot_dir = self.scratch_dir.join(format!(
+            "{slot}-{}",
+            self.next_scratch_id.fetch_add(1, Ordering::Relaxed)
+        ));
+        let bank_snapshots_dir = slot_dir.join("snapshot");
+        let (account_run_paths, _) =
+            create_all_accounts_run_and_snapshot_dirs(&[slot_dir.join("accounts")])
+                .and_then(|paths| std::fs::create_dir_all(&bank_snapshots_dir).map(|_| paths))
+                .map_err(|err| HistoricalBankError::ScratchDirectory {
+                    source: err,
+                    path: slot_dir.clone(),
+                })?;
+
+        let snapshot_config = match snapshot_config {
+            Some(snapshot_config) => SnapshotConfig {
+                full_snapshot_archives_dir: snapshot_config.full_snapshot_archives_dir.clone(),
+                incremental_snapshot_archives_dir: snapshot_config
+                    .incremental_snapshot_archives_dir
+                    .clone(),
+                bank_snapshots_dir,
+                ..SnapshotConfig::new_load_only()
+            },
+            None => SnapshotConfig::new_disabled(),
+        };
+        let process_options = ProcessOptions {
+            use_snapshot_archives_at_startup: UseSnapshotArchivesAtStartup::Always,
+            ..ProcessOptions::default()
+        };
+
+        info!("Reconstructing bank for historical slot {slot}");
+        let result = bank_forks_utils::load_bank_at_slot(
+            genesis_config,
+            &blockstore,
+            account_run_paths,
+            &snapshot_config,
+            process_options,
+            slot,
+            self.exit.clone(),
+        );
+        let bank = match result {
+            Ok(bank) => bank,
+            Err(err) => {
+                move_and_async_delete_path(&slot_dir);
+                return Err(err.into());
+            }
+        };
+        bank.set_callback(Some(Box::new(DeleteScratchDirOnDrop(slot_dir))));
+
+        let mut banks = self.banks.lock().unwrap();
+        if banks.len() >= MAX_CACHED_HISTORICAL_BANKS {
+            // Requests may still hold the evicted bank, which deletes its scratch directory once
+            // it is dropped
+            banks.pop_front();
+        }
+        banks.push_back(Arc::clone(&bank));
+        Ok(bank)
+    }
+
+    fn get_cached(&self, slot: Slot) -> Option<Arc<Bank>> {
+        let mut banks = self.banks.lock().unwrap();
+        let position = banks.iter().position(|bank| bank.slot() == slot)?;
+        let bank = banks.remove(position)?;
+        banks.push_back(Arc::clone(&bank));
+        Some(bank)
+    }
+
+    fn genesis_config(&self, ledger_path: &Path) -> Result<&GenesisConfig, HistoricalBankError> {
+        if let Some(genesis_config) = self.genesis_config.get() {
+            return Ok(genesis_config);
+        }
+        let genesis_config = open_genesis_config(ledger_path, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE)
+            .map_err(|err| HistoricalBankError::OpenGenesisConfig(err.to_string()))?;
+        Ok(self.genesis_config.get_or_init(|| genesis_config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_ledger::{
+            blockstore_processor::fill_blockstore_slot_with_ticks,
+            create_new_tmp_ledger_auto_delete, genesis_utils::create_genesis_config,
+        },
+        std::fs,
+    };
+
+    #[test]
+    fn test_get_or_load_historical_bank() {
+        let genesis_config = create_genesis_config(10_000).genesis_config;
+        let ticks_per_slot = genesis_config.ticks_per_slot;
+        let (ledger_path, mut last_entry_hash) =
+            create_new_tmp_ledger_auto_delete!(&genesis_config);
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        for slot in 1..=3 {
+            last_entry_hash = fill_blockstore_slot_with_ticks(
+                &blockstore,
+                ticks_per_slot,
+                slot,
+                slot - 1,
+                last_entry_hash,
+            );
+        }
+        blockstore.set_roots([0, 1, 2, 3].iter()).unwrap();
+
+        let scratch_dir = tempfile::tempdir().unwrap();
+        let historical_banks = HistoricalBanks::new(
+            scratch_dir.path().to_path_buf(),
+            Arc::new(AtomicBool::new(false)),
+        );
+        let scratch_dirs = || {
+            fs::read_dir(scratch_dir.path())
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+                .filter(|name| !name.ends_with("_to_be_deleted"))
+                .count()
+        };
+
+        let bank = historical_banks
+            .get_or_load(2, ledger_path.path(), None)
+            .unwrap();
+        assert_eq!(bank.slot(), 2);
+        assert!(bank.is_frozen());
+        assert_eq!(bank.parent_slot(), 1);
+        // Replaying with secondary access leaves the blockstore untouched
+        assert_eq!(blockstore.get_bank_hash(2), None);
+        assert!(Arc::ptr_eq(
+            &bank,
+            &historical_banks
+                .get_or_load(2, ledger_path.path(), None)
+                .unwrap()
+        ));
+
+        // Other slots cannot be reconstructed while a bank is being reconstructed
+        let load_guard = historical_banks.load_lock.lock().unwrap();
+        *historical_banks.loading_slot.lock().unwrap() = Some(1);
+        assert!(matches!(
+            historical_banks.get_or_load(3, ledger_path.path(), None),
+            Err(HistoricalBankError::Busy(1))
+        ));
+        *historical_banks.loading_slot.lock().unwrap() = None;
+        drop(load_guard);
+
+        // Slots which are not rooted cannot be reconstructed
+        assert!(historical_banks
+            .get_or_load(4, ledger_path.path(), None)
+            .is_err());
+
+        // Evicting the bank keeps its scratch directory until it is dropped
+        for slot in [1, 3] {
+            historical_banks
+                .get_or_load(slot, ledger_path.path(), None)
+                .unwrap();
+        }
+        assert_eq!(scratch_dirs(), 3);
+        drop(bank);
+        assert_eq!(scratch_dirs(), 2);
+    }
+}
@@ -1,6 +1,7 @@
 #![allow(clippy::arithmetic_side_effects)]
 mod cluster_tpu_info;
 pub mod filter;
+pub mod historical_banks;
 pub mod max_slots;
 pub mod optimistically_confirmed_bank_tracker;
 pub mod parsed_token_accounts;
@@ -3,30 +3,36 @@
 use solana_runtime::installed_scheduler_pool::BankWithScheduler;
 use {
     crate::{
-        filter::filter_allows, max_slots::MaxSlots,
+        filter::filter_allows,
+        historical_banks::HistoricalBanks,
+        max_slots::MaxSlots,
         optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
-        parsed_token_accounts::*, rpc_cache::LargestAccountsCache, rpc_health::*,
+        parsed_token_accounts::*,
+        rpc_cache::{LargestAccountsCache, ProgramAccountsCursorCache, ProgramAccountsCursorId},
+        rpc_health::*,
     },
+    agave_banking_stage_ingress_types::{BankingBundleSender, MAX_BUNDLE_LEN},
     base64::{prelude::BASE64_STANDARD, Engine},
     bincode::{config::Options, serialize},
-    crossbeam_channel::{unbounded, Receiver, Sender},
+    crossbeam_channel::{unbounded, Receiver, Sender, TrySendError},
     jsonrpc_core::{
         futures::future::{self, FutureExt, OptionFuture},
         types::error,
         BoxFuture, Error, Metadata, Result,
     },
     jsonrpc_derive::rpc,
-    solana_account::{AccountSharedData, ReadableAccount},
+    solana_account::{AccountSharedData, ReadableAccount, WritableAccount},
     solana_account_decoder::{
         encode_ui_account,
         parse_account_data::SplTokenAdditionalDataV2,
         parse_token::{is_known_spl_token_id, token_amount_to_ui_amount_v3, UiTokenAmount},
-        UiAccount, UiAccountEncoding, UiDataSliceConfig, MAX_BASE58_BYTES,
+        UiAccount, UiAccountData, UiAccountEncoding, UiDataSliceConfig, MAX_BASE58_BYTES,
     },
     solana_accounts_db::{
         accounts::AccountAddressFilter,
         accounts_index::{
-            AccountIndex, AccountSecondaryIndexes, IndexKey, ScanConfig, ScanOrder, ScanResult,
+            AccountIndex, AccountSecondaryIndexes, IndexKey, ScanConfig, ScanError, ScanOrder,
+            ScanResult,
         },
     },
     solana_client::connection_cache::Protocol,
@@ -47,8 +53,9 @@ use {
     },
     solana_message::{AddressLoader, SanitizedMessage},
     solana_metrics::inc_new_counter_info,
-    solana_perf::packet::PACKET_DATA_SIZE,
+    solana_perf::packet::{to_packet_batches, PACKET_DATA_SIZE},
     solana_program_pack::Pack,
+    solana_program_runtime::execution_trace::ExecutionTraceFormat,
     solana_pubkey::{Pubkey, PUBKEY_BYTES},
     solana_rpc_client_api::{
         config::*,
@@ -57,14 +64,18 @@ use {
         request::{
             TokenAccountsFilter, DELINQUENT_VALIDATOR_SLOT_DISTANCE,
             MAX_GET_CONFIRMED_BLOCKS_RANGE, MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
-            MAX_GET_PROGRAM_ACCOUNT_FILTERS, MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS,
-            MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS,
-            MAX_RPC_VOTE_ACCOUNT_INFO_EPOCH_CREDITS_HISTORY, NUM_LARGEST_ACCOUNTS,
+            MAX_GET_PROGRAM_ACCOUNTS_V2_LIMIT, MAX_GET_PROGRAM_ACCOUNT_FILTERS,
+            MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS, MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS,
+            MAX_RPC_VOTE_ACCOUNT_INFO_EPOCH_CREDITS_HISTORY, MAX_SIMULATE_BUNDLE_STATE_OVERRIDES,
+            MAX_SIMULATE_BUNDLE_TRANSACTIONS, NUM_LARGEST_ACCOUNTS,
         },
         response::{Response as RpcResponse, *},
     },
     solana_runtime::{
-        bank::{Bank, TransactionSimulationResult},
+        bank::{
+            Bank, BundleTransactionSimulationResult, TransactionSimulationConfig,
+            TransactionSimulationResult,
+        },
         bank_forks::BankForks,
         commitment::{BlockCommitmentArray, BlockCommitmentCache},
         non_circulating_supply::{calculate_non_circulating_supply, NonCirculatingSupply},
@@ -77,7 +88,7 @@ use {
     solana_signature::Signature,
     solana_signer::Signer,
     solana_stake_program,
-    solana_storage_bigtable::Error as StorageError,
+    solana_storage_bigtable::{Error as StorageError, LongTermLedgerStorage},
     solana_transaction::{
         sanitized::{MessageHash, SanitizedTransaction, MAX_TX_ACCOUNT_LOCKS},
         versioned::VersionedTransaction,
@@ -110,6 +121,7 @@ use {
         collections::{BinaryHeap, HashMap, HashSet},
         convert::TryFrom,
         net::SocketAddr,
+        path::PathBuf,
         str::FromStr,
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
@@ -141,6 +153,10 @@ type RpcCustomResult<T> = std::result::Result<T, RpcCustomError>;
 
 pub const MAX_REQUEST_BODY_SIZE: usize = 50 * (1 << 10); // 50kB
 pub const PERFORMANCE_SAMPLES_LIMIT: usize = 720;
+/// Maximum number of `getProgramAccountsV2` cursors, each pinning a bank
+const MAX_PROGRAM_ACCOUNTS_CURSORS: usize = 1_000;
+/// Time after which a `getProgramAccountsV2` cursor expires
+const PROGRAM_ACCOUNTS_CURSOR_TTL: Duration = Duration::from_secs(60);
 
 fn new_response<T>(bank: &Bank, value: T) -> RpcResponse<T> {
     RpcResponse {
@@ -177,6 +193,12 @@ pub struct JsonRpcConfig {
     pub max_request_body_size: Option<usize>,
     /// Disable the health check, used for tests and TestValidator
     pub disable_health_check: bool,
+    /// Serve `getAccountInfoAtSlot` for slots no longer in bank forks by reconstructing banks
+    /// from snapshot archives and the blockstore, using this scratch directory
+    pub historical_account_state_path: Option<PathBuf>,
+    /// Forward bundles received with `sendBundle` to the banking stage. Bundles are rejected
+    /// when unset
+    pub bundle_sender: Option<BankingBundleSender>,
 }
 
 impl Default for JsonRpcConfig {
@@ -197,6 +219,8 @@ impl Default for JsonRpcConfig {
             rpc_scan_and_fix_roots: Default::default(),
             max_request_body_size: Option::default(),
             disable_health_check: Default::default(),
+            historical_account_state_path: Option::default(),
+            bundle_sender: Option::default(),
         }
     }
 }
@@ -218,6 +242,8 @@ pub struct RpcBigtableConfig {
     pub bigtable_app_profile_id: String,
     pub timeout: Option<Duration>,
     pub max_message_size: usize,
+    /// Serve and upload long-term ledger storage from this local directory instead of BigTable
+    pub long_term_storage_path: Option<PathBuf>,
 }
 
 impl Default for RpcBigtableConfig {
@@ -230,6 +256,7 @@ impl Default for RpcBigtableConfig {
             bigtable_app_profile_id,
             timeout: None,
             max_message_size: solana_storage_bigtable::DEFAULT_MAX_MESSAGE_SIZE,
+            long_term_storage_path: None,
         }
     }
 }
@@ -247,7 +274,7 @@ pub struct JsonRpcRequestProcessor {
     cluster_info: Arc<ClusterInfo>,
     genesis_hash: Hash,
     transaction_sender: Sender<TransactionInfo>,
-    bigtable_ledger_storage: Option<solana_storage_bigtable::LedgerStorage>,
+    bigtable_ledger_storage: Option<Arc<dyn LongTermLedgerStorage>>,
     optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
     largest_accounts_cache: Arc<RwLock<LargestAccountsCache>>,
     max_slots: Arc<MaxSlots>,
@@ -255,6 +282,8 @@ pub struct JsonRpcRequestProcessor {
     max_complete_transaction_status_slot: Arc<AtomicU64>,
     prioritization_fee_cache: Arc<PrioritizationFeeCache>,
     runtime: Arc<Runtime>,
+    historical_banks: Option<Arc<HistoricalBanks>>,
+    program_accounts_cursors: Arc<ProgramAccountsCursorCache>,
 }
 impl Metadata for JsonRpcRequestProcessor {}
 
@@ -410,7 +439,7 @@ impl JsonRpcRequestProcessor {
         health: Arc<RpcHealth>,
         cluster_info: Arc<ClusterInfo>,
         genesis_hash: Hash,
-        bigtable_ledger_storage: Option<solana_storage_bigtable::LedgerStorage>,
+        bigtable_ledger_storage: Option<Arc<dyn LongTermLedgerStorage>>,
         optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
         largest_accounts_cache: Arc<RwLock<LargestAccountsCache>>,
         max_slots: Arc<MaxSlots>,
@@ -420,6 +449,14 @@ impl JsonRpcRequestProcessor {
         runtime: Arc<Runtime>,
     ) -> (Self, Receiver<TransactionInfo>) {
         let (transaction_sender, transaction_receiver) = unbounded();
+        let historical_banks = config.historical_account_state_path.clone().map(|path| {
+            let exit = Arc::new(AtomicBool::new(false));
+            validator_exit.write().unwrap().register_exit(Box::new({
+                let exit = exit.clone();
+                move || exit.store(true, Ordering::Relaxed)
+            }));
+            Arc::new(HistoricalBanks::new(path, exit))
+        });
         (
             Self {
                 config,
@@ -440,6 +477,11 @@ impl JsonRpcRequestProcessor {
                 max_complete_transaction_status_slot,
                 prioritization_fee_cache,
                 runtime,
+                historical_banks,
+                program_accounts_cursors: Arc::new(ProgramAccountsCursorCache::new(
+                    MAX_PROGRAM_ACCOUNTS_CURSORS,
+                    PROGRAM_ACCOUNTS_CURSOR_TTL,
+                )),
             },
             transaction_receiver,
         )
@@ -527,6 +569,11 @@ impl JsonRpcRequestProcessor {
             max_complete_transaction_status_slot: Arc::new(AtomicU64::default()),
             prioritization_fee_cache: Arc::new(PrioritizationFeeCache::default()),
             runtime,
+            historical_banks: None,
+            program_accounts_cursors: Arc::new(ProgramAccountsCursorCache::new(
+                MAX_PROGRAM_ACCOUNTS_CURSORS,
+                PROGRAM_ACCOUNTS_CURSOR_TTL,
+            )),
         }
     }
 
@@ -558,6 +605,65 @@ impl JsonRpcRequestProcessor {
         Ok(new_response(&bank, response))
     }
 
+    pub async fn get_account_info_at_slot(
+        &self,
+        pubkey: Pubkey,
+        slot: Slot,
+        config: Option<RpcAccountInfoAtSlotConfig>,
+    ) -> Result<RpcResponse<Option<UiAccount>>> {
+        let RpcAccountInfoAtSlotConfig {
+            encoding,
+            data_slice,
+        } = config.unwrap_or_default();
+        let encoding = encoding.unwrap_or(UiAccountEncoding::Binary);
+
+        let (root, bank) = {
+            let bank_forks = self.bank_forks.read().unwrap();
+            (bank_forks.root(), bank_forks.get(slot))
+        };
+        if slot != root && !self.blockstore.is_root(slot) {
+            return Err(RpcCustomError::HistoricalAccountStateNotAvailable {
+                slot,
+                reason: "slot is not rooted".to_string(),
+            }
+            .into());
+        }
+        let bank = match bank {
+            Some(bank) => bank,
+            None => {
+                let Some(historical_banks) = self.historical_banks.clone() else {
+                    return Err(RpcCustomError::HistoricalAccountStateNotAvailable {
+                        slot,
+                        reason: "historical account state is not enabled".to_string(),
+                    }
+                    .into());
+                };
+                let ledger_path = self.blockstore.ledger_path().to_path_buf();
+                let snapshot_config = self.snapshot_config.clone();
+                self.runtime
+                    .spawn_blocking(move || {
+                        historical_banks.get_or_load(slot, &ledger_path, snapshot_config.as_ref())
+                    })
+                    .await
+                    .expect("rpc: historical bank reconstruction panicked")
+                    .map_err(|err| RpcCustomError::HistoricalAccountStateNotAvailable {
+                        slot,
+                        reason: err.to_string(),
+                    })?
+            }
+        };
+
+        let response = self
+            .runtime
+            .spawn_blocking({
+                let bank = Arc::clone(&bank);
+                move || get_encoded_account(&bank, &pubkey, encoding, data_slice, None)
+            })
+            .await
+            .expect("rpc: get_encoded_account panicked")?;
+        Ok(new_response(&bank, response))
+    }
+
     pub async fn get_multiple_accounts(
         &self,
         pubkeys: Vec<Pubkey>,
@@ -669,6 +775,116 @@ impl JsonRpcRequestProcessor {
         })
     }
 
+    pub async fn get_program_accounts_v2(
+        &self,
+        program_id: Pubkey,
+        config: RpcProgramAccountsV2Config,
+        mut filters: Vec<RpcFilterType>,
+    ) -> Result<RpcResponse<RpcKeyedAccountsPage>> {
+        let RpcProgramAccountsV2Config {
+            account_config:
+                RpcAccountInfoConfig {
+                    encoding,
+                    data_slice: data_slice_config,
+                    commitment,
+                    min_context_slot,
+                },
+            limit,
+            cursor,
+            ..
+        } = config;
+        let limit = limit.unwrap_or(MAX_GET_PROGRAM_ACCOUNTS_V2_LIMIT);
+        if limit == 0 || limit > MAX_GET_PROGRAM_ACCOUNTS_V2_LIMIT {
+            return Err(Error::invalid_params(format!(
+                "Invalid limit; max {MAX_GET_PROGRAM_ACCOUNTS_V2_LIMIT}"
+            )));
+        }
+        let cursor = cursor
+            .map(|cursor| {
+                let id = cursor
+                    .parse::<ProgramAccountsCursorId>()
+                    .map_err(|()| Error::invalid_params("Invalid param: cursor"))?;
+                let cursor = self
+                    .program_accounts_cursors
+                    .get(&id)
+                    .ok_or(RpcCustomError::CursorNotAvailable)?;
+                if cursor.program_id != program_id {
+                    return Err(Error::invalid_params(
+                        "Invalid param: cursor of another program",
+                    ));
+                }
+                Ok(cursor)
+            })
+            .transpose()?;
+        let is_first_page = cursor.is_none();
+        let (bank, start_after) = match cursor {
+            Some(cursor) => (cursor.bank, Some(cursor.last_pubkey)),
+            None => (
+                self.get_bank_with_config(RpcContextConfig {
+                    commitment,
+                    min_context_slot,
+                })?,
+                None,
+            ),
+        };
+        let encoding = encoding.unwrap_or(UiAccountEncoding::Binary);
+        optimize_filters(&mut filters);
+        let (keyed_accounts, next_start_after) = {
+            let bank = Arc::clone(&bank);
+            self.runtime
+                .spawn_blocking(move || {
+                    bank.get_filtered_program_accounts_after(
+                        &program_id,
+                        start_after.as_ref(),
+                        limit,
+                        |account| {
+                            filters
+                                .iter()
+                                .all(|filter_type| filter_allows(filter_type, account))
+                        },
+                        bank.byte_limit_for_scans(),
+                    )
+                })
+                .await
+                .expect("Failed to spawn blocking task")
+                .map_err(|e| match e {
+                    // The cursor's bank can no longer be read consistently, so the walk has to
+                    // restart from a newer bank
+                    ScanError::RootAdvanced { .. } if !is_first_page => {
+                        RpcCustomError::CursorNotAvailable
+                    }
+                    e => RpcCustomError::ScanError {
+                        message: e.to_string(),
+                    },
+                })?
+        };
+        // Pages may also end early, after visiting many accounts which did not pass the filters
+        let cursor = next_start_after.map(|next_start_after| {
+            self.program_accounts_cursors
+                .insert(Arc::clone(&bank), program_id, next_start_after)
+                .to_string()
+        });
+        let accounts = if is_known_spl_token_id(&program_id)
+            && encoding == UiAccountEncoding::JsonParsed
+        {
+            get_parsed_token_accounts(Arc::clone(&bank), keyed_accounts.into_iter()).collect()
+        } else {
+            keyed_accounts
+                .into_iter()
+                .map(|(pubkey, account)| {
+                    Ok(RpcKeyedAccount {
+                        pubkey: pubkey.to_string(),
+                        account: encode_account(&account, &pubkey, encoding, data_slice_config)?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?
+        };
+        Ok(new_response(
+            &bank,
+            RpcKeyedAccountsPage { accounts, cursor },
+        ))
+    }
+
     fn filter_map_rewards<'a, F>(
         rewards: Option<Rewards>,
         slot: Slot,
@@ -2207,7 +2423,21 @@ impl JsonRpcRequestProcessor {
         sort_results: bool,
     ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
         optimize_filters(&mut filters);
-        if self
+        if let Some(index_key) =
+            get_program_data_index_filter(&program_id, &filters, &self.config.account_indexes)
+        {
+            self.get_filtered_indexed_accounts(
+                &bank,
+                &index_key,
+                &program_id,
+                filters,
+                sort_results,
+            )
+            .await
+            .map_err(|e| RpcCustomError::ScanError {
+                message: e.to_string(),
+            })
+        } else if self
             .config
             .account_indexes
             .contains(&AccountIndex::ProgramId)
@@ -2403,14 +2633,19 @@ impl JsonRpcRequestProcessor {
 }
 
 pub(crate) fn optimize_filters(filters: &mut [RpcFilterType]) {
-    filters.iter_mut().for_each(|filter_type| {
-        if let RpcFilterType::Memcmp(compare) = filter_type {
-            if let Err(err) = compare.convert_to_raw_bytes() {
-                // All filters should have been previously verified
-                warn!("Invalid filter: bytes could not be decoded, {err}");
+    filters
+        .iter_mut()
+        .for_each(|filter_type| match filter_type {
+            RpcFilterType::Memcmp(compare) => {
+                if let Err(err) = compare.convert_to_raw_bytes() {
+                    // All filters should have been previously verified
+                    warn!("Invalid filter: bytes could not be decoded, {err}");
+                }
             }
-        }
-    })
+            RpcFilterType::Or(filters) => optimize_filters(filters),
+            RpcFilterType::Not(filter) => optimize_filters(std::slice::from_mut(filter.as_mut())),
+            _ => {}
+        })
 }
 
 fn verify_transaction(transaction: &SanitizedTransaction) -> Result<()> {
@@ -2446,13 +2681,36 @@ pub fn verify_pubkey(input: &str) -> Result<Pubkey> {
         .map_err(|e| Error::invalid_params(format!("Invalid param: {e:?}")))
 }
 
+/// Decodes the data of a `simulateBundle` state override. Compressed data is rejected, since it
+/// could inflate far beyond the size of the request.
+fn decode_data_override(address: &str, data: &UiAccountData) -> Result<Vec<u8>> {
+    if matches!(
+        data,
+        UiAccountData::Binary(_, UiAccountEncoding::Base64Zstd)
+    ) {
+        return Err(Error::invalid_params(
+            "base64+zstd encoding not supported for data overrides",
+        ));
+    }
+    let data = data
+        .decode()
+        .ok_or_else(|| Error::invalid_params(format!("invalid data override for {address}")))?;
+    if data.len() as u64 > solana_system_interface::MAX_PERMITTED_DATA_LENGTH {
+        return Err(Error::invalid_params(format!(
+            "data override for {address} is larger than {} bytes",
+            solana_system_interface::MAX_PERMITTED_DATA_LENGTH
+        )));
+    }
+    Ok(data)
+}
+
 fn verify_hash(input: &str) -> Result<Hash> {
     input
         .parse()
         .map_err(|e| Error::invalid_params(format!("Invalid param: {e:?}")))
 }
 
-fn verify_signature(input: &str) -> Result<Signature> {
+pub fn verify_signature(input: &str) -> Result<Signature> {
     input
         .parse()
         .map_err(|e| Error::invalid_params(format!("Invalid param: {e:?}")))
@@ -2586,6 +2844,11 @@ fn get_spl_token_owner_filter(program_id: &Pubkey, filters: &[RpcFilterType]) ->
                 }
             }
             RpcFilterType::TokenAccountState => token_account_state_filter = true,
+            // these filters cannot narrow the search to a secondary index
+            RpcFilterType::Or(_)
+            | RpcFilterType::Not(_)
+            | RpcFilterType::DataRange(_)
+            | RpcFilterType::Lamports(_) => {}
         }
     }
     if data_size_filter == Some(account_packed_len as u64)
@@ -2636,6 +2899,11 @@ fn get_spl_token_mint_filter(program_id: &Pubkey, filters: &[RpcFilterType]) ->
                 }
             }
             RpcFilterType::TokenAccountState => token_account_state_filter = true,
+            // these filters cannot narrow the search to a secondary index
+            RpcFilterType::Or(_)
+            | RpcFilterType::Not(_)
+            | RpcFilterType::DataRange(_)
+            | RpcFilterType::Lamports(_) => {}
         }
     }
     if data_size_filter == Some(account_packed_len as u64)
@@ -2654,6 +2922,30 @@ fn get_spl_token_mint_filter(program_id: &Pubkey, filters: &[RpcFilterType]) ->
     }
 }
 
+/// Analyze custom filters to determine if the result will be a subset of the accounts found in a
+/// configured program data index.
+/// NOTE: `optimize_filters()` should almost always be called before using this method because of
+/// the requirement that `Memcmp::raw_bytes_as_ref().is_some()`.
+fn get_program_data_index_filter(
+    program_id: &Pubkey,
+    filters: &[RpcFilterType],
+    account_indexes: &AccountSecondaryIndexes,
+) -> Option<IndexKey> {
+    account_indexes
+        .program_data_indexes()
+        .filter(|program_data_index| program_data_index.program_id == *program_id)
+        .find_map(|program_data_index| {
+            filters.iter().find_map(|filter| match filter {
+                RpcFilterType::Memcmp(memcmp) => memcmp
+                    .raw_bytes_as_ref()
+                    .and_then(|bytes| program_data_index.memcmp_index_key(memcmp.offset(), bytes))
+                    .filter(|data_key| account_indexes.include_key(data_key))
+                    .map(|data_key| IndexKey::ProgramData(*program_data_index, data_key)),
+                _ => None,
+            })
+        })
+}
+
 /// Analyze a passed Pubkey that may be a Token program id or Mint address to determine the program
 /// id and optional Mint
 fn get_token_program_id_and_mint(
@@ -3172,6 +3464,15 @@ pub mod rpc_accounts {
             config: Option<RpcAccountInfoConfig>,
         ) -> BoxFuture<Result<RpcResponse<Option<UiAccount>>>>;
 
+        #[rpc(meta, name = "getAccountInfoAtSlot")]
+        fn get_account_info_at_slot(
+            &self,
+            meta: Self::Metadata,
+            pubkey_str: String,
+            slot: Slot,
+            config: Option<RpcAccountInfoAtSlotConfig>,
+        ) -> BoxFuture<Result<RpcResponse<Option<UiAccount>>>>;
+
         #[rpc(meta, name = "getMultipleAccounts")]
         fn get_multiple_accounts(
             &self,
@@ -3226,6 +3527,21 @@ pub mod rpc_accounts {
             .boxed()
         }
 
+        fn get_account_info_at_slot(
+            &self,
+            meta: Self::Metadata,
+            pubkey_str: String,
+            slot: Slot,
+            config: Option<RpcAccountInfoAtSlotConfig>,
+        ) -> BoxFuture<Result<RpcResponse<Option<UiAccount>>>> {
+            debug!("get_account_info_at_slot rpc request received: {pubkey_str:?} {slot}");
+            async move {
+                let pubkey = verify_pubkey(&pubkey_str)?;
+                meta.get_account_info_at_slot(pubkey, slot, config).await
+            }
+            .boxed()
+        }
+
         fn get_multiple_accounts(
             &self,
             meta: Self::Metadata,
@@ -3305,6 +3621,14 @@ pub mod rpc_accounts_scan {
             config: Option<RpcProgramAccountsConfig>,
         ) -> BoxFuture<Result<OptionalContext<Vec<RpcKeyedAccount>>>>;
 
+        #[rpc(meta, name = "getProgramAccountsV2")]
+        fn get_program_accounts_v2(
+            &self,
+            meta: Self::Metadata,
+            program_id_str: String,
+            config: Option<RpcProgramAccountsV2Config>,
+        ) -> BoxFuture<Result<RpcResponse<RpcKeyedAccountsPage>>>;
+
         #[rpc(meta, name = "getLargestAccounts")]
         fn get_largest_accounts(
             &self,
@@ -3380,6 +3704,24 @@ pub mod rpc_accounts_scan {
             .boxed()
         }
 
+        fn get_program_accounts_v2(
+            &self,
+            meta: Self::Metadata,
+            program_id_str: String,
+            config: Option<RpcProgramAccountsV2Config>,
+        ) -> BoxFuture<Result<RpcResponse<RpcKeyedAccountsPage>>> {
+            debug!("get_program_accounts_v2 rpc request received: {program_id_str:?}");
+            async move {
+                let program_id = verify_pubkey(&program_id_str)?;
+                let mut config = config.unwrap_or_default();
+                let filters = config.filters.take().unwrap_or_default();
+                verify_filters(&filters)?;
+                meta.get_program_accounts_v2(program_id, config, filters)
+                    .await
+            }
+            .boxed()
+        }
+
         fn get_largest_accounts(
             &self,
             meta: Self::Metadata,
@@ -3509,6 +3851,14 @@ pub mod rpc_full {
             config: Option<RpcSendTransactionConfig>,
         ) -> Result<String>;
 
+        #[rpc(meta, name = "sendBundle")]
+        fn send_bundle(
+            &self,
+            meta: Self::Metadata,
+            data: Vec<String>,
+            config: Option<RpcSendBundleConfig>,
+        ) -> Result<Vec<String>>;
+
         #[rpc(meta, name = "simulateTransaction")]
         fn simulate_transaction(
             &self,
@@ -3517,6 +3867,14 @@ pub mod rpc_full {
             config: Option<RpcSimulateTransactionConfig>,
         ) -> Result<RpcResponse<RpcSimulateTransactionResult>>;
 
+        #[rpc(meta, name = "simulateBundle")]
+        fn simulate_bundle(
+            &self,
+            meta: Self::Metadata,
+            data: Vec<String>,
+            config: Option<RpcSimulateBundleConfig>,
+        ) -> Result<RpcResponse<RpcSimulateBundleResult>>;
+
         #[rpc(meta, name = "minimumLedgerSlot")]
         fn minimum_ledger_slot(&self, meta: Self::Metadata) -> Result<Slot>;
 
@@ -3891,6 +4249,7 @@ pub mod rpc_full {
                     loaded_accounts_data_size,
                     return_data,
                     inner_instructions: _, // Always `None` due to `enable_cpi_recording = false`
+                    instruction_traces: _,
                 } = preflight_bank.simulate_transaction(&transaction, false)
                 {
                     match err {
@@ -3912,6 +4271,7 @@ pub mod rpc_full {
                             return_data: return_data.map(|return_data| return_data.into()),
                             inner_instructions: None,
                             replacement_blockhash: None,
+                            execution_trace: None,
                         },
                     }
                     .into());
@@ -3930,6 +4290,71 @@ pub mod rpc_full {
             )
         }
 
+        fn send_bundle(
+            &self,
+            meta: Self::Metadata,
+            data: Vec<String>,
+            config: Option<RpcSendBundleConfig>,
+        ) -> Result<Vec<String>> {
+            debug!("send_bundle rpc request received");
+            let Some(bundle_sender) = meta.config.bundle_sender.as_ref() else {
+                return Err(RpcCustomError::BundleNotAccepted {
+                    reason: "bundles are not enabled on this node".to_string(),
+                }
+                .into());
+            };
+            if data.is_empty() {
+                return Err(Error::invalid_params("No transactions provided"));
+            }
+            if data.len() > MAX_BUNDLE_LEN {
+                return Err(Error::invalid_params(format!(
+                    "Too many transactions provided; max {MAX_BUNDLE_LEN}"
+                )));
+            }
+            let RpcSendBundleConfig { encoding } = config.unwrap_or_default();
+            let tx_encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
+            let binary_encoding = tx_encoding.into_binary_encoding().ok_or_else(|| {
+                Error::invalid_params(format!(
+                    "unsupported encoding: {tx_encoding}. Supported encodings: base58, base64"
+                ))
+            })?;
+
+            let transactions = data
+                .into_iter()
+                .map(|data| {
+                    decode_and_deserialize::<VersionedTransaction>(data, binary_encoding)
+                        .map(|(_wire_transaction, transaction)| transaction)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let signatures = transactions
+                .iter()
+                .map(|transaction| {
+                    transaction
+                        .signatures
+                        .first()
+                        .map(|signature| signature.to_string())
+                        .ok_or_else(|| {
+                            Error::from(RpcCustomError::TransactionSignatureVerificationFailure)
+                        })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            // Signatures are verified by the banking stage, along with the rest of the bundle.
+            let bundle = to_packet_batches(&transactions, transactions.len())
+                .pop()
+                .expect("bundle is not empty");
+            bundle_sender.try_send(bundle).map_err(|err| {
+                let reason = match err {
+                    TrySendError::Full(_) => "bundle queue is full",
+                    TrySendError::Disconnected(_) => "bundles are no longer processed",
+                };
+                Error::from(RpcCustomError::BundleNotAccepted {
+                    reason: reason.to_string(),
+                })
+            })?;
+            Ok(signatures)
+        }
+
         fn simulate_transaction(
             &self,
             meta: Self::Metadata,
@@ -3945,6 +4370,7 @@ pub mod rpc_full {
                 accounts: config_accounts,
                 min_context_slot,
                 inner_instructions: enable_cpi_recording,
+                execution_trace: execution_trace_format,
             } = config.unwrap_or_default();
             let tx_encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
             let binary_encoding = tx_encoding.into_binary_encoding().ok_or_else(|| {
@@ -3993,7 +4419,14 @@ pub mod rpc_full {
                 loaded_accounts_data_size,
                 return_data,
                 inner_instructions,
-            } = bank.simulate_transaction(&transaction, enable_cpi_recording);
+                instruction_traces,
+            } = bank.simulate_transaction_with_config(
+                &transaction,
+                TransactionSimulationConfig {
+                    enable_cpi_recording,
+                    enable_instruction_trace_recording: execution_trace_format.is_some(),
+                },
+            );
 
             let account_keys = transaction.message().account_keys();
             let number_of_accounts = account_keys.len();
@@ -4023,44 +4456,263 @@ pub mod rpc_full {
                         post_simulation_accounts_map.insert(pubkey, data);
                     }
 
-                    Some(
-                        config_accounts
-                            .addresses
+                    Some(
+                        config_accounts
+                            .addresses
+                            .iter()
+                            .map(|address_str| {
+                                let pubkey = verify_pubkey(address_str)?;
+                                get_encoded_account(
+                                    bank,
+                                    &pubkey,
+                                    accounts_encoding,
+                                    None,
+                                    Some(&post_simulation_accounts_map),
+                                )
+                            })
+                            .collect::<Result<Vec<_>>>()?,
+                    )
+                }
+            } else {
+                None
+            };
+
+            let inner_instructions = inner_instructions.map(|info| {
+                map_inner_instructions(info)
+                    .map(|converted| parse_ui_inner_instructions(converted, &account_keys))
+                    .collect()
+            });
+
+            let execution_trace = execution_trace_format.zip(instruction_traces).map(
+                |(format, instruction_traces)| {
+                    let format = match format {
+                        RpcExecutionTraceFormat::Tree => ExecutionTraceFormat::Tree,
+                        RpcExecutionTraceFormat::ChromeTrace => ExecutionTraceFormat::ChromeTrace,
+                    };
+                    format.export(&instruction_traces)
+                },
+            );
+
+            Ok(new_response(
+                bank,
+                RpcSimulateTransactionResult {
+                    err: result.err().map(Into::into),
+                    logs: Some(logs),
+                    accounts,
+                    units_consumed: Some(units_consumed),
+                    loaded_accounts_data_size: Some(loaded_accounts_data_size),
+                    return_data: return_data.map(|return_data| return_data.into()),
+                    inner_instructions,
+                    replacement_blockhash: blockhash,
+                    execution_trace,
+                },
+            ))
+        }
+
+        fn simulate_bundle(
+            &self,
+            meta: Self::Metadata,
+            data: Vec<String>,
+            config: Option<RpcSimulateBundleConfig>,
+        ) -> Result<RpcResponse<RpcSimulateBundleResult>> {
+            debug!("simulate_bundle rpc request received");
+            let RpcSimulateBundleConfig {
+                sig_verify,
+                replace_recent_blockhash,
+                commitment,
+                encoding,
+                accounts: config_accounts,
+                min_context_slot,
+                inner_instructions: enable_cpi_recording,
+                state_overrides,
+            } = config.unwrap_or_default();
+            if data.is_empty() {
+                return Err(Error::invalid_params("No transactions provided"));
+            }
+            if data.len() > MAX_SIMULATE_BUNDLE_TRANSACTIONS {
+                return Err(Error::invalid_params(format!(
+                    "Too many transactions provided; max {MAX_SIMULATE_BUNDLE_TRANSACTIONS}"
+                )));
+            }
+            if replace_recent_blockhash && sig_verify {
+                return Err(Error::invalid_params(
+                    "sigVerify may not be used with replaceRecentBlockhash",
+                ));
+            }
+            if state_overrides.as_ref().is_some_and(|state_overrides| {
+                state_overrides.len() > MAX_SIMULATE_BUNDLE_STATE_OVERRIDES
+            }) {
+                return Err(Error::invalid_params(format!(
+                    "Too many state overrides provided; max {MAX_SIMULATE_BUNDLE_STATE_OVERRIDES}"
+                )));
+            }
+            let tx_encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
+            let binary_encoding = tx_encoding.into_binary_encoding().ok_or_else(|| {
+                Error::invalid_params(format!(
+                    "unsupported encoding: {tx_encoding}. Supported encodings: base58, base64"
+                ))
+            })?;
+
+            let (accounts_encoding, addresses) = match config_accounts {
+                Some(config_accounts) => {
+                    let accounts_encoding = config_accounts
+                        .encoding
+                        .unwrap_or(UiAccountEncoding::Base64);
+                    if accounts_encoding == UiAccountEncoding::Binary
+                        || accounts_encoding == UiAccountEncoding::Base58
+                    {
+                        return Err(Error::invalid_params("base58 encoding not supported"));
+                    }
+                    if config_accounts.addresses.len() > MAX_MULTIPLE_ACCOUNTS {
+                        return Err(Error::invalid_params(format!(
+                            "Too many accounts provided; max {MAX_MULTIPLE_ACCOUNTS}"
+                        )));
+                    }
+                    let addresses = config_accounts
+                        .addresses
+                        .iter()
+                        .map(|address| verify_pubkey(address))
+                        .collect::<Result<Vec<_>>>()?;
+                    (accounts_encoding, Some(addresses))
+                }
+                None => (UiAccountEncoding::Base64, None),
+            };
+
+            let bank = &*meta.get_bank_with_config(RpcContextConfig {
+                commitment,
+                min_context_slot,
+            })?;
+            let blockhash = replace_recent_blockhash.then(|| {
+                let recent_blockhash = bank.last_blockhash();
+                let last_valid_block_height = bank
+                    .get_blockhash_last_valid_block_height(&recent_blockhash)
+                    .expect("bank blockhash queue should contain blockhash");
+                (recent_blockhash, last_valid_block_height)
+            });
+
+            let transactions = data
+                .into_iter()
+                .map(|data| {
+                    let (_, mut unsanitized_tx) =
+                        decode_and_deserialize::<VersionedTransaction>(data, binary_encoding)?;
+                    if let Some((recent_blockhash, _)) = blockhash {
+                        unsanitized_tx
+                            .message
+                            .set_recent_blockhash(recent_blockhash);
+                    }
+                    let transaction = sanitize_transaction(
+                        unsanitized_tx,
+                        bank,
+                        bank.get_reserved_account_keys(),
+                    )?;
+                    if sig_verify {
+                        verify_transaction(&transaction)?;
+                    }
+                    Ok(transaction)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let mut state_overrides_map = HashMap::new();
+            for (address, account_override) in state_overrides.unwrap_or_default() {
+                let pubkey = verify_pubkey(&address)?;
+                let mut account = bank.get_account(&pubkey).unwrap_or_default();
+                if let Some(lamports) = account_override.lamports {
+                    account.set_lamports(lamports);
+                }
+                if let Some(owner) = account_override.owner {
+                    account.set_owner(verify_pubkey(&owner)?);
+                }
+                if let Some(data) = account_override.data {
+                    account.set_data_from_slice(&decode_data_override(&address, &data)?);
+                }
+                state_overrides_map.insert(pubkey, account);
+            }
+
+            let simulation_results = bank.simulate_bundle(
+                &transactions,
+                state_overrides_map.clone(),
+                enable_cpi_recording,
+            );
+
+            // Accounts as the bundle left them so far, so that requested
+            // accounts a transaction does not reference are reported too
+            let mut bundle_accounts = state_overrides_map;
+            let encode_accounts = |bundle_accounts: &HashMap<Pubkey, AccountSharedData>| {
+                addresses
+                    .as_ref()
+                    .map(|addresses| {
+                        addresses
                             .iter()
-                            .map(|address_str| {
-                                let pubkey = verify_pubkey(address_str)?;
+                            .map(|pubkey| {
+                                if bundle_accounts
+                                    .get(pubkey)
+                                    .is_some_and(|account| account.lamports() == 0)
+                                {
+                                    return Ok(None);
+                                }
                                 get_encoded_account(
                                     bank,
-                                    &pubkey,
+                                    pubkey,
                                     accounts_encoding,
                                     None,
-                                    Some(&post_simulation_accounts_map),
+                                    Some(bundle_accounts),
                                 )
                             })
-                            .collect::<Result<Vec<_>>>()?,
-                    )
-                }
-            } else {
-                None
+                            .collect::<Result<Vec<_>>>()
+                    })
+                    .transpose()
             };
 
-            let inner_instructions = inner_instructions.map(|info| {
-                map_inner_instructions(info)
-                    .map(|converted| parse_ui_inner_instructions(converted, &account_keys))
-                    .collect()
-            });
+            let mut transaction_results = Vec::with_capacity(transactions.len());
+            for (transaction, simulation_result) in transactions.iter().zip(simulation_results) {
+                let BundleTransactionSimulationResult {
+                    pre_simulation_accounts,
+                    simulation_result:
+                        TransactionSimulationResult {
+                            result,
+                            logs,
+                            post_simulation_accounts,
+                            units_consumed,
+                            loaded_accounts_data_size,
+                            return_data,
+                            inner_instructions,
+                        },
+                } = simulation_result;
+
+                bundle_accounts.extend(pre_simulation_accounts);
+                let pre_accounts = encode_accounts(&bundle_accounts)?;
+                bundle_accounts.extend(post_simulation_accounts);
+                let post_accounts = encode_accounts(&bundle_accounts)?;
+
+                let account_keys = transaction.message().account_keys();
+                let inner_instructions = inner_instructions.map(|info| {
+                    map_inner_instructions(info)
+                        .map(|converted| parse_ui_inner_instructions(converted, &account_keys))
+                        .collect()
+                });
 
-            Ok(new_response(
-                bank,
-                RpcSimulateTransactionResult {
+                transaction_results.push(RpcSimulateBundleTransactionResult {
                     err: result.err().map(Into::into),
                     logs: Some(logs),
-                    accounts,
+                    pre_accounts,
+                    post_accounts,
                     units_consumed: Some(units_consumed),
                     loaded_accounts_data_size: Some(loaded_accounts_data_size),
                     return_data: return_data.map(|return_data| return_data.into()),
                     inner_instructions,
-                    replacement_blockhash: blockhash,
+                });
+            }
+
+            Ok(new_response(
+                bank,
+                RpcSimulateBundleResult {
+                    transaction_results,
+                    replacement_blockhash: blockhash.map(
+                        |(recent_blockhash, last_valid_block_height)| RpcBlockhash {
+                            blockhash: recent_blockhash.to_string(),
+                            last_valid_block_height,
+                        },
+                    ),
                 },
             ))
         }
@@ -4482,11 +5134,15 @@ pub mod tests {
         },
         agave_reserved_account_keys::ReservedAccountKeys,
         bincode::deserialize,
+        crossbeam_channel::bounded,
         jsonrpc_core::{futures, ErrorCode, MetaIoHandler, Output, Response, Value},
         jsonrpc_core_client::transports::local,
         serde::de::DeserializeOwned,
         solana_account::{Account, WritableAccount},
-        solana_accounts_db::accounts_db::{AccountsDbConfig, ACCOUNTS_DB_CONFIG_FOR_TESTING},
+        solana_accounts_db::{
+            accounts_db::{AccountsDbConfig, ACCOUNTS_DB_CONFIG_FOR_TESTING},
+            accounts_index::{AccountSecondaryIndexesIncludeExclude, ProgramDataIndex},
+        },
         solana_address_lookup_table_interface::{
             self as address_lookup_table,
             state::{AddressLookupTable, LookupTableMeta},
@@ -4518,6 +5174,9 @@ pub mod tests {
         solana_rpc_client_api::{
             custom_error::{
                 JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE,
+                JSON_RPC_SERVER_ERROR_BUNDLE_NOT_ACCEPTED,
+                JSON_RPC_SERVER_ERROR_CURSOR_NOT_AVAILABLE,
+                JSON_RPC_SERVER_ERROR_HISTORICAL_ACCOUNT_STATE_NOT_AVAILABLE,
                 JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_NOT_AVAILABLE,
                 JSON_RPC_SERVER_ERROR_UNSUPPORTED_TRANSACTION_VERSION,
             },
@@ -5680,6 +6339,50 @@ pub mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_rpc_get_account_info_at_slot() {
+        let rpc = RpcHandler::start();
+        let address = rpc.mint_keypair.pubkey().to_string();
+
+        // The root bank is served from bank forks
+        let request = create_test_request("getAccountInfoAtSlot", Some(json!([address, 0])));
+        let result: Value = parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result["context"]["slot"], 0);
+        assert_eq!(result["value"]["lamports"], TEST_MINT_LAMPORTS);
+
+        // Slots that are not rooted are rejected
+        let request = create_test_request("getAccountInfoAtSlot", Some(json!([address, 3])));
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(
+            code,
+            JSON_RPC_SERVER_ERROR_HISTORICAL_ACCOUNT_STATE_NOT_AVAILABLE
+        );
+
+        // Rooted slots that are no longer in bank forks need historical account state
+        rpc.blockstore.set_roots(std::iter::once(&3)).unwrap();
+        let request = create_test_request("getAccountInfoAtSlot", Some(json!([address, 3])));
+        let (code, message) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(
+            code,
+            JSON_RPC_SERVER_ERROR_HISTORICAL_ACCOUNT_STATE_NOT_AVAILABLE
+        );
+        assert!(message.contains("not enabled"));
+
+        // With historical account state enabled, reconstruction fails without a genesis config
+        let rpc = RpcHandler::start_with_config(JsonRpcConfig {
+            historical_account_state_path: Some(get_tmp_ledger_path!()),
+            ..JsonRpcConfig::default()
+        });
+        rpc.blockstore.set_roots(std::iter::once(&3)).unwrap();
+        let request = create_test_request("getAccountInfoAtSlot", Some(json!([address, 3])));
+        let (code, message) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(
+            code,
+            JSON_RPC_SERVER_ERROR_HISTORICAL_ACCOUNT_STATE_NOT_AVAILABLE
+        );
+        assert!(message.contains("genesis"));
+    }
+
     #[test]
     fn test_rpc_get_multiple_accounts() {
         let rpc = RpcHandler::start();
@@ -5948,6 +6651,328 @@ pub mod tests {
         assert_eq!(result.len(), 0);
     }
 
+    #[test]
+    fn test_rpc_get_program_accounts_v2() {
+        let rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+
+        let program_id = Pubkey::new_unique();
+        let mut pubkeys = (0..5)
+            .map(|_| {
+                let pubkey = Pubkey::new_unique();
+                bank.store_account(&pubkey, &AccountSharedData::new(42, 0, &program_id));
+                pubkey
+            })
+            .collect::<Vec<_>>();
+        pubkeys.sort_unstable();
+
+        // Walk the accounts in pages of two
+        let mut loaded = vec![];
+        let mut cursor: Option<String> = None;
+        loop {
+            let request = create_test_request(
+                "getProgramAccountsV2",
+                Some(json!([
+                    program_id.to_string(),
+                    {"limit": 2, "cursor": cursor},
+                ])),
+            );
+            let result: RpcResponse<RpcKeyedAccountsPage> =
+                parse_success_result(rpc.handle_request_sync(request));
+            assert_eq!(result.context.slot, bank.slot());
+            assert!(result.value.accounts.len() <= 2);
+            loaded.extend(
+                result
+                    .value
+                    .accounts
+                    .into_iter()
+                    .map(|keyed_account| keyed_account.pubkey),
+            );
+            cursor = result.value.cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(
+            loaded,
+            pubkeys.iter().map(ToString::to_string).collect::<Vec<_>>()
+        );
+
+        let request = create_test_request(
+            "getProgramAccountsV2",
+            Some(json!([program_id.to_string(), {"limit": 2}])),
+        );
+        let result: RpcResponse<RpcKeyedAccountsPage> =
+            parse_success_result(rpc.handle_request_sync(request));
+        let cursor = result.value.cursor.unwrap();
+
+        // Cursor of another program
+        let request = create_test_request(
+            "getProgramAccountsV2",
+            Some(json!([Pubkey::new_unique().to_string(), {"cursor": cursor}])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+
+        // Cursor which expired or was evicted
+        let request = create_test_request(
+            "getProgramAccountsV2",
+            Some(json!([
+                program_id.to_string(),
+                {"cursor": bs58::encode([0; 16]).into_string()},
+            ])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, JSON_RPC_SERVER_ERROR_CURSOR_NOT_AVAILABLE);
+
+        // Invalid cursor and limit
+        for config in [json!({"cursor": "III"}), json!({"limit": 0})] {
+            let request = create_test_request(
+                "getProgramAccountsV2",
+                Some(json!([program_id.to_string(), config])),
+            );
+            let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+            assert_eq!(code, ErrorCode::InvalidParams.code());
+        }
+    }
+
+    #[test]
+    fn test_rpc_get_program_accounts_v2_root_advanced() {
+        let rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+        let program_id = Pubkey::new_unique();
+        for _ in 0..3 {
+            bank.store_account(
+                &Pubkey::new_unique(),
+                &AccountSharedData::new(42, 0, &program_id),
+            );
+        }
+
+        let request = create_test_request(
+            "getProgramAccountsV2",
+            Some(json!([program_id.to_string(), {"limit": 1}])),
+        );
+        let result: RpcResponse<RpcKeyedAccountsPage> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result.context.slot, bank.slot());
+        let cursor = result.value.cursor.unwrap();
+
+        // The pinned bank can't be read consistently once the root advances past it
+        rpc.add_roots_to_blockstore(vec![bank.slot() + 1]);
+        let request = create_test_request(
+            "getProgramAccountsV2",
+            Some(json!([program_id.to_string(), {"limit": 1, "cursor": cursor}])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, JSON_RPC_SERVER_ERROR_CURSOR_NOT_AVAILABLE);
+
+        // Restarting from the first page reads a newer bank
+        let request = create_test_request(
+            "getProgramAccountsV2",
+            Some(json!([program_id.to_string(), {"limit": 1}])),
+        );
+        let result: RpcResponse<RpcKeyedAccountsPage> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert!(result.context.slot > bank.slot());
+        assert_eq!(result.value.accounts.len(), 1);
+    }
+
+    #[test]
+    fn test_rpc_simulate_bundle() {
+        let rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+        let rent_exempt_amount = bank.get_minimum_balance_for_rent_exemption(0);
+        let recent_blockhash = bank.confirmed_last_blockhash();
+        let funding = 100 * rent_exempt_amount;
+
+        // Alice only exists in the state overrides, and funds Bob, who pays
+        // for the second transaction
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        let carol = solana_pubkey::new_rand();
+        let transactions = [
+            system_transaction::transfer(&alice, &bob.pubkey(), funding, recent_blockhash),
+            system_transaction::transfer(&bob, &carol, rent_exempt_amount, recent_blockhash),
+        ]
+        .map(|tx| BASE64_STANDARD.encode(serialize(&tx).unwrap()));
+
+        // Simulation bank must be frozen
+        bank.freeze();
+
+        let request = create_test_request(
+            "simulateBundle",
+            Some(json!([
+                transactions,
+                {
+                    "encoding": "base64",
+                    "sigVerify": true,
+                    "accounts": {
+                        "addresses": [
+                            alice.pubkey().to_string(),
+                            bob.pubkey().to_string(),
+                            carol.to_string(),
+                        ],
+                    },
+                    "stateOverrides": {
+                        alice.pubkey().to_string(): {"lamports": 10 * funding},
+                    },
+                },
+            ])),
+        );
+        let result: RpcResponse<RpcSimulateBundleResult> =
+            parse_success_result(rpc.handle_request_sync(request));
+        let [first, second] = &result.value.transaction_results[..] else {
+            panic!("expected two results");
+        };
+        let lamports = |accounts: &Option<Vec<Option<UiAccount>>>, index: usize| {
+            accounts.as_ref().unwrap()[index]
+                .as_ref()
+                .map(|account| account.lamports)
+        };
+        assert_eq!(first.err, None);
+        assert_eq!(lamports(&first.pre_accounts, 0), Some(10 * funding));
+        assert_eq!(lamports(&first.pre_accounts, 1), None);
+        assert_eq!(lamports(&first.post_accounts, 1), Some(funding));
+        assert_eq!(second.err, None);
+        assert_eq!(second.pre_accounts, first.post_accounts);
+        assert_eq!(lamports(&second.pre_accounts, 2), None);
+        assert_eq!(lamports(&second.post_accounts, 2), Some(rent_exempt_amount));
+        assert_eq!(
+            lamports(&second.post_accounts, 0),
+            lamports(&first.post_accounts, 0)
+        );
+
+        // Nothing was committed
+        assert_eq!(bank.get_balance(&alice.pubkey()), 0);
+        assert_eq!(bank.get_balance(&bob.pubkey()), 0);
+
+        // Without the overrides, Alice cannot pay
+        let request = create_test_request(
+            "simulateBundle",
+            Some(json!([transactions, {"encoding": "base64"}])),
+        );
+        let result: RpcResponse<RpcSimulateBundleResult> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(
+            result.value.transaction_results[0].err,
+            Some(TransactionError::AccountNotFound.into())
+        );
+
+        // An empty bundle
+        let request = create_test_request("simulateBundle", Some(json!([[]])));
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+
+        // Compressed data overrides are rejected
+        let request = create_test_request(
+            "simulateBundle",
+            Some(json!([
+                transactions,
+                {
+                    "encoding": "base64",
+                    "stateOverrides": {
+                        carol.to_string(): {"data": ["", "base64+zstd"]},
+                    },
+                },
+            ])),
+        );
+        let (code, message) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+        assert_eq!(
+            message,
+            "base64+zstd encoding not supported for data overrides"
+        );
+
+        // So are data overrides larger than an account can be
+        let data = vec![0; solana_system_interface::MAX_PERMITTED_DATA_LENGTH as usize + 1];
+        let request = create_test_request(
+            "simulateBundle",
+            Some(json!([
+                transactions,
+                {
+                    "encoding": "base64",
+                    "stateOverrides": {
+                        carol.to_string(): {"data": [BASE64_STANDARD.encode(data), "base64"]},
+                    },
+                },
+            ])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+
+        // And too many overrides
+        let state_overrides = (0..=MAX_SIMULATE_BUNDLE_STATE_OVERRIDES)
+            .map(|_| {
+                (
+                    solana_pubkey::new_rand().to_string(),
+                    json!({"lamports": 1}),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>();
+        let request = create_test_request(
+            "simulateBundle",
+            Some(json!([
+                transactions,
+                {"encoding": "base64", "stateOverrides": state_overrides},
+            ])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+    }
+
+    #[test]
+    fn test_rpc_send_bundle() {
+        let (bundle_sender, bundle_receiver) = bounded(1);
+        let rpc = RpcHandler::start_with_config(JsonRpcConfig {
+            bundle_sender: Some(bundle_sender),
+            ..JsonRpcConfig::default()
+        });
+        let recent_blockhash = rpc.working_bank().confirmed_last_blockhash();
+        let transactions = [1, 2].map(|lamports| {
+            system_transaction::transfer(
+                &rpc.mint_keypair,
+                &solana_pubkey::new_rand(),
+                lamports,
+                recent_blockhash,
+            )
+        });
+        let encoded_transactions = transactions
+            .each_ref()
+            .map(|tx| BASE64_STANDARD.encode(serialize(tx).unwrap()));
+
+        let request = create_test_request(
+            "sendBundle",
+            Some(json!([encoded_transactions, {"encoding": "base64"}])),
+        );
+        let signatures: Vec<String> = parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(
+            signatures,
+            transactions
+                .iter()
+                .map(|tx| tx.signatures[0].to_string())
+                .collect::<Vec<_>>()
+        );
+        let bundle = bundle_receiver.try_recv().unwrap();
+        assert_eq!(bundle.len(), 2);
+        for (packet, tx) in bundle.iter().zip(&transactions) {
+            assert_eq!(packet.deserialize_slice::<Transaction, _>(..).unwrap(), *tx);
+        }
+
+        // The second bundle fills the queue
+        let request = create_test_request(
+            "sendBundle",
+            Some(json!([encoded_transactions, {"encoding": "base64"}])),
+        );
+        parse_success_result::<Vec<String>>(rpc.handle_request_sync(request.clone()));
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request.clone()));
+        assert_eq!(code, JSON_RPC_SERVER_ERROR_BUNDLE_NOT_ACCEPTED);
+
+        // Bundles are not enabled
+        let rpc = RpcHandler::start();
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, JSON_RPC_SERVER_ERROR_BUNDLE_NOT_ACCEPTED);
+    }
+
     #[test]
     fn test_rpc_simulate_transaction() {
         let rpc = RpcHandler::start();
@@ -6583,6 +7608,62 @@ pub mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_rpc_simulate_transaction_with_execution_trace() {
+        let rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+        let rent_exempt_amount = bank.get_minimum_balance_for_rent_exemption(0);
+        let recent_blockhash = bank.confirmed_last_blockhash();
+        let RpcHandler {
+            ref meta, ref io, ..
+        } = rpc;
+
+        let tx = system_transaction::transfer(
+            &rpc.mint_keypair,
+            &solana_pubkey::new_rand(),
+            rent_exempt_amount,
+            recent_blockhash,
+        );
+        let tx_serialized_encoded = bs58::encode(serialize(&tx).unwrap()).into_string();
+        bank.freeze();
+
+        let simulate = |format: &str| {
+            let req = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"simulateTransaction","params":["{tx_serialized_encoded}", {{"executionTrace": "{format}"}}]}}"#,
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            result["result"]["value"]["executionTrace"].clone()
+        };
+
+        let tree = simulate("tree");
+        let top_level_instructions = tree.as_array().unwrap();
+        assert_eq!(top_level_instructions.len(), 1);
+        assert_eq!(
+            top_level_instructions[0]["programId"],
+            system_program::id().to_string()
+        );
+        assert_eq!(top_level_instructions[0]["stackHeight"], 1);
+        assert_eq!(top_level_instructions[0]["computeUnitsConsumed"], 150);
+        assert_eq!(top_level_instructions[0]["innerInstructions"], json!([]));
+
+        let chrome_trace = simulate("chromeTrace");
+        let trace_events = chrome_trace["traceEvents"].as_array().unwrap();
+        assert_eq!(trace_events.len(), 1);
+        assert_eq!(trace_events[0]["name"], system_program::id().to_string());
+        assert_eq!(trace_events[0]["ph"], "X");
+
+        // Tracing is opt-in
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"simulateTransaction","params":["{tx_serialized_encoded}"]}}"#,
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert!(result["result"]["value"].get("executionTrace").is_none());
+    }
+
     #[test]
     #[should_panic(expected = "simulation bank must be frozen")]
     fn test_rpc_simulate_transaction_panic_on_unfrozen_bank() {
@@ -6930,6 +8011,25 @@ pub mod tests {
         assert!(verify_filter(&filter).is_err());
     }
 
+    #[test]
+    fn test_optimize_nested_filters() {
+        let bytes = vec![1, 2, 3];
+        let base58 = RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &bytes));
+        let raw = RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, bytes));
+        let mut filters = vec![RpcFilterType::Or(vec![
+            RpcFilterType::DataSize(3),
+            RpcFilterType::Not(Box::new(base58)),
+        ])];
+        optimize_filters(&mut filters);
+        assert_eq!(
+            filters,
+            vec![RpcFilterType::Or(vec![
+                RpcFilterType::DataSize(3),
+                RpcFilterType::Not(Box::new(raw)),
+            ])]
+        );
+    }
+
     #[test]
     fn test_rpc_verify_pubkey() {
         let pubkey = solana_pubkey::new_rand();
@@ -8731,6 +9831,90 @@ pub mod tests {
         .is_none());
     }
 
+    #[test]
+    fn test_get_program_data_index_filter() {
+        let program_id = Pubkey::new_unique();
+        let program_data_index = ProgramDataIndex::new(program_id, 8, 32).unwrap();
+        let mut account_indexes = AccountSecondaryIndexes::default();
+        account_indexes
+            .indexes
+            .insert(AccountIndex::ProgramData(program_data_index));
+        let data_key = Pubkey::new_unique();
+
+        // Filtering on the indexed range
+        assert!(matches!(
+            get_program_data_index_filter(
+                &program_id,
+                &[
+                    RpcFilterType::DataSize(72),
+                    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(8, data_key.to_bytes().to_vec())),
+                ],
+                &account_indexes,
+            ),
+            Some(IndexKey::ProgramData(index, key)) if index == program_data_index && key == data_key
+        ));
+
+        // Filtering on a range covering the indexed range
+        let mut bytes = vec![1, 2];
+        bytes.extend_from_slice(&data_key.to_bytes());
+        assert!(matches!(
+            get_program_data_index_filter(
+                &program_id,
+                &[RpcFilterType::Memcmp(Memcmp::new_raw_bytes(6, bytes))],
+                &account_indexes,
+            ),
+            Some(IndexKey::ProgramData(_, key)) if key == data_key
+        ));
+
+        // Wrong program id
+        assert!(get_program_data_index_filter(
+            &Pubkey::new_unique(),
+            &[RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                8,
+                data_key.to_bytes().to_vec()
+            ))],
+            &account_indexes,
+        )
+        .is_none());
+
+        // Wrong offset
+        assert!(get_program_data_index_filter(
+            &program_id,
+            &[RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                9,
+                data_key.to_bytes().to_vec()
+            ))],
+            &account_indexes,
+        )
+        .is_none());
+
+        // Too few bytes
+        assert!(get_program_data_index_filter(
+            &program_id,
+            &[RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                8,
+                data_key.to_bytes()[..31].to_vec()
+            ))],
+            &account_indexes,
+        )
+        .is_none());
+
+        // Excluded key
+        account_indexes.keys = Some(AccountSecondaryIndexesIncludeExclude {
+            keys: [data_key].into_iter().collect(),
+            exclude: true,
+        });
+        assert!(get_program_data_index_filter(
+            &program_id,
+            &[RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                8,
+                data_key.to_bytes().to_vec()
+            ))],
+            &account_indexes,
+        )
+        .is_none());
+    }
+
     #[test]
     fn test_rpc_single_gossip() {
         let exit = Arc::new(AtomicBool::new(false));
@@ -1,8 +1,13 @@
 use {
+    solana_pubkey::Pubkey,
     solana_rpc_client_api::{config::RpcLargestAccountsFilter, response::RpcAccountBalance},
+    solana_runtime::bank::Bank,
     std::{
-        collections::HashMap,
-        time::{Duration, SystemTime},
+        collections::{HashMap, VecDeque},
+        fmt,
+        str::FromStr,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant, SystemTime},
     },
 };
 
@@ -58,6 +63,119 @@ impl LargestAccountsCache {
     }
 }
 
+/// Opaque id of a `getProgramAccountsV2` cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct ProgramAccountsCursorId(u128);
+
+impl fmt::Display for ProgramAccountsCursorId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&bs58::encode(self.0.to_le_bytes()).into_string())
+    }
+}
+
+impl FromStr for ProgramAccountsCursorId {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = bs58::decode(s).into_vec().map_err(|_| ())?;
+        let bytes = <[u8; 16]>::try_from(bytes).map_err(|_| ())?;
+        Ok(Self(u128::from_le_bytes(bytes)))
+    }
+}
+
+/// Position of a `getProgramAccountsV2` page. It pins the bank of the first
+/// page, so that the following pages are read from the same bank even after
+/// it was pruned from the bank forks. Pages can no longer be read once the
+/// root advanced past that bank.
+#[derive(Debug, Clone)]
+pub(crate) struct ProgramAccountsCursor {
+    pub(crate) bank: Arc<Bank>,
+    pub(crate) program_id: Pubkey,
+    pub(crate) last_pubkey: Pubkey,
+    created: Instant,
+}
+
+/// Cursors of `getProgramAccountsV2`, expiring after a fixed time. Once full,
+/// the oldest cursors are evicted first.
+#[derive(Debug)]
+pub(crate) struct ProgramAccountsCursorCache {
+    max_cursors: usize,
+    ttl: Duration,
+    cursors: Mutex<ProgramAccountsCursors>,
+}
+
+#[derive(Debug, Default)]
+struct ProgramAccountsCursors {
+    by_id: HashMap<ProgramAccountsCursorId, ProgramAccountsCursor>,
+    /// Ids in the order their cursors were created, which is also the order
+    /// in which they expire
+    ids: VecDeque<ProgramAccountsCursorId>,
+}
+
+impl ProgramAccountsCursors {
+    fn evict_expired(&mut self, ttl: Duration) {
+        while let Some(id) = self.ids.front() {
+            if self.by_id[id].created.elapsed() < ttl {
+                break;
+            }
+            self.by_id.remove(id);
+            self.ids.pop_front();
+        }
+    }
+}
+
+impl ProgramAccountsCursorCache {
+    pub(crate) fn new(max_cursors: usize, ttl: Duration) -> Self {
+        Self {
+            max_cursors,
+            ttl,
+            cursors: Mutex::default(),
+        }
+    }
+
+    /// Stores the position after `last_pubkey` in `bank`, returning the id
+    /// of the new cursor
+    pub(crate) fn insert(
+        &self,
+        bank: Arc<Bank>,
+        program_id: Pubkey,
+        last_pubkey: Pubkey,
+    ) -> ProgramAccountsCursorId {
+        let mut cursors = self.cursors.lock().unwrap();
+        cursors.evict_expired(self.ttl);
+        while cursors.ids.len() >= self.max_cursors {
+            let Some(id) = cursors.ids.pop_front() else {
+                break;
+            };
+            cursors.by_id.remove(&id);
+        }
+        let id = loop {
+            let id = ProgramAccountsCursorId(rand::random());
+            if !cursors.by_id.contains_key(&id) {
+                break id;
+            }
+        };
+        cursors.by_id.insert(
+            id,
+            ProgramAccountsCursor {
+                bank,
+                program_id,
+                last_pubkey,
+                created: Instant::now(),
+            },
+        );
+        cursors.ids.push_back(id);
+        id
+    }
+
+    /// Returns the cursor of `id`, unless it expired or was evicted
+    pub(crate) fn get(&self, id: &ProgramAccountsCursorId) -> Option<ProgramAccountsCursor> {
+        let mut cursors = self.cursors.lock().unwrap();
+        cursors.evict_expired(self.ttl);
+        cursors.by_id.get(id).cloned()
+    }
+}
+
 #[cfg(test)]
 pub mod test {
     use super::*;
@@ -74,4 +192,40 @@ pub mod test {
         std::thread::sleep(Duration::from_secs(1));
         assert_eq!(cache.get_largest_accounts(&filter), None);
     }
+
+    #[test]
+    fn test_program_accounts_cursor_cache() {
+        let bank = Arc::new(Bank::default_for_tests());
+        let program_id = Pubkey::new_unique();
+        let cache = ProgramAccountsCursorCache::new(2, Duration::from_secs(60));
+
+        let ids = (0..3)
+            .map(|_| cache.insert(bank.clone(), program_id, Pubkey::new_unique()))
+            .collect::<Vec<_>>();
+        // The oldest cursor was evicted to make room
+        assert!(cache.get(&ids[0]).is_none());
+        for id in &ids[1..] {
+            let cursor = cache.get(id).unwrap();
+            assert_eq!(cursor.bank.slot(), bank.slot());
+            assert_eq!(cursor.program_id, program_id);
+            assert_eq!(id.to_string().parse::<ProgramAccountsCursorId>(), Ok(*id));
+        }
+        assert!("III".parse::<ProgramAccountsCursorId>().is_err());
+        assert!(bs58::encode([0; 8])
+            .into_string()
+            .parse::<ProgramAccountsCursorId>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_program_accounts_cursors_expire() {
+        let bank = Arc::new(Bank::default_for_tests());
+        let cache = ProgramAccountsCursorCache::new(2, Duration::from_millis(10));
+        let id = cache.insert(bank.clone(), Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(cache.get(&id).is_some());
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(cache.get(&id).is_none());
+        // Expired cursors no longer pin their bank
+        assert_eq!(Arc::strong_count(&bank), 1);
+    }
 }
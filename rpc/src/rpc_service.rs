@@ -45,7 +45,9 @@ use {
         send_transaction_service::{self, SendTransactionService},
         transaction_client::{ConnectionCacheClient, TpuClientNextClient, TransactionClient},
     },
-    solana_storage_bigtable::CredentialType,
+    solana_storage_bigtable::{
+        CredentialType, FileLedgerStorageConfig, LedgerStorageConfig, LongTermLedgerStorageConfig,
+    },
     solana_validator_exit::Exit,
     std::{
         net::{SocketAddr, UdpSocket},
@@ -741,22 +743,31 @@ impl JsonRpcService {
                 ref bigtable_app_profile_id,
                 timeout,
                 max_message_size,
+                ref long_term_storage_path,
             }) = config.rpc_bigtable_config
             {
-                let bigtable_config = solana_storage_bigtable::LedgerStorageConfig {
-                    read_only: !enable_bigtable_ledger_upload,
-                    timeout,
-                    credential_type: CredentialType::Filepath(None),
-                    instance_name: bigtable_instance_name.clone(),
-                    app_profile_id: bigtable_app_profile_id.clone(),
-                    max_message_size,
+                let storage_config = match long_term_storage_path {
+                    Some(path) => {
+                        LongTermLedgerStorageConfig::FileSystem(FileLedgerStorageConfig {
+                            path: path.clone(),
+                            read_only: !enable_bigtable_ledger_upload,
+                        })
+                    }
+                    None => LongTermLedgerStorageConfig::BigTable(LedgerStorageConfig {
+                        read_only: !enable_bigtable_ledger_upload,
+                        timeout,
+                        credential_type: CredentialType::Filepath(None),
+                        instance_name: bigtable_instance_name.clone(),
+                        app_profile_id: bigtable_app_profile_id.clone(),
+                        max_message_size,
+                    }),
                 };
                 runtime
-                    .block_on(solana_storage_bigtable::LedgerStorage::new_with_config(
-                        bigtable_config,
+                    .block_on(solana_storage_bigtable::new_long_term_ledger_storage(
+                        storage_config,
                     ))
                     .map(|bigtable_ledger_storage| {
-                        info!("BigTable ledger storage initialized");
+                        info!("Long-term ledger storage initialized");
 
                         let bigtable_ledger_upload_service = if enable_bigtable_ledger_upload {
                             Some(Arc::new(BigTableUploadService::new_with_config(
@@ -778,7 +789,7 @@ impl JsonRpcService {
                         )
                     })
                     .unwrap_or_else(|err| {
-                        error!("Failed to initialize BigTable ledger storage: {err:?}");
+                        error!("Failed to initialize long-term ledger storage: {err:?}");
                         (None, None)
                     })
             } else {
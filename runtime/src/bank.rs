@@ -45,7 +45,7 @@ use {
         inflation_rewards::points::InflationPointCalculationEvent,
         installed_scheduler_pool::{BankWithScheduler, InstalledSchedulerRwLock},
         rent_collector::RentCollectorWithMetrics,
-        runtime_config::RuntimeConfig,
+        runtime_config::{MissingAccountLoader, RuntimeConfig},
         snapshot_hash::SnapshotHash,
         stake_account::StakeAccount,
         stake_weighted_timestamp::{
@@ -62,6 +62,7 @@ use {
     agave_reserved_account_keys::ReservedAccountKeys,
     agave_syscalls::{
         create_program_runtime_environment_v1, create_program_runtime_environment_v2,
+        create_syscall_tracing_program_runtime_environment_v1,
     },
     ahash::{AHashSet, RandomState},
     dashmap::DashMap,
@@ -77,6 +78,7 @@ use {
         account_locks::validate_account_locks,
         accounts::{AccountAddressFilter, Accounts, PubkeyAccountSlot},
         accounts_db::{AccountStorageEntry, AccountsDb, AccountsDbConfig, DuplicatesLtHash},
+        accounts_file::MatchAccountOwnerError,
         accounts_hash::AccountsLtHash,
         accounts_index::{IndexKey, ScanConfig, ScanResult},
         accounts_update_notifier_interface::AccountsUpdateNotifier,
@@ -105,12 +107,16 @@ use {
     solana_keypair::Keypair,
     solana_lattice_hash::lt_hash::LtHash,
     solana_measure::{meas_dur, measure::Measure, measure_time, measure_us},
-    solana_message::{inner_instruction::InnerInstructions, AccountKeys, SanitizedMessage},
+    solana_message::{inner_instruction::InnerInstructions, SanitizedMessage},
     solana_native_token::LAMPORTS_PER_SOL,
     solana_packet::PACKET_DATA_SIZE,
     solana_precompile_error::PrecompileError,
     solana_program_runtime::{
-        invoke_context::BuiltinFunctionWithContext, loaded_programs::ProgramCacheEntry,
+        invoke_context::{BuiltinFunctionWithContext, InstructionTrace},
+        loaded_programs::{
+            ProgramCache, ProgramCacheEntry, ProgramCacheEntryType, MAX_LOADED_ENTRY_COUNT,
+        },
+        persistent_program_cache::PersistentProgramCache,
     },
     solana_pubkey::Pubkey,
     solana_rent_collector::RentCollector,
@@ -315,6 +321,33 @@ pub struct TransactionSimulationResult {
     pub loaded_accounts_data_size: u32,
     pub return_data: Option<TransactionReturnData>,
     pub inner_instructions: Option<Vec<InnerInstructions>>,
+    /// Recorded when the simulation enables instruction trace recording
+    pub instruction_traces: Option<Vec<InstructionTrace>>,
+}
+
+/// What a transaction simulation records, see
+/// [Bank::simulate_transaction_with_config]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TransactionSimulationConfig {
+    /// Record the instructions invoked through CPI
+    pub enable_cpi_recording: bool,
+    /// Record a trace of every executed instruction, including the syscalls
+    /// of programs
+    pub enable_instruction_trace_recording: bool,
+}
+
+/// The outcome of one transaction of a simulated bundle, see
+/// [`Bank::simulate_bundle`].
+#[derive(Debug, PartialEq)]
+pub struct BundleTransactionSimulationResult {
+    /// The transaction's accounts as it saw them, in the order of its account
+    /// keys.
+    pub pre_simulation_accounts: Vec<TransactionAccount>,
+    /// The accounts in `simulation_result.post_simulation_accounts` are the
+    /// transaction's accounts as the next transaction sees them, in the order
+    /// of its account keys. If the transaction failed, only its fee was
+    /// charged.
+    pub simulation_result: TransactionSimulationResult,
 }
 
 #[derive(Clone, Debug)]
@@ -547,12 +580,15 @@ impl PartialEq for Bank {
             collector_fee_details: _,
             compute_budget: _,
             transaction_account_lock_limit: _,
+            missing_account_loader: _,
+            missing_account_load_locks: _,
             fee_structure: _,
             cache_for_accounts_lt_hash: _,
             stats_for_accounts_lt_hash: _,
             block_id,
             bank_hash_stats: _,
             epoch_rewards_calculation_cache: _,
+            syscall_tracing_program_cache: _,
             // Ignore new fields explicitly if they do not impact PartialEq.
             // Adding ".." will remove compile-time checks that if a new field
             // is added to the struct, this PartialEq is accordingly updated.
@@ -866,6 +902,13 @@ pub struct Bank {
     /// The max number of accounts that a transaction may lock.
     transaction_account_lock_limit: Option<usize>,
 
+    /// Source of accounts that transactions load but this bank does not have.
+    missing_account_loader: Option<Arc<dyn MissingAccountLoader>>,
+
+    /// Serializes the loads of each missing account, so that it is fetched and
+    /// stored only once.
+    missing_account_load_locks: DashMap<Pubkey, Arc<Mutex<()>>>,
+
     /// Fee structure to use for assessing transaction fees.
     fee_structure: FeeStructure,
 
@@ -903,6 +946,11 @@ pub struct Bank {
     /// This is used to avoid recalculating the same epoch rewards at epoch boundary.
     /// The hashmap is keyed by parent_hash.
     epoch_rewards_calculation_cache: Arc<Mutex<HashMap<Hash, Arc<PartitionedRewardsCalculation>>>>,
+
+    /// The program cache of simulations which trace syscalls, shared by the banks of its epoch
+    /// so that programs are not compiled again for every simulation. The feature set, and with
+    /// it the program runtime environment, only changes at epoch boundaries.
+    syscall_tracing_program_cache: Arc<Mutex<Option<(Epoch, EpochProgramCache)>>>,
 }
 
 #[derive(Debug)]
@@ -913,6 +961,7 @@ struct VoteReward {
 }
 
 type VoteRewards = DashMap<Pubkey, VoteReward, RandomState>;
+type EpochProgramCache = Arc<RwLock<ProgramCache<BankForks>>>;
 
 #[derive(Debug, Default)]
 pub struct NewBankOptions {
@@ -1091,6 +1140,8 @@ impl Bank {
             collector_fee_details: RwLock::new(CollectorFeeDetails::default()),
             compute_budget: None,
             transaction_account_lock_limit: None,
+            missing_account_loader: None,
+            missing_account_load_locks: DashMap::default(),
             fee_structure: FeeStructure::default(),
             #[cfg(feature = "dev-context-only-utils")]
             hash_overrides: Arc::new(Mutex::new(HashOverrides::default())),
@@ -1100,6 +1151,7 @@ impl Bank {
             block_id: RwLock::new(None),
             bank_hash_stats: AtomicBankHashStats::default(),
             epoch_rewards_calculation_cache: Arc::new(Mutex::new(HashMap::default())),
+            syscall_tracing_program_cache: Arc::default(),
         };
 
         bank.transaction_processor =
@@ -1132,6 +1184,7 @@ impl Bank {
         bank.ancestors = Ancestors::from(vec![bank.slot()]);
         bank.compute_budget = runtime_config.compute_budget;
         bank.transaction_account_lock_limit = runtime_config.transaction_account_lock_limit;
+        bank.missing_account_loader = runtime_config.missing_account_loader.clone();
         bank.transaction_debug_keys = debug_keys;
         bank.cluster_type = Some(genesis_config.cluster_type);
 
@@ -1344,6 +1397,8 @@ impl Bank {
             collector_fee_details: RwLock::new(CollectorFeeDetails::default()),
             compute_budget: parent.compute_budget,
             transaction_account_lock_limit: parent.transaction_account_lock_limit,
+            missing_account_loader: parent.missing_account_loader.clone(),
+            missing_account_load_locks: DashMap::default(),
             fee_structure: parent.fee_structure.clone(),
             #[cfg(feature = "dev-context-only-utils")]
             hash_overrides: parent.hash_overrides.clone(),
@@ -1353,6 +1408,7 @@ impl Bank {
             block_id: RwLock::new(None),
             bank_hash_stats: AtomicBankHashStats::default(),
             epoch_rewards_calculation_cache: parent.epoch_rewards_calculation_cache.clone(),
+            syscall_tracing_program_cache: parent.syscall_tracing_program_cache.clone(),
         };
 
         let (_, ancestors_time_us) = measure_us!({
@@ -1491,6 +1547,7 @@ impl Bank {
         if program_cache.upcoming_environments.is_some() {
             if let Some((key, program_to_recompile)) = program_cache.programs_to_recompile.pop() {
                 let effective_epoch = program_cache.latest_root_epoch.saturating_add(1);
+                let persistent_cache = program_cache.persistent_cache.clone();
                 drop(program_cache);
                 let environments_for_epoch = self
                     .transaction_processor
@@ -1505,6 +1562,7 @@ impl Bank {
                     self.slot,
                     &mut ExecuteTimings::default(),
                     false,
+                    persistent_cache.as_deref(),
                 ) {
                     recompiled.tx_usage_counter.fetch_add(
                         program_to_recompile
@@ -1794,6 +1852,8 @@ impl Bank {
             collector_fee_details: RwLock::new(CollectorFeeDetails::default()),
             compute_budget: runtime_config.compute_budget,
             transaction_account_lock_limit: runtime_config.transaction_account_lock_limit,
+            missing_account_loader: runtime_config.missing_account_loader.clone(),
+            missing_account_load_locks: DashMap::default(),
             fee_structure: FeeStructure::default(),
             #[cfg(feature = "dev-context-only-utils")]
             hash_overrides: Arc::new(Mutex::new(HashOverrides::default())),
@@ -1803,6 +1863,7 @@ impl Bank {
             block_id: RwLock::new(None),
             bank_hash_stats: AtomicBankHashStats::new(&fields.bank_hash_stats),
             epoch_rewards_calculation_cache: Arc::new(Mutex::new(HashMap::default())),
+            syscall_tracing_program_cache: Arc::default(),
         };
 
         bank.transaction_processor =
@@ -3079,10 +3140,26 @@ impl Bank {
         &self,
         transaction: &impl TransactionWithMeta,
         enable_cpi_recording: bool,
+    ) -> TransactionSimulationResult {
+        self.simulate_transaction_with_config(
+            transaction,
+            TransactionSimulationConfig {
+                enable_cpi_recording,
+                ..TransactionSimulationConfig::default()
+            },
+        )
+    }
+
+    /// Run transactions against a frozen bank without committing the results,
+    /// recording what `config` asks for
+    pub fn simulate_transaction_with_config(
+        &self,
+        transaction: &impl TransactionWithMeta,
+        config: TransactionSimulationConfig,
     ) -> TransactionSimulationResult {
         assert!(self.is_frozen(), "simulation bank must be frozen");
 
-        self.simulate_transaction_unchecked(transaction, enable_cpi_recording)
+        self.simulate_transaction_unchecked_with_config(transaction, config)
     }
 
     /// Run transactions against a bank without committing the results; does not check if the bank
@@ -3091,18 +3168,174 @@ impl Bank {
         &self,
         transaction: &impl TransactionWithMeta,
         enable_cpi_recording: bool,
+    ) -> TransactionSimulationResult {
+        self.simulate_transaction_unchecked_with_config(
+            transaction,
+            TransactionSimulationConfig {
+                enable_cpi_recording,
+                ..TransactionSimulationConfig::default()
+            },
+        )
+    }
+
+    /// Like [Self::simulate_transaction_unchecked], recording what `config`
+    /// asks for
+    pub fn simulate_transaction_unchecked_with_config(
+        &self,
+        transaction: &impl TransactionWithMeta,
+        config: TransactionSimulationConfig,
     ) -> TransactionSimulationResult {
         let account_keys = transaction.account_keys();
         let number_of_accounts = account_keys.len();
-        let account_overrides = self.get_account_overrides_for_simulation(&account_keys);
+        let account_overrides = self.get_account_overrides_for_simulation(
+            account_keys
+                .iter()
+                .any(|pubkey| *pubkey == sysvar::slot_history::id()),
+        );
         let batch = self.prepare_unlocked_batch_from_single_tx(transaction);
+
+        let processing_result = self
+            .load_and_execute_transactions_for_simulation(&batch, &account_overrides, config)
+            .pop()
+            .unwrap_or(Err(TransactionError::InvalidProgramForExecution));
+        Self::transaction_simulation_result(processing_result, number_of_accounts)
+    }
+
+    /// Run a bundle of transactions in order against a frozen bank without
+    /// committing the results. Each transaction sees the writes of the
+    /// transactions before it, and all of them see `state_overrides` in place
+    /// of the bank's accounts. An overridden account with zero lamports does
+    /// not exist.
+    ///
+    /// Overrides of executable program accounts are not seen by the program
+    /// cache, so programs are still executed as deployed in the bank.
+    pub fn simulate_bundle(
+        &self,
+        transactions: &[impl TransactionWithMeta],
+        state_overrides: HashMap<Pubkey, AccountSharedData>,
+        enable_cpi_recording: bool,
+    ) -> Vec<BundleTransactionSimulationResult> {
+        assert!(self.is_frozen(), "simulation bank must be frozen");
+
+        let mut account_overrides =
+            self.get_account_overrides_for_simulation(transactions.iter().any(|transaction| {
+                transaction
+                    .account_keys()
+                    .iter()
+                    .any(|pubkey| *pubkey == sysvar::slot_history::id())
+            }));
+        for (pubkey, account) in &state_overrides {
+            account_overrides.set_account(pubkey, Some(account.clone()));
+        }
+
+        let tx_account_lock_limit = self.get_transaction_account_lock_limit();
+        let lock_results = transactions
+            .iter()
+            .map(|transaction| {
+                validate_account_locks(transaction.account_keys(), tx_account_lock_limit)
+            })
+            .collect();
+        let mut batch =
+            TransactionBatch::new(lock_results, self, OwnedOrBorrowed::Borrowed(transactions));
+        batch.set_needs_unlock(false);
+
+        let processing_results = self.load_and_execute_transactions_for_simulation(
+            &batch,
+            &account_overrides,
+            TransactionSimulationConfig {
+                enable_cpi_recording,
+                ..TransactionSimulationConfig::default()
+            },
+        );
+
+        // Track account states through the bundle the same way the SVM does
+        // within a batch, to report what each transaction saw.
+        let mut bundle_accounts = state_overrides;
+        let bundle_account = |bundle_accounts: &HashMap<Pubkey, AccountSharedData>,
+                              pubkey: &Pubkey| {
+            let account = match bundle_accounts.get(pubkey) {
+                Some(account) if account.lamports() == 0 => AccountSharedData::default(),
+                Some(account) => account.clone(),
+                None => self.get_account_with_fixed_root(pubkey).unwrap_or_default(),
+            };
+            (*pubkey, account)
+        };
+        transactions
+            .iter()
+            .zip(processing_results)
+            .map(|(transaction, processing_result)| {
+                let account_keys = transaction.account_keys();
+                let pre_simulation_accounts = account_keys
+                    .iter()
+                    .map(|pubkey| bundle_account(&bundle_accounts, pubkey))
+                    .collect();
+
+                match &processing_result {
+                    Ok(ProcessedTransaction::Executed(executed_tx))
+                        if executed_tx.was_successful() =>
+                    {
+                        let accounts = &executed_tx.loaded_transaction.accounts;
+                        for (i, (pubkey, account)) in accounts.iter().enumerate() {
+                            if i < account_keys.len() && transaction.is_writable(i) {
+                                bundle_accounts.insert(*pubkey, account.clone());
+                            }
+                        }
+                    }
+                    Ok(ProcessedTransaction::Executed(executed_tx)) => {
+                        for (pubkey, account) in &executed_tx.loaded_transaction.rollback_accounts {
+                            bundle_accounts.insert(*pubkey, account.clone());
+                        }
+                    }
+                    Ok(ProcessedTransaction::FeesOnly(fees_only_tx)) => {
+                        for (pubkey, account) in &fees_only_tx.rollback_accounts {
+                            bundle_accounts.insert(*pubkey, account.clone());
+                        }
+                    }
+                    Err(_) => {}
+                }
+
+                let mut simulation_result =
+                    Self::transaction_simulation_result(processing_result, account_keys.len());
+                simulation_result.post_simulation_accounts = account_keys
+                    .iter()
+                    .map(|pubkey| bundle_account(&bundle_accounts, pubkey))
+                    .collect();
+                BundleTransactionSimulationResult {
+                    pre_simulation_accounts,
+                    simulation_result,
+                }
+            })
+            .collect()
+    }
+
+    fn load_and_execute_transactions_for_simulation(
+        &self,
+        batch: &TransactionBatch<impl TransactionWithMeta>,
+        account_overrides: &AccountOverrides,
+        config: TransactionSimulationConfig,
+    ) -> Vec<TransactionProcessingResult> {
+        let TransactionSimulationConfig {
+            enable_cpi_recording,
+            enable_instruction_trace_recording,
+        } = config;
         let mut timings = ExecuteTimings::default();
 
+        // Programs are traced in an environment of their own, so that the
+        // syscalls of other executions are not traced
+        let syscall_tracing_transaction_processor;
+        let transaction_processor = if enable_instruction_trace_recording {
+            syscall_tracing_transaction_processor =
+                self.new_syscall_tracing_transaction_processor();
+            &syscall_tracing_transaction_processor
+        } else {
+            &self.transaction_processor
+        };
+
         let LoadAndExecuteTransactionsOutput {
-            mut processing_results,
-            ..
-        } = self.load_and_execute_transactions(
-            &batch,
+            processing_results, ..
+        } = self.load_and_execute_transactions_with_processor(
+            transaction_processor,
+            batch,
             // After simulation, transactions will need to be forwarded to the leader
             // for processing. During forwarding, the transaction could expire if the
             // delay is not accounted for.
@@ -3110,7 +3343,7 @@ impl Bank {
             &mut timings,
             &mut TransactionErrorMetrics::default(),
             TransactionProcessingConfig {
-                account_overrides: Some(&account_overrides),
+                account_overrides: Some(account_overrides),
                 check_program_modification_slot: self.check_program_modification_slot,
                 log_messages_bytes_limit: None,
                 limit_to_load_programs: true,
@@ -3119,21 +3352,118 @@ impl Bank {
                     enable_log_recording: true,
                     enable_return_data_recording: true,
                     enable_transaction_balance_recording: false,
+                    enable_instruction_trace_recording,
+                    enable_instruction_trace_account_data_recording: false,
                 },
             },
         );
 
         debug!("simulate_transaction: {:?}", timings);
+        processing_results
+    }
 
-        let processing_result = processing_results
-            .pop()
-            .unwrap_or(Err(TransactionError::InvalidProgramForExecution));
+    /// Execute and commit `transaction` as replay would, with its programs in
+    /// a debugging environment, returning its commit result and a trace of
+    /// every instruction it executed. The traces of sBPF programs include the
+    /// register states of every sBPF instruction executed.
+    pub fn process_transaction_with_instruction_traces(
+        &self,
+        transaction: VersionedTransaction,
+    ) -> Result<(TransactionCommitResult, Vec<InstructionTrace>)> {
+        let batch = self.prepare_entry_batch(vec![transaction])?;
+        let mut timings = ExecuteTimings::default();
+        let debugging_transaction_processor = self.new_debugging_transaction_processor();
+        let LoadAndExecuteTransactionsOutput {
+            processing_results,
+            processed_counts,
+            ..
+        } = self.load_and_execute_transactions_with_processor(
+            &debugging_transaction_processor,
+            &batch,
+            MAX_PROCESSING_AGE,
+            &mut timings,
+            &mut TransactionErrorMetrics::default(),
+            TransactionProcessingConfig {
+                account_overrides: None,
+                check_program_modification_slot: self.check_program_modification_slot,
+                log_messages_bytes_limit: None,
+                limit_to_load_programs: false,
+                recording_config: ExecutionRecordingConfig {
+                    enable_cpi_recording: true,
+                    enable_log_recording: true,
+                    enable_return_data_recording: true,
+                    enable_transaction_balance_recording: false,
+                    enable_instruction_trace_recording: true,
+                    enable_instruction_trace_account_data_recording: false,
+                },
+            },
+        );
+        let instruction_traces = match processing_results.first() {
+            Some(Ok(ProcessedTransaction::Executed(executed_tx))) => executed_tx
+                .execution_details
+                .instruction_traces
+                .clone()
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        let mut commit_results = self.commit_transactions(
+            batch.sanitized_transactions(),
+            processing_results,
+            &processed_counts,
+            &mut timings,
+        );
+        Ok((commit_results.remove(0), instruction_traces))
+    }
+
+    fn new_debugging_transaction_processor(&self) -> TransactionBatchProcessor<BankForks> {
+        let program_runtime_environment_v1 = create_program_runtime_environment_v1(
+            &self.feature_set.runtime_features(),
+            &self.compute_budget().unwrap_or_default().to_budget(),
+            false, /* deployment */
+            true,  /* debugging_features */
+        )
+        .unwrap();
+        self.transaction_processor
+            .new_with_program_runtime_environment_v1(Arc::new(program_runtime_environment_v1))
+    }
+
+    /// Returns a transaction processor whose programs trace their syscalls. Its program cache is
+    /// reused by the following banks of this epoch.
+    fn new_syscall_tracing_transaction_processor(&self) -> TransactionBatchProcessor<BankForks> {
+        let mut syscall_tracing_program_cache = self.syscall_tracing_program_cache.lock().unwrap();
+        if let Some((epoch, program_cache)) = syscall_tracing_program_cache.as_ref() {
+            if *epoch == self.epoch {
+                return self
+                    .transaction_processor
+                    .new_with_program_cache(Arc::clone(program_cache));
+            }
+        }
+        let program_runtime_environment_v1 = create_syscall_tracing_program_runtime_environment_v1(
+            &self.feature_set.runtime_features(),
+            &self.compute_budget().unwrap_or_default().to_budget(),
+            false, /* deployment */
+            false, /* debugging_features */
+        )
+        .unwrap();
+        let transaction_processor = self
+            .transaction_processor
+            .new_with_program_runtime_environment_v1(Arc::new(program_runtime_environment_v1));
+        *syscall_tracing_program_cache =
+            Some((self.epoch, Arc::clone(&transaction_processor.program_cache)));
+        transaction_processor
+    }
+
+    fn transaction_simulation_result(
+        processing_result: TransactionProcessingResult,
+        number_of_accounts: usize,
+    ) -> TransactionSimulationResult {
         let (
             post_simulation_accounts,
             result,
             logs,
             return_data,
             inner_instructions,
+            instruction_traces,
             units_consumed,
             loaded_accounts_data_size,
         ) = match processing_result {
@@ -3152,6 +3482,7 @@ impl Bank {
                         details.log_messages,
                         details.return_data,
                         details.inner_instructions,
+                        details.instruction_traces,
                         details.executed_units,
                         executed_tx.loaded_transaction.loaded_accounts_data_size,
                     )
@@ -3162,11 +3493,12 @@ impl Bank {
                     None,
                     None,
                     None,
+                    None,
                     0,
                     fees_only_tx.rollback_accounts.data_size() as u32,
                 ),
             },
-            Err(error) => (vec![], Err(error), None, None, None, 0, 0),
+            Err(error) => (vec![], Err(error), None, None, None, None, 0, 0),
         };
         let logs = logs.unwrap_or_default();
 
@@ -3178,13 +3510,14 @@ impl Bank {
             loaded_accounts_data_size,
             return_data,
             inner_instructions,
+            instruction_traces,
         }
     }
 
-    fn get_account_overrides_for_simulation(&self, account_keys: &AccountKeys) -> AccountOverrides {
+    fn get_account_overrides_for_simulation(&self, loads_slot_history: bool) -> AccountOverrides {
         let mut account_overrides = AccountOverrides::default();
         let slot_history_id = sysvar::slot_history::id();
-        if account_keys.iter().any(|pubkey| *pubkey == slot_history_id) {
+        if loads_slot_history {
             let current_account = self.get_account_with_fixed_root(&slot_history_id);
             let slot_history = current_account
                 .as_ref()
@@ -3246,6 +3579,25 @@ impl Bank {
         timings: &mut ExecuteTimings,
         error_counters: &mut TransactionErrorMetrics,
         processing_config: TransactionProcessingConfig,
+    ) -> LoadAndExecuteTransactionsOutput {
+        self.load_and_execute_transactions_with_processor(
+            &self.transaction_processor,
+            batch,
+            max_age,
+            timings,
+            error_counters,
+            processing_config,
+        )
+    }
+
+    fn load_and_execute_transactions_with_processor(
+        &self,
+        transaction_processor: &TransactionBatchProcessor<BankForks>,
+        batch: &TransactionBatch<impl TransactionWithMeta>,
+        max_age: usize,
+        timings: &mut ExecuteTimings,
+        error_counters: &mut TransactionErrorMetrics,
+        processing_config: TransactionProcessingConfig,
     ) -> LoadAndExecuteTransactionsOutput {
         let sanitized_txs = batch.sanitized_transactions();
 
@@ -3269,15 +3621,13 @@ impl Bank {
             rent_collector: Some(&rent_collector_with_metrics),
         };
 
-        let sanitized_output = self
-            .transaction_processor
-            .load_and_execute_sanitized_transactions(
-                self,
-                sanitized_txs,
-                check_results,
-                &processing_environment,
-                &processing_config,
-            );
+        let sanitized_output = transaction_processor.load_and_execute_sanitized_transactions(
+            self,
+            sanitized_txs,
+            check_results,
+            &processing_environment,
+            &processing_config,
+        );
 
         // Accumulate the errors returned by the batch processor.
         error_counters.accumulate(&sanitized_output.error_metrics);
@@ -3837,6 +4187,8 @@ impl Bank {
                 enable_log_recording: true,
                 enable_return_data_recording: true,
                 enable_transaction_balance_recording: false,
+                enable_instruction_trace_recording: false,
+                enable_instruction_trace_account_data_recording: false,
             },
             &mut ExecuteTimings::default(),
             Some(1000 * 1000),
@@ -3968,6 +4320,37 @@ impl Bank {
             .flush_accounts_cache(false, Some(self.slot()))
     }
 
+    /// Fetch an account this bank never had from the configured
+    /// [`MissingAccountLoader`] and store it, unless the bank is frozen.
+    fn load_missing_account(&self, pubkey: &Pubkey) -> Option<AccountSharedData> {
+        let loader = self.missing_account_loader.as_ref()?;
+        let load_lock = self
+            .missing_account_load_locks
+            .entry(*pubkey)
+            .or_default()
+            .clone();
+        let _load_guard = load_lock.lock().unwrap();
+        // Another load of the account may have stored it while this one waited
+        let account = match self
+            .rc
+            .accounts
+            .accounts_db
+            .load_with_fixed_root(&self.ancestors, pubkey)
+        {
+            Some((account, _)) => Some(account),
+            None => loader.load_missing_account(pubkey).inspect(|account| {
+                // Holding the freeze lock keeps the bank from being frozen
+                // while the account is stored
+                let freeze_lock = self.freeze_lock();
+                if *freeze_lock == Hash::default() && !self.freeze_started() {
+                    self.store_account_and_update_capitalization(pubkey, account);
+                }
+            }),
+        };
+        self.missing_account_load_locks.remove(pubkey);
+        account
+    }
+
     /// Technically this issues (or even burns!) new lamports,
     /// so be extra careful for its usage
     fn store_account_and_update_capitalization(
@@ -4247,6 +4630,28 @@ impl Bank {
         )
     }
 
+    /// Returns at most `limit` program accounts that pass `filter`, in pubkey order, starting
+    /// after `start_after`, with the pubkey to start the next page after unless this page
+    /// reached the last account
+    pub fn get_filtered_program_accounts_after<F: Fn(&AccountSharedData) -> bool>(
+        &self,
+        program_id: &Pubkey,
+        start_after: Option<&Pubkey>,
+        limit: usize,
+        filter: F,
+        byte_limit_for_scan: Option<usize>,
+    ) -> ScanResult<(Vec<TransactionAccount>, Option<Pubkey>)> {
+        self.rc.accounts.load_by_program_with_filter_after(
+            &self.ancestors,
+            self.bank_id,
+            program_id,
+            start_after,
+            limit,
+            filter,
+            byte_limit_for_scan,
+        )
+    }
+
     pub fn get_filtered_indexed_accounts<F: Fn(&AccountSharedData) -> bool>(
         &self,
         index_key: &IndexKey,
@@ -5630,11 +6035,19 @@ impl InvokeContextCallback for Bank {
 
 impl TransactionProcessingCallback for Bank {
     fn account_matches_owners(&self, account: &Pubkey, owners: &[Pubkey]) -> Option<usize> {
-        self.rc
+        match self
+            .rc
             .accounts
             .accounts_db
             .account_matches_owners(&self.ancestors, account, owners)
-            .ok()
+        {
+            Ok(index) => Some(index),
+            Err(MatchAccountOwnerError::NoMatch) => None,
+            Err(MatchAccountOwnerError::UnableToLoad) => {
+                let account = self.load_missing_account(account)?;
+                owners.iter().position(|owner| account.owner() == owner)
+            }
+        }
     }
 
     fn get_account_shared_data(&self, pubkey: &Pubkey) -> Option<AccountSharedData> {
@@ -5643,6 +6056,20 @@ impl TransactionProcessingCallback for Bank {
             .accounts_db
             .load_with_fixed_root(&self.ancestors, pubkey)
             .map(|(acc, _)| acc)
+            .or_else(|| {
+                // Closed accounts are still in the index, and must stay closed
+                self.missing_account_loader.as_ref()?;
+                matches!(
+                    self.rc.accounts.accounts_db.account_matches_owners(
+                        &self.ancestors,
+                        pubkey,
+                        &[]
+                    ),
+                    Err(MatchAccountOwnerError::UnableToLoad)
+                )
+                .then(|| self.load_missing_account(pubkey))
+                .flatten()
+            })
     }
 
     // NOTE: must hold idempotent for the same set of arguments
@@ -5912,6 +6339,13 @@ impl Bank {
         let environments = self
             .transaction_processor
             .get_environments_for_epoch(effective_epoch)?;
+        let persistent_cache = self
+            .transaction_processor
+            .program_cache
+            .read()
+            .unwrap()
+            .persistent_cache
+            .clone();
         load_program_with_pubkey(
             self,
             &environments,
@@ -5919,9 +6353,68 @@ impl Bank {
             self.slot(),
             &mut ExecuteTimings::default(), // Called by ledger-tool, metrics not accumulated.
             reload,
+            persistent_cache.as_deref(),
         )
     }
 
+    /// Loads the programs which `persistent_cache` recorded as loaded in the
+    /// current environment into the program cache, so that they don't have to
+    /// be loaded on first use. They are verified and compiled like any other
+    /// program. Returns the number of programs loaded.
+    pub fn load_persisted_programs(&self, persistent_cache: &PersistentProgramCache) -> usize {
+        use rayon::prelude::*;
+
+        let Some(environments) = self
+            .transaction_processor
+            .get_environments_for_epoch(self.epoch())
+        else {
+            return 0;
+        };
+        let loaded: HashSet<Pubkey> = self
+            .transaction_processor
+            .program_cache
+            .read()
+            .unwrap()
+            .get_flattened_entries(true, true)
+            .into_iter()
+            .map(|(program_id, _program)| program_id)
+            .collect();
+        let mut keys = persistent_cache.programs(&environments.program_runtime_v1);
+        keys.retain(|key| !loaded.contains(&key.program_id));
+        keys.truncate(MAX_LOADED_ENTRY_COUNT);
+        let programs: Vec<_> = keys
+            .into_par_iter()
+            .filter_map(|key| {
+                let program = load_program_with_pubkey(
+                    self,
+                    &environments,
+                    &key.program_id,
+                    self.slot(),
+                    &mut ExecuteTimings::default(),
+                    false,
+                    None,
+                )
+                .filter(|program| {
+                    matches!(program.program, ProgramCacheEntryType::Loaded(_))
+                        && program.deployment_slot == key.deployment_slot
+                });
+                if program.is_none() {
+                    // The program was closed, redeployed or no longer loads
+                    // in this environment
+                    persistent_cache.remove(&key);
+                }
+                Some((key.program_id, program?))
+            })
+            .collect();
+
+        let mut program_cache = self.transaction_processor.program_cache.write().unwrap();
+        let num_programs = programs.len();
+        for (program_id, program) in programs {
+            program_cache.assign_program(program_id, program);
+        }
+        num_programs
+    }
+
     pub fn withdraw(&self, pubkey: &Pubkey, lamports: u64) -> Result<()> {
         match self.get_account_with_fixed_root(pubkey) {
             Some(mut account) => {
@@ -255,6 +255,7 @@ fn new_executed_processing_result(
                 return_data: None,
                 executed_units: 0,
                 accounts_data_len_delta: 0,
+                instruction_traces: None,
             },
             programs_modified_by_tx: HashMap::new(),
         },
@@ -4622,6 +4623,8 @@ fn test_pre_post_transaction_balances() {
             enable_log_recording: false,
             enable_return_data_recording: false,
             enable_transaction_balance_recording: true,
+            enable_instruction_trace_recording: false,
+            enable_instruction_trace_account_data_recording: false,
         },
         &mut ExecuteTimings::default(),
         None,
@@ -8263,6 +8266,8 @@ fn test_tx_log_order(relax_intrabatch_account_locks: bool) {
                 enable_log_recording: true,
                 enable_return_data_recording: false,
                 enable_transaction_balance_recording: false,
+                enable_instruction_trace_recording: false,
+                enable_instruction_trace_account_data_recording: false,
             },
             &mut ExecuteTimings::default(),
             None,
@@ -8375,6 +8380,8 @@ fn test_tx_return_data() {
                     enable_log_recording: false,
                     enable_return_data_recording: true,
                     enable_transaction_balance_recording: false,
+                    enable_instruction_trace_recording: false,
+                    enable_instruction_trace_account_data_recording: false,
                 },
                 &mut ExecuteTimings::default(),
                 None,
@@ -11435,10 +11442,232 @@ fn test_failed_simulation_load_error() {
             loaded_accounts_data_size: 0,
             return_data: None,
             inner_instructions: None,
+            instruction_traces: None,
         }
     );
 }
 
+/// Test that simulations record instruction traces when asked to
+#[test]
+fn test_simulate_transaction_instruction_traces() {
+    let (genesis_config, mint_keypair) = create_genesis_config(LAMPORTS_PER_SOL);
+    let bank = Bank::new_for_tests(&genesis_config);
+    let (bank, _bank_forks) = bank.wrap_with_bank_forks_for_tests();
+    let transaction = system_transaction::transfer(
+        &mint_keypair,
+        &Pubkey::new_unique(),
+        LAMPORTS_PER_SOL / 2,
+        bank.last_blockhash(),
+    );
+
+    bank.freeze();
+    let sanitized = RuntimeTransaction::from_transaction_for_tests(transaction);
+    let simulation = bank.simulate_transaction(&sanitized, false);
+    assert_eq!(simulation.result, Ok(()));
+    assert_eq!(simulation.instruction_traces, None);
+
+    let simulation = bank.simulate_transaction_with_config(
+        &sanitized,
+        TransactionSimulationConfig {
+            enable_instruction_trace_recording: true,
+            ..TransactionSimulationConfig::default()
+        },
+    );
+    assert_eq!(simulation.result, Ok(()));
+    let instruction_traces = simulation.instruction_traces.unwrap();
+    assert_eq!(instruction_traces.len(), 1);
+    let instruction_trace = &instruction_traces[0];
+    assert_eq!(instruction_trace.program_id, system_program::id());
+    assert_eq!(instruction_trace.stack_height, 1);
+    assert_eq!(
+        instruction_trace.compute_units_consumed,
+        simulation.units_consumed
+    );
+    assert_eq!(
+        instruction_trace.compute_units_after,
+        instruction_trace.compute_units_before - instruction_trace.compute_units_consumed
+    );
+    assert!(instruction_trace.syscalls.is_empty());
+
+    // The following banks of the epoch reuse the programs loaded to trace syscalls
+    let child_bank = Arc::new(Bank::new_from_parent(
+        bank.clone(),
+        &Pubkey::default(),
+        bank.slot() + 1,
+    ));
+    assert_eq!(child_bank.epoch(), bank.epoch());
+    let program_cache = |bank: &Bank| {
+        bank.new_syscall_tracing_transaction_processor()
+            .program_cache
+    };
+    assert!(Arc::ptr_eq(
+        &program_cache(&bank),
+        &program_cache(&child_bank)
+    ));
+    assert!(!Arc::ptr_eq(
+        &program_cache(&bank),
+        &bank.transaction_processor.program_cache
+    ));
+}
+
+/// Test that transactions processed with instruction traces are charged fees
+/// and committed, and record the register states of sBPF programs
+#[test]
+fn test_process_transaction_with_instruction_traces() {
+    let (mut genesis_config, mint_keypair) = create_genesis_config(LAMPORTS_PER_SOL);
+    genesis_config.fee_rate_governor = FeeRateGovernor::new(5_000, 0);
+    let mut bank = Bank::new_for_tests(&genesis_config);
+    let mut feature_set = FeatureSet::all_enabled();
+    feature_set.deactivate(&feature_set::disable_sbpf_v0_execution::id());
+    feature_set.deactivate(&feature_set::reenable_sbpf_v0_execution::id());
+    bank.feature_set = Arc::new(feature_set);
+    let (root_bank, bank_forks) = bank.wrap_with_bank_forks_for_tests();
+
+    let program_id = Pubkey::new_unique();
+    let program_data = include_bytes!("../../../programs/bpf_loader/test_elfs/out/noop_aligned.so");
+    root_bank.store_account(
+        &program_id,
+        &AccountSharedData::from(Account {
+            lamports: Rent::default().minimum_balance(program_data.len()).min(1),
+            data: program_data.to_vec(),
+            owner: bpf_loader::id(),
+            executable: true,
+            rent_epoch: 0,
+        }),
+    );
+    // Advance the bank so that the program becomes effective
+    goto_end_of_slot(root_bank.clone());
+    let bank = new_from_parent_with_fork_next_slot(root_bank, bank_forks.as_ref());
+
+    let recipient = Pubkey::new_unique();
+    let message = Message::new(
+        &[
+            system_instruction::transfer(&mint_keypair.pubkey(), &recipient, LAMPORTS_PER_SOL / 2),
+            Instruction::new_with_bytes(program_id, &[], Vec::new()),
+        ],
+        Some(&mint_keypair.pubkey()),
+    );
+    let transaction = Transaction::new(&[&mint_keypair], message, bank.last_blockhash());
+    let pre_balance = bank.get_balance(&mint_keypair.pubkey());
+
+    let (commit_result, instruction_traces) = bank
+        .process_transaction_with_instruction_traces(transaction.into())
+        .unwrap();
+    assert_eq!(commit_result.unwrap().status, Ok(()));
+    assert_eq!(
+        bank.get_balance(&mint_keypair.pubkey()),
+        pre_balance - LAMPORTS_PER_SOL / 2 - 5_000
+    );
+    assert_eq!(bank.get_balance(&recipient), LAMPORTS_PER_SOL / 2);
+
+    let [transfer_trace, program_trace] = instruction_traces.as_slice() else {
+        panic!("unexpected traces {instruction_traces:?}");
+    };
+    assert_eq!(transfer_trace.program_id, system_program::id());
+    assert!(transfer_trace.register_trace.is_empty());
+    assert_eq!(program_trace.program_id, program_id);
+    // Every executed sBPF instruction consumes at least one compute unit
+    assert!(!program_trace.register_trace.is_empty());
+    assert!(program_trace.register_trace.len() as u64 <= program_trace.compute_units_consumed);
+}
+
+/// Test that bundle simulations thread writes and state overrides through
+/// their transactions without committing them
+#[test]
+fn test_simulate_bundle() {
+    let (genesis_config, _mint_keypair) = create_genesis_config(LAMPORTS_PER_SOL);
+    let bank = Bank::new_for_tests(&genesis_config);
+    let (bank, _bank_forks) = bank.wrap_with_bank_forks_for_tests();
+    bank.freeze();
+
+    // Alice only exists in the overrides, and Bob is funded by the first
+    // transaction to pay for the second.
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let carol = Pubkey::new_unique();
+    let state_overrides = HashMap::from([(
+        alice.pubkey(),
+        AccountSharedData::new(10 * LAMPORTS_PER_SOL, 0, &system_program::id()),
+    )]);
+    let transactions = [
+        system_transaction::transfer(
+            &alice,
+            &bob.pubkey(),
+            LAMPORTS_PER_SOL,
+            bank.last_blockhash(),
+        ),
+        system_transaction::transfer(&bob, &carol, LAMPORTS_PER_SOL / 2, bank.last_blockhash()),
+        system_transaction::transfer(&bob, &carol, 10 * LAMPORTS_PER_SOL, bank.last_blockhash()),
+    ]
+    .map(RuntimeTransaction::from_transaction_for_tests);
+
+    let results = bank.simulate_bundle(&transactions, state_overrides, false);
+    assert_eq!(results.len(), 3);
+    let lamports = |accounts: &[TransactionAccount], pubkey: &Pubkey| {
+        accounts
+            .iter()
+            .find(|(address, _)| address == pubkey)
+            .unwrap()
+            .1
+            .lamports()
+    };
+
+    let first = &results[0];
+    assert_eq!(first.simulation_result.result, Ok(()));
+    assert_eq!(
+        lamports(&first.pre_simulation_accounts, &alice.pubkey()),
+        10 * LAMPORTS_PER_SOL
+    );
+    assert_eq!(lamports(&first.pre_simulation_accounts, &bob.pubkey()), 0);
+    assert_eq!(
+        lamports(
+            &first.simulation_result.post_simulation_accounts,
+            &bob.pubkey()
+        ),
+        LAMPORTS_PER_SOL
+    );
+
+    let second = &results[1];
+    assert_eq!(second.simulation_result.result, Ok(()));
+    assert_eq!(
+        second.pre_simulation_accounts,
+        vec![
+            first.simulation_result.post_simulation_accounts[1].clone(),
+            (carol, AccountSharedData::default()),
+            first.simulation_result.post_simulation_accounts[2].clone(),
+        ]
+    );
+    assert_eq!(
+        lamports(&second.simulation_result.post_simulation_accounts, &carol),
+        LAMPORTS_PER_SOL / 2
+    );
+
+    // A failed transaction only charges its fee.
+    let third = &results[2];
+    assert_eq!(
+        third.simulation_result.result,
+        Err(TransactionError::InstructionError(
+            0,
+            SystemError::ResultWithNegativeLamports.into()
+        ))
+    );
+    let fee = lamports(&third.pre_simulation_accounts, &bob.pubkey())
+        - lamports(
+            &third.simulation_result.post_simulation_accounts,
+            &bob.pubkey(),
+        );
+    assert_eq!(fee, bank.get_lamports_per_signature());
+    assert_eq!(
+        lamports(&third.simulation_result.post_simulation_accounts, &carol),
+        LAMPORTS_PER_SOL / 2
+    );
+
+    // Nothing was committed.
+    assert_eq!(bank.get_balance(&alice.pubkey()), 0);
+    assert_eq!(bank.get_balance(&bob.pubkey()), 0);
+    assert_eq!(bank.get_balance(&carol), 0);
+}
+
 #[test]
 fn test_filter_program_errors_and_collect_fee_details() {
     // TX  | PROCESSING RESULT           | COLLECT            | COLLECT
@@ -12211,3 +12440,67 @@ fn test_should_use_vote_keyed_leader_schedule() {
         }
     }
 }
+
+#[derive(Debug)]
+struct CountingMissingAccountLoader {
+    account: AccountSharedData,
+    loads: AtomicUsize,
+}
+
+impl MissingAccountLoader for CountingMissingAccountLoader {
+    fn load_missing_account(&self, _pubkey: &Pubkey) -> Option<AccountSharedData> {
+        self.loads.fetch_add(1, Relaxed);
+        std::thread::sleep(Duration::from_millis(10));
+        Some(self.account.clone())
+    }
+}
+
+#[test]
+fn test_load_missing_account_concurrently() {
+    let (genesis_config, _mint_keypair) = create_genesis_config(LAMPORTS_PER_SOL);
+    let loader = Arc::new(CountingMissingAccountLoader {
+        account: AccountSharedData::new(42, 7, &Pubkey::new_unique()),
+        loads: AtomicUsize::new(0),
+    });
+    let runtime_config = RuntimeConfig {
+        missing_account_loader: Some(loader.clone()),
+        ..RuntimeConfig::default()
+    };
+    let bank = Bank::new_with_paths_for_tests(
+        &genesis_config,
+        Arc::new(runtime_config),
+        BankTestConfig::default(),
+        Vec::new(),
+    );
+    let capitalization = bank.capitalization();
+    // Creating the bank may have loaded missing accounts already
+    let loads = loader.loads.load(Relaxed);
+
+    // Concurrent loads of a missing account fetch and store it once
+    let pubkey = Pubkey::new_unique();
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                assert_eq!(
+                    bank.get_account_shared_data(&pubkey),
+                    Some(loader.account.clone())
+                );
+            });
+        }
+    });
+    assert_eq!(loader.loads.load(Relaxed), loads + 1);
+    assert_eq!(bank.capitalization(), capitalization + 42);
+
+    // Frozen banks return the accounts they load without storing them
+    bank.freeze();
+    let capitalization = bank.capitalization();
+    let loads = loader.loads.load(Relaxed);
+    let pubkey = Pubkey::new_unique();
+    assert_eq!(
+        bank.get_account_shared_data(&pubkey),
+        Some(loader.account.clone())
+    );
+    assert_eq!(loader.loads.load(Relaxed), loads + 1);
+    assert_eq!(bank.capitalization(), capitalization);
+    assert_eq!(bank.get_account_with_fixed_root_no_cache(&pubkey), None);
+}
@@ -1,4 +1,9 @@
-use solana_compute_budget::compute_budget::ComputeBudget;
+use {
+    solana_account::AccountSharedData,
+    solana_compute_budget::compute_budget::ComputeBudget,
+    solana_pubkey::Pubkey,
+    std::{fmt::Debug, sync::Arc},
+};
 
 #[cfg(feature = "frozen-abi")]
 impl ::solana_frozen_abi::abi_example::AbiExample for RuntimeConfig {
@@ -14,4 +19,20 @@ pub struct RuntimeConfig {
     pub compute_budget: Option<ComputeBudget>,
     pub log_messages_bytes_limit: Option<usize>,
     pub transaction_account_lock_limit: Option<usize>,
+    /// Source of accounts that transactions load but the bank does not have.
+    pub missing_account_loader: Option<Arc<dyn MissingAccountLoader>>,
+}
+
+/// Supplies accounts missing from a bank when transactions load them, for
+/// instance from another cluster. Accounts it returns are stored in the bank,
+/// unless the bank is frozen.
+///
+/// The loader is only asked for accounts that the bank never had, so closed
+/// accounts are not supplied again. It may be asked for the same account more
+/// than once, e.g. by frozen banks serving simulations, and should return the
+/// same account each time.
+pub trait MissingAccountLoader: Debug + Send + Sync {
+    /// Return the account at `pubkey`, or `None` if the source does not have
+    /// it either.
+    fn load_missing_account(&self, pubkey: &Pubkey) -> Option<AccountSharedData>;
 }
@@ -0,0 +1,415 @@
+//! Shared memory layout used by the banking stage to hand scheduling
+//! decisions to an external process.
+//!
+//! When the validator runs with `--external-scheduler-path`, it creates a file
+//! at that path and maps a [`Region`] over it. For each scheduling pass:
+//!
+//! 1. The validator writes up to [`MAX_TRANSACTIONS_PER_REQUEST`] of its
+//!    highest priority buffered transactions into [`Request`], then bumps
+//!    [`Header::request_sequence`].
+//! 2. The external scheduler observes the new sequence, picks which of those
+//!    transactions should run on which worker, writes them into [`Response`]
+//!    and stores the same sequence into [`Header::response_sequence`].
+//! 3. The validator takes the account locks for each assignment, in order, and
+//!    sends the resulting batches to its consume workers. Assignments that
+//!    conflict with in-flight work are returned to the validator's queue and
+//!    offered again later.
+//!
+//! If a request is not answered in time, the validator schedules with its
+//! built-in scheduler instead and leaves the request in place. Once the
+//! external scheduler answers that request, for example after it (re)starts,
+//! the validator resumes publishing new requests.
+//!
+//! Fields written by one side while the other may be reading them are either
+//! atomics or only accessed through volatile reads, so a misbehaving peer
+//! cannot cause the other side to act on values the compiler assumed stable.
+//!
+//! The external scheduler never sees or executes transactions directly, so a
+//! misbehaving or absent scheduler can delay block production but cannot
+//! break account locking guarantees.
+
+use {
+    memmap2::MmapMut,
+    std::{
+        fs::{File, OpenOptions},
+        io,
+        mem::size_of,
+        path::Path,
+        ptr::{self, addr_of, addr_of_mut},
+        sync::atomic::{AtomicU32, AtomicU64, Ordering},
+    },
+};
+
+/// Identifies a file created by the validator for this protocol.
+pub const MAGIC: [u8; 8] = *b"AGVSCHED";
+/// Bumped whenever the layout of [`Region`] changes.
+pub const VERSION: u32 = 1;
+/// Maximum number of consume workers described in a request.
+pub const MAX_WORKERS: usize = 64;
+/// Maximum number of transactions offered to the scheduler at once.
+pub const MAX_TRANSACTIONS_PER_REQUEST: usize = 2048;
+/// Maximum number of account keys per transaction, matching the runtime's
+/// maximum transaction account lock limit.
+pub const MAX_ACCOUNTS_PER_TRANSACTION: usize = 128;
+
+#[repr(C)]
+pub struct Header {
+    /// [`MAGIC`] as a little endian integer.
+    pub magic: AtomicU64,
+    pub version: AtomicU32,
+    /// Number of consume workers transactions may be assigned to.
+    pub num_workers: AtomicU32,
+    /// Incremented by the validator once a new [`Request`] is written.
+    pub request_sequence: AtomicU64,
+    /// Set to the handled `request_sequence` by the external scheduler once
+    /// the matching [`Response`] is written.
+    pub response_sequence: AtomicU64,
+}
+
+#[repr(C)]
+pub struct TransactionEntry {
+    /// Priority the validator uses to order its own queue.
+    pub priority: u64,
+    /// Estimated cost in compute units.
+    pub cost: u64,
+    pub num_account_keys: u32,
+    _padding: u32,
+    /// Bitmask of writable account keys, indexed like `account_keys`.
+    pub writable: [u64; MAX_ACCOUNTS_PER_TRANSACTION / 64],
+    pub account_keys: [[u8; 32]; MAX_ACCOUNTS_PER_TRANSACTION],
+}
+
+impl TransactionEntry {
+    pub fn account_keys(&self) -> &[[u8; 32]] {
+        &self.account_keys[..(self.num_account_keys as usize).min(MAX_ACCOUNTS_PER_TRANSACTION)]
+    }
+
+    pub fn is_writable(&self, index: usize) -> bool {
+        index < MAX_ACCOUNTS_PER_TRANSACTION && self.writable[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Fill in the account keys and writable mask.
+    /// Returns `false` and leaves the entry without accounts if there are more
+    /// than [`MAX_ACCOUNTS_PER_TRANSACTION`] keys.
+    pub fn set_accounts(&mut self, accounts: impl IntoIterator<Item = ([u8; 32], bool)>) -> bool {
+        self.num_account_keys = 0;
+        self.writable = [0; MAX_ACCOUNTS_PER_TRANSACTION / 64];
+        for (index, (key, is_writable)) in accounts.into_iter().enumerate() {
+            if index >= MAX_ACCOUNTS_PER_TRANSACTION {
+                self.num_account_keys = 0;
+                return false;
+            }
+            self.account_keys[index] = key;
+            if is_writable {
+                self.writable[index / 64] |= 1 << (index % 64);
+            }
+            self.num_account_keys += 1;
+        }
+        true
+    }
+}
+
+#[repr(C)]
+pub struct Request {
+    pub num_transactions: AtomicU32,
+    _padding: u32,
+    /// Compute units already scheduled but not yet completed, per worker.
+    pub in_flight_cus: [u64; MAX_WORKERS],
+    /// Transactions already scheduled but not yet completed, per worker.
+    pub in_flight_transactions: [u64; MAX_WORKERS],
+    /// Offered transactions, in descending priority order.
+    pub transactions: [TransactionEntry; MAX_TRANSACTIONS_PER_REQUEST],
+}
+
+impl Request {
+    pub fn transactions(&self) -> &[TransactionEntry] {
+        let num_transactions = self.num_transactions.load(Ordering::Relaxed) as usize;
+        &self.transactions[..num_transactions.min(MAX_TRANSACTIONS_PER_REQUEST)]
+    }
+
+    pub fn set_num_transactions(&self, num_transactions: usize) {
+        self.num_transactions.store(
+            num_transactions.min(MAX_TRANSACTIONS_PER_REQUEST) as u32,
+            Ordering::Relaxed,
+        );
+    }
+}
+
+/// Schedule the transaction at `index` in [`Request::transactions`] on
+/// consume worker `worker`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Assignment {
+    pub index: u32,
+    pub worker: u32,
+}
+
+#[repr(C)]
+pub struct Response {
+    pub num_assignments: AtomicU32,
+    _padding: u32,
+    /// Assignments are applied in order. Offered transactions without an
+    /// assignment stay buffered in the validator.
+    pub assignments: [Assignment; MAX_TRANSACTIONS_PER_REQUEST],
+}
+
+impl Response {
+    pub fn assignments(&self) -> &[Assignment] {
+        let num_assignments = self.num_assignments.load(Ordering::Relaxed) as usize;
+        &self.assignments[..num_assignments.min(MAX_TRANSACTIONS_PER_REQUEST)]
+    }
+
+    /// Replace the assignments, truncating to [`MAX_TRANSACTIONS_PER_REQUEST`].
+    pub fn set_assignments(&mut self, assignments: &[Assignment]) {
+        let len = assignments.len().min(MAX_TRANSACTIONS_PER_REQUEST);
+        self.assignments[..len].copy_from_slice(&assignments[..len]);
+        self.num_assignments.store(len as u32, Ordering::Relaxed);
+    }
+}
+
+/// The full contents of the shared file.
+#[repr(C)]
+pub struct Region {
+    pub header: Header,
+    pub request: Request,
+    pub response: Response,
+}
+
+/// A [`Region`] mapped from a file, usable from either side of the protocol.
+pub struct SharedRegion {
+    mmap: MmapMut,
+    // Keep the file open for the lifetime of the mapping.
+    _file: File,
+}
+
+impl SharedRegion {
+    /// Create (or truncate) the file at `path` and initialize the header.
+    /// Called by the validator.
+    pub fn create(path: impl AsRef<Path>, num_workers: usize) -> io::Result<Self> {
+        if num_workers == 0 || num_workers > MAX_WORKERS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("number of workers must be between 1 and {MAX_WORKERS}"),
+            ));
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(size_of::<Region>() as u64)?;
+        // SAFETY: The file was just truncated and zero-extended to the size of
+        // `Region`, and the all-zero bit pattern is valid for every field.
+        let mmap = unsafe { MmapMut::map_mut(&file)? };
+        let region = Self { mmap, _file: file };
+        let header = region.header();
+        header.version.store(VERSION, Ordering::Relaxed);
+        header
+            .num_workers
+            .store(num_workers as u32, Ordering::Relaxed);
+        header
+            .magic
+            .store(u64::from_le_bytes(MAGIC), Ordering::Release);
+        Ok(region)
+    }
+
+    /// Map an existing file created by the validator, verifying its header.
+    /// Called by the external scheduler.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        if file.metadata()?.len() != size_of::<Region>() as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unexpected scheduler region size",
+            ));
+        }
+        // SAFETY: The size was checked above. The contents are only ever
+        // interpreted as plain integers and byte arrays.
+        let mmap = unsafe { MmapMut::map_mut(&file)? };
+        let region = Self { mmap, _file: file };
+        let header = region.header();
+        if header.magic.load(Ordering::Acquire).to_le_bytes() != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a scheduler region",
+            ));
+        }
+        let version = header.version.load(Ordering::Relaxed);
+        if version != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported scheduler region version {version}, expected {VERSION}"),
+            ));
+        }
+        Ok(region)
+    }
+
+    // The mapping is page aligned and exactly `size_of::<Region>()`. No
+    // reference to the whole `Region` is ever created, since the other side
+    // writes parts of it concurrently; only the part a side owns at a given
+    // point of the protocol is borrowed.
+    fn region_ptr(&self) -> *const Region {
+        self.mmap.as_ptr() as *const Region
+    }
+
+    fn region_mut_ptr(&mut self) -> *mut Region {
+        self.mmap.as_mut_ptr() as *mut Region
+    }
+
+    pub fn header(&self) -> &Header {
+        // SAFETY: All header fields are atomics.
+        unsafe { &*addr_of!((*self.region_ptr()).header) }
+    }
+
+    pub fn num_workers(&self) -> usize {
+        self.header().num_workers.load(Ordering::Relaxed) as usize
+    }
+
+    /// Scheduler side: the request for the sequence returned by
+    /// [`Self::poll_request`].
+    pub fn request(&self) -> &Request {
+        // SAFETY: The validator does not write the request until the
+        // scheduler has answered it.
+        unsafe { &*addr_of!((*self.region_ptr()).request) }
+    }
+
+    /// Validator side: the request to fill before [`Self::publish_request`].
+    pub fn request_mut(&mut self) -> &mut Request {
+        // SAFETY: The scheduler only reads the request after it is published.
+        unsafe { &mut *addr_of_mut!((*self.region_mut_ptr()).request) }
+    }
+
+    /// Scheduler side: the response to fill before [`Self::publish_response`].
+    pub fn response_mut(&mut self) -> &mut Response {
+        // SAFETY: The validator only reads the response through
+        // `read_assignments`, using volatile reads.
+        unsafe { &mut *addr_of_mut!((*self.region_mut_ptr()).response) }
+    }
+
+    /// Validator side: copy the assignments of the response into
+    /// `assignments`. A misbehaving scheduler may still be writing them, so
+    /// every value is read exactly once and must be validated by the caller.
+    pub fn read_assignments(&self, assignments: &mut Vec<Assignment>) {
+        assignments.clear();
+        // SAFETY: `num_assignments` is an atomic, the indices read are within
+        // the `assignments` array and any bit pattern is a valid `Assignment`.
+        unsafe {
+            let response = addr_of!((*self.region_ptr()).response);
+            let num_assignments =
+                (*addr_of!((*response).num_assignments)).load(Ordering::Relaxed) as usize;
+            let first = addr_of!((*response).assignments) as *const Assignment;
+            assignments.extend(
+                (0..num_assignments.min(MAX_TRANSACTIONS_PER_REQUEST))
+                    .map(|index| ptr::read_volatile(first.add(index))),
+            );
+        }
+    }
+
+    /// Validator side: publish the request written via [`Self::request_mut`].
+    /// Returns the sequence number the response must carry.
+    pub fn publish_request(&self) -> u64 {
+        self.header()
+            .request_sequence
+            .fetch_add(1, Ordering::Release)
+            + 1
+    }
+
+    /// Validator side: whether the response for `sequence` has been written.
+    pub fn is_response_ready(&self, sequence: u64) -> bool {
+        self.header().response_sequence.load(Ordering::Acquire) == sequence
+    }
+
+    /// Scheduler side: return the sequence of a request that was published
+    /// after `last_sequence`, if any.
+    pub fn poll_request(&self, last_sequence: u64) -> Option<u64> {
+        let sequence = self.header().request_sequence.load(Ordering::Acquire);
+        (sequence != last_sequence).then_some(sequence)
+    }
+
+    /// Scheduler side: publish the response written via
+    /// [`Self::response_mut`] for the request with `sequence`.
+    pub fn publish_response(&self, sequence: u64) {
+        self.header()
+            .response_sequence
+            .store(sequence, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    #[test]
+    fn test_create_and_open() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("scheduler");
+        assert!(SharedRegion::create(&path, 0).is_err());
+        assert!(SharedRegion::create(&path, MAX_WORKERS + 1).is_err());
+
+        let validator = SharedRegion::create(&path, 4).unwrap();
+        let scheduler = SharedRegion::open(&path).unwrap();
+        assert_eq!(validator.num_workers(), 4);
+        assert_eq!(scheduler.num_workers(), 4);
+
+        std::fs::write(dir.path().join("other"), [0; 16]).unwrap();
+        assert_eq!(
+            SharedRegion::open(dir.path().join("other"))
+                .err()
+                .unwrap()
+                .kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_request_response_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("scheduler");
+        let mut validator = SharedRegion::create(&path, 2).unwrap();
+        let mut scheduler = SharedRegion::open(&path).unwrap();
+        assert_eq!(scheduler.poll_request(0), None);
+
+        let keys = [[1; 32], [2; 32]];
+        let request = validator.request_mut();
+        request.set_num_transactions(1);
+        assert!(request.transactions[0].set_accounts(keys.into_iter().zip([true, false])));
+        let sequence = validator.publish_request();
+        assert!(!validator.is_response_ready(sequence));
+
+        assert_eq!(scheduler.poll_request(0), Some(sequence));
+        let request = scheduler.request();
+        let entry = &request.transactions()[0];
+        assert_eq!(entry.account_keys(), &keys);
+        assert!(entry.is_writable(0));
+        assert!(!entry.is_writable(1));
+
+        scheduler.response_mut().set_assignments(&[Assignment {
+            index: 0,
+            worker: 1,
+        }]);
+        scheduler.publish_response(sequence);
+        assert_eq!(scheduler.poll_request(sequence), None);
+
+        assert!(validator.is_response_ready(sequence));
+        let mut assignments = Vec::new();
+        validator.read_assignments(&mut assignments);
+        assert_eq!(
+            assignments,
+            [Assignment {
+                index: 0,
+                worker: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn test_set_accounts_too_many_keys() {
+        let dir = TempDir::new().unwrap();
+        let mut region = SharedRegion::create(dir.path().join("scheduler"), 1).unwrap();
+        let keys = vec![[0; 32]; MAX_ACCOUNTS_PER_TRANSACTION + 1];
+        let entry = &mut region.request_mut().transactions[0];
+        assert!(!entry.set_accounts(keys.into_iter().map(|key| (key, false))));
+        assert_eq!(entry.num_account_keys, 0);
+    }
+}
@@ -8,4 +8,5 @@ declare dcou_tainted_packages=(
   agave-store-tool
   agave-store-histogram
   solana-dos
+  solana-local-cluster
 )
@@ -0,0 +1,790 @@
+//! A [`LongTermLedgerStorage`] backed by a local filesystem directory, for operators that keep
+//! archival ledger history on their own disks or on a mounted object store.
+//!
+//! Each BigTable table becomes a directory of the same name, and each row a file named by its
+//! row key and holding the same compressed cell data that would have been written to BigTable:
+//!
+//! ```text
+//! <path>/blocks/<slot shard>/<slot subshard>/<slot key>
+//! <path>/entries/<slot shard>/<slot subshard>/<slot key>
+//! <path>/tx/<signature shard>/<signature>
+//! <path>/tx-by-addr/<address>/<inverted slot shard>/<inverted slot subshard>/<inverted slot key>
+//! ```
+//!
+//! Slot keys are sharded by their high hex digits, and transactions by the first characters of
+//! the signature, to keep directory sizes bounded. Range queries only list the shards covering
+//! the slots they return.
+//!
+//! The file system is accessed on tokio's blocking thread pool.
+use {
+    crate::{
+        collect_signatures_for_address,
+        compression::{compress_best, decompress},
+        confirmed_block_to_cells, key_to_slot, slot_to_blocks_key, slot_to_entries_key,
+        slot_to_tx_by_addr_key, ConfirmedBlockCells, Error, LongTermLedgerStorage, Result,
+        TransactionInfo,
+    },
+    async_trait::async_trait,
+    log::*,
+    solana_clock::Slot,
+    solana_pubkey::Pubkey,
+    solana_signature::Signature,
+    solana_storage_proto::convert::{entries, generated, tx_by_addr},
+    solana_transaction_status::{
+        ConfirmedBlock, ConfirmedTransactionStatusWithSignature,
+        ConfirmedTransactionWithStatusMeta, EntrySummary, TransactionByAddrInfo, TransactionStatus,
+        VersionedConfirmedBlockWithEntries,
+    },
+    std::{
+        collections::HashSet,
+        fs,
+        io::{self, ErrorKind},
+        iter,
+        path::{Path, PathBuf},
+    },
+};
+
+// Number of low-order hex digits of a slot key dropped to form its shard directory, so each
+// shard covers 2^24 slots
+const SLOT_SHARD_KEY_DIGITS: usize = 6;
+// Number of low-order hex digits of a slot key dropped to form its subshard directory within its
+// shard, so each subshard holds at most 4096 slots
+const SLOT_SUBSHARD_KEY_DIGITS: usize = 3;
+// Number of leading signature characters used as its shard directory
+const SIGNATURE_SHARD_LEN: usize = 2;
+// Suffix of files that are still being written
+const TEMP_FILE_SUFFIX: &str = ".tmp";
+
+const BLOCKS_DIR: &str = "blocks";
+const ENTRIES_DIR: &str = "entries";
+const TX_DIR: &str = "tx";
+const TX_BY_ADDR_DIR: &str = "tx-by-addr";
+
+#[derive(Debug, Clone)]
+pub struct FileLedgerStorageConfig {
+    pub path: PathBuf,
+    pub read_only: bool,
+}
+
+#[derive(Clone)]
+pub struct FileLedgerStorage {
+    path: PathBuf,
+    read_only: bool,
+}
+
+impl FileLedgerStorage {
+    pub fn new(path: impl Into<PathBuf>, read_only: bool) -> Result<Self> {
+        Self::new_with_config(FileLedgerStorageConfig {
+            path: path.into(),
+            read_only,
+        })
+    }
+
+    pub fn new_with_config(config: FileLedgerStorageConfig) -> Result<Self> {
+        let FileLedgerStorageConfig { path, read_only } = config;
+        if read_only {
+            if !path.is_dir() {
+                return Err(io::Error::new(
+                    ErrorKind::NotFound,
+                    format!("{} is not a directory", path.display()),
+                )
+                .into());
+            }
+        } else {
+            for table in [BLOCKS_DIR, ENTRIES_DIR, TX_DIR, TX_BY_ADDR_DIR] {
+                fs::create_dir_all(path.join(table))?;
+            }
+        }
+        Ok(Self { path, read_only })
+    }
+
+    // Run `f`, which accesses the file system, on the blocking thread pool
+    async fn run_blocking<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(Self) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let storage = self.clone();
+        tokio::task::spawn_blocking(move || f(storage))
+            .await
+            .map_err(Error::TokioJoinError)?
+    }
+
+    fn block_path(&self, slot: Slot) -> PathBuf {
+        slot_key_path(&self.path.join(BLOCKS_DIR), &slot_to_blocks_key(slot))
+    }
+
+    fn entries_path(&self, slot: Slot) -> PathBuf {
+        slot_key_path(&self.path.join(ENTRIES_DIR), &slot_to_entries_key(slot))
+    }
+
+    fn tx_path(&self, signature: &str) -> PathBuf {
+        self.path
+            .join(TX_DIR)
+            .join(&signature[..SIGNATURE_SHARD_LEN])
+            .join(signature)
+    }
+
+    fn tx_by_addr_dir(&self, address: &str) -> PathBuf {
+        self.path.join(TX_BY_ADDR_DIR).join(address)
+    }
+
+    fn tx_by_addr_path(&self, row_key: &str) -> PathBuf {
+        // `tx-by-addr` row keys are of the form `<address>/<inverted slot key>`
+        let (address, key) = row_key.split_once('/').unwrap();
+        slot_key_path(&self.tx_by_addr_dir(address), key)
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        if self.read_only {
+            Err(Error::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn get_transaction_info(&self, signature: &Signature) -> Result<TransactionInfo> {
+        read_bincode_file(&self.tx_path(&signature.to_string()))?.ok_or(Error::SignatureNotFound)
+    }
+
+    fn get_tx_by_addr(&self, path: &Path) -> Result<Vec<TransactionByAddrInfo>> {
+        let tx_by_addr = read_protobuf_file::<tx_by_addr::TransactionByAddr>(path)?
+            .ok_or_else(|| Error::ObjectCorrupt(path.display().to_string()))?;
+        tx_by_addr.try_into().map_err(|err| {
+            Error::ObjectCorrupt(format!("Failed to deserialize {}: {err}", path.display()))
+        })
+    }
+
+    fn read_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>> {
+        slot_keys_from(self.path.join(BLOCKS_DIR), slot_to_blocks_key(start_slot))
+            .map(|key| Ok(key_to_slot(&key?)))
+            .filter_map(Result::transpose)
+            .take(limit)
+            .collect()
+    }
+
+    fn read_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlock> {
+        let path = self.block_path(slot);
+        read_protobuf_file::<generated::ConfirmedBlock>(&path)?
+            .ok_or(Error::BlockNotFound(slot))?
+            .try_into()
+            .map_err(|_err| Error::ObjectCorrupt(path.display().to_string()))
+    }
+
+    fn read_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionWithStatusMeta>> {
+        let TransactionInfo { slot, index, .. } = self.get_transaction_info(signature)?;
+
+        let block = self.read_confirmed_block(slot)?;
+        match block.transactions.into_iter().nth(index as usize) {
+            Some(tx_with_meta) if tx_with_meta.transaction_signature() == signature => {
+                Ok(Some(ConfirmedTransactionWithStatusMeta {
+                    slot,
+                    tx_with_meta,
+                    block_time: block.block_time,
+                }))
+            }
+            _ => {
+                warn!("Transaction info or confirmed block for {signature} is corrupt");
+                Ok(None)
+            }
+        }
+    }
+
+    fn read_confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> Result<Vec<(ConfirmedTransactionStatusWithSignature, u32)>> {
+        let (first_slot, before_transaction_index) = match before_signature {
+            None => (Slot::MAX, 0),
+            Some(before_signature) => {
+                let TransactionInfo { slot, index, .. } =
+                    self.get_transaction_info(before_signature)?;
+                (slot, index)
+            }
+        };
+        let (last_slot, until_transaction_index) = match until_signature {
+            None => (0, u32::MAX),
+            Some(until_signature) => {
+                let TransactionInfo { slot, index, .. } =
+                    self.get_transaction_info(until_signature)?;
+                (slot, index)
+            }
+        };
+
+        // Inverted slot keys sort in descending slot order. Rows are read lazily, so only the
+        // shards up to the row completing `limit` are listed
+        let first_key = slot_to_tx_by_addr_key(first_slot);
+        let last_key = slot_to_tx_by_addr_key(last_slot);
+        let address_dir = self.tx_by_addr_dir(&address.to_string());
+        let rows = slot_keys_from(address_dir.clone(), first_key)
+            .take_while(|key| !matches!(key, Ok(key) if *key > last_key))
+            .map(|key| {
+                let key = key?;
+                let slot = !key_to_slot(&key)
+                    .ok_or_else(|| Error::ObjectCorrupt(format!("{TX_BY_ADDR_DIR}/{key}")))?;
+                Ok((
+                    slot,
+                    self.get_tx_by_addr(&slot_key_path(&address_dir, &key))?,
+                ))
+            });
+
+        collect_signatures_for_address(
+            rows,
+            (first_slot, before_transaction_index),
+            (last_slot, until_transaction_index),
+            limit,
+        )
+    }
+
+    fn write_confirmed_block_with_entries(
+        &self,
+        slot: Slot,
+        confirmed_block: VersionedConfirmedBlockWithEntries,
+    ) -> Result<()> {
+        let ConfirmedBlockCells {
+            tx_cells,
+            tx_by_addr_cells,
+            entry_cell,
+            block_cell: (_, block),
+            num_transactions,
+        } = confirmed_block_to_cells(slot, confirmed_block);
+
+        let mut bytes_written = 0;
+        for (signature, transaction_info) in &tx_cells {
+            bytes_written += write_file(
+                &self.tx_path(signature),
+                &compress_best(&bincode::serialize(transaction_info).unwrap())?,
+            )?;
+        }
+        for (row_key, tx_by_addr) in &tx_by_addr_cells {
+            bytes_written += write_protobuf_file(&self.tx_by_addr_path(row_key), tx_by_addr)?;
+        }
+        let num_entries = match entry_cell {
+            Some((_, entries)) => {
+                bytes_written += write_protobuf_file(&self.entries_path(slot), &entries)?;
+                entries.entries.len()
+            }
+            None => 0,
+        };
+
+        // Store the block itself last, so that partially uploaded blocks never become visible to
+        // `get_confirmed_block()` and `get_confirmed_blocks()`
+        bytes_written += write_protobuf_file(&self.block_path(slot), &block)?;
+        datapoint_info!(
+            "storage-file-upload-block",
+            ("slot", slot, i64),
+            ("transactions", num_transactions, i64),
+            ("entries", num_entries, i64),
+            ("bytes", bytes_written, i64),
+        );
+        Ok(())
+    }
+
+    fn remove_confirmed_block(&self, slot: Slot, dry_run: bool) -> Result<()> {
+        let confirmed_block = self.read_confirmed_block(slot)?;
+
+        let mut addresses: HashSet<Pubkey> = HashSet::new();
+        let mut tx_paths = vec![];
+        for transaction_with_meta in confirmed_block.transactions.iter() {
+            // Attempt to delete rows for all addresses, reserved or not, since it's hard to be sure
+            // which of them were previously uploaded
+            addresses.extend(transaction_with_meta.account_keys().iter().copied());
+
+            let signature = transaction_with_meta.transaction_signature();
+            match self.get_transaction_info(signature) {
+                Ok(transaction_info) if transaction_info.slot == slot => {
+                    tx_paths.push(self.tx_path(&signature.to_string()));
+                }
+                Ok(transaction_info) => {
+                    warn!(
+                        "skipped tx file for {signature} because it belongs to slot {}",
+                        transaction_info.slot
+                    );
+                }
+                Err(err) => warn!("skipped tx file for {signature}: {err:?}"),
+            }
+        }
+
+        let tx_by_addr_paths: Vec<_> = addresses
+            .into_iter()
+            .map(|address| {
+                self.tx_by_addr_path(&format!("{address}/{}", slot_to_tx_by_addr_key(slot)))
+            })
+            .filter(|path| path.is_file())
+            .collect();
+        let entries_path = self.entries_path(slot);
+        let entries_exist = entries_path.is_file();
+
+        if !dry_run {
+            for path in tx_by_addr_paths.iter().chain(tx_paths.iter()) {
+                remove_file(path)?;
+            }
+            if entries_exist {
+                remove_file(&entries_path)?;
+            }
+            remove_file(&self.block_path(slot))?;
+        }
+
+        info!(
+            "{}deleted ledger data for slot {}: {} transaction files, {} address slot files, {} \
+             entry file",
+            if dry_run { "[dry run] " } else { "" },
+            slot,
+            tx_paths.len(),
+            tx_by_addr_paths.len(),
+            if entries_exist { "with" } else { "WITHOUT" }
+        );
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LongTermLedgerStorage for FileLedgerStorage {
+    async fn get_first_available_block(&self) -> Result<Option<Slot>> {
+        trace!("FileLedgerStorage::get_first_available_block request received");
+        Ok(self.get_confirmed_blocks(0, 1).await?.first().copied())
+    }
+
+    async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>> {
+        trace!(
+            "FileLedgerStorage::get_confirmed_blocks request received: {start_slot:?} {limit:?}"
+        );
+        self.run_blocking(move |storage| storage.read_confirmed_blocks(start_slot, limit))
+            .await
+    }
+
+    async fn get_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlock> {
+        trace!("FileLedgerStorage::get_confirmed_block request received: {slot:?}");
+        self.run_blocking(move |storage| storage.read_confirmed_block(slot))
+            .await
+    }
+
+    async fn confirmed_block_exists(&self, slot: Slot) -> Result<bool> {
+        trace!("FileLedgerStorage::confirmed_block_exists request received: {slot:?}");
+        self.run_blocking(move |storage| Ok(storage.block_path(slot).is_file()))
+            .await
+    }
+
+    async fn get_entries(&self, slot: Slot) -> Result<Vec<EntrySummary>> {
+        trace!("FileLedgerStorage::get_entries request received: {slot:?}");
+        self.run_blocking(move |storage| {
+            let entries = read_protobuf_file::<entries::Entries>(&storage.entries_path(slot))?
+                .ok_or(Error::BlockNotFound(slot))?;
+            Ok(entries.entries.into_iter().map(Into::into).collect())
+        })
+        .await
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus> {
+        trace!("FileLedgerStorage::get_signature_status request received: {signature:?}");
+        let signature = *signature;
+        self.run_blocking(move |storage| Ok(storage.get_transaction_info(&signature)?.into()))
+            .await
+    }
+
+    async fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionWithStatusMeta>> {
+        trace!("FileLedgerStorage::get_confirmed_transaction request received: {signature:?}");
+        let signature = *signature;
+        self.run_blocking(move |storage| storage.read_confirmed_transaction(&signature))
+            .await
+    }
+
+    async fn get_confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> Result<Vec<(ConfirmedTransactionStatusWithSignature, u32)>> {
+        trace!(
+            "FileLedgerStorage::get_confirmed_signatures_for_address request received: \
+             {address:?}"
+        );
+        let address = *address;
+        let before_signature = before_signature.copied();
+        let until_signature = until_signature.copied();
+        self.run_blocking(move |storage| {
+            storage.read_confirmed_signatures_for_address(
+                &address,
+                before_signature.as_ref(),
+                until_signature.as_ref(),
+                limit,
+            )
+        })
+        .await
+    }
+
+    async fn upload_confirmed_block_with_entries(
+        &self,
+        slot: Slot,
+        confirmed_block: VersionedConfirmedBlockWithEntries,
+    ) -> Result<()> {
+        trace!("FileLedgerStorage::upload_confirmed_block_with_entries request received: {slot:?}");
+        self.check_writable()?;
+        self.run_blocking(move |storage| {
+            storage.write_confirmed_block_with_entries(slot, confirmed_block)
+        })
+        .await
+    }
+
+    async fn delete_confirmed_block(&self, slot: Slot, dry_run: bool) -> Result<()> {
+        if !dry_run {
+            self.check_writable()?;
+        }
+        self.run_blocking(move |storage| storage.remove_confirmed_block(slot, dry_run))
+            .await
+    }
+}
+
+// The path of the file of slot key `key` in the table or address directory `dir`
+fn slot_key_path(dir: &Path, key: &str) -> PathBuf {
+    let (shard, subshard) = slot_key_shards(key);
+    dir.join(shard).join(subshard).join(key)
+}
+
+// The shard and subshard directory names of slot key `key`
+fn slot_key_shards(key: &str) -> (&str, &str) {
+    let subshard_start = key.len() - SLOT_SHARD_KEY_DIGITS;
+    let subshard_end = key.len() - SLOT_SUBSHARD_KEY_DIGITS;
+    (&key[..subshard_start], &key[subshard_start..subshard_end])
+}
+
+// Iterator over slot keys, or the errors listing them
+type SlotKeys = Box<dyn Iterator<Item = Result<String>>>;
+
+// The committed slot keys in the table or address directory `dir` from `start_key` on, in lexical
+// order. Each shard and subshard directory is only listed once the iteration reaches it
+fn slot_keys_from(dir: PathBuf, start_key: String) -> SlotKeys {
+    let (start_shard, start_subshard) = slot_key_shards(&start_key);
+    let (start_shard, start_subshard) = (start_shard.to_string(), start_subshard.to_string());
+    Box::new(
+        sorted_dir_entries_from(&dir, &start_shard)
+            .into_iter()
+            .flat_map(move |shard| -> SlotKeys {
+                let shard = match shard {
+                    Ok(shard) => shard,
+                    Err(err) => return Box::new(iter::once(Err(err))),
+                };
+                // The subshards and keys of the following shards are all past `start_key`
+                let (start_subshard, start_key) = if shard == start_shard {
+                    (start_subshard.clone(), start_key.clone())
+                } else {
+                    (String::new(), String::new())
+                };
+                let shard_dir = dir.join(shard);
+                Box::new(
+                    sorted_dir_entries_from(&shard_dir, &start_subshard)
+                        .into_iter()
+                        .flat_map(move |subshard| {
+                            let subshard = match subshard {
+                                Ok(subshard) => subshard,
+                                Err(err) => return vec![Err(err)],
+                            };
+                            let start_key = if subshard == start_subshard {
+                                start_key.as_str()
+                            } else {
+                                ""
+                            };
+                            sorted_dir_entries_from(&shard_dir.join(subshard), start_key)
+                        }),
+                )
+            }),
+    )
+}
+
+// The names of the committed files in `dir` from `start` on, in lexical order, or the error
+// listing them
+fn sorted_dir_entries_from(dir: &Path, start: &str) -> Vec<Result<String>> {
+    match sorted_dir_entries(dir) {
+        Ok(names) => names
+            .into_iter()
+            .filter(|name| name.as_str() >= start)
+            .map(Ok)
+            .collect(),
+        Err(err) => vec![Err(err)],
+    }
+}
+
+// Return the names of the committed files in `dir`, in lexical order. A missing directory is
+// treated as empty
+fn sorted_dir_entries(dir: &Path) -> Result<Vec<String>> {
+    let read_dir = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err.into()),
+    };
+    let mut names = vec![];
+    for entry in read_dir {
+        if let Ok(name) = entry?.file_name().into_string() {
+            if !name.ends_with(TEMP_FILE_SUFFIX) {
+                names.push(name);
+            }
+        }
+    }
+    names.sort_unstable();
+    Ok(names)
+}
+
+fn read_file(path: &Path) -> Result<Option<Vec<u8>>> {
+    match fs::read(path) {
+        Ok(data) => Ok(Some(decompress(&data)?)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn read_bincode_file<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    read_file(path)?
+        .map(|data| {
+            bincode::deserialize(&data).map_err(|err| {
+                warn!("Failed to deserialize {}: {err}", path.display());
+                Error::ObjectCorrupt(path.display().to_string())
+            })
+        })
+        .transpose()
+}
+
+fn read_protobuf_file<P: prost::Message + Default>(path: &Path) -> Result<Option<P>> {
+    read_file(path)?
+        .map(|data| {
+            P::decode(&data[..]).map_err(|err| {
+                warn!("Failed to deserialize {}: {err}", path.display());
+                Error::ObjectCorrupt(path.display().to_string())
+            })
+        })
+        .transpose()
+}
+
+// Write `data` to a temporary file and rename it into place, so readers never observe a
+// partially written file. Returns the number of bytes written
+fn write_file(path: &Path, data: &[u8]) -> Result<usize> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(TEMP_FILE_SUFFIX);
+    fs::write(&temp_path, data)?;
+    fs::rename(&temp_path, path)?;
+    Ok(data.len())
+}
+
+fn write_protobuf_file<P: prost::Message>(path: &Path, data: &P) -> Result<usize> {
+    write_file(path, &compress_best(&data.encode_to_vec())?)
+}
+
+fn remove_file(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_hash::Hash,
+        solana_keypair::Keypair,
+        solana_transaction::{versioned::VersionedTransaction, Transaction},
+        solana_transaction_status::{
+            TransactionStatusMeta, VersionedConfirmedBlock, VersionedTransactionWithStatusMeta,
+        },
+    };
+
+    fn new_block(slot: Slot, num_transactions: usize) -> VersionedConfirmedBlockWithEntries {
+        new_block_with_recipient(slot, num_transactions, &Pubkey::new_unique())
+    }
+
+    fn new_block_with_recipient(
+        slot: Slot,
+        num_transactions: usize,
+        recipient: &Pubkey,
+    ) -> VersionedConfirmedBlockWithEntries {
+        let transactions = (0..num_transactions)
+            .map(|_| {
+                let from = Keypair::new();
+                let transaction: Transaction =
+                    solana_system_transaction::transfer(&from, recipient, 1, Hash::default());
+                VersionedTransactionWithStatusMeta {
+                    transaction: VersionedTransaction::from(transaction),
+                    meta: TransactionStatusMeta::default(),
+                }
+            })
+            .collect();
+        VersionedConfirmedBlockWithEntries {
+            block: VersionedConfirmedBlock {
+                previous_blockhash: Hash::default().to_string(),
+                blockhash: Hash::new_unique().to_string(),
+                parent_slot: slot.saturating_sub(1),
+                transactions,
+                rewards: vec![],
+                num_partitions: None,
+                block_time: Some(slot as i64),
+                block_height: Some(slot),
+            },
+            entries: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upload_and_get_confirmed_block() {
+        let ledger_dir = tempfile::tempdir().unwrap();
+        let storage = FileLedgerStorage::new(ledger_dir.path(), false).unwrap();
+        assert_eq!(storage.get_first_available_block().await.unwrap(), None);
+
+        // Straddle a shard boundary
+        let slots = [(1 << 24) - 1, 1 << 24, (1 << 24) + 2];
+        for slot in slots {
+            storage
+                .upload_confirmed_block_with_entries(slot, new_block(slot, 2))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            storage.get_first_available_block().await.unwrap(),
+            Some(slots[0])
+        );
+        assert_eq!(
+            storage.get_confirmed_blocks(slots[1], 10).await.unwrap(),
+            slots[1..]
+        );
+        assert_eq!(
+            storage.get_confirmed_blocks(0, 2).await.unwrap(),
+            slots[..2]
+        );
+        assert!(storage.confirmed_block_exists(slots[2]).await.unwrap());
+        assert!(!storage.confirmed_block_exists(slots[2] + 1).await.unwrap());
+        assert!(matches!(
+            storage.get_confirmed_block(slots[2] + 1).await,
+            Err(Error::BlockNotFound(_))
+        ));
+
+        let block = storage.get_confirmed_block(slots[1]).await.unwrap();
+        assert_eq!(block.transactions.len(), 2);
+        let signature = *block.transactions[1].transaction_signature();
+        let transaction = storage
+            .get_confirmed_transaction(&signature)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(transaction.slot, slots[1]);
+        assert_eq!(
+            storage.get_signature_status(&signature).await.unwrap().slot,
+            slots[1]
+        );
+
+        // Every transaction in `new_block` pays from a fresh fee payer
+        let fee_payer = block.transactions[1].account_keys()[0];
+        let signatures = storage
+            .get_confirmed_signatures_for_address(&fee_payer, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(signatures.len(), 1);
+        assert_eq!(signatures[0].0.signature, signature);
+        assert_eq!(signatures[0].1, 1);
+
+        storage
+            .delete_confirmed_block(slots[1], false)
+            .await
+            .unwrap();
+        assert!(!storage.confirmed_block_exists(slots[1]).await.unwrap());
+        assert!(matches!(
+            storage.get_signature_status(&signature).await,
+            Err(Error::SignatureNotFound)
+        ));
+        assert!(storage
+            .get_confirmed_signatures_for_address(&fee_payer, None, None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_confirmed_signatures_for_address_across_shards() {
+        let ledger_dir = tempfile::tempdir().unwrap();
+        let storage = FileLedgerStorage::new(ledger_dir.path(), false).unwrap();
+        let recipient = Pubkey::new_unique();
+
+        // Straddle subshard and shard boundaries
+        let slots = [
+            (1 << 12) - 1,
+            1 << 12,
+            (1 << 24) - 1,
+            1 << 24,
+            (1 << 24) + (1 << 12),
+        ];
+        for slot in slots {
+            storage
+                .upload_confirmed_block_with_entries(
+                    slot,
+                    new_block_with_recipient(slot, 2, &recipient),
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            storage.get_confirmed_blocks(1 << 12, 3).await.unwrap(),
+            slots[1..4]
+        );
+
+        let signatures = storage
+            .get_confirmed_signatures_for_address(&recipient, None, None, 100)
+            .await
+            .unwrap();
+        let signature_slots: Vec<_> = signatures.iter().map(|(info, _)| info.slot).collect();
+        let expected_slots: Vec<_> = slots.iter().rev().flat_map(|slot| [*slot; 2]).collect();
+        assert_eq!(signature_slots, expected_slots);
+        assert_eq!(signatures[0].1, 1);
+        assert_eq!(signatures[1].1, 0);
+
+        // `before` and `until` are exclusive, and `limit` stops at the first rows
+        let signatures_from = |before: usize, until: usize, limit: usize| {
+            let storage = storage.clone();
+            let before = signatures[before].0.signature;
+            let until = signatures[until].0.signature;
+            async move {
+                storage
+                    .get_confirmed_signatures_for_address(
+                        &recipient,
+                        Some(&before),
+                        Some(&until),
+                        limit,
+                    )
+                    .await
+                    .unwrap()
+            }
+        };
+        assert_eq!(signatures_from(1, 8, 100).await, signatures[2..8]);
+        assert_eq!(signatures_from(1, 8, 3).await, signatures[2..5]);
+        assert_eq!(
+            storage
+                .get_confirmed_signatures_for_address(&recipient, None, None, 3)
+                .await
+                .unwrap(),
+            signatures[..3]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_only() {
+        let ledger_dir = tempfile::tempdir().unwrap();
+        assert!(FileLedgerStorage::new(ledger_dir.path().join("missing"), true).is_err());
+
+        let storage = FileLedgerStorage::new(ledger_dir.path(), true).unwrap();
+        assert!(matches!(
+            storage
+                .upload_confirmed_block_with_entries(1, new_block(1, 1))
+                .await,
+            Err(Error::ReadOnly)
+        ));
+    }
+}
@@ -3,6 +3,7 @@
 use {
     crate::bigtable::RowKey,
     agave_reserved_account_keys::ReservedAccountKeys,
+    async_trait::async_trait,
     log::*,
     serde::{Deserialize, Serialize},
     solana_clock::{Slot, UnixTimestamp},
@@ -44,8 +45,11 @@ extern crate serde_derive;
 mod access_token;
 mod bigtable;
 mod compression;
+mod file_storage;
 mod root_ca_certificate;
 
+pub use file_storage::{FileLedgerStorage, FileLedgerStorageConfig};
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("BigTable: {0}")]
@@ -63,6 +67,12 @@ pub enum Error {
     #[error("Signature not found")]
     SignatureNotFound,
 
+    #[error("Object is corrupt: {0}")]
+    ObjectCorrupt(String),
+
+    #[error("Storage is read-only")]
+    ReadOnly,
+
     #[error("tokio error")]
     TokioJoinError(JoinError),
 }
@@ -111,6 +121,48 @@ fn key_to_slot(key: &str) -> Option<Slot> {
     }
 }
 
+// Flatten `tx-by-addr` rows, visited in descending slot order, into at most `limit` signatures.
+// Records in `first_slot` at or after `before_transaction_index`, and records in `last_slot` at or
+// before `until_transaction_index`, are filtered out
+fn collect_signatures_for_address(
+    rows: impl IntoIterator<Item = Result<(Slot, Vec<TransactionByAddrInfo>)>>,
+    (first_slot, before_transaction_index): (Slot, u32),
+    (last_slot, until_transaction_index): (Slot, u32),
+    limit: usize,
+) -> Result<Vec<(ConfirmedTransactionStatusWithSignature, u32)>> {
+    let mut infos = vec![];
+
+    'outer: for row in rows {
+        let (slot, mut cell_data) = row?;
+        cell_data.reverse();
+        for tx_by_addr_info in cell_data.into_iter() {
+            // Filter out records before `before_transaction_index`
+            if slot == first_slot && tx_by_addr_info.index >= before_transaction_index {
+                continue;
+            }
+            // Filter out records after `until_transaction_index`
+            if slot == last_slot && tx_by_addr_info.index <= until_transaction_index {
+                continue;
+            }
+            infos.push((
+                ConfirmedTransactionStatusWithSignature {
+                    signature: tx_by_addr_info.signature,
+                    slot,
+                    err: tx_by_addr_info.err,
+                    memo: tx_by_addr_info.memo,
+                    block_time: tx_by_addr_info.block_time,
+                },
+                tx_by_addr_info.index,
+            ));
+            // Respect limit
+            if infos.len() >= limit {
+                break 'outer;
+            }
+        }
+    }
+    Ok(infos)
+}
+
 // A serialized `StoredConfirmedBlock` is stored in the `block` table
 //
 // StoredConfirmedBlock holds the same contents as ConfirmedBlock, but is slightly compressed and avoids
@@ -381,6 +433,98 @@ impl From<LegacyTransactionByAddrInfo> for TransactionByAddrInfo {
     }
 }
 
+// The rows written to each table when a confirmed block is uploaded to long-term storage
+struct ConfirmedBlockCells {
+    tx_cells: Vec<(RowKey, TransactionInfo)>,
+    tx_by_addr_cells: Vec<(RowKey, tx_by_addr::TransactionByAddr)>,
+    entry_cell: Option<(RowKey, entries::Entries)>,
+    block_cell: (RowKey, generated::ConfirmedBlock),
+    num_transactions: usize,
+}
+
+fn confirmed_block_to_cells(
+    slot: Slot,
+    confirmed_block: VersionedConfirmedBlockWithEntries,
+) -> ConfirmedBlockCells {
+    let mut by_addr: HashMap<&Pubkey, Vec<TransactionByAddrInfo>> = HashMap::new();
+    let VersionedConfirmedBlockWithEntries {
+        block: confirmed_block,
+        entries,
+    } = confirmed_block;
+
+    let reserved_account_keys = ReservedAccountKeys::new_all_activated();
+    let mut tx_cells = Vec::with_capacity(confirmed_block.transactions.len());
+    for (index, transaction_with_meta) in confirmed_block.transactions.iter().enumerate() {
+        let VersionedTransactionWithStatusMeta { meta, transaction } = transaction_with_meta;
+        let err = meta.status.clone().err();
+        let index = index as u32;
+        let signature = transaction.signatures[0];
+        let memo = extract_and_fmt_memos(transaction_with_meta);
+
+        for address in transaction_with_meta.account_keys().iter() {
+            // Historical note that previously only a set of sysvar ids were
+            // skipped from being uploaded. Now we skip uploaded for the set
+            // of all reserved account keys which will continue to grow in
+            // the future.
+            if !reserved_account_keys.is_reserved(address) {
+                by_addr
+                    .entry(address)
+                    .or_default()
+                    .push(TransactionByAddrInfo {
+                        signature,
+                        err: err.clone(),
+                        index,
+                        memo: memo.clone(),
+                        block_time: confirmed_block.block_time,
+                    });
+            }
+        }
+
+        tx_cells.push((
+            signature.to_string(),
+            TransactionInfo {
+                slot,
+                index,
+                err,
+                memo,
+            },
+        ));
+    }
+
+    let tx_by_addr_cells: Vec<_> = by_addr
+        .into_iter()
+        .map(|(address, transaction_info_by_addr)| {
+            (
+                format!("{}/{}", address, slot_to_tx_by_addr_key(slot)),
+                tx_by_addr::TransactionByAddr {
+                    tx_by_addrs: transaction_info_by_addr
+                        .into_iter()
+                        .map(|by_addr| by_addr.into())
+                        .collect(),
+                },
+            )
+        })
+        .collect();
+
+    let entry_cell = (!entries.is_empty()).then(|| {
+        (
+            slot_to_entries_key(slot),
+            entries::Entries {
+                entries: entries.into_iter().enumerate().map(Into::into).collect(),
+            },
+        )
+    });
+
+    let num_transactions = confirmed_block.transactions.len();
+    ConfirmedBlockCells {
+        tx_cells,
+        tx_by_addr_cells,
+        entry_cell,
+        block_cell: (slot_to_blocks_key(slot), confirmed_block.into()),
+        num_transactions,
+    }
+}
+
 pub const DEFAULT_INSTANCE_NAME: &str = "solana-ledger";
 pub const DEFAULT_APP_PROFILE_ID: &str = "default";
 pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024; // 64MB
@@ -791,8 +935,6 @@ impl LedgerStorage {
             }
         };
 
-        let mut infos = vec![];
-
         let starting_slot_tx_len = bigtable
             .get_protobuf_or_bincode_cell::<Vec<LegacyTransactionByAddrInfo>, tx_by_addr::TransactionByAddr>(
                 "tx-by-addr",
@@ -826,60 +968,44 @@ impl LedgerStorage {
             )
             .await?;
 
-        'outer: for (row_key, data) in tx_by_addr_data {
-            let slot = !key_to_slot(&row_key[address_prefix.len()..]).ok_or_else(|| {
-                bigtable::Error::ObjectCorrupt(format!(
-                    "Failed to convert key to slot: tx-by-addr/{row_key}"
-                ))
-            })?;
-
-            let deserialized_cell_data = bigtable::deserialize_protobuf_or_bincode_cell_data::<
-                Vec<LegacyTransactionByAddrInfo>,
-                tx_by_addr::TransactionByAddr,
-            >(&data, "tx-by-addr", row_key.clone())?;
-
-            let mut cell_data: Vec<TransactionByAddrInfo> = match deserialized_cell_data {
-                bigtable::CellData::Bincode(tx_by_addr) => {
-                    tx_by_addr.into_iter().map(|legacy| legacy.into()).collect()
-                }
-                bigtable::CellData::Protobuf(tx_by_addr) => {
-                    tx_by_addr.try_into().map_err(|error| {
-                        bigtable::Error::ObjectCorrupt(format!(
-                            "Failed to deserialize: {}: tx-by-addr/{}",
-                            error,
-                            row_key.clone()
-                        ))
-                    })?
-                }
-            };
+        let rows = tx_by_addr_data.into_iter().map(
+            |(row_key, data)| -> Result<(Slot, Vec<TransactionByAddrInfo>)> {
+                let slot = !key_to_slot(&row_key[address_prefix.len()..]).ok_or_else(|| {
+                    bigtable::Error::ObjectCorrupt(format!(
+                        "Failed to convert key to slot: tx-by-addr/{row_key}"
+                    ))
+                })?;
+
+                let deserialized_cell_data =
+                    bigtable::deserialize_protobuf_or_bincode_cell_data::<
+                        Vec<LegacyTransactionByAddrInfo>,
+                        tx_by_addr::TransactionByAddr,
+                    >(&data, "tx-by-addr", row_key.clone())?;
+
+                let cell_data: Vec<TransactionByAddrInfo> = match deserialized_cell_data {
+                    bigtable::CellData::Bincode(tx_by_addr) => {
+                        tx_by_addr.into_iter().map(|legacy| legacy.into()).collect()
+                    }
+                    bigtable::CellData::Protobuf(tx_by_addr) => {
+                        tx_by_addr.try_into().map_err(|error| {
+                            bigtable::Error::ObjectCorrupt(format!(
+                                "Failed to deserialize: {}: tx-by-addr/{}",
+                                error,
+                                row_key.clone()
+                            ))
+                        })?
+                    }
+                };
+                Ok((slot, cell_data))
+            },
+        );
 
-            cell_data.reverse();
-            for tx_by_addr_info in cell_data.into_iter() {
-                // Filter out records before `before_transaction_index`
-                if slot == first_slot && tx_by_addr_info.index >= before_transaction_index {
-                    continue;
-                }
-                // Filter out records after `until_transaction_index`
-                if slot == last_slot && tx_by_addr_info.index <= until_transaction_index {
-                    continue;
-                }
-                infos.push((
-                    ConfirmedTransactionStatusWithSignature {
-                        signature: tx_by_addr_info.signature,
-                        slot,
-                        err: tx_by_addr_info.err,
-                        memo: tx_by_addr_info.memo,
-                        block_time: tx_by_addr_info.block_time,
-                    },
-                    tx_by_addr_info.index,
-                ));
-                // Respect limit
-                if infos.len() >= limit {
-                    break 'outer;
-                }
-            }
-        }
-        Ok(infos)
+        collect_signatures_for_address(
+            rows,
+            (first_slot, before_transaction_index),
+            (last_slot, until_transaction_index),
+            limit,
+        )
     }
 
     /// Upload a new confirmed block and associated meta data.
@@ -905,73 +1031,16 @@ impl LedgerStorage {
         confirmed_block: VersionedConfirmedBlockWithEntries,
     ) -> Result<()> {
         trace!("LedgerStorage::upload_confirmed_block_with_entries request received: {slot:?}");
-        let mut by_addr: HashMap<&Pubkey, Vec<TransactionByAddrInfo>> = HashMap::new();
-        let VersionedConfirmedBlockWithEntries {
-            block: confirmed_block,
-            entries,
-        } = confirmed_block;
-
-        let reserved_account_keys = ReservedAccountKeys::new_all_activated();
-        let mut tx_cells = Vec::with_capacity(confirmed_block.transactions.len());
-        for (index, transaction_with_meta) in confirmed_block.transactions.iter().enumerate() {
-            let VersionedTransactionWithStatusMeta { meta, transaction } = transaction_with_meta;
-            let err = meta.status.clone().err();
-            let index = index as u32;
-            let signature = transaction.signatures[0];
-            let memo = extract_and_fmt_memos(transaction_with_meta);
-
-            for address in transaction_with_meta.account_keys().iter() {
-                // Historical note that previously only a set of sysvar ids were
-                // skipped from being uploaded. Now we skip uploaded for the set
-                // of all reserved account keys which will continue to grow in
-                // the future.
-                if !reserved_account_keys.is_reserved(address) {
-                    by_addr
-                        .entry(address)
-                        .or_default()
-                        .push(TransactionByAddrInfo {
-                            signature,
-                            err: err.clone(),
-                            index,
-                            memo: memo.clone(),
-                            block_time: confirmed_block.block_time,
-                        });
-                }
-            }
-
-            tx_cells.push((
-                signature.to_string(),
-                TransactionInfo {
-                    slot,
-                    index,
-                    err,
-                    memo,
-                },
-            ));
-        }
-
-        let tx_by_addr_cells: Vec<_> = by_addr
-            .into_iter()
-            .map(|(address, transaction_info_by_addr)| {
-                (
-                    format!("{}/{}", address, slot_to_tx_by_addr_key(slot)),
-                    tx_by_addr::TransactionByAddr {
-                        tx_by_addrs: transaction_info_by_addr
-                            .into_iter()
-                            .map(|by_addr| by_addr.into())
-                            .collect(),
-                    },
-                )
-            })
-            .collect();
-
-        let num_entries = entries.len();
-        let entry_cell = (
-            slot_to_entries_key(slot),
-            entries::Entries {
-                entries: entries.into_iter().enumerate().map(Into::into).collect(),
-            },
-        );
+        let ConfirmedBlockCells {
+            tx_cells,
+            tx_by_addr_cells,
+            entry_cell,
+            block_cell,
+            num_transactions,
+        } = confirmed_block_to_cells(slot, confirmed_block);
+        let num_entries = entry_cell
+            .as_ref()
+            .map_or(0, |(_, entries)| entries.entries.len());
 
         let mut tasks = vec![];
 
@@ -994,7 +1063,7 @@ impl LedgerStorage {
             }));
         }
 
-        if num_entries > 0 {
+        if let Some(entry_cell) = entry_cell {
             let conn = self.connection.clone();
             tasks.push(tokio::spawn(async move {
                 conn.put_protobuf_cells_with_retry::<entries::Entries>("entries", &[entry_cell])
@@ -1028,12 +1097,10 @@ impl LedgerStorage {
             return Err(err);
         }
 
-        let num_transactions = confirmed_block.transactions.len();
-
         // Store the block itself last, after all other metadata about the block has been
         // successfully stored.  This avoids partial uploaded blocks from becoming visible to
         // `get_confirmed_block()` and `get_confirmed_blocks()`
-        let blocks_cells = [(slot_to_blocks_key(slot), confirmed_block.into())];
+        let blocks_cells = [block_cell];
         bytes_written += self
             .connection
             .put_protobuf_cells_with_retry::<generated::ConfirmedBlock>("blocks", &blocks_cells)
@@ -1188,6 +1255,162 @@ impl LedgerStorage {
     }
 }
 
+/// A backend for long-term ledger storage, holding the confirmed blocks, transactions and
+/// address signatures that have been purged from the local Blockstore.
+///
+/// All backends share the `blocks`, `entries`, `tx` and `tx-by-addr` layout and cell encodings
+/// defined by [`LedgerStorage`]
+#[async_trait]
+pub trait LongTermLedgerStorage: Send + Sync {
+    /// Return the available slot that contains a block
+    async fn get_first_available_block(&self) -> Result<Option<Slot>>;
+
+    /// Fetch the next slots after the provided slot that contains a block
+    ///
+    /// start_slot: slot to start the search from (inclusive)
+    /// limit: stop after this many slots have been found
+    async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>>;
+
+    /// Fetch the confirmed block from the desired slot
+    async fn get_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlock>;
+
+    /// Does the confirmed block exist in the storage
+    async fn confirmed_block_exists(&self, slot: Slot) -> Result<bool>;
+
+    /// Fetch the entry summaries of the block at the desired slot
+    async fn get_entries(&self, slot: Slot) -> Result<Vec<EntrySummary>>;
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus>;
+
+    /// Fetch a confirmed transaction
+    async fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionWithStatusMeta>>;
+
+    /// Get confirmed signatures for the provided address, in descending ledger order
+    ///
+    /// address: address to search for
+    /// before_signature: start with the first signature older than this one
+    /// until_signature: end with the last signature more recent than this one
+    /// limit: stop after this many signatures
+    async fn get_confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> Result<
+        Vec<(
+            ConfirmedTransactionStatusWithSignature,
+            u32, /*slot index*/
+        )>,
+    >;
+
+    /// Upload a new confirmed block and associated meta data.
+    async fn upload_confirmed_block_with_entries(
+        &self,
+        slot: Slot,
+        confirmed_block: VersionedConfirmedBlockWithEntries,
+    ) -> Result<()>;
+
+    /// Delete a confirmed block and associated meta data.
+    async fn delete_confirmed_block(&self, slot: Slot, dry_run: bool) -> Result<()>;
+}
+
+#[async_trait]
+impl LongTermLedgerStorage for LedgerStorage {
+    async fn get_first_available_block(&self) -> Result<Option<Slot>> {
+        LedgerStorage::get_first_available_block(self).await
+    }
+
+    async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>> {
+        LedgerStorage::get_confirmed_blocks(self, start_slot, limit).await
+    }
+
+    async fn get_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlock> {
+        LedgerStorage::get_confirmed_block(self, slot).await
+    }
+
+    async fn confirmed_block_exists(&self, slot: Slot) -> Result<bool> {
+        LedgerStorage::confirmed_block_exists(self, slot).await
+    }
+
+    async fn get_entries(&self, slot: Slot) -> Result<Vec<EntrySummary>> {
+        Ok(LedgerStorage::get_entries(self, slot).await?.collect())
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus> {
+        LedgerStorage::get_signature_status(self, signature).await
+    }
+
+    async fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionWithStatusMeta>> {
+        LedgerStorage::get_confirmed_transaction(self, signature).await
+    }
+
+    async fn get_confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> Result<Vec<(ConfirmedTransactionStatusWithSignature, u32)>> {
+        LedgerStorage::get_confirmed_signatures_for_address(
+            self,
+            address,
+            before_signature,
+            until_signature,
+            limit,
+        )
+        .await
+    }
+
+    async fn upload_confirmed_block_with_entries(
+        &self,
+        slot: Slot,
+        confirmed_block: VersionedConfirmedBlockWithEntries,
+    ) -> Result<()> {
+        LedgerStorage::upload_confirmed_block_with_entries(self, slot, confirmed_block).await
+    }
+
+    async fn delete_confirmed_block(&self, slot: Slot, dry_run: bool) -> Result<()> {
+        LedgerStorage::delete_confirmed_block(self, slot, dry_run).await
+    }
+}
+
+/// Selects the backend of a [`LongTermLedgerStorage`]
+#[derive(Debug)]
+pub enum LongTermLedgerStorageConfig {
+    BigTable(LedgerStorageConfig),
+    FileSystem(FileLedgerStorageConfig),
+}
+
+impl LongTermLedgerStorageConfig {
+    pub fn read_only(&self) -> bool {
+        match self {
+            Self::BigTable(config) => config.read_only,
+            Self::FileSystem(config) => config.read_only,
+        }
+    }
+}
+
+/// Open the long-term ledger storage backend described by `config`
+pub async fn new_long_term_ledger_storage(
+    config: LongTermLedgerStorageConfig,
+) -> Result<Arc<dyn LongTermLedgerStorage>> {
+    Ok(match config {
+        LongTermLedgerStorageConfig::BigTable(config) => {
+            Arc::new(LedgerStorage::new_with_config(config).await?)
+        }
+        LongTermLedgerStorageConfig::FileSystem(config) => {
+            Arc::new(FileLedgerStorage::new_with_config(config)?)
+        }
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
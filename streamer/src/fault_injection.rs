@@ -0,0 +1,319 @@
+//! In-process fault injection for packets received by [`receiver`] and
+//! [`receiver_atomic`].
+//!
+//! This is meant for test clusters that run every validator in one process,
+//! where faults cannot be injected with `tc` or iptables. A
+//! [`PacketFaultInjector`] installed with [`set_packet_fault_injector`]
+//! decides the fate of every packet received on every UDP socket served by
+//! the streamer: it is delivered, dropped, delayed or duplicated. Delayed and
+//! duplicated packets are sent to the receiving socket again from a loopback
+//! socket owned by this module, and their original source address is
+//! restored when they arrive.
+//!
+//! Packets received over QUIC are not affected.
+//!
+//! [`receiver`]: crate::streamer::receiver
+//! [`receiver_atomic`]: crate::streamer::receiver_atomic
+
+use {
+    crate::packet::PinnedPacketBatch,
+    solana_net_utils::sockets::bind_to_localhost_unique,
+    solana_packet::Packet,
+    std::{
+        cmp::Reverse,
+        collections::{hash_map::DefaultHasher, BinaryHeap, HashMap, VecDeque},
+        hash::{Hash, Hasher},
+        net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Condvar, LazyLock, Mutex, RwLock,
+        },
+        thread::Builder,
+        time::{Duration, Instant},
+    },
+};
+
+/// What happens to a received packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PacketFate {
+    Drop,
+    /// Deliver `copies` copies of the packet after `delay`.
+    Deliver {
+        delay: Duration,
+        copies: usize,
+    },
+}
+
+impl PacketFate {
+    pub const UNCHANGED: Self = Self::Deliver {
+        delay: Duration::ZERO,
+        copies: 1,
+    };
+}
+
+pub trait PacketFaultInjector: Send + Sync {
+    /// Decide the fate of a packet sent from `from` and received on `to`.
+    fn packet_fate(&self, from: SocketAddr, to: SocketAddr) -> PacketFate;
+}
+
+/// Set once an injector has been installed. Until then, received packets
+/// are not inspected at all.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static INJECTOR: RwLock<Option<Arc<dyn PacketFaultInjector>>> = RwLock::new(None);
+static RESENDER: LazyLock<Arc<Resender>> =
+    LazyLock::new(|| Resender::new().expect("bind fault injection socket"));
+
+/// Install `injector` for every socket in this process, replacing the
+/// previous one, or remove it with `None`. Packets already delayed are still
+/// delivered.
+pub fn set_packet_fault_injector(injector: Option<Arc<dyn PacketFaultInjector>>) {
+    if injector.is_some() {
+        LazyLock::force(&RESENDER);
+        ENABLED.store(true, Ordering::Relaxed);
+    }
+    *INJECTOR.write().unwrap() = injector;
+}
+
+/// Apply the installed injector to `batch`, received on `local_addr`.
+pub(crate) fn apply_packet_faults(local_addr: SocketAddr, batch: &mut PinnedPacketBatch) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let injector = INJECTOR.read().unwrap().clone();
+    let resender = &*RESENDER;
+    let local_addr = loopback_if_unspecified(local_addr);
+    batch.retain_mut(|packet| {
+        let from = packet.meta().socket_addr();
+        if from == resender.addr {
+            if let Some(from) = resender.take_original_sender(local_addr, packet) {
+                packet.meta_mut().set_socket_addr(&from);
+            }
+            return true;
+        }
+
+        let fate = injector.as_ref().map_or(PacketFate::UNCHANGED, |injector| {
+            injector.packet_fate(from, local_addr)
+        });
+        match fate {
+            PacketFate::Drop => false,
+            PacketFate::Deliver { delay, copies } if delay.is_zero() => {
+                for _ in 1..copies {
+                    resender.schedule(local_addr, from, packet, delay);
+                }
+                copies > 0
+            }
+            PacketFate::Deliver { delay, copies } => {
+                for _ in 0..copies {
+                    resender.schedule(local_addr, from, packet, delay);
+                }
+                false
+            }
+        }
+    });
+}
+
+struct DelayedPacket {
+    deadline: Instant,
+    to: SocketAddr,
+    payload: Vec<u8>,
+}
+
+impl PartialEq for DelayedPacket {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline
+    }
+}
+
+impl Eq for DelayedPacket {}
+
+impl PartialOrd for DelayedPacket {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DelayedPacket {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.deadline.cmp(&other.deadline)
+    }
+}
+
+/// Sends delayed packets to their destination once their deadline passes.
+struct Resender {
+    socket: UdpSocket,
+    addr: SocketAddr,
+    queue: Mutex<BinaryHeap<Reverse<DelayedPacket>>>,
+    queue_changed: Condvar,
+    /// Original senders of packets in flight from `socket`, keyed by
+    /// destination and payload hash.
+    original_senders: Mutex<HashMap<(SocketAddr, u64), VecDeque<SocketAddr>>>,
+}
+
+impl Resender {
+    fn new() -> std::io::Result<Arc<Self>> {
+        let socket = bind_to_localhost_unique()?;
+        let resender = Arc::new(Self {
+            addr: socket.local_addr()?,
+            socket,
+            queue: Mutex::default(),
+            queue_changed: Condvar::new(),
+            original_senders: Mutex::default(),
+        });
+        let thread_resender = resender.clone();
+        Builder::new()
+            .name("solFaultResend".to_string())
+            .spawn(move || thread_resender.run())?;
+        Ok(resender)
+    }
+
+    fn schedule(&self, to: SocketAddr, from: SocketAddr, packet: &Packet, delay: Duration) {
+        let Some(payload) = packet.data(..) else {
+            return;
+        };
+        self.original_senders
+            .lock()
+            .unwrap()
+            .entry((to, payload_hash(payload)))
+            .or_default()
+            .push_back(from);
+        self.queue.lock().unwrap().push(Reverse(DelayedPacket {
+            deadline: Instant::now() + delay,
+            to,
+            payload: payload.to_vec(),
+        }));
+        self.queue_changed.notify_one();
+    }
+
+    fn take_original_sender(&self, to: SocketAddr, packet: &Packet) -> Option<SocketAddr> {
+        let key = (to, payload_hash(packet.data(..)?));
+        let mut original_senders = self.original_senders.lock().unwrap();
+        let senders = original_senders.get_mut(&key)?;
+        let from = senders.pop_front();
+        if senders.is_empty() {
+            original_senders.remove(&key);
+        }
+        from
+    }
+
+    fn run(&self) {
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            match queue.peek() {
+                Some(Reverse(packet)) if packet.deadline <= now => {
+                    let Reverse(packet) = queue.pop().unwrap();
+                    if let Err(err) = self.socket.send_to(&packet.payload, packet.to) {
+                        trace!("failed to resend delayed packet to {}: {err}", packet.to);
+                    }
+                }
+                Some(Reverse(packet)) => {
+                    let timeout = packet.deadline - now;
+                    queue = self.queue_changed.wait_timeout(queue, timeout).unwrap().0;
+                }
+                None => queue = self.queue_changed.wait(queue).unwrap(),
+            }
+        }
+    }
+}
+
+fn payload_hash(payload: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    payload.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn loopback_if_unspecified(addr: SocketAddr) -> SocketAddr {
+    if addr.ip().is_unspecified() {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), addr.port())
+    } else {
+        addr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::packet::{self, PACKETS_PER_BATCH},
+    };
+
+    /// Delivers, then delays and duplicates, then drops packets sent to
+    /// `target`, in turn.
+    struct RotatingFaults {
+        target: SocketAddr,
+        count: Mutex<usize>,
+    }
+
+    impl PacketFaultInjector for RotatingFaults {
+        fn packet_fate(&self, _from: SocketAddr, to: SocketAddr) -> PacketFate {
+            if to != self.target {
+                return PacketFate::UNCHANGED;
+            }
+            let mut count = self.count.lock().unwrap();
+            *count += 1;
+            match *count % 3 {
+                1 => PacketFate::UNCHANGED,
+                2 => PacketFate::Deliver {
+                    delay: Duration::from_millis(50),
+                    copies: 2,
+                },
+                _ => PacketFate::Drop,
+            }
+        }
+    }
+
+    fn recv_with_faults(socket: &UdpSocket) -> PinnedPacketBatch {
+        let mut batch = PinnedPacketBatch::with_capacity(PACKETS_PER_BATCH);
+        batch.resize(PACKETS_PER_BATCH, Packet::default());
+        #[cfg(unix)]
+        {
+            use {
+                nix::poll::{PollFd, PollFlags},
+                std::os::fd::AsFd,
+            };
+            let mut poll_fd = [PollFd::new(socket.as_fd(), PollFlags::POLLIN)];
+            packet::recv_from(&mut batch, socket, None, &mut poll_fd).unwrap();
+        }
+        #[cfg(not(unix))]
+        packet::recv_from(&mut batch, socket, None).unwrap();
+        apply_packet_faults(socket.local_addr().unwrap(), &mut batch);
+        batch
+    }
+
+    #[test]
+    fn test_packet_faults() {
+        let receiver = bind_to_localhost_unique().unwrap();
+        receiver.set_nonblocking(true).unwrap();
+        let receiver_addr = receiver.local_addr().unwrap();
+        let sender = bind_to_localhost_unique().unwrap();
+        let sender_addr = sender.local_addr().unwrap();
+
+        set_packet_fault_injector(Some(Arc::new(RotatingFaults {
+            target: receiver_addr,
+            count: Mutex::new(0),
+        })));
+        for payload in [b"a", b"b", b"c"] {
+            sender.send_to(payload, receiver_addr).unwrap();
+        }
+        std::thread::sleep(Duration::from_millis(10));
+        let batch = recv_with_faults(&receiver);
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].data(..), Some(&b"a"[..]));
+
+        // Both copies of the delayed packet arrive with the original sender.
+        std::thread::sleep(Duration::from_millis(100));
+        let batch = recv_with_faults(&receiver);
+        assert_eq!(batch.len(), 2);
+        for packet in batch.iter() {
+            assert_eq!(packet.data(..), Some(&b"b"[..]));
+            assert_eq!(packet.meta().socket_addr(), sender_addr);
+        }
+
+        set_packet_fault_injector(None);
+        for payload in [b"d", b"e", b"f"] {
+            sender.send_to(payload, receiver_addr).unwrap();
+        }
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(recv_with_faults(&receiver).len(), 3);
+    }
+}
@@ -1,6 +1,8 @@
 #![allow(clippy::arithmetic_side_effects)]
 pub mod atomic_udp_socket;
 pub mod evicting_sender;
+#[cfg(feature = "dev-context-only-utils")]
+pub mod fault_injection;
 pub mod msghdr;
 pub mod nonblocking;
 pub mod packet;
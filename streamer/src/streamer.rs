@@ -183,6 +183,8 @@ fn recv_loop<P: SocketProvider>(
 
     let mut socket = provider.current_socket_ref();
     setup_socket(&socket)?;
+    #[cfg(feature = "dev-context-only-utils")]
+    let mut local_addr = socket.local_addr()?;
     #[cfg(unix)]
     let mut poll_fd = [PollFd::new(socket.as_fd(), PollFlags::POLLIN)];
 
@@ -215,6 +217,12 @@ fn recv_loop<P: SocketProvider>(
 
             if let Ok(len) = result {
                 if len > 0 {
+                    #[cfg(feature = "dev-context-only-utils")]
+                    crate::fault_injection::apply_packet_faults(local_addr, &mut packet_batch);
+                    let len = packet_batch.len();
+                    if len == 0 {
+                        break;
+                    }
                     let StreamerReceiveStats {
                         packets_count,
                         packet_batches_count,
@@ -249,6 +257,10 @@ fn recv_loop<P: SocketProvider>(
         if let CurrentSocket::Changed(s) = provider.current_socket() {
             socket = s;
             setup_socket(&socket)?;
+            #[cfg(feature = "dev-context-only-utils")]
+            {
+                local_addr = socket.local_addr()?;
+            }
 
             #[cfg(unix)]
             {
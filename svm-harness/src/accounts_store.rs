@@ -0,0 +1,75 @@
+//! An in-memory account store for the SVM.
+
+use {
+    solana_account::{AccountSharedData, ReadableAccount, WritableAccount},
+    solana_pubkey::Pubkey,
+    solana_sdk_ids::native_loader,
+    solana_svm_callback::{InvokeContextCallback, TransactionProcessingCallback},
+    std::{collections::HashMap, sync::RwLock},
+};
+
+/// Accounts kept in memory, which the SVM loads transaction accounts from.
+///
+/// Accounts with zero lamports do not exist: storing one removes the account.
+#[derive(Debug, Default)]
+pub struct AccountsStore {
+    accounts: RwLock<HashMap<Pubkey, AccountSharedData>>,
+}
+
+impl AccountsStore {
+    pub fn new(accounts: impl IntoIterator<Item = (Pubkey, AccountSharedData)>) -> Self {
+        let store = Self::default();
+        for (pubkey, account) in accounts {
+            store.set_account(pubkey, account);
+        }
+        store
+    }
+
+    pub fn get_account(&self, pubkey: &Pubkey) -> Option<AccountSharedData> {
+        self.accounts.read().unwrap().get(pubkey).cloned()
+    }
+
+    /// Store `account` at `pubkey`, or remove the account at `pubkey` if
+    /// `account` has zero lamports.
+    pub fn set_account(&self, pubkey: Pubkey, account: AccountSharedData) {
+        let mut accounts = self.accounts.write().unwrap();
+        if account.lamports() == 0 {
+            accounts.remove(&pubkey);
+        } else {
+            accounts.insert(pubkey, account);
+        }
+    }
+
+    pub fn remove_account(&self, pubkey: &Pubkey) -> Option<AccountSharedData> {
+        self.accounts.write().unwrap().remove(pubkey)
+    }
+
+    /// A copy of every account in the store.
+    pub fn accounts(&self) -> HashMap<Pubkey, AccountSharedData> {
+        self.accounts.read().unwrap().clone()
+    }
+}
+
+impl InvokeContextCallback for AccountsStore {}
+
+impl TransactionProcessingCallback for AccountsStore {
+    fn account_matches_owners(&self, account: &Pubkey, owners: &[Pubkey]) -> Option<usize> {
+        let accounts = self.accounts.read().unwrap();
+        let account = accounts.get(account)?;
+        owners.iter().position(|owner| account.owner() == owner)
+    }
+
+    fn get_account_shared_data(&self, pubkey: &Pubkey) -> Option<AccountSharedData> {
+        self.get_account(pubkey)
+    }
+
+    fn add_builtin_account(&self, name: &str, program_id: &Pubkey) {
+        let mut accounts = self.accounts.write().unwrap();
+        accounts.entry(*program_id).or_insert_with(|| {
+            let mut account = AccountSharedData::new(1, name.len(), &native_loader::id());
+            account.set_data_from_slice(name.as_bytes());
+            account.set_executable(true);
+            account
+        });
+    }
+}
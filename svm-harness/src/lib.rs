@@ -0,0 +1,756 @@
+//! Run transactions with the SVM outside of a validator.
+//!
+//! [`SvmHarness`] wires a [`TransactionBatchProcessor`] to an in-memory
+//! [`AccountsStore`] holding the builtin programs, precompiles and sysvars
+//! of a new cluster, so that a batch of transactions can be executed with a
+//! single call:
+//!
+//! ```ignore
+//! let harness = SvmHarness::default();
+//! harness.accounts().set_account(payer, funded_account);
+//! let output = harness.execute(&transactions);
+//! for diff in &output.account_diffs {
+//!     println!("{}: {:?} -> {:?}", diff.pubkey, diff.pre, diff.post);
+//! }
+//! harness.commit(&output);
+//! ```
+//!
+//! Transactions are not checked for age or duplicate signatures before they
+//! are executed; that is left to the caller.
+
+pub mod accounts_store;
+
+#[allow(deprecated)]
+use solana_sysvar::recent_blockhashes::{IterItem, RecentBlockhashes};
+use {
+    crate::accounts_store::AccountsStore,
+    agave_feature_set::FeatureSet,
+    agave_precompiles::{get_precompile, get_precompiles, is_precompile},
+    agave_syscalls::{
+        create_program_runtime_environment_v1, create_program_runtime_environment_v2,
+    },
+    solana_account::{AccountSharedData, ReadableAccount, WritableAccount},
+    solana_builtins::BUILTINS,
+    solana_clock::{Clock, Slot},
+    solana_compute_budget_instruction::instructions_processor::process_compute_budget_instructions,
+    solana_epoch_schedule::EpochSchedule,
+    solana_fee::{calculate_fee_details, FeeFeatures},
+    solana_fee_structure::{FeeBudgetLimits, FeeStructure},
+    solana_hash::Hash,
+    solana_loader_v3_interface::{get_program_data_address, state::UpgradeableLoaderState},
+    solana_precompile_error::PrecompileError,
+    solana_program_runtime::{
+        execution_budget::SVMTransactionExecutionBudget,
+        loaded_programs::{BlockRelation, ForkGraph, ProgramCacheEntry},
+    },
+    solana_pubkey::Pubkey,
+    solana_rent::Rent,
+    solana_rent_collector::RentCollector,
+    solana_sdk_ids::{bpf_loader_upgradeable, native_loader, sysvar},
+    solana_slot_hashes::SlotHashes,
+    solana_svm::{
+        account_loader::CheckedTransactionDetails,
+        transaction_processing_result::{ProcessedTransaction, TransactionProcessingResult},
+        transaction_processor::{
+            ExecutionRecordingConfig, TransactionBatchProcessor, TransactionProcessingConfig,
+            TransactionProcessingEnvironment,
+        },
+    },
+    solana_svm_callback::{InvokeContextCallback, TransactionProcessingCallback},
+    solana_svm_transaction::svm_transaction::SVMTransaction,
+    solana_transaction_error::TransactionResult,
+    std::{
+        cmp::Ordering,
+        collections::HashMap,
+        sync::{Arc, RwLock},
+    },
+};
+
+/// Settings of the cluster that transactions are executed in.
+#[derive(Clone)]
+pub struct SvmHarnessConfig {
+    pub feature_set: FeatureSet,
+    /// The budget the program runtime environment is created with.
+    /// Transactions still set their own limits with compute budget
+    /// instructions.
+    pub compute_budget: SVMTransactionExecutionBudget,
+    pub lamports_per_signature: u64,
+    pub rent: Rent,
+    pub epoch_schedule: EpochSchedule,
+    /// Programs added with [`SvmHarness::add_program`] are deployed in the
+    /// slot before this one, so it must not be zero.
+    pub slot: Slot,
+    /// The blockhash transactions are executed under, which is what durable
+    /// nonces advance to.
+    pub blockhash: Hash,
+    pub recording_config: ExecutionRecordingConfig,
+}
+
+impl Default for SvmHarnessConfig {
+    fn default() -> Self {
+        Self {
+            feature_set: FeatureSet::all_enabled(),
+            compute_budget: SVMTransactionExecutionBudget::default(),
+            lamports_per_signature: FeeStructure::default().lamports_per_signature,
+            rent: Rent::default(),
+            epoch_schedule: EpochSchedule::default(),
+            slot: 1,
+            blockhash: Hash::default(),
+            recording_config: ExecutionRecordingConfig {
+                enable_log_recording: true,
+                enable_return_data_recording: true,
+                ..ExecutionRecordingConfig::default()
+            },
+        }
+    }
+}
+
+/// The state of an account before and after a batch of transactions.
+///
+/// `None` means the account does not exist.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountDiff {
+    pub pubkey: Pubkey,
+    pub pre: Option<AccountSharedData>,
+    pub post: Option<AccountSharedData>,
+}
+
+/// The result of executing a batch of transactions with [`SvmHarness::execute`].
+pub struct ExecutionOutput {
+    /// One result per transaction, in the order they were passed in.
+    pub processing_results: Vec<TransactionProcessingResult>,
+    /// Every account whose state was changed by the batch, in the order it
+    /// was first written.
+    pub account_diffs: Vec<AccountDiff>,
+}
+
+/// Slots only ever advance in the harness, so every slot is an ancestor of
+/// the slots after it.
+struct HarnessForkGraph;
+
+impl ForkGraph for HarnessForkGraph {
+    fn relationship(&self, a: Slot, b: Slot) -> BlockRelation {
+        match a.cmp(&b) {
+            Ordering::Less => BlockRelation::Ancestor,
+            Ordering::Equal => BlockRelation::Equal,
+            Ordering::Greater => BlockRelation::Descendant,
+        }
+    }
+}
+
+/// Loads accounts from the store, and runs the precompiles enabled by the
+/// feature set.
+struct HarnessCallback<'a> {
+    accounts: &'a AccountsStore,
+    feature_set: &'a FeatureSet,
+}
+
+impl InvokeContextCallback for HarnessCallback<'_> {
+    fn is_precompile(&self, program_id: &Pubkey) -> bool {
+        is_precompile(program_id, |feature_id| {
+            self.feature_set.is_active(feature_id)
+        })
+    }
+
+    fn process_precompile(
+        &self,
+        program_id: &Pubkey,
+        data: &[u8],
+        instruction_datas: Vec<&[u8]>,
+    ) -> Result<(), PrecompileError> {
+        match get_precompile(program_id, |feature_id| {
+            self.feature_set.is_active(feature_id)
+        }) {
+            Some(precompile) => precompile.verify(data, &instruction_datas, self.feature_set),
+            None => Err(PrecompileError::InvalidPublicKey),
+        }
+    }
+}
+
+impl TransactionProcessingCallback for HarnessCallback<'_> {
+    fn account_matches_owners(&self, account: &Pubkey, owners: &[Pubkey]) -> Option<usize> {
+        self.accounts.account_matches_owners(account, owners)
+    }
+
+    fn get_account_shared_data(&self, pubkey: &Pubkey) -> Option<AccountSharedData> {
+        self.accounts.get_account(pubkey)
+    }
+}
+
+/// Executes transactions with the SVM against an in-memory [`AccountsStore`].
+pub struct SvmHarness {
+    accounts: AccountsStore,
+    config: SvmHarnessConfig,
+    rent_collector: RentCollector,
+    // The program cache only keeps a weak reference to the fork graph
+    _fork_graph: Arc<RwLock<HarnessForkGraph>>,
+    processor: TransactionBatchProcessor<HarnessForkGraph>,
+}
+
+impl Default for SvmHarness {
+    fn default() -> Self {
+        Self::new(SvmHarnessConfig::default())
+    }
+}
+
+impl SvmHarness {
+    pub fn new(config: SvmHarnessConfig) -> Self {
+        Self::new_with_accounts(config, AccountsStore::default())
+    }
+
+    /// Create a harness on top of existing accounts. Sysvar accounts in
+    /// `accounts` are overwritten; builtin and precompile program accounts
+    /// are kept.
+    pub fn new_with_accounts(config: SvmHarnessConfig, accounts: AccountsStore) -> Self {
+        let epoch = config.epoch_schedule.get_epoch(config.slot);
+        let runtime_features = config.feature_set.runtime_features();
+        let fork_graph = Arc::new(RwLock::new(HarnessForkGraph));
+        let processor = TransactionBatchProcessor::new(
+            config.slot,
+            epoch,
+            Arc::downgrade(&fork_graph),
+            Some(Arc::new(
+                create_program_runtime_environment_v1(
+                    &runtime_features,
+                    &config.compute_budget,
+                    false,
+                    false,
+                )
+                .unwrap(),
+            )),
+            Some(Arc::new(create_program_runtime_environment_v2(
+                &config.compute_budget,
+                false,
+            ))),
+        );
+
+        for builtin in BUILTINS {
+            let enabled = builtin
+                .enable_feature_id
+                .is_none_or(|feature_id| config.feature_set.is_active(&feature_id));
+            // Builtins migrated to Core BPF have to be added as programs
+            let migrated = builtin
+                .core_bpf_migration_config
+                .as_ref()
+                .is_some_and(|migration| config.feature_set.is_active(&migration.feature_id));
+            if enabled && !migrated {
+                processor.add_builtin(
+                    &accounts,
+                    builtin.program_id,
+                    builtin.name,
+                    ProgramCacheEntry::new_builtin(0, builtin.name.len(), builtin.entrypoint),
+                );
+            }
+        }
+
+        // Precompiles are not executed by the program runtime, they only need
+        // an executable account to be invoked
+        for precompile in get_precompiles() {
+            let enabled = precompile
+                .feature
+                .is_none_or(|feature_id| config.feature_set.is_active(&feature_id));
+            if enabled && accounts.get_account(&precompile.program_id).is_none() {
+                let mut account = AccountSharedData::new(1, 0, &native_loader::id());
+                account.set_executable(true);
+                accounts.set_account(precompile.program_id, account);
+            }
+        }
+
+        let rent_collector = RentCollector::new(
+            epoch,
+            config.epoch_schedule.clone(),
+            RentCollector::default().slots_per_year,
+            config.rent.clone(),
+        );
+        let harness = Self {
+            accounts,
+            config,
+            rent_collector,
+            _fork_graph: fork_graph,
+            processor,
+        };
+        harness.update_sysvars();
+        harness
+    }
+
+    pub fn accounts(&self) -> &AccountsStore {
+        &self.accounts
+    }
+
+    pub fn config(&self) -> &SvmHarnessConfig {
+        &self.config
+    }
+
+    pub fn slot(&self) -> Slot {
+        self.config.slot
+    }
+
+    /// Deploy `elf` as an upgradeable program without an upgrade authority,
+    /// in the slot before the current one so that it can be invoked right
+    /// away. A program already deployed at `program_id` is replaced.
+    pub fn add_program(&self, program_id: Pubkey, elf: &[u8]) {
+        let programdata_address = get_program_data_address(&program_id);
+
+        let program_data = bincode::serialize(&UpgradeableLoaderState::Program {
+            programdata_address,
+        })
+        .unwrap();
+        let mut program_account = AccountSharedData::new(
+            self.config.rent.minimum_balance(program_data.len()),
+            0,
+            &bpf_loader_upgradeable::id(),
+        );
+        program_account.set_data(program_data);
+        program_account.set_executable(true);
+        self.accounts.set_account(program_id, program_account);
+
+        let mut programdata_data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+            slot: self.config.slot.saturating_sub(1),
+            upgrade_authority_address: None,
+        })
+        .unwrap();
+        programdata_data.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+        programdata_data.extend_from_slice(elf);
+        let mut programdata_account = AccountSharedData::new(
+            self.config.rent.minimum_balance(programdata_data.len()),
+            0,
+            &bpf_loader_upgradeable::id(),
+        );
+        programdata_account.set_data(programdata_data);
+        self.accounts
+            .set_account(programdata_address, programdata_account);
+
+        // Earlier batches may have cached the program being replaced
+        self.processor
+            .program_cache
+            .write()
+            .unwrap()
+            .remove_programs([program_id].into_iter());
+    }
+
+    /// Advance to `slot`, updating the clock and the other slot-dependent
+    /// sysvars.
+    pub fn warp_to_slot(&mut self, slot: Slot) {
+        assert!(
+            slot >= self.config.slot,
+            "cannot warp back from slot {} to slot {slot}",
+            self.config.slot,
+        );
+        let epoch = self.config.epoch_schedule.get_epoch(slot);
+        self.processor = self.processor.new_from(slot, epoch);
+        self.rent_collector.epoch = epoch;
+        self.config.slot = slot;
+        self.update_sysvars();
+    }
+
+    /// Execute `transactions` as one batch against the store, without
+    /// changing it. Later transactions see the writes of earlier ones.
+    pub fn execute(&self, transactions: &[impl SVMTransaction]) -> ExecutionOutput {
+        let check_results = transactions
+            .iter()
+            .map(|transaction| self.check_transaction(transaction))
+            .collect::<Vec<_>>();
+        let environment = TransactionProcessingEnvironment {
+            blockhash: self.config.blockhash,
+            blockhash_lamports_per_signature: self.config.lamports_per_signature,
+            epoch_total_stake: 0,
+            feature_set: self.config.feature_set.runtime_features(),
+            rent_collector: Some(&self.rent_collector),
+        };
+        let processing_config = TransactionProcessingConfig {
+            recording_config: self.config.recording_config,
+            ..TransactionProcessingConfig::default()
+        };
+        let callback = HarnessCallback {
+            accounts: &self.accounts,
+            feature_set: &self.config.feature_set,
+        };
+        let output = self.processor.load_and_execute_sanitized_transactions(
+            &callback,
+            transactions,
+            check_results,
+            &environment,
+            &processing_config,
+        );
+
+        let account_diffs = self.account_diffs(transactions, &output.processing_results);
+        ExecutionOutput {
+            processing_results: output.processing_results,
+            account_diffs,
+        }
+    }
+
+    /// Write the account changes in `output` to the store and make programs
+    /// deployed by its transactions available to later batches.
+    pub fn commit(&self, output: &ExecutionOutput) {
+        for diff in &output.account_diffs {
+            match &diff.post {
+                Some(account) => self.accounts.set_account(diff.pubkey, account.clone()),
+                None => {
+                    self.accounts.remove_account(&diff.pubkey);
+                }
+            }
+        }
+
+        let mut program_cache = self.processor.program_cache.write().unwrap();
+        for processing_result in &output.processing_results {
+            if let Ok(ProcessedTransaction::Executed(executed_tx)) = processing_result {
+                if executed_tx.was_successful() {
+                    program_cache.merge(&executed_tx.programs_modified_by_tx);
+                }
+            }
+        }
+    }
+
+    pub fn execute_and_commit(&self, transactions: &[impl SVMTransaction]) -> ExecutionOutput {
+        let output = self.execute(transactions);
+        self.commit(&output);
+        output
+    }
+
+    fn check_transaction(
+        &self,
+        transaction: &impl SVMTransaction,
+    ) -> TransactionResult<CheckedTransactionDetails> {
+        let compute_budget_and_limits = process_compute_budget_instructions(
+            transaction.program_instructions_iter(),
+            &self.config.feature_set,
+        )
+        .map(|limits| {
+            let fee_budget = FeeBudgetLimits::from(limits);
+            let fee_details = calculate_fee_details(
+                transaction,
+                false,
+                self.config.lamports_per_signature,
+                fee_budget.prioritization_fee,
+                FeeFeatures::from(&self.config.feature_set),
+            );
+            limits.get_compute_budget_and_limits(
+                fee_budget.loaded_accounts_data_size_limit,
+                fee_details,
+            )
+        });
+        Ok(CheckedTransactionDetails::new(
+            None,
+            compute_budget_and_limits,
+        ))
+    }
+
+    /// Collect the accounts the bank would store for each transaction: the
+    /// writable accounts of successful transactions, and the fee payer and
+    /// nonce account of failed ones.
+    fn account_diffs(
+        &self,
+        transactions: &[impl SVMTransaction],
+        processing_results: &[TransactionProcessingResult],
+    ) -> Vec<AccountDiff> {
+        let mut account_diffs: Vec<AccountDiff> = vec![];
+        let mut diff_indexes = HashMap::new();
+
+        for (transaction, processing_result) in transactions.iter().zip(processing_results) {
+            let written_accounts: Vec<_> = match processing_result {
+                Ok(ProcessedTransaction::Executed(executed_tx)) if executed_tx.was_successful() => {
+                    executed_tx
+                        .loaded_transaction
+                        .accounts
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| {
+                            transaction.is_writable(*i)
+                                && !(transaction.is_invoked(*i)
+                                    && !transaction.is_instruction_account(*i))
+                        })
+                        .map(|(_, (pubkey, account))| (pubkey, account))
+                        .collect()
+                }
+                Ok(ProcessedTransaction::Executed(executed_tx)) => executed_tx
+                    .loaded_transaction
+                    .rollback_accounts
+                    .iter()
+                    .map(|(pubkey, account)| (pubkey, account))
+                    .collect(),
+                Ok(ProcessedTransaction::FeesOnly(fees_only_tx)) => fees_only_tx
+                    .rollback_accounts
+                    .iter()
+                    .map(|(pubkey, account)| (pubkey, account))
+                    .collect(),
+                Err(_) => vec![],
+            };
+
+            for (pubkey, account) in written_accounts {
+                let post = (account.lamports() != 0).then(|| account.clone());
+                match diff_indexes.get(pubkey) {
+                    Some(&index) => account_diffs[index].post = post,
+                    None => {
+                        diff_indexes.insert(*pubkey, account_diffs.len());
+                        account_diffs.push(AccountDiff {
+                            pubkey: *pubkey,
+                            pre: self.accounts.get_account(pubkey),
+                            post,
+                        });
+                    }
+                }
+            }
+        }
+
+        account_diffs.retain(|diff| diff.pre != diff.post);
+        account_diffs
+    }
+
+    fn update_sysvars(&self) {
+        let slot = self.config.slot;
+        let epoch_schedule = &self.config.epoch_schedule;
+        let clock = Clock {
+            slot,
+            epoch: epoch_schedule.get_epoch(slot),
+            leader_schedule_epoch: epoch_schedule.get_leader_schedule_epoch(slot),
+            ..Clock::default()
+        };
+        self.set_sysvar(sysvar::clock::id(), &clock);
+        self.set_sysvar(sysvar::epoch_schedule::id(), epoch_schedule);
+        self.set_sysvar(sysvar::rent::id(), &self.config.rent);
+        self.set_sysvar(sysvar::slot_hashes::id(), &SlotHashes::new(&[]));
+        // The system program requires a recent blockhash to advance nonces
+        #[allow(deprecated)]
+        let recent_blockhashes: RecentBlockhashes = [IterItem(
+            0,
+            &self.config.blockhash,
+            self.config.lamports_per_signature,
+        )]
+        .into_iter()
+        .collect();
+        #[allow(deprecated)]
+        self.set_sysvar(sysvar::recent_blockhashes::id(), &recent_blockhashes);
+
+        self.processor.reset_sysvar_cache();
+        self.processor
+            .fill_missing_sysvar_cache_entries(&self.accounts);
+    }
+
+    fn set_sysvar(&self, pubkey: Pubkey, sysvar: &impl serde::Serialize) {
+        let data = bincode::serialize(sysvar).unwrap();
+        let mut account = AccountSharedData::new(
+            self.config.rent.minimum_balance(data.len()).max(1),
+            0,
+            &sysvar::id(),
+        );
+        account.set_data(data);
+        self.accounts.set_account(pubkey, account);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_instruction::{error::InstructionError, Instruction},
+        solana_keypair::Keypair,
+        solana_native_token::LAMPORTS_PER_SOL,
+        solana_sdk_ids::ed25519_program,
+        solana_signer::Signer,
+        solana_system_interface::program as system_program,
+        solana_system_transaction as system_transaction,
+        solana_transaction::{sanitized::SanitizedTransaction, Transaction},
+        solana_transaction_error::TransactionError,
+    };
+
+    const HELLO_SOLANA_ELF: &[u8] =
+        include_bytes!("../../svm/tests/example-programs/hello-solana/hello_solana_program.so");
+    const NOOP_ELF: &[u8] =
+        include_bytes!("../../programs/bpf_loader/test_elfs/out/noop_aligned.so");
+
+    fn funded_account(lamports: u64) -> AccountSharedData {
+        AccountSharedData::new(lamports, 0, &system_program::id())
+    }
+
+    #[test]
+    fn test_execute_and_commit() {
+        let harness = SvmHarness::default();
+        let lamports_per_signature = harness.config().lamports_per_signature;
+        let alice = Keypair::new();
+        let bob = Pubkey::new_unique();
+        harness
+            .accounts()
+            .set_account(alice.pubkey(), funded_account(LAMPORTS_PER_SOL));
+
+        let transactions = [
+            SanitizedTransaction::from_transaction_for_tests(system_transaction::transfer(
+                &alice,
+                &bob,
+                LAMPORTS_PER_SOL / 4,
+                Hash::default(),
+            )),
+            SanitizedTransaction::from_transaction_for_tests(system_transaction::transfer(
+                &alice,
+                &bob,
+                LAMPORTS_PER_SOL / 4,
+                Hash::new_unique(),
+            )),
+        ];
+        let output = harness.execute(&transactions);
+        for processing_result in &output.processing_results {
+            let Ok(ProcessedTransaction::Executed(executed_tx)) = processing_result else {
+                panic!("unexpected result {processing_result:?}");
+            };
+            assert!(executed_tx.was_successful());
+        }
+
+        let alice_post = LAMPORTS_PER_SOL / 2 - 2 * lamports_per_signature;
+        assert_eq!(
+            output.account_diffs,
+            vec![
+                AccountDiff {
+                    pubkey: alice.pubkey(),
+                    pre: Some(funded_account(LAMPORTS_PER_SOL)),
+                    post: Some(funded_account(alice_post)),
+                },
+                AccountDiff {
+                    pubkey: bob,
+                    pre: None,
+                    post: Some(funded_account(LAMPORTS_PER_SOL / 2)),
+                },
+            ]
+        );
+
+        // Executing leaves the store untouched
+        assert_eq!(harness.accounts().get_account(&bob), None);
+
+        harness.commit(&output);
+        assert_eq!(
+            harness.accounts().get_account(&alice.pubkey()),
+            Some(funded_account(alice_post))
+        );
+        assert_eq!(
+            harness.accounts().get_account(&bob),
+            Some(funded_account(LAMPORTS_PER_SOL / 2))
+        );
+    }
+
+    #[test]
+    fn test_execute_failed_transaction() {
+        let harness = SvmHarness::default();
+        let lamports_per_signature = harness.config().lamports_per_signature;
+        let alice = Keypair::new();
+        let bob = Pubkey::new_unique();
+        harness
+            .accounts()
+            .set_account(alice.pubkey(), funded_account(LAMPORTS_PER_SOL));
+
+        // Only the fee is charged when the transfer fails
+        let transaction = SanitizedTransaction::from_transaction_for_tests(
+            system_transaction::transfer(&alice, &bob, 2 * LAMPORTS_PER_SOL, Hash::default()),
+        );
+        let output = harness.execute_and_commit(&[transaction]);
+        let Ok(ProcessedTransaction::Executed(executed_tx)) = &output.processing_results[0] else {
+            panic!("unexpected result {:?}", output.processing_results[0]);
+        };
+        assert!(!executed_tx.was_successful());
+        assert_eq!(
+            output.account_diffs,
+            vec![AccountDiff {
+                pubkey: alice.pubkey(),
+                pre: Some(funded_account(LAMPORTS_PER_SOL)),
+                post: Some(funded_account(LAMPORTS_PER_SOL - lamports_per_signature)),
+            }]
+        );
+        assert_eq!(harness.accounts().get_account(&bob), None);
+
+        // Nothing is loaded for an unfunded fee payer
+        let transaction = SanitizedTransaction::from_transaction_for_tests(
+            system_transaction::transfer(&Keypair::new(), &bob, 1, Hash::default()),
+        );
+        let output = harness.execute(&[transaction]);
+        assert_eq!(
+            output.processing_results[0].as_ref().err(),
+            Some(&TransactionError::AccountNotFound)
+        );
+        assert!(output.account_diffs.is_empty());
+    }
+
+    /// The log messages of the first transaction of `output`, which must
+    /// have succeeded
+    fn successful_log_messages(output: &ExecutionOutput) -> Vec<String> {
+        let Ok(ProcessedTransaction::Executed(executed_tx)) = &output.processing_results[0] else {
+            panic!("unexpected result {:?}", output.processing_results[0]);
+        };
+        assert_eq!(executed_tx.execution_details.status, Ok(()));
+        executed_tx.execution_details.log_messages.clone().unwrap()
+    }
+
+    #[test]
+    fn test_add_program() {
+        let harness = SvmHarness::default();
+        let alice = Keypair::new();
+        harness
+            .accounts()
+            .set_account(alice.pubkey(), funded_account(LAMPORTS_PER_SOL));
+        let program_id = Pubkey::new_unique();
+        harness.add_program(program_id, HELLO_SOLANA_ELF);
+
+        let invoke = |blockhash| {
+            SanitizedTransaction::from_transaction_for_tests(Transaction::new_signed_with_payer(
+                &[Instruction::new_with_bytes(program_id, &[], vec![])],
+                Some(&alice.pubkey()),
+                &[&alice],
+                blockhash,
+            ))
+        };
+        let hello = "Program log: Hello, Solana!".to_string();
+        let output = harness.execute_and_commit(&[invoke(Hash::default())]);
+        assert!(successful_log_messages(&output).contains(&hello));
+
+        // The program is still invocable once cached by the first batch
+        let output = harness.execute_and_commit(&[invoke(Hash::new_unique())]);
+        assert!(successful_log_messages(&output).contains(&hello));
+
+        // Redeploying replaces the cached program
+        harness.add_program(program_id, NOOP_ELF);
+        let output = harness.execute_and_commit(&[invoke(Hash::new_unique())]);
+        assert!(!successful_log_messages(&output).contains(&hello));
+    }
+
+    #[test]
+    fn test_precompiles() {
+        let harness = SvmHarness::default();
+        let alice = Keypair::new();
+        harness
+            .accounts()
+            .set_account(alice.pubkey(), funded_account(LAMPORTS_PER_SOL));
+        for precompile in get_precompiles() {
+            let account = harness
+                .accounts()
+                .get_account(&precompile.program_id)
+                .unwrap();
+            assert!(account.executable());
+        }
+
+        let verify = |data: &[u8]| {
+            SanitizedTransaction::from_transaction_for_tests(Transaction::new_signed_with_payer(
+                &[Instruction::new_with_bytes(
+                    ed25519_program::id(),
+                    data,
+                    vec![],
+                )],
+                Some(&alice.pubkey()),
+                &[&alice],
+                Hash::new_unique(),
+            ))
+        };
+        // No signatures to verify
+        let output = harness.execute(&[verify(&[0, 0])]);
+        successful_log_messages(&output);
+
+        // Data too short for its signature offsets fails verification
+        let output = harness.execute(&[verify(&[1, 0])]);
+        let Ok(ProcessedTransaction::Executed(executed_tx)) = &output.processing_results[0] else {
+            panic!("unexpected result {:?}", output.processing_results[0]);
+        };
+        assert!(matches!(
+            executed_tx.execution_details.status,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(_)
+            ))
+        ));
+    }
+}
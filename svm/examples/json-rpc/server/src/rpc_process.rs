@@ -340,6 +340,8 @@ impl JsonRpcRequestProcessor {
                     enable_log_recording: true,
                     enable_return_data_recording: true,
                     enable_transaction_balance_recording: true,
+                    enable_instruction_trace_recording: false,
+                    enable_instruction_trace_account_data_recording: false,
                 },
             },
         );
@@ -673,6 +675,7 @@ pub mod rpc {
                 accounts: config_accounts,
                 min_context_slot: _,
                 inner_instructions: enable_cpi_recording,
+                execution_trace: _,
             } = config.unwrap_or_default();
             let tx_encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
             let binary_encoding = tx_encoding.into_binary_encoding().ok_or_else(|| {
@@ -765,6 +768,7 @@ pub mod rpc {
                     return_data: return_data.map(|return_data| return_data.into()),
                     inner_instructions,
                     replacement_blockhash: None,
+                    execution_trace: None,
                 },
             ))
         }
@@ -4,8 +4,11 @@ use {
 };
 
 /// Encapsulates overridden accounts, typically used for transaction
-/// simulations. Account overrides are currently not used when loading the
-/// durable nonce account or when constructing the instructions sysvar account.
+/// simulations. An overridden account with zero lamports does not exist.
+///
+/// Account overrides are currently not used when loading the durable nonce
+/// account, when constructing the instructions sysvar account, or when
+/// loading executable programs into the program cache.
 #[derive(Default)]
 pub struct AccountOverrides {
     accounts: HashMap<Pubkey, AccountSharedData>,
@@ -13,7 +16,7 @@ pub struct AccountOverrides {
 
 impl AccountOverrides {
     /// Insert or remove an account with a given pubkey to/from the list of overrides.
-    fn set_account(&mut self, pubkey: &Pubkey, account: Option<AccountSharedData>) {
+    pub fn set_account(&mut self, pubkey: &Pubkey, account: Option<AccountSharedData>) {
         match account {
             Some(account) => self.accounts.insert(*pubkey, account),
             None => self.accounts.remove(pubkey),
@@ -28,9 +31,13 @@ impl AccountOverrides {
     }
 
     /// Gets the account if it's found in the list of overrides
-    pub(crate) fn get(&self, pubkey: &Pubkey) -> Option<&AccountSharedData> {
+    pub fn get(&self, pubkey: &Pubkey) -> Option<&AccountSharedData> {
         self.accounts.get(pubkey)
     }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&Pubkey, &AccountSharedData)> {
+        self.accounts.iter()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,25 @@
+use {
+    solana_clock::Slot, solana_program_runtime::invoke_context::InstructionTrace,
+    solana_signature::Signature, std::sync::Arc,
+};
+
+/// Receives the instruction traces recorded while executing transactions
+pub trait InstructionTraceNotifier {
+    /// Called once a transaction has executed, with the compute unit limit it
+    /// requested and the traces of all of its instructions in invocation order
+    fn notify_instruction_traces(
+        &self,
+        slot: Slot,
+        signature: &Signature,
+        compute_unit_limit: u64,
+        instruction_traces: &[InstructionTrace],
+    );
+
+    /// Whether the traces notified should include the data of the accounts
+    /// of every instruction, which is costly to copy
+    fn account_data_enabled(&self) -> bool {
+        false
+    }
+}
+
+pub type InstructionTraceNotifierArc = Arc<dyn InstructionTraceNotifier + Sync + Send>;
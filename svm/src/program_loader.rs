@@ -4,9 +4,12 @@ use {
     solana_instruction::error::InstructionError,
     solana_loader_v3_interface::state::UpgradeableLoaderState,
     solana_loader_v4_interface::state::{LoaderV4State, LoaderV4Status},
-    solana_program_runtime::loaded_programs::{
-        LoadProgramMetrics, ProgramCacheEntry, ProgramCacheEntryOwner, ProgramCacheEntryType,
-        ProgramRuntimeEnvironment, ProgramRuntimeEnvironments, DELAY_VISIBILITY_SLOT_OFFSET,
+    solana_program_runtime::{
+        loaded_programs::{
+            LoadProgramMetrics, ProgramCacheEntry, ProgramCacheEntryOwner, ProgramCacheEntryType,
+            ProgramRuntimeEnvironment, ProgramRuntimeEnvironments, DELAY_VISIBILITY_SLOT_OFFSET,
+        },
+        persistent_program_cache::PersistentProgramCache,
     },
     solana_pubkey::Pubkey,
     solana_sdk_ids::{bpf_loader, bpf_loader_deprecated, bpf_loader_upgradeable, loader_v4},
@@ -123,6 +126,7 @@ pub fn load_program_with_pubkey<CB: TransactionProcessingCallback>(
     slot: Slot,
     execute_timings: &mut ExecuteTimings,
     reload: bool,
+    persistent_cache: Option<&PersistentProgramCache>,
 ) -> Option<Arc<ProgramCacheEntry>> {
     let mut load_program_metrics = LoadProgramMetrics {
         program_id: pubkey.to_string(),
@@ -204,6 +208,18 @@ pub fn load_program_with_pubkey<CB: TransactionProcessingCallback>(
         )
     });
 
+    // Record the programs that were loaded, so that they can be loaded ahead
+    // of use after a restart
+    if let (Some(persistent_cache), ProgramCacheEntryType::Loaded(executable)) =
+        (persistent_cache, &loaded_program.program)
+    {
+        persistent_cache.insert(
+            *pubkey,
+            loaded_program.deployment_slot,
+            executable.get_loader(),
+        );
+    }
+
     load_program_metrics.submit_datapoint(&mut execute_timings.details);
     loaded_program.update_access_slot(slot);
     Some(Arc::new(loaded_program))
@@ -254,6 +270,7 @@ mod tests {
         solana_account::WritableAccount,
         solana_program_runtime::{
             loaded_programs::{BlockRelation, ForkGraph, ProgramRuntimeEnvironments},
+            persistent_program_cache::PersistedProgramKey,
             solana_sbpf::program::BuiltinProgram,
         },
         solana_sdk_ids::{bpf_loader, bpf_loader_upgradeable},
@@ -527,6 +544,7 @@ mod tests {
             500,
             &mut ExecuteTimings::default(),
             false,
+            None,
         );
         assert!(result.is_none());
     }
@@ -550,6 +568,7 @@ mod tests {
             0, // Slot 0
             &mut ExecuteTimings::default(),
             false,
+            None,
         );
 
         let loaded_program = ProgramCacheEntry::new_tombstone(
@@ -585,6 +604,7 @@ mod tests {
             200,
             &mut ExecuteTimings::default(),
             false,
+            None,
         );
         let loaded_program = ProgramCacheEntry::new_tombstone(
             0,
@@ -613,6 +633,7 @@ mod tests {
             200,
             &mut ExecuteTimings::default(),
             false,
+            None,
         );
 
         let environments = ProgramRuntimeEnvironments::default();
@@ -629,6 +650,53 @@ mod tests {
         assert_eq!(result.unwrap(), Arc::new(expected.unwrap()));
     }
 
+    #[test]
+    fn test_load_program_with_persistent_cache() {
+        let key = Pubkey::new_unique();
+        let invalid_key = Pubkey::new_unique();
+        let mock_bank = MockBankCallback::default();
+        let mut account_data = AccountSharedData::default();
+        account_data.set_owner(bpf_loader::id());
+        account_data.set_data(load_test_program());
+        mock_bank
+            .account_shared_data
+            .borrow_mut()
+            .insert(key, account_data.clone());
+        account_data.set_data(vec![0; 64]);
+        mock_bank
+            .account_shared_data
+            .borrow_mut()
+            .insert(invalid_key, account_data);
+        let batch_processor = TransactionBatchProcessor::<TestForkGraph>::default();
+        let environments = batch_processor.get_environments_for_epoch(0).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let persistent_cache = PersistentProgramCache::new(dir.path()).unwrap();
+
+        // Only programs which were loaded successfully are recorded
+        for key in [key, invalid_key] {
+            let result = load_program_with_pubkey(
+                &mock_bank,
+                &environments,
+                &key,
+                0,
+                &mut ExecuteTimings::default(),
+                false,
+                Some(&persistent_cache),
+            )
+            .unwrap();
+            assert_eq!(result.is_tombstone(), key == invalid_key);
+        }
+        persistent_cache.flush();
+        assert_eq!(
+            persistent_cache.programs(&environments.program_runtime_v1),
+            vec![PersistedProgramKey::new(
+                key,
+                0,
+                &environments.program_runtime_v1
+            )],
+        );
+    }
+
     #[test]
     fn test_load_program_program_loader_v3() {
         let key1 = Pubkey::new_unique();
@@ -667,6 +735,7 @@ mod tests {
             0,
             &mut ExecuteTimings::default(),
             false,
+            None,
         );
         let loaded_program = ProgramCacheEntry::new_tombstone(
             0,
@@ -705,6 +774,7 @@ mod tests {
             200,
             &mut ExecuteTimings::default(),
             false,
+            None,
         );
 
         let data = account_data.data();
@@ -755,6 +825,7 @@ mod tests {
             0,
             &mut ExecuteTimings::default(),
             false,
+            None,
         );
         let loaded_program = ProgramCacheEntry::new_tombstone(
             0,
@@ -789,6 +860,7 @@ mod tests {
             200,
             &mut ExecuteTimings::default(),
             false,
+            None,
         );
 
         let data = account_data.data()[LoaderV4State::program_data_offset()..].to_vec();
@@ -840,6 +912,7 @@ mod tests {
                 200,
                 &mut ExecuteTimings::default(),
                 false,
+                None,
             )
             .unwrap();
             assert_ne!(
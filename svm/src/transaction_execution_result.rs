@@ -6,7 +6,9 @@
 pub use solana_message::inner_instruction::{InnerInstruction, InnerInstructionsList};
 use {
     crate::account_loader::LoadedTransaction,
-    solana_program_runtime::loaded_programs::ProgramCacheEntry,
+    solana_program_runtime::{
+        invoke_context::InstructionTrace, loaded_programs::ProgramCacheEntry,
+    },
     solana_pubkey::Pubkey,
     solana_transaction_context::TransactionReturnData,
     solana_transaction_error::TransactionResult,
@@ -42,6 +44,9 @@ pub struct TransactionExecutionDetails {
     /// The change in accounts data len for this transaction.
     /// NOTE: This value is valid IFF `status` is `Ok`.
     pub accounts_data_len_delta: i64,
+    /// Traces of the processed instructions in invocation order, when
+    /// instruction trace recording is enabled.
+    pub instruction_traces: Option<Vec<InstructionTrace>>,
 }
 
 impl TransactionExecutionDetails {
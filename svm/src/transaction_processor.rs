@@ -8,6 +8,7 @@ use {
             TransactionLoadResult, ValidatedTransactionDetails,
         },
         account_overrides::AccountOverrides,
+        instruction_trace_notifier::InstructionTraceNotifierArc,
         message_processor::process_message,
         nonce_info::NonceInfo,
         program_loader::{get_program_modification_slot, load_program_with_pubkey},
@@ -89,6 +90,13 @@ pub struct ExecutionRecordingConfig {
     pub enable_log_recording: bool,
     pub enable_return_data_recording: bool,
     pub enable_transaction_balance_recording: bool,
+    /// Record an `InstructionTrace` for every processed instruction. Not
+    /// enabled by `new_single_setting`, since it is only needed by tracers.
+    pub enable_instruction_trace_recording: bool,
+    /// Copy the data of every instruction's accounts into its
+    /// `InstructionTrace`, which is costly for large accounts. Only applies
+    /// when instruction traces are recorded.
+    pub enable_instruction_trace_account_data_recording: bool,
 }
 
 impl ExecutionRecordingConfig {
@@ -98,6 +106,8 @@ impl ExecutionRecordingConfig {
             enable_log_recording: option,
             enable_cpi_recording: option,
             enable_transaction_balance_recording: option,
+            enable_instruction_trace_recording: false,
+            enable_instruction_trace_account_data_recording: false,
         }
     }
 }
@@ -164,6 +174,10 @@ pub struct TransactionBatchProcessor<FG: ForkGraph> {
     pub builtin_program_ids: RwLock<HashSet<Pubkey>>,
 
     execution_cost: SVMTransactionExecutionCost,
+
+    /// Receives the instruction traces of executed transactions, when
+    /// instruction trace recording is enabled
+    instruction_trace_notifier: RwLock<Option<InstructionTraceNotifierArc>>,
 }
 
 impl<FG: ForkGraph> Debug for TransactionBatchProcessor<FG> {
@@ -189,6 +203,7 @@ impl<FG: ForkGraph> Default for TransactionBatchProcessor<FG> {
             ))),
             builtin_program_ids: RwLock::new(HashSet::new()),
             execution_cost: SVMTransactionExecutionCost::default(),
+            instruction_trace_notifier: RwLock::default(),
         }
     }
 }
@@ -243,8 +258,8 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
     /// Create a new `TransactionBatchProcessor` from the current instance, but
     /// with the provided slot and epoch.
     ///
-    /// * Inherits the program cache and builtin program ids from the current
-    ///   instance.
+    /// * Inherits the program cache, builtin program ids and instruction trace
+    ///   notifier from the current instance.
     /// * Resets the sysvar cache.
     pub fn new_from(&self, slot: Slot, epoch: Epoch) -> Self {
         Self {
@@ -254,6 +269,68 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
             program_cache: self.program_cache.clone(),
             builtin_program_ids: RwLock::new(self.builtin_program_ids.read().unwrap().clone()),
             execution_cost: self.execution_cost,
+            instruction_trace_notifier: RwLock::new(
+                self.instruction_trace_notifier.read().unwrap().clone(),
+            ),
+        }
+    }
+
+    /// Create a new `TransactionBatchProcessor` for the same slot, whose
+    /// programs are loaded in `program_runtime_environment_v1` instead of the
+    /// environment of the current instance, e.g. to trace their syscalls.
+    ///
+    /// * Has its own program cache, holding the builtins of the current
+    ///   instance. Programs loaded into it are never seen by the current
+    ///   instance.
+    /// * Inherits the sysvar cache and builtin program ids, but no instruction
+    ///   trace notifier.
+    pub fn new_with_program_runtime_environment_v1(
+        &self,
+        program_runtime_environment_v1: ProgramRuntimeEnvironment,
+    ) -> Self {
+        let program_cache = self.program_cache.read().unwrap();
+        let mut processor = Self::new_uninitialized(self.slot, self.epoch);
+        processor.execution_cost = self.execution_cost;
+        {
+            let mut new_program_cache = processor.program_cache.write().unwrap();
+            // Programs deployed after the root are only visible to the forks they were
+            // deployed on, even if the new program cache outlives this slot
+            new_program_cache.latest_root_slot = program_cache.latest_root_slot;
+            new_program_cache.latest_root_epoch = program_cache.latest_root_epoch;
+            if let Some(fork_graph) = program_cache.fork_graph.clone() {
+                new_program_cache.set_fork_graph(fork_graph);
+            }
+            self.configure_program_runtime_environments_inner(
+                &mut new_program_cache,
+                Some(program_runtime_environment_v1),
+                Some(program_cache.environments.program_runtime_v2.clone()),
+            );
+            for (program_id, builtin) in program_cache.get_builtins() {
+                new_program_cache.assign_program(program_id, builtin);
+            }
+        }
+        *processor.sysvar_cache.write().unwrap() = self.sysvar_cache.read().unwrap().clone();
+        *processor.builtin_program_ids.write().unwrap() =
+            self.builtin_program_ids.read().unwrap().clone();
+        processor
+    }
+
+    /// Create a new `TransactionBatchProcessor` for the same slot, whose
+    /// programs are loaded into `program_cache`, e.g. the program cache of a
+    /// processor created by `new_with_program_runtime_environment_v1` for an
+    /// earlier slot of the same epoch.
+    ///
+    /// * Inherits the sysvar cache and builtin program ids, but no instruction
+    ///   trace notifier.
+    pub fn new_with_program_cache(&self, program_cache: Arc<RwLock<ProgramCache<FG>>>) -> Self {
+        Self {
+            slot: self.slot,
+            epoch: self.epoch,
+            sysvar_cache: RwLock::new(self.sysvar_cache.read().unwrap().clone()),
+            program_cache,
+            builtin_program_ids: RwLock::new(self.builtin_program_ids.read().unwrap().clone()),
+            execution_cost: self.execution_cost,
+            instruction_trace_notifier: RwLock::default(),
         }
     }
 
@@ -263,6 +340,27 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
         self.execution_cost = cost;
     }
 
+    /// Sets the notifier which receives the instruction traces of transactions
+    /// executed with instruction trace recording enabled.
+    pub fn set_instruction_trace_notifier(&self, notifier: Option<InstructionTraceNotifierArc>) {
+        *self.instruction_trace_notifier.write().unwrap() = notifier;
+    }
+
+    /// Whether a notifier is set to receive instruction traces
+    pub fn has_instruction_trace_notifier(&self) -> bool {
+        self.instruction_trace_notifier.read().unwrap().is_some()
+    }
+
+    /// Whether the instruction trace notifier wants the data of the accounts
+    /// of every instruction
+    pub fn instruction_trace_notifier_account_data_enabled(&self) -> bool {
+        self.instruction_trace_notifier
+            .read()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|notifier| notifier.account_data_enabled())
+    }
+
     fn configure_program_runtime_environments_inner(
         &self,
         program_cache: &mut ProgramCache<FG>,
@@ -396,6 +494,13 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
             .enable_transaction_balance_recording
             .then(|| BalanceCollector::new_with_transaction_count(sanitized_txs.len()));
 
+        let instruction_trace_notifier =
+            if config.recording_config.enable_instruction_trace_recording {
+                self.instruction_trace_notifier.read().unwrap().clone()
+            } else {
+                None
+            };
+
         let (mut load_us, mut execution_us): (u64, u64) = (0, 0);
 
         // Validate, execute, and collect results from each transaction in order.
@@ -444,6 +549,7 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
                     Ok(ProcessedTransaction::FeesOnly(Box::new(fees_only_tx)))
                 }
                 TransactionLoadResult::Loaded(loaded_transaction) => {
+                    let compute_unit_limit = loaded_transaction.compute_budget.compute_unit_limit;
                     let executed_tx = self.execute_loaded_transaction(
                         callbacks,
                         tx,
@@ -455,6 +561,18 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
                         config,
                     );
 
+                    if let (Some(notifier), Some(instruction_traces)) = (
+                        &instruction_trace_notifier,
+                        &executed_tx.execution_details.instruction_traces,
+                    ) {
+                        notifier.notify_instruction_traces(
+                            self.slot,
+                            tx.signature(),
+                            compute_unit_limit,
+                            instruction_traces,
+                        );
+                    }
+
                     // Update loaded accounts cache with account states which might have changed.
                     // Also update local program cache with modifications made by the transaction,
                     // if it executed successfully.
@@ -756,6 +874,7 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
                         self.slot,
                         execute_timings,
                         false,
+                        program_cache.persistent_cache.as_deref(),
                     )
                     .expect("called load_program_with_pubkey() with nonexistent account");
                     program.tx_usage_counter.store(count, Ordering::Relaxed);
@@ -852,7 +971,11 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
         let pre_account_state_info =
             TransactionAccountStateInfo::new(&transaction_context, tx, rent_collector);
 
-        let log_collector = if config.recording_config.enable_log_recording {
+        // Instruction traces include log messages, so logs are collected when
+        // either is recorded
+        let log_collector = if config.recording_config.enable_log_recording
+            || config.recording_config.enable_instruction_trace_recording
+        {
             match config.log_messages_bytes_limit {
                 None => Some(LogCollector::new_ref()),
                 Some(log_messages_bytes_limit) => Some(LogCollector::new_ref_with_limit(Some(
@@ -880,6 +1003,13 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
             compute_budget,
             self.execution_cost,
         );
+        if config.recording_config.enable_instruction_trace_recording {
+            invoke_context.enable_instruction_trace_recording(
+                config
+                    .recording_config
+                    .enable_instruction_trace_account_data_recording,
+            );
+        }
 
         let mut process_message_time = Measure::start("process_message_time");
         let process_result = process_message(
@@ -891,6 +1021,7 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
         );
         process_message_time.stop();
 
+        let instruction_traces = invoke_context.take_instruction_traces();
         drop(invoke_context);
 
         execute_timings.execute_accessories.process_message_us += process_message_time.as_us();
@@ -923,8 +1054,9 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
                 err
             });
 
-        let log_messages: Option<TransactionLogMessages> =
-            log_collector.and_then(|log_collector| {
+        let log_messages: Option<TransactionLogMessages> = log_collector
+            .filter(|_| config.recording_config.enable_log_recording)
+            .and_then(|log_collector| {
                 Rc::try_unwrap(log_collector)
                     .map(|log_collector| log_collector.into_inner().into_messages())
                     .ok()
@@ -974,6 +1106,7 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
                 return_data,
                 executed_units,
                 accounts_data_len_delta,
+                instruction_traces,
             },
             loaded_transaction,
             programs_modified_by_tx: program_cache_for_tx_batch.drain_modified_entries(),
@@ -1401,7 +1534,7 @@ mod tests {
         let executed_tx = batch_processor.execute_loaded_transaction(
             &mock_bank,
             &sanitized_transaction,
-            loaded_transaction,
+            loaded_transaction.clone(),
             &mut ExecuteTimings::default(),
             &mut TransactionErrorMetrics::default(),
             &mut program_cache_for_tx_batch,
@@ -1411,6 +1544,27 @@ mod tests {
 
         assert!(executed_tx.execution_details.log_messages.is_none());
         assert!(executed_tx.execution_details.inner_instructions.is_some());
+        assert!(executed_tx.execution_details.instruction_traces.is_none());
+
+        processing_config.recording_config.enable_cpi_recording = false;
+        processing_config
+            .recording_config
+            .enable_instruction_trace_recording = true;
+
+        let executed_tx = batch_processor.execute_loaded_transaction(
+            &mock_bank,
+            &sanitized_transaction,
+            loaded_transaction,
+            &mut ExecuteTimings::default(),
+            &mut TransactionErrorMetrics::default(),
+            &mut program_cache_for_tx_batch,
+            &processing_environment,
+            &processing_config,
+        );
+
+        assert!(executed_tx.execution_details.log_messages.is_none());
+        assert!(executed_tx.execution_details.inner_instructions.is_none());
+        assert!(executed_tx.execution_details.instruction_traces.is_some());
     }
 
     #[test]
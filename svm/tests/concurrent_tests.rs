@@ -256,6 +256,8 @@ fn svm_concurrent() {
                     enable_return_data_recording: false,
                     enable_cpi_recording: false,
                     enable_transaction_balance_recording: false,
+                    enable_instruction_trace_recording: false,
+                    enable_instruction_trace_account_data_recording: false,
                 },
                 ..Default::default()
             };
@@ -347,6 +347,7 @@ fn execute_fixture_as_instr(
         42,
         &mut ExecuteTimings::default(),
         false,
+        None,
     )
     .unwrap();
 
@@ -284,10 +284,54 @@ fn consume_compute_meter(invoke_context: &InvokeContext, amount: u64) -> Result<
     Ok(())
 }
 
+/// Wraps the `rust` function of a syscall in a builtin function that invokes
+/// it through [InvokeContext::trace_syscall], so that it shows up in
+/// instruction traces
+macro_rules! traced_syscall {
+    ($name:expr, $syscall:expr) => {{
+        declare_builtin_function!(
+            TracedSyscall,
+            fn rust(
+                invoke_context: &mut InvokeContext,
+                arg1: u64,
+                arg2: u64,
+                arg3: u64,
+                arg4: u64,
+                arg5: u64,
+                memory_mapping: &mut MemoryMapping,
+            ) -> Result<u64, Error> {
+                invoke_context.trace_syscall($name, |invoke_context| {
+                    $syscall(invoke_context, arg1, arg2, arg3, arg4, arg5, memory_mapping)
+                })
+            }
+        );
+        TracedSyscall::vm
+    }};
+}
+
+macro_rules! register_syscall {
+    ($result:expr, $trace_syscalls:expr, $name:expr, $syscall:ident $(<$generic:ty>)? $(,)?) => {
+        if $trace_syscalls {
+            $result.register_function(
+                $name,
+                traced_syscall!($name, $syscall::rust$(::<$generic>)?),
+            )
+        } else {
+            $result.register_function($name, $syscall::vm$(::<$generic>)?)
+        }
+    };
+}
+
 macro_rules! register_feature_gated_function {
-    ($result:expr, $is_feature_active:expr, $name:expr, $call:expr $(,)?) => {
+    (
+        $result:expr,
+        $trace_syscalls:expr,
+        $is_feature_active:expr,
+        $name:expr,
+        $syscall:ident $(<$generic:ty>)? $(,)?
+    ) => {
         if $is_feature_active {
-            $result.register_function($name, $call)
+            register_syscall!($result, $trace_syscalls, $name, $syscall $(<$generic>)?)
         } else {
             Ok(())
         }
@@ -299,6 +343,41 @@ pub fn create_program_runtime_environment_v1<'a>(
     compute_budget: &SVMTransactionExecutionBudget,
     reject_deployment_of_broken_elfs: bool,
     debugging_features: bool,
+) -> Result<BuiltinProgram<InvokeContext<'a>>, Error> {
+    new_program_runtime_environment_v1(
+        feature_set,
+        compute_budget,
+        reject_deployment_of_broken_elfs,
+        debugging_features,
+        false, /* trace_syscalls */
+    )
+}
+
+/// Like [create_program_runtime_environment_v1], but every syscall is invoked
+/// through [InvokeContext::trace_syscall], so that syscalls are recorded in
+/// instruction traces. Only meant for programs loaded to be traced, as this
+/// adds overhead to every syscall.
+pub fn create_syscall_tracing_program_runtime_environment_v1<'a>(
+    feature_set: &SVMFeatureSet,
+    compute_budget: &SVMTransactionExecutionBudget,
+    reject_deployment_of_broken_elfs: bool,
+    debugging_features: bool,
+) -> Result<BuiltinProgram<InvokeContext<'a>>, Error> {
+    new_program_runtime_environment_v1(
+        feature_set,
+        compute_budget,
+        reject_deployment_of_broken_elfs,
+        debugging_features,
+        true, /* trace_syscalls */
+    )
+}
+
+fn new_program_runtime_environment_v1<'a>(
+    feature_set: &SVMFeatureSet,
+    compute_budget: &SVMTransactionExecutionBudget,
+    reject_deployment_of_broken_elfs: bool,
+    debugging_features: bool,
+    trace_syscalls: bool,
 ) -> Result<BuiltinProgram<InvokeContext<'a>>, Error> {
     let enable_alt_bn128_syscall = feature_set.enable_alt_bn128_syscall;
     let enable_alt_bn128_compression_syscall = feature_set.enable_alt_bn128_compression_syscall;
@@ -351,179 +430,258 @@ pub fn create_program_runtime_environment_v1<'a>(
     let mut result = BuiltinProgram::new_loader(config);
 
     // Abort
-    result.register_function("abort", SyscallAbort::vm)?;
+    register_syscall!(result, trace_syscalls, "abort", SyscallAbort)?;
 
     // Panic
-    result.register_function("sol_panic_", SyscallPanic::vm)?;
+    register_syscall!(result, trace_syscalls, "sol_panic_", SyscallPanic)?;
 
     // Logging
-    result.register_function("sol_log_", SyscallLog::vm)?;
-    result.register_function("sol_log_64_", SyscallLogU64::vm)?;
-    result.register_function("sol_log_pubkey", SyscallLogPubkey::vm)?;
-    result.register_function("sol_log_compute_units_", SyscallLogBpfComputeUnits::vm)?;
+    register_syscall!(result, trace_syscalls, "sol_log_", SyscallLog)?;
+    register_syscall!(result, trace_syscalls, "sol_log_64_", SyscallLogU64)?;
+    register_syscall!(result, trace_syscalls, "sol_log_pubkey", SyscallLogPubkey)?;
+    register_syscall!(
+        result,
+        trace_syscalls,
+        "sol_log_compute_units_",
+        SyscallLogBpfComputeUnits
+    )?;
 
     // Program defined addresses (PDA)
-    result.register_function(
+    register_syscall!(
+        result,
+        trace_syscalls,
         "sol_create_program_address",
-        SyscallCreateProgramAddress::vm,
+        SyscallCreateProgramAddress
     )?;
-    result.register_function(
+    register_syscall!(
+        result,
+        trace_syscalls,
         "sol_try_find_program_address",
-        SyscallTryFindProgramAddress::vm,
+        SyscallTryFindProgramAddress
     )?;
 
     // Sha256
-    result.register_function("sol_sha256", SyscallHash::vm::<Sha256Hasher>)?;
+    register_syscall!(
+        result,
+        trace_syscalls,
+        "sol_sha256",
+        SyscallHash<Sha256Hasher>
+    )?;
 
     // Keccak256
-    result.register_function("sol_keccak256", SyscallHash::vm::<Keccak256Hasher>)?;
+    register_syscall!(
+        result,
+        trace_syscalls,
+        "sol_keccak256",
+        SyscallHash<Keccak256Hasher>
+    )?;
 
     // Secp256k1 Recover
-    result.register_function("sol_secp256k1_recover", SyscallSecp256k1Recover::vm)?;
+    register_syscall!(
+        result,
+        trace_syscalls,
+        "sol_secp256k1_recover",
+        SyscallSecp256k1Recover
+    )?;
 
     // Blake3
     register_feature_gated_function!(
         result,
+        trace_syscalls,
         blake3_syscall_enabled,
         "sol_blake3",
-        SyscallHash::vm::<Blake3Hasher>,
+        SyscallHash<Blake3Hasher>,
     )?;
 
     // Elliptic Curve Operations
     register_feature_gated_function!(
         result,
+        trace_syscalls,
         curve25519_syscall_enabled,
         "sol_curve_validate_point",
-        SyscallCurvePointValidation::vm,
+        SyscallCurvePointValidation,
     )?;
     register_feature_gated_function!(
         result,
+        trace_syscalls,
         curve25519_syscall_enabled,
         "sol_curve_group_op",
-        SyscallCurveGroupOps::vm,
+        SyscallCurveGroupOps,
     )?;
     register_feature_gated_function!(
         result,
+        trace_syscalls,
         curve25519_syscall_enabled,
         "sol_curve_multiscalar_mul",
-        SyscallCurveMultiscalarMultiplication::vm,
+        SyscallCurveMultiscalarMultiplication,
     )?;
 
     // Sysvars
-    result.register_function("sol_get_clock_sysvar", SyscallGetClockSysvar::vm)?;
-    result.register_function(
+    register_syscall!(
+        result,
+        trace_syscalls,
+        "sol_get_clock_sysvar",
+        SyscallGetClockSysvar
+    )?;
+    register_syscall!(
+        result,
+        trace_syscalls,
         "sol_get_epoch_schedule_sysvar",
-        SyscallGetEpochScheduleSysvar::vm,
+        SyscallGetEpochScheduleSysvar
     )?;
     register_feature_gated_function!(
         result,
+        trace_syscalls,
         !disable_fees_sysvar,
         "sol_get_fees_sysvar",
-        SyscallGetFeesSysvar::vm,
+        SyscallGetFeesSysvar,
+    )?;
+    register_syscall!(
+        result,
+        trace_syscalls,
+        "sol_get_rent_sysvar",
+        SyscallGetRentSysvar
     )?;
-    result.register_function("sol_get_rent_sysvar", SyscallGetRentSysvar::vm)?;
 
     register_feature_gated_function!(
         result,
+        trace_syscalls,
         last_restart_slot_syscall_enabled,
         "sol_get_last_restart_slot",
-        SyscallGetLastRestartSlotSysvar::vm,
+        SyscallGetLastRestartSlotSysvar,
     )?;
 
-    result.register_function(
+    register_syscall!(
+        result,
+        trace_syscalls,
         "sol_get_epoch_rewards_sysvar",
-        SyscallGetEpochRewardsSysvar::vm,
+        SyscallGetEpochRewardsSysvar
     )?;
 
     // Memory ops
-    result.register_function("sol_memcpy_", SyscallMemcpy::vm)?;
-    result.register_function("sol_memmove_", SyscallMemmove::vm)?;
-    result.register_function("sol_memset_", SyscallMemset::vm)?;
-    result.register_function("sol_memcmp_", SyscallMemcmp::vm)?;
+    register_syscall!(result, trace_syscalls, "sol_memcpy_", SyscallMemcpy)?;
+    register_syscall!(result, trace_syscalls, "sol_memmove_", SyscallMemmove)?;
+    register_syscall!(result, trace_syscalls, "sol_memset_", SyscallMemset)?;
+    register_syscall!(result, trace_syscalls, "sol_memcmp_", SyscallMemcmp)?;
 
     // Processed sibling instructions
-    result.register_function(
+    register_syscall!(
+        result,
+        trace_syscalls,
         "sol_get_processed_sibling_instruction",
-        SyscallGetProcessedSiblingInstruction::vm,
+        SyscallGetProcessedSiblingInstruction
     )?;
 
     // Stack height
-    result.register_function("sol_get_stack_height", SyscallGetStackHeight::vm)?;
+    register_syscall!(
+        result,
+        trace_syscalls,
+        "sol_get_stack_height",
+        SyscallGetStackHeight
+    )?;
 
     // Return data
-    result.register_function("sol_set_return_data", SyscallSetReturnData::vm)?;
-    result.register_function("sol_get_return_data", SyscallGetReturnData::vm)?;
+    register_syscall!(
+        result,
+        trace_syscalls,
+        "sol_set_return_data",
+        SyscallSetReturnData
+    )?;
+    register_syscall!(
+        result,
+        trace_syscalls,
+        "sol_get_return_data",
+        SyscallGetReturnData
+    )?;
 
     // Cross-program invocation
-    result.register_function("sol_invoke_signed_c", SyscallInvokeSignedC::vm)?;
-    result.register_function("sol_invoke_signed_rust", SyscallInvokeSignedRust::vm)?;
+    register_syscall!(
+        result,
+        trace_syscalls,
+        "sol_invoke_signed_c",
+        SyscallInvokeSignedC
+    )?;
+    register_syscall!(
+        result,
+        trace_syscalls,
+        "sol_invoke_signed_rust",
+        SyscallInvokeSignedRust
+    )?;
 
     // Memory allocator
     register_feature_gated_function!(
         result,
+        trace_syscalls,
         !disable_deploy_of_alloc_free_syscall,
         "sol_alloc_free_",
-        SyscallAllocFree::vm,
+        SyscallAllocFree,
     )?;
 
     // Alt_bn128
     register_feature_gated_function!(
         result,
+        trace_syscalls,
         enable_alt_bn128_syscall,
         "sol_alt_bn128_group_op",
-        SyscallAltBn128::vm,
+        SyscallAltBn128,
     )?;
 
     // Big_mod_exp
     register_feature_gated_function!(
         result,
+        trace_syscalls,
         enable_big_mod_exp_syscall,
         "sol_big_mod_exp",
-        SyscallBigModExp::vm,
+        SyscallBigModExp,
     )?;
 
     // Poseidon
     register_feature_gated_function!(
         result,
+        trace_syscalls,
         enable_poseidon_syscall,
         "sol_poseidon",
-        SyscallPoseidon::vm,
+        SyscallPoseidon,
     )?;
 
     // Accessing remaining compute units
     register_feature_gated_function!(
         result,
+        trace_syscalls,
         remaining_compute_units_syscall_enabled,
         "sol_remaining_compute_units",
-        SyscallRemainingComputeUnits::vm
+        SyscallRemainingComputeUnits
     )?;
 
     // Alt_bn128_compression
     register_feature_gated_function!(
         result,
+        trace_syscalls,
         enable_alt_bn128_compression_syscall,
         "sol_alt_bn128_compression",
-        SyscallAltBn128Compression::vm,
+        SyscallAltBn128Compression,
     )?;
 
     // Sysvar getter
     register_feature_gated_function!(
         result,
+        trace_syscalls,
         get_sysvar_syscall_enabled,
         "sol_get_sysvar",
-        SyscallGetSysvar::vm,
+        SyscallGetSysvar,
     )?;
 
     // Get Epoch Stake
     register_feature_gated_function!(
         result,
+        trace_syscalls,
         enable_get_epoch_stake_syscall,
         "sol_get_epoch_stake",
-        SyscallGetEpochStake::vm,
+        SyscallGetEpochStake,
     )?;
 
     // Log data
-    result.register_function("sol_log_data", SyscallLogData::vm)?;
+    register_syscall!(result, trace_syscalls, "sol_log_data", SyscallLogData)?;
 
     Ok(result)
 }
@@ -5248,4 +5406,39 @@ mod tests {
         );
         assert_access_violation!(result, 0x100000000, 4);
     }
+
+    #[test]
+    fn test_syscall_tracing_program_runtime_environment() {
+        let feature_set = SVMFeatureSet::all_enabled();
+        let compute_budget = SVMTransactionExecutionBudget::default();
+        let environment =
+            create_program_runtime_environment_v1(&feature_set, &compute_budget, false, false)
+                .unwrap();
+        let tracing_environment = create_syscall_tracing_program_runtime_environment_v1(
+            &feature_set,
+            &compute_budget,
+            false,
+            false,
+        )
+        .unwrap();
+        let registered_syscalls = |environment: &BuiltinProgram<InvokeContext>| {
+            environment
+                .get_function_registry()
+                .iter()
+                .map(|(key, (name, _function))| (key, name.to_vec()))
+                .collect::<Vec<_>>()
+        };
+
+        // The same syscalls are registered, only invoked differently
+        assert_eq!(
+            registered_syscalls(&environment),
+            registered_syscalls(&tracing_environment)
+        );
+        assert_ne!(environment, tracing_environment);
+        assert_eq!(
+            environment,
+            create_program_runtime_environment_v1(&feature_set, &compute_budget, false, false)
+                .unwrap()
+        );
+    }
 }
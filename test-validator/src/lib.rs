@@ -5,6 +5,7 @@ use {
     crossbeam_channel::Receiver,
     log::*,
     solana_account::{Account, AccountSharedData, ReadableAccount, WritableAccount},
+    solana_account_decoder_client_types::UiAccountEncoding,
     solana_accounts_db::{
         accounts_db::AccountsDbConfig, accounts_index::AccountsIndexConfig,
         hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
@@ -43,11 +44,15 @@ use {
     solana_rent::Rent,
     solana_rpc::{rpc::JsonRpcConfig, rpc_pubsub_service::PubSubConfig},
     solana_rpc_client::{nonblocking, rpc_client::RpcClient},
-    solana_rpc_client_api::request::MAX_MULTIPLE_ACCOUNTS,
+    solana_rpc_client_api::{
+        config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+        filter::RpcFilterType,
+        request::MAX_MULTIPLE_ACCOUNTS,
+    },
     solana_runtime::{
         bank_forks::BankForks,
         genesis_utils::{self, create_genesis_config_with_leader_ex_no_features},
-        runtime_config::RuntimeConfig,
+        runtime_config::{MissingAccountLoader, RuntimeConfig},
         snapshot_config::SnapshotConfig,
         snapshot_utils::SnapshotInterval,
     },
@@ -60,14 +65,14 @@ use {
     std::{
         collections::{HashMap, HashSet},
         ffi::OsStr,
-        fmt::Display,
+        fmt::{self, Display},
         fs::{self, remove_dir_all, File},
         io::Read,
         net::{IpAddr, Ipv4Addr, SocketAddr},
         num::{NonZero, NonZeroU64},
         path::{Path, PathBuf},
         str::FromStr,
-        sync::{Arc, RwLock},
+        sync::{Arc, Mutex, RwLock},
         time::Duration,
     },
     tokio::time::sleep,
@@ -138,6 +143,7 @@ pub struct TestValidatorGenesis {
     pub tpu_enable_udp: bool,
     pub geyser_plugin_manager: Arc<RwLock<GeyserPluginManager>>,
     admin_rpc_service_post_init: Arc<RwLock<Option<AdminRpcRequestMetadataPostInit>>>,
+    lazy_clone_source: Option<Arc<RpcAccountLoader>>,
 }
 
 impl Default for TestValidatorGenesis {
@@ -172,10 +178,93 @@ impl Default for TestValidatorGenesis {
             geyser_plugin_manager: Arc::new(RwLock::new(GeyserPluginManager::new())),
             admin_rpc_service_post_init:
                 Arc::<RwLock<Option<AdminRpcRequestMetadataPostInit>>>::default(),
+            lazy_clone_source: Option::<Arc<RpcAccountLoader>>::default(),
         }
     }
 }
 
+/// Adjust an account cloned from another cluster for use in genesis.
+fn transform_cloned_account(address: &Pubkey, account: Account) -> AccountSharedData {
+    let mut account_shared_data = AccountSharedData::from(account);
+    // ignore the error
+    try_transform_program_data(address, &mut account_shared_data).ok();
+    account_shared_data
+}
+
+/// An account requested from the cluster: `None` until fetched, then `Some(None)` if it does
+/// not exist on the cluster
+type FetchedAccount = Arc<Mutex<Option<Option<AccountSharedData>>>>;
+
+/// Fetches accounts missing from the test validator's banks over RPC, see
+/// [`TestValidatorGenesis::lazy_clone_accounts`].
+struct RpcAccountLoader {
+    rpc_client: Arc<RpcClient>,
+    min_context_slot: Slot,
+    /// Accounts requested so far
+    fetched: Mutex<HashMap<Pubkey, FetchedAccount>>,
+}
+
+impl fmt::Debug for RpcAccountLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcAccountLoader")
+            .field("url", &self.rpc_client.url())
+            .field("min_context_slot", &self.min_context_slot)
+            .finish()
+    }
+}
+
+impl MissingAccountLoader for RpcAccountLoader {
+    fn load_missing_account(&self, address: &Pubkey) -> Option<AccountSharedData> {
+        let fetched = self
+            .fetched
+            .lock()
+            .unwrap()
+            .entry(*address)
+            .or_default()
+            .clone();
+        // Hold the account's lock while fetching so that concurrent transactions
+        // loading the same account wait for it instead of missing it, while
+        // other accounts are fetched in parallel
+        let mut fetched = fetched.lock().unwrap();
+        if let Some(account) = fetched.as_ref() {
+            return account.clone();
+        }
+
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64Zstd),
+            min_context_slot: Some(self.min_context_slot),
+            ..RpcAccountInfoConfig::default()
+        };
+        // The blocking client cannot be used from the tokio runtime that RPC
+        // requests such as transaction simulation may be served on
+        let response = std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    self.rpc_client
+                        .get_multiple_accounts_with_config(&[*address], config)
+                })
+                .join()
+                .unwrap()
+        });
+        let account = match response {
+            Ok(response) => {
+                debug!("Fetched {address} at slot {}", response.context.slot);
+                response.value.into_iter().next().flatten().map(|account| {
+                    info!("Cloned {address} on first use");
+                    transform_cloned_account(address, account)
+                })
+            }
+            Err(err) => {
+                // Not cached, so that the next transaction tries again
+                warn!("Failed to fetch {address}: {err}");
+                return None;
+            }
+        };
+        *fetched = Some(account.clone());
+        account
+    }
+}
+
 fn try_transform_program_data(
     address: &Pubkey,
     account: &mut AccountSharedData,
@@ -374,15 +463,65 @@ impl TestValidatorGenesis {
             addresses,
             rpc_client,
             skip_missing,
-            |address, account| {
-                let mut account_shared_data = AccountSharedData::from(account);
-                // ignore the error
-                try_transform_program_data(address, &mut account_shared_data).ok();
-                Ok(account_shared_data)
-            },
+            |address, account| Ok(transform_cloned_account(address, account)),
         )
     }
 
+    /// Clone every account owned by `program_id` that matches all `filters`,
+    /// as used by `getProgramAccounts`.
+    ///
+    /// RPC nodes only serve their latest state, so accounts are read at the
+    /// current slot of the RPC node, and the request fails if the node has not
+    /// reached `min_context_slot` yet. Accounts cloned by separate calls may
+    /// therefore be from different slots.
+    pub fn clone_program_accounts(
+        &mut self,
+        program_id: &Pubkey,
+        filters: Vec<RpcFilterType>,
+        rpc_client: &RpcClient,
+        min_context_slot: Slot,
+    ) -> Result<&mut Self, String> {
+        info!("Fetching accounts owned by {program_id} over RPC...");
+        let config = RpcProgramAccountsConfig {
+            filters: (!filters.is_empty()).then_some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64Zstd),
+                min_context_slot: Some(min_context_slot),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = rpc_client
+            .get_program_accounts_with_config(program_id, config)
+            .map_err(|err| format!("Failed to fetch accounts owned by {program_id}: {err}"))?;
+        info!("Cloning {} accounts owned by {program_id}", accounts.len());
+        for (address, account) in accounts {
+            self.add_account(address, transform_cloned_account(&address, account));
+        }
+        Ok(self)
+    }
+
+    /// Clone accounts from `rpc_client` the first time a transaction loads
+    /// them, if they are not in the test validator already.
+    ///
+    /// Like with [`Self::clone_program_accounts`], each account is read at the
+    /// current slot of the RPC node when it is first loaded, which must be at
+    /// least `min_context_slot`, so accounts may be from different slots. Each
+    /// address is fetched once, unless the request fails, so accounts that do
+    /// not exist on the cluster cost a single request.
+    pub fn lazy_clone_accounts(
+        &mut self,
+        rpc_client: Arc<RpcClient>,
+        min_context_slot: Slot,
+    ) -> &mut Self {
+        self.lazy_clone_source = Some(Arc::new(RpcAccountLoader {
+            rpc_client,
+            min_context_slot,
+            fetched: Mutex::default(),
+        }));
+        self
+    }
+
     pub fn deep_clone_address_lookup_table_accounts<T>(
         &mut self,
         addresses: T,
@@ -1093,6 +1232,10 @@ impl TestValidator {
                 }),
             log_messages_bytes_limit: config.log_messages_bytes_limit,
             transaction_account_lock_limit: config.transaction_account_lock_limit,
+            missing_account_loader: config
+                .lazy_clone_source
+                .clone()
+                .map(|loader| loader as Arc<dyn MissingAccountLoader>),
         };
 
         let mut validator_config = ValidatorConfig {
@@ -9,18 +9,20 @@ use {
     log::*,
     serde::{de::Deserializer, Deserialize, Serialize},
     solana_accounts_db::accounts_index::AccountIndex,
+    solana_clock::Slot,
     solana_core::{
         admin_rpc_post_init::AdminRpcRequestMetadataPostInit,
+        banking_stage::transaction_outcome_log::TransactionOutcomeRecord,
         consensus::{tower_storage::TowerStorage, Tower},
         repair::repair_service,
         validator::ValidatorStartProgress,
     },
-    solana_geyser_plugin_manager::GeyserPluginManagerRequest,
+    solana_geyser_plugin_manager::{AccountsReplaySource, GeyserPluginManagerRequest},
     solana_gossip::contact_info::{ContactInfo, Protocol, SOCKET_ADDR_UNSPECIFIED},
     solana_keypair::{read_keypair_file, Keypair},
     solana_net_utils::sockets::bind_to,
     solana_pubkey::Pubkey,
-    solana_rpc::rpc::verify_pubkey,
+    solana_rpc::rpc::{verify_pubkey, verify_signature},
     solana_rpc_client_api::{config::RpcAccountIndex, custom_error::RpcCustomError},
     solana_signer::Signer,
     solana_validator_exit::Exit,
@@ -148,6 +150,38 @@ impl Display for AdminRpcRepairWhitelist {
 impl solana_cli_output::VerboseDisplay for AdminRpcRepairWhitelist {}
 impl solana_cli_output::QuietDisplay for AdminRpcRepairWhitelist {}
 
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminRpcTransactionOutcomes {
+    pub signature: String,
+    /// First and last slot currently retained by the outcome log.
+    pub retained_slots: Option<(Slot, Slot)>,
+    pub outcomes: Vec<TransactionOutcomeRecord>,
+}
+
+impl Display for AdminRpcTransactionOutcomes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Signature: {}", self.signature)?;
+        match self.retained_slots {
+            Some((first, last)) => writeln!(f, "Retained slots: {first}..={last}")?,
+            None => writeln!(f, "Retained slots: none")?,
+        }
+        if self.outcomes.is_empty() {
+            return writeln!(f, "No outcomes recorded");
+        }
+        for record in &self.outcomes {
+            writeln!(
+                f,
+                "  slot {} at {}ms: {}",
+                record.slot, record.timestamp, record.outcome
+            )?;
+        }
+        Ok(())
+    }
+}
+impl solana_cli_output::VerboseDisplay for AdminRpcTransactionOutcomes {}
+impl solana_cli_output::QuietDisplay for AdminRpcTransactionOutcomes {}
+
 #[rpc]
 pub trait AdminRpc {
     type Metadata;
@@ -169,12 +203,31 @@ pub trait AdminRpc {
         config_file: String,
     ) -> BoxFuture<Result<()>>;
 
+    /// Reload a plugin, replaying the notifications received during the reload to the new
+    /// instance
+    #[rpc(meta, name = "reloadPluginWithReplay")]
+    fn reload_plugin_with_replay(
+        &self,
+        meta: Self::Metadata,
+        name: String,
+        config_file: String,
+    ) -> BoxFuture<Result<()>>;
+
     #[rpc(meta, name = "unloadPlugin")]
     fn unload_plugin(&self, meta: Self::Metadata, name: String) -> BoxFuture<Result<()>>;
 
     #[rpc(meta, name = "loadPlugin")]
     fn load_plugin(&self, meta: Self::Metadata, config_file: String) -> BoxFuture<Result<String>>;
 
+    /// Load a plugin and notify it of every account in the latest snapshot, as at validator
+    /// startup, followed by the accounts updated in the root bank since the snapshot
+    #[rpc(meta, name = "loadPluginWithAccountReplay")]
+    fn load_plugin_with_account_replay(
+        &self,
+        meta: Self::Metadata,
+        config_file: String,
+    ) -> BoxFuture<Result<String>>;
+
     #[rpc(meta, name = "listPlugins")]
     fn list_plugins(&self, meta: Self::Metadata) -> BoxFuture<Result<Vec<String>>>;
 
@@ -240,6 +293,13 @@ pub trait AdminRpc {
     #[rpc(meta, name = "setRepairWhitelist")]
     fn set_repair_whitelist(&self, meta: Self::Metadata, whitelist: Vec<Pubkey>) -> Result<()>;
 
+    #[rpc(meta, name = "transactionOutcomes")]
+    fn transaction_outcomes(
+        &self,
+        meta: Self::Metadata,
+        signature_str: String,
+    ) -> Result<AdminRpcTransactionOutcomes>;
+
     #[rpc(meta, name = "getSecondaryIndexKeySize")]
     fn get_secondary_index_key_size(
         &self,
@@ -331,60 +391,38 @@ impl AdminRpc for AdminRpcImpl {
         name: String,
         config_file: String,
     ) -> BoxFuture<Result<()>> {
-        Box::pin(async move {
-            // Construct channel for plugin to respond to this particular rpc request instance
-            let (response_sender, response_receiver) = oneshot_channel();
-
-            // Send request to plugin manager if there is a geyser service
-            if let Some(ref rpc_to_manager_sender) = meta.rpc_to_plugin_manager_sender {
-                rpc_to_manager_sender
-                    .send(GeyserPluginManagerRequest::ReloadPlugin {
-                        name,
-                        config_file,
-                        response_sender,
-                    })
-                    .expect("GeyerPluginService should never drop request receiver");
-            } else {
-                return Err(jsonrpc_core::Error {
-                    code: ErrorCode::InvalidRequest,
-                    message: "No geyser plugin service".to_string(),
-                    data: None,
-                });
-            }
+        Self::send_reload_plugin_request(meta, name, config_file, false)
+    }
 
-            // Await response from plugin manager
-            response_receiver
-                .await
-                .expect("GeyerPluginService's oneshot sender shouldn't drop early")
-        })
+    fn reload_plugin_with_replay(
+        &self,
+        meta: Self::Metadata,
+        name: String,
+        config_file: String,
+    ) -> BoxFuture<Result<()>> {
+        Self::send_reload_plugin_request(meta, name, config_file, true)
     }
 
     fn load_plugin(&self, meta: Self::Metadata, config_file: String) -> BoxFuture<Result<String>> {
-        Box::pin(async move {
-            // Construct channel for plugin to respond to this particular rpc request instance
-            let (response_sender, response_receiver) = oneshot_channel();
-
-            // Send request to plugin manager if there is a geyser service
-            if let Some(ref rpc_to_manager_sender) = meta.rpc_to_plugin_manager_sender {
-                rpc_to_manager_sender
-                    .send(GeyserPluginManagerRequest::LoadPlugin {
-                        config_file,
-                        response_sender,
-                    })
-                    .expect("GeyerPluginService should never drop request receiver");
-            } else {
-                return Err(jsonrpc_core::Error {
-                    code: ErrorCode::InvalidRequest,
-                    message: "No geyser plugin service".to_string(),
-                    data: None,
-                });
-            }
+        Self::send_load_plugin_request(meta, config_file, None)
+    }
 
-            // Await response from plugin manager
-            response_receiver
-                .await
-                .expect("GeyerPluginService's oneshot sender shouldn't drop early")
-        })
+    fn load_plugin_with_account_replay(
+        &self,
+        meta: Self::Metadata,
+        config_file: String,
+    ) -> BoxFuture<Result<String>> {
+        let source = match meta.with_post_init(|post_init| {
+            Ok(AccountsReplaySource {
+                bank_forks: post_init.bank_forks.clone(),
+                ledger_path: post_init.ledger_path.clone(),
+                snapshot_config: post_init.snapshot_config.clone(),
+            })
+        }) {
+            Ok(source) => source,
+            Err(err) => return Box::pin(async move { Err(err) }),
+        };
+        Self::send_load_plugin_request(meta, config_file, Some(source))
     }
 
     fn unload_plugin(&self, meta: Self::Metadata, name: String) -> BoxFuture<Result<()>> {
@@ -629,6 +667,32 @@ impl AdminRpc for AdminRpcImpl {
         })
     }
 
+    fn transaction_outcomes(
+        &self,
+        meta: Self::Metadata,
+        signature_str: String,
+    ) -> Result<AdminRpcTransactionOutcomes> {
+        debug!("transaction_outcomes request received: {signature_str}");
+
+        let signature = verify_signature(&signature_str)?;
+        meta.with_post_init(|post_init| {
+            let Some(transaction_outcome_log) = post_init.transaction_outcome_log.as_ref() else {
+                return Err(jsonrpc_core::Error {
+                    code: ErrorCode::InvalidRequest,
+                    message: "The transaction outcome log is not enabled, restart the validator \
+                              with --transaction-outcome-log-slots"
+                        .to_string(),
+                    data: None,
+                });
+            };
+            Ok(AdminRpcTransactionOutcomes {
+                signature: signature.to_string(),
+                retained_slots: transaction_outcome_log.slot_range(),
+                outcomes: transaction_outcome_log.get(&signature),
+            })
+        })
+    }
+
     fn get_secondary_index_key_size(
         &self,
         meta: Self::Metadata,
@@ -667,9 +731,10 @@ impl AdminRpc for AdminRpcImpl {
                 .indexes
                 .iter()
                 .filter_map(|index| {
+                    let rpc_account_index = rpc_account_index_from_account_index(index)?;
                     accounts_index
                         .get_index_key_size(index, &index_key)
-                        .map(|size| (rpc_account_index_from_account_index(index), size))
+                        .map(|size| (rpc_account_index, size))
                 })
                 .collect::<HashMap<_, _>>();
 
@@ -757,6 +822,74 @@ impl AdminRpc for AdminRpcImpl {
 }
 
 impl AdminRpcImpl {
+    fn send_reload_plugin_request(
+        meta: AdminRpcRequestMetadata,
+        name: String,
+        config_file: String,
+        replay_notifications: bool,
+    ) -> BoxFuture<Result<()>> {
+        Box::pin(async move {
+            // Construct channel for plugin to respond to this particular rpc request instance
+            let (response_sender, response_receiver) = oneshot_channel();
+
+            // Send request to plugin manager if there is a geyser service
+            if let Some(ref rpc_to_manager_sender) = meta.rpc_to_plugin_manager_sender {
+                rpc_to_manager_sender
+                    .send(GeyserPluginManagerRequest::ReloadPlugin {
+                        name,
+                        config_file,
+                        replay_notifications,
+                        response_sender,
+                    })
+                    .expect("GeyerPluginService should never drop request receiver");
+            } else {
+                return Err(jsonrpc_core::Error {
+                    code: ErrorCode::InvalidRequest,
+                    message: "No geyser plugin service".to_string(),
+                    data: None,
+                });
+            }
+
+            // Await response from plugin manager
+            response_receiver
+                .await
+                .expect("GeyerPluginService's oneshot sender shouldn't drop early")
+        })
+    }
+
+    fn send_load_plugin_request(
+        meta: AdminRpcRequestMetadata,
+        config_file: String,
+        replay_accounts_from: Option<AccountsReplaySource>,
+    ) -> BoxFuture<Result<String>> {
+        Box::pin(async move {
+            // Construct channel for plugin to respond to this particular rpc request instance
+            let (response_sender, response_receiver) = oneshot_channel();
+
+            // Send request to plugin manager if there is a geyser service
+            if let Some(ref rpc_to_manager_sender) = meta.rpc_to_plugin_manager_sender {
+                rpc_to_manager_sender
+                    .send(GeyserPluginManagerRequest::LoadPlugin {
+                        config_file,
+                        replay_accounts_from,
+                        response_sender,
+                    })
+                    .expect("GeyerPluginService should never drop request receiver");
+            } else {
+                return Err(jsonrpc_core::Error {
+                    code: ErrorCode::InvalidRequest,
+                    message: "No geyser plugin service".to_string(),
+                    data: None,
+                });
+            }
+
+            // Await response from plugin manager
+            response_receiver
+                .await
+                .expect("GeyerPluginService's oneshot sender shouldn't drop early")
+        })
+    }
+
     fn add_authorized_voter_keypair(
         meta: AdminRpcRequestMetadata,
         authorized_voter: Keypair,
@@ -809,11 +942,14 @@ impl AdminRpcImpl {
     }
 }
 
-fn rpc_account_index_from_account_index(account_index: &AccountIndex) -> RpcAccountIndex {
+fn rpc_account_index_from_account_index(account_index: &AccountIndex) -> Option<RpcAccountIndex> {
     match account_index {
-        AccountIndex::ProgramId => RpcAccountIndex::ProgramId,
-        AccountIndex::SplTokenOwner => RpcAccountIndex::SplTokenOwner,
-        AccountIndex::SplTokenMint => RpcAccountIndex::SplTokenMint,
+        AccountIndex::ProgramId => Some(RpcAccountIndex::ProgramId),
+        AccountIndex::SplTokenOwner => Some(RpcAccountIndex::SplTokenOwner),
+        AccountIndex::SplTokenMint => Some(RpcAccountIndex::SplTokenMint),
+        // program data index keys are derived from account data rather than being account
+        // addresses, so they are not reported here
+        AccountIndex::ProgramData(_) => None,
     }
 }
 
@@ -951,6 +1087,7 @@ mod tests {
         },
         solana_core::{
             admin_rpc_post_init::{KeyUpdaterType, KeyUpdaters},
+            banking_stage::transaction_outcome_log::{TransactionOutcome, TransactionOutcomeLog},
             consensus::tower_storage::NullTowerStorage,
             validator::{Validator, ValidatorConfig, ValidatorTpuConfig},
         },
@@ -969,6 +1106,7 @@ mod tests {
         solana_runtime::{
             bank::{Bank, BankTestConfig},
             bank_forks::BankForks,
+            snapshot_config::SnapshotConfig,
         },
         solana_streamer::socket::SocketAddrSpace,
         solana_system_interface::program as system_program,
@@ -1038,6 +1176,9 @@ mod tests {
                         solana_core::cluster_slots_service::cluster_slots::ClusterSlots::default(),
                     ),
                     gossip_socket: None,
+                    transaction_outcome_log: None,
+                    ledger_path: PathBuf::default(),
+                    snapshot_config: SnapshotConfig::new_disabled(),
                 }))),
                 staked_nodes_overrides: Arc::new(RwLock::new(HashMap::new())),
                 rpc_to_plugin_manager_sender: None,
@@ -1376,6 +1517,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_transaction_outcomes() {
+        let rpc = RpcHandler::start_with_config(TestConfig::default());
+        let RpcHandler { io, meta, .. } = rpc;
+        let signature = solana_signature::Signature::new_unique();
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"transactionOutcomes","params":["{signature}"]}}"#,
+        );
+
+        // The outcome log is disabled by default
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(
+            result["error"]["code"],
+            Value::from(ErrorCode::InvalidRequest.code())
+        );
+
+        let transaction_outcome_log = Arc::new(TransactionOutcomeLog::new(8));
+        transaction_outcome_log.record(3, signature, TransactionOutcome::Received);
+        transaction_outcome_log.record(4, signature, TransactionOutcome::Committed(None));
+        meta.post_init
+            .write()
+            .unwrap()
+            .as_mut()
+            .unwrap()
+            .transaction_outcome_log = Some(transaction_outcome_log);
+
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let outcomes: AdminRpcTransactionOutcomes =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(outcomes.signature, signature.to_string());
+        assert_eq!(outcomes.retained_slots, Some((3, 4)));
+        assert_eq!(
+            outcomes
+                .outcomes
+                .into_iter()
+                .map(|record| (record.slot, record.outcome))
+                .collect::<Vec<_>>(),
+            vec![
+                (3, TransactionOutcome::Received),
+                (4, TransactionOutcome::Committed(None)),
+            ]
+        );
+    }
+
     // This test checks that the rpc call to `set_identity` works a expected with
     // Bank but without validator.
     #[test]
@@ -28,6 +28,7 @@ use {
         rpc_pubsub_service::PubSubConfig,
     },
     solana_rpc_client::rpc_client::RpcClient,
+    solana_rpc_client_api::filter::RpcFilterType,
     solana_signer::Signer,
     solana_streamer::socket::SocketAddrSpace,
     solana_system_interface::program as system_program,
@@ -38,6 +39,7 @@ use {
         net::{IpAddr, Ipv4Addr, SocketAddr},
         path::{Path, PathBuf},
         process::exit,
+        str::FromStr,
         sync::{Arc, RwLock},
         time::{Duration, SystemTime, UNIX_EPOCH},
     },
@@ -69,12 +71,7 @@ fn main() {
     let indexes: HashSet<AccountIndex> = matches
         .values_of("account_indexes")
         .unwrap_or_default()
-        .map(|value| match value {
-            "program-id" => AccountIndex::ProgramId,
-            "spl-token-mint" => AccountIndex::SplTokenMint,
-            "spl-token-owner" => AccountIndex::SplTokenOwner,
-            _ => unreachable!(),
-        })
+        .map(|value| cli::parse_account_index(value).unwrap())
         .collect();
 
     let account_indexes = AccountSecondaryIndexes {
@@ -300,6 +297,41 @@ fn main() {
         .map(|v| v.into_iter().collect())
         .unwrap_or_default();
 
+    let program_accounts_to_clone: Vec<(Pubkey, Vec<RpcFilterType>)> = matches
+        .values_of("clone_program_accounts")
+        .unwrap_or_default()
+        .map(|value| {
+            let (program_id, filters) = value.split_once('=').unwrap_or((value, "[]"));
+            let program_id = Pubkey::from_str(program_id).unwrap_or_else(|err| {
+                println!("Error: invalid program id {program_id}: {err}");
+                exit(1);
+            });
+            let filters = serde_json::from_str(filters).unwrap_or_else(|err| {
+                println!("Error: invalid filters for {program_id}: {err}");
+                exit(1);
+            });
+            (program_id, filters)
+        })
+        .collect();
+
+    let lazy_clone = matches.is_present("lazy_clone");
+
+    let clone_min_context_slot = if lazy_clone || !program_accounts_to_clone.is_empty() {
+        Some(match value_t!(matches, "clone_min_context_slot", Slot) {
+            Ok(clone_min_context_slot) => clone_min_context_slot,
+            Err(_) => cluster_rpc_client
+                .as_ref()
+                .expect("--clone-program-accounts and --lazy-clone require --json-rpc-url argument")
+                .get_slot()
+                .unwrap_or_else(|err| {
+                    println!("Unable to get current cluster slot: {err}");
+                    exit(1);
+                }),
+        })
+    } else {
+        None
+    };
+
     let clone_feature_set = matches.is_present("clone_feature_set");
 
     let warp_slot = if matches.is_present("warp_slot") {
@@ -543,6 +575,30 @@ fn main() {
         }
     }
 
+    if let Some(clone_min_context_slot) = clone_min_context_slot {
+        for (program_id, filters) in program_accounts_to_clone {
+            if let Err(e) = genesis.clone_program_accounts(
+                &program_id,
+                filters,
+                cluster_rpc_client
+                    .as_ref()
+                    .expect("--clone-program-accounts requires --json-rpc-url argument"),
+                clone_min_context_slot,
+            ) {
+                println!("Error: clone_program_accounts failed: {e}");
+                exit(1);
+            }
+        }
+
+        if lazy_clone {
+            let json_rpc_url = value_t_or_exit!(matches, "json_rpc_url", String);
+            genesis.lazy_clone_accounts(
+                Arc::new(RpcClient::new(normalize_to_url_if_moniker(json_rpc_url))),
+                clone_min_context_slot,
+            );
+        }
+    }
+
     if clone_feature_set {
         if let Err(e) = genesis.clone_feature_set(
             cluster_rpc_client
@@ -6,6 +6,7 @@ use {
         accounts_db::{
             DEFAULT_ACCOUNTS_SHRINK_OPTIMIZE_TOTAL_SPACE, DEFAULT_ACCOUNTS_SHRINK_RATIO,
         },
+        accounts_index::AccountIndex,
         hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
     },
     solana_clap_utils::{
@@ -73,6 +74,7 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
         .subcommand(commands::set_identity::command())
         .subcommand(commands::set_log_filter::command())
         .subcommand(commands::staked_nodes_overrides::command())
+        .subcommand(commands::transaction_outcome::command())
         .subcommand(commands::wait_for_restart_window::command())
         .subcommand(commands::set_public_address::command());
 
@@ -383,12 +385,38 @@ pub fn port_range_validator(port_range: String) -> Result<(), String> {
     }
 }
 
+/// Parses an `--account-index` value: `program-id`, `spl-token-owner`, `spl-token-mint` or
+/// `program-data:<PROGRAM_ID>:<OFFSET>:<LENGTH>`
+pub fn parse_account_index(value: &str) -> Result<AccountIndex, String> {
+    match value {
+        "program-id" => Ok(AccountIndex::ProgramId),
+        "spl-token-mint" => Ok(AccountIndex::SplTokenMint),
+        "spl-token-owner" => Ok(AccountIndex::SplTokenOwner),
+        _ => match value.strip_prefix("program-data:") {
+            Some(program_data_index) => program_data_index.parse().map(AccountIndex::ProgramData),
+            None => Err(format!(
+                "invalid account index {value:?}, expected one of program-id, spl-token-owner, \
+                 spl-token-mint or program-data:<PROGRAM_ID>:<OFFSET>:<LENGTH>"
+            )),
+        },
+    }
+}
+
+pub(crate) fn account_index_validator(value: String) -> Result<(), String> {
+    parse_account_index(&value).map(|_| ())
+}
+
 pub(crate) fn hash_validator(hash: String) -> Result<(), String> {
     Hash::from_str(&hash)
         .map(|_| ())
         .map_err(|e| format!("{e:?}"))
 }
 
+pub(crate) const ACCOUNT_INDEX_HELP: &str = "Enable an accounts index, indexed by the selected \
+    account field. [possible values: program-id, spl-token-owner, spl-token-mint, \
+    program-data:<PROGRAM_ID>:<OFFSET>:<LENGTH>] A program-data index covers the accounts \
+    owned by PROGRAM_ID, keyed by LENGTH (at most 32) bytes of account data at OFFSET";
+
 /// Test validator
 pub fn test_app<'a>(version: &'a str, default_args: &'a DefaultTestArgs) -> App<'a, 'a> {
     App::new("solana-test-validator")
@@ -471,9 +499,9 @@ pub fn test_app<'a>(version: &'a str, default_args: &'a DefaultTestArgs) -> App<
                 .long("account-index")
                 .takes_value(true)
                 .multiple(true)
-                .possible_values(&["program-id", "spl-token-owner", "spl-token-mint"])
+                .validator(account_index_validator)
                 .value_name("INDEX")
-                .help("Enable an accounts index, indexed by the selected account field"),
+                .help(ACCOUNT_INDEX_HELP),
         )
         .arg(
             Arg::with_name("faucet_port")
@@ -759,6 +787,46 @@ pub fn test_app<'a>(version: &'a str, default_args: &'a DefaultTestArgs) -> App<
                      already exists then this parameter is silently ignored",
                 ),
         )
+        .arg(
+            Arg::with_name("clone_program_accounts")
+                .long("clone-program-accounts")
+                .value_name("PROGRAM_ID[=FILTERS]")
+                .takes_value(true)
+                .multiple(true)
+                .requires("json_rpc_url")
+                .help(
+                    "Copy every account owned by PROGRAM_ID from the cluster referenced by the \
+                     --url argument, at its latest slot. FILTERS is a JSON array \
+                     of getProgramAccounts filters the accounts must match, for instance \
+                     '[{\"dataSize\":165}]'. If the ledger already exists then this parameter \
+                     is silently ignored",
+                ),
+        )
+        .arg(
+            Arg::with_name("lazy_clone")
+                .long("lazy-clone")
+                .takes_value(false)
+                .requires("json_rpc_url")
+                .help(
+                    "Copy accounts from the cluster referenced by the --url argument, at its \
+                     latest slot, when a transaction first loads them and they do not exist in \
+                     the test validator",
+                ),
+        )
+        .arg(
+            Arg::with_name("clone_min_context_slot")
+                .long("clone-min-context-slot")
+                .value_name("SLOT")
+                .takes_value(true)
+                .validator(is_slot)
+                .requires("json_rpc_url")
+                .help(
+                    "Fail --clone-program-accounts and --lazy-clone requests if the cluster \
+                     has not reached SLOT. Accounts are always read at the latest slot of the \
+                     cluster, so accounts cloned at different times may be from different \
+                     slots [default: the current slot of the cluster at startup]",
+                ),
+        )
         .arg(
             Arg::with_name("warp_slot")
                 .required(false)
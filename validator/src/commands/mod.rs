@@ -10,6 +10,7 @@ pub mod set_identity;
 pub mod set_log_filter;
 pub mod set_public_address;
 pub mod staked_nodes_overrides;
+pub mod transaction_outcome;
 pub mod wait_for_restart_window;
 
 use thiserror::Error;
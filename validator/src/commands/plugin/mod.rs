@@ -25,12 +25,14 @@ impl FromClapArgMatches for PluginUnloadArgs {
 #[derive(Debug, PartialEq)]
 pub struct PluginLoadArgs {
     pub config: String,
+    pub replay_accounts: bool,
 }
 
 impl FromClapArgMatches for PluginLoadArgs {
     fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
         Ok(PluginLoadArgs {
             config: value_t!(matches, "config", String)?,
+            replay_accounts: matches.is_present("replay_accounts"),
         })
     }
 }
@@ -39,6 +41,7 @@ impl FromClapArgMatches for PluginLoadArgs {
 pub struct PluginReloadArgs {
     pub name: String,
     pub config: String,
+    pub replay_notifications: bool,
 }
 
 impl FromClapArgMatches for PluginReloadArgs {
@@ -46,6 +49,7 @@ impl FromClapArgMatches for PluginReloadArgs {
         Ok(PluginReloadArgs {
             name: value_t!(matches, "name", String)?,
             config: value_t!(matches, "config", String)?,
+            replay_notifications: matches.is_present("replay_notifications"),
         })
     }
 }
@@ -71,7 +75,16 @@ pub fn command<'a>() -> App<'a, 'a> {
                      and the new config path",
                 )
                 .arg(&name_arg)
-                .arg(&config_arg),
+                .arg(&config_arg)
+                .arg(
+                    Arg::with_name("replay_notifications")
+                        .long("replay-notifications")
+                        .takes_value(false)
+                        .help(
+                            "Buffer the account, transaction and slot notifications received \
+                             while the plugin reloads, and replay them to the new instance",
+                        ),
+                ),
         )
         .subcommand(
             SubCommand::with_name("load")
@@ -79,7 +92,17 @@ pub fn command<'a>() -> App<'a, 'a> {
                     "Load a new geyser plugin. You must specify the config path. Fails if \
                      overwriting (use reload)",
                 )
-                .arg(&config_arg),
+                .arg(&config_arg)
+                .arg(
+                    Arg::with_name("replay_accounts")
+                        .long("replay-accounts")
+                        .takes_value(false)
+                        .help(
+                            "Notify the plugin of every account in the latest snapshot once it \
+                             is loaded, as at validator startup, followed by the accounts \
+                             updated since the snapshot",
+                        ),
+                ),
         )
 }
 
@@ -108,24 +131,43 @@ pub fn execute(matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
             println!("Successfully unloaded plugin: {name}");
         }
         ("load", Some(subcommand_matches)) => {
-            let PluginLoadArgs { config } =
-                PluginLoadArgs::from_clap_arg_match(subcommand_matches)?;
+            let PluginLoadArgs {
+                config,
+                replay_accounts,
+            } = PluginLoadArgs::from_clap_arg_match(subcommand_matches)?;
 
             let admin_client = admin_rpc_service::connect(ledger_path);
-            let name = admin_rpc_service::runtime()
-                .block_on(async { admin_client.await?.load_plugin(config.clone()).await })?;
+            let name = admin_rpc_service::runtime().block_on(async {
+                let admin_client = admin_client.await?;
+                if replay_accounts {
+                    admin_client
+                        .load_plugin_with_account_replay(config.clone())
+                        .await
+                } else {
+                    admin_client.load_plugin(config.clone()).await
+                }
+            })?;
             println!("Successfully loaded plugin: {name}");
         }
         ("reload", Some(subcommand_matches)) => {
-            let PluginReloadArgs { name, config } =
-                PluginReloadArgs::from_clap_arg_match(subcommand_matches)?;
+            let PluginReloadArgs {
+                name,
+                config,
+                replay_notifications,
+            } = PluginReloadArgs::from_clap_arg_match(subcommand_matches)?;
 
             let admin_client = admin_rpc_service::connect(ledger_path);
             admin_rpc_service::runtime().block_on(async {
-                admin_client
-                    .await?
-                    .reload_plugin(name.clone(), config.clone())
-                    .await
+                let admin_client = admin_client.await?;
+                if replay_notifications {
+                    admin_client
+                        .reload_plugin_with_replay(name.clone(), config.clone())
+                        .await
+                } else {
+                    admin_client
+                        .reload_plugin(name.clone(), config.clone())
+                        .await
+                }
             })?;
             println!("Successfully reloaded plugin: {name}");
         }
@@ -176,6 +218,23 @@ mod tests {
             args,
             PluginLoadArgs {
                 config: "testconfig".to_string(),
+                replay_accounts: false,
+            }
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_plugin_load_with_replay_accounts() {
+        let app = command();
+        let matches =
+            app.get_matches_from(vec![COMMAND, "load", "testconfig", "--replay-accounts"]);
+        let subcommand_matches = matches.subcommand_matches("load").unwrap();
+        let args = PluginLoadArgs::from_clap_arg_match(subcommand_matches).unwrap();
+        assert_eq!(
+            args,
+            PluginLoadArgs {
+                config: "testconfig".to_string(),
+                replay_accounts: true,
             }
         );
     }
@@ -207,6 +266,29 @@ mod tests {
             PluginReloadArgs {
                 name: "testname".to_string(),
                 config: "testconfig".to_string(),
+                replay_notifications: false,
+            }
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_plugin_reload_with_replay_notifications() {
+        let app = command();
+        let matches = app.get_matches_from(vec![
+            COMMAND,
+            "reload",
+            "testname",
+            "testconfig",
+            "--replay-notifications",
+        ]);
+        let subcommand_matches = matches.subcommand_matches("reload").unwrap();
+        let args = PluginReloadArgs::from_clap_arg_match(subcommand_matches).unwrap();
+        assert_eq!(
+            args,
+            PluginReloadArgs {
+                name: "testname".to_string(),
+                config: "testconfig".to_string(),
+                replay_notifications: true,
             }
         );
     }
@@ -1,7 +1,10 @@
 use {
     crate::{
         bootstrap::RpcBootstrapConfig,
-        cli::{hash_validator, port_range_validator, port_validator, DefaultArgs},
+        cli::{
+            account_index_validator, hash_validator, port_range_validator, port_validator,
+            DefaultArgs, ACCOUNT_INDEX_HELP,
+        },
         commands::{FromClapArgMatches, Result},
     },
     clap::{values_t, App, Arg, ArgMatches},
@@ -1060,6 +1063,17 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             .default_value(&default_args.rpc_bigtable_max_message_size)
             .help("Max encoding and decoding message size used in Bigtable Grpc client"),
     )
+    .arg(
+        Arg::with_name("rpc_long_term_storage_path")
+            .long("rpc-long-term-storage-path")
+            .value_name("DIR")
+            .takes_value(true)
+            .help(
+                "Use this local directory, instead of a BigTable instance, as the long-term \
+                 ledger storage for --enable-rpc-bigtable-ledger-storage and \
+                 --enable-bigtable-ledger-upload",
+            ),
+    )
     .arg(
         Arg::with_name("rpc_pubsub_worker_threads")
             .long("rpc-pubsub-worker-threads")
@@ -1231,6 +1245,19 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             .requires("enable_rpc_transaction_history")
             .help("Verifies blockstore roots on boot and fixes any gaps"),
     )
+    .arg(
+        Arg::with_name("rpc_historical_account_state_path")
+            .long("rpc-historical-account-state-path")
+            .value_name("DIR")
+            .takes_value(true)
+            .help(
+                "Enable serving getAccountInfoAtSlot for rooted slots that are no longer in \
+                 memory. Banks are reconstructed from the snapshot archives and the blockstore \
+                 using this scratch directory, which needs room for 3 full copies of the \
+                 accounts: one for each of the 2 cached banks and one for the bank being \
+                 reconstructed. Requests for other slots fail while a bank is reconstructed",
+            ),
+    )
     .arg(
         Arg::with_name("rpc_max_request_body_size")
             .long("rpc-max-request-body-size")
@@ -1256,6 +1283,29 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             .takes_value(false)
             .help("Еnable Geyser interface even if no Geyser configs are specified."),
     )
+    .arg(
+        Arg::with_name("persistent_program_cache")
+            .long("persistent-program-cache")
+            .value_name("DIR")
+            .takes_value(true)
+            .help(
+                "Record in DIR which programs were loaded, and load them again at startup \
+                 before replay instead of on first use. Records are keyed by program address, \
+                 deployment slot and program runtime environment, so a feature activation that \
+                 changes the environment invalidates them. Programs are always verified and \
+                 JIT compiled when loaded",
+            ),
+    )
+    .arg(
+        Arg::with_name("compute_profile_path")
+            .long("compute-profile-path")
+            .value_name("FILE")
+            .takes_value(true)
+            .help(
+                "Profile the compute units and time spent per program while replaying \
+                 transactions, and write the report as JSON to FILE when the validator exits",
+            ),
+    )
     .arg(
         Arg::with_name("snapshot_archive_format")
             .long("snapshot-archive-format")
@@ -1338,9 +1388,9 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             .long("account-index")
             .takes_value(true)
             .multiple(true)
-            .possible_values(&["program-id", "spl-token-owner", "spl-token-mint"])
+            .validator(account_index_validator)
             .value_name("INDEX")
-            .help("Enable an accounts index, indexed by the selected account field"),
+            .help(ACCOUNT_INDEX_HELP),
     )
     .arg(
         Arg::with_name("account_index_exclude_key")
@@ -1563,6 +1613,19 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             .takes_value(false)
             .help("Disables the banking trace"),
     )
+    .arg(
+        Arg::with_name("transaction_outcome_log_slots")
+            .long("transaction-outcome-log-slots")
+            .value_name("SLOTS")
+            .takes_value(true)
+            .validator(|s| is_within_range(s, 1..))
+            .help(
+                "Record what happened to each transaction received while leader (dropped, \
+                 cost-limit rejected, retried, committed, ...) and retain these outcomes for \
+                 the given number of recent slots. Outcomes can be queried with the \
+                 transaction-outcome command. Disabled by default.",
+            ),
+    )
     .arg(
         Arg::with_name("delay_leader_block_for_pending_fork")
             .hidden(hidden_unless_forced())
@@ -1595,6 +1658,29 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             .default_value(BlockProductionMethod::default().into())
             .help(BlockProductionMethod::cli_message()),
     )
+    .arg(
+        Arg::with_name("external_scheduler_path")
+            .long("external-scheduler-path")
+            .value_name("PATH")
+            .takes_value(true)
+            .help(
+                "Delegate leader transaction scheduling to an external process. The validator \
+                 creates a shared memory file at PATH, offers buffered transactions to the \
+                 process through it and executes the returned assignments. While the process \
+                 is absent or does not answer in time, transactions are scheduled with the \
+                 scheduler selected by --block-production-method.",
+            ),
+    )
+    .arg(
+        Arg::with_name("enable_bundles")
+            .long("enable-bundles")
+            .takes_value(false)
+            .requires("rpc_port")
+            .help(
+                "Accept bundles, groups of transactions that are committed together, in order, \
+                 or not at all, with the sendBundle RPC method and execute them while leader.",
+            ),
+    )
     .arg(
         Arg::with_name("transaction_struct")
             .long("transaction-structure")
@@ -450,6 +450,7 @@ pub fn execute(
                 .ok()
                 .map(Duration::from_secs),
             max_message_size: value_t_or_exit!(matches, "rpc_bigtable_max_message_size", usize),
+            long_term_storage_path: value_t!(matches, "rpc_long_term_storage_path", PathBuf).ok(),
         })
     } else {
         None
@@ -590,6 +591,13 @@ pub fn execute(
                 usize
             )),
             skip_preflight_health_check: matches.is_present("skip_preflight_health_check"),
+            historical_account_state_path: value_t!(
+                matches,
+                "rpc_historical_account_state_path",
+                PathBuf
+            )
+            .ok(),
+            bundle_sender: None,
         },
         on_start_geyser_plugin_config_files,
         geyser_plugin_always_enabled: matches.is_present("geyser_plugin_always_enabled"),
@@ -701,6 +709,8 @@ pub fn execute(
             .is_present("delay_leader_block_for_pending_fork"),
         wen_restart_proto_path: value_t!(matches, "wen_restart", PathBuf).ok(),
         wen_restart_coordinator: value_t!(matches, "wen_restart_coordinator", Pubkey).ok(),
+        compute_profile_path: value_t!(matches, "compute_profile_path", PathBuf).ok(),
+        persistent_program_cache_path: value_t!(matches, "persistent_program_cache", PathBuf).ok(),
         turbine_disabled: Arc::<AtomicBool>::default(),
         retransmit_xdp,
         broadcast_stage_type: BroadcastStageType::Standard,
@@ -723,6 +733,10 @@ pub fn execute(
         ),
         transaction_struct: value_t_or_exit!(matches, "transaction_struct", TransactionStructure),
         enable_block_production_forwarding: staked_nodes_overrides_path.is_some(),
+        transaction_outcome_log_slots: value_t!(matches, "transaction_outcome_log_slots", usize)
+            .ok(),
+        external_scheduler_path: value_t!(matches, "external_scheduler_path", PathBuf).ok(),
+        enable_bundles: matches.is_present("enable_bundles"),
         banking_trace_dir_byte_limit: parse_banking_trace_dir_byte_limit(matches),
         validator_exit: Arc::new(RwLock::new(Exit::default())),
         validator_exit_backpressure: [(
@@ -1361,12 +1375,7 @@ fn process_account_indexes(matches: &ArgMatches) -> AccountSecondaryIndexes {
     let account_indexes: HashSet<AccountIndex> = matches
         .values_of("account_indexes")
         .unwrap_or_default()
-        .map(|value| match value {
-            "program-id" => AccountIndex::ProgramId,
-            "spl-token-mint" => AccountIndex::SplTokenMint,
-            "spl-token-owner" => AccountIndex::SplTokenOwner,
-            _ => unreachable!(),
-        })
+        .map(|value| cli::parse_account_index(value).unwrap())
         .collect();
 
     let account_indexes_include_keys: HashSet<Pubkey> =
@@ -0,0 +1,114 @@
+use {
+    crate::{
+        admin_rpc_service,
+        commands::{FromClapArgMatches, Result},
+    },
+    clap::{value_t, App, Arg, ArgMatches, SubCommand},
+    solana_clap_utils::input_validators::is_parsable,
+    solana_cli_output::OutputFormat,
+    solana_signature::Signature,
+    std::path::Path,
+};
+
+const COMMAND: &str = "transaction-outcome";
+
+#[derive(Debug, PartialEq)]
+pub struct TransactionOutcomeArgs {
+    pub signature: Signature,
+    pub output: OutputFormat,
+}
+
+impl FromClapArgMatches for TransactionOutcomeArgs {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(TransactionOutcomeArgs {
+            signature: value_t!(matches, "signature", Signature)?,
+            output: OutputFormat::from_matches(matches, "output", false),
+        })
+    }
+}
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about("Explain what happened to a transaction received while this validator was leader")
+        .after_help(
+            "Note: requires the validator to be running with --transaction-outcome-log-slots",
+        )
+        .arg(
+            Arg::with_name("signature")
+                .index(1)
+                .value_name("SIGNATURE")
+                .takes_value(true)
+                .required(true)
+                .validator(is_parsable::<Signature>)
+                .help("Transaction signature"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .takes_value(true)
+                .value_name("MODE")
+                .possible_values(&["json", "json-compact"])
+                .help("Output display mode"),
+        )
+}
+
+pub fn execute(matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
+    let TransactionOutcomeArgs { signature, output } =
+        TransactionOutcomeArgs::from_clap_arg_match(matches)?;
+
+    let admin_client = admin_rpc_service::connect(ledger_path);
+    let transaction_outcomes = admin_rpc_service::runtime().block_on(async move {
+        admin_client
+            .await?
+            .transaction_outcomes(signature.to_string())
+            .await
+    })?;
+
+    println!("{}", output.formatted_string(&transaction_outcomes));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::commands::tests::{
+            verify_args_struct_by_command, verify_args_struct_by_command_is_error,
+        },
+    };
+
+    #[test]
+    fn verify_args_struct_by_command_transaction_outcome_default() {
+        let signature = Signature::new_unique();
+        verify_args_struct_by_command(
+            command(),
+            vec![COMMAND, &signature.to_string()],
+            TransactionOutcomeArgs {
+                signature,
+                output: OutputFormat::Display,
+            },
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_transaction_outcome_output_json() {
+        let signature = Signature::new_unique();
+        verify_args_struct_by_command(
+            command(),
+            vec![COMMAND, &signature.to_string(), "--output", "json"],
+            TransactionOutcomeArgs {
+                signature,
+                output: OutputFormat::Json,
+            },
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_transaction_outcome_invalid_signature() {
+        verify_args_struct_by_command_is_error::<TransactionOutcomeArgs>(
+            command(),
+            vec![COMMAND, "not-a-signature"],
+        );
+    }
+}
@@ -74,6 +74,9 @@ pub fn main() {
         ("set-public-address", Some(subcommand_matches)) => {
             commands::set_public_address::execute(subcommand_matches, &ledger_path)
         }
+        ("transaction-outcome", Some(subcommand_matches)) => {
+            commands::transaction_outcome::execute(subcommand_matches, &ledger_path)
+        }
         _ => unreachable!(),
     }
     .unwrap_or_else(|err| {